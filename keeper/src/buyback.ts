@@ -1,6 +1,9 @@
 import { AnchorProvider } from '@coral-xyz/anchor';
+import { PublicKey } from '@solana/web3.js';
+import winston from 'winston';
 import { JupiterService } from './jupiter';
 import { Logger } from './logger';
+import { PriorityFeeManager } from './priorityFees';
 
 const logger = Logger.getInstance();
 
@@ -8,12 +11,17 @@ export class BuybackExecutor {
   constructor(
     private provider: AnchorProvider,
     private jupiter: JupiterService,
+    private fees: PriorityFeeManager,
     private config: any
   ) {}
 
-  async executeBuyback(usdcAmount: number): Promise<void> {
+  async executeBuyback(
+    usdcAmount: number,
+    parent: winston.Logger = logger
+  ): Promise<void> {
+    const log = parent.child({ step: 'buyback' });
     try {
-      logger.info('Starting buyback execution', { usdcAmount });
+      log.info('Starting buyback execution', { usdcAmount });
 
       // 1. Get quote from Jupiter
       const quote = await this.jupiter.getQuote(
@@ -23,7 +31,7 @@ export class BuybackExecutor {
         this.config.limits.slippageBps
       );
 
-      logger.info('Buyback quote received', {
+      log.info('Buyback quote received', {
         usdcIn: quote.inAmount,
         tokensOut: quote.outAmount,
         priceImpact: quote.priceImpactPct,
@@ -31,7 +39,7 @@ export class BuybackExecutor {
 
       // 2. Validate slippage
       if (quote.priceImpactPct > this.config.limits.slippageBps / 100) {
-        logger.warn('Price impact too high, skipping buyback', {
+        log.warn('Price impact too high, skipping buyback', {
           priceImpact: quote.priceImpactPct,
           maxAllowed: this.config.limits.slippageBps / 100,
         });
@@ -39,17 +47,23 @@ export class BuybackExecutor {
       }
 
       if (this.config.dryRun) {
-        logger.info('[DRY RUN] Would execute buyback', {
+        log.info('[DRY RUN] Would execute buyback', {
           usdcAmount,
           estimatedTokens: quote.outAmount,
         });
         return;
       }
 
-      // 3. Execute swap via Jupiter
-      const result = await this.jupiter.executeSwap(quote);
+      // 3. Execute swap via Jupiter, priced for the treasury's USDC write lock
+      const priorityFee = await this.fees.estimate('buyback', [
+        new PublicKey(this.config.treasuryAddress),
+        new PublicKey(this.config.treasuryUsdcAccount),
+      ]);
+      const result = await this.fees.track('buyback', () =>
+        this.jupiter.executeSwap(quote, priorityFee)
+      );
 
-      logger.info('Buyback executed successfully', {
+      log.info('Buyback executed successfully', {
         signature: result.signature,
         usdcSpent: result.inputAmount,
         tokensReceived: result.outputAmount,
@@ -63,7 +77,7 @@ export class BuybackExecutor {
       // await this.burnTokens(result.outputAmount);
 
     } catch (error) {
-      logger.error('Buyback execution failed', { error });
+      log.error('Buyback execution failed', { error });
       throw error;
     }
   }
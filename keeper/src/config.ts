@@ -1,6 +1,23 @@
 import * as fs from 'fs';
 import * as path from 'path';
 
+export interface PriorityFeeConfig {
+  /** Percentile of recent fees paid for the same write-locked accounts */
+  percentile: number;
+  /** Floor price in micro-lamports per compute unit */
+  minMicroLamports: number;
+  /** Price multiplier per consecutive expiry of the same operation */
+  escalationMultiplier: number;
+  maxEscalations: number;
+  computeUnitLimit: number;
+  /** Most an operation may spend on priority fees, in lamports */
+  maxBudgetLamports: {
+    conversion: number;
+    buyback: number;
+    liquidity: number;
+  };
+}
+
 export interface KeeperConfig {
   rpcUrl: string;
   wsUrl: string;
@@ -26,6 +43,7 @@ export interface KeeperConfig {
     checkIntervalSeconds: number;
     logLevel: string;
   };
+  priorityFees: PriorityFeeConfig;
 }
 
 export function loadConfig(): KeeperConfig {
@@ -56,5 +74,15 @@ export function loadConfig(): KeeperConfig {
     throw new Error(`Allocations must sum to 1.0, got ${totalAllocation}`);
   }
 
+  const fees = config.priorityFees;
+  if (fees.percentile < 0 || fees.percentile > 100) {
+    throw new Error(`Priority fee percentile must be 0-100, got ${fees.percentile}`);
+  }
+  if (fees.escalationMultiplier < 1) {
+    throw new Error(
+      `Priority fee escalation multiplier must be at least 1, got ${fees.escalationMultiplier}`
+    );
+  }
+
   return config;
 }
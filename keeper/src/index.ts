@@ -1,11 +1,19 @@
 import { Connection, PublicKey, Keypair } from '@solana/web3.js';
 import { AnchorProvider, Program, Wallet } from '@coral-xyz/anchor';
+import winston from 'winston';
 import { Logger } from './logger';
 import { JupiterService } from './jupiter';
-import { TreasuryMonitor } from './treasury';
+import {
+  TreasuryMonitor,
+  PAUSE_ALL,
+  PAUSE_BUYBACKS,
+  PAUSE_CONVERSIONS,
+  PAUSE_LIQUIDITY,
+} from './treasury';
 import { BuybackExecutor } from './buyback';
 import { LiquidityManager } from './liquidity';
 import { loadConfig } from './config';
+import { PriorityFeeManager } from './priorityFees';
 import * as fs from 'fs';
 
 const logger = Logger.getInstance();
@@ -16,6 +24,7 @@ class KeeperBot {
   private program: Program;
   private config: any;
   private jupiter: JupiterService;
+  private fees: PriorityFeeManager;
   private treasuryMonitor: TreasuryMonitor;
   private buybackExecutor: BuybackExecutor;
   private liquidityManager: LiquidityManager;
@@ -38,6 +47,10 @@ class KeeperBot {
 
     // Initialize services
     this.jupiter = new JupiterService(this.config, this.connection);
+    this.fees = new PriorityFeeManager(
+      this.connection,
+      this.config.priorityFees
+    );
     this.treasuryMonitor = new TreasuryMonitor(
       this.connection,
       new PublicKey(this.config.treasuryAddress),
@@ -46,10 +59,12 @@ class KeeperBot {
     this.buybackExecutor = new BuybackExecutor(
       this.provider,
       this.jupiter,
+      this.fees,
       this.config
     );
     this.liquidityManager = new LiquidityManager(
       this.provider,
+      this.fees,
       this.config
     );
 
@@ -76,17 +91,21 @@ class KeeperBot {
   }
 
   private async runCycle(): Promise<void> {
-    logger.info('Running keeper cycle...');
+    const log = Logger.operation('cycle', {
+      slot: await this.connection.getSlot(),
+    });
+    log.info('Running keeper cycle...');
 
     // 1. Check treasury state
     const treasuryState = await this.treasuryMonitor.getTreasuryState();
     
-    if (treasuryState.paused) {
-      logger.warn('Treasury is paused, skipping cycle');
+    const paused = (flag: number) => (treasuryState.pauseFlags & flag) !== 0;
+    if ((treasuryState.pauseFlags & PAUSE_ALL) === PAUSE_ALL) {
+      log.warn('Treasury is paused, skipping cycle');
       return;
     }
 
-    logger.info('Treasury state', {
+    log.info('Treasury state', {
       solBalance: treasuryState.solBalance,
       usdcBalance: treasuryState.usdcBalance,
       totalSolCollected: treasuryState.totalSolCollected,
@@ -94,29 +113,52 @@ class KeeperBot {
     });
 
     // 2. Convert SOL to USDC if threshold met
-    if (treasuryState.solBalance >= this.config.limits.minSolToSwap) {
-      logger.info('SOL threshold met, initiating swap to USDC', {
+    if (
+      !paused(PAUSE_CONVERSIONS) &&
+      treasuryState.solBalance >= this.config.limits.minSolToSwap
+    ) {
+      log.info('SOL threshold met, initiating swap to USDC', {
         solAmount: treasuryState.solBalance,
       });
 
-      const usdcReceived = await this.swapSolToUsdc(treasuryState.solBalance);
+      const usdcReceived = await this.swapSolToUsdc(treasuryState.solBalance, log);
       
       if (usdcReceived > 0) {
-        logger.info('SOL -> USDC swap successful', { usdcReceived });
+        log.info('SOL -> USDC swap successful', { usdcReceived });
         treasuryState.usdcBalance += usdcReceived;
       }
     }
 
-    // 3. Check cooldown
-    const timeSinceLastOp = Date.now() / 1000 - treasuryState.lastOperationTimestamp;
+    // 3. Check cooldowns; buybacks and liquidity each run on their own timer
     const cooldownSeconds = this.config.limits.cooldownMinutes * 60;
-
-    if (timeSinceLastOp < cooldownSeconds) {
-      logger.info('Cooldown period active', {
-        timeSinceLastOp,
-        cooldownSeconds,
-        remainingSeconds: cooldownSeconds - timeSinceLastOp,
-      });
+    const liquidityCooldownSeconds =
+      treasuryState.liquidityCooldownSeconds > 0
+        ? treasuryState.liquidityCooldownSeconds
+        : cooldownSeconds;
+    const cooledDown = (operation: string, lastTimestamp: number, cooldown: number) => {
+      const timeSinceLastOp = Date.now() / 1000 - lastTimestamp;
+      if (timeSinceLastOp < cooldown) {
+        log.info('Cooldown period active', {
+          operation,
+          timeSinceLastOp,
+          cooldownSeconds: cooldown,
+          remainingSeconds: cooldown - timeSinceLastOp,
+        });
+        return false;
+      }
+      return true;
+    };
+    const buybackReady = cooledDown(
+      'buyback',
+      treasuryState.lastBuybackTimestamp,
+      cooldownSeconds
+    );
+    const liquidityReady = cooledDown(
+      'liquidity',
+      treasuryState.lastLiquidityTimestamp,
+      liquidityCooldownSeconds
+    );
+    if (!buybackReady && !liquidityReady) {
       return;
     }
 
@@ -124,13 +166,18 @@ class KeeperBot {
     const buybackBudget = this.calculateBuybackBudget(treasuryState);
     const liquidityBudget = this.calculateLiquidityBudget(treasuryState);
 
-    logger.info('Operation budgets calculated', {
+    log.info('Operation budgets calculated', {
       buybackBudget,
       liquidityBudget,
     });
 
     // 5. Execute buyback if budget available
-    if (buybackBudget > 0 && treasuryState.usdcBalance > 0) {
+    if (
+      !paused(PAUSE_BUYBACKS) &&
+      buybackReady &&
+      buybackBudget > 0 &&
+      treasuryState.usdcBalance > 0
+    ) {
       const buybackAmount = Math.min(
         buybackBudget,
         treasuryState.usdcBalance,
@@ -138,13 +185,18 @@ class KeeperBot {
       );
 
       if (buybackAmount > 0) {
-        logger.info('Executing buyback', { buybackAmount });
-        await this.buybackExecutor.executeBuyback(buybackAmount);
+        log.info('Executing buyback', { buybackAmount });
+        await this.buybackExecutor.executeBuyback(buybackAmount, log);
       }
     }
 
     // 6. Add liquidity if budget available
-    if (liquidityBudget > 0 && treasuryState.usdcBalance > 0) {
+    if (
+      !paused(PAUSE_LIQUIDITY) &&
+      liquidityReady &&
+      liquidityBudget > 0 &&
+      treasuryState.usdcBalance > 0
+    ) {
       const liquidityAmount = Math.min(
         liquidityBudget,
         treasuryState.usdcBalance,
@@ -152,15 +204,18 @@ class KeeperBot {
       );
 
       if (liquidityAmount > 0) {
-        logger.info('Adding liquidity', { liquidityAmount });
-        await this.liquidityManager.addLiquidity(liquidityAmount);
+        log.info('Adding liquidity', { liquidityAmount });
+        await this.liquidityManager.addLiquidity(liquidityAmount, log);
       }
     }
 
-    logger.info('Keeper cycle complete');
+    log.info('Keeper cycle complete');
   }
 
-  private async swapSolToUsdc(solAmount: number): Promise<number> {
+  private async swapSolToUsdc(
+    solAmount: number,
+    log: winston.Logger
+  ): Promise<number> {
     try {
       const quote = await this.jupiter.getQuote(
         'So11111111111111111111111111111111111111112', // SOL mint
@@ -170,7 +225,7 @@ class KeeperBot {
       );
 
       if (this.config.dryRun) {
-        logger.info('[DRY RUN] Would swap SOL to USDC', {
+        log.info('[DRY RUN] Would swap SOL to USDC', {
           solAmount,
           estimatedUsdc: quote.outAmount,
           priceImpact: quote.priceImpactPct,
@@ -178,15 +233,20 @@ class KeeperBot {
         return quote.outAmount;
       }
 
-      const result = await this.jupiter.executeSwap(quote);
-      logger.info('SOL -> USDC swap executed', {
+      const priorityFee = await this.fees.estimate('conversion', [
+        new PublicKey(this.config.treasuryAddress),
+      ]);
+      const result = await this.fees.track('conversion', () =>
+        this.jupiter.executeSwap(quote, priorityFee)
+      );
+      log.info('SOL -> USDC swap executed', {
         signature: result.signature,
         usdcReceived: result.outputAmount,
       });
 
       return result.outputAmount;
     } catch (error) {
-      logger.error('Failed to swap SOL to USDC', { error });
+      log.error('Failed to swap SOL to USDC', { error });
       return 0;
     }
   }
@@ -70,7 +70,11 @@ export class JupiterService {
     }
   }
 
-  async executeSwap(quote: JupiterQuote): Promise<SwapResult> {
+  /** `priorityFeeMicroLamports` prices the swap transaction's compute units */
+  async executeSwap(
+    quote: JupiterQuote,
+    priorityFeeMicroLamports?: number
+  ): Promise<SwapResult> {
     if (this.config.dryRun) {
       logger.info('[DRY RUN] Would execute swap', {
         inputMint: quote.inputMint,
@@ -93,6 +97,7 @@ export class JupiterService {
         quoteResponse: quote,
         userPublicKey: this.config.treasuryAddress,
         wrapAndUnwrapSol: true,
+        computeUnitPriceMicroLamports: priorityFeeMicroLamports,
       });
 
       const swapTransactionBuf = Buffer.from(
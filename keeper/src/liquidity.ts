@@ -1,40 +1,57 @@
 import { AnchorProvider } from '@coral-xyz/anchor';
+import { PublicKey } from '@solana/web3.js';
+import winston from 'winston';
 import { Logger } from './logger';
+import { PriorityFeeManager } from './priorityFees';
 
 const logger = Logger.getInstance();
 
 export class LiquidityManager {
   constructor(
     private provider: AnchorProvider,
+    private fees: PriorityFeeManager,
     private config: any
   ) {}
 
-  async addLiquidity(usdcAmount: number): Promise<void> {
+  async addLiquidity(
+    usdcAmount: number,
+    parent: winston.Logger = logger
+  ): Promise<void> {
+    const log = parent.child({ step: 'liquidity' });
     try {
-      logger.info('Adding liquidity', { usdcAmount });
+      log.info('Adding liquidity', { usdcAmount });
 
       // Calculate token amount based on pool ratio
       const tokenAmount = await this.calculateTokenAmount(usdcAmount);
 
       if (this.config.dryRun) {
-        logger.info('[DRY RUN] Would add liquidity', {
+        log.info('[DRY RUN] Would add liquidity', {
           usdcAmount,
           tokenAmount,
         });
         return;
       }
 
-      // In production:
+      const priorityFee = await this.fees.estimate('liquidity', [
+        new PublicKey(this.config.treasuryAddress),
+        new PublicKey(this.config.treasuryUsdcAccount),
+      ]);
+
+      // In production, with this.fees.instructions(priorityFee) prepended and
+      // sent through this.fees.track('liquidity', ...):
       // 1. Call DEX add liquidity instruction (Raydium/Orca)
-      // 2. Update program state via add_liquidity instruction
+      // 2. Update program state via add_liquidity instruction, which moves both
+      //    USDC and tokenAmount from the treasury vaults and rejects a ratio off
+      //    the pool price by more than slippageBps
 
-      logger.info('Liquidity added successfully', {
+      log.info('Liquidity added successfully', {
         usdcAmount,
         tokenAmount,
+        priorityFee,
       });
 
     } catch (error) {
-      logger.error('Failed to add liquidity', { error });
+      log.error('Failed to add liquidity', { error });
       throw error;
     }
   }
@@ -1,3 +1,4 @@
+import { randomUUID } from 'crypto';
 import winston from 'winston';
 
 export class Logger {
@@ -14,13 +15,19 @@ export class Logger {
           winston.format.errors({ stack: true }),
           winston.format.json()
         ),
+        defaultMeta: { service: 'keeper' },
         transports: [
-          new winston.transports.Console({
-            format: winston.format.combine(
-              winston.format.colorize(),
-              winston.format.simple()
-            ),
-          }),
+          // JSON lines for log aggregation; LOG_FORMAT=pretty for a terminal
+          new winston.transports.Console(
+            process.env.LOG_FORMAT === 'pretty'
+              ? {
+                  format: winston.format.combine(
+                    winston.format.colorize(),
+                    winston.format.simple()
+                  ),
+                }
+              : {}
+          ),
           new winston.transports.File({
             filename: 'logs/error.log',
             level: 'error',
@@ -34,4 +41,20 @@ export class Logger {
 
     return Logger.instance;
   }
+
+  /**
+   * Logger for one operation: every line carries the same `operationId`, so the
+   * operation can be followed across the keeper, the program logs and the
+   * off-chain tools (which take it as `USDCBALL_OPERATION_ID`)
+   */
+  static operation(
+    kind: string,
+    meta: Record<string, unknown> = {}
+  ): winston.Logger {
+    return Logger.getInstance().child({
+      operation: kind,
+      operationId: `${kind}-${randomUUID()}`,
+      ...meta,
+    });
+  }
 }
@@ -0,0 +1,126 @@
+import {
+  ComputeBudgetProgram,
+  Connection,
+  PublicKey,
+  TransactionInstruction,
+} from '@solana/web3.js';
+import { PriorityFeeConfig } from './config';
+import { Logger } from './logger';
+
+const logger = Logger.getInstance();
+
+export type Operation = 'conversion' | 'buyback' | 'liquidity';
+
+/** Value at percentile `p` (0-100) of `values`, nearest-rank; 0 when empty */
+export function percentile(values: number[], p: number): number {
+  if (values.length === 0) return 0;
+  const sorted = [...values].sort((a, b) => a - b);
+  const rank = Math.ceil((p / 100) * sorted.length);
+  return sorted[Math.min(Math.max(rank, 1), sorted.length) - 1];
+}
+
+/**
+ * Prices compute units from recent prioritization fees paid for the accounts an
+ * operation write-locks. Each consecutive expiry of an operation multiplies its
+ * price by `escalationMultiplier`, and a landed transaction resets it. The price
+ * never exceeds what keeps the operation within its lamport budget.
+ */
+export class PriorityFeeManager {
+  private expiries: Map<Operation, number> = new Map();
+
+  constructor(
+    private connection: Connection,
+    private config: PriorityFeeConfig
+  ) {}
+
+  /** Compute unit price in micro-lamports for `operation` */
+  async estimate(
+    operation: Operation,
+    writableAccounts: PublicKey[]
+  ): Promise<number> {
+    let recent = 0;
+    try {
+      const fees = await this.connection.getRecentPrioritizationFees({
+        lockedWritableAccounts: writableAccounts,
+      });
+      recent = percentile(
+        fees.map((f) => f.prioritizationFee),
+        this.config.percentile
+      );
+    } catch (error) {
+      logger.warn('Failed to fetch recent prioritization fees', { error });
+    }
+
+    const expiries = this.expiries.get(operation) || 0;
+    const escalated =
+      Math.max(recent, this.config.minMicroLamports) *
+      Math.pow(this.config.escalationMultiplier, expiries);
+    const price = Math.min(Math.floor(escalated), this.maxPrice(operation));
+
+    logger.debug('Priority fee estimated', {
+      operation,
+      recentMicroLamports: recent,
+      expiries,
+      microLamports: price,
+    });
+    return price;
+  }
+
+  /** Compute budget instructions to prepend to an operation's transaction */
+  instructions(microLamports: number): TransactionInstruction[] {
+    return [
+      ComputeBudgetProgram.setComputeUnitLimit({
+        units: this.config.computeUnitLimit,
+      }),
+      ComputeBudgetProgram.setComputeUnitPrice({ microLamports }),
+    ];
+  }
+
+  /**
+   * Run `send` for `operation`, resetting its escalation once it lands and
+   * escalating when it expires
+   */
+  async track<T>(operation: Operation, send: () => Promise<T>): Promise<T> {
+    try {
+      const result = await send();
+      this.recordLanded(operation);
+      return result;
+    } catch (error) {
+      if (isExpiry(error)) {
+        this.recordExpiry(operation);
+      }
+      throw error;
+    }
+  }
+
+  recordExpiry(operation: Operation): void {
+    const expiries = Math.min(
+      (this.expiries.get(operation) || 0) + 1,
+      this.config.maxEscalations
+    );
+    this.expiries.set(operation, expiries);
+    logger.warn('Transaction expired, escalating priority fee', {
+      operation,
+      expiries,
+    });
+  }
+
+  recordLanded(operation: Operation): void {
+    this.expiries.delete(operation);
+  }
+
+  /** Highest price keeping a full compute unit limit within the operation's budget */
+  private maxPrice(operation: Operation): number {
+    const budgetLamports = this.config.maxBudgetLamports[operation];
+    return Math.floor((budgetLamports * 1_000_000) / this.config.computeUnitLimit);
+  }
+}
+
+/** Whether `error` is a transaction that expired before landing */
+export function isExpiry(error: unknown): boolean {
+  return (
+    error instanceof Error &&
+    (error.name === 'TransactionExpiredBlockheightExceededError' ||
+      error.message.includes('block height exceeded'))
+  );
+}
@@ -4,6 +4,13 @@ import { Logger } from './logger';
 
 const logger = Logger.getInstance();
 
+/** Bits of `Treasury.pauseFlags`, one per independently pausable operation type */
+export const PAUSE_BUYBACKS = 1 << 0;
+export const PAUSE_LIQUIDITY = 1 << 1;
+export const PAUSE_CONVERSIONS = 1 << 2;
+export const PAUSE_FEES = 1 << 3;
+export const PAUSE_ALL = 0b1111;
+
 export interface TreasuryState {
   authority: PublicKey;
   solBalance: number;
@@ -20,7 +27,12 @@ export interface TreasuryState {
   totalLiquidityUsdc: number;
   totalTokensBurned: number;
   lastOperationTimestamp: number;
-  paused: boolean;
+  /** Buybacks and liquidity operations cool down independently */
+  lastBuybackTimestamp: number;
+  lastLiquidityTimestamp: number;
+  /** 0 means liquidity shares `cooldownSeconds` */
+  liquidityCooldownSeconds: number;
+  pauseFlags: number;
 }
 
 export class TreasuryMonitor {
@@ -74,7 +86,10 @@ export class TreasuryMonitor {
       totalLiquidityUsdc: 400_000_000,
       totalTokensBurned: 0,
       lastOperationTimestamp: Math.floor(Date.now() / 1000) - 7200, // 2 hours ago
-      paused: false,
+      lastBuybackTimestamp: Math.floor(Date.now() / 1000) - 7200,
+      lastLiquidityTimestamp: Math.floor(Date.now() / 1000) - 10800, // 3 hours ago
+      liquidityCooldownSeconds: 0,
+      pauseFlags: 0,
     };
   }
 
@@ -0,0 +1,171 @@
+import { Connection, PublicKey } from '@solana/web3.js';
+import { PriorityFeeConfig } from '../src/config';
+import { isExpiry, percentile, PriorityFeeManager } from '../src/priorityFees';
+
+jest.mock('../src/logger', () => ({
+  Logger: {
+    getInstance: () => ({ debug: jest.fn(), warn: jest.fn() }),
+  },
+}));
+
+const config: PriorityFeeConfig = {
+  percentile: 75,
+  minMicroLamports: 1_000,
+  escalationMultiplier: 2,
+  maxEscalations: 3,
+  computeUnitLimit: 400_000,
+  // At 400k CUs: 40k micro-lamports for conversions, 100k for buybacks
+  maxBudgetLamports: {
+    conversion: 16_000,
+    buyback: 40_000,
+    liquidity: 16_000,
+  },
+};
+
+function connectionWith(fees: number[] | Error) {
+  const getRecentPrioritizationFees = jest.fn(async () => {
+    if (fees instanceof Error) throw fees;
+    return fees.map((prioritizationFee, slot) => ({ slot, prioritizationFee }));
+  });
+  return {
+    connection: { getRecentPrioritizationFees } as unknown as Connection,
+    getRecentPrioritizationFees,
+  };
+}
+
+function expired(): Error {
+  const error = new Error('Signature abc has expired: block height exceeded.');
+  error.name = 'TransactionExpiredBlockheightExceededError';
+  return error;
+}
+
+describe('percentile', () => {
+  it('takes the nearest-rank value', () => {
+    const fees = [40, 10, 30, 20];
+    expect(percentile(fees, 0)).toBe(10);
+    expect(percentile(fees, 50)).toBe(20);
+    expect(percentile(fees, 75)).toBe(30);
+    expect(percentile(fees, 100)).toBe(40);
+  });
+
+  it('is 0 without samples', () => {
+    expect(percentile([], 75)).toBe(0);
+  });
+});
+
+describe('PriorityFeeManager', () => {
+  it('prices from fees paid on the accounts the operation write-locks', async () => {
+    const { connection, getRecentPrioritizationFees } = connectionWith([
+      2_000, 8_000, 4_000, 6_000,
+    ]);
+    const accounts = [PublicKey.unique(), PublicKey.unique()];
+
+    const price = await new PriorityFeeManager(connection, config).estimate(
+      'buyback',
+      accounts
+    );
+
+    expect(price).toBe(6_000);
+    expect(getRecentPrioritizationFees).toHaveBeenCalledWith({
+      lockedWritableAccounts: accounts,
+    });
+  });
+
+  it('never prices below the floor', async () => {
+    const { connection } = connectionWith([0, 0, 10]);
+
+    const price = await new PriorityFeeManager(connection, config).estimate(
+      'buyback',
+      []
+    );
+
+    expect(price).toBe(1_000);
+  });
+
+  it('falls back to the floor when recent fees are unavailable', async () => {
+    const { connection } = connectionWith(new Error('rpc down'));
+
+    const price = await new PriorityFeeManager(connection, config).estimate(
+      'buyback',
+      []
+    );
+
+    expect(price).toBe(1_000);
+  });
+
+  it('keeps each operation within its own budget', async () => {
+    const { connection } = connectionWith([1_000_000]);
+    const fees = new PriorityFeeManager(connection, config);
+
+    expect(await fees.estimate('conversion', [])).toBe(40_000);
+    expect(await fees.estimate('buyback', [])).toBe(100_000);
+  });
+
+  it('escalates after each expiry up to the limit', async () => {
+    const { connection } = connectionWith([5_000]);
+    const fees = new PriorityFeeManager(connection, config);
+
+    fees.recordExpiry('buyback');
+    expect(await fees.estimate('buyback', [])).toBe(10_000);
+    fees.recordExpiry('buyback');
+    fees.recordExpiry('buyback');
+    fees.recordExpiry('buyback');
+    expect(await fees.estimate('buyback', [])).toBe(40_000);
+
+    // Other operations keep their own count
+    expect(await fees.estimate('liquidity', [])).toBe(5_000);
+  });
+
+  it('caps an escalated price at the budget', async () => {
+    const { connection } = connectionWith([15_000]);
+    const fees = new PriorityFeeManager(connection, config);
+
+    fees.recordExpiry('conversion');
+    fees.recordExpiry('conversion');
+
+    expect(await fees.estimate('conversion', [])).toBe(40_000);
+  });
+
+  it('escalates on expired sends and resets once one lands', async () => {
+    const { connection } = connectionWith([5_000]);
+    const fees = new PriorityFeeManager(connection, config);
+
+    await expect(
+      fees.track('buyback', async () => {
+        throw expired();
+      })
+    ).rejects.toThrow('block height exceeded');
+    expect(await fees.estimate('buyback', [])).toBe(10_000);
+
+    await expect(
+      fees.track('buyback', async () => {
+        throw new Error('custom program error: 0x1771');
+      })
+    ).rejects.toThrow('0x1771');
+    expect(await fees.estimate('buyback', [])).toBe(10_000);
+
+    await expect(fees.track('buyback', async () => 'sig')).resolves.toBe('sig');
+    expect(await fees.estimate('buyback', [])).toBe(5_000);
+  });
+
+  it('sets the configured compute unit limit and price', () => {
+    const { connection } = connectionWith([]);
+
+    const [limit, price] = new PriorityFeeManager(
+      connection,
+      config
+    ).instructions(7_500);
+
+    expect(limit.data.readUInt32LE(1)).toBe(400_000);
+    expect(price.data.readBigUInt64LE(1)).toBe(BigInt(7_500));
+  });
+});
+
+describe('isExpiry', () => {
+  it('recognises expired transactions only', () => {
+    expect(isExpiry(expired())).toBe(true);
+    expect(isExpiry(new Error('block height exceeded'))).toBe(true);
+    expect(isExpiry(new Error('insufficient funds'))).toBe(false);
+    expect(isExpiry('block height exceeded')).toBe(false);
+  });
+});
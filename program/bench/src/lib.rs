@@ -0,0 +1,34 @@
+//! Compute-unit budgets for USDCBALL instructions.
+//!
+//! `tests/compute_units.rs` runs every instruction against the compiled SBF program
+//! under `solana-program-test` and fails when one exceeds its budget here. Raise a
+//! budget deliberately, in the same change that makes an instruction more expensive.
+
+/// Maximum compute units each instruction may consume, keyed by instruction name.
+pub const CU_BUDGETS: &[(&str, u64)] = &[
+    ("initialize", 25_000),
+    ("initialize_fee_vault", 15_000),
+    ("initialize_fee_counter", 15_000),
+    ("deposit_fee", 12_000),
+    ("record_fee", 4_000),
+    ("sync_fee_counter", 8_000),
+    ("record_usdc_conversion", 8_000),
+    ("update_config", 8_000),
+    ("set_instruction_enabled", 8_000),
+    ("open_quote_board", 15_000),
+    ("initialize_pool_registry", 15_000),
+    ("register_pool", 10_000),
+    ("set_pool_limits", 10_000),
+    ("register_operator", 15_000),
+    ("open_lp_position", 15_000),
+    ("execute_burn", 15_000),
+    ("emergency_pause", 8_000),
+    ("resume", 8_000),
+];
+
+pub fn budget_for(instruction: &str) -> Option<u64> {
+    CU_BUDGETS
+        .iter()
+        .find(|(name, _)| *name == instruction)
+        .map(|(_, budget)| *budget)
+}
@@ -0,0 +1,578 @@
+//! Sends instructions with spoofed accounts and signers against the compiled
+//! program and checks each is rejected with the specific error expected.
+//!
+//! Covers forged PDAs (a valid account copied to an address off its seeds, or the
+//! PDA of another key), signers without the required role, substituted token
+//! accounts and replayed one-time instructions, with one test per instruction.
+//! Like the CU benchmark it requires `target/deploy/usdcball.so` and fails
+//! without it, so it is ignored by default; `anchor run bench` runs it.
+
+use anchor_lang::{
+    error::ErrorCode as AnchorError,
+    solana_program::{
+        instruction::{AccountMeta, Instruction, InstructionError},
+        program_pack::Pack,
+        system_program,
+    },
+    AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas,
+};
+use anchor_spl::{token::spl_token, token_2022::spl_token_2022};
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    transaction::{Transaction, TransactionError},
+};
+use std::path::PathBuf;
+use usdcball::ErrorCode;
+
+const TOKEN_ACCOUNT_BALANCE: u64 = 1_000_000_000;
+
+/// `SystemError::AccountAlreadyInUse`, returned when `init` targets an existing account
+const SYSTEM_ACCOUNT_ALREADY_IN_USE: u32 = 0;
+
+fn sbf_out_dir() -> PathBuf {
+    std::env::var("SBF_OUT_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../target/deploy"))
+}
+
+fn token_account_of(program: Pubkey, mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    let mut data = vec![0u8; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+    Account {
+        lamports: 10_000_000,
+        data,
+        owner: program,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn mint_account_of(program: Pubkey) -> Account {
+    let mut data = vec![0u8; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        decimals: 6,
+        is_initialized: true,
+        supply: TOKEN_ACCOUNT_BALANCE * 3,
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+    Account {
+        lamports: 10_000_000,
+        data,
+        owner: program,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+struct Accounts {
+    treasury: Pubkey,
+    usdc_mint: Pubkey,
+    token_mint: Pubkey,
+    treasury_usdc: Pubkey,
+    treasury_token: Pubkey,
+    /// USDC account owned by the attacker instead of the treasury
+    attacker_usdc: Pubkey,
+    /// Token-program account holding the treasury's tokens, not USDC
+    wrong_mint_usdc: Pubkey,
+    /// Token account data under the system program instead of a token program
+    unowned_usdc: Pubkey,
+}
+
+struct Harness {
+    ctx: ProgramTestContext,
+    failures: Vec<String>,
+}
+
+impl Harness {
+    /// Processes `ix` signed by the payer and `signers`, returning the instruction
+    /// error it failed with.
+    async fn submit(&mut self, ix: Instruction, signers: &[&Keypair]) -> Option<TransactionError> {
+        let payer = self.ctx.payer.insecure_clone();
+        let blockhash = self.ctx.banks_client.get_latest_blockhash().await.unwrap();
+        let mut all: Vec<&Keypair> = vec![&payer];
+        all.extend_from_slice(signers);
+        let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &all, blockhash);
+        self.ctx
+            .banks_client
+            .process_transaction(tx)
+            .await
+            .err()
+            .map(|err| err.unwrap())
+    }
+
+    /// Setup steps, which must succeed
+    async fn send(&mut self, name: &str, ix: Instruction, signers: &[&Keypair]) {
+        if let Some(err) = self.submit(ix, signers).await {
+            panic!("{name} failed during setup: {err:?}");
+        }
+    }
+
+    /// Records a failure unless `ix` is rejected with custom error `code`
+    async fn expect(&mut self, name: &str, ix: Instruction, signers: &[&Keypair], code: u32) {
+        match self.submit(ix, signers).await {
+            Some(TransactionError::InstructionError(_, InstructionError::Custom(got)))
+                if got == code => {}
+            other => self
+                .failures
+                .push(format!("{name}: expected error {code}, got {other:?}")),
+        }
+    }
+
+    /// Writes a copy of the program-owned account at `source`, rewritten by
+    /// `tamper`, to `target`
+    async fn forge<T: AccountDeserialize + AccountSerialize>(
+        &mut self,
+        source: Pubkey,
+        target: Pubkey,
+        tamper: impl FnOnce(&mut T),
+    ) {
+        let account = self
+            .ctx
+            .banks_client
+            .get_account(source)
+            .await
+            .unwrap()
+            .expect("forged account source exists");
+        let mut state = T::try_deserialize(&mut account.data.as_slice()).unwrap();
+        tamper(&mut state);
+        let mut data = Vec::with_capacity(account.data.len());
+        state.try_serialize(&mut data).unwrap();
+        data.resize(account.data.len(), 0);
+
+        self.ctx.set_account(
+            &target,
+            &AccountSharedData::from(Account { data, ..account }),
+        );
+    }
+
+    fn authority(&self) -> Pubkey {
+        self.ctx.payer.pubkey()
+    }
+
+    /// Fails the test with every case that was not rejected as expected
+    fn finish(self) {
+        assert!(
+            self.failures.is_empty(),
+            "spoofed instructions not rejected as expected:\n{}",
+            self.failures.join("\n")
+        );
+    }
+}
+
+fn ix(data: Vec<u8>, accounts: Vec<AccountMeta>) -> Instruction {
+    Instruction {
+        program_id: usdcball::ID,
+        accounts,
+        data,
+    }
+}
+
+fn anchor(code: AnchorError) -> u32 {
+    code.into()
+}
+
+fn program(code: ErrorCode) -> u32 {
+    code.into()
+}
+
+fn initialize(a: &Accounts, authority: Pubkey) -> Instruction {
+    ix(
+        usdcball::instruction::Initialize {
+            buyback_allocation_bps: 5000,
+            liquidity_allocation_bps: 3000,
+            reserve_allocation_bps: 2000,
+            max_usdc_per_cycle: TOKEN_ACCOUNT_BALANCE,
+            cooldown_seconds: 0,
+            slippage_bps: 200,
+            expected_token_decimals: 6,
+            expected_token_mint_authority: None,
+            launch_epochs: 0,
+        }
+        .data(),
+        usdcball::accounts::Initialize {
+            treasury: a.treasury,
+            token_mint: a.token_mint,
+            usdc_mint: a.usdc_mint,
+            authority,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+    )
+}
+
+fn operator_pda(operator: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"operator", operator.as_ref()], &usdcball::ID).0
+}
+
+fn register_operator(treasury: Pubkey, authority: Pubkey, operator: Pubkey) -> Instruction {
+    ix(
+        usdcball::instruction::RegisterOperator { operator }.data(),
+        usdcball::accounts::RegisterOperator {
+            treasury,
+            operator_stats: operator_pda(operator),
+            authority,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+    )
+}
+
+fn update_config(treasury: Pubkey, authority: Pubkey, activity: Option<Pubkey>) -> Instruction {
+    ix(
+        usdcball::instruction::UpdateConfig {
+            max_usdc_per_cycle: None,
+            cooldown_seconds: Some(0),
+            slippage_bps: Some(100),
+            keeper_reward_bps: None,
+            keeper_reward_cap_usdc: None,
+        }
+        .data(),
+        usdcball::accounts::UpdateConfig {
+            treasury,
+            authority,
+            authority_activity: activity,
+        }
+        .to_account_metas(None),
+    )
+}
+
+fn execute_buyback(a: &Accounts, treasury_usdc: Pubkey, authority: Pubkey) -> Instruction {
+    ix(
+        usdcball::instruction::ExecuteBuyback {
+            usdc_amount: 1_000_000,
+            min_tokens_out: 0,
+            allow_partial: false,
+            venue: usdcball::Venue::Jupiter,
+            swap_data: vec![],
+        }
+        .data(),
+        usdcball::accounts::ExecuteBuyback {
+            treasury: a.treasury,
+            treasury_usdc,
+            token_mint: a.token_mint,
+            treasury_token: a.treasury_token,
+            token_usd_oracle: None,
+            token_switchboard_feed: None,
+            twap: None,
+            twap_pool_usdc: None,
+            twap_pool_token: None,
+            authority,
+            operator_stats: operator_pda(authority),
+            authority_activity: None,
+            co_signer: None,
+            co_signer_operator: None,
+            token_program: spl_token::ID,
+            quote_board: None,
+        }
+        .to_account_metas(None),
+    )
+}
+
+/// Starts the compiled program with an initialized treasury, and operator
+/// accounts for both the authority and the attacker, so only the operator role
+/// tells them apart
+async fn setup() -> (Harness, Accounts, Keypair) {
+    let out_dir = sbf_out_dir();
+    assert!(
+        out_dir.join("usdcball.so").exists(),
+        "usdcball.so not found in {}; run `anchor build` first",
+        out_dir.display()
+    );
+    std::env::set_var("SBF_OUT_DIR", &out_dir);
+
+    let mut pt = ProgramTest::default();
+    pt.prefer_bpf(true);
+    pt.add_program("usdcball", usdcball::ID, None);
+
+    let attacker = Keypair::new();
+    let attacker_key = attacker.pubkey();
+    let (treasury, _) = Pubkey::find_program_address(&[b"treasury"], &usdcball::ID);
+    let a = Accounts {
+        treasury,
+        usdc_mint: Pubkey::new_unique(),
+        token_mint: Pubkey::new_unique(),
+        treasury_usdc: Pubkey::new_unique(),
+        treasury_token: Pubkey::new_unique(),
+        attacker_usdc: Pubkey::new_unique(),
+        wrong_mint_usdc: Pubkey::new_unique(),
+        unowned_usdc: Pubkey::new_unique(),
+    };
+    pt.add_account(a.usdc_mint, mint_account_of(spl_token::ID));
+    pt.add_account(a.token_mint, mint_account_of(spl_token_2022::ID));
+    pt.add_account(
+        a.treasury_usdc,
+        token_account_of(spl_token::ID, a.usdc_mint, treasury, TOKEN_ACCOUNT_BALANCE),
+    );
+    pt.add_account(
+        a.treasury_token,
+        token_account_of(spl_token_2022::ID, a.token_mint, treasury, 0),
+    );
+    pt.add_account(
+        a.attacker_usdc,
+        token_account_of(
+            spl_token::ID,
+            a.usdc_mint,
+            attacker_key,
+            TOKEN_ACCOUNT_BALANCE,
+        ),
+    );
+    pt.add_account(
+        a.wrong_mint_usdc,
+        token_account_of(spl_token::ID, a.token_mint, treasury, TOKEN_ACCOUNT_BALANCE),
+    );
+    pt.add_account(
+        a.unowned_usdc,
+        token_account_of(
+            system_program::ID,
+            a.usdc_mint,
+            treasury,
+            TOKEN_ACCOUNT_BALANCE,
+        ),
+    );
+
+    let mut h = Harness {
+        ctx: pt.start_with_context().await,
+        failures: vec![],
+    };
+    let authority = h.authority();
+    h.send("initialize", initialize(&a, authority), &[]).await;
+    h.send(
+        "register_operator",
+        register_operator(treasury, authority, authority),
+        &[],
+    )
+    .await;
+    h.send(
+        "register_operator for the attacker",
+        register_operator(treasury, authority, attacker_key),
+        &[],
+    )
+    .await;
+    (h, a, attacker)
+}
+
+fn set_pauser(treasury: Pubkey, authority: Pubkey, pauser: Pubkey) -> Instruction {
+    ix(
+        usdcball::instruction::SetPauser { pauser }.data(),
+        usdcball::accounts::UpdateConfig {
+            treasury,
+            authority,
+            authority_activity: None,
+        }
+        .to_account_metas(None),
+    )
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/usdcball.so; run with `anchor run bench`"]
+async fn initialize_cannot_be_replayed() {
+    let (mut h, a, attacker) = setup().await;
+
+    // The treasury can only be created once, by anyone
+    h.expect(
+        "initialize replayed by another signer",
+        initialize(&a, attacker.pubkey()),
+        &[&attacker],
+        SYSTEM_ACCOUNT_ALREADY_IN_USE,
+    )
+    .await;
+    h.finish();
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/usdcball.so; run with `anchor run bench`"]
+async fn update_config_rejects_spoofed_signers_and_pdas() {
+    let (mut h, a, attacker) = setup().await;
+    let (treasury, authority, attacker_key) = (a.treasury, h.authority(), attacker.pubkey());
+
+    h.expect(
+        "update_config signed by a non-authority",
+        update_config(treasury, attacker_key, None),
+        &[&attacker],
+        anchor(AnchorError::ConstraintRaw),
+    )
+    .await;
+
+    // Forged PDA: a copy of the treasury naming the attacker as authority, stored
+    // off the treasury seeds
+    let forged_treasury = Pubkey::new_unique();
+    h.forge::<usdcball::Treasury>(treasury, forged_treasury, |t| t.authority = attacker_key)
+        .await;
+    h.expect(
+        "update_config against a forged treasury",
+        update_config(forged_treasury, attacker_key, None),
+        &[&attacker],
+        anchor(AnchorError::ConstraintSeeds),
+    )
+    .await;
+
+    // A key-activity PDA derived for another key
+    let (attacker_activity, _) =
+        Pubkey::find_program_address(&[b"key_activity", attacker_key.as_ref()], &usdcball::ID);
+    h.send(
+        "open_key_activity",
+        ix(
+            usdcball::instruction::OpenKeyActivity { key: attacker_key }.data(),
+            usdcball::accounts::OpenKeyActivity {
+                activity: attacker_activity,
+                payer: authority,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+        ),
+        &[],
+    )
+    .await;
+    h.expect(
+        "update_config logging to another key's activity",
+        update_config(treasury, authority, Some(attacker_activity)),
+        &[],
+        anchor(AnchorError::ConstraintSeeds),
+    )
+    .await;
+
+    // Wrong bump: the treasury at its own address, with its stored bump altered.
+    // Runs last since it leaves the treasury unusable.
+    h.forge::<usdcball::Treasury>(treasury, treasury, |t| t.bump = t.bump.wrapping_sub(1))
+        .await;
+    h.expect(
+        "update_config against a treasury with a wrong bump",
+        update_config(treasury, authority, None),
+        &[],
+        anchor(AnchorError::ConstraintSeeds),
+    )
+    .await;
+    h.finish();
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/usdcball.so; run with `anchor run bench`"]
+async fn execute_buyback_rejects_non_operators_and_substituted_accounts() {
+    let (mut h, a, attacker) = setup().await;
+    let authority = h.authority();
+
+    h.expect(
+        "execute_buyback signed by a non-operator",
+        execute_buyback(&a, a.treasury_usdc, attacker.pubkey()),
+        &[&attacker],
+        anchor(AnchorError::ConstraintRaw),
+    )
+    .await;
+
+    // Substituted token accounts, checked before any transfer is attempted
+    h.expect(
+        "execute_buyback from an attacker-owned USDC account",
+        execute_buyback(&a, a.attacker_usdc, authority),
+        &[],
+        program(ErrorCode::VaultNotOwnedByTreasury),
+    )
+    .await;
+    h.expect(
+        "execute_buyback from a token account of another mint",
+        execute_buyback(&a, a.wrong_mint_usdc, authority),
+        &[],
+        program(ErrorCode::WrongUsdcMint),
+    )
+    .await;
+    h.expect(
+        "execute_buyback from token data outside the token program",
+        execute_buyback(&a, a.unowned_usdc, authority),
+        &[],
+        anchor(AnchorError::AccountOwnedByWrongProgram),
+    )
+    .await;
+    h.finish();
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/usdcball.so; run with `anchor run bench`"]
+async fn emergency_pause_requires_the_pauser() {
+    let (mut h, a, attacker) = setup().await;
+
+    h.expect(
+        "emergency_pause signed by a non-pauser",
+        ix(
+            usdcball::instruction::EmergencyPause {}.data(),
+            usdcball::accounts::PauseTreasury {
+                treasury: a.treasury,
+                pauser: attacker.pubkey(),
+                pauser_activity: None,
+            }
+            .to_account_metas(None),
+        ),
+        &[&attacker],
+        anchor(AnchorError::ConstraintRaw),
+    )
+    .await;
+    h.finish();
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/usdcball.so; run with `anchor run bench`"]
+async fn resume_is_refused_to_the_pauser() {
+    let (mut h, a, attacker) = setup().await;
+    let authority = h.authority();
+
+    // The pauser role may halt the treasury but never resume it
+    h.send(
+        "set_pauser",
+        set_pauser(a.treasury, authority, attacker.pubkey()),
+        &[],
+    )
+    .await;
+    h.expect(
+        "resume signed by the pauser",
+        ix(
+            usdcball::instruction::Resume {}.data(),
+            usdcball::accounts::EmergencyAction {
+                treasury: a.treasury,
+                authority: attacker.pubkey(),
+                authority_activity: None,
+            }
+            .to_account_metas(None),
+        ),
+        &[&attacker],
+        anchor(AnchorError::ConstraintRaw),
+    )
+    .await;
+    h.finish();
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/usdcball.so; run with `anchor run bench`"]
+async fn record_fee_rejects_a_treasury_outside_the_program() {
+    let (mut h, _, attacker) = setup().await;
+
+    // A forged treasury is refused where it is read in place by owner check
+    let fee_counter = Pubkey::find_program_address(&[b"fee_counter"], &usdcball::ID).0;
+    let fee_vault = Pubkey::find_program_address(&[b"fee_vault"], &usdcball::ID).0;
+    h.expect(
+        "record_fee with a treasury outside the program",
+        ix(
+            usdcball::instruction::RecordFee { amount: 1 }.data(),
+            usdcball::accounts::RecordFee {
+                treasury: attacker.pubkey(),
+                fee_counter,
+                fee_vault,
+                sol_usd_oracle: None,
+            }
+            .to_account_metas(None),
+        ),
+        &[],
+        program(ErrorCode::InvalidTreasuryAccount),
+    )
+    .await;
+    h.finish();
+}
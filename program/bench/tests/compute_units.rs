@@ -0,0 +1,546 @@
+//! Runs each instruction against the compiled program and checks its CU usage.
+//!
+//! Requires `anchor build` to have produced `target/deploy/usdcball.so` and fails
+//! without it, so it is ignored by default; `anchor run bench` runs it.
+
+use anchor_lang::{
+    solana_program::{
+        instruction::Instruction, program_pack::Pack, system_instruction, system_program,
+    },
+    InstructionData, ToAccountMetas,
+};
+use anchor_spl::{token::spl_token, token_2022::spl_token_2022};
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account, pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::Transaction,
+};
+use std::path::PathBuf;
+use usdcball_bench::budget_for;
+
+const TOKEN_ACCOUNT_BALANCE: u64 = 1_000_000_000;
+
+fn sbf_out_dir() -> PathBuf {
+    std::env::var("SBF_OUT_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../target/deploy"))
+}
+
+fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    token_account_of(spl_token::ID, mint, owner, amount)
+}
+
+fn token_account_of(program: Pubkey, mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    let mut data = vec![0u8; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+    Account {
+        lamports: 10_000_000,
+        data,
+        owner: program,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn mint_account() -> Account {
+    mint_account_of(spl_token::ID)
+}
+
+fn mint_account_of(program: Pubkey) -> Account {
+    let mut data = vec![0u8; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        decimals: 6,
+        is_initialized: true,
+        supply: TOKEN_ACCOUNT_BALANCE * 3,
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+    Account {
+        lamports: 10_000_000,
+        data,
+        owner: program,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+struct Bench {
+    ctx: ProgramTestContext,
+    pool_usdc: Pubkey,
+    token_mint: Pubkey,
+    treasury_token: Pubkey,
+    pool_token: Pubkey,
+    results: Vec<(&'static str, u64)>,
+}
+
+impl Bench {
+    /// Simulates `ix` to read its CU consumption, then processes it so later
+    /// instructions observe the updated state.
+    async fn run(&mut self, name: &'static str, ix: Instruction) {
+        let payer = self.ctx.payer.insecure_clone();
+        let blockhash = self.ctx.banks_client.get_latest_blockhash().await.unwrap();
+        let tx =
+            Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer], blockhash);
+
+        let sim = self
+            .ctx
+            .banks_client
+            .simulate_transaction(tx.clone())
+            .await
+            .unwrap();
+        let details = sim.simulation_details.expect("simulation details");
+        if let Some(Err(err)) = sim.result {
+            panic!("{name} failed: {err:?}\n{}", details.logs.join("\n"));
+        }
+        self.results.push((name, details.units_consumed));
+
+        self.ctx.banks_client.process_transaction(tx).await.unwrap();
+    }
+
+    /// Processes `ix` without measuring it, for setup steps outside the program.
+    async fn send(&mut self, ix: Instruction) {
+        let payer = self.ctx.payer.insecure_clone();
+        let blockhash = self.ctx.banks_client.get_latest_blockhash().await.unwrap();
+        let tx =
+            Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer], blockhash);
+        self.ctx.banks_client.process_transaction(tx).await.unwrap();
+    }
+
+    fn authority(&self) -> Pubkey {
+        self.ctx.payer.pubkey()
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/usdcball.so; run with `anchor run bench`"]
+async fn instructions_stay_within_cu_budget() {
+    let out_dir = sbf_out_dir();
+    assert!(
+        out_dir.join("usdcball.so").exists(),
+        "usdcball.so not found in {}; run `anchor build` first",
+        out_dir.display()
+    );
+    std::env::set_var("SBF_OUT_DIR", &out_dir);
+
+    let mut pt = ProgramTest::default();
+    pt.prefer_bpf(true);
+    pt.add_program("usdcball", usdcball::ID, None);
+
+    let (treasury, _) = Pubkey::find_program_address(&[b"treasury"], &usdcball::ID);
+    let usdc_mint = Keypair::new().pubkey();
+    let token_mint = Keypair::new().pubkey();
+    let pool_usdc = Keypair::new().pubkey();
+    let treasury_token = Keypair::new().pubkey();
+    let pool_token = Keypair::new().pubkey();
+    let lp_mint = Keypair::new().pubkey();
+    pt.add_account(usdc_mint, mint_account());
+    pt.add_account(lp_mint, mint_account());
+    pt.add_account(token_mint, mint_account_of(spl_token_2022::ID));
+    pt.add_account(pool_usdc, token_account(usdc_mint, Pubkey::new_unique(), 0));
+    pt.add_account(
+        treasury_token,
+        token_account_of(
+            spl_token_2022::ID,
+            token_mint,
+            treasury,
+            TOKEN_ACCOUNT_BALANCE,
+        ),
+    );
+    pt.add_account(
+        pool_token,
+        token_account_of(spl_token_2022::ID, token_mint, Pubkey::new_unique(), 0),
+    );
+
+    let ctx = pt.start_with_context().await;
+    let mut bench = Bench {
+        ctx,
+        pool_usdc,
+        token_mint,
+        treasury_token,
+        pool_token,
+        results: vec![],
+    };
+    let authority = bench.authority();
+
+    let ix = |data: Vec<u8>, accounts: Vec<_>| Instruction {
+        program_id: usdcball::ID,
+        accounts,
+        data,
+    };
+
+    bench
+        .run(
+            "initialize",
+            ix(
+                usdcball::instruction::Initialize {
+                    buyback_allocation_bps: 5000,
+                    liquidity_allocation_bps: 3000,
+                    reserve_allocation_bps: 2000,
+                    max_usdc_per_cycle: TOKEN_ACCOUNT_BALANCE,
+                    cooldown_seconds: 0,
+                    slippage_bps: 200,
+                    expected_token_decimals: 6,
+                    expected_token_mint_authority: None,
+                    launch_epochs: 0,
+                }
+                .data(),
+                usdcball::accounts::Initialize {
+                    treasury,
+                    token_mint,
+                    usdc_mint,
+                    authority,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+            ),
+        )
+        .await;
+
+    let (fee_vault, _) = Pubkey::find_program_address(&[b"fee_vault"], &usdcball::ID);
+    bench
+        .run(
+            "initialize_fee_vault",
+            ix(
+                usdcball::instruction::InitializeFeeVault {}.data(),
+                usdcball::accounts::InitializeFeeVault {
+                    treasury,
+                    fee_vault,
+                    authority,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+            ),
+        )
+        .await;
+
+    let (fee_counter, _) = Pubkey::find_program_address(&[b"fee_counter"], &usdcball::ID);
+    bench
+        .run(
+            "initialize_fee_counter",
+            ix(
+                usdcball::instruction::InitializeFeeCounter {}.data(),
+                usdcball::accounts::InitializeFeeCounter {
+                    treasury,
+                    fee_counter,
+                    authority,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+            ),
+        )
+        .await;
+
+    bench
+        .run(
+            "deposit_fee",
+            ix(
+                usdcball::instruction::DepositFee {
+                    amount: 1_000_000_000,
+                }
+                .data(),
+                usdcball::accounts::DepositFee {
+                    treasury,
+                    fee_vault,
+                    fee_counter,
+                    sol_usd_oracle: None,
+                    depositor: authority,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+            ),
+        )
+        .await;
+
+    // Fees that reach the vault by a plain transfer are picked up by record_fee
+    bench
+        .send(system_instruction::transfer(
+            &authority,
+            &fee_vault,
+            1_000_000_000,
+        ))
+        .await;
+    bench
+        .run(
+            "record_fee",
+            ix(
+                usdcball::instruction::RecordFee {
+                    amount: 1_000_000_000,
+                }
+                .data(),
+                usdcball::accounts::RecordFee {
+                    treasury,
+                    fee_counter,
+                    fee_vault,
+                    sol_usd_oracle: None,
+                }
+                .to_account_metas(None),
+            ),
+        )
+        .await;
+
+    bench
+        .run(
+            "sync_fee_counter",
+            ix(
+                usdcball::instruction::SyncFeeCounter {}.data(),
+                usdcball::accounts::SyncFeeCounter {
+                    treasury,
+                    fee_counter,
+                }
+                .to_account_metas(None),
+            ),
+        )
+        .await;
+
+    bench
+        .run(
+            "record_usdc_conversion",
+            ix(
+                usdcball::instruction::RecordUsdcConversion {
+                    usdc_amount: TOKEN_ACCOUNT_BALANCE,
+                }
+                .data(),
+                usdcball::accounts::RecordConversion {
+                    treasury,
+                    usdc_usd_oracle: None,
+                    authority,
+                    authority_activity: None,
+                }
+                .to_account_metas(None),
+            ),
+        )
+        .await;
+
+    bench
+        .run(
+            "update_config",
+            ix(
+                usdcball::instruction::UpdateConfig {
+                    max_usdc_per_cycle: None,
+                    cooldown_seconds: Some(0),
+                    slippage_bps: Some(100),
+                    keeper_reward_bps: None,
+                    keeper_reward_cap_usdc: None,
+                }
+                .data(),
+                usdcball::accounts::UpdateConfig {
+                    treasury,
+                    authority,
+                    authority_activity: None,
+                }
+                .to_account_metas(None),
+            ),
+        )
+        .await;
+
+    bench
+        .run(
+            "set_instruction_enabled",
+            ix(
+                usdcball::instruction::SetInstructionEnabled {
+                    instruction: usdcball::GatedInstruction::RecordFee,
+                    enabled: true,
+                }
+                .data(),
+                usdcball::accounts::UpdateConfig {
+                    treasury,
+                    authority,
+                    authority_activity: None,
+                }
+                .to_account_metas(None),
+            ),
+        )
+        .await;
+
+    let (quote_board, _) = Pubkey::find_program_address(&[b"quote_board"], &usdcball::ID);
+    bench
+        .run(
+            "open_quote_board",
+            ix(
+                usdcball::instruction::OpenQuoteBoard {}.data(),
+                usdcball::accounts::OpenQuoteBoard {
+                    quote_board,
+                    treasury,
+                    token_mint: bench.token_mint,
+                    payer: authority,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+            ),
+        )
+        .await;
+
+    // execute_buyback swaps through a venue program the bench does not load, so it
+    // is left out of the CU measurements.
+    let (pool_registry, _) = Pubkey::find_program_address(&[b"pool_registry"], &usdcball::ID);
+    bench
+        .run(
+            "initialize_pool_registry",
+            ix(
+                usdcball::instruction::InitializePoolRegistry {}.data(),
+                usdcball::accounts::InitializePoolRegistry {
+                    treasury,
+                    pool_registry,
+                    authority,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+            ),
+        )
+        .await;
+
+    bench
+        .run(
+            "register_pool",
+            ix(
+                usdcball::instruction::RegisterPool {
+                    venue: usdcball::Venue::Raydium,
+                    usdc_vault: bench.pool_usdc,
+                    token_vault: bench.pool_token,
+                }
+                .data(),
+                usdcball::accounts::UpdatePoolRegistry {
+                    treasury,
+                    pool_registry,
+                    authority,
+                }
+                .to_account_metas(None),
+            ),
+        )
+        .await;
+
+    bench
+        .run(
+            "set_pool_limits",
+            ix(
+                usdcball::instruction::SetPoolLimits {
+                    usdc_vault: bench.pool_usdc,
+                    token_vault: bench.pool_token,
+                    slippage_bps: 300,
+                    max_usdc_amount: TOKEN_ACCOUNT_BALANCE,
+                }
+                .data(),
+                usdcball::accounts::UpdatePoolRegistry {
+                    treasury,
+                    pool_registry,
+                    authority,
+                }
+                .to_account_metas(None),
+            ),
+        )
+        .await;
+
+    let (operator_stats, _) =
+        Pubkey::find_program_address(&[b"operator", authority.as_ref()], &usdcball::ID);
+    bench
+        .run(
+            "register_operator",
+            ix(
+                usdcball::instruction::RegisterOperator {
+                    operator: authority,
+                }
+                .data(),
+                usdcball::accounts::RegisterOperator {
+                    treasury,
+                    operator_stats,
+                    authority,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+            ),
+        )
+        .await;
+
+    let (lp_position, _) = Pubkey::find_program_address(
+        &[b"lp_position", bench.pool_usdc.as_ref(), bench.pool_token.as_ref()],
+        &usdcball::ID,
+    );
+    bench
+        .run(
+            "open_lp_position",
+            ix(
+                usdcball::instruction::OpenLpPosition {}.data(),
+                usdcball::accounts::OpenLpPosition {
+                    treasury,
+                    lp_position,
+                    pool_usdc: bench.pool_usdc,
+                    pool_token: bench.pool_token,
+                    pool_registry,
+                    lp_mint,
+                    authority,
+                    token_program: spl_token::ID,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+            ),
+        )
+        .await;
+
+    bench
+        .run(
+            "execute_burn",
+            ix(
+                usdcball::instruction::ExecuteBurn { amount: 1_000_000 }.data(),
+                usdcball::accounts::ExecuteBurn {
+                    treasury,
+                    token_mint: bench.token_mint,
+                    treasury_token: bench.treasury_token,
+                    authority,
+                    authority_activity: None,
+                    token_program: spl_token_2022::ID,
+                }
+                .to_account_metas(None),
+            ),
+        )
+        .await;
+
+    bench
+        .run(
+            "emergency_pause",
+            ix(
+                usdcball::instruction::EmergencyPause {}.data(),
+                usdcball::accounts::PauseTreasury {
+                    treasury,
+                    pauser: authority,
+                    pauser_activity: None,
+                }
+                .to_account_metas(None),
+            ),
+        )
+        .await;
+
+    bench
+        .run(
+            "resume",
+            ix(
+                usdcball::instruction::Resume {}.data(),
+                usdcball::accounts::EmergencyAction {
+                    treasury,
+                    authority,
+                    authority_activity: None,
+                }
+                .to_account_metas(None),
+            ),
+        )
+        .await;
+
+    let mut over_budget = vec![];
+    println!("{:<28} {:>10} {:>10}", "instruction", "units", "budget");
+    for (name, units) in &bench.results {
+        let budget = budget_for(name).unwrap_or_else(|| panic!("no CU budget for {name}"));
+        println!("{name:<28} {units:>10} {budget:>10}");
+        if *units > budget {
+            over_budget.push(format!("{name}: {units} > {budget}"));
+        }
+    }
+    assert!(
+        over_budget.is_empty(),
+        "instructions over CU budget: {over_budget:?}"
+    );
+}
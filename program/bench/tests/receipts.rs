@@ -0,0 +1,258 @@
+//! Sweeps an operation receipt holding USDCBALL under Token-2022 against the
+//! compiled program.
+//!
+//! The mint carries a transfer fee, so the sweep has to go through
+//! `transfer_checked` and harvest the fee withheld on the receipt when the venue
+//! delivered before the receipt can close. Like the CU benchmark it requires
+//! `target/deploy/usdcball.so` and fails without it, so it is ignored by default;
+//! `anchor run bench` runs it.
+
+use anchor_lang::{
+    solana_program::{instruction::Instruction, program_pack::Pack, system_program},
+    AccountSerialize, InstructionData, Space, ToAccountMetas,
+};
+use anchor_spl::{
+    token::spl_token,
+    token_2022::spl_token_2022::{
+        self,
+        extension::{
+            transfer_fee::{TransferFee, TransferFeeAmount, TransferFeeConfig},
+            BaseStateWithExtensions, BaseStateWithExtensionsMut, ExtensionType,
+            StateWithExtensions, StateWithExtensionsMut,
+        },
+        state::{Account as TokenAccount, AccountState, Mint},
+    },
+};
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::{account::Account, pubkey::Pubkey, signer::Signer, transaction::Transaction};
+use std::path::PathBuf;
+use usdcball::{OperationKind, PendingOperation, Venue};
+
+const DELIVERED: u64 = 1_000_000;
+/// Fee the mint withheld on the receipt when the venue delivered
+const WITHHELD_ON_DELIVERY: u64 = 10_101;
+const FEE_BPS: u16 = 100;
+
+fn sbf_out_dir() -> PathBuf {
+    std::env::var("SBF_OUT_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../target/deploy"))
+}
+
+fn account_of(owner: Pubkey, data: Vec<u8>) -> Account {
+    Account {
+        lamports: 10_000_000,
+        data,
+        owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn usdc_mint() -> Account {
+    let mut data = vec![0u8; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        decimals: 6,
+        is_initialized: true,
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+    account_of(spl_token::ID, data)
+}
+
+/// Token-2022 mint charging `FEE_BPS` on every transfer
+fn fee_mint() -> Account {
+    let len = ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferFeeConfig])
+        .unwrap();
+    let mut data = vec![0u8; len];
+    let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+    let fee = TransferFee {
+        epoch: 0.into(),
+        maximum_fee: u64::MAX.into(),
+        transfer_fee_basis_points: FEE_BPS.into(),
+    };
+    let config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+    config.older_transfer_fee = fee;
+    config.newer_transfer_fee = fee;
+    state.base = Mint {
+        decimals: 6,
+        is_initialized: true,
+        supply: DELIVERED + WITHHELD_ON_DELIVERY,
+        ..Default::default()
+    };
+    state.pack_base();
+    state.init_account_type().unwrap();
+    account_of(spl_token_2022::ID, data)
+}
+
+/// Token-2022 account of the fee mint, with `withheld` fees held back on it
+fn fee_token_account(mint: Pubkey, owner: Pubkey, amount: u64, withheld: u64) -> Account {
+    let len = ExtensionType::try_calculate_account_len::<TokenAccount>(&[
+        ExtensionType::TransferFeeAmount,
+    ])
+    .unwrap();
+    let mut data = vec![0u8; len];
+    let mut state =
+        StateWithExtensionsMut::<TokenAccount>::unpack_uninitialized(&mut data).unwrap();
+    state
+        .init_extension::<TransferFeeAmount>(true)
+        .unwrap()
+        .withheld_amount = withheld.into();
+    state.base = TokenAccount {
+        mint,
+        owner,
+        amount,
+        state: AccountState::Initialized,
+        ..Default::default()
+    };
+    state.pack_base();
+    state.init_account_type().unwrap();
+    account_of(spl_token_2022::ID, data)
+}
+
+/// A released buyback whose receipt is due to settle
+fn released_operation(initiator: Pubkey, receipt: Pubkey, bump: u8) -> Account {
+    let operation = PendingOperation {
+        index: 0,
+        kind: OperationKind::Buyback,
+        usdc_amount: 500_000,
+        initiator,
+        opened_slot: 0,
+        deadline_slot: u64::MAX / 2,
+        venue: Venue::Jupiter,
+        receipt,
+        min_output: DELIVERED,
+        released: true,
+        funding_vault: Pubkey::default(),
+        opened_at: 0,
+        bump,
+    };
+    let mut data = Vec::with_capacity(8 + PendingOperation::INIT_SPACE);
+    operation.try_serialize(&mut data).unwrap();
+    data.resize(8 + PendingOperation::INIT_SPACE, 0);
+    account_of(usdcball::ID, data)
+}
+
+async fn send(ctx: &mut ProgramTestContext, name: &str, ix: Instruction) {
+    let blockhash = ctx.banks_client.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        blockhash,
+    );
+    if let Err(err) = ctx.banks_client.process_transaction(tx).await {
+        panic!("{name} failed: {err:?}");
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/usdcball.so; run with `anchor run bench`"]
+async fn settle_receipt_sweeps_a_token_2022_receipt() {
+    let out_dir = sbf_out_dir();
+    assert!(
+        out_dir.join("usdcball.so").exists(),
+        "usdcball.so not found in {}; run `anchor build` first",
+        out_dir.display()
+    );
+    std::env::set_var("SBF_OUT_DIR", &out_dir);
+
+    let mut pt = ProgramTest::default();
+    pt.prefer_bpf(true);
+    pt.add_program("usdcball", usdcball::ID, None);
+
+    let (treasury, _) = Pubkey::find_program_address(&[b"treasury"], &usdcball::ID);
+    let (operation, operation_bump) =
+        Pubkey::find_program_address(&[b"operation", &0u64.to_le_bytes()], &usdcball::ID);
+    let (receipt, _) =
+        Pubkey::find_program_address(&[b"operation_receipt", operation.as_ref()], &usdcball::ID);
+    let usdc = Pubkey::new_unique();
+    let token_mint = Pubkey::new_unique();
+    let destination = Pubkey::new_unique();
+    pt.add_account(usdc, usdc_mint());
+    pt.add_account(token_mint, fee_mint());
+    pt.add_account(
+        receipt,
+        fee_token_account(token_mint, treasury, DELIVERED, WITHHELD_ON_DELIVERY),
+    );
+    pt.add_account(destination, fee_token_account(token_mint, treasury, 0, 0));
+
+    let mut ctx = pt.start_with_context().await;
+    let payer = ctx.payer.pubkey();
+    ctx.set_account(
+        &operation,
+        &released_operation(payer, receipt, operation_bump).into(),
+    );
+
+    let initialize = Instruction {
+        program_id: usdcball::ID,
+        accounts: usdcball::accounts::Initialize {
+            treasury,
+            token_mint,
+            usdc_mint: usdc,
+            authority: payer,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: usdcball::instruction::Initialize {
+            buyback_allocation_bps: 5000,
+            liquidity_allocation_bps: 3000,
+            reserve_allocation_bps: 2000,
+            max_usdc_per_cycle: DELIVERED,
+            cooldown_seconds: 0,
+            slippage_bps: 200,
+            expected_token_decimals: 6,
+            expected_token_mint_authority: None,
+            launch_epochs: 0,
+        }
+        .data(),
+    };
+    send(&mut ctx, "initialize", initialize).await;
+
+    let settle = Instruction {
+        program_id: usdcball::ID,
+        accounts: usdcball::accounts::SettleReceipt {
+            treasury,
+            operation,
+            receipt,
+            receipt_mint: token_mint,
+            destination,
+            initiator: payer,
+            caller: payer,
+            token_program: spl_token_2022::ID,
+        }
+        .to_account_metas(None),
+        data: usdcball::instruction::SettleReceipt {}.data(),
+    };
+    send(&mut ctx, "settle_receipt", settle).await;
+
+    let banks = &mut ctx.banks_client;
+    // The sweep itself pays the fee, which stays withheld on the destination
+    let fee = DELIVERED * u64::from(FEE_BPS) / 10_000;
+    let data = banks.get_account(destination).await.unwrap().unwrap().data;
+    let swept = StateWithExtensions::<TokenAccount>::unpack(&data).unwrap();
+    assert_eq!(swept.base.amount, DELIVERED - fee);
+    assert_eq!(
+        u64::from(
+            swept
+                .get_extension::<TransferFeeAmount>()
+                .unwrap()
+                .withheld_amount
+        ),
+        fee
+    );
+
+    // The fee withheld on delivery was harvested to the mint so the receipt could close
+    let data = banks.get_account(token_mint).await.unwrap().unwrap().data;
+    let mint = StateWithExtensions::<Mint>::unpack(&data).unwrap();
+    assert_eq!(
+        u64::from(
+            mint.get_extension::<TransferFeeConfig>()
+                .unwrap()
+                .withheld_amount
+        ),
+        WITHHELD_ON_DELIVERY
+    );
+    assert!(banks.get_account(receipt).await.unwrap().is_none());
+    assert!(banks.get_account(operation).await.unwrap().is_none());
+}
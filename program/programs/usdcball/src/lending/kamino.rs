@@ -0,0 +1,154 @@
+//! Kamino Lend support. Idle reserve USDC is deposited into a Kamino reserve in
+//! exchange for its collateral token (kTokens), which the treasury later redeems
+//! for USDC plus accrued interest. Kamino requires the reserve to have been
+//! refreshed earlier in the same transaction.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+};
+
+use super::LendingAdapter;
+use crate::{Treasury, UsdcSpend};
+
+declare_id!("KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD");
+
+/// Anchor discriminator of `deposit_reserve_liquidity`
+const DEPOSIT_RESERVE_LIQUIDITY: [u8; 8] = [169, 201, 30, 126, 6, 205, 102, 68];
+/// Anchor discriminator of `redeem_reserve_collateral`
+const REDEEM_RESERVE_COLLATERAL: [u8; 8] = [234, 117, 181, 125, 185, 142, 220, 29];
+
+pub struct Kamino;
+
+impl LendingAdapter for Kamino {
+    fn program_id(&self) -> Pubkey {
+        ID
+    }
+
+    fn deposit_spend(&self) -> UsdcSpend {
+        UsdcSpend::KaminoDeposit
+    }
+
+    fn market(&self, treasury: &Treasury) -> Pubkey {
+        treasury.kamino_reserve
+    }
+
+    fn deposited_usdc(&self, treasury: &Treasury) -> u64 {
+        treasury.kamino_deposited_usdc
+    }
+
+    fn has_position(&self, treasury: &Treasury) -> bool {
+        treasury.kamino_collateral_amount > 0
+    }
+}
+
+/// Accounts of a Kamino reserve deposit or redemption. `owner` is the treasury PDA.
+pub struct ReserveAccounts<'info> {
+    pub owner: AccountInfo<'info>,
+    pub reserve: AccountInfo<'info>,
+    pub lending_market: AccountInfo<'info>,
+    pub lending_market_authority: AccountInfo<'info>,
+    pub liquidity_mint: AccountInfo<'info>,
+    pub liquidity_supply: AccountInfo<'info>,
+    pub collateral_mint: AccountInfo<'info>,
+    pub user_liquidity: AccountInfo<'info>,
+    pub user_collateral: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub instructions: AccountInfo<'info>,
+    pub program: AccountInfo<'info>,
+}
+
+impl<'info> ReserveAccounts<'info> {
+    fn invoke(
+        &self,
+        metas: Vec<AccountMeta>,
+        discriminator: [u8; 8],
+        amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let mut data = discriminator.to_vec();
+        data.extend_from_slice(&amount.to_le_bytes());
+        let ix = Instruction {
+            program_id: ID,
+            accounts: metas,
+            data,
+        };
+        invoke_signed(
+            &ix,
+            &[
+                self.owner.clone(),
+                self.reserve.clone(),
+                self.lending_market.clone(),
+                self.lending_market_authority.clone(),
+                self.liquidity_mint.clone(),
+                self.liquidity_supply.clone(),
+                self.collateral_mint.clone(),
+                self.user_liquidity.clone(),
+                self.user_collateral.clone(),
+                self.token_program.clone(),
+                self.instructions.clone(),
+                self.program.clone(),
+            ],
+            signer_seeds,
+        )?;
+        Ok(())
+    }
+}
+
+/// Deposit `liquidity_amount` USDC from `user_liquidity` for kTokens into `user_collateral`
+pub fn deposit_reserve_liquidity(
+    accounts: &ReserveAccounts,
+    liquidity_amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let metas = vec![
+        AccountMeta::new_readonly(accounts.owner.key(), true),
+        AccountMeta::new(accounts.reserve.key(), false),
+        AccountMeta::new_readonly(accounts.lending_market.key(), false),
+        AccountMeta::new_readonly(accounts.lending_market_authority.key(), false),
+        AccountMeta::new_readonly(accounts.liquidity_mint.key(), false),
+        AccountMeta::new(accounts.liquidity_supply.key(), false),
+        AccountMeta::new(accounts.collateral_mint.key(), false),
+        AccountMeta::new(accounts.user_liquidity.key(), false),
+        AccountMeta::new(accounts.user_collateral.key(), false),
+        // Collateral and liquidity token program: both SPL Token for USDC
+        AccountMeta::new_readonly(accounts.token_program.key(), false),
+        AccountMeta::new_readonly(accounts.token_program.key(), false),
+        AccountMeta::new_readonly(accounts.instructions.key(), false),
+    ];
+    accounts.invoke(
+        metas,
+        DEPOSIT_RESERVE_LIQUIDITY,
+        liquidity_amount,
+        signer_seeds,
+    )
+}
+
+/// Redeem `collateral_amount` kTokens from `user_collateral` for USDC into `user_liquidity`
+pub fn redeem_reserve_collateral(
+    accounts: &ReserveAccounts,
+    collateral_amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let metas = vec![
+        AccountMeta::new_readonly(accounts.owner.key(), true),
+        AccountMeta::new_readonly(accounts.lending_market.key(), false),
+        AccountMeta::new(accounts.reserve.key(), false),
+        AccountMeta::new_readonly(accounts.lending_market_authority.key(), false),
+        AccountMeta::new_readonly(accounts.liquidity_mint.key(), false),
+        AccountMeta::new(accounts.collateral_mint.key(), false),
+        AccountMeta::new(accounts.liquidity_supply.key(), false),
+        AccountMeta::new(accounts.user_collateral.key(), false),
+        AccountMeta::new(accounts.user_liquidity.key(), false),
+        AccountMeta::new_readonly(accounts.token_program.key(), false),
+        AccountMeta::new_readonly(accounts.token_program.key(), false),
+        AccountMeta::new_readonly(accounts.instructions.key(), false),
+    ];
+    accounts.invoke(
+        metas,
+        REDEEM_RESERVE_COLLATERAL,
+        collateral_amount,
+        signer_seeds,
+    )
+}
@@ -0,0 +1,179 @@
+//! MarginFi v2 support. The treasury PDA owns a MarginFi account and lends idle
+//! reserve USDC into one bank of its group. Positions are asset shares inside the
+//! MarginFi account rather than tokens, so the treasury tracks the principal it
+//! deposited and treats anything withdrawn above it as interest.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+};
+
+use super::LendingAdapter;
+use crate::{Treasury, UsdcSpend};
+
+declare_id!("MFv2hWf31Z9kbCa1snEPYctwafyhdvnV7FZnsebVacA");
+
+/// Anchor discriminator of `marginfi_account_initialize`
+const MARGINFI_ACCOUNT_INITIALIZE: [u8; 8] = [43, 78, 61, 255, 148, 52, 249, 154];
+/// Anchor discriminator of `lending_account_deposit`
+const LENDING_ACCOUNT_DEPOSIT: [u8; 8] = [171, 94, 235, 103, 82, 64, 212, 140];
+/// Anchor discriminator of `lending_account_withdraw`
+const LENDING_ACCOUNT_WITHDRAW: [u8; 8] = [36, 72, 74, 19, 210, 210, 192, 192];
+
+pub struct MarginFi;
+
+impl LendingAdapter for MarginFi {
+    fn program_id(&self) -> Pubkey {
+        ID
+    }
+
+    fn deposit_spend(&self) -> UsdcSpend {
+        UsdcSpend::MarginFiDeposit
+    }
+
+    fn market(&self, treasury: &Treasury) -> Pubkey {
+        treasury.marginfi_bank
+    }
+
+    fn deposited_usdc(&self, treasury: &Treasury) -> u64 {
+        treasury.marginfi_deposited_usdc
+    }
+}
+
+/// Accounts of a MarginFi deposit or withdrawal. `authority` is the treasury PDA.
+pub struct BankAccounts<'info> {
+    pub group: AccountInfo<'info>,
+    pub marginfi_account: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+    pub bank: AccountInfo<'info>,
+    pub user_liquidity: AccountInfo<'info>,
+    pub liquidity_vault: AccountInfo<'info>,
+    pub liquidity_vault_authority: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub program: AccountInfo<'info>,
+}
+
+fn invoke(
+    accounts: Vec<AccountMeta>,
+    data: Vec<u8>,
+    account_infos: &[AccountInfo],
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let ix = Instruction {
+        program_id: ID,
+        accounts,
+        data,
+    };
+    invoke_signed(&ix, account_infos, signer_seeds)?;
+    Ok(())
+}
+
+/// Create `marginfi_account` in `group` with the treasury as its authority
+pub fn initialize_account<'info>(
+    group: AccountInfo<'info>,
+    marginfi_account: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    fee_payer: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    program: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let metas = vec![
+        AccountMeta::new_readonly(group.key(), false),
+        AccountMeta::new(marginfi_account.key(), true),
+        AccountMeta::new_readonly(authority.key(), true),
+        AccountMeta::new(fee_payer.key(), true),
+        AccountMeta::new_readonly(system_program.key(), false),
+    ];
+    invoke(
+        metas,
+        MARGINFI_ACCOUNT_INITIALIZE.to_vec(),
+        &[
+            group,
+            marginfi_account,
+            authority,
+            fee_payer,
+            system_program,
+            program,
+        ],
+        signer_seeds,
+    )
+}
+
+/// Deposit `amount` USDC from `user_liquidity` into the bank
+pub fn deposit(accounts: &BankAccounts, amount: u64, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+    let metas = vec![
+        AccountMeta::new_readonly(accounts.group.key(), false),
+        AccountMeta::new(accounts.marginfi_account.key(), false),
+        AccountMeta::new_readonly(accounts.authority.key(), true),
+        AccountMeta::new(accounts.bank.key(), false),
+        AccountMeta::new(accounts.user_liquidity.key(), false),
+        AccountMeta::new(accounts.liquidity_vault.key(), false),
+        AccountMeta::new_readonly(accounts.token_program.key(), false),
+    ];
+    let mut data = LENDING_ACCOUNT_DEPOSIT.to_vec();
+    data.extend_from_slice(&amount.to_le_bytes());
+    // deposit_up_to_limit: None
+    data.push(0);
+    invoke(
+        metas,
+        data,
+        &[
+            accounts.group.clone(),
+            accounts.marginfi_account.clone(),
+            accounts.authority.clone(),
+            accounts.bank.clone(),
+            accounts.user_liquidity.clone(),
+            accounts.liquidity_vault.clone(),
+            accounts.token_program.clone(),
+            accounts.program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// Withdraw `amount` USDC (or the whole position, when `withdraw_all`) into
+/// `user_liquidity`. `health_accounts` are the bank and oracle accounts MarginFi's
+/// health check expects for the account's remaining balances.
+pub fn withdraw<'info>(
+    accounts: &BankAccounts<'info>,
+    amount: u64,
+    withdraw_all: bool,
+    health_accounts: &[AccountInfo<'info>],
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let mut metas = vec![
+        AccountMeta::new_readonly(accounts.group.key(), false),
+        AccountMeta::new(accounts.marginfi_account.key(), false),
+        AccountMeta::new_readonly(accounts.authority.key(), true),
+        AccountMeta::new(accounts.bank.key(), false),
+        AccountMeta::new(accounts.user_liquidity.key(), false),
+        AccountMeta::new_readonly(accounts.liquidity_vault_authority.key(), false),
+        AccountMeta::new(accounts.liquidity_vault.key(), false),
+        AccountMeta::new_readonly(accounts.token_program.key(), false),
+    ];
+    metas.extend(health_accounts.iter().map(|a| AccountMeta {
+        pubkey: a.key(),
+        is_signer: false,
+        is_writable: a.is_writable,
+    }));
+    let mut data = LENDING_ACCOUNT_WITHDRAW.to_vec();
+    data.extend_from_slice(&amount.to_le_bytes());
+    // withdraw_all: Some(bool)
+    data.extend_from_slice(&[1, withdraw_all as u8]);
+
+    let mut account_infos = vec![
+        accounts.group.clone(),
+        accounts.marginfi_account.clone(),
+        accounts.authority.clone(),
+        accounts.bank.clone(),
+        accounts.user_liquidity.clone(),
+        accounts.liquidity_vault_authority.clone(),
+        accounts.liquidity_vault.clone(),
+        accounts.token_program.clone(),
+        accounts.program.clone(),
+    ];
+    account_infos.extend_from_slice(health_accounts);
+    invoke(metas, data, &account_infos, signer_seeds)
+}
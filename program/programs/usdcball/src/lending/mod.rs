@@ -0,0 +1,92 @@
+//! Lending venue dispatch. Each money market lives in its own module and
+//! implements [`LendingAdapter`]; adding a venue means adding a module, a
+//! `LendingVenue` variant and the treasury fields tracking its position.
+
+use anchor_lang::prelude::*;
+
+use crate::{ErrorCode, LendingVenue, Treasury, UsdcSpend};
+
+pub mod kamino;
+pub mod marginfi;
+pub mod solend;
+
+/// What the program needs to know about a venue to account for reserve USDC lent to it.
+pub trait LendingAdapter {
+    fn program_id(&self) -> Pubkey;
+
+    /// Spend deposits into this venue are budgeted as
+    fn deposit_spend(&self) -> UsdcSpend;
+
+    /// Reserve or bank the treasury lends into; default while unset
+    fn market(&self, treasury: &Treasury) -> Pubkey;
+
+    /// Reserve USDC deposited into this venue and not yet withdrawn, excluding interest
+    fn deposited_usdc(&self, treasury: &Treasury) -> u64;
+
+    /// Whether the treasury still holds a position in this venue
+    fn has_position(&self, treasury: &Treasury) -> bool {
+        self.deposited_usdc(treasury) > 0
+    }
+}
+
+impl LendingVenue {
+    /// Every venue reserve USDC can be lent to
+    pub const ALL: [LendingVenue; 3] = [
+        LendingVenue::Kamino,
+        LendingVenue::MarginFi,
+        LendingVenue::Solend,
+    ];
+
+    pub fn adapter(self) -> Option<&'static dyn LendingAdapter> {
+        match self {
+            LendingVenue::None => None,
+            LendingVenue::Kamino => Some(&kamino::Kamino),
+            LendingVenue::MarginFi => Some(&marginfi::MarginFi),
+            LendingVenue::Solend => Some(&solend::Solend),
+        }
+    }
+}
+
+/// Check `venue` is the active lending venue with a market configured, and
+/// authorize a deposit of `usdc_amount` against the reserve budget.
+pub fn authorize_deposit(
+    treasury: &mut Treasury,
+    venue: LendingVenue,
+    usdc_amount: u64,
+    now: i64,
+) -> Result<&'static dyn LendingAdapter> {
+    require!(!treasury.fully_paused(), ErrorCode::Paused);
+    require!(
+        treasury.lending_venue == venue,
+        ErrorCode::LendingVenueInactive
+    );
+    let adapter = venue.adapter().ok_or(ErrorCode::LendingVenueInactive)?;
+    require!(
+        adapter.market(treasury) != Pubkey::default(),
+        ErrorCode::LendingVenueInactive
+    );
+    require!(usdc_amount > 0, ErrorCode::InvalidAmount);
+    treasury.authorize_usdc_spend(adapter.deposit_spend(), usdc_amount, now)?;
+    Ok(adapter)
+}
+
+/// Deposited principal backing `collateral_amount` out of `collateral_total`
+/// collateral tokens. The last tokens take whatever principal rounding left behind.
+pub fn principal_share(deposited_usdc: u64, collateral_total: u64, collateral_amount: u64) -> u64 {
+    if collateral_amount >= collateral_total {
+        return deposited_usdc;
+    }
+    (deposited_usdc as u128 * collateral_amount as u128 / collateral_total as u128) as u64
+}
+
+/// Whether `remaining_collateral`, priced at the rate of a redemption that paid
+/// `received_usdc` for `redeemed_collateral`, is still worth `deposited_usdc`
+pub fn covers_principal(
+    remaining_collateral: u64,
+    received_usdc: u64,
+    redeemed_collateral: u64,
+    deposited_usdc: u64,
+) -> bool {
+    remaining_collateral as u128 * received_usdc as u128
+        >= deposited_usdc as u128 * redeemed_collateral as u128
+}
@@ -0,0 +1,144 @@
+//! Solend main pool support. Idle reserve USDC is deposited into a Solend reserve
+//! in exchange for its collateral token (cTokens), which the treasury later
+//! redeems for USDC plus accrued interest. Solend requires the reserve to have
+//! been refreshed earlier in the same transaction.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+};
+
+use super::LendingAdapter;
+use crate::{Treasury, UsdcSpend};
+
+declare_id!("So1endDq2YkqhipRh3WViPa8hdiSpxWy6z3Z6tMCpAo");
+
+/// Tag of `DepositReserveLiquidity`
+const DEPOSIT_RESERVE_LIQUIDITY: u8 = 4;
+/// Tag of `RedeemReserveCollateral`
+const REDEEM_RESERVE_COLLATERAL: u8 = 5;
+
+pub struct Solend;
+
+impl LendingAdapter for Solend {
+    fn program_id(&self) -> Pubkey {
+        ID
+    }
+
+    fn deposit_spend(&self) -> UsdcSpend {
+        UsdcSpend::SolendDeposit
+    }
+
+    fn market(&self, treasury: &Treasury) -> Pubkey {
+        treasury.solend_reserve
+    }
+
+    fn deposited_usdc(&self, treasury: &Treasury) -> u64 {
+        treasury.solend_deposited_usdc
+    }
+
+    fn has_position(&self, treasury: &Treasury) -> bool {
+        treasury.solend_collateral_amount > 0
+    }
+}
+
+/// Accounts of a Solend reserve deposit or redemption. `transfer_authority` is
+/// the treasury PDA.
+pub struct ReserveAccounts<'info> {
+    pub user_liquidity: AccountInfo<'info>,
+    pub user_collateral: AccountInfo<'info>,
+    pub reserve: AccountInfo<'info>,
+    pub liquidity_supply: AccountInfo<'info>,
+    pub collateral_mint: AccountInfo<'info>,
+    pub lending_market: AccountInfo<'info>,
+    pub lending_market_authority: AccountInfo<'info>,
+    pub transfer_authority: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub program: AccountInfo<'info>,
+}
+
+impl<'info> ReserveAccounts<'info> {
+    fn invoke(
+        &self,
+        metas: Vec<AccountMeta>,
+        tag: u8,
+        amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let mut data = vec![tag];
+        data.extend_from_slice(&amount.to_le_bytes());
+        let ix = Instruction {
+            program_id: ID,
+            accounts: metas,
+            data,
+        };
+        invoke_signed(
+            &ix,
+            &[
+                self.user_liquidity.clone(),
+                self.user_collateral.clone(),
+                self.reserve.clone(),
+                self.liquidity_supply.clone(),
+                self.collateral_mint.clone(),
+                self.lending_market.clone(),
+                self.lending_market_authority.clone(),
+                self.transfer_authority.clone(),
+                self.token_program.clone(),
+                self.program.clone(),
+            ],
+            signer_seeds,
+        )?;
+        Ok(())
+    }
+}
+
+/// Deposit `liquidity_amount` USDC from `user_liquidity` for cTokens into `user_collateral`
+pub fn deposit_reserve_liquidity(
+    accounts: &ReserveAccounts,
+    liquidity_amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let metas = vec![
+        AccountMeta::new(accounts.user_liquidity.key(), false),
+        AccountMeta::new(accounts.user_collateral.key(), false),
+        AccountMeta::new(accounts.reserve.key(), false),
+        AccountMeta::new(accounts.liquidity_supply.key(), false),
+        AccountMeta::new(accounts.collateral_mint.key(), false),
+        AccountMeta::new_readonly(accounts.lending_market.key(), false),
+        AccountMeta::new_readonly(accounts.lending_market_authority.key(), false),
+        AccountMeta::new_readonly(accounts.transfer_authority.key(), true),
+        AccountMeta::new_readonly(accounts.token_program.key(), false),
+    ];
+    accounts.invoke(
+        metas,
+        DEPOSIT_RESERVE_LIQUIDITY,
+        liquidity_amount,
+        signer_seeds,
+    )
+}
+
+/// Redeem `collateral_amount` cTokens from `user_collateral` for USDC into `user_liquidity`
+pub fn redeem_reserve_collateral(
+    accounts: &ReserveAccounts,
+    collateral_amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let metas = vec![
+        AccountMeta::new(accounts.user_collateral.key(), false),
+        AccountMeta::new(accounts.user_liquidity.key(), false),
+        AccountMeta::new(accounts.reserve.key(), false),
+        AccountMeta::new(accounts.collateral_mint.key(), false),
+        AccountMeta::new(accounts.liquidity_supply.key(), false),
+        AccountMeta::new_readonly(accounts.lending_market.key(), false),
+        AccountMeta::new_readonly(accounts.lending_market_authority.key(), false),
+        AccountMeta::new_readonly(accounts.transfer_authority.key(), true),
+        AccountMeta::new_readonly(accounts.token_program.key(), false),
+    ];
+    accounts.invoke(
+        metas,
+        REDEEM_RESERVE_COLLATERAL,
+        collateral_amount,
+        signer_seeds,
+    )
+}
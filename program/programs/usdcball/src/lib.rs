@@ -1,5 +1,146 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_lang::solana_program::keccak;
+use anchor_lang::Discriminator;
+use anchor_spl::associated_token::{get_associated_token_address_with_program_id, AssociatedToken};
+use anchor_spl::token::spl_token;
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    transfer_fee::{TransferFeeAmount, TransferFeeConfig},
+    transfer_hook, BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+};
+use anchor_spl::token_2022::spl_token_2022::instruction::AuthorityType;
+use anchor_spl::token_2022::Token2022;
+use anchor_spl::token_2022_extensions::transfer_fee::{
+    self, HarvestWithheldTokensToMint, WithdrawWithheldTokensFromMint,
+};
+use anchor_spl::token_interface::{self, CloseAccount, Transfer};
+use lending::{kamino, marginfi, solend};
+
+pub mod lending;
+pub mod merkle;
+pub mod meteora_dlmm;
+pub mod oracle;
+pub mod orca_whirlpool;
+pub mod raydium_clmm;
+pub mod realms;
+pub mod squads;
+pub mod strategy;
+pub mod venues;
+
+/// Default number of slots an escrowed operation has to settle before it can be refunded
+pub const DEFAULT_SETTLEMENT_WINDOW_SLOTS: u64 = 150;
+
+/// Shortest gap between two `report_skip` calls of one operator, for treasuries
+/// whose cooldown is shorter
+pub const MIN_SKIP_REPORT_INTERVAL_SECONDS: i64 = 60;
+
+/// Default length of a treasury epoch
+pub const DEFAULT_EPOCH_DURATION_SECONDS: i64 = 7 * 24 * 3600;
+
+/// Period over which the tier-1 reserve allowance streams in
+pub const SECONDS_PER_MONTH: i64 = 30 * 24 * 3600;
+
+/// Default wait between queueing a reserve withdrawal and executing it
+pub const DEFAULT_RESERVE_WITHDRAWAL_DELAY_SECONDS: i64 = 48 * 3600;
+
+/// Delay between adding an address book entry and paying out to it
+pub const ADDRESS_BOOK_TIMELOCK_SECONDS: i64 = 48 * 3600;
+/// Longest address book label, in bytes
+pub const MAX_ADDRESS_LABEL_LEN: usize = 32;
+
+/// Maximum number of holdings in one reserve attestation
+pub const MAX_ATTESTED_HOLDINGS: usize = 16;
+
+/// Delay between proposing and executing a vault authority migration
+pub const VAULT_MIGRATION_TIMELOCK_SECONDS: i64 = 48 * 3600;
+/// Delay between proposing a token migration and swapping or redirecting to the new mint
+pub const TOKEN_MIGRATION_TIMELOCK_SECONDS: i64 = 7 * 24 * 3600;
+
+/// Maximum number of pools in the pool registry
+pub const MAX_REGISTERED_POOLS: usize = 16;
+
+/// Most pools `route_best` compares in one call
+pub const MAX_ROUTE_CANDIDATES: usize = 3;
+
+/// Minimum notice between creating a liquidity mining campaign and its start
+pub const CAMPAIGN_TIMELOCK_SECONDS: i64 = 48 * 3600;
+
+/// Fixed-point scale of `Campaign::acc_reward_per_share`
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000;
+
+/// Holding age at which a holder earns the full `MAX_HOLDER_BOOST_BPS`
+pub const HOLDER_BOOST_FULL_SECONDS: i64 = 180 * 24 * 3600;
+
+/// Largest boost a long-term holder gets over a fresh wallet
+pub const MAX_HOLDER_BOOST_BPS: u16 = 5_000;
+
+/// Days in the rolling window of the global outflow cap
+pub const OUTFLOW_WINDOW_DAYS: usize = 7;
+
+/// Delay before a raised (or removed) global outflow cap takes effect
+pub const OUTFLOW_CAP_TIMELOCK_SECONDS: i64 = 7 * 24 * 3600;
+
+/// Hourly buckets of the rolling buyback + liquidity volume window
+pub const VOLUME_WINDOW_HOURS: usize = 24;
+
+/// Seed of the PDA an integrating program signs `forward_fee` with, derived under
+/// that program's own ID
+pub const FEE_SOURCE_SEED: &[u8] = b"usdcball_fee_source";
+
+/// Price observations kept per pool TWAP
+pub const TWAP_SAMPLES: usize = 16;
+
+/// Maximum entries in one `record_fees_batch` call
+pub const MAX_FEE_BATCH_ENTRIES: usize = 64;
+
+/// Upper bound on the keeper reward for `crank_buyback` (5%)
+pub const MAX_KEEPER_REWARD_BPS: u16 = 500;
+
+/// Entries kept in the operations calendar
+pub const MAX_CALENDAR_ENTRIES: usize = 8;
+
+/// Bounds of the token weight in a liquidity bootstrapping pool
+pub const MIN_LBP_WEIGHT_BPS: u16 = 100;
+pub const MAX_LBP_WEIGHT_BPS: u16 = 9_900;
+
+/// Every `PauseFlag` bit, as set by `emergency_pause`
+pub const PAUSE_ALL: u8 = 0b1111;
+
+/// Offsets (after the discriminator) of the Treasury fields `record_fee` and
+/// `record_transfer_fee` read in place instead of deserializing the account;
+/// pinned by `tests/account_layout.rs`
+pub const TREASURY_TOKEN_MINT_OFFSET: usize = 32;
+pub const TREASURY_TOTAL_SOL_COLLECTED_OFFSET: usize = 120;
+pub const TREASURY_PAUSE_FLAGS_OFFSET: usize = 176;
+pub const TREASURY_INSTRUCTION_ENABLED_OFFSET: usize = 177;
+pub const TREASURY_SOL_USD_ORACLE_OFFSET: usize = 340;
+pub const TREASURY_ORACLE_MAX_AGE_OFFSET: usize = 372;
+
+/// Offsets of Treasury fields in the full-size layout, with every `Option` field
+/// `Some`, by which `migrate_treasury` tells the fields an older account lacks;
+/// pinned by `tests/treasury_migration.rs`
+pub const TREASURY_EXPECTED_MINT_AUTHORITY_OFFSET: usize = 812;
+pub const TREASURY_EXPECTED_FREEZE_AUTHORITY_OFFSET: usize = 845;
+pub const TREASURY_OPERATOR_OFFSET: usize = 902;
+pub const TREASURY_PAUSER_OFFSET: usize = 934;
+pub const TREASURY_RESERVE_WITHDRAWAL_DELAY_OFFSET: usize = 1362;
+
+/// Size of the oldest Treasury layout `migrate_treasury` upgrades, the one first
+/// pinned by `tests/account_layout.rs`. Every layout since appends fields before
+/// `bump` and leaves the rest in place.
+pub const TREASURY_MIN_MIGRATABLE_SIZE: usize = 691;
+
+/// Decimals of the USDC mint
+pub const USDC_DECIMALS: u8 = 6;
+
+/// Per-cycle USDC limit while the treasury is in launch mode (100 USDC)
+pub const LAUNCH_MAX_USDC_PER_CYCLE: u64 = 100_000_000;
+
+/// Tick bounds Raydium CLMM accepts
+pub const CLMM_MIN_TICK: i32 = -443_636;
+pub const CLMM_MAX_TICK: i32 = 443_636;
+
+/// Decimals of native SOL amounts (lamports)
+pub const SOL_DECIMALS: u8 = 9;
 
 declare_id!("USDCbaf11111111111111111111111111111111111");
 
@@ -8,6 +149,7 @@ pub mod usdcball {
     use super::*;
 
     /// Initialize the treasury with configuration parameters
+    #[allow(clippy::too_many_arguments)]
     pub fn initialize(
         ctx: Context<Initialize>,
         buyback_allocation_bps: u16,
@@ -16,6 +158,9 @@ pub mod usdcball {
         max_usdc_per_cycle: u64,
         cooldown_seconds: i64,
         slippage_bps: u16,
+        expected_token_decimals: u8,
+        expected_token_mint_authority: Option<Pubkey>,
+        launch_epochs: u64,
     ) -> Result<()> {
         require!(
             buyback_allocation_bps + liquidity_allocation_bps + reserve_allocation_bps == 10000,
@@ -23,8 +168,24 @@ pub mod usdcball {
         );
         require!(slippage_bps <= 1000, ErrorCode::SlippageTooHigh); // Max 10%
 
+        // Catch a fat-fingered init with the wrong mints before anything depends on them
+        let usdc_mint = &ctx.accounts.usdc_mint;
+        require!(
+            usdc_mint.decimals == USDC_DECIMALS,
+            ErrorCode::InvalidUsdcMint
+        );
+        let token_mint = &ctx.accounts.token_mint;
+        let token_mint_authority: Option<Pubkey> = token_mint.mint_authority.into();
+        require!(
+            token_mint.decimals == expected_token_decimals
+                && token_mint_authority == expected_token_mint_authority,
+            ErrorCode::InvalidTokenMint
+        );
+
         let treasury = &mut ctx.accounts.treasury;
         treasury.authority = ctx.accounts.authority.key();
+        treasury.token_mint = ctx.accounts.token_mint.key();
+        treasury.usdc_mint = ctx.accounts.usdc_mint.key();
         treasury.buyback_allocation_bps = buyback_allocation_bps;
         treasury.liquidity_allocation_bps = liquidity_allocation_bps;
         treasury.reserve_allocation_bps = reserve_allocation_bps;
@@ -36,8 +197,140 @@ pub mod usdcball {
         treasury.total_buybacks_usdc = 0;
         treasury.total_liquidity_usdc = 0;
         treasury.total_tokens_burned = 0;
+        treasury.total_transfer_fees_harvested = 0;
         treasury.last_operation_timestamp = 0;
-        treasury.paused = false;
+        treasury.pause_flags = 0;
+        treasury.instruction_enabled = u64::MAX;
+        treasury.dividend_share_bps = 0;
+        treasury.dividend_epoch = 0;
+        treasury.total_dividends_usdc = 0;
+        treasury.co_signer = Pubkey::default();
+        treasury.cosign_threshold_usdc = 0;
+        treasury.post_resume_delay_seconds = 0;
+        treasury.warmup_until = 0;
+        treasury.bounty_max_payout_usdc = 0;
+        treasury.bounty_timelock_seconds = 0;
+        treasury.bounty_payout_count = 0;
+        treasury.total_bounty_funded_usdc = 0;
+        treasury.total_bounty_paid_usdc = 0;
+        treasury.watchdog_threshold_seconds = 0;
+        treasury.stalled = false;
+        treasury.settlement_window_slots = DEFAULT_SETTLEMENT_WINDOW_SLOTS;
+        treasury.operation_count = 0;
+        treasury.min_sol_to_convert = 0;
+        treasury.sol_pending_conversion = 0;
+        treasury.sol_usd_oracle = Pubkey::default();
+        treasury.oracle_max_age_seconds = 0;
+        treasury.total_sol_collected_usd = 0;
+        treasury.epoch_duration_seconds = DEFAULT_EPOCH_DURATION_SECONDS;
+        treasury.current_epoch = 0;
+        treasury.epoch_start_timestamp = Clock::get()?.unix_timestamp;
+        treasury.burn_target_per_epoch = 0;
+        treasury.burn_carryover = 0;
+        treasury.epoch_tokens_burned = 0;
+        treasury.guardian = Pubkey::default();
+        treasury.governance = Pubkey::default();
+        treasury.reserve_ops_monthly_limit_usdc = 0;
+        treasury.reserve_ops_stream_start = 0;
+        treasury.total_reserve_ops_usdc = 0;
+        treasury.total_reserve_spent_usdc = 0;
+        treasury.epoch_crank_tip_lamports = 0;
+        treasury.token_usd_oracle = Pubkey::default();
+        treasury.conditional_buyback_count = 0;
+        treasury.pending_vault_successor = Pubkey::default();
+        treasury.vault_migration_eta = 0;
+        treasury.campaign_count = 0;
+        treasury.large_operation_threshold = 0;
+        treasury.usdc_usd_oracle = Pubkey::default();
+        treasury.depeg_threshold_bps = 0;
+        treasury.mm_ltv_bps = 0;
+        treasury.mm_liquidation_bps = 0;
+        treasury.total_tokens_lent = 0;
+        treasury.weekly_outflow_cap_usdc = 0;
+        treasury.pending_weekly_outflow_cap_usdc = 0;
+        treasury.weekly_outflow_cap_eta = 0;
+        treasury.outflow_day = 0;
+        treasury.daily_outflows_usdc = [0; OUTFLOW_WINDOW_DAYS];
+        treasury.token_switchboard_feed = Pubkey::default();
+        treasury.switchboard_max_age_seconds = 0;
+        treasury.twap_max_deviation_bps = 0;
+        treasury.expected_mint_authority = expected_token_mint_authority;
+        treasury.expected_freeze_authority = token_mint.freeze_authority.into();
+        treasury.mint_config_verified_at = 0;
+        treasury.settlement_max_deviation_bps = 0;
+        treasury.degraded_execution_limit = 0;
+        treasury.degraded_executions = 0;
+        treasury.keeper_reward_bps = 0;
+        treasury.keeper_reward_cap_usdc = 0;
+        treasury.operator = ctx.accounts.authority.key();
+        treasury.pauser = ctx.accounts.authority.key();
+        treasury.squads_multisig = Pubkey::default();
+        treasury.squads_vault_index = 0;
+        treasury.config_delay_seconds = 0;
+        treasury.realms_governance = Pubkey::default();
+        treasury.lbp_count = 0;
+        treasury.circuit_breaker_deviation_bps = 0;
+        treasury.rounding_policy = RoundingPolicy::Floor;
+        treasury.daily_volume_cap_usdc = 0;
+        treasury.pending_daily_volume_cap_usdc = 0;
+        treasury.daily_volume_cap_eta = 0;
+        treasury.volume_hour = 0;
+        treasury.hourly_volume_usdc = [0; VOLUME_WINDOW_HOURS];
+        treasury.last_buyback_timestamp = 0;
+        treasury.last_liquidity_timestamp = 0;
+        treasury.liquidity_cooldown_seconds = 0;
+        treasury.budget_epoch_duration_seconds = 0;
+        treasury.budget_epoch = 0;
+        treasury.budget_epoch_start = 0;
+        treasury.epoch_base_usdc_converted = 0;
+        treasury.epoch_base_buybacks_usdc = 0;
+        treasury.epoch_base_liquidity_usdc = 0;
+        treasury.epoch_base_reserve_usdc = 0;
+        treasury.total_lp_burned = 0;
+        treasury.reserve_withdrawal_delay_seconds = DEFAULT_RESERVE_WITHDRAWAL_DELAY_SECONDS;
+        treasury.reserve_withdrawal_count = 0;
+        treasury.kamino_reserve = Pubkey::default();
+        treasury.kamino_collateral_amount = 0;
+        treasury.kamino_deposited_usdc = 0;
+        treasury.lending_venue = LendingVenue::None;
+        treasury.marginfi_bank = Pubkey::default();
+        treasury.marginfi_account = Pubkey::default();
+        treasury.marginfi_deposited_usdc = 0;
+        treasury.circuit_breaker_tripped_at = 0;
+        treasury.last_reviewed_proposal_at = 0;
+        treasury.launch_mode_until_epoch = launch_epochs;
+        treasury.solend_reserve = Pubkey::default();
+        treasury.solend_collateral_amount = 0;
+        treasury.solend_deposited_usdc = 0;
+        treasury.supply_snapshot = 0;
+        treasury.supply_snapshot_burned = 0;
+        treasury.supply_snapshot_slot = 0;
+        treasury.total_external_burned = 0;
+        treasury.total_yield_harvested_usdc = 0;
+        treasury.clmm_tick_lower = 0;
+        treasury.clmm_tick_upper = 0;
+        treasury.clmm_pool = Pubkey::default();
+        treasury.clmm_position_mint = Pubkey::default();
+        treasury.clmm_liquidity = 0;
+        treasury.burn_milestone_interval_tokens = 0;
+        treasury.buyback_milestone_interval_usdc = 0;
+        treasury.dlmm_bin_offset_lower = 0;
+        treasury.dlmm_bin_offset_upper = 0;
+        treasury.dlmm_lb_pair = Pubkey::default();
+        treasury.dlmm_position = Pubkey::default();
+        treasury.dlmm_lower_bin_id = 0;
+        treasury.dlmm_upper_bin_id = 0;
+        treasury.dlmm_usdc_deposited = 0;
+        treasury.total_lp_fees_earned = 0;
+        treasury.epoch_base_lp_fees_earned = 0;
+        treasury.lp_lock_count = 0;
+        treasury.strategy_deposited_usdc = 0;
+        treasury.total_buyback_tokens_received = 0;
+        treasury.funnel_base_sol_collected = 0;
+        treasury.funnel_base_usdc_converted = 0;
+        treasury.funnel_base_buybacks_usdc = 0;
+        treasury.funnel_base_tokens_received = 0;
+        treasury.epoch_base_dividends_usdc = 0;
         treasury.bump = ctx.bumps.treasury;
 
         emit!(TreasuryInitialized {
@@ -45,433 +338,16549 @@ pub mod usdcball {
             buyback_allocation_bps,
             liquidity_allocation_bps,
             reserve_allocation_bps,
+            usdc_mint: treasury.usdc_mint,
+            token_mint: treasury.token_mint,
+            token_decimals: token_mint.decimals,
+            token_mint_authority,
         });
 
         Ok(())
     }
 
-    /// Record incoming SOL fees to the treasury
+    /// Record incoming SOL fees to the treasury. Fee routers call this constantly, so
+    /// it reads the treasury in place and only writes the small `FeeCounter`; the
+    /// counts reach the treasury on the next `sync_fee_counter` or deposit.
     pub fn record_fee(ctx: Context<RecordFee>, amount: u64) -> Result<()> {
-        let treasury = &mut ctx.accounts.treasury;
-        require!(!treasury.paused, ErrorCode::Paused);
+        let treasury = ctx.accounts.treasury.try_borrow_data()?;
+        require!(
+            treasury.len() > 8 + TREASURY_ORACLE_MAX_AGE_OFFSET + 8
+                && treasury[..8] == Treasury::DISCRIMINATOR,
+            ErrorCode::InvalidTreasuryAccount
+        );
+        let read_u64 = |offset: usize| Treasury::read_u64_at(&treasury, offset);
+        require!(
+            treasury[8 + TREASURY_PAUSE_FLAGS_OFFSET] & PauseFlag::Fees.mask() == 0,
+            ErrorCode::Paused
+        );
+        require!(
+            read_u64(TREASURY_INSTRUCTION_ENABLED_OFFSET)? & GatedInstruction::RecordFee.mask()
+                != 0,
+            ErrorCode::InstructionDisabled
+        );
 
-        treasury.total_sol_collected = treasury
-            .total_sol_collected
+        // Only lamports that reached the fee vault outside `deposit_fee` can be recorded
+        let fee_vault = &mut ctx.accounts.fee_vault;
+        require!(
+            amount <= fee_vault.unrecorded_lamports(&fee_vault.to_account_info())?,
+            ErrorCode::FeeNotReceived
+        );
+        fee_vault.recorded_lamports = fee_vault
+            .recorded_lamports
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        // Value the fee at the current SOL/USD price once an oracle is configured
+        let clock = Clock::get()?;
+        let sol_usd_oracle = Treasury::read_pubkey_at(&treasury, TREASURY_SOL_USD_ORACLE_OFFSET)?;
+        let usd_value = if sol_usd_oracle == Pubkey::default() {
+            0
+        } else {
+            let oracle_account = ctx
+                .accounts
+                .sol_usd_oracle
+                .as_ref()
+                .filter(|a| a.key() == sol_usd_oracle)
+                .ok_or(ErrorCode::InvalidOracle)?;
+            oracle::read_price(
+                oracle_account,
+                clock.unix_timestamp,
+                read_u64(TREASURY_ORACLE_MAX_AGE_OFFSET)? as i64,
+            )?
+            .value_usd(amount, SOL_DECIMALS)?
+        };
+
+        let mut counter = ctx.accounts.fee_counter.load_mut()?;
+        counter.unsynced_lamports = counter
+            .unsynced_lamports
             .checked_add(amount)
             .ok_or(ErrorCode::Overflow)?;
+        counter.unsynced_usd = counter
+            .unsynced_usd
+            .checked_add(usd_value)
+            .ok_or(ErrorCode::Overflow)?;
+        counter.fee_count = counter.fee_count.saturating_add(1);
+        counter.last_fee_timestamp = clock.unix_timestamp;
 
         emit!(FeeRecorded {
             amount,
-            total_collected: treasury.total_sol_collected,
+            total_collected: read_u64(TREASURY_TOTAL_SOL_COLLECTED_OFFSET)?
+                .checked_add(counter.unsynced_lamports)
+                .ok_or(ErrorCode::Overflow)?,
+            usd_value,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Create the zero-copy counter `record_fee` writes to
+    pub fn initialize_fee_counter(ctx: Context<InitializeFeeCounter>) -> Result<()> {
+        let mut counter = ctx.accounts.fee_counter.load_init()?;
+        counter.bump = ctx.bumps.fee_counter;
+        Ok(())
+    }
+
+    /// Transfer-hook entrypoint: the USDCBALL mint's transfer hook program calls
+    /// this by CPI from its `Execute` handler, passing the transfer's accounts and
+    /// amount through, so every transfer records the Token-2022 fee it withholds
+    /// without a separate `record_fee` call. The hook proves itself by signing with
+    /// its `FEE_SOURCE_SEED` PDA; `transfer_fee_counter` and that PDA go in the
+    /// hook's extra account metas.
+    pub fn record_transfer_fee(
+        ctx: Context<RecordTransferFee>,
+        amount: u64,
+        source_bump: u8,
+    ) -> Result<()> {
+        let treasury = ctx.accounts.treasury.try_borrow_data()?;
+        require!(
+            treasury.len() > 8 + TREASURY_ORACLE_MAX_AGE_OFFSET + 8
+                && treasury[..8] == Treasury::DISCRIMINATOR,
+            ErrorCode::InvalidTreasuryAccount
+        );
+        require!(
+            treasury[8 + TREASURY_PAUSE_FLAGS_OFFSET] & PauseFlag::Fees.mask() == 0,
+            ErrorCode::Paused
+        );
+        let instruction_enabled =
+            Treasury::read_u64_at(&treasury, TREASURY_INSTRUCTION_ENABLED_OFFSET)?;
+        require!(
+            instruction_enabled & GatedInstruction::RecordTransferFee.mask() != 0,
+            ErrorCode::InstructionDisabled
+        );
+        let token_mint = Treasury::read_pubkey_at(&treasury, TREASURY_TOKEN_MINT_OFFSET)?;
+        require_keys_eq!(
+            ctx.accounts.mint.key(),
+            token_mint,
+            ErrorCode::InvalidTokenMint
+        );
+
+        let clock = Clock::get()?;
+        let (hook_program, fee) =
+            transfer_hook_accrual(&ctx.accounts.mint.try_borrow_data()?, clock.epoch, amount)?;
+        let expected_source =
+            Pubkey::create_program_address(&[FEE_SOURCE_SEED, &[source_bump]], &hook_program)
+                .map_err(|_| ErrorCode::InvalidFeeSource)?;
+        require_keys_eq!(
+            ctx.accounts.hook_authority.key(),
+            expected_source,
+            ErrorCode::InvalidFeeSource
+        );
+
+        let mut counter = ctx.accounts.transfer_fee_counter.load_mut()?;
+        counter.accrued_fees = counter
+            .accrued_fees
+            .checked_add(fee)
+            .ok_or(ErrorCode::Overflow)?;
+        counter.transferred_amount = counter.transferred_amount.saturating_add(amount);
+        counter.transfer_count = counter.transfer_count.saturating_add(1);
+        counter.last_transfer_timestamp = clock.unix_timestamp;
+
+        emit!(TransferFeeRecorded {
+            source: ctx.accounts.source_token.key(),
+            destination: ctx.accounts.destination_token.key(),
+            amount,
+            fee,
+            total_accrued: counter.accrued_fees,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Create the zero-copy counter `record_transfer_fee` writes to
+    pub fn initialize_transfer_fee_counter(
+        ctx: Context<InitializeTransferFeeCounter>,
+    ) -> Result<()> {
+        let mut counter = ctx.accounts.transfer_fee_counter.load_init()?;
+        counter.bump = ctx.bumps.transfer_fee_counter;
+        Ok(())
+    }
+
+    /// Permissionless: fold fees recorded by `record_fee` into the treasury totals
+    pub fn sync_fee_counter(ctx: Context<SyncFeeCounter>) -> Result<()> {
+        let mut counter = ctx.accounts.fee_counter.load_mut()?;
+        let synced = counter.unsynced_lamports;
+        ctx.accounts.treasury.absorb_fee_counter(&mut counter)?;
+
+        emit!(FeeCounterSynced {
+            lamports: synced,
+            total_collected: ctx.accounts.treasury.total_sol_collected,
             timestamp: Clock::get()?.unix_timestamp,
         });
 
         Ok(())
     }
 
-    /// Execute a buyback operation (USDC -> USDCBALL token)
-    pub fn execute_buyback(
-        ctx: Context<ExecuteBuyback>,
+    /// Create the program-owned vault that holds SOL fees until conversion
+    pub fn initialize_fee_vault(ctx: Context<InitializeFeeVault>) -> Result<()> {
+        ctx.accounts.fee_vault.bump = ctx.bumps.fee_vault;
+        Ok(())
+    }
+
+    /// Transfer `amount` lamports from the depositor into the fee vault and record
+    /// them as collected fees
+    pub fn deposit_fee(ctx: Context<DepositFee>, amount: u64) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.require_not_paused(PauseFlag::Fees)?;
+        treasury.require_enabled(GatedInstruction::RecordFee)?;
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        treasury.absorb_fee_counter(&mut *ctx.accounts.fee_counter.load_mut()?)?;
+
+        let received = transfer_to_fee_vault(
+            &ctx.accounts.depositor,
+            &mut ctx.accounts.fee_vault,
+            &ctx.accounts.system_program,
+            amount,
+        )?;
+
+        let clock = Clock::get()?;
+        let usd_value =
+            treasury.credit_sol_fee(received, ctx.accounts.sol_usd_oracle.as_deref(), &clock)?;
+
+        emit!(FeeRecorded {
+            amount: received,
+            total_collected: treasury.total_sol_collected,
+            usd_value,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Revenue entrypoint for other programs, called by CPI. Moves `lamports` into
+    /// the fee vault and `usdc_amount` into the USDC vault from `payer`, and tags
+    /// them with the calling program, proven by its signed `FEE_SOURCE_SEED` PDA.
+    pub fn forward_fee(
+        ctx: Context<ForwardFee>,
+        lamports: u64,
         usdc_amount: u64,
-        min_tokens_out: u64,
+        source_bump: u8,
     ) -> Result<()> {
         let treasury = &mut ctx.accounts.treasury;
-        require!(!treasury.paused, ErrorCode::Paused);
+        treasury.require_not_paused(PauseFlag::Fees)?;
+        treasury.require_enabled(GatedInstruction::RecordFee)?;
+        require!(lamports > 0 || usdc_amount > 0, ErrorCode::InvalidAmount);
+
+        let source_program = ctx.accounts.source_program.key();
+        let expected_source =
+            Pubkey::create_program_address(&[FEE_SOURCE_SEED, &[source_bump]], &source_program)
+                .map_err(|_| ErrorCode::InvalidFeeSource)?;
+        require_keys_eq!(
+            ctx.accounts.source_authority.key(),
+            expected_source,
+            ErrorCode::InvalidFeeSource
+        );
 
-        // Check cooldown
         let clock = Clock::get()?;
-        let time_since_last = clock.unix_timestamp - treasury.last_operation_timestamp;
+        let mut usd_value = 0;
+        if lamports > 0 {
+            treasury.absorb_fee_counter(&mut *ctx.accounts.fee_counter.load_mut()?)?;
+            let received = transfer_to_fee_vault(
+                &ctx.accounts.payer,
+                &mut ctx.accounts.fee_vault,
+                &ctx.accounts.system_program,
+                lamports,
+            )?;
+            require!(received == lamports, ErrorCode::FeeNotReceived);
+            usd_value = treasury.credit_sol_fee(
+                lamports,
+                ctx.accounts.sol_usd_oracle.as_deref(),
+                &clock,
+            )?;
+        }
+
+        if usdc_amount > 0 {
+            let (Some(source_usdc), Some(treasury_usdc), Some(token_program)) = (
+                ctx.accounts.source_usdc.as_ref(),
+                ctx.accounts.treasury_usdc.as_ref(),
+                ctx.accounts.token_program.as_ref(),
+            ) else {
+                return err!(ErrorCode::MissingUsdcAccounts);
+            };
+            let cpi_accounts = Transfer {
+                from: source_usdc.to_account_info(),
+                to: treasury_usdc.to_account_info(),
+                authority: ctx.accounts.payer.to_account_info(),
+            };
+            transfer_tokens(
+                CpiContext::new(token_program.to_account_info(), cpi_accounts),
+                usdc_amount,
+            )?;
+
+            // USDC revenue needs no conversion and feeds the budgets directly
+            treasury.total_usdc_converted = treasury
+                .total_usdc_converted
+                .checked_add(usdc_amount)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        emit!(FeeForwarded {
+            source_program,
+            lamports,
+            usdc_amount,
+            usd_value,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Record many small fees from a fee router in one instruction. The entries
+    /// are settled by a single transfer of their sum into the fee vault.
+    pub fn record_fees_batch(ctx: Context<DepositFee>, entries: Vec<FeeEntry>) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.require_not_paused(PauseFlag::Fees)?;
+        treasury.require_enabled(GatedInstruction::RecordFee)?;
         require!(
-            time_since_last >= treasury.cooldown_seconds,
-            ErrorCode::CooldownNotMet
+            !entries.is_empty() && entries.len() <= MAX_FEE_BATCH_ENTRIES,
+            ErrorCode::InvalidFeeBatch
         );
+        treasury.absorb_fee_counter(&mut *ctx.accounts.fee_counter.load_mut()?)?;
+
+        let total = entries.iter().try_fold(0u64, |sum, entry| {
+            require!(entry.amount > 0, ErrorCode::InvalidFeeBatch);
+            sum.checked_add(entry.amount)
+                .ok_or_else(|| error!(ErrorCode::Overflow))
+        })?;
+        let received = transfer_to_fee_vault(
+            &ctx.accounts.depositor,
+            &mut ctx.accounts.fee_vault,
+            &ctx.accounts.system_program,
+            total,
+        )?;
+        require!(received == total, ErrorCode::FeeNotReceived);
+
+        let clock = Clock::get()?;
+        let usd_value =
+            treasury.credit_sol_fee(total, ctx.accounts.sol_usd_oracle.as_deref(), &clock)?;
+
+        emit!(FeeRecorded {
+            amount: total,
+            total_collected: treasury.total_sol_collected,
+            usd_value,
+            timestamp: clock.unix_timestamp,
+        });
+        emit!(FeeBatchRecorded {
+            depositor: ctx.accounts.depositor.key(),
+            entries,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Execute a buyback operation (USDC -> USDCBALL token) by swapping on `venue`.
+    /// `remaining_accounts` holds the venue program followed by its swap accounts;
+    /// the treasury signs, and the vault balance changes are checked against
+    /// `usdc_amount` and `min_tokens_out`.
+    /// With `allow_partial`, an amount above the per-cycle cap or remaining allocation
+    /// is clamped to the maximum permissible amount instead of failing.
+    pub fn execute_buyback<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteBuyback<'info>>,
+        usdc_amount: u64,
+        min_tokens_out: u64,
+        allow_partial: bool,
+        venue: Venue,
+        swap_data: Vec<u8>,
+    ) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.require_not_paused(PauseFlag::Buybacks)?;
+        treasury.require_enabled(GatedInstruction::ExecuteBuyback)?;
+        ctx.accounts
+            .operator_stats
+            .require_scope(OperationKind::Buyback, Some(venue))?;
+
+        // Check cooldown
+        let clock = Clock::get()?;
+        treasury.require_ready(OperationKind::Buyback, clock.unix_timestamp)?;
+
+        let requested_usdc_amount = usdc_amount;
+        let remaining_budget = treasury.remaining_buyback_budget()?;
+        let (usdc_amount, min_tokens_out) = if allow_partial {
+            let executable = usdc_amount
+                .min(treasury.cycle_limit_usdc())
+                .min(remaining_budget);
+            require!(executable > 0, ErrorCode::ExceedsAllocation);
+
+            // Scale the minimum output down with the executed amount
+            let scaled_min_out = (min_tokens_out as u128)
+                .checked_mul(executable as u128)
+                .ok_or(ErrorCode::Overflow)?
+                .checked_div(requested_usdc_amount as u128)
+                .ok_or(ErrorCode::Overflow)? as u64;
+            (executable, scaled_min_out)
+        } else {
+            (usdc_amount, min_tokens_out)
+        };
 
         // Check per-cycle limit
         require!(
-            usdc_amount <= treasury.max_usdc_per_cycle,
+            usdc_amount <= treasury.cycle_limit_usdc(),
             ErrorCode::ExceedsMaxPerCycle
         );
 
-        // Verify allocation
-        let max_buyback = (treasury.total_usdc_converted as u128)
-            .checked_mul(treasury.buyback_allocation_bps as u128)
-            .ok_or(ErrorCode::Overflow)?
-            .checked_div(10000)
-            .ok_or(ErrorCode::Overflow)? as u64;
+        let oracle_price = check_oracle_floor(
+            treasury,
+            ctx.accounts.token_usd_oracle.as_deref(),
+            ctx.accounts.token_switchboard_feed.as_deref(),
+            usdc_amount,
+            min_tokens_out,
+            ctx.accounts.token_mint.decimals,
+            clock.unix_timestamp,
+        )?;
 
-        require!(
-            treasury.total_buybacks_usdc + usdc_amount <= max_buyback,
-            ErrorCode::ExceedsAllocation
-        );
+        // Large operations need the co-signer in the same transaction
+        treasury.require_co_signer(
+            usdc_amount,
+            ctx.accounts.authority.key(),
+            ctx.accounts.co_signer.as_ref().map(|s| s.key()),
+            ctx.accounts.co_signer_operator.as_deref(),
+        )?;
 
-        // Transfer USDC from treasury to Jupiter/DEX for swap
-        let seeds = &[
-            b"treasury".as_ref(),
-            &[treasury.bump],
-        ];
-        let signer = &[&seeds[..]];
+        let twap_price = check_twap(
+            ctx.accounts.twap.as_deref_mut().map(|t| &mut **t),
+            ctx.accounts.twap_pool_usdc.as_deref(),
+            ctx.accounts.twap_pool_token.as_deref(),
+            ctx.accounts.token_mint.decimals,
+            treasury.twap_max_deviation_bps,
+            clock.unix_timestamp,
+        )?;
+        // Without an oracle, settlement is judged against the pre-trade TWAP
+        let reference_price = oracle_price.or(twap_price);
 
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.treasury_usdc.to_account_info(),
-            to: ctx.accounts.destination_usdc.to_account_info(),
-            authority: ctx.accounts.treasury.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-        token::transfer(cpi_ctx, usdc_amount)?;
+        treasury.authorize_usdc_spend(UsdcSpend::Buyback, usdc_amount, clock.unix_timestamp)?;
 
-        // Update state
-        treasury.total_buybacks_usdc = treasury
-            .total_buybacks_usdc
-            .checked_add(usdc_amount)
-            .ok_or(ErrorCode::Overflow)?;
-        treasury.last_operation_timestamp = clock.unix_timestamp;
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let signer = &[&seeds[..]];
+        let usdc_before = ctx.accounts.treasury_usdc.amount;
+        let tokens_before = ctx.accounts.treasury_token.amount;
+        venues::invoke_swap(
+            venue,
+            ctx.remaining_accounts,
+            swap_data,
+            treasury.key(),
+            signer,
+        )?;
 
-        emit!(BuybackExecuted {
+        // Enforce the minimum output on what the treasury actually received
+        ctx.accounts.treasury_usdc.reload()?;
+        ctx.accounts.treasury_token.reload()?;
+        let spent = usdc_before.saturating_sub(ctx.accounts.treasury_usdc.amount);
+        let tokens_received = ctx
+            .accounts
+            .treasury_token
+            .amount
+            .saturating_sub(tokens_before);
+        let (execution_price, degraded_execution) = treasury.settle_buyback(
             usdc_amount,
+            spent,
+            tokens_received,
+            min_tokens_out,
+            reference_price,
+            ctx.accounts.token_mint.decimals,
+            clock.unix_timestamp,
+        )?;
+        record_quote(
+            ctx.accounts.quote_board.as_mut(),
+            spent,
+            tokens_received,
+            &clock,
+        );
+
+        emit!(BuybackExecuted {
+            usdc_amount: spent,
+            requested_usdc_amount,
             min_tokens_out,
+            tokens_received,
+            execution_price,
+            reference_price: reference_price.unwrap_or(0),
+            degraded_execution,
             timestamp: clock.unix_timestamp,
         });
+        if let Some(alert) = treasury.large_operation_alert(
+            AlertedOperation::Buyback,
+            spent,
+            ctx.accounts.authority.key(),
+            ctx.remaining_accounts[0].key(),
+            usdc_before,
+            &clock,
+        ) {
+            emit!(alert);
+        }
+
+        record_key_activity(
+            ctx.accounts.authority_activity.as_mut(),
+            KeyAction::Buyback,
+            spent,
+        )?;
 
         Ok(())
     }
 
-    /// Add liquidity to DEX pool
-    pub fn add_liquidity(
-        ctx: Context<AddLiquidity>,
+    /// Deposit USDC paired with treasury-held USDCBALL into a registered
+    /// constant-product pool through its venue. `remaining_accounts` are the venue
+    /// program followed by its deposit accounts, and `deposit_data` the venue's
+    /// deposit instruction, which may take at most `usdc_amount` and `token_amount`
+    /// and must mint at least `min_lp_amount` LP tokens to `treasury_lp`.
+    pub fn add_liquidity<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AddLiquidity<'info>>,
         usdc_amount: u64,
         token_amount: u64,
+        deposit_data: Vec<u8>,
+        min_lp_amount: u64,
     ) -> Result<()> {
         let treasury = &mut ctx.accounts.treasury;
-        require!(!treasury.paused, ErrorCode::Paused);
+        treasury.require_not_paused(PauseFlag::Liquidity)?;
+        treasury.require_enabled(GatedInstruction::AddLiquidity)?;
 
         let clock = Clock::get()?;
-        let time_since_last = clock.unix_timestamp - treasury.last_operation_timestamp;
+        treasury.require_ready(OperationKind::Liquidity, clock.unix_timestamp)?;
+        if treasury.check_usdc_peg(ctx.accounts.usdc_usd_oracle.as_deref(), &clock)? {
+            return Ok(());
+        }
+
+        let registry = &ctx.accounts.pool_registry;
+        let pool = registry
+            .find(
+                &ctx.accounts.pool_usdc.key(),
+                &ctx.accounts.pool_token.key(),
+            )
+            .map(|i| &registry.pools[i])
+            .ok_or(ErrorCode::UnregisteredPool)?;
         require!(
-            time_since_last >= treasury.cooldown_seconds,
-            ErrorCode::CooldownNotMet
+            pool.pool_type == PoolType::ConstantProduct,
+            ErrorCode::WrongPoolType
         );
-
+        ctx.accounts
+            .operator_stats
+            .require_scope(OperationKind::Liquidity, Some(pool.venue))?;
         require!(
-            usdc_amount <= treasury.max_usdc_per_cycle,
+            usdc_amount <= pool.max_usdc_amount(treasury),
             ErrorCode::ExceedsMaxPerCycle
         );
 
-        // Verify allocation
-        let max_liquidity = (treasury.total_usdc_converted as u128)
-            .checked_mul(treasury.liquidity_allocation_bps as u128)
-            .ok_or(ErrorCode::Overflow)?
-            .checked_div(10000)
-            .ok_or(ErrorCode::Overflow)? as u64;
+        treasury.require_co_signer(
+            usdc_amount,
+            ctx.accounts.authority.key(),
+            ctx.accounts.co_signer.as_ref().map(|s| s.key()),
+            ctx.accounts.co_signer_operator.as_deref(),
+        )?;
 
+        // The deposit must match the pool's current price within slippage
         require!(
-            treasury.total_liquidity_usdc + usdc_amount <= max_liquidity,
-            ErrorCode::ExceedsAllocation
+            deposit_matches_pool_price(
+                usdc_amount,
+                token_amount,
+                ctx.accounts.pool_usdc.amount,
+                ctx.accounts.pool_token.amount,
+                pool.slippage_bps(treasury),
+            ),
+            ErrorCode::DepositRatioMismatch
         );
 
-        // Transfer USDC to LP pool
-        let seeds = &[
-            b"treasury".as_ref(),
-            &[treasury.bump],
-        ];
+        observe_pool_price(
+            ctx.accounts.twap.as_deref_mut().map(|t| &mut **t),
+            Some((
+                ctx.accounts.pool_usdc.amount,
+                ctx.accounts.pool_token.amount,
+            )),
+            ctx.accounts.token_mint.decimals,
+            treasury.twap_max_deviation_bps,
+            clock.unix_timestamp,
+        )?;
+
+        let venue = pool.venue;
+        treasury.authorize_usdc_spend(UsdcSpend::Liquidity, usdc_amount, clock.unix_timestamp)?;
+
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
         let signer = &[&seeds[..]];
+        let usdc_before = ctx.accounts.treasury_usdc.amount;
+        let tokens_before = ctx.accounts.treasury_token.amount;
+        let lp_before = ctx.accounts.treasury_lp.amount;
+        venues::invoke_deposit(
+            venue,
+            ctx.remaining_accounts,
+            deposit_data,
+            treasury.key(),
+            signer,
+        )?;
 
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.treasury_usdc.to_account_info(),
-            to: ctx.accounts.pool_usdc.to_account_info(),
-            authority: ctx.accounts.treasury.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-        token::transfer(cpi_ctx, usdc_amount)?;
+        // The deposit is only done once the treasury holds the LP tokens for it
+        ctx.accounts.treasury_usdc.reload()?;
+        ctx.accounts.treasury_token.reload()?;
+        ctx.accounts.treasury_lp.reload()?;
+        let usdc_spent = usdc_before.saturating_sub(ctx.accounts.treasury_usdc.amount);
+        let tokens_spent = tokens_before.saturating_sub(ctx.accounts.treasury_token.amount);
+        let lp_balance = ctx.accounts.treasury_lp.amount;
+        let lp_received = lp_balance.saturating_sub(lp_before);
+        require!(
+            usdc_spent <= usdc_amount && tokens_spent <= token_amount,
+            ErrorCode::InvalidVenueInstruction
+        );
+        require!(
+            lp_received > 0 && lp_received >= min_lp_amount,
+            ErrorCode::SlippageExceeded
+        );
 
         // Update state
-        treasury.total_liquidity_usdc = treasury
-            .total_liquidity_usdc
-            .checked_add(usdc_amount)
-            .ok_or(ErrorCode::Overflow)?;
-        treasury.last_operation_timestamp = clock.unix_timestamp;
+        treasury.settle_usdc_spend(
+            UsdcSpend::Liquidity,
+            usdc_amount,
+            usdc_spent,
+            clock.unix_timestamp,
+        )?;
+        treasury.record_operation(OperationKind::Liquidity, clock.unix_timestamp);
+        treasury.stalled = false;
+        if let Some(lp_position) = ctx.accounts.lp_position.as_mut() {
+            require_keys_eq!(
+                ctx.accounts.treasury_lp.mint,
+                lp_position.lp_mint,
+                ErrorCode::LpPositionMismatch
+            );
+            lp_position.record_deposit(usdc_spent, tokens_spent, lp_balance)?;
+        }
 
         emit!(LiquidityAdded {
-            usdc_amount,
-            token_amount,
+            usdc_amount: usdc_spent,
+            token_amount: tokens_spent,
             timestamp: clock.unix_timestamp,
         });
+        if let Some(alert) = treasury.large_operation_alert(
+            AlertedOperation::Liquidity,
+            usdc_spent,
+            ctx.accounts.authority.key(),
+            ctx.accounts.pool_usdc.key(),
+            usdc_before,
+            &clock,
+        ) {
+            emit!(alert);
+        }
+
+        record_key_activity(
+            ctx.accounts.authority_activity.as_mut(),
+            KeyAction::Liquidity,
+            usdc_spent,
+        )?;
 
         Ok(())
     }
 
-    /// Record USDC conversion from SOL
-    pub fn record_usdc_conversion(ctx: Context<RecordConversion>, usdc_amount: u64) -> Result<()> {
-        let treasury = &mut ctx.accounts.treasury;
-        require!(!treasury.paused, ErrorCode::Paused);
+    /// Set the tick range the next Raydium CLMM position is opened over. An open
+    /// position keeps the range it was opened with.
+    pub fn set_clmm_tick_range(
+        ctx: Context<UpdateConfig>,
+        tick_lower: i32,
+        tick_upper: i32,
+    ) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        require!(
+            CLMM_MIN_TICK <= tick_lower && tick_lower < tick_upper && tick_upper <= CLMM_MAX_TICK,
+            ErrorCode::InvalidTickRange
+        );
 
-        treasury.total_usdc_converted = treasury
-            .total_usdc_converted
-            .checked_add(usdc_amount)
-            .ok_or(ErrorCode::Overflow)?;
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.clmm_tick_lower = tick_lower;
+        treasury.clmm_tick_upper = tick_upper;
 
-        emit!(UsdcConverted {
-            amount: usdc_amount,
-            total_converted: treasury.total_usdc_converted,
+        emit!(ConfigUpdated {
             timestamp: Clock::get()?.unix_timestamp,
         });
 
         Ok(())
     }
 
-    /// Emergency pause all operations
-    pub fn emergency_pause(ctx: Context<EmergencyAction>) -> Result<()> {
+    /// Open the treasury's Raydium CLMM position in a registered pool over the
+    /// configured tick range. The position NFT goes to the treasury PDA's token
+    /// account; `increase_clmm_liquidity` adds liquidity to it.
+    pub fn open_clmm_position(
+        ctx: Context<OpenClmmPosition>,
+        tick_array_lower_start: i32,
+        tick_array_upper_start: i32,
+    ) -> Result<()> {
         let treasury = &mut ctx.accounts.treasury;
-        treasury.paused = true;
-
-        emit!(EmergencyPaused {
-            timestamp: Clock::get()?.unix_timestamp,
-        });
+        treasury.require_not_paused(PauseFlag::Liquidity)?;
+        treasury.require_enabled(GatedInstruction::AddLiquidity)?;
+        ctx.accounts
+            .operator_stats
+            .require_scope(OperationKind::Liquidity, Some(Venue::Raydium))?;
+        require!(
+            treasury.clmm_position_mint == Pubkey::default(),
+            ErrorCode::ClmmPositionOpen
+        );
+        require!(
+            treasury.clmm_tick_lower < treasury.clmm_tick_upper,
+            ErrorCode::InvalidTickRange
+        );
+        let clock = Clock::get()?;
+        if treasury.check_usdc_peg(ctx.accounts.usdc_usd_oracle.as_deref(), &clock)? {
+            return Ok(());
+        }
 
-        Ok(())
-    }
+        let range = raydium_clmm::TickRange {
+            tick_lower: treasury.clmm_tick_lower,
+            tick_upper: treasury.clmm_tick_upper,
+            tick_array_lower_start,
+            tick_array_upper_start,
+        };
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        raydium_clmm::open_position(
+            &ctx.accounts.position_accounts(),
+            &raydium_clmm::OpenAccounts {
+                payer: ctx.accounts.authority.to_account_info(),
+                nft_mint: ctx.accounts.position_nft_mint.to_account_info(),
+                rent: ctx.accounts.rent.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+            },
+            &range,
+            &[&seeds[..]],
+        )?;
 
-    /// Resume operations after pause
-    pub fn resume(ctx: Context<EmergencyAction>) -> Result<()> {
         let treasury = &mut ctx.accounts.treasury;
-        treasury.paused = false;
+        treasury.clmm_pool = ctx.accounts.pool_state.key();
+        treasury.clmm_position_mint = ctx.accounts.position_nft_mint.key();
+        treasury.clmm_liquidity = 0;
 
-        emit!(OperationsResumed {
-            timestamp: Clock::get()?.unix_timestamp,
+        emit!(ClmmPositionOpened {
+            pool: treasury.clmm_pool,
+            position_mint: treasury.clmm_position_mint,
+            tick_lower: range.tick_lower,
+            tick_upper: range.tick_upper,
+            timestamp: clock.unix_timestamp,
         });
 
+        record_key_activity(
+            ctx.accounts.authority_activity.as_mut(),
+            KeyAction::Liquidity,
+            0,
+        )?;
+
         Ok(())
     }
 
-    /// Update treasury configuration
-    pub fn update_config(
-        ctx: Context<UpdateConfig>,
-        max_usdc_per_cycle: Option<u64>,
-        cooldown_seconds: Option<i64>,
-        slippage_bps: Option<u16>,
+    /// Add `liquidity` to the treasury's Raydium CLMM position, paying at most
+    /// `usdc_amount_max` USDC and `token_amount_max` tokens. Raydium takes the
+    /// amounts the current price and the position's range call for.
+    pub fn increase_clmm_liquidity(
+        ctx: Context<ClmmLiquidity>,
+        liquidity: u128,
+        usdc_amount_max: u64,
+        token_amount_max: u64,
     ) -> Result<()> {
+        let position = ctx.accounts.position_accounts();
         let treasury = &mut ctx.accounts.treasury;
+        treasury.require_not_paused(PauseFlag::Liquidity)?;
+        treasury.require_enabled(GatedInstruction::AddLiquidity)?;
+        ctx.accounts
+            .operator_stats
+            .require_scope(OperationKind::Liquidity, Some(Venue::Raydium))?;
+        require!(liquidity > 0, ErrorCode::InvalidAmount);
 
-        if let Some(max_cycle) = max_usdc_per_cycle {
-            treasury.max_usdc_per_cycle = max_cycle;
+        let clock = Clock::get()?;
+        treasury.require_ready(OperationKind::Liquidity, clock.unix_timestamp)?;
+        if treasury.check_usdc_peg(ctx.accounts.usdc_usd_oracle.as_deref(), &clock)? {
+            return Ok(());
         }
 
-        if let Some(cooldown) = cooldown_seconds {
-            treasury.cooldown_seconds = cooldown;
-        }
+        let registry = &ctx.accounts.pool_registry;
+        let pool = registry
+            .find(
+                &ctx.accounts.pool_usdc_vault.key(),
+                &ctx.accounts.pool_token_vault.key(),
+            )
+            .map(|i| &registry.pools[i])
+            .ok_or(ErrorCode::UnregisteredPool)?;
+        require!(
+            usdc_amount_max <= pool.max_usdc_amount(treasury),
+            ErrorCode::ExceedsMaxPerCycle
+        );
+        treasury.require_co_signer(
+            usdc_amount_max,
+            ctx.accounts.authority.key(),
+            ctx.accounts.co_signer.as_ref().map(|s| s.key()),
+            ctx.accounts.co_signer_operator.as_deref(),
+        )?;
 
-        if let Some(slippage) = slippage_bps {
-            require!(slippage <= 1000, ErrorCode::SlippageTooHigh);
-            treasury.slippage_bps = slippage;
-        }
+        treasury.authorize_usdc_spend(
+            UsdcSpend::Liquidity,
+            usdc_amount_max,
+            clock.unix_timestamp,
+        )?;
 
-        emit!(ConfigUpdated {
-            timestamp: Clock::get()?.unix_timestamp,
-        });
+        let (amount_0_max, amount_1_max) = raydium_clmm::order_pair(
+            &treasury.usdc_mint,
+            &treasury.token_mint,
+            usdc_amount_max,
+            token_amount_max,
+        );
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let usdc_before = ctx.accounts.treasury_usdc.amount;
+        let token_before = ctx.accounts.treasury_token.amount;
+        raydium_clmm::increase_liquidity(
+            &position,
+            liquidity,
+            amount_0_max,
+            amount_1_max,
+            &[&seeds[..]],
+        )?;
 
-        Ok(())
-    }
+        ctx.accounts.treasury_usdc.reload()?;
+        ctx.accounts.treasury_token.reload()?;
+        let usdc_amount = usdc_before.saturating_sub(ctx.accounts.treasury_usdc.amount);
+        let token_amount = token_before.saturating_sub(ctx.accounts.treasury_token.amount);
+        require!(
+            usdc_amount <= usdc_amount_max && token_amount <= token_amount_max,
+            ErrorCode::SlippageExceeded
+        );
+
+        treasury.settle_usdc_spend(
+            UsdcSpend::Liquidity,
+            usdc_amount_max,
+            usdc_amount,
+            clock.unix_timestamp,
+        )?;
+        treasury.clmm_liquidity = treasury
+            .clmm_liquidity
+            .checked_add(liquidity)
+            .ok_or(ErrorCode::Overflow)?;
+        treasury.record_operation(OperationKind::Liquidity, clock.unix_timestamp);
+        treasury.stalled = false;
+
+        emit!(ClmmLiquidityAdded {
+            position_mint: treasury.clmm_position_mint,
+            liquidity,
+            usdc_amount,
+            token_amount,
+            total_liquidity: treasury.clmm_liquidity,
+            timestamp: clock.unix_timestamp,
+        });
+
+        record_key_activity(
+            ctx.accounts.authority_activity.as_mut(),
+            KeyAction::Liquidity,
+            usdc_amount,
+        )?;
+
+        Ok(())
+    }
+
+    /// Record a USDC conversion from SOL settled outside the program, as reported by
+    /// the authority. `convert_sol_to_usdc` performs and verifies the swap on-chain.
+    pub fn record_usdc_conversion(ctx: Context<RecordConversion>, usdc_amount: u64) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.require_not_paused(PauseFlag::Conversions)?;
+        treasury.require_enabled(GatedInstruction::RecordUsdcConversion)?;
+        let clock = Clock::get()?;
+        if treasury.check_usdc_peg(ctx.accounts.usdc_usd_oracle.as_deref(), &clock)? {
+            return Ok(());
+        }
+        require!(
+            treasury.sol_pending_conversion >= treasury.min_sol_to_convert,
+            ErrorCode::BelowConversionThreshold
+        );
+
+        // A conversion sweeps everything that has accumulated since the last one
+        treasury.sol_pending_conversion = 0;
+        treasury.total_usdc_converted = treasury
+            .total_usdc_converted
+            .checked_add(usdc_amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(UsdcConverted {
+            amount: usdc_amount,
+            total_converted: treasury.total_usdc_converted,
+            timestamp: clock.unix_timestamp,
+        });
+
+        record_key_activity(
+            ctx.accounts.authority_activity.as_mut(),
+            KeyAction::Conversion,
+            usdc_amount,
+        )?;
+
+        Ok(())
+    }
+
+    /// Convert collected SOL on-chain: move `lamports` from the fee vault into the
+    /// treasury's wrapped SOL account, swap wrapped SOL for USDC on `venue`
+    /// (`remaining_accounts`: venue program + swap accounts) and credit the USDC
+    /// that actually reached the treasury vault as converted
+    pub fn convert_sol_to_usdc<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ConvertSolToUsdc<'info>>,
+        lamports: u64,
+        min_usdc_out: u64,
+        venue: Venue,
+        swap_data: Vec<u8>,
+    ) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.require_not_paused(PauseFlag::Conversions)?;
+        treasury.require_enabled(GatedInstruction::RecordUsdcConversion)?;
+        // A conversion draws on no allocation bucket, so only the venue is scoped
+        ctx.accounts.operator_stats.require_venue(venue)?;
+        let clock = Clock::get()?;
+        if treasury.check_usdc_peg(ctx.accounts.usdc_usd_oracle.as_deref(), &clock)? {
+            return Ok(());
+        }
+        require!(
+            treasury.sol_pending_conversion >= treasury.min_sol_to_convert,
+            ErrorCode::BelowConversionThreshold
+        );
+
+        if lamports > 0 {
+            let fee_vault = &mut ctx.accounts.fee_vault;
+            require!(
+                lamports <= fee_vault.recorded_lamports,
+                ErrorCode::InsufficientFeeVault
+            );
+            fee_vault.recorded_lamports -= lamports;
+            **fee_vault.to_account_info().try_borrow_mut_lamports()? -= lamports;
+            **ctx
+                .accounts
+                .treasury_wsol
+                .to_account_info()
+                .try_borrow_mut_lamports()? += lamports;
+
+            token_interface::sync_native(CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::SyncNative {
+                    account: ctx.accounts.treasury_wsol.to_account_info(),
+                },
+            ))?;
+            ctx.accounts.treasury_wsol.reload()?;
+        }
+
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let signer = &[&seeds[..]];
+        let wsol_before = ctx.accounts.treasury_wsol.amount;
+        let usdc_before = ctx.accounts.treasury_usdc.amount;
+        venues::invoke_swap(
+            venue,
+            ctx.remaining_accounts,
+            swap_data,
+            treasury.key(),
+            signer,
+        )?;
+
+        ctx.accounts.treasury_wsol.reload()?;
+        ctx.accounts.treasury_usdc.reload()?;
+        let spent = wsol_before.saturating_sub(ctx.accounts.treasury_wsol.amount);
+        let received = ctx
+            .accounts
+            .treasury_usdc
+            .amount
+            .saturating_sub(usdc_before);
+        require!(
+            received > 0 && received >= min_usdc_out,
+            ErrorCode::SlippageExceeded
+        );
+
+        treasury.sol_pending_conversion = treasury.sol_pending_conversion.saturating_sub(spent);
+        treasury.total_usdc_converted = treasury
+            .total_usdc_converted
+            .checked_add(received)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(UsdcConverted {
+            amount: received,
+            total_converted: treasury.total_usdc_converted,
+            timestamp: clock.unix_timestamp,
+        });
+
+        record_key_activity(
+            ctx.accounts.authority_activity.as_mut(),
+            KeyAction::Conversion,
+            received,
+        )?;
+
+        Ok(())
+    }
+
+    /// Emergency pause all operations; callable by the pauser, the guardian or the
+    /// admin. Only the admin can resume.
+    pub fn emergency_pause(ctx: Context<PauseTreasury>) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.pause_flags = PAUSE_ALL;
+
+        emit!(EmergencyPaused {
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        record_key_activity(
+            ctx.accounts.pauser_activity.as_mut(),
+            KeyAction::Emergency,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    /// Pause one operation type, leaving the others running; callable by the
+    /// pauser, the guardian or the admin
+    pub fn pause_operation(ctx: Context<PauseTreasury>, flag: PauseFlag) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.pause_flags |= flag.mask();
+
+        emit!(OperationTypePaused {
+            flag,
+            pause_flags: treasury.pause_flags,
+            by: ctx.accounts.pauser.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        record_key_activity(
+            ctx.accounts.pauser_activity.as_mut(),
+            KeyAction::Emergency,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    /// Resume one paused operation type. Unlike `resume`, this starts no warm-up.
+    pub fn resume_operation(ctx: Context<EmergencyAction>, flag: PauseFlag) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.pause_flags &= !flag.mask();
+
+        emit!(OperationTypeResumed {
+            flag,
+            pause_flags: treasury.pause_flags,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        record_key_activity(
+            ctx.accounts.authority_activity.as_mut(),
+            KeyAction::Emergency,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    /// Resume operations after pause. The first operation afterwards must wait out a
+    /// warm-up (`post_resume_delay_seconds`, or a fresh cooldown when unset).
+    pub fn resume(ctx: Context<EmergencyAction>) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.pause_flags = 0;
+        treasury.degraded_executions = 0;
+
+        let now = Clock::get()?.unix_timestamp;
+        let warmup = if treasury.post_resume_delay_seconds > 0 {
+            treasury.post_resume_delay_seconds
+        } else {
+            treasury.cooldown_seconds
+        };
+        treasury.warmup_until = now.checked_add(warmup).ok_or(ErrorCode::Overflow)?;
+
+        emit!(OperationsResumed {
+            timestamp: now,
+            warmup_until: treasury.warmup_until,
+        });
+
+        record_key_activity(
+            ctx.accounts.authority_activity.as_mut(),
+            KeyAction::Emergency,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    /// Update treasury configuration immediately; only while no config delay is set,
+    /// otherwise changes go through `propose_config`
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        max_usdc_per_cycle: Option<u64>,
+        cooldown_seconds: Option<i64>,
+        slippage_bps: Option<u16>,
+        keeper_reward_bps: Option<u16>,
+        keeper_reward_cap_usdc: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        let treasury = &mut ctx.accounts.treasury;
+        require!(
+            treasury.config_delay_seconds == 0,
+            ErrorCode::ConfigChangeTimelocked
+        );
+
+        treasury.apply_config_change(&ConfigChange {
+            allocations: None,
+            max_usdc_per_cycle,
+            cooldown_seconds,
+            slippage_bps,
+            keeper_reward_bps,
+            keeper_reward_cap_usdc,
+            config_delay_seconds: None,
+            bounty_max_payout_usdc: None,
+            bounty_timelock_seconds: None,
+        })?;
+
+        emit!(ConfigUpdated {
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Enable or disable a single instruction without pausing the whole treasury
+    pub fn set_instruction_enabled(
+        ctx: Context<UpdateConfig>,
+        instruction: GatedInstruction,
+        enabled: bool,
+    ) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        let treasury = &mut ctx.accounts.treasury;
+
+        if enabled {
+            treasury.instruction_enabled |= instruction.mask();
+        } else {
+            treasury.instruction_enabled &= !instruction.mask();
+        }
+
+        emit!(InstructionToggled {
+            instruction,
+            enabled,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set the share of the reserve allocation paid out as holder dividends.
+    /// Zero disables dividends.
+    pub fn set_dividend_share(ctx: Context<UpdateConfig>, dividend_share_bps: u16) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        require!(dividend_share_bps <= 10000, ErrorCode::InvalidDividendShare);
+
+        let treasury = &mut ctx.accounts.treasury;
+        let old_share_bps = treasury.dividend_share_bps;
+        treasury.dividend_share_bps = dividend_share_bps;
+
+        emit!(DividendShareUpdated {
+            old_share_bps,
+            new_share_bps: dividend_share_bps,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Fund the next dividend epoch from the treasury and commit to its Merkle root.
+    /// Each leaf is `keccak(holder || amount_le)`, see `merkle::claim_leaf`.
+    pub fn create_dividend_distribution(
+        ctx: Context<CreateDividendDistribution>,
+        merkle_root: [u8; 32],
+        usdc_amount: u64,
+    ) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        require!(!treasury.fully_paused(), ErrorCode::Paused);
+        treasury.require_enabled(GatedInstruction::CreateDividendDistribution)?;
+        require!(
+            treasury.dividend_share_bps > 0,
+            ErrorCode::DividendsDisabled
+        );
+        require!(usdc_amount > 0, ErrorCode::InvalidAmount);
+
+        let clock = Clock::get()?;
+        spend_usdc(
+            treasury,
+            UsdcSpend::Dividends,
+            &ctx.accounts.treasury_usdc,
+            ctx.accounts.dividend_vault.to_account_info(),
+            &ctx.accounts.token_program,
+            usdc_amount,
+            clock.unix_timestamp,
+        )?;
+
+        let distribution = &mut ctx.accounts.distribution;
+        distribution.epoch = treasury.dividend_epoch;
+        distribution.merkle_root = merkle_root;
+        distribution.total_usdc = usdc_amount;
+        distribution.claimed_usdc = 0;
+        distribution.created_at = clock.unix_timestamp;
+        distribution.bump = ctx.bumps.distribution;
+
+        treasury.dividend_epoch = treasury
+            .dividend_epoch
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(DividendDistributionCreated {
+            epoch: distribution.epoch,
+            merkle_root,
+            usdc_amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Claim a holder's dividend for one epoch by proving inclusion in its Merkle root
+    pub fn claim_dividend(
+        ctx: Context<ClaimDividend>,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        require!(!ctx.accounts.treasury.fully_paused(), ErrorCode::Paused);
+        ctx.accounts
+            .treasury
+            .require_enabled(GatedInstruction::ClaimDividend)?;
+
+        let claimant = ctx.accounts.claimant.key();
+        let distribution = &mut ctx.accounts.distribution;
+        require!(
+            merkle::verify(
+                &proof,
+                &distribution.merkle_root,
+                merkle::claim_leaf(&claimant, amount)
+            ),
+            ErrorCode::InvalidMerkleProof
+        );
+
+        let claimed_usdc = distribution
+            .claimed_usdc
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(
+            claimed_usdc <= distribution.total_usdc,
+            ErrorCode::ExceedsAllocation
+        );
+        distribution.claimed_usdc = claimed_usdc;
+
+        let epoch_bytes = distribution.epoch.to_le_bytes();
+        let seeds = &[
+            b"dividend".as_ref(),
+            epoch_bytes.as_ref(),
+            &[distribution.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.dividend_vault.to_account_info(),
+            to: ctx.accounts.claimant_usdc.to_account_info(),
+            authority: distribution.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        transfer_tokens(cpi_ctx, amount)?;
+
+        let clock = Clock::get()?;
+        let claim = &mut ctx.accounts.claim;
+        claim.distribution = distribution.key();
+        claim.claimant = claimant;
+        claim.amount = amount;
+        claim.claimed_at = clock.unix_timestamp;
+
+        emit!(DividendClaimed {
+            epoch: distribution.epoch,
+            claimant,
+            amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Require a second signer for buybacks and liquidity adds above `threshold_usdc`:
+    /// the admin, another registered operator or the dedicated `co_signer`. A
+    /// threshold of zero disables co-signing.
+    pub fn set_cosign_policy(
+        ctx: Context<UpdateConfig>,
+        co_signer: Pubkey,
+        threshold_usdc: u64,
+    ) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        let treasury = &mut ctx.accounts.treasury;
+        require!(
+            threshold_usdc == 0
+                || (co_signer != Pubkey::default()
+                    && co_signer != treasury.authority
+                    && co_signer != treasury.operator),
+            ErrorCode::InvalidCoSigner
+        );
+
+        treasury.co_signer = co_signer;
+        treasury.cosign_threshold_usdc = threshold_usdc;
+
+        emit!(CosignPolicyUpdated {
+            co_signer,
+            threshold_usdc,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set a dedicated warm-up applied after `resume`; zero falls back to the cooldown
+    pub fn set_post_resume_delay(
+        ctx: Context<UpdateConfig>,
+        post_resume_delay_seconds: i64,
+    ) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        require!(post_resume_delay_seconds >= 0, ErrorCode::InvalidDelay);
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.post_resume_delay_seconds = post_resume_delay_seconds;
+
+        emit!(ConfigUpdated {
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set a dedicated cooldown between liquidity operations; zero falls back to
+    /// `cooldown_seconds`, which always applies to buybacks
+    pub fn set_liquidity_cooldown(
+        ctx: Context<UpdateConfig>,
+        liquidity_cooldown_seconds: i64,
+    ) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        require!(liquidity_cooldown_seconds >= 0, ErrorCode::InvalidDelay);
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.liquidity_cooldown_seconds = liquidity_cooldown_seconds;
+
+        emit!(ConfigUpdated {
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Create the bug bounty escrow vault owned by the treasury
+    pub fn initialize_bounty_escrow(ctx: Context<InitializeBountyEscrow>) -> Result<()> {
+        emit!(BountyEscrowInitialized {
+            vault: ctx.accounts.bounty_vault.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set the per-payout cap and the delay between proposing and paying a bounty.
+    /// Under a configuration delay this can only tighten the policy; raising the cap
+    /// or shortening the timelock goes through `propose_config`.
+    pub fn set_bounty_policy(
+        ctx: Context<UpdateConfig>,
+        max_payout_usdc: u64,
+        timelock_seconds: i64,
+    ) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        require!(timelock_seconds >= 0, ErrorCode::InvalidDelay);
+
+        let treasury = &mut ctx.accounts.treasury;
+        require!(
+            treasury.config_delay_seconds == 0
+                || (max_payout_usdc <= treasury.bounty_max_payout_usdc
+                    && timelock_seconds >= treasury.bounty_timelock_seconds),
+            ErrorCode::ConfigChangeTimelocked
+        );
+        treasury.bounty_max_payout_usdc = max_payout_usdc;
+        treasury.bounty_timelock_seconds = timelock_seconds;
+
+        emit!(BountyPolicyUpdated {
+            max_payout_usdc,
+            timelock_seconds,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Move USDC from the reserve allocation into the bounty escrow
+    pub fn fund_bounty_escrow(ctx: Context<FundBountyEscrow>, usdc_amount: u64) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        require!(!treasury.fully_paused(), ErrorCode::Paused);
+        treasury.require_enabled(GatedInstruction::FundBountyEscrow)?;
+        require!(usdc_amount > 0, ErrorCode::InvalidAmount);
+
+        let clock = Clock::get()?;
+        spend_usdc(
+            treasury,
+            UsdcSpend::Bounty,
+            &ctx.accounts.treasury_usdc,
+            ctx.accounts.bounty_vault.to_account_info(),
+            &ctx.accounts.token_program,
+            usdc_amount,
+            clock.unix_timestamp,
+        )?;
+
+        emit!(BountyEscrowFunded {
+            usdc_amount,
+            total_funded: treasury.total_bounty_funded_usdc,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Queue a bounty payout; it becomes executable after the bounty timelock
+    pub fn propose_bounty_payout(
+        ctx: Context<ProposeBountyPayout>,
+        usdc_amount: u64,
+    ) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        require!(usdc_amount > 0, ErrorCode::InvalidAmount);
+        require!(
+            usdc_amount <= treasury.bounty_max_payout_usdc,
+            ErrorCode::ExceedsBountyCap
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let payout = &mut ctx.accounts.payout;
+        payout.index = treasury.bounty_payout_count;
+        payout.recipient = ctx.accounts.recipient_usdc.key();
+        payout.usdc_amount = usdc_amount;
+        payout.proposed_at = now;
+        payout.executable_at = now
+            .checked_add(treasury.bounty_timelock_seconds)
+            .ok_or(ErrorCode::Overflow)?;
+        payout.executed = false;
+        payout.bump = ctx.bumps.payout;
+
+        treasury.bounty_payout_count = treasury
+            .bounty_payout_count
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(BountyPayoutProposed {
+            index: payout.index,
+            recipient: payout.recipient,
+            usdc_amount,
+            executable_at: payout.executable_at,
+        });
+
+        Ok(())
+    }
+
+    /// Pay out a queued bounty from the escrow once its timelock has passed
+    pub fn execute_bounty_payout(ctx: Context<ExecuteBountyPayout>) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        require!(!treasury.fully_paused(), ErrorCode::Paused);
+        treasury.require_enabled(GatedInstruction::ExecuteBountyPayout)?;
+
+        let payout = &mut ctx.accounts.payout;
+        require!(!payout.executed, ErrorCode::BountyAlreadyExecuted);
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= payout.executable_at, ErrorCode::TimelockActive);
+
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.bounty_vault.to_account_info(),
+            to: ctx.accounts.recipient_usdc.to_account_info(),
+            authority: treasury.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        transfer_tokens(cpi_ctx, payout.usdc_amount)?;
+
+        payout.executed = true;
+        treasury.total_bounty_paid_usdc = treasury
+            .total_bounty_paid_usdc
+            .checked_add(payout.usdc_amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(BountyPaid {
+            index: payout.index,
+            recipient: payout.recipient,
+            usdc_amount: payout.usdc_amount,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Cancel a queued bounty payout that has not been executed
+    pub fn cancel_bounty_payout(ctx: Context<CancelBountyPayout>) -> Result<()> {
+        let payout = &ctx.accounts.payout;
+        require!(!payout.executed, ErrorCode::BountyAlreadyExecuted);
+
+        emit!(BountyPayoutCancelled {
+            index: payout.index,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set how long the treasury may sit idle with available budget before
+    /// `ping_watchdog` reports it as stalled; zero disables the watchdog
+    pub fn set_watchdog_threshold(
+        ctx: Context<UpdateConfig>,
+        watchdog_threshold_seconds: i64,
+    ) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        require!(watchdog_threshold_seconds >= 0, ErrorCode::InvalidDelay);
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.watchdog_threshold_seconds = watchdog_threshold_seconds;
+
+        emit!(ConfigUpdated {
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless check for operational neglect: flags the treasury and emits
+    /// `TreasuryStalled` if no buyback or liquidity operation ran for longer than the
+    /// watchdog threshold while budget was available
+    pub fn ping_watchdog(ctx: Context<PingWatchdog>) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        if treasury.watchdog_threshold_seconds == 0
+            || (treasury.is_paused(PauseFlag::Buybacks) && treasury.is_paused(PauseFlag::Liquidity))
+        {
+            return Ok(());
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        let idle_seconds = now - treasury.last_operation_timestamp;
+        let buyback_budget = treasury.remaining_buyback_budget()?;
+        let liquidity_budget = treasury.remaining_liquidity_budget()?;
+
+        if idle_seconds <= treasury.watchdog_threshold_seconds
+            || (buyback_budget == 0 && liquidity_budget == 0)
+        {
+            return Ok(());
+        }
+
+        treasury.stalled = true;
+
+        emit!(TreasuryStalled {
+            idle_seconds,
+            buyback_budget,
+            liquidity_budget,
+            caller: ctx.accounts.caller.key(),
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Set how many slots an escrowed operation has to settle before it can be refunded
+    pub fn set_settlement_window(
+        ctx: Context<UpdateConfig>,
+        settlement_window_slots: u64,
+    ) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        require!(settlement_window_slots > 0, ErrorCode::InvalidDelay);
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.settlement_window_slots = settlement_window_slots;
+
+        emit!(ConfigUpdated {
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// First phase of a two-phase operation: move USDC from the treasury vault into a
+    /// per-operation escrow. Budgets are charged now and released again on refund.
+    pub fn open_operation(
+        ctx: Context<OpenOperation>,
+        kind: OperationKind,
+        usdc_amount: u64,
+        venue: Venue,
+    ) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.require_not_paused(kind.pause_flag())?;
+        treasury.require_enabled(kind.gate())?;
+        require!(usdc_amount > 0, ErrorCode::InvalidAmount);
+        ctx.accounts
+            .operator_stats
+            .require_scope(kind, Some(venue))?;
+
+        let clock = Clock::get()?;
+        treasury.require_ready(kind, clock.unix_timestamp)?;
+        require!(
+            usdc_amount <= treasury.cycle_limit_usdc(),
+            ErrorCode::ExceedsMaxPerCycle
+        );
+        treasury.require_co_signer(
+            usdc_amount,
+            ctx.accounts.operator.key(),
+            ctx.accounts.co_signer.as_ref().map(|s| s.key()),
+            ctx.accounts.co_signer_operator.as_deref(),
+        )?;
+
+        // The escrowed amount is charged against the allocation up front
+        spend_usdc(
+            treasury,
+            kind.into(),
+            &ctx.accounts.treasury_usdc,
+            ctx.accounts.escrow.to_account_info(),
+            &ctx.accounts.token_program,
+            usdc_amount,
+            clock.unix_timestamp,
+        )?;
+        treasury.record_operation(kind, clock.unix_timestamp);
+        treasury.stalled = false;
+
+        let operation = &mut ctx.accounts.operation;
+        operation.index = treasury.operation_count;
+        operation.kind = kind;
+        operation.venue = venue;
+        operation.usdc_amount = usdc_amount;
+        operation.initiator = ctx.accounts.operator.key();
+        operation.funding_vault = ctx.accounts.treasury_usdc.key();
+        operation.opened_slot = clock.slot;
+        operation.opened_at = clock.unix_timestamp;
+        operation.deadline_slot = clock
+            .slot
+            .checked_add(treasury.settlement_window_slots)
+            .ok_or(ErrorCode::Overflow)?;
+        operation.bump = ctx.bumps.operation;
+
+        treasury.operation_count = treasury
+            .operation_count
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(OperationOpened {
+            index: operation.index,
+            kind,
+            venue,
+            usdc_amount,
+            deadline_slot: operation.deadline_slot,
+            timestamp: clock.unix_timestamp,
+        });
+        if let Some(alert) = treasury.large_operation_alert(
+            AlertedOperation::EscrowOpened,
+            usdc_amount,
+            operation.initiator,
+            ctx.accounts.escrow.key(),
+            ctx.accounts.treasury_usdc.amount,
+            &clock,
+        ) {
+            emit!(alert);
+        }
+
+        Ok(())
+    }
+
+    /// Second phase: release the escrow into the USDC vault of a registered pool on
+    /// the operation's venue before the deadline and close the operation. An
+    /// operation with a committed receipt stays open until the venue's delivery is
+    /// swept with `settle_receipt`.
+    pub fn settle_operation(ctx: Context<SettleOperation>) -> Result<()> {
+        let clock = Clock::get()?;
+        let operation = &ctx.accounts.operation;
+        require!(
+            clock.slot <= operation.deadline_slot,
+            ErrorCode::OperationExpired
+        );
+        // The scope may have been narrowed since the operation was opened
+        ctx.accounts
+            .operator_stats
+            .require_scope(operation.kind, Some(operation.venue))?;
+
+        let treasury = &ctx.accounts.treasury;
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let signer = &[&seeds[..]];
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.escrow.to_account_info(),
+            to: ctx.accounts.destination_usdc.to_account_info(),
+            authority: treasury.to_account_info(),
+        };
+        transfer_tokens(
+            CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, signer),
+            ctx.accounts.escrow.amount,
+        )?;
+
+        let cpi_accounts = CloseAccount {
+            account: ctx.accounts.escrow.to_account_info(),
+            destination: ctx.accounts.operator.to_account_info(),
+            authority: treasury.to_account_info(),
+        };
+        token_interface::close_account(CpiContext::new_with_signer(
+            cpi_program,
+            cpi_accounts,
+            signer,
+        ))?;
+
+        let stats = &mut ctx.accounts.operator_stats;
+        stats.successful_operations = stats
+            .successful_operations
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+        stats.total_volume_usdc = stats
+            .total_volume_usdc
+            .checked_add(operation.usdc_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        stats.last_active_timestamp = clock.unix_timestamp;
+
+        emit!(OperationSettled {
+            index: operation.index,
+            kind: operation.kind,
+            usdc_amount: operation.usdc_amount,
+            destination: ctx.accounts.destination_usdc.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        if operation.has_receipt() {
+            ctx.accounts.operation.released = true;
+        } else {
+            ctx.accounts
+                .operation
+                .close(ctx.accounts.operator.to_account_info())?;
+        }
+
+        Ok(())
+    }
+
+    /// Permissionless: return the escrow of an operation that missed its settlement
+    /// deadline to the vault it was funded from and close the operation. Only the
+    /// escrowed `usdc_amount` is refunded against the budgets; anything else sent to
+    /// the escrow is swept to the same vault without crediting an allocation.
+    pub fn refund_expired(ctx: Context<RefundExpired>) -> Result<()> {
+        let clock = Clock::get()?;
+        let operation = &ctx.accounts.operation;
+        require!(
+            clock.slot > operation.deadline_slot,
+            ErrorCode::OperationNotExpired
+        );
+
+        let treasury = &mut ctx.accounts.treasury;
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let signer = &[&seeds[..]];
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+
+        let refunded = operation.usdc_amount;
+        let surplus = ctx
+            .accounts
+            .escrow
+            .amount
+            .checked_sub(refunded)
+            .ok_or(ErrorCode::Overflow)?;
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.escrow.to_account_info(),
+            to: ctx.accounts.treasury_usdc.to_account_info(),
+            authority: treasury.to_account_info(),
+        };
+        transfer_tokens(
+            CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, signer),
+            refunded,
+        )?;
+        if surplus > 0 {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.escrow.to_account_info(),
+                to: ctx.accounts.treasury_usdc.to_account_info(),
+                authority: treasury.to_account_info(),
+            };
+            transfer_tokens(
+                CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, signer),
+                surplus,
+            )?;
+        }
+
+        let cpi_accounts = CloseAccount {
+            account: ctx.accounts.escrow.to_account_info(),
+            destination: ctx.accounts.initiator.to_account_info(),
+            authority: treasury.to_account_info(),
+        };
+        token_interface::close_account(CpiContext::new_with_signer(
+            cpi_program,
+            cpi_accounts,
+            signer,
+        ))?;
+
+        treasury.release_operation(operation.kind, operation.usdc_amount, operation.opened_at)?;
+
+        if operation.has_receipt() {
+            let (Some(receipt), Some(receipt_mint), Some(destination), Some(receipt_token_program)) = (
+                &ctx.accounts.receipt,
+                &ctx.accounts.receipt_mint,
+                &ctx.accounts.receipt_destination,
+                &ctx.accounts.receipt_token_program,
+            ) else {
+                return err!(ErrorCode::ReceiptMismatch);
+            };
+            require_keys_eq!(receipt.key(), operation.receipt, ErrorCode::ReceiptMismatch);
+            // Nothing is owed before release; anything sent early goes to the treasury
+            sweep_receipt(
+                treasury,
+                receipt,
+                receipt_mint,
+                destination,
+                ctx.accounts.initiator.to_account_info(),
+                receipt_token_program,
+            )?;
+        }
+
+        let stats = &mut ctx.accounts.operator_stats;
+        stats.expired_operations = stats
+            .expired_operations
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(OperationRefunded {
+            index: operation.index,
+            kind: operation.kind,
+            usdc_amount: refunded,
+            surplus_usdc: surplus,
+            caller: ctx.accounts.caller.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// For venues that cannot settle in one transaction: commit, before the escrow
+    /// is released, the one-time treasury-owned account the venue must deliver the
+    /// output to. `settle_receipt` later sweeps exactly that account, so no
+    /// off-chain party holds the funds in flight.
+    pub fn commit_operation_receipt(
+        ctx: Context<CommitOperationReceipt>,
+        min_output: u64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let operation = &mut ctx.accounts.operation;
+        require!(!operation.has_receipt(), ErrorCode::ReceiptAlreadyCommitted);
+        require!(
+            clock.slot <= operation.deadline_slot,
+            ErrorCode::OperationExpired
+        );
+        require!(min_output > 0, ErrorCode::InvalidAmount);
+        if operation.kind == OperationKind::Buyback {
+            require_keys_eq!(
+                ctx.accounts.output_mint.key(),
+                ctx.accounts.treasury.token_mint,
+                ErrorCode::InvalidTokenMint
+            );
+        }
+
+        operation.receipt = ctx.accounts.receipt.key();
+        operation.min_output = min_output;
+
+        emit!(OperationReceiptCommitted {
+            index: operation.index,
+            kind: operation.kind,
+            receipt: operation.receipt,
+            mint: ctx.accounts.output_mint.key(),
+            min_output,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless: sweep a released operation's receipt into the treasury and
+    /// close the operation. Settles once `min_output` arrived, or with whatever
+    /// arrived once the claim window (`settlement_window_slots` past the deadline)
+    /// has closed.
+    pub fn settle_receipt(ctx: Context<SettleReceipt>) -> Result<()> {
+        let clock = Clock::get()?;
+        let operation = &ctx.accounts.operation;
+        let received = ctx.accounts.receipt.amount;
+        let short = operation.require_settleable(
+            received,
+            clock.slot,
+            ctx.accounts.treasury.settlement_window_slots,
+        )?;
+
+        sweep_receipt(
+            &ctx.accounts.treasury,
+            &ctx.accounts.receipt,
+            &ctx.accounts.receipt_mint,
+            &ctx.accounts.destination,
+            ctx.accounts.initiator.to_account_info(),
+            &ctx.accounts.token_program,
+        )?;
+        if operation.kind == OperationKind::Buyback {
+            ctx.accounts.treasury.record_buyback_tokens(received)?;
+        }
+
+        emit!(OperationReceiptSettled {
+            index: operation.index,
+            kind: operation.kind,
+            received,
+            min_output: operation.min_output,
+            short,
+            destination: ctx.accounts.destination.key(),
+            caller: ctx.accounts.caller.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Register a key allowed to run two-phase operations and start its statistics
+    pub fn register_operator(ctx: Context<RegisterOperator>, operator: Pubkey) -> Result<()> {
+        let stats = &mut ctx.accounts.operator_stats;
+        stats.operator = operator;
+        stats.active = true;
+        stats.successful_operations = 0;
+        stats.skipped_operations = 0;
+        stats.expired_operations = 0;
+        stats.total_volume_usdc = 0;
+        stats.last_active_timestamp = 0;
+        stats.last_heartbeat_timestamp = 0;
+        stats.heartbeat_count = 0;
+        stats.allowed_kinds = u8::MAX;
+        stats.allowed_venues = u8::MAX;
+        stats.bump = ctx.bumps.operator_stats;
+
+        emit!(OperatorUpdated {
+            operator,
+            active: true,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Revoke or reinstate an operator. Statistics are kept either way.
+    pub fn set_operator_active(ctx: Context<SetOperatorActive>, active: bool) -> Result<()> {
+        let stats = &mut ctx.accounts.operator_stats;
+        stats.active = active;
+
+        emit!(OperatorUpdated {
+            operator: stats.operator,
+            active,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Restrict an operator to the operation kinds and venues in the given bitmasks
+    /// (bit `n` = the `n`-th `OperationKind` / `Venue` variant)
+    pub fn set_operator_scope(
+        ctx: Context<SetOperatorActive>,
+        allowed_kinds: u8,
+        allowed_venues: u8,
+    ) -> Result<()> {
+        let stats = &mut ctx.accounts.operator_stats;
+        stats.allowed_kinds = allowed_kinds;
+        stats.allowed_venues = allowed_venues;
+
+        emit!(OperatorScopeUpdated {
+            operator: stats.operator,
+            allowed_kinds,
+            allowed_venues,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Operator crank that found the buyback path blocked: record the skip on-chain
+    /// instead of failing, so it counts towards the operator's statistics. Counts at
+    /// most once per cooldown window per operator.
+    pub fn report_skip(ctx: Context<ReportSkip>) -> Result<()> {
+        let clock = Clock::get()?;
+        let treasury = &ctx.accounts.treasury;
+        let reason = treasury
+            .buyback_skip_reason(clock.unix_timestamp)?
+            .ok_or(ErrorCode::NothingToSkip)?;
+
+        let stats = &mut ctx.accounts.operator_stats;
+        stats.record_skip(clock.unix_timestamp, treasury.cooldown_seconds)?;
+
+        emit!(SkippedOperation {
+            instruction: GatedInstruction::ExecuteBuyback,
+            reason,
+            caller: ctx.accounts.operator.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// View: an operator's track record
+    pub fn get_operator_stats(ctx: Context<ViewOperator>) -> Result<OperatorStats> {
+        Ok(ctx.accounts.operator_stats.stats())
+    }
+
+    /// Set how much SOL must accumulate before the conversion crank may run
+    pub fn set_min_sol_to_convert(
+        ctx: Context<UpdateConfig>,
+        min_sol_to_convert: u64,
+    ) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.min_sol_to_convert = min_sol_to_convert;
+
+        emit!(ConfigUpdated {
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Configure the SOL/USD price feed used for USD-denominated accounting
+    pub fn set_oracle(
+        ctx: Context<UpdateConfig>,
+        sol_usd_oracle: Pubkey,
+        oracle_max_age_seconds: i64,
+    ) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        require!(oracle_max_age_seconds > 0, ErrorCode::InvalidDelay);
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.sol_usd_oracle = sol_usd_oracle;
+        treasury.oracle_max_age_seconds = oracle_max_age_seconds;
+
+        emit!(ConfigUpdated {
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Create the treasury-owned escrow that holds `mint` between the legs of a
+    /// multi-step route on `venue`
+    pub fn initialize_route_escrow(
+        ctx: Context<InitializeRouteEscrow>,
+        venue: Venue,
+    ) -> Result<()> {
+        emit!(RouteEscrowInitialized {
+            venue,
+            mint: ctx.accounts.mint.key(),
+            escrow: ctx.accounts.route_escrow.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless: move whatever a partial route left in an intermediate escrow
+    /// back to the treasury vault. Residual wrapped SOL is queued for conversion again.
+    pub fn sweep_route_escrow(ctx: Context<SweepRouteEscrow>, venue: Venue) -> Result<()> {
+        let amount = ctx.accounts.route_escrow.amount;
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        let treasury = &mut ctx.accounts.treasury;
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.route_escrow.to_account_info(),
+            to: ctx.accounts.treasury_vault.to_account_info(),
+            authority: treasury.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        transfer_tokens(cpi_ctx, amount)?;
+
+        let mint = ctx.accounts.route_escrow.mint;
+        if mint == spl_token::native_mint::ID {
+            treasury.sol_pending_conversion = treasury
+                .sol_pending_conversion
+                .checked_add(amount)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        emit!(RouteResidualSwept {
+            venue,
+            mint,
+            amount,
+            caller: ctx.accounts.caller.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set the per-epoch burn target and the epoch length
+    pub fn set_burn_target(
+        ctx: Context<UpdateConfig>,
+        burn_target_per_epoch: u64,
+        epoch_duration_seconds: i64,
+    ) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        require!(epoch_duration_seconds > 0, ErrorCode::InvalidDelay);
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.burn_target_per_epoch = burn_target_per_epoch;
+        treasury.epoch_duration_seconds = epoch_duration_seconds;
+
+        emit!(ConfigUpdated {
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless: close the current epoch once it has ended, record target vs
+    /// achieved burns in its `EpochStats`, carry any shortfall over to the next epoch
+    /// and tip the caller from the treasury's spare lamports
+    pub fn advance_epoch(ctx: Context<AdvanceEpoch>) -> Result<()> {
+        let clock = Clock::get()?;
+        let treasury = &mut ctx.accounts.treasury;
+        let epoch_end = treasury
+            .epoch_start_timestamp
+            .checked_add(treasury.epoch_duration_seconds)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(clock.unix_timestamp >= epoch_end, ErrorCode::EpochNotEnded);
+
+        let burn_target = treasury.epoch_burn_target()?;
+        let shortfall = burn_target.saturating_sub(treasury.epoch_tokens_burned);
+
+        let stats = &mut ctx.accounts.epoch_stats;
+        stats.epoch = treasury.current_epoch;
+        stats.start_timestamp = treasury.epoch_start_timestamp;
+        stats.end_timestamp = clock.unix_timestamp;
+        stats.burn_target = burn_target;
+        stats.carried_over = treasury.burn_carryover;
+        stats.tokens_burned = treasury.epoch_tokens_burned;
+        stats.shortfall = shortfall;
+        stats.funnel = treasury.epoch_funnel();
+        stats.bump = ctx.bumps.epoch_stats;
+
+        treasury.burn_carryover = shortfall;
+        treasury.epoch_tokens_burned = 0;
+        treasury.start_funnel_epoch();
+        treasury.current_epoch = treasury
+            .current_epoch
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+        treasury.epoch_start_timestamp = clock.unix_timestamp;
+
+        // Never dip into the treasury's rent-exempt minimum
+        let treasury_info = treasury.to_account_info();
+        let rent_floor = Rent::get()?.minimum_balance(treasury_info.data_len());
+        let tip = treasury
+            .epoch_crank_tip_lamports
+            .min(treasury_info.lamports().saturating_sub(rent_floor));
+        if tip > 0 {
+            **treasury_info.try_borrow_mut_lamports()? -= tip;
+            **ctx.accounts.caller.try_borrow_mut_lamports()? += tip;
+        }
+
+        emit!(EpochAdvanced {
+            epoch: stats.epoch,
+            burn_target,
+            tokens_burned: stats.tokens_burned,
+            shortfall,
+            caller: ctx.accounts.caller.key(),
+            tip_lamports: tip,
+            timestamp: clock.unix_timestamp,
+        });
+        emit!(EpochFunnelRecorded {
+            epoch: stats.epoch,
+            funnel: stats.funnel,
+            timestamp: clock.unix_timestamp,
+        });
+        if treasury.current_epoch == treasury.launch_mode_until_epoch {
+            emit!(LaunchModeEnded {
+                epoch: treasury.current_epoch,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Set the budget epoch length. Turning epochs on opens the first epoch now;
+    /// turning them off returns the budgets to lifetime totals.
+    pub fn set_budget_epoch_duration(
+        ctx: Context<UpdateConfig>,
+        budget_epoch_duration_seconds: i64,
+    ) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        require!(budget_epoch_duration_seconds >= 0, ErrorCode::InvalidDelay);
+
+        let now = Clock::get()?.unix_timestamp;
+        let treasury = &mut ctx.accounts.treasury;
+        if budget_epoch_duration_seconds == 0 {
+            treasury.budget_epoch_start = 0;
+            treasury.epoch_base_usdc_converted = 0;
+            treasury.epoch_base_buybacks_usdc = 0;
+            treasury.epoch_base_liquidity_usdc = 0;
+            treasury.epoch_base_reserve_usdc = 0;
+            treasury.epoch_base_lp_fees_earned = 0;
+            treasury.epoch_base_dividends_usdc = 0;
+        } else if treasury.budget_epoch_duration_seconds == 0 {
+            treasury.start_budget_epoch(now);
+        }
+        treasury.budget_epoch_duration_seconds = budget_epoch_duration_seconds;
+
+        emit!(ConfigUpdated { timestamp: now });
+
+        Ok(())
+    }
+
+    /// Permissionless: close the budget epoch once it has run its length, record
+    /// what was converted and spent per bucket in its `Epoch` account, and reset
+    /// the buyback, liquidity and reserve budgets for the next one
+    pub fn roll_epoch(ctx: Context<RollEpoch>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let treasury = &mut ctx.accounts.treasury;
+        require!(
+            treasury.budget_epoch_duration_seconds > 0,
+            ErrorCode::BudgetEpochsDisabled
+        );
+        let epoch_end = treasury
+            .budget_epoch_start
+            .checked_add(treasury.budget_epoch_duration_seconds)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(now >= epoch_end, ErrorCode::EpochNotEnded);
+
+        let epoch = &mut ctx.accounts.epoch;
+        epoch.index = treasury.budget_epoch;
+        epoch.start_timestamp = treasury.budget_epoch_start;
+        epoch.end_timestamp = now;
+        epoch.usdc_converted = treasury
+            .total_usdc_converted
+            .saturating_sub(treasury.epoch_base_usdc_converted);
+        epoch.buybacks_usdc = treasury
+            .total_buybacks_usdc
+            .saturating_sub(treasury.epoch_base_buybacks_usdc);
+        epoch.liquidity_usdc = treasury
+            .total_liquidity_usdc
+            .saturating_sub(treasury.epoch_base_liquidity_usdc);
+        epoch.reserve_usdc = treasury
+            .total_reserve_committed_usdc()
+            .saturating_sub(treasury.epoch_base_reserve_usdc);
+        epoch.bump = ctx.bumps.epoch;
+
+        treasury.budget_epoch = treasury
+            .budget_epoch
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+        treasury.start_budget_epoch(now);
+
+        emit!(EpochRolled {
+            epoch: epoch.index,
+            usdc_converted: epoch.usdc_converted,
+            buybacks_usdc: epoch.buybacks_usdc,
+            liquidity_usdc: epoch.liquidity_usdc,
+            reserve_usdc: epoch.reserve_usdc,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Configure the reserve drawdown tiers. The tier-1 stream starts on first configuration.
+    pub fn set_reserve_policy(
+        ctx: Context<UpdateConfig>,
+        guardian: Pubkey,
+        governance: Pubkey,
+        ops_monthly_limit_usdc: u64,
+    ) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.guardian = guardian;
+        treasury.governance = governance;
+        treasury.reserve_ops_monthly_limit_usdc = ops_monthly_limit_usdc;
+        let clock = Clock::get()?;
+        if treasury.reserve_ops_stream_start == 0 {
+            treasury.reserve_ops_stream_start = clock.unix_timestamp;
+        }
+
+        emit!(ReservePolicyUpdated {
+            guardian,
+            governance,
+            ops_monthly_limit_usdc,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Draw USDC from the reserve under the rules of `tier`:
+    /// operations spending is limited to the streamed allowance, market defense
+    /// needs the guardian's co-signature and a full drawdown needs governance.
+    /// The destination must be an active address book entry.
+    pub fn draw_reserve(
+        ctx: Context<DrawReserve>,
+        tier: ReserveTier,
+        usdc_amount: u64,
+    ) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        require!(!treasury.fully_paused(), ErrorCode::Paused);
+        require!(usdc_amount > 0, ErrorCode::InvalidAmount);
+
+        let clock = Clock::get()?;
+        ctx.accounts.entry.require_active(clock.unix_timestamp)?;
+        match tier {
+            ReserveTier::Operations => {
+                require!(
+                    usdc_amount <= treasury.streamed_reserve_ops_allowance(clock.unix_timestamp)?,
+                    ErrorCode::ExceedsStreamedAllowance
+                );
+                treasury.total_reserve_ops_usdc = treasury
+                    .total_reserve_ops_usdc
+                    .checked_add(usdc_amount)
+                    .ok_or(ErrorCode::Overflow)?;
+            }
+            ReserveTier::MarketDefense => {
+                let guardian = ctx.accounts.guardian.as_ref().map(|s| s.key());
+                require!(
+                    treasury.guardian != Pubkey::default() && guardian == Some(treasury.guardian),
+                    ErrorCode::GuardianRequired
+                );
+            }
+            ReserveTier::FullDrawdown => {
+                let governance = ctx.accounts.governance.as_ref().map(|s| s.key());
+                require!(
+                    treasury.governance != Pubkey::default()
+                        && governance == Some(treasury.governance),
+                    ErrorCode::GovernanceRequired
+                );
+            }
+        }
+        spend_usdc(
+            treasury,
+            UsdcSpend::Reserve,
+            &ctx.accounts.treasury_usdc,
+            ctx.accounts.destination_usdc.to_account_info(),
+            &ctx.accounts.token_program,
+            usdc_amount,
+            clock.unix_timestamp,
+        )?;
+
+        emit!(ReserveDrawn {
+            tier,
+            usdc_amount,
+            destination: ctx.accounts.destination_usdc.key(),
+            label: ctx.accounts.entry.label.clone(),
+            timestamp: clock.unix_timestamp,
+        });
+        if let Some(alert) = treasury.large_operation_alert(
+            AlertedOperation::ReserveDraw,
+            usdc_amount,
+            ctx.accounts.authority.key(),
+            ctx.accounts.destination_usdc.key(),
+            ctx.accounts.treasury_usdc.amount,
+            &clock,
+        ) {
+            emit!(alert);
+        }
+
+        Ok(())
+    }
+
+    /// Add `destination_usdc` to the address book under `label`. Reserve draws and
+    /// withdrawals can target it once `ADDRESS_BOOK_TIMELOCK_SECONDS` have passed.
+    pub fn add_address_book_entry(ctx: Context<AddAddressBookEntry>, label: String) -> Result<()> {
+        require!(
+            !label.is_empty() && label.len() <= MAX_ADDRESS_LABEL_LEN,
+            ErrorCode::InvalidAddressLabel
+        );
+        let clock = Clock::get()?;
+        let entry = &mut ctx.accounts.entry;
+        entry.destination = ctx.accounts.destination_usdc.key();
+        entry.label = label;
+        entry.added_at = clock.unix_timestamp;
+        entry.active_at = clock
+            .unix_timestamp
+            .checked_add(ADDRESS_BOOK_TIMELOCK_SECONDS)
+            .ok_or(ErrorCode::Overflow)?;
+        entry.bump = ctx.bumps.entry;
+
+        emit!(AddressBookEntryAdded {
+            destination: entry.destination,
+            label: entry.label.clone(),
+            active_at: entry.active_at,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Remove an address book entry; withdrawals queued to it can no longer execute
+    pub fn remove_address_book_entry(ctx: Context<RemoveAddressBookEntry>) -> Result<()> {
+        emit!(AddressBookEntryRemoved {
+            destination: ctx.accounts.entry.destination,
+            label: ctx.accounts.entry.label.clone(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Close an entry of the reserve allowlist the address book replaced
+    pub fn remove_reserve_destination(ctx: Context<RemoveReserveDestination>) -> Result<()> {
+        emit!(ReserveDestinationUpdated {
+            destination: ctx.accounts.entry.destination,
+            registered: false,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set the wait between queueing a reserve withdrawal and executing it
+    pub fn set_reserve_withdrawal_delay(
+        ctx: Context<UpdateConfig>,
+        reserve_withdrawal_delay_seconds: i64,
+    ) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        require!(
+            reserve_withdrawal_delay_seconds >= 0,
+            ErrorCode::InvalidDelay
+        );
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.reserve_withdrawal_delay_seconds = reserve_withdrawal_delay_seconds;
+
+        emit!(ConfigUpdated {
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Queue a withdrawal of `usdc_amount` from the reserve allocation to an active
+    /// address book entry, executable after `reserve_withdrawal_delay_seconds`
+    pub fn queue_reserve_withdrawal(
+        ctx: Context<QueueReserveWithdrawal>,
+        usdc_amount: u64,
+    ) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        require!(usdc_amount > 0, ErrorCode::InvalidAmount);
+        require!(
+            usdc_amount <= treasury.remaining_reserve_budget()?,
+            ErrorCode::ExceedsAllocation
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.entry.require_active(now)?;
+        let withdrawal = &mut ctx.accounts.withdrawal;
+        withdrawal.index = treasury.reserve_withdrawal_count;
+        withdrawal.destination = ctx.accounts.entry.destination;
+        withdrawal.usdc_amount = usdc_amount;
+        withdrawal.queued_at = now;
+        withdrawal.eta = now
+            .checked_add(treasury.reserve_withdrawal_delay_seconds)
+            .ok_or(ErrorCode::Overflow)?;
+        withdrawal.bump = ctx.bumps.withdrawal;
+        treasury.reserve_withdrawal_count = treasury
+            .reserve_withdrawal_count
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(ReserveWithdrawalQueued {
+            index: withdrawal.index,
+            destination: withdrawal.destination,
+            label: ctx.accounts.entry.label.clone(),
+            usdc_amount,
+            eta: withdrawal.eta,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Execute a queued reserve withdrawal once its delay has passed, provided the
+    /// destination is still in the address book and the reserve budget still covers it
+    pub fn withdraw_reserve(ctx: Context<WithdrawReserve>) -> Result<()> {
+        let clock = Clock::get()?;
+        let treasury = &mut ctx.accounts.treasury;
+        require!(!treasury.fully_paused(), ErrorCode::Paused);
+        let withdrawal = &ctx.accounts.withdrawal;
+        require!(
+            clock.unix_timestamp >= withdrawal.eta,
+            ErrorCode::TimelockActive
+        );
+        let usdc_amount = withdrawal.usdc_amount;
+        spend_usdc(
+            treasury,
+            UsdcSpend::Reserve,
+            &ctx.accounts.treasury_usdc,
+            ctx.accounts.destination_usdc.to_account_info(),
+            &ctx.accounts.token_program,
+            usdc_amount,
+            clock.unix_timestamp,
+        )?;
+
+        emit!(ReserveWithdrawn {
+            index: withdrawal.index,
+            destination: withdrawal.destination,
+            label: ctx.accounts.entry.label.clone(),
+            usdc_amount,
+            total_reserve_spent: treasury.total_reserve_spent_usdc,
+            timestamp: clock.unix_timestamp,
+        });
+        if let Some(alert) = treasury.large_operation_alert(
+            AlertedOperation::ReserveDraw,
+            usdc_amount,
+            ctx.accounts.authority.key(),
+            withdrawal.destination,
+            ctx.accounts.treasury_usdc.amount,
+            &clock,
+        ) {
+            emit!(alert);
+        }
+
+        Ok(())
+    }
+
+    /// Drop a queued reserve withdrawal
+    pub fn cancel_reserve_withdrawal(ctx: Context<CancelReserveWithdrawal>) -> Result<()> {
+        emit!(ReserveWithdrawalCancelled {
+            index: ctx.accounts.withdrawal.index,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set the Kamino reserve idle reserve USDC is lent into. Switching reserves
+    /// needs the current position redeemed first.
+    pub fn set_kamino_reserve(ctx: Context<UpdateConfig>, kamino_reserve: Pubkey) -> Result<()> {
+        ctx.accounts.record_activity()?;
+
+        let treasury = &mut ctx.accounts.treasury;
+        require!(
+            treasury.kamino_collateral_amount == 0,
+            ErrorCode::LendingPositionOpen
+        );
+        treasury.kamino_reserve = kamino_reserve;
+
+        emit!(ConfigUpdated {
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Lend `usdc_amount` of unspent reserve allocation to the configured Kamino
+    /// reserve. The deposit stays committed against the reserve budget until it is
+    /// redeemed. The client refreshes the reserve earlier in the transaction.
+    pub fn deposit_reserve_to_kamino(ctx: Context<KaminoLending>, usdc_amount: u64) -> Result<()> {
+        let lending = ctx.accounts.reserve_accounts();
+        let treasury = &mut ctx.accounts.treasury;
+        let clock = Clock::get()?;
+        let adapter = lending::authorize_deposit(
+            treasury,
+            LendingVenue::Kamino,
+            usdc_amount,
+            clock.unix_timestamp,
+        )?;
+
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let usdc_before = ctx.accounts.treasury_usdc.amount;
+        let collateral_before = ctx.accounts.treasury_collateral.amount;
+        kamino::deposit_reserve_liquidity(&lending, usdc_amount, &[&seeds[..]])?;
+
+        ctx.accounts.treasury_usdc.reload()?;
+        ctx.accounts.treasury_collateral.reload()?;
+        let deposited = usdc_before.saturating_sub(ctx.accounts.treasury_usdc.amount);
+        let minted = ctx
+            .accounts
+            .treasury_collateral
+            .amount
+            .saturating_sub(collateral_before);
+        require!(
+            deposited == usdc_amount && minted > 0,
+            ErrorCode::UnexpectedLendingTransfer
+        );
+
+        treasury.settle_usdc_spend(
+            adapter.deposit_spend(),
+            usdc_amount,
+            deposited,
+            clock.unix_timestamp,
+        )?;
+        treasury.kamino_collateral_amount = treasury
+            .kamino_collateral_amount
+            .checked_add(minted)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(KaminoDeposited {
+            reserve: treasury.kamino_reserve,
+            usdc_amount: deposited,
+            collateral_minted: minted,
+            total_deposited_usdc: treasury.kamino_deposited_usdc,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Redeem `collateral_amount` kTokens for USDC. The redeemed share of the
+    /// deposited principal goes back to the reserve budget; anything above it is
+    /// interest. Allowed while paused so funds can be pulled back to the vault.
+    pub fn withdraw_reserve_from_kamino(
+        ctx: Context<KaminoLending>,
+        collateral_amount: u64,
+    ) -> Result<()> {
+        let lending = ctx.accounts.reserve_accounts();
+        let treasury = &mut ctx.accounts.treasury;
+        require!(
+            collateral_amount > 0 && collateral_amount <= treasury.kamino_collateral_amount,
+            ErrorCode::InvalidAmount
+        );
+        let principal = treasury.kamino_principal_share(collateral_amount);
+
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let usdc_before = ctx.accounts.treasury_usdc.amount;
+        let collateral_before = ctx.accounts.treasury_collateral.amount;
+        kamino::redeem_reserve_collateral(&lending, collateral_amount, &[&seeds[..]])?;
+
+        ctx.accounts.treasury_usdc.reload()?;
+        ctx.accounts.treasury_collateral.reload()?;
+        let received = ctx
+            .accounts
+            .treasury_usdc
+            .amount
+            .saturating_sub(usdc_before);
+        let redeemed = collateral_before.saturating_sub(ctx.accounts.treasury_collateral.amount);
+        require!(
+            redeemed == collateral_amount,
+            ErrorCode::UnexpectedLendingTransfer
+        );
+
+        treasury.kamino_collateral_amount -= collateral_amount;
+        treasury.kamino_deposited_usdc -= principal;
+
+        emit!(KaminoWithdrawn {
+            reserve: treasury.kamino_reserve,
+            collateral_amount,
+            usdc_received: received,
+            principal_usdc: principal,
+            interest_usdc: received.saturating_sub(principal),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Redeem `collateral_amount` kTokens worth only accrued interest and credit the USDC
+    /// received as newly available, the same as converted fees. The redemption's
+    /// own rate prices the kTokens left, which must still cover the deposited principal.
+    pub fn harvest_kamino_yield(ctx: Context<KaminoLending>, collateral_amount: u64) -> Result<()> {
+        let lending = ctx.accounts.reserve_accounts();
+        let treasury = &mut ctx.accounts.treasury;
+        require!(!treasury.fully_paused(), ErrorCode::Paused);
+        require!(
+            collateral_amount > 0 && collateral_amount < treasury.kamino_collateral_amount,
+            ErrorCode::InvalidAmount
+        );
+
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let usdc_before = ctx.accounts.treasury_usdc.amount;
+        let collateral_before = ctx.accounts.treasury_collateral.amount;
+        kamino::redeem_reserve_collateral(&lending, collateral_amount, &[&seeds[..]])?;
+
+        ctx.accounts.treasury_usdc.reload()?;
+        ctx.accounts.treasury_collateral.reload()?;
+        let received = ctx
+            .accounts
+            .treasury_usdc
+            .amount
+            .saturating_sub(usdc_before);
+        let redeemed = collateral_before.saturating_sub(ctx.accounts.treasury_collateral.amount);
+        require!(
+            redeemed == collateral_amount,
+            ErrorCode::UnexpectedLendingTransfer
+        );
+        let remaining = treasury.kamino_collateral_amount - collateral_amount;
+        require!(
+            lending::covers_principal(
+                remaining,
+                received,
+                collateral_amount,
+                treasury.kamino_deposited_usdc
+            ),
+            ErrorCode::HarvestExceedsInterest
+        );
+
+        treasury.kamino_collateral_amount = remaining;
+        treasury.record_yield(received)?;
+
+        emit!(YieldHarvested {
+            venue: LendingVenue::Kamino,
+            usdc_amount: received,
+            total_harvested_usdc: treasury.total_yield_harvested_usdc,
+            total_converted: treasury.total_usdc_converted,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Choose the venue reserve lending deposits go to. Switching venues needs every
+    /// open position closed first, so reserve USDC is only ever lent to one venue.
+    pub fn set_lending_venue(
+        ctx: Context<UpdateConfig>,
+        lending_venue: LendingVenue,
+    ) -> Result<()> {
+        ctx.accounts.record_activity()?;
+
+        let treasury = &mut ctx.accounts.treasury;
+        require!(
+            !treasury.has_open_lending_position(),
+            ErrorCode::LendingPositionOpen
+        );
+        treasury.lending_venue = lending_venue;
+
+        emit!(ConfigUpdated {
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set the MarginFi bank idle reserve USDC is lent into. Switching banks needs
+    /// the current position withdrawn first.
+    pub fn set_marginfi_bank(ctx: Context<UpdateConfig>, marginfi_bank: Pubkey) -> Result<()> {
+        ctx.accounts.record_activity()?;
+
+        let treasury = &mut ctx.accounts.treasury;
+        require!(
+            treasury.marginfi_deposited_usdc == 0,
+            ErrorCode::LendingPositionOpen
+        );
+        treasury.marginfi_bank = marginfi_bank;
+
+        emit!(ConfigUpdated {
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Create the MarginFi account the treasury lends through, with the treasury
+    /// as its authority
+    pub fn init_marginfi_account(ctx: Context<InitMarginFiAccount>) -> Result<()> {
+        let treasury = &ctx.accounts.treasury;
+        require!(
+            treasury.marginfi_account == Pubkey::default(),
+            ErrorCode::LendingPositionOpen
+        );
+
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        marginfi::initialize_account(
+            ctx.accounts.marginfi_group.to_account_info(),
+            ctx.accounts.marginfi_account.to_account_info(),
+            treasury.to_account_info(),
+            ctx.accounts.authority.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.marginfi_program.to_account_info(),
+            &[&seeds[..]],
+        )?;
+
+        ctx.accounts.treasury.marginfi_account = ctx.accounts.marginfi_account.key();
+
+        emit!(ConfigUpdated {
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Lend `usdc_amount` of unspent reserve allocation to the configured MarginFi
+    /// bank. The deposit stays committed against the reserve budget until it is
+    /// withdrawn.
+    pub fn deposit_reserve_to_marginfi(
+        ctx: Context<MarginFiLending>,
+        usdc_amount: u64,
+    ) -> Result<()> {
+        let bank = ctx.accounts.bank_accounts();
+        let treasury = &mut ctx.accounts.treasury;
+        let clock = Clock::get()?;
+        let adapter = lending::authorize_deposit(
+            treasury,
+            LendingVenue::MarginFi,
+            usdc_amount,
+            clock.unix_timestamp,
+        )?;
+
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let usdc_before = ctx.accounts.treasury_usdc.amount;
+        marginfi::deposit(&bank, usdc_amount, &[&seeds[..]])?;
+
+        ctx.accounts.treasury_usdc.reload()?;
+        let deposited = usdc_before.saturating_sub(ctx.accounts.treasury_usdc.amount);
+        require!(
+            deposited == usdc_amount,
+            ErrorCode::UnexpectedLendingTransfer
+        );
+        treasury.settle_usdc_spend(
+            adapter.deposit_spend(),
+            usdc_amount,
+            deposited,
+            clock.unix_timestamp,
+        )?;
+
+        emit!(MarginFiDeposited {
+            bank: treasury.marginfi_bank,
+            usdc_amount: deposited,
+            total_deposited_usdc: treasury.marginfi_deposited_usdc,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw `usdc_amount` from the MarginFi bank, or the whole position with
+    /// `withdraw_all`. Principal is withdrawn first and returns to the reserve
+    /// budget; anything above it is interest. `remaining_accounts` are the bank and
+    /// oracle accounts MarginFi's health check needs. Allowed while paused so funds
+    /// can be pulled back to the vault.
+    pub fn withdraw_reserve_from_marginfi<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MarginFiLending<'info>>,
+        usdc_amount: u64,
+        withdraw_all: bool,
+    ) -> Result<()> {
+        let bank = ctx.accounts.bank_accounts();
+        let treasury = &mut ctx.accounts.treasury;
+        require!(
+            treasury.marginfi_deposited_usdc > 0,
+            ErrorCode::InvalidAmount
+        );
+        require!(withdraw_all || usdc_amount > 0, ErrorCode::InvalidAmount);
+
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let usdc_before = ctx.accounts.treasury_usdc.amount;
+        marginfi::withdraw(
+            &bank,
+            usdc_amount,
+            withdraw_all,
+            ctx.remaining_accounts,
+            &[&seeds[..]],
+        )?;
+
+        ctx.accounts.treasury_usdc.reload()?;
+        let received = ctx
+            .accounts
+            .treasury_usdc
+            .amount
+            .saturating_sub(usdc_before);
+        require!(
+            withdraw_all || received == usdc_amount,
+            ErrorCode::UnexpectedLendingTransfer
+        );
+
+        let principal = if withdraw_all {
+            treasury.marginfi_deposited_usdc
+        } else {
+            received.min(treasury.marginfi_deposited_usdc)
+        };
+        treasury.marginfi_deposited_usdc -= principal;
+
+        emit!(MarginFiWithdrawn {
+            bank: treasury.marginfi_bank,
+            usdc_received: received,
+            principal_usdc: principal,
+            interest_usdc: received.saturating_sub(principal),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw the whole MarginFi position, lend the deposited principal straight
+    /// back, and credit the interest left over as newly available USDC, the same as
+    /// converted fees. `remaining_accounts` are the health-check accounts of the
+    /// withdrawal.
+    pub fn harvest_marginfi_yield<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MarginFiLending<'info>>,
+    ) -> Result<()> {
+        let bank = ctx.accounts.bank_accounts();
+        let treasury = &mut ctx.accounts.treasury;
+        require!(!treasury.fully_paused(), ErrorCode::Paused);
+        let principal = treasury.marginfi_deposited_usdc;
+        require!(principal > 0, ErrorCode::InvalidAmount);
+
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let usdc_before = ctx.accounts.treasury_usdc.amount;
+        marginfi::withdraw(&bank, 0, true, ctx.remaining_accounts, &[&seeds[..]])?;
+        ctx.accounts.treasury_usdc.reload()?;
+        let received = ctx
+            .accounts
+            .treasury_usdc
+            .amount
+            .saturating_sub(usdc_before);
+        require!(received >= principal, ErrorCode::UnexpectedLendingTransfer);
+
+        marginfi::deposit(&bank, principal, &[&seeds[..]])?;
+        ctx.accounts.treasury_usdc.reload()?;
+        let interest = ctx
+            .accounts
+            .treasury_usdc
+            .amount
+            .saturating_sub(usdc_before);
+        require!(
+            interest == received - principal,
+            ErrorCode::UnexpectedLendingTransfer
+        );
+        treasury.record_yield(interest)?;
+
+        emit!(YieldHarvested {
+            venue: LendingVenue::MarginFi,
+            usdc_amount: interest,
+            total_harvested_usdc: treasury.total_yield_harvested_usdc,
+            total_converted: treasury.total_usdc_converted,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set the Solend reserve idle reserve USDC is lent into. Switching reserves
+    /// needs the current position redeemed first.
+    pub fn set_solend_reserve(ctx: Context<UpdateConfig>, solend_reserve: Pubkey) -> Result<()> {
+        ctx.accounts.record_activity()?;
+
+        let treasury = &mut ctx.accounts.treasury;
+        require!(
+            treasury.solend_collateral_amount == 0,
+            ErrorCode::LendingPositionOpen
+        );
+        treasury.solend_reserve = solend_reserve;
+
+        emit!(ConfigUpdated {
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Lend `usdc_amount` of unspent reserve allocation to the configured Solend
+    /// reserve. The deposit stays committed against the reserve budget until it is
+    /// redeemed. The client refreshes the reserve earlier in the transaction.
+    pub fn deposit_reserve_to_solend(ctx: Context<SolendLending>, usdc_amount: u64) -> Result<()> {
+        let lending = ctx.accounts.reserve_accounts();
+        let treasury = &mut ctx.accounts.treasury;
+        let clock = Clock::get()?;
+        let adapter = lending::authorize_deposit(
+            treasury,
+            LendingVenue::Solend,
+            usdc_amount,
+            clock.unix_timestamp,
+        )?;
+
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let usdc_before = ctx.accounts.treasury_usdc.amount;
+        let collateral_before = ctx.accounts.treasury_collateral.amount;
+        solend::deposit_reserve_liquidity(&lending, usdc_amount, &[&seeds[..]])?;
+
+        ctx.accounts.treasury_usdc.reload()?;
+        ctx.accounts.treasury_collateral.reload()?;
+        let deposited = usdc_before.saturating_sub(ctx.accounts.treasury_usdc.amount);
+        let minted = ctx
+            .accounts
+            .treasury_collateral
+            .amount
+            .saturating_sub(collateral_before);
+        require!(
+            deposited == usdc_amount && minted > 0,
+            ErrorCode::UnexpectedLendingTransfer
+        );
+
+        treasury.settle_usdc_spend(
+            adapter.deposit_spend(),
+            usdc_amount,
+            deposited,
+            clock.unix_timestamp,
+        )?;
+        treasury.solend_collateral_amount = treasury
+            .solend_collateral_amount
+            .checked_add(minted)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(SolendDeposited {
+            reserve: treasury.solend_reserve,
+            usdc_amount: deposited,
+            collateral_minted: minted,
+            total_deposited_usdc: treasury.solend_deposited_usdc,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Redeem `collateral_amount` cTokens for USDC. The redeemed share of the
+    /// deposited principal goes back to the reserve budget; anything above it is
+    /// interest. Allowed while paused so funds can be pulled back to the vault.
+    pub fn withdraw_reserve_from_solend(
+        ctx: Context<SolendLending>,
+        collateral_amount: u64,
+    ) -> Result<()> {
+        let lending = ctx.accounts.reserve_accounts();
+        let treasury = &mut ctx.accounts.treasury;
+        require!(
+            collateral_amount > 0 && collateral_amount <= treasury.solend_collateral_amount,
+            ErrorCode::InvalidAmount
+        );
+        let principal = treasury.solend_principal_share(collateral_amount);
+
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let usdc_before = ctx.accounts.treasury_usdc.amount;
+        let collateral_before = ctx.accounts.treasury_collateral.amount;
+        solend::redeem_reserve_collateral(&lending, collateral_amount, &[&seeds[..]])?;
+
+        ctx.accounts.treasury_usdc.reload()?;
+        ctx.accounts.treasury_collateral.reload()?;
+        let received = ctx
+            .accounts
+            .treasury_usdc
+            .amount
+            .saturating_sub(usdc_before);
+        let redeemed = collateral_before.saturating_sub(ctx.accounts.treasury_collateral.amount);
+        require!(
+            redeemed == collateral_amount,
+            ErrorCode::UnexpectedLendingTransfer
+        );
+
+        treasury.solend_collateral_amount -= collateral_amount;
+        treasury.solend_deposited_usdc -= principal;
+
+        emit!(SolendWithdrawn {
+            reserve: treasury.solend_reserve,
+            collateral_amount,
+            usdc_received: received,
+            principal_usdc: principal,
+            interest_usdc: received.saturating_sub(principal),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Redeem `collateral_amount` cTokens worth only accrued interest and credit the USDC
+    /// received as newly available, the same as converted fees. The redemption's
+    /// own rate prices the cTokens left, which must still cover the deposited principal.
+    pub fn harvest_solend_yield(ctx: Context<SolendLending>, collateral_amount: u64) -> Result<()> {
+        let lending = ctx.accounts.reserve_accounts();
+        let treasury = &mut ctx.accounts.treasury;
+        require!(!treasury.fully_paused(), ErrorCode::Paused);
+        require!(
+            collateral_amount > 0 && collateral_amount < treasury.solend_collateral_amount,
+            ErrorCode::InvalidAmount
+        );
+
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let usdc_before = ctx.accounts.treasury_usdc.amount;
+        let collateral_before = ctx.accounts.treasury_collateral.amount;
+        solend::redeem_reserve_collateral(&lending, collateral_amount, &[&seeds[..]])?;
+
+        ctx.accounts.treasury_usdc.reload()?;
+        ctx.accounts.treasury_collateral.reload()?;
+        let received = ctx
+            .accounts
+            .treasury_usdc
+            .amount
+            .saturating_sub(usdc_before);
+        let redeemed = collateral_before.saturating_sub(ctx.accounts.treasury_collateral.amount);
+        require!(
+            redeemed == collateral_amount,
+            ErrorCode::UnexpectedLendingTransfer
+        );
+        let remaining = treasury.solend_collateral_amount - collateral_amount;
+        require!(
+            lending::covers_principal(
+                remaining,
+                received,
+                collateral_amount,
+                treasury.solend_deposited_usdc
+            ),
+            ErrorCode::HarvestExceedsInterest
+        );
+
+        treasury.solend_collateral_amount = remaining;
+        treasury.record_yield(received)?;
+
+        emit!(YieldHarvested {
+            venue: LendingVenue::Solend,
+            usdc_amount: received,
+            total_harvested_usdc: treasury.total_yield_harvested_usdc,
+            total_converted: treasury.total_usdc_converted,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Record a proof-of-reserves snapshot for the current epoch. `remaining_accounts`
+    /// are the treasury-owned token accounts to attest (vaults, LP tokens, lending
+    /// receipts), classified by `kinds` in the same order.
+    pub fn attest_reserves<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AttestReserves<'info>>,
+        kinds: Vec<HoldingKind>,
+    ) -> Result<()> {
+        require!(
+            !kinds.is_empty()
+                && kinds.len() == ctx.remaining_accounts.len()
+                && kinds.len() <= MAX_ATTESTED_HOLDINGS,
+            ErrorCode::InvalidAttestation
+        );
+
+        let clock = Clock::get()?;
+        let treasury = &ctx.accounts.treasury;
+        let mut holdings = Vec::with_capacity(kinds.len());
+        for (info, kind) in ctx.remaining_accounts.iter().zip(kinds) {
+            let account = InterfaceAccount::<token_interface::TokenAccount>::try_from(info)?;
+            require_keys_eq!(account.owner, treasury.key(), ErrorCode::InvalidAttestation);
+            holdings.push(AttestedHolding {
+                kind,
+                account: info.key(),
+                mint: account.mint,
+                amount: account.amount,
+            });
+        }
+
+        let attestation = &mut ctx.accounts.attestation;
+        attestation.epoch = treasury.current_epoch;
+        attestation.attester = ctx.accounts.authority.key();
+        attestation.slot = clock.slot;
+        attestation.timestamp = clock.unix_timestamp;
+        attestation.hash = Attestation::hash_holdings(attestation.epoch, clock.slot, &holdings);
+        attestation.holdings = holdings;
+        attestation.bump = ctx.bumps.attestation;
+
+        emit!(ReservesAttested {
+            epoch: attestation.epoch,
+            holdings: attestation.holdings.len() as u8,
+            hash: attestation.hash,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// View: liquidity lock report for exchanges and aggregators. `remaining_accounts`
+    /// are the treasury's LP token accounts and `LpLock` accounts; the report lists
+    /// each with the time its tokens stay locked until, plus the LP held, locked and
+    /// burned in total.
+    pub fn attest_lp_locks<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AttestLpLocks<'info>>,
+    ) -> Result<LpLockAttestation> {
+        require!(
+            !ctx.remaining_accounts.is_empty()
+                && ctx.remaining_accounts.len() <= MAX_ATTESTED_HOLDINGS,
+            ErrorCode::InvalidAttestation
+        );
+
+        let clock = Clock::get()?;
+        let treasury = &ctx.accounts.treasury;
+        let mut holdings = Vec::with_capacity(ctx.remaining_accounts.len());
+        let mut total_lp_held = 0u64;
+        let mut total_lp_locked = 0u64;
+        for info in ctx.remaining_accounts.iter() {
+            let holding = if info.owner == &crate::ID {
+                let lp_lock = Account::<LpLock>::try_from(info)?;
+                LpHolding {
+                    account: lp_lock.vault,
+                    lp_mint: lp_lock.lp_mint,
+                    amount: lp_lock.amount,
+                    locked_until: lp_lock.locked_until,
+                }
+            } else {
+                let account = InterfaceAccount::<token_interface::TokenAccount>::try_from(info)?;
+                require_keys_eq!(account.owner, treasury.key(), ErrorCode::InvalidAttestation);
+                // LP tokens in treasury-owned accounts can be withdrawn at any time
+                LpHolding {
+                    account: info.key(),
+                    lp_mint: account.mint,
+                    amount: account.amount,
+                    locked_until: 0,
+                }
+            };
+            total_lp_held = total_lp_held
+                .checked_add(holding.amount)
+                .ok_or(ErrorCode::Overflow)?;
+            if holding.locked_until > clock.unix_timestamp {
+                total_lp_locked = total_lp_locked
+                    .checked_add(holding.amount)
+                    .ok_or(ErrorCode::Overflow)?;
+            }
+            holdings.push(holding);
+        }
+
+        Ok(LpLockAttestation {
+            holdings,
+            total_lp_held,
+            total_lp_locked,
+            total_lp_burned: treasury.total_lp_burned,
+            slot: clock.slot,
+            timestamp: clock.unix_timestamp,
+        })
+    }
+
+    /// Set the tip paid to the caller of `advance_epoch`
+    pub fn set_epoch_crank_tip(ctx: Context<UpdateConfig>, tip_lamports: u64) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.epoch_crank_tip_lamports = tip_lamports;
+
+        emit!(ConfigUpdated {
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Configure the USDCBALL/USD price feed used by conditional buybacks
+    pub fn set_token_oracle(ctx: Context<UpdateConfig>, token_usd_oracle: Pubkey) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.token_usd_oracle = token_usd_oracle;
+
+        emit!(ConfigUpdated {
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Place a buyback that operators may only execute while the token trades at or
+    /// below `max_price_micro_usd`, until `expires_at`
+    pub fn place_conditional_buyback(
+        ctx: Context<PlaceConditionalBuyback>,
+        usdc_amount: u64,
+        max_price_micro_usd: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let treasury = &mut ctx.accounts.treasury;
+        require!(usdc_amount > 0, ErrorCode::InvalidAmount);
+        require!(
+            max_price_micro_usd > 0 && expires_at > clock.unix_timestamp,
+            ErrorCode::InvalidAmount
+        );
+        require!(
+            treasury.token_usd_oracle != Pubkey::default(),
+            ErrorCode::InvalidOracle
+        );
+        treasury.require_co_signer(
+            usdc_amount,
+            ctx.accounts.authority.key(),
+            ctx.accounts.co_signer.as_ref().map(|s| s.key()),
+            ctx.accounts.co_signer_operator.as_deref(),
+        )?;
+
+        let order = &mut ctx.accounts.order;
+        order.index = treasury.conditional_buyback_count;
+        order.creator = ctx.accounts.authority.key();
+        order.usdc_amount = usdc_amount;
+        order.max_price_micro_usd = max_price_micro_usd;
+        order.expires_at = expires_at;
+        order.bump = ctx.bumps.order;
+
+        treasury.conditional_buyback_count = treasury
+            .conditional_buyback_count
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(ConditionalBuybackPlaced {
+            index: order.index,
+            usdc_amount,
+            max_price_micro_usd,
+            expires_at,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw a conditional buyback that has not executed
+    pub fn cancel_conditional_buyback(ctx: Context<CancelConditionalBuyback>) -> Result<()> {
+        emit!(ConditionalBuybackClosed {
+            index: ctx.accounts.order.index,
+            executed: false,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Operator crank: execute a conditional buyback while the oracle price is at or
+    /// below the order's limit, swapping on `venue`. `remaining_accounts` holds the
+    /// venue program followed by its swap accounts, and the fill itself must clear
+    /// the order's limit price. Subject to the same limits as `execute_buyback`.
+    pub fn execute_conditional_buyback<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteConditionalBuyback<'info>>,
+        venue: Venue,
+        swap_data: Vec<u8>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let treasury = &mut ctx.accounts.treasury;
+        let order = &ctx.accounts.order;
+        treasury.require_not_paused(PauseFlag::Buybacks)?;
+        treasury.require_enabled(GatedInstruction::ExecuteBuyback)?;
+        require!(
+            clock.unix_timestamp <= order.expires_at,
+            ErrorCode::OrderExpired
+        );
+
+        let price = oracle::read_price(
+            &ctx.accounts.token_usd_oracle,
+            clock.unix_timestamp,
+            treasury.oracle_max_age_seconds,
+        )?
+        .micro_usd_per_unit()?;
+        require!(
+            price <= order.max_price_micro_usd,
+            ErrorCode::PriceConditionNotMet
+        );
+
+        let usdc_amount = order.usdc_amount;
+        treasury.require_ready(OperationKind::Buyback, clock.unix_timestamp)?;
+        require!(
+            usdc_amount <= treasury.cycle_limit_usdc(),
+            ErrorCode::ExceedsMaxPerCycle
+        );
+
+        ctx.accounts
+            .operator_stats
+            .require_scope(OperationKind::Buyback, Some(venue))?;
+
+        treasury.authorize_usdc_spend(UsdcSpend::Buyback, usdc_amount, clock.unix_timestamp)?;
+
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let signer = &[&seeds[..]];
+        let usdc_before = ctx.accounts.treasury_usdc.amount;
+        let tokens_before = ctx.accounts.treasury_token.amount;
+        venues::invoke_swap(
+            venue,
+            ctx.remaining_accounts,
+            swap_data,
+            treasury.key(),
+            signer,
+        )?;
+
+        // Hold the fill itself to the order's limit, not just the oracle read above
+        ctx.accounts.treasury_usdc.reload()?;
+        ctx.accounts.treasury_token.reload()?;
+        let spent = usdc_before.saturating_sub(ctx.accounts.treasury_usdc.amount);
+        let tokens_received = ctx
+            .accounts
+            .treasury_token
+            .amount
+            .saturating_sub(tokens_before);
+        let (execution_price, degraded_execution) = treasury.settle_buyback(
+            usdc_amount,
+            spent,
+            tokens_received,
+            0,
+            Some(price),
+            ctx.accounts.token_mint.decimals,
+            clock.unix_timestamp,
+        )?;
+        require!(
+            execution_price <= order.max_price_micro_usd,
+            ErrorCode::PriceConditionNotMet
+        );
+        record_quote(
+            ctx.accounts.quote_board.as_mut(),
+            spent,
+            tokens_received,
+            &clock,
+        );
+
+        let stats = &mut ctx.accounts.operator_stats;
+        stats.successful_operations = stats
+            .successful_operations
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+        stats.total_volume_usdc = stats
+            .total_volume_usdc
+            .checked_add(spent)
+            .ok_or(ErrorCode::Overflow)?;
+        stats.last_active_timestamp = clock.unix_timestamp;
+
+        emit!(BuybackExecuted {
+            usdc_amount: spent,
+            requested_usdc_amount: usdc_amount,
+            min_tokens_out: 0,
+            tokens_received,
+            execution_price,
+            reference_price: price,
+            degraded_execution,
+            timestamp: clock.unix_timestamp,
+        });
+        emit!(ConditionalBuybackClosed {
+            index: order.index,
+            executed: true,
+            timestamp: clock.unix_timestamp,
+        });
+        if let Some(alert) = treasury.large_operation_alert(
+            AlertedOperation::ConditionalBuyback,
+            spent,
+            ctx.accounts.operator.key(),
+            ctx.remaining_accounts[0].key(),
+            usdc_before,
+            &clock,
+        ) {
+            emit!(alert);
+        }
+
+        Ok(())
+    }
+
+    /// Emergency only: propose moving vault ownership to `successor` (a successor
+    /// program's PDA or a multisig). Requires the treasury to be paused and the
+    /// guardian's co-signature; executable after `VAULT_MIGRATION_TIMELOCK_SECONDS`.
+    pub fn propose_vault_migration(
+        ctx: Context<ProposeVaultMigration>,
+        successor: Pubkey,
+    ) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        require!(treasury.fully_paused(), ErrorCode::NotPaused);
+        require!(
+            successor != Pubkey::default() && successor != treasury.key(),
+            ErrorCode::InvalidSuccessor
+        );
+
+        let clock = Clock::get()?;
+        treasury.pending_vault_successor = successor;
+        treasury.vault_migration_eta = clock
+            .unix_timestamp
+            .checked_add(VAULT_MIGRATION_TIMELOCK_SECONDS)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(VaultMigrationProposed {
+            successor,
+            guardian: ctx.accounts.guardian.key(),
+            eta: treasury.vault_migration_eta,
+            timestamp: clock.unix_timestamp,
+        });
+
+        record_key_activity(
+            ctx.accounts.guardian_activity.as_mut(),
+            KeyAction::GuardianCoSign,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    /// Abort a pending vault migration
+    pub fn cancel_vault_migration(ctx: Context<EmergencyAction>) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        require!(
+            treasury.pending_vault_successor != Pubkey::default(),
+            ErrorCode::NoPendingMigration
+        );
+        let successor = treasury.pending_vault_successor;
+        treasury.pending_vault_successor = Pubkey::default();
+        treasury.vault_migration_eta = 0;
+
+        emit!(VaultMigrationCancelled {
+            successor,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        record_key_activity(
+            ctx.accounts.authority_activity.as_mut(),
+            KeyAction::Emergency,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    /// Hand the treasury-owned token accounts in `remaining_accounts` (all under
+    /// `token_program`) to the proposed successor once the timelock has passed.
+    /// Call once per token program; the proposal stays open until cancelled.
+    pub fn execute_vault_migration<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteVaultMigration<'info>>,
+    ) -> Result<()> {
+        let treasury = &ctx.accounts.treasury;
+        require!(treasury.fully_paused(), ErrorCode::NotPaused);
+        let successor = treasury.pending_vault_successor;
+        require!(
+            successor != Pubkey::default(),
+            ErrorCode::NoPendingMigration
+        );
+        let clock = Clock::get()?;
+        require!(
+            clock.unix_timestamp >= treasury.vault_migration_eta,
+            ErrorCode::TimelockActive
+        );
+        require!(!ctx.remaining_accounts.is_empty(), ErrorCode::InvalidAmount);
+
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let signer = &[&seeds[..]];
+        let token_program = ctx.accounts.token_program.to_account_info();
+
+        for info in ctx.remaining_accounts {
+            let vault = InterfaceAccount::<token_interface::TokenAccount>::try_from(info)?;
+            require_keys_eq!(
+                vault.owner,
+                treasury.key(),
+                ErrorCode::VaultNotOwnedByTreasury
+            );
+
+            let cpi_accounts = token_interface::SetAuthority {
+                current_authority: treasury.to_account_info(),
+                account_or_mint: info.clone(),
+            };
+            token_interface::set_authority(
+                CpiContext::new_with_signer(token_program.clone(), cpi_accounts, signer),
+                AuthorityType::AccountOwner,
+                Some(successor),
+            )?;
+
+            emit!(VaultAuthorityMigrated {
+                vault: info.key(),
+                mint: vault.mint,
+                amount: vault.amount,
+                successor,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Liveness ping from an operator's automation
+    pub fn heartbeat(ctx: Context<Heartbeat>) -> Result<()> {
+        let clock = Clock::get()?;
+        let stats = &mut ctx.accounts.operator_stats;
+        stats.last_heartbeat_timestamp = clock.unix_timestamp;
+        stats.heartbeat_count = stats
+            .heartbeat_count
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(OperatorHeartbeat {
+            operator: stats.operator,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Create the registry of pools liquidity may be deployed to
+    pub fn initialize_pool_registry(ctx: Context<InitializePoolRegistry>) -> Result<()> {
+        ctx.accounts.pool_registry.bump = ctx.bumps.pool_registry;
+        Ok(())
+    }
+
+    /// Allow liquidity deposits into the pool with these vaults
+    pub fn register_pool(
+        ctx: Context<UpdatePoolRegistry>,
+        venue: Venue,
+        usdc_vault: Pubkey,
+        token_vault: Pubkey,
+    ) -> Result<()> {
+        let registry = &mut ctx.accounts.pool_registry;
+        require!(
+            registry.find(&usdc_vault, &token_vault).is_none(),
+            ErrorCode::PoolAlreadyRegistered
+        );
+        require!(
+            registry.pools.len() < MAX_REGISTERED_POOLS,
+            ErrorCode::PoolRegistryFull
+        );
+        registry.pools.push(RegisteredPool {
+            venue,
+            usdc_vault,
+            token_vault,
+            slippage_bps: 0,
+            max_usdc_amount: 0,
+            pool_type: PoolType::ConstantProduct,
+        });
+
+        emit!(PoolRegistryUpdated {
+            venue,
+            usdc_vault,
+            token_vault,
+            registered: true,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Stop liquidity deposits into a pool
+    pub fn deregister_pool(
+        ctx: Context<UpdatePoolRegistry>,
+        usdc_vault: Pubkey,
+        token_vault: Pubkey,
+    ) -> Result<()> {
+        let registry = &mut ctx.accounts.pool_registry;
+        let index = registry
+            .find(&usdc_vault, &token_vault)
+            .ok_or(ErrorCode::UnregisteredPool)?;
+        let pool = registry.pools.remove(index);
+
+        emit!(PoolRegistryUpdated {
+            venue: pool.venue,
+            usdc_vault,
+            token_vault,
+            registered: false,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Override the global `slippage_bps` for one registered pool and cap its
+    /// operations below `max_usdc_per_cycle`; zero falls back to the global value
+    pub fn set_pool_limits(
+        ctx: Context<UpdatePoolRegistry>,
+        usdc_vault: Pubkey,
+        token_vault: Pubkey,
+        slippage_bps: u16,
+        max_usdc_amount: u64,
+    ) -> Result<()> {
+        require!(slippage_bps <= 1000, ErrorCode::SlippageTooHigh);
+        let registry = &mut ctx.accounts.pool_registry;
+        let index = registry
+            .find(&usdc_vault, &token_vault)
+            .ok_or(ErrorCode::UnregisteredPool)?;
+        let pool = &mut registry.pools[index];
+        pool.slippage_bps = slippage_bps;
+        pool.max_usdc_amount = max_usdc_amount;
+
+        emit!(PoolLimitsUpdated {
+            venue: pool.venue,
+            usdc_vault,
+            token_vault,
+            slippage_bps,
+            max_usdc_amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set how the treasury deposits into a registered pool: `add_liquidity` for
+    /// constant-product pools, `add_liquidity_meteora` for Meteora DLMM pairs
+    pub fn set_pool_type(
+        ctx: Context<UpdatePoolRegistry>,
+        usdc_vault: Pubkey,
+        token_vault: Pubkey,
+        pool_type: PoolType,
+    ) -> Result<()> {
+        let registry = &mut ctx.accounts.pool_registry;
+        let index = registry
+            .find(&usdc_vault, &token_vault)
+            .ok_or(ErrorCode::UnregisteredPool)?;
+        let pool = &mut registry.pools[index];
+        pool.pool_type = pool_type;
+
+        emit!(PoolTypeUpdated {
+            venue: pool.venue,
+            usdc_vault,
+            token_vault,
+            pool_type,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Create a liquidity mining campaign paying `reward_budget` USDCBALL from the
+    /// treasury token vault to stakers of a registered pool's `lp_mint`, streamed per
+    /// second from `start_time` for `duration_seconds`. The budget is escrowed in the
+    /// campaign's reward vault; `start_time` is at least `CAMPAIGN_TIMELOCK_SECONDS`
+    /// out so `cancel_campaign` can return it before anything streams.
+    pub fn create_campaign(
+        ctx: Context<CreateCampaign>,
+        reward_budget: u64,
+        start_time: i64,
+        duration_seconds: i64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(
+            start_time >= clock.unix_timestamp + CAMPAIGN_TIMELOCK_SECONDS,
+            ErrorCode::TimelockActive
+        );
+        require!(duration_seconds > 0, ErrorCode::InvalidDelay);
+        let reward_rate = reward_budget / duration_seconds as u64;
+        require!(reward_rate > 0, ErrorCode::InvalidAmount);
+
+        let treasury = &mut ctx.accounts.treasury;
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let signer = &[&seeds[..]];
+
+        // Rate rounding leaves dust in the treasury rather than the reward vault
+        let funded = reward_rate * duration_seconds as u64;
+        let cpi_accounts = token_interface::TransferChecked {
+            from: ctx.accounts.treasury_token.to_account_info(),
+            mint: ctx.accounts.token_mint.to_account_info(),
+            to: ctx.accounts.reward_vault.to_account_info(),
+            authority: treasury.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_2022_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token_interface::transfer_checked(cpi_ctx, funded, ctx.accounts.token_mint.decimals)?;
+
+        // Stream only what arrived; the mint's transfer fee is withheld on the way in
+        ctx.accounts.reward_vault.reload()?;
+        let received = ctx.accounts.reward_vault.amount;
+        let reward_rate = received / duration_seconds as u64;
+        require!(reward_rate > 0, ErrorCode::InvalidAmount);
+        let reward_budget = reward_rate * duration_seconds as u64;
+
+        let pool = ctx.accounts.lp_position.pool_usdc;
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.index = treasury.campaign_count;
+        campaign.pool = pool;
+        campaign.lp_mint = ctx.accounts.lp_mint.key();
+        campaign.reward_budget = reward_budget;
+        campaign.reward_rate_per_second = reward_rate;
+        campaign.start_time = start_time;
+        campaign.end_time = start_time
+            .checked_add(duration_seconds)
+            .ok_or(ErrorCode::Overflow)?;
+        campaign.acc_reward_per_share = 0;
+        campaign.last_update_time = start_time;
+        campaign.total_staked = 0;
+        campaign.total_claimed = 0;
+        campaign.bump = ctx.bumps.campaign;
+
+        treasury.campaign_count = treasury
+            .campaign_count
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(CampaignCreated {
+            index: campaign.index,
+            pool,
+            lp_mint: campaign.lp_mint,
+            reward_budget,
+            start_time,
+            end_time: campaign.end_time,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Cancel a campaign before it starts, returning its escrowed rewards to the
+    /// treasury token vault. Staked LP tokens stay withdrawable through `unstake_lp`.
+    pub fn cancel_campaign(ctx: Context<CancelCampaign>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let campaign = &mut ctx.accounts.campaign;
+        require!(now < campaign.start_time, ErrorCode::CampaignStarted);
+
+        let refunded = ctx.accounts.reward_vault.amount;
+        let treasury = &ctx.accounts.treasury;
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let signer = &[&seeds[..]];
+        let cpi_accounts = token_interface::TransferChecked {
+            from: ctx.accounts.reward_vault.to_account_info(),
+            mint: ctx.accounts.token_mint.to_account_info(),
+            to: ctx.accounts.treasury_token.to_account_info(),
+            authority: treasury.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_2022_program.to_account_info();
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+            refunded,
+            ctx.accounts.token_mint.decimals,
+        )?;
+
+        campaign.cancel(now);
+
+        emit!(CampaignCancelled {
+            index: campaign.index,
+            refunded,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Open a staking position in a campaign
+    pub fn open_stake_position(ctx: Context<OpenStakePosition>) -> Result<()> {
+        let position = &mut ctx.accounts.position;
+        position.campaign = ctx.accounts.campaign.key();
+        position.owner = ctx.accounts.owner.key();
+        position.amount = 0;
+        position.reward_debt = 0;
+        position.pending_rewards = 0;
+        position.bump = ctx.bumps.position;
+        Ok(())
+    }
+
+    /// Stake LP tokens into a campaign
+    pub fn stake_lp(ctx: Context<StakeLp>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        let now = Clock::get()?.unix_timestamp;
+        let campaign = &mut ctx.accounts.campaign;
+        require!(now < campaign.end_time, ErrorCode::CampaignEnded);
+        campaign.accrue(now)?;
+
+        let position = &mut ctx.accounts.position;
+        campaign.settle(position)?;
+
+        let cpi_accounts = token_interface::TransferChecked {
+            from: ctx.accounts.owner_lp.to_account_info(),
+            mint: ctx.accounts.lp_mint.to_account_info(),
+            to: ctx.accounts.stake_vault.to_account_info(),
+            authority: ctx.accounts.owner.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token_interface::transfer_checked(
+            CpiContext::new(cpi_program, cpi_accounts),
+            amount,
+            ctx.accounts.lp_mint.decimals,
+        )?;
+
+        position.amount = position
+            .amount
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        campaign.total_staked = campaign
+            .total_staked
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        position.reward_debt = campaign.reward_debt_for(position.amount)?;
+
+        emit!(LpStaked {
+            campaign: campaign.key(),
+            owner: position.owner,
+            amount,
+            total_staked: campaign.total_staked,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw staked LP tokens; accrued rewards stay claimable
+    pub fn unstake_lp(ctx: Context<UnstakeLp>, amount: u64) -> Result<()> {
+        let position = &mut ctx.accounts.position;
+        require!(
+            amount > 0 && amount <= position.amount,
+            ErrorCode::InvalidAmount
+        );
+        let now = Clock::get()?.unix_timestamp;
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.accrue(now)?;
+        campaign.settle(position)?;
+
+        let treasury = &ctx.accounts.treasury;
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let signer = &[&seeds[..]];
+        let cpi_accounts = token_interface::TransferChecked {
+            from: ctx.accounts.stake_vault.to_account_info(),
+            mint: ctx.accounts.lp_mint.to_account_info(),
+            to: ctx.accounts.owner_lp.to_account_info(),
+            authority: treasury.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+            amount,
+            ctx.accounts.lp_mint.decimals,
+        )?;
+
+        position.amount = position
+            .amount
+            .checked_sub(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        campaign.total_staked = campaign
+            .total_staked
+            .checked_sub(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        position.reward_debt = campaign.reward_debt_for(position.amount)?;
+
+        emit!(LpUnstaked {
+            campaign: campaign.key(),
+            owner: position.owner,
+            amount,
+            total_staked: campaign.total_staked,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Claim USDCBALL rewards accrued by a staking position
+    pub fn claim_campaign_rewards(ctx: Context<ClaimCampaignRewards>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.accrue(now)?;
+        let position = &mut ctx.accounts.position;
+        campaign.settle(position)?;
+        position.reward_debt = campaign.reward_debt_for(position.amount)?;
+
+        let amount = position.pending_rewards;
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        position.pending_rewards = 0;
+        campaign.total_claimed = campaign
+            .total_claimed
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let treasury = &ctx.accounts.treasury;
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let signer = &[&seeds[..]];
+        let cpi_accounts = token_interface::TransferChecked {
+            from: ctx.accounts.reward_vault.to_account_info(),
+            mint: ctx.accounts.token_mint.to_account_info(),
+            to: ctx.accounts.owner_token.to_account_info(),
+            authority: treasury.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_2022_program.to_account_info();
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+            amount,
+            ctx.accounts.token_mint.decimals,
+        )?;
+
+        emit!(CampaignRewardsClaimed {
+            campaign: campaign.key(),
+            owner: position.owner,
+            amount,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Set the USDC amount above which operations emit `LargeOperationAlert` (0 disables)
+    pub fn set_large_operation_threshold(
+        ctx: Context<UpdateConfig>,
+        large_operation_threshold: u64,
+    ) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.large_operation_threshold = large_operation_threshold;
+
+        emit!(ConfigUpdated {
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Buyback routed to the best of 2-3 registered constant-product pools, quoted
+    /// in-program from their vault balances. `remaining_accounts` holds, per
+    /// candidate: its USDC vault, its token vault, the venue program and then
+    /// `account_count - 1` swap accounts. The fill on the chosen pool is held to the
+    /// same oracle, TWAP and settlement checks as `execute_buyback`.
+    pub fn route_best<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RouteBest<'info>>,
+        usdc_amount: u64,
+        min_tokens_out: u64,
+        candidates: Vec<RouteCandidate>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.require_not_paused(PauseFlag::Buybacks)?;
+        treasury.require_enabled(GatedInstruction::ExecuteBuyback)?;
+        let operator_stats = &ctx.accounts.operator_stats;
+        operator_stats.require_scope(OperationKind::Buyback, None)?;
+        treasury.require_ready(OperationKind::Buyback, clock.unix_timestamp)?;
+        require!(usdc_amount > 0, ErrorCode::InvalidAmount);
+        treasury.require_co_signer(
+            usdc_amount,
+            ctx.accounts.authority.key(),
+            ctx.accounts.co_signer.as_ref().map(|s| s.key()),
+            ctx.accounts.co_signer_operator.as_deref(),
+        )?;
+        treasury.authorize_usdc_spend(UsdcSpend::Buyback, usdc_amount, clock.unix_timestamp)?;
+        require!(
+            (2..=MAX_ROUTE_CANDIDATES).contains(&candidates.len()),
+            ErrorCode::InvalidRoute
+        );
+
+        // Quote every candidate on a venue the operator may use whose size limit
+        // admits `usdc_amount` from its current reserves
+        let registry = &ctx.accounts.pool_registry;
+        let mut rest = ctx.remaining_accounts;
+        let mut quotes = Vec::with_capacity(candidates.len());
+        for (index, candidate) in candidates.iter().enumerate() {
+            let len = 2 + candidate.account_count as usize;
+            require!(rest.len() >= len, ErrorCode::InvalidRoute);
+            let (accounts, tail) = rest.split_at(len);
+            rest = tail;
+
+            let pool = registry
+                .find(&accounts[0].key(), &accounts[1].key())
+                .map(|i| &registry.pools[i])
+                .ok_or(ErrorCode::UnregisteredPool)?;
+            if usdc_amount > pool.max_usdc_amount(treasury)
+                || !operator_stats.allows_venue(pool.venue)
+            {
+                continue;
+            }
+            let fee_bps = pool
+                .venue
+                .handler()
+                .constant_product_fee_bps()
+                .ok_or(ErrorCode::VenueNotQuotable)?;
+            let pool_usdc =
+                InterfaceAccount::<token_interface::TokenAccount>::try_from(&accounts[0])?;
+            let pool_token =
+                InterfaceAccount::<token_interface::TokenAccount>::try_from(&accounts[1])?;
+            require!(
+                pool_usdc.mint == treasury.usdc_mint && pool_token.mint == treasury.token_mint,
+                ErrorCode::InvalidRoute
+            );
+            let expected = venues::quote_constant_product(
+                usdc_amount,
+                pool_usdc.amount,
+                pool_token.amount,
+                fee_bps,
+            )
+            .ok_or(ErrorCode::Overflow)?;
+            quotes.push((
+                expected,
+                index,
+                pool.venue,
+                pool.usdc_vault,
+                pool.slippage_bps(treasury),
+                accounts,
+            ));
+        }
+        require!(rest.is_empty(), ErrorCode::InvalidRoute);
+
+        let &(expected_tokens_out, best_index, venue, pool_usdc, slippage_bps, pool_accounts) =
+            quotes
+                .iter()
+                .max_by_key(|q| q.0)
+                .ok_or(ErrorCode::ExceedsMaxPerCycle)?;
+        let runner_up_tokens_out = quotes
+            .iter()
+            .filter(|q| q.1 != best_index)
+            .map(|q| q.0)
+            .max()
+            .unwrap_or(0);
+        // Hold the fill to the chosen pool's slippage as well as the caller's minimum
+        let min_tokens_out = min_tokens_out
+            .max((expected_tokens_out as u128 * (10_000 - slippage_bps as u128) / 10_000) as u64);
+        require!(
+            expected_tokens_out >= min_tokens_out,
+            ErrorCode::SlippageExceeded
+        );
+        let token_decimals = ctx.accounts.token_mint.decimals;
+        let oracle_price = check_oracle_floor(
+            treasury,
+            ctx.accounts.token_usd_oracle.as_deref(),
+            ctx.accounts.token_switchboard_feed.as_deref(),
+            usdc_amount,
+            min_tokens_out,
+            token_decimals,
+            clock.unix_timestamp,
+        )?;
+        // The TWAP, when passed, must be the chosen pool's
+        let twap_price = check_twap(
+            ctx.accounts.twap.as_deref_mut().map(|t| &mut **t),
+            Some(&InterfaceAccount::try_from(&pool_accounts[0])?),
+            Some(&InterfaceAccount::try_from(&pool_accounts[1])?),
+            token_decimals,
+            treasury.twap_max_deviation_bps,
+            clock.unix_timestamp,
+        )?;
+        let reference_price = oracle_price.or(twap_price);
+
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let signer = &[&seeds[..]];
+        let usdc_before = ctx.accounts.treasury_usdc.amount;
+        let tokens_before = ctx.accounts.treasury_token.amount;
+        venues::invoke_swap(
+            venue,
+            &pool_accounts[2..],
+            candidates[best_index].swap_data.clone(),
+            treasury.key(),
+            signer,
+        )?;
+
+        // The swap data is built off-chain, so check what it actually did
+        ctx.accounts.treasury_usdc.reload()?;
+        ctx.accounts.treasury_token.reload()?;
+        let spent = usdc_before.saturating_sub(ctx.accounts.treasury_usdc.amount);
+        let received = ctx
+            .accounts
+            .treasury_token
+            .amount
+            .saturating_sub(tokens_before);
+        let (execution_price, degraded_execution) = treasury.settle_buyback(
+            usdc_amount,
+            spent,
+            received,
+            min_tokens_out,
+            reference_price,
+            token_decimals,
+            clock.unix_timestamp,
+        )?;
+        record_quote(ctx.accounts.quote_board.as_mut(), spent, received, &clock);
+
+        emit!(RouteSelected {
+            venue,
+            pool_usdc,
+            candidates: candidates.len() as u8,
+            expected_tokens_out,
+            runner_up_tokens_out,
+            tokens_received: received,
+            timestamp: clock.unix_timestamp,
+        });
+        emit!(BuybackExecuted {
+            usdc_amount: spent,
+            requested_usdc_amount: usdc_amount,
+            min_tokens_out,
+            tokens_received: received,
+            execution_price,
+            reference_price: reference_price.unwrap_or(0),
+            degraded_execution,
+            timestamp: clock.unix_timestamp,
+        });
+        if let Some(alert) = treasury.large_operation_alert(
+            AlertedOperation::Buyback,
+            spent,
+            ctx.accounts.authority.key(),
+            pool_usdc,
+            usdc_before,
+            &clock,
+        ) {
+            emit!(alert);
+        }
+
+        record_key_activity(
+            ctx.accounts.authority_activity.as_mut(),
+            KeyAction::Buyback,
+            spent,
+        )?;
+
+        Ok(())
+    }
+
+    /// Configure the USDC depeg guard: conversions and liquidity adds are switched
+    /// off once `usdc_usd_oracle` deviates more than `depeg_threshold_bps` from $1
+    pub fn set_depeg_guard(
+        ctx: Context<UpdateConfig>,
+        usdc_usd_oracle: Pubkey,
+        depeg_threshold_bps: u16,
+    ) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        require!(
+            depeg_threshold_bps > 0 && depeg_threshold_bps <= 10_000,
+            ErrorCode::InvalidDepegThreshold
+        );
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.usdc_usd_oracle = usdc_usd_oracle;
+        treasury.depeg_threshold_bps = depeg_threshold_bps;
+
+        emit!(ConfigUpdated {
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless crank: check the USDC peg and trip the guard if it is broken
+    pub fn check_usdc_peg(ctx: Context<CheckUsdcPeg>) -> Result<()> {
+        let clock = Clock::get()?;
+        let oracle = ctx.accounts.usdc_usd_oracle.to_account_info();
+        ctx.accounts
+            .treasury
+            .check_usdc_peg(Some(&oracle), &clock)?;
+        Ok(())
+    }
+
+    /// Burn `amount` bought-back USDCBALL from the treasury token vault
+    pub fn execute_burn(ctx: Context<ExecuteBurn>, amount: u64) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.require_not_paused(PauseFlag::Buybacks)?;
+        treasury.require_enabled(GatedInstruction::ExecuteBurn)?;
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = token_interface::Burn {
+            mint: ctx.accounts.token_mint.to_account_info(),
+            from: ctx.accounts.treasury_token.to_account_info(),
+            authority: treasury.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token_interface::burn(cpi_ctx, amount)?;
+
+        treasury.record_burn(amount)?;
+
+        emit!(TokensBurned {
+            amount,
+            total_burned: treasury.total_tokens_burned,
+            epoch: treasury.current_epoch,
+            epoch_burned: treasury.epoch_tokens_burned,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        record_key_activity(ctx.accounts.authority_activity.as_mut(), KeyAction::Burn, 0)?;
+
+        Ok(())
+    }
+
+    /// Record the token mint's current supply as the baseline external burns are
+    /// verified against
+    pub fn snapshot_token_supply(ctx: Context<ExternalBurn>) -> Result<()> {
+        let supply = ctx.accounts.token_mint.supply;
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.snapshot_supply(supply, Clock::get()?.slot);
+
+        emit!(SupplySnapshotRecorded {
+            supply,
+            total_burned: treasury.total_tokens_burned,
+            slot: treasury.supply_snapshot_slot,
+        });
+
+        record_key_activity(ctx.accounts.authority_activity.as_mut(), KeyAction::Burn, 0)?;
+
+        Ok(())
+    }
+
+    /// Credit `amount` tokens burned outside the program, e.g. by a community burn
+    /// tool, to the burn totals. The burn is only accepted up to the supply drop
+    /// since the last snapshot that the program's own burns do not account for;
+    /// `burn_signature` identifies the burn transaction in the event.
+    pub fn register_external_burn(
+        ctx: Context<ExternalBurn>,
+        amount: u64,
+        burn_signature: [u8; 64],
+    ) -> Result<()> {
+        let supply = ctx.accounts.token_mint.supply;
+        let treasury = &mut ctx.accounts.treasury;
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        let verifiable = treasury.unregistered_external_burn(supply)?;
+        require!(amount <= verifiable, ErrorCode::UnverifiableBurn);
+
+        treasury.record_burn(amount)?;
+        treasury.total_external_burned = treasury
+            .total_external_burned
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(ExternalBurnRegistered {
+            amount,
+            burn_signature,
+            supply,
+            total_burned: treasury.total_tokens_burned,
+            total_external_burned: treasury.total_external_burned,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        record_key_activity(ctx.accounts.authority_activity.as_mut(), KeyAction::Burn, 0)?;
+
+        Ok(())
+    }
+
+    /// Record the Merkle root of holder balances for the current epoch. Leaves are
+    /// `merkle::holder_leaf(holder, balance, held_since)`.
+    pub fn record_holder_snapshot(
+        ctx: Context<RecordHolderSnapshot>,
+        root: [u8; 32],
+        holder_count: u32,
+        total_balance: u64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let treasury = &ctx.accounts.treasury;
+        let snapshot = &mut ctx.accounts.snapshot;
+        snapshot.epoch = treasury.current_epoch;
+        snapshot.root = root;
+        snapshot.holder_count = holder_count;
+        snapshot.total_balance = total_balance;
+        snapshot.slot = clock.slot;
+        snapshot.timestamp = clock.unix_timestamp;
+        snapshot.bump = ctx.bumps.snapshot;
+
+        emit!(HolderSnapshotRecorded {
+            epoch: snapshot.epoch,
+            root,
+            holder_count,
+            total_balance,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// View: prove a holder's snapshot entry and return the boost it earns
+    pub fn get_holder_boost(
+        ctx: Context<ViewHolderBoost>,
+        balance: u64,
+        held_since: i64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<HolderBoost> {
+        let snapshot = &ctx.accounts.snapshot;
+        require!(
+            merkle::verify(
+                &proof,
+                &snapshot.root,
+                merkle::holder_leaf(&ctx.accounts.holder.key(), balance, held_since)
+            ),
+            ErrorCode::InvalidMerkleProof
+        );
+        let holding_seconds = snapshot.timestamp.saturating_sub(held_since).max(0);
+        Ok(HolderBoost {
+            epoch: snapshot.epoch,
+            balance,
+            holding_seconds,
+            boost_bps: HolderSnapshot::boost_bps(holding_seconds),
+        })
+    }
+
+    /// Swap up to `usdc_amount` USDC for USDCBALL on `venue` and burn everything
+    /// received in the same instruction. `remaining_accounts` is the venue program
+    /// followed by its swap accounts; the swap must pay out into `treasury_token`.
+    /// Held to the same oracle, TWAP and settlement checks as `execute_buyback`.
+    pub fn buyback_and_burn<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BuybackAndBurn<'info>>,
+        usdc_amount: u64,
+        min_tokens_out: u64,
+        venue: Venue,
+        swap_data: Vec<u8>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.require_not_paused(PauseFlag::Buybacks)?;
+        treasury.require_enabled(GatedInstruction::ExecuteBuyback)?;
+        treasury.require_enabled(GatedInstruction::ExecuteBurn)?;
+        ctx.accounts
+            .operator_stats
+            .require_scope(OperationKind::Buyback, Some(venue))?;
+        treasury.require_ready(OperationKind::Buyback, clock.unix_timestamp)?;
+        require!(usdc_amount > 0, ErrorCode::InvalidAmount);
+        require!(
+            usdc_amount <= treasury.cycle_limit_usdc(),
+            ErrorCode::ExceedsMaxPerCycle
+        );
+        let oracle_price = check_oracle_floor(
+            treasury,
+            ctx.accounts.token_usd_oracle.as_deref(),
+            ctx.accounts.token_switchboard_feed.as_deref(),
+            usdc_amount,
+            min_tokens_out,
+            ctx.accounts.token_mint.decimals,
+            clock.unix_timestamp,
+        )?;
+        treasury.require_co_signer(
+            usdc_amount,
+            ctx.accounts.authority.key(),
+            ctx.accounts.co_signer.as_ref().map(|s| s.key()),
+            ctx.accounts.co_signer_operator.as_deref(),
+        )?;
+        let twap_price = check_twap(
+            ctx.accounts.twap.as_deref_mut().map(|t| &mut **t),
+            ctx.accounts.twap_pool_usdc.as_deref(),
+            ctx.accounts.twap_pool_token.as_deref(),
+            ctx.accounts.token_mint.decimals,
+            treasury.twap_max_deviation_bps,
+            clock.unix_timestamp,
+        )?;
+        let reference_price = oracle_price.or(twap_price);
+        treasury.authorize_usdc_spend(UsdcSpend::Buyback, usdc_amount, clock.unix_timestamp)?;
+
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let signer = &[&seeds[..]];
+        let usdc_before = ctx.accounts.treasury_usdc.amount;
+        let tokens_before = ctx.accounts.treasury_token.amount;
+        venues::invoke_swap(
+            venue,
+            ctx.remaining_accounts,
+            swap_data,
+            treasury.key(),
+            signer,
+        )?;
+
+        ctx.accounts.treasury_usdc.reload()?;
+        ctx.accounts.treasury_token.reload()?;
+        let spent = usdc_before.saturating_sub(ctx.accounts.treasury_usdc.amount);
+        let received = ctx
+            .accounts
+            .treasury_token
+            .amount
+            .saturating_sub(tokens_before);
+        let (execution_price, degraded_execution) = treasury.settle_buyback(
+            usdc_amount,
+            spent,
+            received,
+            min_tokens_out,
+            reference_price,
+            ctx.accounts.token_mint.decimals,
+            clock.unix_timestamp,
+        )?;
+        record_quote(ctx.accounts.quote_board.as_mut(), spent, received, &clock);
+
+        // Burn exactly what the swap delivered, so nothing bought sits in the vault
+        let cpi_accounts = token_interface::Burn {
+            mint: ctx.accounts.token_mint.to_account_info(),
+            from: ctx.accounts.treasury_token.to_account_info(),
+            authority: treasury.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_2022_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token_interface::burn(cpi_ctx, received)?;
+        treasury.record_burn(received)?;
+
+        emit!(BuybackExecuted {
+            usdc_amount: spent,
+            requested_usdc_amount: usdc_amount,
+            min_tokens_out,
+            tokens_received: received,
+            execution_price,
+            reference_price: reference_price.unwrap_or(0),
+            degraded_execution,
+            timestamp: clock.unix_timestamp,
+        });
+        emit!(TokensBurned {
+            amount: received,
+            total_burned: treasury.total_tokens_burned,
+            epoch: treasury.current_epoch,
+            epoch_burned: treasury.epoch_tokens_burned,
+            timestamp: clock.unix_timestamp,
+        });
+        if let Some(alert) = treasury.large_operation_alert(
+            AlertedOperation::Buyback,
+            spent,
+            ctx.accounts.authority.key(),
+            ctx.remaining_accounts[0].key(),
+            usdc_before,
+            &clock,
+        ) {
+            emit!(alert);
+        }
+
+        record_key_activity(
+            ctx.accounts.authority_activity.as_mut(),
+            KeyAction::Buyback,
+            spent,
+        )?;
+
+        Ok(())
+    }
+
+    /// Set the LTV market makers may borrow inventory at and the LTV at which
+    /// their loans can be liquidated (0 LTV disables new loans)
+    pub fn set_mm_lending_policy(
+        ctx: Context<UpdateConfig>,
+        ltv_bps: u16,
+        liquidation_bps: u16,
+    ) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        require!(
+            ltv_bps < liquidation_bps && liquidation_bps <= 10_000,
+            ErrorCode::InvalidLendingPolicy
+        );
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.mm_ltv_bps = ltv_bps;
+        treasury.mm_liquidation_bps = liquidation_bps;
+
+        emit!(ConfigUpdated {
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Register a market maker allowed to borrow treasury USDCBALL inventory
+    pub fn register_market_maker(
+        ctx: Context<RegisterMarketMaker>,
+        market_maker: Pubkey,
+    ) -> Result<()> {
+        let loan = &mut ctx.accounts.loan;
+        loan.market_maker = market_maker;
+        loan.active = true;
+        loan.collateral_usdc = 0;
+        loan.borrowed_tokens = 0;
+        loan.bump = ctx.bumps.loan;
+
+        emit!(MarketMakerUpdated {
+            market_maker,
+            active: true,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Allow or stop new borrowing by a registered market maker
+    pub fn set_market_maker_active(ctx: Context<SetMarketMakerActive>, active: bool) -> Result<()> {
+        let loan = &mut ctx.accounts.loan;
+        loan.active = active;
+
+        emit!(MarketMakerUpdated {
+            market_maker: loan.market_maker,
+            active,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Post `collateral_usdc` and borrow `token_amount` treasury USDCBALL. The loan
+    /// must stay within `mm_ltv_bps` at the oracle price.
+    pub fn borrow_inventory(
+        ctx: Context<BorrowInventory>,
+        collateral_usdc: u64,
+        token_amount: u64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.require_not_paused(PauseFlag::Liquidity)?;
+        require!(treasury.mm_ltv_bps > 0, ErrorCode::InvalidLendingPolicy);
+        let loan = &mut ctx.accounts.loan;
+        require!(loan.active, ErrorCode::MarketMakerInactive);
+
+        if collateral_usdc > 0 {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.market_maker_usdc.to_account_info(),
+                to: ctx.accounts.collateral_vault.to_account_info(),
+                authority: ctx.accounts.market_maker.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            transfer_tokens(CpiContext::new(cpi_program, cpi_accounts), collateral_usdc)?;
+        }
+
+        if token_amount > 0 {
+            let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+            let signer = &[&seeds[..]];
+            let cpi_accounts = token_interface::TransferChecked {
+                from: ctx.accounts.treasury_token.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                to: ctx.accounts.market_maker_token.to_account_info(),
+                authority: treasury.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_2022_program.to_account_info();
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+                token_amount,
+                ctx.accounts.token_mint.decimals,
+            )?;
+        }
+
+        loan.collateral_usdc = loan
+            .collateral_usdc
+            .checked_add(collateral_usdc)
+            .ok_or(ErrorCode::Overflow)?;
+        loan.borrowed_tokens = loan
+            .borrowed_tokens
+            .checked_add(token_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        loan.last_update_timestamp = clock.unix_timestamp;
+        treasury.total_tokens_lent = treasury
+            .total_tokens_lent
+            .checked_add(token_amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let borrowed_value_usd = oracle::read_price(
+            &ctx.accounts.token_usd_oracle,
+            clock.unix_timestamp,
+            treasury.oracle_max_age_seconds,
+        )?
+        .value_usd(loan.borrowed_tokens, ctx.accounts.token_mint.decimals)?;
+        require!(
+            loan.within_ltv(borrowed_value_usd, treasury.mm_ltv_bps),
+            ErrorCode::LoanUndercollateralized
+        );
+
+        emit!(InventoryBorrowed {
+            market_maker: loan.market_maker,
+            collateral_added: collateral_usdc,
+            tokens_borrowed: token_amount,
+            collateral_usdc: loan.collateral_usdc,
+            borrowed_tokens: loan.borrowed_tokens,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Return `token_amount` borrowed USDCBALL and withdraw `collateral_out` USDC,
+    /// as long as what remains borrowed stays within `mm_ltv_bps`
+    pub fn repay_inventory(
+        ctx: Context<RepayInventory>,
+        token_amount: u64,
+        collateral_out: u64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let treasury = &mut ctx.accounts.treasury;
+        let loan = &mut ctx.accounts.loan;
+        require!(
+            token_amount <= loan.borrowed_tokens && collateral_out <= loan.collateral_usdc,
+            ErrorCode::InvalidAmount
+        );
+
+        if token_amount > 0 {
+            let cpi_accounts = token_interface::TransferChecked {
+                from: ctx.accounts.market_maker_token.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                to: ctx.accounts.treasury_token.to_account_info(),
+                authority: ctx.accounts.market_maker.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_2022_program.to_account_info();
+            token_interface::transfer_checked(
+                CpiContext::new(cpi_program, cpi_accounts),
+                token_amount,
+                ctx.accounts.token_mint.decimals,
+            )?;
+        }
+
+        loan.borrowed_tokens -= token_amount;
+        loan.collateral_usdc -= collateral_out;
+        loan.last_update_timestamp = clock.unix_timestamp;
+        treasury.total_tokens_lent = treasury.total_tokens_lent.saturating_sub(token_amount);
+
+        if collateral_out > 0 {
+            if loan.borrowed_tokens > 0 {
+                let borrowed_value_usd = oracle::read_price(
+                    &ctx.accounts.token_usd_oracle,
+                    clock.unix_timestamp,
+                    treasury.oracle_max_age_seconds,
+                )?
+                .value_usd(loan.borrowed_tokens, ctx.accounts.token_mint.decimals)?;
+                require!(
+                    loan.within_ltv(borrowed_value_usd, treasury.mm_ltv_bps),
+                    ErrorCode::LoanUndercollateralized
+                );
+            }
+
+            let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+            let signer = &[&seeds[..]];
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.collateral_vault.to_account_info(),
+                to: ctx.accounts.market_maker_usdc.to_account_info(),
+                authority: treasury.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            transfer_tokens(
+                CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+                collateral_out,
+            )?;
+        }
+
+        emit!(InventoryRepaid {
+            market_maker: loan.market_maker,
+            tokens_repaid: token_amount,
+            collateral_withdrawn: collateral_out,
+            collateral_usdc: loan.collateral_usdc,
+            borrowed_tokens: loan.borrowed_tokens,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless: seize the collateral of a loan whose LTV has reached
+    /// `mm_liquidation_bps` into the treasury and deactivate the market maker.
+    /// The outstanding tokens are written off against the seized USDC.
+    pub fn liquidate_market_maker(ctx: Context<LiquidateMarketMaker>) -> Result<()> {
+        let clock = Clock::get()?;
+        let treasury = &mut ctx.accounts.treasury;
+        let loan = &mut ctx.accounts.loan;
+        require!(loan.borrowed_tokens > 0, ErrorCode::LoanHealthy);
+
+        let borrowed_value_usd = oracle::read_price(
+            &ctx.accounts.token_usd_oracle,
+            clock.unix_timestamp,
+            treasury.oracle_max_age_seconds,
+        )?
+        .value_usd(loan.borrowed_tokens, ctx.accounts.token_mint.decimals)?;
+        require!(
+            !loan.within_ltv(borrowed_value_usd, treasury.mm_liquidation_bps),
+            ErrorCode::LoanHealthy
+        );
+
+        let collateral_seized = loan.collateral_usdc;
+        let tokens_written_off = loan.borrowed_tokens;
+        if collateral_seized > 0 {
+            let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+            let signer = &[&seeds[..]];
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.collateral_vault.to_account_info(),
+                to: ctx.accounts.treasury_usdc.to_account_info(),
+                authority: treasury.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            transfer_tokens(
+                CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+                collateral_seized,
+            )?;
+        }
+
+        loan.collateral_usdc = 0;
+        loan.borrowed_tokens = 0;
+        loan.active = false;
+        loan.last_update_timestamp = clock.unix_timestamp;
+        treasury.total_tokens_lent = treasury
+            .total_tokens_lent
+            .saturating_sub(tokens_written_off);
+
+        emit!(MarketMakerLiquidated {
+            market_maker: loan.market_maker,
+            collateral_seized,
+            tokens_written_off,
+            borrowed_value_usd,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set the global weekly outflow cap. Lowering it (or setting the first cap)
+    /// applies immediately and cancels any pending raise; raising or removing it
+    /// only takes effect through `apply_weekly_outflow_cap` after the timelock.
+    pub fn set_weekly_outflow_cap(ctx: Context<UpdateConfig>, cap_usdc: u64) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        let now = Clock::get()?.unix_timestamp;
+        let treasury = &mut ctx.accounts.treasury;
+        let current = treasury.weekly_outflow_cap_usdc;
+        let tightens = cap_usdc != 0 && (current == 0 || cap_usdc <= current);
+
+        if tightens {
+            treasury.weekly_outflow_cap_usdc = cap_usdc;
+            treasury.pending_weekly_outflow_cap_usdc = 0;
+            treasury.weekly_outflow_cap_eta = 0;
+        } else {
+            treasury.pending_weekly_outflow_cap_usdc = cap_usdc;
+            treasury.weekly_outflow_cap_eta = now
+                .checked_add(OUTFLOW_CAP_TIMELOCK_SECONDS)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        emit!(OutflowCapUpdated {
+            cap_usdc: treasury.weekly_outflow_cap_usdc,
+            pending_cap_usdc: treasury.pending_weekly_outflow_cap_usdc,
+            eta: treasury.weekly_outflow_cap_eta,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Apply a raised weekly outflow cap once its timelock has passed
+    pub fn apply_weekly_outflow_cap(ctx: Context<UpdateConfig>) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        let now = Clock::get()?.unix_timestamp;
+        let treasury = &mut ctx.accounts.treasury;
+        require!(
+            treasury.weekly_outflow_cap_eta != 0,
+            ErrorCode::NoPendingOutflowCap
+        );
+        require!(
+            now >= treasury.weekly_outflow_cap_eta,
+            ErrorCode::TimelockActive
+        );
+
+        treasury.weekly_outflow_cap_usdc = treasury.pending_weekly_outflow_cap_usdc;
+        treasury.pending_weekly_outflow_cap_usdc = 0;
+        treasury.weekly_outflow_cap_eta = 0;
+
+        emit!(OutflowCapUpdated {
+            cap_usdc: treasury.weekly_outflow_cap_usdc,
+            pending_cap_usdc: 0,
+            eta: 0,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Set the rolling 24-hour buyback + liquidity volume cap. Like the weekly
+    /// outflow cap, lowering it applies immediately and raising or removing it
+    /// only through `apply_daily_volume_cap` after the timelock.
+    pub fn set_daily_volume_cap(ctx: Context<UpdateConfig>, cap_usdc: u64) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        let now = Clock::get()?.unix_timestamp;
+        let treasury = &mut ctx.accounts.treasury;
+        let current = treasury.daily_volume_cap_usdc;
+        let tightens = cap_usdc != 0 && (current == 0 || cap_usdc <= current);
+
+        if tightens {
+            treasury.daily_volume_cap_usdc = cap_usdc;
+            treasury.pending_daily_volume_cap_usdc = 0;
+            treasury.daily_volume_cap_eta = 0;
+        } else {
+            treasury.pending_daily_volume_cap_usdc = cap_usdc;
+            treasury.daily_volume_cap_eta = now
+                .checked_add(OUTFLOW_CAP_TIMELOCK_SECONDS)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        emit!(VolumeCapUpdated {
+            cap_usdc: treasury.daily_volume_cap_usdc,
+            pending_cap_usdc: treasury.pending_daily_volume_cap_usdc,
+            eta: treasury.daily_volume_cap_eta,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Apply a raised daily volume cap once its timelock has passed
+    pub fn apply_daily_volume_cap(ctx: Context<UpdateConfig>) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        let now = Clock::get()?.unix_timestamp;
+        let treasury = &mut ctx.accounts.treasury;
+        require!(
+            treasury.daily_volume_cap_eta != 0,
+            ErrorCode::NoPendingOutflowCap
+        );
+        require!(
+            now >= treasury.daily_volume_cap_eta,
+            ErrorCode::TimelockActive
+        );
+
+        treasury.daily_volume_cap_usdc = treasury.pending_daily_volume_cap_usdc;
+        treasury.pending_daily_volume_cap_usdc = 0;
+        treasury.daily_volume_cap_eta = 0;
+
+        emit!(VolumeCapUpdated {
+            cap_usdc: treasury.daily_volume_cap_usdc,
+            pending_cap_usdc: 0,
+            eta: 0,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Set the Switchboard feed the buyback price guard falls back to, and how old
+    /// its result may be
+    pub fn set_switchboard_fallback(
+        ctx: Context<UpdateConfig>,
+        token_switchboard_feed: Pubkey,
+        switchboard_max_age_seconds: i64,
+    ) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        require!(switchboard_max_age_seconds > 0, ErrorCode::InvalidDelay);
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.token_switchboard_feed = token_switchboard_feed;
+        treasury.switchboard_max_age_seconds = switchboard_max_age_seconds;
+
+        emit!(ConfigUpdated {
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Start a price TWAP for a registered pool
+    pub fn initialize_twap(ctx: Context<InitializeTwap>) -> Result<()> {
+        let twap = &mut ctx.accounts.twap;
+        twap.usdc_vault = ctx.accounts.pool_usdc.key();
+        twap.token_vault = ctx.accounts.pool_token.key();
+        twap.head = 0;
+        twap.len = 0;
+        twap.bump = ctx.bumps.twap;
+        Ok(())
+    }
+
+    /// Set how far a pool's spot price may deviate from its TWAP (0 disables the check)
+    pub fn set_twap_deviation(ctx: Context<UpdateConfig>, max_deviation_bps: u16) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        require!(max_deviation_bps <= 10_000, ErrorCode::SlippageTooHigh);
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.twap_max_deviation_bps = max_deviation_bps;
+
+        emit!(ConfigUpdated {
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set the mint and freeze authority `verify_mint_config` expects the USDCBALL
+    /// mint to have (`None` = revoked)
+    pub fn set_expected_mint_config(
+        ctx: Context<UpdateConfig>,
+        mint_authority: Option<Pubkey>,
+        freeze_authority: Option<Pubkey>,
+    ) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.expected_mint_authority = mint_authority;
+        treasury.expected_freeze_authority = freeze_authority;
+
+        emit!(ConfigUpdated {
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless crank: check the USDCBALL mint's authorities against the
+    /// expected ones and switch buybacks off if either has changed
+    pub fn verify_mint_config(ctx: Context<VerifyMintConfig>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let treasury = &mut ctx.accounts.treasury;
+        let token_mint = &ctx.accounts.token_mint;
+        let mint_authority: Option<Pubkey> = token_mint.mint_authority.into();
+        let freeze_authority: Option<Pubkey> = token_mint.freeze_authority.into();
+
+        if mint_authority == treasury.expected_mint_authority
+            && freeze_authority == treasury.expected_freeze_authority
+        {
+            treasury.mint_config_verified_at = now;
+            return Ok(());
+        }
+
+        treasury.instruction_enabled &= !GatedInstruction::ExecuteBuyback.mask();
+        emit!(MintConfigChanged {
+            mint_authority,
+            freeze_authority,
+            expected_mint_authority: treasury.expected_mint_authority,
+            expected_freeze_authority: treasury.expected_freeze_authority,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Configure the settlement price check: buybacks realizing a price more than
+    /// `max_deviation_bps` above the oracle/TWAP reference are flagged as degraded,
+    /// and `degraded_execution_limit` of them trip the circuit breaker
+    pub fn set_settlement_guard(
+        ctx: Context<UpdateConfig>,
+        max_deviation_bps: u16,
+        degraded_execution_limit: u16,
+    ) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        require!(max_deviation_bps <= 10_000, ErrorCode::SlippageTooHigh);
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.settlement_max_deviation_bps = max_deviation_bps;
+        treasury.degraded_execution_limit = degraded_execution_limit;
+
+        emit!(ConfigUpdated {
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Choose how the allocation split rounds converted USDC into buckets
+    pub fn set_rounding_policy(ctx: Context<UpdateConfig>, policy: RoundingPolicy) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        ctx.accounts.treasury.rounding_policy = policy;
+
+        emit!(ConfigUpdated {
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Arm the price-deviation circuit breaker: a buyback whose realized price is
+    /// more than `deviation_bps` above the oracle/TWAP reference pauses every
+    /// operation until the admin calls `resume` (0 = off)
+    pub fn set_circuit_breaker(ctx: Context<UpdateConfig>, deviation_bps: u16) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        require!(deviation_bps <= 10_000, ErrorCode::SlippageTooHigh);
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.circuit_breaker_deviation_bps = deviation_bps;
+
+        emit!(ConfigUpdated {
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless buyback crank. Swaps `usdc_amount` on `venue`
+    /// (`remaining_accounts`: venue program + swap accounts) once cooldown and budget
+    /// allow, and pays the caller `keeper_reward_bps` of it in USDC, at most
+    /// `keeper_reward_cap_usdc`. The output is checked against the token price feed,
+    /// which must be configured. A blocked crank emits `SkippedOperation` instead of
+    /// failing.
+    pub fn crank_buyback<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CrankBuyback<'info>>,
+        usdc_amount: u64,
+        venue: Venue,
+        swap_data: Vec<u8>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let treasury = &mut ctx.accounts.treasury;
+        let skip_reason = treasury
+            .buyback_skip_reason(clock.unix_timestamp)?
+            .or(treasury.in_launch_mode().then_some(SkipReason::LaunchMode));
+        if let Some(reason) = skip_reason {
+            emit!(SkippedOperation {
+                instruction: GatedInstruction::ExecuteBuyback,
+                reason,
+                caller: ctx.accounts.keeper.key(),
+                timestamp: clock.unix_timestamp,
+            });
+            return Ok(());
+        }
+
+        require!(usdc_amount > 0, ErrorCode::InvalidAmount);
+        require!(
+            usdc_amount <= treasury.cycle_limit_usdc(),
+            ErrorCode::ExceedsMaxPerCycle
+        );
+        // Nobody co-signs a permissionless crank; launch mode was skipped above
+        treasury.require_co_signer(usdc_amount, ctx.accounts.keeper.key(), None, None)?;
+        treasury.authorize_usdc_spend(UsdcSpend::Buyback, usdc_amount, clock.unix_timestamp)?;
+
+        let pyth = ctx
+            .accounts
+            .token_usd_oracle
+            .as_deref()
+            .filter(|a| a.key() == treasury.token_usd_oracle);
+        let switchboard = ctx
+            .accounts
+            .token_switchboard_feed
+            .as_deref()
+            .filter(|a| a.key() == treasury.token_switchboard_feed);
+        let price = oracle::read_price_with_fallback(
+            pyth,
+            switchboard,
+            clock.unix_timestamp,
+            treasury.oracle_max_age_seconds,
+            treasury.switchboard_max_age_seconds,
+        )?;
+
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let signer = &[&seeds[..]];
+        let usdc_before = ctx.accounts.treasury_usdc.amount;
+        let tokens_before = ctx.accounts.treasury_token.amount;
+        venues::invoke_swap(
+            venue,
+            ctx.remaining_accounts,
+            swap_data,
+            treasury.key(),
+            signer,
+        )?;
+
+        ctx.accounts.treasury_usdc.reload()?;
+        ctx.accounts.treasury_token.reload()?;
+        let spent = usdc_before.saturating_sub(ctx.accounts.treasury_usdc.amount);
+        let received = ctx
+            .accounts
+            .treasury_token
+            .amount
+            .saturating_sub(tokens_before);
+        require!(spent <= usdc_amount, ErrorCode::InvalidVenueInstruction);
+
+        // The caller chose the route, so the fill must clear the oracle price less slippage
+        let decimals = ctx.accounts.token_mint.decimals;
+        let fair_tokens_out = price.amount_for_usd(spent, decimals)?;
+        let floor =
+            (fair_tokens_out as u128 * (10_000 - treasury.slippage_bps as u128) / 10_000) as u64;
+        require!(
+            received > 0 && received >= floor,
+            ErrorCode::SlippageExceeded
+        );
+        let reference_price = price.micro_usd_per_unit()?;
+        let execution_price = Twap::spot_price(spent, received, decimals).unwrap_or(u64::MAX);
+        let degraded_execution = treasury.record_settlement(
+            Some(reference_price),
+            execution_price,
+            clock.unix_timestamp,
+        );
+        record_quote(
+            ctx.accounts.quote_board.as_deref_mut(),
+            spent,
+            received,
+            &clock,
+        );
+        treasury.record_buyback_tokens(received)?;
+
+        treasury.settle_usdc_spend(UsdcSpend::Buyback, usdc_amount, spent, clock.unix_timestamp)?;
+
+        // The reward is paid out of the buyback allocation
+        let reward = treasury.keeper_reward(spent);
+        if reward > 0 {
+            spend_usdc(
+                treasury,
+                UsdcSpend::Buyback,
+                &ctx.accounts.treasury_usdc,
+                ctx.accounts.keeper_usdc.to_account_info(),
+                &ctx.accounts.token_program,
+                reward,
+                clock.unix_timestamp,
+            )?;
+        }
+        treasury.record_operation(OperationKind::Buyback, clock.unix_timestamp);
+        treasury.stalled = false;
+
+        emit!(BuybackExecuted {
+            usdc_amount: spent,
+            requested_usdc_amount: usdc_amount,
+            min_tokens_out: floor,
+            tokens_received: received,
+            execution_price,
+            reference_price,
+            degraded_execution,
+            timestamp: clock.unix_timestamp,
+        });
+        emit!(KeeperRewardPaid {
+            keeper: ctx.accounts.keeper.key(),
+            usdc_amount: reward,
+            cranked_usdc_amount: spent,
+            reward_bps: treasury.keeper_reward_bps,
+            timestamp: clock.unix_timestamp,
+        });
+        if let Some(alert) = treasury.large_operation_alert(
+            AlertedOperation::Buyback,
+            spent,
+            ctx.accounts.keeper.key(),
+            ctx.accounts.treasury_usdc.key(),
+            usdc_before,
+            &clock,
+        ) {
+            emit!(alert);
+        }
+
+        Ok(())
+    }
+
+    /// Create the operations calendar
+    pub fn initialize_calendar(ctx: Context<InitializeCalendar>) -> Result<()> {
+        let calendar = &mut ctx.accounts.calendar;
+        calendar.bump = ctx.bumps.calendar;
+        calendar.entries = ctx.accounts.treasury.calendar_entries()?;
+        calendar.updated_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// Permissionless: rebuild the calendar from the treasury's current schedule.
+    /// Cranks call it after operations that move a date.
+    pub fn sync_calendar(ctx: Context<SyncCalendar>) -> Result<()> {
+        let calendar = &mut ctx.accounts.calendar;
+        calendar.entries = ctx.accounts.treasury.calendar_entries()?;
+        calendar.updated_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// End-of-life: propose migrating to `new_token_mint` at `ratio_numerator /
+    /// ratio_denominator` new tokens per old token. Requires the guardian's
+    /// co-signature; swaps and the final redirect open after
+    /// `TOKEN_MIGRATION_TIMELOCK_SECONDS`.
+    pub fn propose_token_migration(
+        ctx: Context<ProposeTokenMigration>,
+        ratio_numerator: u64,
+        ratio_denominator: u64,
+    ) -> Result<()> {
+        require!(
+            ratio_numerator > 0 && ratio_denominator > 0,
+            ErrorCode::InvalidMigrationRatio
+        );
+        let old_mint = ctx.accounts.treasury.token_mint;
+        let new_mint = ctx.accounts.new_token_mint.key();
+        require!(new_mint != old_mint, ErrorCode::InvalidMigrationRatio);
+
+        let clock = Clock::get()?;
+        let migration = &mut ctx.accounts.migration;
+        migration.old_mint = old_mint;
+        migration.new_mint = new_mint;
+        migration.ratio_numerator = ratio_numerator;
+        migration.ratio_denominator = ratio_denominator;
+        migration.eta = clock
+            .unix_timestamp
+            .checked_add(TOKEN_MIGRATION_TIMELOCK_SECONDS)
+            .ok_or(ErrorCode::Overflow)?;
+        migration.old_tokens_migrated = 0;
+        migration.new_tokens_received = 0;
+        migration.finalized_at = 0;
+        migration.bump = ctx.bumps.migration;
+
+        emit!(TokenMigrationProposed {
+            old_mint,
+            new_mint,
+            ratio_numerator,
+            ratio_denominator,
+            guardian: ctx.accounts.guardian.key(),
+            eta: migration.eta,
+            timestamp: clock.unix_timestamp,
+        });
+
+        record_key_activity(
+            ctx.accounts.guardian_activity.as_mut(),
+            KeyAction::GuardianCoSign,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    /// Abort a token migration before any treasury tokens were swapped
+    pub fn cancel_token_migration(ctx: Context<CancelTokenMigration>) -> Result<()> {
+        let migration = &ctx.accounts.migration;
+        require!(
+            migration.old_tokens_migrated == 0,
+            ErrorCode::TokenMigrationInProgress
+        );
+
+        emit!(TokenMigrationCancelled {
+            old_mint: migration.old_mint,
+            new_mint: migration.new_mint,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Swap `amount` treasury-held old tokens for new ones at the fixed ratio: the
+    /// old tokens go to the migration's old-token escrow and the new ones come out
+    /// of its pre-funded new-token escrow
+    pub fn migrate_treasury_tokens(ctx: Context<MigrateTreasuryTokens>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        let clock = Clock::get()?;
+        let migration = &mut ctx.accounts.migration;
+        require!(
+            migration.finalized_at == 0,
+            ErrorCode::MigrationAlreadyFinalized
+        );
+        require!(
+            clock.unix_timestamp >= migration.eta,
+            ErrorCode::TimelockActive
+        );
+        let new_amount = migration.new_amount_for(amount)?;
+        require!(new_amount > 0, ErrorCode::InvalidAmount);
+
+        let treasury = &ctx.accounts.treasury;
+        let treasury_seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let cpi_accounts = token_interface::TransferChecked {
+            from: ctx.accounts.treasury_old_token.to_account_info(),
+            mint: ctx.accounts.old_token_mint.to_account_info(),
+            to: ctx.accounts.old_token_escrow.to_account_info(),
+            authority: treasury.to_account_info(),
+        };
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                cpi_accounts,
+                &[&treasury_seeds[..]],
+            ),
+            amount,
+            ctx.accounts.old_token_mint.decimals,
+        )?;
+
+        let migration_seeds = &[b"token_migration".as_ref(), &[migration.bump]];
+        let cpi_accounts = token_interface::TransferChecked {
+            from: ctx.accounts.new_token_escrow.to_account_info(),
+            mint: ctx.accounts.new_token_mint.to_account_info(),
+            to: ctx.accounts.treasury_new_token.to_account_info(),
+            authority: migration.to_account_info(),
+        };
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                cpi_accounts,
+                &[&migration_seeds[..]],
+            ),
+            new_amount,
+            ctx.accounts.new_token_mint.decimals,
+        )?;
+
+        migration.old_tokens_migrated = migration
+            .old_tokens_migrated
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        migration.new_tokens_received = migration
+            .new_tokens_received
+            .checked_add(new_amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(TreasuryTokensMigrated {
+            old_mint: migration.old_mint,
+            new_mint: migration.new_mint,
+            old_amount: amount,
+            new_amount,
+            ratio_numerator: migration.ratio_numerator,
+            ratio_denominator: migration.ratio_denominator,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Point the treasury at the new mint, which redirects every buyback, burn and
+    /// LP instruction to it. Requires the treasury to be paused and the guardian's
+    /// co-signature. The token price feed is cleared and the mint-config check
+    /// re-pinned to the new mint, so set the new token oracle before resuming.
+    pub fn finalize_token_migration(ctx: Context<FinalizeTokenMigration>) -> Result<()> {
+        let clock = Clock::get()?;
+        let migration = &mut ctx.accounts.migration;
+        require!(
+            migration.finalized_at == 0,
+            ErrorCode::MigrationAlreadyFinalized
+        );
+        require!(
+            clock.unix_timestamp >= migration.eta,
+            ErrorCode::TimelockActive
+        );
+        migration.finalized_at = clock.unix_timestamp;
+
+        let treasury = &mut ctx.accounts.treasury;
+        require!(treasury.fully_paused(), ErrorCode::NotPaused);
+        let new_token_mint = &ctx.accounts.new_token_mint;
+        treasury.token_mint = migration.new_mint;
+        treasury.token_usd_oracle = Pubkey::default();
+        treasury.expected_mint_authority = new_token_mint.mint_authority.into();
+        treasury.expected_freeze_authority = new_token_mint.freeze_authority.into();
+        treasury.mint_config_verified_at = clock.unix_timestamp;
+
+        emit!(TokenMigrationFinalized {
+            old_mint: migration.old_mint,
+            new_mint: migration.new_mint,
+            old_tokens_migrated: migration.old_tokens_migrated,
+            new_tokens_received: migration.new_tokens_received,
+            guardian: ctx.accounts.guardian.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        record_key_activity(
+            ctx.accounts.guardian_activity.as_mut(),
+            KeyAction::GuardianCoSign,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    /// Hand the admin role (configuration and role assignment) to `admin`
+    pub fn set_admin(ctx: Context<UpdateConfig>, admin: Pubkey) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        require!(admin != Pubkey::default(), ErrorCode::InvalidRoleAccount);
+        let treasury = &mut ctx.accounts.treasury;
+        let previous = treasury.authority;
+        treasury.authority = admin;
+        // A binding only describes the admin it was verified against
+        if treasury.squads_multisig != Pubkey::default()
+            && admin
+                != squads::vault_address(&treasury.squads_multisig, treasury.squads_vault_index)
+        {
+            treasury.squads_multisig = Pubkey::default();
+            treasury.squads_vault_index = 0;
+        }
+        if treasury.realms_governance != admin {
+            treasury.realms_governance = Pubkey::default();
+        }
+
+        emit!(AdminRoleSet {
+            previous,
+            admin,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Assign the operator role (buybacks, burns, liquidity, conversions)
+    pub fn set_operator_role(ctx: Context<UpdateConfig>, operator: Pubkey) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        let treasury = &mut ctx.accounts.treasury;
+        require!(
+            operator != Pubkey::default()
+                && (treasury.cosign_threshold_usdc == 0 || operator != treasury.co_signer),
+            ErrorCode::InvalidRoleAccount
+        );
+        let previous = treasury.operator;
+        treasury.operator = operator;
+
+        emit!(OperatorRoleSet {
+            previous,
+            operator,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Assign the pauser role, which can only call `emergency_pause`
+    pub fn set_pauser(ctx: Context<UpdateConfig>, pauser: Pubkey) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        require!(pauser != Pubkey::default(), ErrorCode::InvalidRoleAccount);
+        let treasury = &mut ctx.accounts.treasury;
+        let previous = treasury.pauser;
+        treasury.pauser = pauser;
+
+        emit!(PauserRoleSet {
+            previous,
+            pauser,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Record that the admin is vault `vault_index` of the Squads multisig
+    /// `multisig`, so clients and monitors can verify who controls the treasury.
+    /// Call it through the multisig once the admin role was handed to the vault.
+    pub fn bind_squads_multisig(ctx: Context<BindSquadsMultisig>, vault_index: u8) -> Result<()> {
+        let multisig = &ctx.accounts.multisig;
+        squads::check_multisig(multisig)?;
+        let vault = squads::vault_address(&multisig.key(), vault_index);
+        let treasury = &mut ctx.accounts.treasury;
+        require_keys_eq!(vault, treasury.authority, ErrorCode::AdminNotSquadsVault);
+        treasury.squads_multisig = multisig.key();
+        treasury.squads_vault_index = vault_index;
+
+        emit!(SquadsMultisigBound {
+            multisig: multisig.key(),
+            vault,
+            vault_index,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Open the activity log of `key`, recording its use of the admin, operator,
+    /// pauser or guardian role from then on. Permissionless.
+    pub fn open_key_activity(ctx: Context<OpenKeyActivity>, key: Pubkey) -> Result<()> {
+        let activity = &mut ctx.accounts.activity;
+        activity.key = key;
+        activity.opened_at = Clock::get()?.unix_timestamp;
+        activity.bump = ctx.bumps.activity;
+        Ok(())
+    }
+
+    /// Open the quote board that settled buybacks publish their execution price to.
+    /// Permissionless: anyone may pay for the account.
+    pub fn open_quote_board(ctx: Context<OpenQuoteBoard>) -> Result<()> {
+        let board = &mut ctx.accounts.quote_board;
+        board.token_decimals = ctx.accounts.token_mint.decimals;
+        board.bump = ctx.bumps.quote_board;
+        Ok(())
+    }
+
+    /// Set the intervals milestones are recorded at: every `burn_interval_tokens`
+    /// tokens burned and every `buyback_interval_usdc` USDC spent on buybacks.
+    /// Zero turns that kind of milestone off.
+    pub fn set_milestone_intervals(
+        ctx: Context<UpdateConfig>,
+        burn_interval_tokens: u64,
+        buyback_interval_usdc: u64,
+    ) -> Result<()> {
+        ctx.accounts.record_activity()?;
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.burn_milestone_interval_tokens = burn_interval_tokens;
+        treasury.buyback_milestone_interval_usdc = buyback_interval_usdc;
+
+        emit!(ConfigUpdated {
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Record the `threshold` milestone of `kind` once the lifetime total has
+    /// crossed it, in a `Milestone` account embedding the treasury's totals at
+    /// the time. Permissionless: anyone may pay for the account, and each
+    /// milestone can only be recorded once.
+    pub fn record_milestone(
+        ctx: Context<RecordMilestone>,
+        kind: MilestoneKind,
+        threshold: u64,
+    ) -> Result<()> {
+        let treasury = &ctx.accounts.treasury;
+        treasury.require_milestone_reached(kind, threshold)?;
+
+        let clock = Clock::get()?;
+        let milestone = &mut ctx.accounts.milestone;
+        milestone.kind = kind;
+        milestone.threshold = threshold;
+        milestone.total_tokens_burned = treasury.total_tokens_burned;
+        milestone.total_buybacks_usdc = treasury.total_buybacks_usdc;
+        milestone.total_usdc_converted = treasury.total_usdc_converted;
+        milestone.epoch = treasury.current_epoch;
+        milestone.slot = clock.slot;
+        milestone.timestamp = clock.unix_timestamp;
+        milestone.recorded_by = ctx.accounts.payer.key();
+        milestone.bump = ctx.bumps.milestone;
+
+        emit!(MilestoneReached {
+            milestone: milestone.key(),
+            kind,
+            threshold,
+            total_tokens_burned: milestone.total_tokens_burned,
+            total_buybacks_usdc: milestone.total_buybacks_usdc,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Lengthen the configuration delay immediately. Shortening or removing it is
+    /// itself a configuration change and goes through `propose_config`.
+    pub fn set_config_delay(ctx: Context<UpdateConfig>, delay_seconds: i64) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        let treasury = &mut ctx.accounts.treasury;
+        require!(
+            delay_seconds >= treasury.config_delay_seconds,
+            ErrorCode::ConfigChangeTimelocked
+        );
+        treasury.config_delay_seconds = delay_seconds;
+
+        emit!(ConfigUpdated {
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Queue a change to allocations, limits, the bounty policy or the config delay
+    /// itself, executable after `config_delay_seconds`. One proposal can be pending
+    /// at a time.
+    pub fn propose_config(ctx: Context<ProposeConfig>, change: ConfigChange) -> Result<()> {
+        change.validate()?;
+        record_key_activity(
+            ctx.accounts.authority_activity.as_mut(),
+            KeyAction::Config,
+            0,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.eta = now
+            .checked_add(ctx.accounts.treasury.config_delay_seconds)
+            .ok_or(ErrorCode::Overflow)?;
+        proposal.proposed_at = now;
+        proposal.change = change.clone();
+        proposal.bump = ctx.bumps.proposal;
+
+        emit!(ConfigProposed {
+            change,
+            eta: proposal.eta,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Apply the pending configuration proposal once its delay has passed
+    pub fn execute_config(ctx: Context<ExecuteConfig>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let proposal = &ctx.accounts.proposal;
+        require!(now >= proposal.eta, ErrorCode::TimelockActive);
+        let previous = ctx.accounts.treasury.allocation_split();
+        ctx.accounts
+            .treasury
+            .apply_config_change(&proposal.change)?;
+        if let Some(allocations) = proposal.change.allocations {
+            emit!(AllocationsUpdated {
+                updated_by: ctx.accounts.authority.key(),
+                previous,
+                allocations,
+                timestamp: now,
+            });
+        }
+        if proposal.change.bounty_max_payout_usdc.is_some()
+            || proposal.change.bounty_timelock_seconds.is_some()
+        {
+            emit!(BountyPolicyUpdated {
+                max_payout_usdc: ctx.accounts.treasury.bounty_max_payout_usdc,
+                timelock_seconds: ctx.accounts.treasury.bounty_timelock_seconds,
+                timestamp: now,
+            });
+        }
+        record_key_activity(
+            ctx.accounts.authority_activity.as_mut(),
+            KeyAction::Config,
+            0,
+        )?;
+
+        emit!(ConfigExecuted {
+            change: ctx.accounts.proposal.change.clone(),
+            timestamp: now,
+        });
+        emit!(ConfigUpdated { timestamp: now });
+
+        Ok(())
+    }
+
+    /// Drop the pending configuration proposal
+    pub fn cancel_config(ctx: Context<CancelConfig>) -> Result<()> {
+        emit!(ConfigProposalCancelled {
+            change: ctx.accounts.proposal.change.clone(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Open the response log of `guardian`. Permissionless: anyone may pay for it.
+    pub fn open_guardian_record(ctx: Context<OpenGuardianRecord>, guardian: Pubkey) -> Result<()> {
+        let record = &mut ctx.accounts.record;
+        record.guardian = guardian;
+        record.opened_at = Clock::get()?.unix_timestamp;
+        record.bump = ctx.bumps.record;
+        Ok(())
+    }
+
+    /// Guardian: acknowledge a circuit-breaker trip. The time since the trip is
+    /// logged as a response in the guardian's record.
+    pub fn acknowledge_circuit_breaker(ctx: Context<GuardianRespond>) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        require!(
+            treasury.circuit_breaker_tripped_at != 0,
+            ErrorCode::NoCircuitBreakerTrip
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let response_seconds = now.saturating_sub(treasury.circuit_breaker_tripped_at);
+        treasury.circuit_breaker_tripped_at = 0;
+
+        let record = &mut ctx.accounts.record;
+        record.record_response(response_seconds, false);
+
+        emit!(GuardianResponded {
+            guardian: record.guardian,
+            review: GuardianReview::CircuitBreaker,
+            response_seconds: record.last_response_seconds,
+            vetoed: false,
+            average_response_seconds: record.average_response_seconds(),
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Guardian: review the pending config proposal, closing it when `veto` is set.
+    /// The time since it was proposed is logged as a response in the guardian's
+    /// record; each proposal can be reviewed once.
+    pub fn review_config_proposal(ctx: Context<ReviewConfigProposal>, veto: bool) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        let proposal = &ctx.accounts.proposal;
+        require!(
+            proposal.proposed_at != treasury.last_reviewed_proposal_at,
+            ErrorCode::ProposalAlreadyReviewed
+        );
+        treasury.last_reviewed_proposal_at = proposal.proposed_at;
+
+        let now = Clock::get()?.unix_timestamp;
+        let record = &mut ctx.accounts.record;
+        record.record_response(now.saturating_sub(proposal.proposed_at), veto);
+
+        emit!(GuardianResponded {
+            guardian: record.guardian,
+            review: GuardianReview::ConfigProposal,
+            response_seconds: record.last_response_seconds,
+            vetoed: veto,
+            average_response_seconds: record.average_response_seconds(),
+            timestamp: now,
+        });
+
+        if veto {
+            emit!(ConfigProposalCancelled {
+                change: proposal.change.clone(),
+                timestamp: now,
+            });
+            ctx.accounts
+                .proposal
+                .close(ctx.accounts.authority.to_account_info())?;
+        }
+
+        Ok(())
+    }
+
+    /// View: what each leg of a cycle (convert, buyback, liquidity, reserve) would
+    /// execute right now from the current balances, budgets and oracle prices.
+    /// Oracle-dependent outputs are zero when the feed is not passed or unusable.
+    pub fn simulate_cycle(ctx: Context<SimulateCycle>) -> Result<CycleSimulation> {
+        let clock = Clock::get()?;
+        let now = clock.unix_timestamp;
+        let mut treasury = (*ctx.accounts.treasury).clone();
+
+        // Convert: everything pending that the fee vault still holds, once past the
+        // threshold
+        let sol_to_convert = if !treasury.is_paused(PauseFlag::Conversions)
+            && treasury.is_enabled(GatedInstruction::RecordUsdcConversion)
+            && treasury.sol_pending_conversion >= treasury.min_sol_to_convert
+        {
+            treasury
+                .sol_pending_conversion
+                .min(ctx.accounts.fee_vault.recorded_lamports)
+        } else {
+            0
+        };
+        let usdc_from_conversion = match ctx
+            .accounts
+            .sol_usd_oracle
+            .as_deref()
+            .filter(|a| a.key() == treasury.sol_usd_oracle)
+        {
+            Some(feed) if sol_to_convert > 0 => {
+                oracle::read_price(feed, now, treasury.oracle_max_age_seconds)
+                    .and_then(|price| price.value_usd(sol_to_convert, SOL_DECIMALS))
+                    .unwrap_or(0)
+            }
+            _ => 0,
+        };
+        treasury.total_usdc_converted = treasury
+            .total_usdc_converted
+            .checked_add(usdc_from_conversion)
+            .ok_or(ErrorCode::Overflow)?;
+        let mut usdc_available = ctx
+            .accounts
+            .treasury_usdc
+            .amount
+            .saturating_add(usdc_from_conversion);
+
+        // Buyback: the rest of the allocation, capped per cycle and by the vault
+        let buyback_skip_reason = treasury.buyback_skip_reason(now)?;
+        let buyback_usdc = if buyback_skip_reason.is_none() {
+            treasury
+                .remaining_buyback_budget()?
+                .min(treasury.cycle_limit_usdc())
+                .min(usdc_available)
+        } else {
+            0
+        };
+        usdc_available -= buyback_usdc;
+        let pyth = ctx
+            .accounts
+            .token_usd_oracle
+            .as_deref()
+            .filter(|a| a.key() == treasury.token_usd_oracle);
+        let switchboard = ctx
+            .accounts
+            .token_switchboard_feed
+            .as_deref()
+            .filter(|a| a.key() == treasury.token_switchboard_feed);
+        let buyback_tokens_out = if buyback_usdc > 0 {
+            oracle::read_price_with_fallback(
+                pyth,
+                switchboard,
+                now,
+                treasury.oracle_max_age_seconds,
+                treasury.switchboard_max_age_seconds,
+            )
+            .and_then(|price| price.amount_for_usd(buyback_usdc, ctx.accounts.token_mint.decimals))
+            .unwrap_or(0)
+        } else {
+            0
+        };
+
+        // Liquidity: same limits, from what the buyback leaves in the vault
+        let liquidity_usdc = if !treasury.is_paused(PauseFlag::Liquidity)
+            && treasury.is_enabled(GatedInstruction::AddLiquidity)
+            && treasury
+                .require_ready(OperationKind::Liquidity, now)
+                .is_ok()
+        {
+            treasury
+                .remaining_liquidity_budget()?
+                .min(treasury.cycle_limit_usdc())
+                .min(usdc_available)
+        } else {
+            0
+        };
+
+        Ok(CycleSimulation {
+            sol_to_convert,
+            usdc_from_conversion,
+            buyback_usdc,
+            buyback_tokens_out,
+            buyback_skip_reason,
+            liquidity_usdc,
+            reserve_usdc: treasury.remaining_reserve_budget()?,
+            timestamp: now,
+        })
+    }
+
+    /// Record that the admin is the Realms governance `governance`, enabling
+    /// `update_allocations`. Call it through a proposal once the admin role was
+    /// handed to the governance.
+    pub fn bind_realms_governance(ctx: Context<BindRealmsGovernance>) -> Result<()> {
+        let governance = &ctx.accounts.governance;
+        let realm = realms::check_governance(governance)?;
+        let treasury = &mut ctx.accounts.treasury;
+        require_keys_eq!(
+            governance.key(),
+            treasury.authority,
+            ErrorCode::AdminNotRealmsGovernance
+        );
+        treasury.realms_governance = governance.key();
+
+        emit!(RealmsGovernanceBound {
+            realm,
+            governance: governance.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set the buyback/liquidity/reserve split from an executed Realms proposal.
+    /// The vote and the governance hold-up time stand in for the config delay.
+    pub fn update_allocations(
+        ctx: Context<UpdateAllocations>,
+        allocations: AllocationSplit,
+    ) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        let previous = treasury.allocation_split();
+        treasury.apply_config_change(&ConfigChange {
+            allocations: Some(allocations),
+            max_usdc_per_cycle: None,
+            cooldown_seconds: None,
+            slippage_bps: None,
+            keeper_reward_bps: None,
+            keeper_reward_cap_usdc: None,
+            config_delay_seconds: None,
+            bounty_max_payout_usdc: None,
+            bounty_timelock_seconds: None,
+        })?;
+
+        emit!(AllocationsUpdated {
+            updated_by: ctx.accounts.governance.key(),
+            previous,
+            allocations,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Open a liquidity bootstrapping pool funded with `token_amount` treasury
+    /// tokens and `usdc_amount` USDC from the liquidity allocation. The token weight
+    /// moves linearly from `start_weight_bps` to `end_weight_bps` between
+    /// `start_time` and `end_time`; a falling weight walks the price down. Seeding
+    /// is a liquidity add, held to the same switch, cooldown and size limits.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_lbp(
+        ctx: Context<CreateLbp>,
+        token_amount: u64,
+        usdc_amount: u64,
+        start_time: i64,
+        end_time: i64,
+        start_weight_bps: u16,
+        end_weight_bps: u16,
+    ) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.require_not_paused(PauseFlag::Liquidity)?;
+        treasury.require_enabled(GatedInstruction::AddLiquidity)?;
+        let clock = Clock::get()?;
+        treasury.require_ready(OperationKind::Liquidity, clock.unix_timestamp)?;
+        // The pool account already exists by now, so a broken peg fails the creation
+        // rather than leaving an empty LBP behind; `check_usdc_peg` records the trip
+        require!(
+            !treasury.check_usdc_peg(ctx.accounts.usdc_usd_oracle.as_deref(), &clock)?,
+            ErrorCode::InstructionDisabled
+        );
+        require!(
+            token_amount > 0 && usdc_amount > 0,
+            ErrorCode::InvalidAmount
+        );
+        // A registered LBP pool's own limit applies on top of the per-cycle one
+        let registry = &ctx.accounts.pool_registry;
+        let max_usdc_amount = registry
+            .find(&ctx.accounts.lbp_usdc.key(), &ctx.accounts.lbp_token.key())
+            .map_or(treasury.cycle_limit_usdc(), |i| {
+                registry.pools[i].max_usdc_amount(treasury)
+            });
+        require!(
+            usdc_amount <= max_usdc_amount,
+            ErrorCode::ExceedsMaxPerCycle
+        );
+        require!(
+            start_time >= clock.unix_timestamp && end_time > start_time,
+            ErrorCode::InvalidLbpSchedule
+        );
+        for weight in [start_weight_bps, end_weight_bps] {
+            require!(
+                (MIN_LBP_WEIGHT_BPS..=MAX_LBP_WEIGHT_BPS).contains(&weight),
+                ErrorCode::InvalidLbpSchedule
+            );
+        }
+        treasury.require_co_signer(
+            usdc_amount,
+            ctx.accounts.authority.key(),
+            ctx.accounts.co_signer.as_ref().map(|s| s.key()),
+            ctx.accounts.co_signer_operator.as_deref(),
+        )?;
+
+        spend_usdc(
+            treasury,
+            UsdcSpend::Liquidity,
+            &ctx.accounts.treasury_usdc,
+            ctx.accounts.lbp_usdc.to_account_info(),
+            &ctx.accounts.token_program,
+            usdc_amount,
+            clock.unix_timestamp,
+        )?;
+
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let signer = &[&seeds[..]];
+        let cpi_accounts = token_interface::TransferChecked {
+            from: ctx.accounts.treasury_token.to_account_info(),
+            mint: ctx.accounts.token_mint.to_account_info(),
+            to: ctx.accounts.lbp_token.to_account_info(),
+            authority: treasury.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_2022_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token_interface::transfer_checked(cpi_ctx, token_amount, ctx.accounts.token_mint.decimals)?;
+        // The reserves track what actually arrived, net of any transfer fee
+        ctx.accounts.lbp_token.reload()?;
+
+        treasury.record_operation(OperationKind::Liquidity, clock.unix_timestamp);
+        treasury.stalled = false;
+
+        let lbp = &mut ctx.accounts.lbp;
+        lbp.index = treasury.lbp_count;
+        lbp.token_mint = treasury.token_mint;
+        lbp.token_vault = ctx.accounts.lbp_token.key();
+        lbp.usdc_vault = ctx.accounts.lbp_usdc.key();
+        lbp.start_time = start_time;
+        lbp.end_time = end_time;
+        lbp.start_weight_bps = start_weight_bps;
+        lbp.end_weight_bps = end_weight_bps;
+        lbp.token_reserve = ctx.accounts.lbp_token.amount;
+        lbp.usdc_reserve = usdc_amount;
+        lbp.usdc_seeded = usdc_amount;
+        lbp.tokens_sold = 0;
+        lbp.usdc_raised = 0;
+        lbp.contribution_count = 0;
+        lbp.settled_at = 0;
+        lbp.bump = ctx.bumps.lbp;
+
+        treasury.lbp_count = treasury
+            .lbp_count
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(LbpCreated {
+            index: lbp.index,
+            token_amount: lbp.token_reserve,
+            usdc_amount,
+            start_time,
+            end_time,
+            start_weight_bps,
+            end_weight_bps,
+            timestamp: clock.unix_timestamp,
+        });
+
+        record_key_activity(
+            ctx.accounts.authority_activity.as_mut(),
+            KeyAction::Liquidity,
+            usdc_amount,
+        )?;
+
+        Ok(())
+    }
+
+    /// Buy tokens from an active LBP with `usdc_amount` USDC at the current weights.
+    /// Open to anyone between the pool's start and end time.
+    pub fn contribute_lbp(
+        ctx: Context<ContributeLbp>,
+        usdc_amount: u64,
+        min_tokens_out: u64,
+    ) -> Result<()> {
+        ctx.accounts
+            .treasury
+            .require_not_paused(PauseFlag::Liquidity)?;
+        require!(usdc_amount > 0, ErrorCode::InvalidAmount);
+        let clock = Clock::get()?;
+        let lbp = &mut ctx.accounts.lbp;
+        require!(
+            lbp.settled_at == 0
+                && clock.unix_timestamp >= lbp.start_time
+                && clock.unix_timestamp < lbp.end_time,
+            ErrorCode::LbpNotActive
+        );
+        let weight_bps = lbp.token_weight_bps(clock.unix_timestamp);
+        let tokens_out = lbp.tokens_out(usdc_amount, weight_bps)?;
+        require!(
+            tokens_out > 0 && tokens_out >= min_tokens_out,
+            ErrorCode::SlippageExceeded
+        );
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.contributor_usdc.to_account_info(),
+            to: ctx.accounts.lbp_usdc.to_account_info(),
+            authority: ctx.accounts.contributor.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+        transfer_tokens(cpi_ctx, usdc_amount)?;
+
+        let index_bytes = lbp.index.to_le_bytes();
+        let seeds = &[b"lbp".as_ref(), index_bytes.as_ref(), &[lbp.bump]];
+        let cpi_accounts = token_interface::TransferChecked {
+            from: ctx.accounts.lbp_token.to_account_info(),
+            mint: ctx.accounts.token_mint.to_account_info(),
+            to: ctx.accounts.contributor_token.to_account_info(),
+            authority: lbp.to_account_info(),
+        };
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_2022_program.to_account_info(),
+                cpi_accounts,
+                &[&seeds[..]],
+            ),
+            tokens_out,
+            ctx.accounts.token_mint.decimals,
+        )?;
+
+        lbp.usdc_reserve = lbp
+            .usdc_reserve
+            .checked_add(usdc_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        lbp.token_reserve = lbp
+            .token_reserve
+            .checked_sub(tokens_out)
+            .ok_or(ErrorCode::Overflow)?;
+        lbp.usdc_raised = lbp
+            .usdc_raised
+            .checked_add(usdc_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        lbp.tokens_sold = lbp
+            .tokens_sold
+            .checked_add(tokens_out)
+            .ok_or(ErrorCode::Overflow)?;
+        lbp.contribution_count = lbp
+            .contribution_count
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(LbpContribution {
+            index: lbp.index,
+            contributor: ctx.accounts.contributor.key(),
+            usdc_amount,
+            tokens_out,
+            token_weight_bps: weight_bps,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless once the LBP ended: return unsold tokens and all USDC to the
+    /// treasury. The seed goes back to the liquidity allocation and the raised USDC
+    /// is split across the allocations like converted fees.
+    pub fn settle_lbp(ctx: Context<SettleLbp>) -> Result<()> {
+        let clock = Clock::get()?;
+        let lbp = &mut ctx.accounts.lbp;
+        require!(lbp.settled_at == 0, ErrorCode::LbpAlreadySettled);
+        require!(clock.unix_timestamp >= lbp.end_time, ErrorCode::LbpNotEnded);
+
+        let index_bytes = lbp.index.to_le_bytes();
+        let seeds = &[b"lbp".as_ref(), index_bytes.as_ref(), &[lbp.bump]];
+        let signer = &[&seeds[..]];
+
+        let usdc_returned = ctx.accounts.lbp_usdc.amount;
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.lbp_usdc.to_account_info(),
+            to: ctx.accounts.treasury_usdc.to_account_info(),
+            authority: lbp.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        transfer_tokens(
+            CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+            usdc_returned,
+        )?;
+
+        let tokens_returned = ctx.accounts.lbp_token.amount;
+        let cpi_accounts = token_interface::TransferChecked {
+            from: ctx.accounts.lbp_token.to_account_info(),
+            mint: ctx.accounts.token_mint.to_account_info(),
+            to: ctx.accounts.treasury_token.to_account_info(),
+            authority: lbp.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_2022_program.to_account_info();
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+            tokens_returned,
+            ctx.accounts.token_mint.decimals,
+        )?;
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.total_liquidity_usdc = treasury
+            .total_liquidity_usdc
+            .saturating_sub(lbp.usdc_seeded);
+        treasury.total_usdc_converted = treasury
+            .total_usdc_converted
+            .checked_add(lbp.usdc_raised)
+            .ok_or(ErrorCode::Overflow)?;
+        lbp.settled_at = clock.unix_timestamp;
+
+        emit!(LbpSettled {
+            index: lbp.index,
+            usdc_returned,
+            tokens_returned,
+            usdc_seeded: lbp.usdc_seeded,
+            usdc_raised: lbp.usdc_raised,
+            tokens_sold: lbp.tokens_sold,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Open a treasury Orca Whirlpool position over `[tick_lower, tick_upper)` in a
+    /// registered pool and start tracking it in a `LiquidityPosition` account. The
+    /// position NFT goes to the treasury PDA's token account.
+    pub fn open_whirlpool_position(
+        ctx: Context<OpenWhirlpoolPosition>,
+        tick_lower: i32,
+        tick_upper: i32,
+    ) -> Result<()> {
+        let treasury = &ctx.accounts.treasury;
+        treasury.require_not_paused(PauseFlag::Liquidity)?;
+        treasury.require_enabled(GatedInstruction::AddLiquidity)?;
+        ctx.accounts
+            .operator_stats
+            .require_scope(OperationKind::Liquidity, Some(Venue::Orca))?;
+        require!(
+            CLMM_MIN_TICK <= tick_lower && tick_lower < tick_upper && tick_upper <= CLMM_MAX_TICK,
+            ErrorCode::InvalidTickRange
+        );
+
+        orca_whirlpool::open_position(
+            &orca_whirlpool::OpenAccounts {
+                funder: ctx.accounts.authority.to_account_info(),
+                owner: ctx.accounts.treasury.to_account_info(),
+                position: ctx.accounts.position.to_account_info(),
+                position_mint: ctx.accounts.position_mint.to_account_info(),
+                position_token_account: ctx.accounts.position_token_account.to_account_info(),
+                whirlpool: ctx.accounts.whirlpool.to_account_info(),
+                token_2022_program: ctx.accounts.token_2022_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+                metadata_update_auth: ctx.accounts.metadata_update_auth.to_account_info(),
+                program: ctx.accounts.whirlpool_program.to_account_info(),
+            },
+            tick_lower,
+            tick_upper,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let liquidity_position = &mut ctx.accounts.liquidity_position;
+        liquidity_position.whirlpool = ctx.accounts.whirlpool.key();
+        liquidity_position.position_mint = ctx.accounts.position_mint.key();
+        liquidity_position.position = ctx.accounts.position.key();
+        liquidity_position.tick_lower = tick_lower;
+        liquidity_position.tick_upper = tick_upper;
+        liquidity_position.opened_at = now;
+        liquidity_position.bump = ctx.bumps.liquidity_position;
+
+        emit!(WhirlpoolPositionOpened {
+            whirlpool: liquidity_position.whirlpool,
+            position_mint: liquidity_position.position_mint,
+            tick_lower,
+            tick_upper,
+            timestamp: now,
+        });
+
+        record_key_activity(
+            ctx.accounts.authority_activity.as_mut(),
+            KeyAction::Liquidity,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    /// Add `liquidity` to a treasury Whirlpool position, paying at most
+    /// `usdc_amount_max` USDC and `token_amount_max` tokens
+    pub fn increase_whirlpool_liquidity(
+        ctx: Context<WhirlpoolLiquidity>,
+        liquidity: u128,
+        usdc_amount_max: u64,
+        token_amount_max: u64,
+    ) -> Result<()> {
+        let (position, ticks) = ctx.accounts.position_accounts();
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.require_not_paused(PauseFlag::Liquidity)?;
+        treasury.require_enabled(GatedInstruction::AddLiquidity)?;
+        ctx.accounts
+            .operator_stats
+            .require_scope(OperationKind::Liquidity, Some(Venue::Orca))?;
+        require!(liquidity > 0, ErrorCode::InvalidAmount);
+
+        let clock = Clock::get()?;
+        treasury.require_ready(OperationKind::Liquidity, clock.unix_timestamp)?;
+        if treasury.check_usdc_peg(ctx.accounts.usdc_usd_oracle.as_deref(), &clock)? {
+            return Ok(());
+        }
+
+        let registry = &ctx.accounts.pool_registry;
+        let pool = registry
+            .find(
+                &ctx.accounts.pool_usdc_vault.key(),
+                &ctx.accounts.pool_token_vault.key(),
+            )
+            .map(|i| &registry.pools[i])
+            .ok_or(ErrorCode::UnregisteredPool)?;
+        require!(
+            usdc_amount_max <= pool.max_usdc_amount(treasury),
+            ErrorCode::ExceedsMaxPerCycle
+        );
+        treasury.require_co_signer(
+            usdc_amount_max,
+            ctx.accounts.authority.key(),
+            ctx.accounts.co_signer.as_ref().map(|s| s.key()),
+            ctx.accounts.co_signer_operator.as_deref(),
+        )?;
+
+        treasury.authorize_usdc_spend(
+            UsdcSpend::Liquidity,
+            usdc_amount_max,
+            clock.unix_timestamp,
+        )?;
+
+        let (token_max_a, token_max_b) = orca_whirlpool::order_pair(
+            &treasury.usdc_mint,
+            &treasury.token_mint,
+            usdc_amount_max,
+            token_amount_max,
+        );
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let usdc_before = ctx.accounts.treasury_usdc.amount;
+        let token_before = ctx.accounts.treasury_token.amount;
+        orca_whirlpool::increase_liquidity(
+            &position,
+            &ticks,
+            liquidity,
+            token_max_a,
+            token_max_b,
+            &[&seeds[..]],
+        )?;
+
+        ctx.accounts.treasury_usdc.reload()?;
+        ctx.accounts.treasury_token.reload()?;
+        let usdc_amount = usdc_before.saturating_sub(ctx.accounts.treasury_usdc.amount);
+        let token_amount = token_before.saturating_sub(ctx.accounts.treasury_token.amount);
+        require!(
+            usdc_amount <= usdc_amount_max && token_amount <= token_amount_max,
+            ErrorCode::SlippageExceeded
+        );
+
+        treasury.settle_usdc_spend(
+            UsdcSpend::Liquidity,
+            usdc_amount_max,
+            usdc_amount,
+            clock.unix_timestamp,
+        )?;
+        treasury.record_operation(OperationKind::Liquidity, clock.unix_timestamp);
+        treasury.stalled = false;
+
+        let liquidity_position = &mut ctx.accounts.liquidity_position;
+        liquidity_position.add_liquidity(liquidity, usdc_amount, token_amount)?;
+
+        emit!(WhirlpoolLiquidityChanged {
+            position_mint: liquidity_position.position_mint,
+            liquidity_delta: liquidity,
+            increased: true,
+            usdc_amount,
+            token_amount,
+            total_liquidity: liquidity_position.liquidity,
+            timestamp: clock.unix_timestamp,
+        });
+
+        record_key_activity(
+            ctx.accounts.authority_activity.as_mut(),
+            KeyAction::Liquidity,
+            usdc_amount,
+        )?;
+
+        Ok(())
+    }
+
+    /// Remove `liquidity` from a treasury Whirlpool position, receiving at least
+    /// `usdc_amount_min` USDC and `token_amount_min` tokens into the treasury
+    /// vaults. Returned USDC is not credited back to the liquidity budget.
+    pub fn decrease_whirlpool_liquidity(
+        ctx: Context<WhirlpoolLiquidity>,
+        liquidity: u128,
+        usdc_amount_min: u64,
+        token_amount_min: u64,
+    ) -> Result<()> {
+        let (position, ticks) = ctx.accounts.position_accounts();
+        let treasury = &ctx.accounts.treasury;
+        treasury.require_not_paused(PauseFlag::Liquidity)?;
+        ctx.accounts
+            .operator_stats
+            .require_scope(OperationKind::Liquidity, Some(Venue::Orca))?;
+        require!(liquidity > 0, ErrorCode::InvalidAmount);
+        require!(
+            liquidity <= ctx.accounts.liquidity_position.liquidity,
+            ErrorCode::InsufficientPositionLiquidity
+        );
+
+        let (token_min_a, token_min_b) = orca_whirlpool::order_pair(
+            &treasury.usdc_mint,
+            &treasury.token_mint,
+            usdc_amount_min,
+            token_amount_min,
+        );
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let usdc_before = ctx.accounts.treasury_usdc.amount;
+        let token_before = ctx.accounts.treasury_token.amount;
+        orca_whirlpool::decrease_liquidity(
+            &position,
+            &ticks,
+            liquidity,
+            token_min_a,
+            token_min_b,
+            &[&seeds[..]],
+        )?;
+
+        ctx.accounts.treasury_usdc.reload()?;
+        ctx.accounts.treasury_token.reload()?;
+        let usdc_amount = ctx
+            .accounts
+            .treasury_usdc
+            .amount
+            .saturating_sub(usdc_before);
+        let token_amount = ctx
+            .accounts
+            .treasury_token
+            .amount
+            .saturating_sub(token_before);
+        require!(
+            usdc_amount >= usdc_amount_min && token_amount >= token_amount_min,
+            ErrorCode::SlippageExceeded
+        );
+
+        let liquidity_position = &mut ctx.accounts.liquidity_position;
+        liquidity_position.remove_liquidity(liquidity, usdc_amount, token_amount)?;
+
+        emit!(WhirlpoolLiquidityChanged {
+            position_mint: liquidity_position.position_mint,
+            liquidity_delta: liquidity,
+            increased: false,
+            usdc_amount,
+            token_amount,
+            total_liquidity: liquidity_position.liquidity,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        record_key_activity(
+            ctx.accounts.authority_activity.as_mut(),
+            KeyAction::Liquidity,
+            usdc_amount,
+        )?;
+
+        Ok(())
+    }
+
+    /// Collect the trading fees a treasury Whirlpool position has earned into the
+    /// treasury vaults. Permissionless: the fees can only go to the treasury.
+    pub fn collect_whirlpool_fees(ctx: Context<CollectWhirlpoolFees>) -> Result<()> {
+        let position = ctx.accounts.position_accounts();
+        let treasury = &ctx.accounts.treasury;
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let usdc_before = ctx.accounts.treasury_usdc.amount;
+        let token_before = ctx.accounts.treasury_token.amount;
+        orca_whirlpool::collect_fees(&position, &[&seeds[..]])?;
+
+        ctx.accounts.treasury_usdc.reload()?;
+        ctx.accounts.treasury_token.reload()?;
+        let usdc_amount = ctx
+            .accounts
+            .treasury_usdc
+            .amount
+            .saturating_sub(usdc_before);
+        let token_amount = ctx
+            .accounts
+            .treasury_token
+            .amount
+            .saturating_sub(token_before);
+
+        let liquidity_position = &mut ctx.accounts.liquidity_position;
+        liquidity_position.record_fees(usdc_amount, token_amount)?;
+
+        emit!(WhirlpoolFeesCollected {
+            position_mint: liquidity_position.position_mint,
+            usdc_amount,
+            token_amount,
+            total_usdc_fees: liquidity_position.usdc_fees_collected,
+            total_token_fees: liquidity_position.token_fees_collected,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set the bins the next Meteora DLMM position spans, as offsets from the
+    /// pair's active bin when it is opened. The range must sit on USDC's side of
+    /// the active bin for single-sided USDC deposits.
+    pub fn set_dlmm_bin_range(
+        ctx: Context<UpdateConfig>,
+        lower_offset: i32,
+        upper_offset: i32,
+    ) -> Result<()> {
+        ctx.accounts.record_activity()?;
+        require!(
+            meteora_dlmm::bin_range(0, lower_offset, upper_offset).is_some(),
+            ErrorCode::InvalidBinRange
+        );
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.dlmm_bin_offset_lower = lower_offset;
+        treasury.dlmm_bin_offset_upper = upper_offset;
+
+        emit!(ConfigUpdated {
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Open the treasury's Meteora DLMM position in a registered DLMM pair over the
+    /// configured bin range around `active_id`; `add_liquidity_meteora` deposits
+    /// USDC into it
+    pub fn open_meteora_position(ctx: Context<OpenMeteoraPosition>, active_id: i32) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.require_not_paused(PauseFlag::Liquidity)?;
+        treasury.require_enabled(GatedInstruction::AddLiquidity)?;
+        ctx.accounts
+            .operator_stats
+            .require_scope(OperationKind::Liquidity, Some(Venue::Meteora))?;
+        require!(
+            treasury.dlmm_position == Pubkey::default(),
+            ErrorCode::DlmmPositionOpen
+        );
+        let clock = Clock::get()?;
+        if treasury.check_usdc_peg(ctx.accounts.usdc_usd_oracle.as_deref(), &clock)? {
+            return Ok(());
+        }
+
+        let registry = &ctx.accounts.pool_registry;
+        let pool = registry
+            .find(
+                &ctx.accounts.reserve_usdc.key(),
+                &ctx.accounts.reserve_token.key(),
+            )
+            .map(|i| &registry.pools[i])
+            .ok_or(ErrorCode::UnregisteredPool)?;
+        require!(
+            pool.pool_type == PoolType::MeteoraDlmm,
+            ErrorCode::WrongPoolType
+        );
+
+        let bins = meteora_dlmm::bin_range(
+            active_id,
+            treasury.dlmm_bin_offset_lower,
+            treasury.dlmm_bin_offset_upper,
+        )
+        .ok_or(ErrorCode::InvalidBinRange)?;
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        meteora_dlmm::initialize_position(
+            &meteora_dlmm::OpenAccounts {
+                payer: ctx.accounts.authority.to_account_info(),
+                position: ctx.accounts.position.to_account_info(),
+                lb_pair: ctx.accounts.lb_pair.to_account_info(),
+                owner: ctx.accounts.treasury.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                rent: ctx.accounts.rent.to_account_info(),
+                event_authority: ctx.accounts.event_authority.to_account_info(),
+                program: ctx.accounts.meteora_program.to_account_info(),
+            },
+            *bins.start(),
+            bins.end() - bins.start() + 1,
+            &[&seeds[..]],
+        )?;
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.dlmm_lb_pair = ctx.accounts.lb_pair.key();
+        treasury.dlmm_position = ctx.accounts.position.key();
+        treasury.dlmm_lower_bin_id = *bins.start();
+        treasury.dlmm_upper_bin_id = *bins.end();
+        treasury.dlmm_usdc_deposited = 0;
+
+        emit!(MeteoraPositionOpened {
+            lb_pair: treasury.dlmm_lb_pair,
+            position: treasury.dlmm_position,
+            lower_bin_id: treasury.dlmm_lower_bin_id,
+            upper_bin_id: treasury.dlmm_upper_bin_id,
+            timestamp: clock.unix_timestamp,
+        });
+
+        record_key_activity(
+            ctx.accounts.authority_activity.as_mut(),
+            KeyAction::Liquidity,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    /// Deposit `usdc_amount` from the liquidity allocation into the treasury's
+    /// Meteora DLMM position, spread evenly over its bins with no token side.
+    /// Fails when the pair's active bin is more than `max_active_bin_slippage`
+    /// bins from `active_id`.
+    pub fn add_liquidity_meteora(
+        ctx: Context<AddLiquidityMeteora>,
+        usdc_amount: u64,
+        active_id: i32,
+        max_active_bin_slippage: i32,
+    ) -> Result<()> {
+        let deposit = ctx.accounts.deposit_accounts();
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.require_not_paused(PauseFlag::Liquidity)?;
+        treasury.require_enabled(GatedInstruction::AddLiquidity)?;
+        ctx.accounts
+            .operator_stats
+            .require_scope(OperationKind::Liquidity, Some(Venue::Meteora))?;
+        require!(usdc_amount > 0, ErrorCode::InvalidAmount);
+
+        let clock = Clock::get()?;
+        treasury.require_ready(OperationKind::Liquidity, clock.unix_timestamp)?;
+        if treasury.check_usdc_peg(ctx.accounts.usdc_usd_oracle.as_deref(), &clock)? {
+            return Ok(());
+        }
+
+        let registry = &ctx.accounts.pool_registry;
+        let pool = registry
+            .find(
+                &ctx.accounts.reserve_usdc.key(),
+                &ctx.accounts.reserve_token.key(),
+            )
+            .map(|i| &registry.pools[i])
+            .ok_or(ErrorCode::UnregisteredPool)?;
+        require!(
+            pool.pool_type == PoolType::MeteoraDlmm,
+            ErrorCode::WrongPoolType
+        );
+        require!(
+            usdc_amount <= pool.max_usdc_amount(treasury),
+            ErrorCode::ExceedsMaxPerCycle
+        );
+        treasury.require_co_signer(
+            usdc_amount,
+            ctx.accounts.authority.key(),
+            ctx.accounts.co_signer.as_ref().map(|s| s.key()),
+            ctx.accounts.co_signer_operator.as_deref(),
+        )?;
+
+        treasury.authorize_usdc_spend(UsdcSpend::Liquidity, usdc_amount, clock.unix_timestamp)?;
+
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let usdc_before = ctx.accounts.treasury_usdc.amount;
+        meteora_dlmm::add_liquidity_one_side(
+            &deposit,
+            usdc_amount,
+            active_id,
+            max_active_bin_slippage,
+            treasury.dlmm_lower_bin_id..=treasury.dlmm_upper_bin_id,
+            &[&seeds[..]],
+        )?;
+
+        ctx.accounts.treasury_usdc.reload()?;
+        let spent = usdc_before.saturating_sub(ctx.accounts.treasury_usdc.amount);
+        require!(spent <= usdc_amount, ErrorCode::SlippageExceeded);
+
+        treasury.settle_usdc_spend(
+            UsdcSpend::Liquidity,
+            usdc_amount,
+            spent,
+            clock.unix_timestamp,
+        )?;
+        treasury.dlmm_usdc_deposited = treasury
+            .dlmm_usdc_deposited
+            .checked_add(spent)
+            .ok_or(ErrorCode::Overflow)?;
+        treasury.record_operation(OperationKind::Liquidity, clock.unix_timestamp);
+        treasury.stalled = false;
+
+        emit!(MeteoraLiquidityAdded {
+            position: treasury.dlmm_position,
+            usdc_amount: spent,
+            total_usdc_deposited: treasury.dlmm_usdc_deposited,
+            timestamp: clock.unix_timestamp,
+        });
+
+        record_key_activity(
+            ctx.accounts.authority_activity.as_mut(),
+            KeyAction::Liquidity,
+            spent,
+        )?;
+
+        Ok(())
+    }
+
+    /// Start tracking the treasury's liquidity in a registered constant-product
+    /// pool in an `LpPosition` account, whose LP tokens are minted by `lp_mint`
+    pub fn open_lp_position(ctx: Context<OpenLpPosition>) -> Result<()> {
+        let registry = &ctx.accounts.pool_registry;
+        let pool = registry
+            .find(
+                &ctx.accounts.pool_usdc.key(),
+                &ctx.accounts.pool_token.key(),
+            )
+            .map(|i| &registry.pools[i])
+            .ok_or(ErrorCode::UnregisteredPool)?;
+        require!(
+            pool.pool_type == PoolType::ConstantProduct,
+            ErrorCode::WrongPoolType
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let lp_position = &mut ctx.accounts.lp_position;
+        lp_position.venue = pool.venue;
+        lp_position.pool_usdc = ctx.accounts.pool_usdc.key();
+        lp_position.pool_token = ctx.accounts.pool_token.key();
+        lp_position.lp_mint = ctx.accounts.lp_mint.key();
+        lp_position.opened_at = now;
+        lp_position.bump = ctx.bumps.lp_position;
+
+        emit!(LpPositionOpened {
+            venue: lp_position.venue,
+            pool_usdc: lp_position.pool_usdc,
+            pool_token: lp_position.pool_token,
+            lp_mint: lp_position.lp_mint,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw liquidity from a constant-product pool through its venue and record
+    /// what came back on the pool's `LpPosition`. Admin only and allowed while
+    /// paused, so liquidity can be unwound in an emergency. `remaining_accounts` are
+    /// the venue program followed by its withdrawal accounts, and `withdraw_data`
+    /// the venue's withdrawal instruction, which must burn exactly the LP tokens it
+    /// names. Returned USDC is not credited back to the liquidity budget.
+    pub fn remove_liquidity<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RemoveLiquidity<'info>>,
+        withdraw_data: Vec<u8>,
+        usdc_amount_min: u64,
+        token_amount_min: u64,
+    ) -> Result<()> {
+        let accounts = &mut *ctx.accounts;
+        let (lp_amount, usdc_amount, token_amount) = withdraw_lp(
+            &accounts.treasury,
+            accounts.lp_position.venue,
+            ctx.remaining_accounts,
+            withdraw_data,
+            &mut accounts.treasury_lp,
+            &mut accounts.treasury_usdc,
+            &mut accounts.treasury_token,
+            (usdc_amount_min, token_amount_min),
+        )?;
+        let lp_balance = accounts.treasury_lp.amount;
+
+        let lp_position = &mut accounts.lp_position;
+        lp_position.record_withdrawal(lp_amount, usdc_amount, token_amount, lp_balance)?;
+
+        emit!(LiquidityRemoved {
+            pool_usdc: lp_position.pool_usdc,
+            pool_token: lp_position.pool_token,
+            lp_amount,
+            usdc_amount,
+            token_amount,
+            lp_remaining: lp_balance,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        record_key_activity(
+            accounts.authority_activity.as_mut(),
+            KeyAction::Liquidity,
+            usdc_amount,
+        )?;
+
+        Ok(())
+    }
+
+    /// Grow a Treasury account written by an older program version to the current
+    /// layout. Existing fields keep their values and appended fields take their
+    /// `initialize` defaults; the admin pays the extra rent. Mint authority pins
+    /// appended this way start unset, so re-pin them with `set_expected_mint_config`.
+    pub fn migrate_treasury(ctx: Context<MigrateTreasury>) -> Result<()> {
+        let info = ctx.accounts.treasury.to_account_info();
+        let old_len = info.data_len();
+        let new_len = 8 + Treasury::INIT_SPACE;
+        {
+            let data = info.try_borrow_data()?;
+            require!(
+                old_len >= 40 && data[..8] == Treasury::DISCRIMINATOR,
+                ErrorCode::InvalidTreasuryAccount
+            );
+            let admin = Treasury::read_pubkey_at(&data, 0)?;
+            require_keys_eq!(
+                admin,
+                ctx.accounts.authority.key(),
+                ErrorCode::NotTreasuryAdmin
+            );
+        }
+        require!(
+            (TREASURY_MIN_MIGRATABLE_SIZE..new_len).contains(&old_len),
+            ErrorCode::UnknownTreasuryLayout
+        );
+
+        let rent_due = Rent::get()?
+            .minimum_balance(new_len)
+            .saturating_sub(info.lamports());
+        if rent_due > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: info.clone(),
+                    },
+                ),
+                rent_due,
+            )?;
+        }
+        info.realloc(new_len, true)?;
+        Treasury::upgrade_layout(&mut info.try_borrow_mut_data()?, old_len)?;
+
+        emit!(TreasuryMigrated {
+            old_size: old_len as u64,
+            new_size: new_len as u64,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Claim the trading fees a constant-product LP position has earned. Withdraws
+    /// through the venue like `remove_liquidity`, but only while the LP tokens left
+    /// are still worth the net USDC and tokens contributed at the pool price, so no
+    /// principal comes out. The USDC received counts as LP fees and tops up the
+    /// buyback budget; the tokens stay in the treasury vault.
+    pub fn collect_lp_fees<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CollectLpFees<'info>>,
+        withdraw_data: Vec<u8>,
+        usdc_amount_min: u64,
+        token_amount_min: u64,
+    ) -> Result<()> {
+        let accounts = &mut *ctx.accounts;
+        require!(!accounts.treasury.fully_paused(), ErrorCode::Paused);
+        let (lp_amount, usdc_amount, token_amount) = withdraw_lp(
+            &accounts.treasury,
+            accounts.lp_position.venue,
+            ctx.remaining_accounts,
+            withdraw_data,
+            &mut accounts.treasury_lp,
+            &mut accounts.treasury_usdc,
+            &mut accounts.treasury_token,
+            (usdc_amount_min, token_amount_min),
+        )?;
+        let lp_balance = accounts.treasury_lp.amount;
+
+        accounts.pool_usdc.reload()?;
+        accounts.pool_token.reload()?;
+        accounts.lp_mint.reload()?;
+        let lp_position = &mut accounts.lp_position;
+        require!(
+            lp_position.covers_principal(
+                lp_balance,
+                accounts.lp_mint.supply,
+                accounts.pool_usdc.amount,
+                accounts.pool_token.amount,
+            ),
+            ErrorCode::LpFeesExceedEarnings
+        );
+        lp_position.record_fees(lp_amount, usdc_amount, token_amount, lp_balance)?;
+
+        let treasury = &mut accounts.treasury;
+        treasury.record_lp_fees(usdc_amount)?;
+
+        emit!(LpFeesCollected {
+            pool_usdc: lp_position.pool_usdc,
+            pool_token: lp_position.pool_token,
+            lp_amount,
+            usdc_amount,
+            token_amount,
+            total_lp_fees_earned: treasury.total_lp_fees_earned,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        record_key_activity(
+            accounts.authority_activity.as_mut(),
+            KeyAction::Liquidity,
+            usdc_amount,
+        )?;
+
+        Ok(())
+    }
+
+    /// Lock `amount` LP tokens from a treasury LP account in a new `LpLock` until
+    /// `locked_until`, so holders can verify that liquidity cannot be pulled before
+    /// then. The tokens sit in a vault owned by the lock PDA; only `release_lp`
+    /// moves them, and only after the lock expires.
+    pub fn lock_lp(ctx: Context<LockLp>, amount: u64, locked_until: i64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        let clock = Clock::get()?;
+        require!(
+            locked_until > clock.unix_timestamp,
+            ErrorCode::InvalidLockTime
+        );
+
+        let treasury = &mut ctx.accounts.treasury;
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let signer = &[&seeds[..]];
+        let cpi_accounts = token_interface::TransferChecked {
+            from: ctx.accounts.treasury_lp.to_account_info(),
+            mint: ctx.accounts.lp_mint.to_account_info(),
+            to: ctx.accounts.lp_lock_vault.to_account_info(),
+            authority: treasury.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.lp_mint.decimals)?;
+
+        let lp_lock = &mut ctx.accounts.lp_lock;
+        lp_lock.index = treasury.lp_lock_count;
+        lp_lock.lp_mint = ctx.accounts.lp_mint.key();
+        lp_lock.vault = ctx.accounts.lp_lock_vault.key();
+        lp_lock.amount = amount;
+        lp_lock.locked_at = clock.unix_timestamp;
+        lp_lock.locked_until = locked_until;
+        lp_lock.bump = ctx.bumps.lp_lock;
+        treasury.lp_lock_count = treasury
+            .lp_lock_count
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(LpLocked {
+            lp_mint: lp_lock.lp_mint,
+            lock: lp_lock.key(),
+            amount,
+            locked_until,
+            burned: false,
+            total_lp_burned: treasury.total_lp_burned,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Burn `amount` LP tokens from a treasury LP account, locking that liquidity
+    /// in the pool for good. Counted in `total_lp_burned`.
+    pub fn burn_lp(ctx: Context<BurnLp>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        let treasury = &mut ctx.accounts.treasury;
+        let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+        let signer = &[&seeds[..]];
+        let cpi_accounts = token_interface::Burn {
+            mint: ctx.accounts.lp_mint.to_account_info(),
+            from: ctx.accounts.treasury_lp.to_account_info(),
+            authority: treasury.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token_interface::burn(cpi_ctx, amount)?;
+
+        treasury.total_lp_burned = treasury
+            .total_lp_burned
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(LpLocked {
+            lp_mint: ctx.accounts.lp_mint.key(),
+            lock: Pubkey::default(),
+            amount,
+            locked_until: 0,
+            burned: true,
+            total_lp_burned: treasury.total_lp_burned,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Return an expired `LpLock`'s LP tokens to a treasury LP account, closing
+    /// the lock and its vault
+    pub fn release_lp(ctx: Context<ReleaseLp>) -> Result<()> {
+        let clock = Clock::get()?;
+        let lp_lock = &ctx.accounts.lp_lock;
+        require!(
+            clock.unix_timestamp >= lp_lock.locked_until,
+            ErrorCode::LpStillLocked
+        );
+
+        let index = lp_lock.index.to_le_bytes();
+        let seeds = &[b"lp_lock".as_ref(), index.as_ref(), &[lp_lock.bump]];
+        let signer = &[&seeds[..]];
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let amount = ctx.accounts.lp_lock_vault.amount;
+        let cpi_accounts = token_interface::TransferChecked {
+            from: ctx.accounts.lp_lock_vault.to_account_info(),
+            mint: ctx.accounts.lp_mint.to_account_info(),
+            to: ctx.accounts.treasury_lp.to_account_info(),
+            authority: lp_lock.to_account_info(),
+        };
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, signer),
+            amount,
+            ctx.accounts.lp_mint.decimals,
+        )?;
+
+        let cpi_accounts = CloseAccount {
+            account: ctx.accounts.lp_lock_vault.to_account_info(),
+            destination: ctx.accounts.authority.to_account_info(),
+            authority: lp_lock.to_account_info(),
+        };
+        token_interface::close_account(CpiContext::new_with_signer(
+            cpi_program,
+            cpi_accounts,
+            signer,
+        ))?;
+
+        emit!(LpReleased {
+            lp_mint: lp_lock.lp_mint,
+            lock: lp_lock.key(),
+            amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Whitelist `adapter_program`, a program implementing the `strategy` adapter
+    /// interface, to receive up to `cap_usdc` of reserve USDC. Deposits open once
+    /// the configuration delay has passed, so holders can react to a new adapter.
+    pub fn propose_strategy_adapter(
+        ctx: Context<ProposeStrategyAdapter>,
+        cap_usdc: u64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let adapter = &mut ctx.accounts.adapter;
+        adapter.program = ctx.accounts.adapter_program.key();
+        adapter.cap_usdc = cap_usdc;
+        adapter.proposed_at = now;
+        adapter.eta = now
+            .checked_add(ctx.accounts.treasury.config_delay_seconds)
+            .ok_or(ErrorCode::Overflow)?;
+        adapter.bump = ctx.bumps.adapter;
+
+        emit!(StrategyAdapterProposed {
+            program: adapter.program,
+            cap_usdc,
+            eta: adapter.eta,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Lower a strategy adapter's cap immediately. Raising it means proposing the
+    /// adapter again, through the delay.
+    pub fn set_strategy_adapter_cap(
+        ctx: Context<UpdateStrategyAdapter>,
+        cap_usdc: u64,
+    ) -> Result<()> {
+        let adapter = &mut ctx.accounts.adapter;
+        require!(
+            cap_usdc <= adapter.cap_usdc,
+            ErrorCode::ConfigChangeTimelocked
+        );
+        adapter.cap_usdc = cap_usdc;
+
+        emit!(StrategyAdapterCapSet {
+            program: adapter.program,
+            cap_usdc,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Drop a strategy adapter from the whitelist once nothing is deposited in it
+    pub fn remove_strategy_adapter(ctx: Context<RemoveStrategyAdapter>) -> Result<()> {
+        let adapter = &ctx.accounts.adapter;
+        require!(adapter.deposited_usdc == 0, ErrorCode::StrategyAdapterInUse);
+
+        emit!(StrategyAdapterRemoved {
+            program: adapter.program,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Allocate `usdc_amount` of unspent reserve allocation to a whitelisted
+    /// strategy adapter, within its cap. The deposit stays committed against the
+    /// reserve budget until it is withdrawn. `remaining_accounts` are the adapter's
+    /// own accounts.
+    pub fn deposit_to_strategy<'info>(
+        ctx: Context<'_, '_, 'info, 'info, StrategyAdapterFunds<'info>>,
+        usdc_amount: u64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let accounts = &mut *ctx.accounts;
+        require!(!accounts.treasury.fully_paused(), ErrorCode::Paused);
+        require!(usdc_amount > 0, ErrorCode::InvalidAmount);
+        let adapter = &accounts.adapter;
+        require!(
+            clock.unix_timestamp >= adapter.eta,
+            ErrorCode::TimelockActive
+        );
+        require!(
+            adapter.deposited_usdc.saturating_add(usdc_amount) <= adapter.cap_usdc,
+            ErrorCode::ExceedsStrategyCap
+        );
+        accounts.treasury.authorize_usdc_spend(
+            UsdcSpend::StrategyDeposit,
+            usdc_amount,
+            clock.unix_timestamp,
+        )?;
+
+        let seeds = &[b"treasury".as_ref(), &[accounts.treasury.bump]];
+        let usdc_before = accounts.treasury_usdc.amount;
+        strategy::deposit(
+            &accounts.adapter_accounts(ctx.remaining_accounts),
+            usdc_amount,
+            &[&seeds[..]],
+        )?;
+        accounts.treasury_usdc.reload()?;
+        let deposited = usdc_before.saturating_sub(accounts.treasury_usdc.amount);
+        require!(
+            deposited == usdc_amount,
+            ErrorCode::UnexpectedStrategyTransfer
+        );
+
+        let treasury = &mut accounts.treasury;
+        treasury.settle_usdc_spend(
+            UsdcSpend::StrategyDeposit,
+            usdc_amount,
+            deposited,
+            clock.unix_timestamp,
+        )?;
+        let adapter = &mut accounts.adapter;
+        adapter.deposited_usdc = adapter
+            .deposited_usdc
+            .checked_add(deposited)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(StrategyDeposited {
+            program: adapter.program,
+            usdc_amount: deposited,
+            deposited_usdc: adapter.deposited_usdc,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Pull `usdc_amount` back from a strategy adapter into the vault. Up to the
+    /// amount deposited goes back to the reserve budget; anything above it is
+    /// gain. Allowed while paused so funds can be recalled.
+    pub fn withdraw_from_strategy<'info>(
+        ctx: Context<'_, '_, 'info, 'info, StrategyAdapterFunds<'info>>,
+        usdc_amount: u64,
+    ) -> Result<()> {
+        require!(usdc_amount > 0, ErrorCode::InvalidAmount);
+        let accounts = &mut *ctx.accounts;
+        let seeds = &[b"treasury".as_ref(), &[accounts.treasury.bump]];
+        let usdc_before = accounts.treasury_usdc.amount;
+        strategy::withdraw(
+            &accounts.adapter_accounts(ctx.remaining_accounts),
+            usdc_amount,
+            &[&seeds[..]],
+        )?;
+        accounts.treasury_usdc.reload()?;
+        let received = accounts.treasury_usdc.amount.saturating_sub(usdc_before);
+        require!(
+            received == usdc_amount,
+            ErrorCode::UnexpectedStrategyTransfer
+        );
+
+        let adapter = &mut accounts.adapter;
+        let principal = received.min(adapter.deposited_usdc);
+        adapter.deposited_usdc -= principal;
+        let treasury = &mut accounts.treasury;
+        treasury.strategy_deposited_usdc =
+            treasury.strategy_deposited_usdc.saturating_sub(principal);
+
+        emit!(StrategyWithdrawn {
+            program: adapter.program,
+            usdc_amount: received,
+            principal_usdc: principal,
+            gain_usdc: received - principal,
+            deposited_usdc: adapter.deposited_usdc,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Record the value a strategy adapter reports for the treasury's position.
+    /// Permissionless, so anyone can refresh it for monitoring.
+    pub fn report_strategy_value<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReportStrategyValue<'info>>,
+    ) -> Result<()> {
+        let accounts = strategy::AdapterAccounts {
+            program: ctx.accounts.adapter_program.to_account_info(),
+            treasury: ctx.accounts.treasury.to_account_info(),
+            treasury_usdc: ctx.accounts.treasury_usdc.to_account_info(),
+            usdc_mint: ctx.accounts.usdc_mint.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            extra: ctx.remaining_accounts,
+        };
+        let value = strategy::report_value(&accounts)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let adapter = &mut ctx.accounts.adapter;
+        adapter.reported_value_usdc = value;
+        adapter.reported_at = now;
+
+        emit!(StrategyValueReported {
+            program: adapter.program,
+            value_usdc: value,
+            deposited_usdc: adapter.deposited_usdc,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Sweep Token-2022 transfer fees withheld on USDCBALL into the treasury token
+    /// vault and burn them. Token accounts holding withheld fees are passed as
+    /// writable remaining accounts; fees already harvested to the mint are
+    /// withdrawn as well. Permissionless.
+    pub fn harvest_withheld_fees<'info>(
+        ctx: Context<'_, '_, 'info, 'info, HarvestTransferFees<'info>>,
+    ) -> Result<()> {
+        let treasury = &ctx.accounts.treasury;
+        treasury.require_not_paused(PauseFlag::Fees)?;
+        treasury.require_not_paused(PauseFlag::Buybacks)?;
+        treasury.require_enabled(GatedInstruction::HarvestTransferFees)?;
+        treasury.require_enabled(GatedInstruction::ExecuteBurn)?;
+
+        let harvested = ctx.accounts.sweep_withheld(ctx.remaining_accounts)?;
+        if harvested > 0 {
+            let treasury = &ctx.accounts.treasury;
+            let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+            let signer = &[&seeds[..]];
+            let cpi_accounts = token_interface::Burn {
+                mint: ctx.accounts.token_mint.to_account_info(),
+                from: ctx.accounts.treasury_token.to_account_info(),
+                authority: treasury.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            token_interface::burn(cpi_ctx, harvested)?;
+        }
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.record_burn(harvested)?;
+        let now = Clock::get()?.unix_timestamp;
+        emit!(HarvestedFees {
+            amount: harvested,
+            total_harvested: treasury.total_transfer_fees_harvested,
+            total_burned: treasury.total_tokens_burned,
+            source_accounts: ctx.remaining_accounts.len() as u16,
+            timestamp: now,
+        });
+        if harvested > 0 {
+            emit!(TokensBurned {
+                amount: harvested,
+                total_burned: treasury.total_tokens_burned,
+                epoch: treasury.current_epoch,
+                epoch_burned: treasury.epoch_tokens_burned,
+                timestamp: now,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Withdraw from an LP position's pool through `venue`, checking the withdrawal
+/// burned exactly the LP tokens its data names and paid at least `min_amounts`
+/// (USDC, tokens). Returns the LP tokens burned and the USDC and tokens received.
+#[allow(clippy::too_many_arguments)]
+fn withdraw_lp<'info>(
+    treasury: &Account<'info, Treasury>,
+    venue: Venue,
+    venue_accounts: &[AccountInfo<'info>],
+    withdraw_data: Vec<u8>,
+    treasury_lp: &mut InterfaceAccount<'info, token_interface::TokenAccount>,
+    treasury_usdc: &mut InterfaceAccount<'info, token_interface::TokenAccount>,
+    treasury_token: &mut InterfaceAccount<'info, token_interface::TokenAccount>,
+    min_amounts: (u64, u64),
+) -> Result<(u64, u64, u64)> {
+    let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+    let lp_before = treasury_lp.amount;
+    let usdc_before = treasury_usdc.amount;
+    let token_before = treasury_token.amount;
+    let lp_amount = venues::invoke_withdraw(
+        venue,
+        venue_accounts,
+        withdraw_data,
+        treasury.key(),
+        &[&seeds[..]],
+    )?;
+    require!(lp_amount > 0, ErrorCode::InvalidAmount);
+
+    treasury_lp.reload()?;
+    treasury_usdc.reload()?;
+    treasury_token.reload()?;
+    require!(
+        lp_before.checked_sub(treasury_lp.amount) == Some(lp_amount),
+        ErrorCode::LpPositionMismatch
+    );
+    let usdc_amount = treasury_usdc.amount.saturating_sub(usdc_before);
+    let token_amount = treasury_token.amount.saturating_sub(token_before);
+    require!(
+        usdc_amount >= min_amounts.0 && token_amount >= min_amounts.1,
+        ErrorCode::SlippageExceeded
+    );
+    Ok((lp_amount, usdc_amount, token_amount))
+}
+
+/// Pay `usdc_amount` out of the treasury vault to `to`. Every direct outbound USDC
+/// transfer goes through here, so the budget check, the outflow and volume limits
+/// and the lifetime counter for `spend` cannot drift apart between instructions.
+fn spend_usdc<'info>(
+    treasury: &mut Account<'info, Treasury>,
+    spend: UsdcSpend,
+    treasury_usdc: &InterfaceAccount<'info, token_interface::TokenAccount>,
+    to: AccountInfo<'info>,
+    token_program: &Interface<'info, token_interface::TokenInterface>,
+    usdc_amount: u64,
+    now: i64,
+) -> Result<()> {
+    treasury.authorize_usdc_spend(spend, usdc_amount, now)?;
+
+    let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+    let signer = &[&seeds[..]];
+    let cpi_accounts = Transfer {
+        from: treasury_usdc.to_account_info(),
+        to,
+        authority: treasury.to_account_info(),
+    };
+    let cpi_ctx =
+        CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer);
+    transfer_tokens(cpi_ctx, usdc_amount)?;
+
+    treasury.settle_usdc_spend(spend, usdc_amount, usdc_amount, now)
+}
+
+/// Transfer USDC between accounts of the token program in `ctx`, SPL Token or
+/// Token-2022. USDC carries no extension that needs `transfer_checked`, so the
+/// plain transfer works under either program; USDCBALL may carry a transfer fee
+/// or hook and is always moved with `transfer_checked`.
+#[allow(deprecated)]
+fn transfer_tokens<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, Transfer<'info>>,
+    amount: u64,
+) -> Result<()> {
+    token_interface::transfer(ctx, amount)
+}
+
+/// Transfer hook program of a Token-2022 mint and the fee it withholds on a
+/// transfer of `amount` in `epoch`; the fee is 0 without a transfer fee extension,
+/// and a malformed extension is an error rather than a free transfer
+pub fn transfer_hook_accrual(mint_data: &[u8], epoch: u64, amount: u64) -> Result<(Pubkey, u64)> {
+    let mint = StateWithExtensions::<anchor_spl::token_2022::spl_token_2022::state::Mint>::unpack(
+        mint_data,
+    )
+    .map_err(|_| ErrorCode::InvalidTokenMint)?;
+    let hook_program =
+        transfer_hook::get_program_id(&mint).ok_or(ErrorCode::TransferHookNotConfigured)?;
+    let fee = if mint
+        .get_extension_types()?
+        .contains(&ExtensionType::TransferFeeConfig)
+    {
+        mint.get_extension::<TransferFeeConfig>()?
+            .calculate_epoch_fee(epoch, amount)
+            .ok_or(ErrorCode::Overflow)?
+    } else {
+        0
+    };
+    Ok((hook_program, fee))
+}
+
+/// Move everything in an operation receipt to `destination` and close it,
+/// returning the rent to `rent_to`. Receipts can hold USDCBALL under Token-2022,
+/// so the sweep names the mint, and fees withheld on the receipt when the venue
+/// delivered are harvested to the mint since they would block the close.
+fn sweep_receipt<'info>(
+    treasury: &Account<'info, Treasury>,
+    receipt: &InterfaceAccount<'info, token_interface::TokenAccount>,
+    mint: &InterfaceAccount<'info, token_interface::Mint>,
+    destination: &InterfaceAccount<'info, token_interface::TokenAccount>,
+    rent_to: AccountInfo<'info>,
+    token_program: &Interface<'info, token_interface::TokenInterface>,
+) -> Result<()> {
+    require_keys_eq!(receipt.mint, mint.key(), ErrorCode::ReceiptMismatch);
+    require_keys_eq!(destination.mint, receipt.mint, ErrorCode::ReceiptMismatch);
+    let seeds = &[b"treasury".as_ref(), &[treasury.bump]];
+    let signer = &[&seeds[..]];
+    let cpi_program = token_program.to_account_info();
+
+    if receipt.amount > 0 {
+        let cpi_accounts = token_interface::TransferChecked {
+            from: receipt.to_account_info(),
+            mint: mint.to_account_info(),
+            to: destination.to_account_info(),
+            authority: treasury.to_account_info(),
+        };
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, signer),
+            receipt.amount,
+            mint.decimals,
+        )?;
+    }
+
+    if withheld_transfer_fees(&receipt.to_account_info())? > 0 {
+        let cpi_accounts = HarvestWithheldTokensToMint {
+            token_program_id: cpi_program.clone(),
+            mint: mint.to_account_info(),
+        };
+        transfer_fee::harvest_withheld_tokens_to_mint(
+            CpiContext::new(cpi_program.clone(), cpi_accounts),
+            vec![receipt.to_account_info()],
+        )?;
+    }
+
+    let cpi_accounts = CloseAccount {
+        account: receipt.to_account_info(),
+        destination: rent_to,
+        authority: treasury.to_account_info(),
+    };
+    token_interface::close_account(CpiContext::new_with_signer(
+        cpi_program,
+        cpi_accounts,
+        signer,
+    ))
+}
+
+/// Transfer fees withheld on a token account; 0 outside Token-2022 or without
+/// the transfer fee extension
+fn withheld_transfer_fees(account: &AccountInfo) -> Result<u64> {
+    let data = account.try_borrow_data()?;
+    let Ok(state) =
+        StateWithExtensions::<anchor_spl::token_2022::spl_token_2022::state::Account>::unpack(
+            &data,
+        )
+    else {
+        return Ok(0);
+    };
+    if !state
+        .get_extension_types()?
+        .contains(&ExtensionType::TransferFeeAmount)
+    {
+        return Ok(0);
+    }
+    Ok(state
+        .get_extension::<TransferFeeAmount>()?
+        .withheld_amount
+        .into())
+}
+
+/// Transfer `amount` lamports from `depositor` into the fee vault and mark them
+/// recorded. Returns the lamports the vault actually received.
+fn transfer_to_fee_vault<'info>(
+    depositor: &Signer<'info>,
+    fee_vault: &mut Account<'info, FeeVault>,
+    system_program: &Program<'info, System>,
+    amount: u64,
+) -> Result<u64> {
+    let vault_info = fee_vault.to_account_info();
+    let lamports_before = vault_info.lamports();
+    anchor_lang::system_program::transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: depositor.to_account_info(),
+                to: vault_info.clone(),
+            },
+        ),
+        amount,
+    )?;
+    let received = vault_info
+        .lamports()
+        .checked_sub(lamports_before)
+        .ok_or(ErrorCode::Overflow)?;
+
+    fee_vault.recorded_lamports = fee_vault
+        .recorded_lamports
+        .checked_add(received)
+        .ok_or(ErrorCode::Overflow)?;
+    Ok(received)
+}
+
+/// Log one `action` moving `volume_usdc` against the signer's activity PDA, when the
+/// caller passed it
+fn record_key_activity(
+    activity: Option<&mut Account<KeyActivity>>,
+    action: KeyAction,
+    volume_usdc: u64,
+) -> Result<()> {
+    if let Some(activity) = activity {
+        activity.record(action, volume_usdc, Clock::get()?.unix_timestamp)?;
+    }
+    Ok(())
+}
+
+/// Publish a settled buyback on the quote board, when the caller passed it
+fn record_quote(
+    board: Option<&mut Account<QuoteBoard>>,
+    usdc_amount: u64,
+    token_amount: u64,
+    clock: &Clock,
+) {
+    if let Some(board) = board {
+        board.record(usdc_amount, token_amount, clock);
+    }
+}
+
+/// Whether depositing `usdc_amount` : `token_amount` into a pool holding
+/// `pool_usdc` : `pool_token` stays within `slippage_bps` of the pool price.
+/// An empty pool has no price yet, so any ratio seeds it.
+fn deposit_matches_pool_price(
+    usdc_amount: u64,
+    token_amount: u64,
+    pool_usdc: u64,
+    pool_token: u64,
+    slippage_bps: u16,
+) -> bool {
+    if pool_usdc == 0 && pool_token == 0 {
+        return true;
+    }
+    let deposit = usdc_amount as u128 * pool_token as u128;
+    let at_pool_price = token_amount as u128 * pool_usdc as u128;
+    deposit.abs_diff(at_pool_price).saturating_mul(10000)
+        <= at_pool_price.saturating_mul(slippage_bps as u128)
+}
+
+/// Check the pool's spot price, from its `(usdc, token)` vault balances, against its
+/// TWAP and record it as a new observation.
+/// Without a TWAP account this only passes while the deviation check is off.
+fn observe_pool_price(
+    twap: Option<&mut Twap>,
+    pool_reserves: Option<(u64, u64)>,
+    token_decimals: u8,
+    max_deviation_bps: u16,
+    now: i64,
+) -> Result<()> {
+    let Some(twap) = twap else {
+        require!(max_deviation_bps == 0, ErrorCode::TwapRequired);
+        return Ok(());
+    };
+    let (pool_usdc, pool_token) = pool_reserves.ok_or(ErrorCode::InvalidTwapPool)?;
+    let Some(spot) = Twap::spot_price(pool_usdc, pool_token, token_decimals) else {
+        // An empty pool has no price to observe
+        return Ok(());
+    };
+
+    if max_deviation_bps > 0 {
+        if let Some(average) = twap.average(now) {
+            let deviation = (spot.abs_diff(average) as u128 * 10_000 / average as u128) as u64;
+            require!(
+                deviation <= max_deviation_bps as u64,
+                ErrorCode::TwapDeviationExceeded
+            );
+        }
+    }
+    twap.observe(now, spot);
+    Ok(())
+}
+
+/// Once a token price feed is configured, hold `min_tokens_out` for a buyback of
+/// `usdc_amount` to the oracle floor and return the oracle price. A stale or
+/// missing Pyth price falls back to the Switchboard feed, if one is set.
+fn check_oracle_floor(
+    treasury: &Treasury,
+    pyth: Option<&AccountInfo>,
+    switchboard: Option<&AccountInfo>,
+    usdc_amount: u64,
+    min_tokens_out: u64,
+    token_decimals: u8,
+    now: i64,
+) -> Result<Option<u64>> {
+    if treasury.token_usd_oracle == Pubkey::default()
+        && treasury.token_switchboard_feed == Pubkey::default()
+    {
+        return Ok(None);
+    }
+    let price = oracle::read_price_with_fallback(
+        pyth.filter(|a| a.key() == treasury.token_usd_oracle),
+        switchboard.filter(|a| a.key() == treasury.token_switchboard_feed),
+        now,
+        treasury.oracle_max_age_seconds,
+        treasury.switchboard_max_age_seconds,
+    )?;
+    let fair_tokens_out = price.amount_for_usd(usdc_amount, token_decimals)?;
+    treasury.require_oracle_floor(fair_tokens_out, min_tokens_out)?;
+    Ok(Some(price.micro_usd_per_unit()?))
+}
+
+/// Check a buyback pool's spot price against its TWAP, whose pool vaults must be
+/// passed with it, and return the average from before this observation
+fn check_twap(
+    twap: Option<&mut Twap>,
+    pool_usdc: Option<&InterfaceAccount<token_interface::TokenAccount>>,
+    pool_token: Option<&InterfaceAccount<token_interface::TokenAccount>>,
+    token_decimals: u8,
+    max_deviation_bps: u16,
+    now: i64,
+) -> Result<Option<u64>> {
+    if let Some(twap) = &twap {
+        require!(
+            pool_usdc.map(|a| a.key()) == Some(twap.usdc_vault)
+                && pool_token.map(|a| a.key()) == Some(twap.token_vault),
+            ErrorCode::InvalidTwapPool
+        );
+    }
+    let pool_reserves = pool_usdc
+        .zip(pool_token)
+        .map(|(usdc, token)| (usdc.amount, token.amount));
+    let average = twap.as_ref().and_then(|t| t.average(now));
+    observe_pool_price(twap, pool_reserves, token_decimals, max_deviation_bps, now)?;
+    Ok(average)
+}
+
+// ============================================================================
+// Accounts
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Treasury::INIT_SPACE,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    pub token_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecordFee<'info> {
+    /// CHECK: read in place by `record_fee`, which checks the discriminator. The
+    /// treasury, fee vault and fee counter are the program's only accounts of their
+    /// types, so owner checks replace the costlier PDA derivations.
+    #[account(owner = crate::ID @ ErrorCode::InvalidTreasuryAccount)]
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub fee_counter: AccountLoader<'info, FeeCounter>,
+
+    #[account(mut)]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    /// CHECK: must be `treasury.sol_usd_oracle`; required once an oracle is configured
+    pub sol_usd_oracle: Option<UncheckedAccount<'info>>,
+}
+
+/// Leading accounts mirror the transfer hook `Execute` instruction so a hook can
+/// pass its own accounts straight through. The hook invokes this with them
+/// read-only.
+#[derive(Accounts)]
+pub struct RecordTransferFee<'info> {
+    /// CHECK: source token account of the transfer, only logged
+    pub source_token: UncheckedAccount<'info>,
+
+    /// CHECK: must be `treasury.token_mint`; parsed by `transfer_hook_accrual`
+    #[account(owner = Token2022::id() @ ErrorCode::InvalidTokenMint)]
+    pub mint: UncheckedAccount<'info>,
+
+    /// CHECK: destination token account of the transfer, only logged
+    pub destination_token: UncheckedAccount<'info>,
+
+    /// CHECK: owner or delegate of the source, as passed to the hook
+    pub owner: UncheckedAccount<'info>,
+
+    /// CHECK: read in place like in `record_fee`, which checks the discriminator
+    #[account(owner = crate::ID @ ErrorCode::InvalidTreasuryAccount)]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// The program's only `TransferFeeCounter`, so the owner check replaces the PDA
+    /// derivation
+    #[account(mut)]
+    pub transfer_fee_counter: AccountLoader<'info, TransferFeeCounter>,
+
+    /// The mint's transfer hook program's `FEE_SOURCE_SEED` PDA, which only it can
+    /// sign for
+    pub hook_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeTransferFeeCounter<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<TransferFeeCounter>(),
+        seeds = [b"transfer_fee_counter"],
+        bump
+    )]
+    pub transfer_fee_counter: AccountLoader<'info, TransferFeeCounter>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeFeeCounter<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<FeeCounter>(),
+        seeds = [b"fee_counter"],
+        bump
+    )]
+    pub fee_counter: AccountLoader<'info, FeeCounter>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SyncFeeCounter<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_counter"],
+        bump = fee_counter.load()?.bump,
+    )]
+    pub fee_counter: AccountLoader<'info, FeeCounter>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeFeeVault<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + FeeVault::INIT_SPACE,
+        seeds = [b"fee_vault"],
+        bump
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ForwardFee<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_vault"],
+        bump = fee_vault.bump,
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_counter"],
+        bump = fee_counter.load()?.bump,
+    )]
+    pub fee_counter: AccountLoader<'info, FeeCounter>,
+
+    /// CHECK: must be `treasury.sol_usd_oracle`; required once an oracle is configured
+    pub sol_usd_oracle: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: the program forwarding the fee, recorded as its source
+    #[account(executable)]
+    pub source_program: UncheckedAccount<'info>,
+
+    /// The source program's `FEE_SOURCE_SEED` PDA, which only it can sign for
+    pub source_authority: Signer<'info>,
+
+    /// Pays the lamports and owns `source_usdc`
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Required when forwarding USDC
+    #[account(
+        mut,
+        token::token_program = token_program,
+    )]
+    pub source_usdc: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = treasury_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = treasury_usdc.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_usdc: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    pub token_program: Option<Interface<'info, token_interface::TokenInterface>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DepositFee<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_vault"],
+        bump = fee_vault.bump,
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_counter"],
+        bump = fee_counter.load()?.bump,
+    )]
+    pub fee_counter: AccountLoader<'info, FeeCounter>,
+
+    /// CHECK: must be `treasury.sol_usd_oracle`; required once an oracle is configured
+    pub sol_usd_oracle: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteBuyback<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = treasury_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = treasury_usdc.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        address = treasury.token_mint @ ErrorCode::InvalidTokenMint,
+    )]
+    pub token_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    /// Receives the bought tokens; its balance change is checked against `min_tokens_out`
+    #[account(
+        mut,
+        constraint = treasury_token.mint == treasury.token_mint @ ErrorCode::InvalidTokenMint,
+        constraint = treasury_token.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_token: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// CHECK: must be `treasury.token_usd_oracle`; required once a token price feed is set
+    pub token_usd_oracle: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: must be `treasury.token_switchboard_feed`; used when the Pyth price is unusable
+    pub token_switchboard_feed: Option<UncheckedAccount<'info>>,
+
+    /// TWAP of the pool the buyback trades against, with that pool's vaults.
+    /// Required once `treasury.twap_max_deviation_bps` is set.
+    #[account(
+        mut,
+        seeds = [b"twap", twap.usdc_vault.as_ref(), twap.token_vault.as_ref()],
+        bump = twap.bump,
+    )]
+    pub twap: Option<Box<Account<'info, Twap>>>,
+    #[account(token::token_program = token_program)]
+    pub twap_pool_usdc: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    pub twap_pool_token: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+
+    #[account(
+        constraint = authority.key() == treasury.operator
+    )]
+    pub authority: Signer<'info>,
+
+    /// `Operator` account of `authority`; its scope must cover the operation
+    #[account(
+        seeds = [b"operator", authority.key().as_ref()],
+        bump = operator_stats.bump,
+        constraint = operator_stats.active @ ErrorCode::UnauthorizedOperator,
+    )]
+    pub operator_stats: Account<'info, Operator>,
+
+    #[account(
+        mut,
+        seeds = [b"key_activity", authority.key().as_ref()],
+        bump = authority_activity.bump,
+    )]
+    pub authority_activity: Option<Account<'info, KeyActivity>>,
+
+    /// Required when `usdc_amount` exceeds `treasury.cosign_threshold_usdc`
+    pub co_signer: Option<Signer<'info>>,
+
+    /// `Operator` account of `co_signer` when a second registered operator co-signs
+    pub co_signer_operator: Option<Account<'info, Operator>>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+
+    /// Quote board to publish the fill on, when it has been opened
+    #[account(
+        mut,
+        seeds = [b"quote_board"],
+        bump = quote_board.bump,
+    )]
+    pub quote_board: Option<Account<'info, QuoteBoard>>,
+}
+
+#[derive(Accounts)]
+pub struct AddLiquidity<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+        has_one = token_mint,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = treasury_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = treasury_usdc.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = pool_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+    )]
+    pub pool_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        seeds = [b"pool_registry"],
+        bump = pool_registry.bump,
+        constraint = pool_registry.find(&pool_usdc.key(), &pool_token.key()).is_some()
+            @ ErrorCode::UnregisteredPool,
+    )]
+    pub pool_registry: Account<'info, PoolRegistry>,
+
+    #[account(
+        mint::token_program = token_2022_program,
+    )]
+    pub token_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::token_program = token_2022_program,
+        constraint = treasury_token.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_token: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::token_program = token_2022_program,
+    )]
+    pub pool_token: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// CHECK: must be `treasury.usdc_usd_oracle`; required once the depeg guard is set
+    pub usdc_usd_oracle: Option<UncheckedAccount<'info>>,
+
+    /// Required once `treasury.twap_max_deviation_bps` is set
+    #[account(
+        mut,
+        seeds = [b"twap", pool_usdc.key().as_ref(), pool_token.key().as_ref()],
+        bump = twap.bump,
+    )]
+    pub twap: Option<Box<Account<'info, Twap>>>,
+
+    #[account(
+        constraint = authority.key() == treasury.operator
+    )]
+    pub authority: Signer<'info>,
+
+    /// `Operator` account of `authority`; its scope must cover the operation
+    #[account(
+        seeds = [b"operator", authority.key().as_ref()],
+        bump = operator_stats.bump,
+        constraint = operator_stats.active @ ErrorCode::UnauthorizedOperator,
+    )]
+    pub operator_stats: Account<'info, Operator>,
+
+    #[account(
+        mut,
+        seeds = [b"key_activity", authority.key().as_ref()],
+        bump = authority_activity.bump,
+    )]
+    pub authority_activity: Option<Account<'info, KeyActivity>>,
+
+    /// Required when `usdc_amount` exceeds `treasury.cosign_threshold_usdc`
+    pub co_signer: Option<Signer<'info>>,
+
+    /// `Operator` account of `co_signer` when a second registered operator co-signs
+    pub co_signer_operator: Option<Account<'info, Operator>>,
+
+    /// The pool's LP position, when one is open; records the deposit
+    #[account(
+        mut,
+        seeds = [b"lp_position", pool_usdc.key().as_ref(), pool_token.key().as_ref()],
+        bump = lp_position.bump,
+    )]
+    pub lp_position: Option<Account<'info, LpPosition>>,
+
+    /// Treasury's LP token account for the pool, which the deposit mints into
+    #[account(
+        constraint = treasury_lp.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+        constraint = treasury_lp.key() != treasury_usdc.key()
+            && treasury_lp.key() != treasury_token.key() @ ErrorCode::LpPositionMismatch,
+    )]
+    pub treasury_lp: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct OpenClmmPosition<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+        has_one = token_mint,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    /// CHECK: validated by Raydium against the pool vaults
+    #[account(mut)]
+    pub pool_state: UncheckedAccount<'info>,
+
+    /// Fresh keypair; Raydium creates the position NFT mint
+    #[account(mut)]
+    pub position_nft_mint: Signer<'info>,
+
+    /// CHECK: the treasury's associated token account for the position NFT,
+    /// created by Raydium
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &treasury.key(),
+            &position_nft_mint.key(),
+            &token_2022_program.key(),
+        ),
+    )]
+    pub position_nft_account: UncheckedAccount<'info>,
+
+    /// CHECK: validated by Raydium against `pool_state`
+    #[account(mut)]
+    pub protocol_position: UncheckedAccount<'info>,
+
+    /// CHECK: validated by Raydium against `position_nft_mint`
+    #[account(mut)]
+    pub personal_position: UncheckedAccount<'info>,
+
+    /// CHECK: validated by Raydium against `pool_state`
+    #[account(mut)]
+    pub tick_array_lower: UncheckedAccount<'info>,
+
+    /// CHECK: validated by Raydium against `pool_state`
+    #[account(mut)]
+    pub tick_array_upper: UncheckedAccount<'info>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = treasury_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = treasury_usdc.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::token_program = token_2022_program,
+        constraint = treasury_token.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_token: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// Pool's USDC vault
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = pool_usdc_vault.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+    )]
+    pub pool_usdc_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// Pool's token vault
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::token_program = token_2022_program,
+    )]
+    pub pool_token_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        seeds = [b"pool_registry"],
+        bump = pool_registry.bump,
+        constraint = pool_registry.find(&pool_usdc_vault.key(), &pool_token_vault.key()).is_some()
+            @ ErrorCode::UnregisteredPool,
+    )]
+    pub pool_registry: Account<'info, PoolRegistry>,
+
+    #[account(
+        address = treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        mint::token_program = token_program,
+    )]
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        mint::token_program = token_2022_program,
+    )]
+    pub token_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    /// CHECK: must be `treasury.usdc_usd_oracle`; required once the depeg guard is set
+    pub usdc_usd_oracle: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        constraint = authority.key() == treasury.operator
+    )]
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// `Operator` account of `authority`; its scope must cover the operation
+    #[account(
+        seeds = [b"operator", authority.key().as_ref()],
+        bump = operator_stats.bump,
+        constraint = operator_stats.active @ ErrorCode::UnauthorizedOperator,
+    )]
+    pub operator_stats: Account<'info, Operator>,
+
+    #[account(
+        mut,
+        seeds = [b"key_activity", authority.key().as_ref()],
+        bump = authority_activity.bump,
+    )]
+    pub authority_activity: Option<Account<'info, KeyActivity>>,
+
+    /// CHECK: address checked
+    #[account(address = raydium_clmm::ID)]
+    pub raydium_clmm_program: UncheckedAccount<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+impl<'info> OpenClmmPosition<'info> {
+    fn position_accounts(&self) -> raydium_clmm::PositionAccounts<'info> {
+        let (
+            (token_account_0, token_vault_0, vault_0_mint),
+            (token_account_1, token_vault_1, vault_1_mint),
+        ) = raydium_clmm::order_pair(
+            &self.treasury.usdc_mint,
+            &self.treasury.token_mint,
+            (
+                self.treasury_usdc.to_account_info(),
+                self.pool_usdc_vault.to_account_info(),
+                self.usdc_mint.to_account_info(),
+            ),
+            (
+                self.treasury_token.to_account_info(),
+                self.pool_token_vault.to_account_info(),
+                self.token_mint.to_account_info(),
+            ),
+        );
+        raydium_clmm::PositionAccounts {
+            nft_owner: self.treasury.to_account_info(),
+            nft_account: self.position_nft_account.to_account_info(),
+            pool_state: self.pool_state.to_account_info(),
+            protocol_position: self.protocol_position.to_account_info(),
+            personal_position: self.personal_position.to_account_info(),
+            tick_array_lower: self.tick_array_lower.to_account_info(),
+            tick_array_upper: self.tick_array_upper.to_account_info(),
+            token_account_0,
+            token_account_1,
+            token_vault_0,
+            token_vault_1,
+            vault_0_mint,
+            vault_1_mint,
+            token_program: self.token_program.to_account_info(),
+            token_program_2022: self.token_2022_program.to_account_info(),
+            program: self.raydium_clmm_program.to_account_info(),
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct ClmmLiquidity<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+        has_one = token_mint,
+    )]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    /// CHECK: must be the pool the treasury's position is in
+    #[account(
+        mut,
+        constraint = pool_state.key() == treasury.clmm_pool @ ErrorCode::ClmmPositionNotOpen,
+    )]
+    pub pool_state: UncheckedAccount<'info>,
+
+    /// Treasury-held position NFT
+    #[account(
+        token::token_program = token_2022_program,
+        constraint = position_nft_account.mint == treasury.clmm_position_mint
+            && treasury.clmm_position_mint != Pubkey::default()
+            && position_nft_account.amount == 1 @ ErrorCode::ClmmPositionNotOpen,
+        constraint = position_nft_account.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub position_nft_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// CHECK: validated by Raydium against `pool_state`
+    #[account(mut)]
+    pub protocol_position: UncheckedAccount<'info>,
+
+    /// CHECK: validated by Raydium against the position NFT
+    #[account(mut)]
+    pub personal_position: UncheckedAccount<'info>,
+
+    /// CHECK: validated by Raydium against `pool_state`
+    #[account(mut)]
+    pub tick_array_lower: UncheckedAccount<'info>,
+
+    /// CHECK: validated by Raydium against `pool_state`
+    #[account(mut)]
+    pub tick_array_upper: UncheckedAccount<'info>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = treasury_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = treasury_usdc.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::token_program = token_2022_program,
+        constraint = treasury_token.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_token: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// Pool's USDC vault
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = pool_usdc_vault.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+    )]
+    pub pool_usdc_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// Pool's token vault
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::token_program = token_2022_program,
+    )]
+    pub pool_token_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        seeds = [b"pool_registry"],
+        bump = pool_registry.bump,
+        constraint = pool_registry.find(&pool_usdc_vault.key(), &pool_token_vault.key()).is_some()
+            @ ErrorCode::UnregisteredPool,
+    )]
+    pub pool_registry: Account<'info, PoolRegistry>,
+
+    #[account(
+        address = treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        mint::token_program = token_program,
+    )]
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        mint::token_program = token_2022_program,
+    )]
+    pub token_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    /// CHECK: must be `treasury.usdc_usd_oracle`; required once the depeg guard is set
+    pub usdc_usd_oracle: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        constraint = authority.key() == treasury.operator
+    )]
+    pub authority: Signer<'info>,
+
+    /// `Operator` account of `authority`; its scope must cover the operation
+    #[account(
+        seeds = [b"operator", authority.key().as_ref()],
+        bump = operator_stats.bump,
+        constraint = operator_stats.active @ ErrorCode::UnauthorizedOperator,
+    )]
+    pub operator_stats: Account<'info, Operator>,
+
+    #[account(
+        mut,
+        seeds = [b"key_activity", authority.key().as_ref()],
+        bump = authority_activity.bump,
+    )]
+    pub authority_activity: Option<Account<'info, KeyActivity>>,
+
+    /// Required when `usdc_amount_max` exceeds `treasury.cosign_threshold_usdc`
+    pub co_signer: Option<Signer<'info>>,
+
+    /// `Operator` account of `co_signer` when a second registered operator co-signs
+    pub co_signer_operator: Option<Account<'info, Operator>>,
+
+    /// CHECK: address checked
+    #[account(address = raydium_clmm::ID)]
+    pub raydium_clmm_program: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+impl<'info> ClmmLiquidity<'info> {
+    fn position_accounts(&self) -> raydium_clmm::PositionAccounts<'info> {
+        let (
+            (token_account_0, token_vault_0, vault_0_mint),
+            (token_account_1, token_vault_1, vault_1_mint),
+        ) = raydium_clmm::order_pair(
+            &self.treasury.usdc_mint,
+            &self.treasury.token_mint,
+            (
+                self.treasury_usdc.to_account_info(),
+                self.pool_usdc_vault.to_account_info(),
+                self.usdc_mint.to_account_info(),
+            ),
+            (
+                self.treasury_token.to_account_info(),
+                self.pool_token_vault.to_account_info(),
+                self.token_mint.to_account_info(),
+            ),
+        );
+        raydium_clmm::PositionAccounts {
+            nft_owner: self.treasury.to_account_info(),
+            nft_account: self.position_nft_account.to_account_info(),
+            pool_state: self.pool_state.to_account_info(),
+            protocol_position: self.protocol_position.to_account_info(),
+            personal_position: self.personal_position.to_account_info(),
+            tick_array_lower: self.tick_array_lower.to_account_info(),
+            tick_array_upper: self.tick_array_upper.to_account_info(),
+            token_account_0,
+            token_account_1,
+            token_vault_0,
+            token_vault_1,
+            vault_0_mint,
+            vault_1_mint,
+            token_program: self.token_program.to_account_info(),
+            token_program_2022: self.token_2022_program.to_account_info(),
+            program: self.raydium_clmm_program.to_account_info(),
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct RecordConversion<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    /// CHECK: must be `treasury.usdc_usd_oracle`; required once the depeg guard is set
+    pub usdc_usd_oracle: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        constraint = authority.key() == treasury.operator
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"key_activity", authority.key().as_ref()],
+        bump = authority_activity.bump,
+    )]
+    pub authority_activity: Option<Account<'info, KeyActivity>>,
+}
+
+#[derive(Accounts)]
+pub struct ConvertSolToUsdc<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_vault"],
+        bump = fee_vault.bump,
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = treasury_wsol.mint == spl_token::native_mint::ID @ ErrorCode::NotWrappedSol,
+        constraint = treasury_wsol.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_wsol: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = treasury_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = treasury_usdc.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// CHECK: must be `treasury.usdc_usd_oracle`; required once the depeg guard is set
+    pub usdc_usd_oracle: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        constraint = authority.key() == treasury.operator
+    )]
+    pub authority: Signer<'info>,
+
+    /// `Operator` account of `authority`; its scope must cover the operation
+    #[account(
+        seeds = [b"operator", authority.key().as_ref()],
+        bump = operator_stats.bump,
+        constraint = operator_stats.active @ ErrorCode::UnauthorizedOperator,
+    )]
+    pub operator_stats: Account<'info, Operator>,
+
+    #[account(
+        mut,
+        seeds = [b"key_activity", authority.key().as_ref()],
+        bump = authority_activity.bump,
+    )]
+    pub authority_activity: Option<Account<'info, KeyActivity>>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct EmergencyAction<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"key_activity", authority.key().as_ref()],
+        bump = authority_activity.bump,
+    )]
+    pub authority_activity: Option<Account<'info, KeyActivity>>,
+}
+
+#[derive(Accounts)]
+pub struct PauseTreasury<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        constraint = pauser.key() == treasury.pauser
+            || pauser.key() == treasury.authority
+            || (treasury.guardian != Pubkey::default() && pauser.key() == treasury.guardian)
+    )]
+    pub pauser: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"key_activity", pauser.key().as_ref()],
+        bump = pauser_activity.bump,
+    )]
+    pub pauser_activity: Option<Account<'info, KeyActivity>>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"key_activity", authority.key().as_ref()],
+        bump = authority_activity.bump,
+    )]
+    pub authority_activity: Option<Account<'info, KeyActivity>>,
+}
+
+impl<'info> UpdateConfig<'info> {
+    /// Log a configuration change against the admin's activity PDA
+    fn record_activity(&mut self) -> Result<()> {
+        record_key_activity(self.authority_activity.as_mut(), KeyAction::Config, 0)
+    }
+}
+
+#[derive(Accounts)]
+pub struct HarvestTransferFees<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+        has_one = token_mint,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        mint::token_program = token_program,
+    )]
+    pub token_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = treasury,
+        token::token_program = token_program,
+    )]
+    pub treasury_token: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    pub token_program: Program<'info, Token2022>,
+}
+
+impl<'info> HarvestTransferFees<'info> {
+    /// Move fees withheld in `sources` onto the mint and withdraw everything
+    /// withheld there into the treasury token vault. Returns the amount received.
+    fn sweep_withheld(&mut self, sources: &[AccountInfo<'info>]) -> Result<u64> {
+        let token_program = self.token_program.to_account_info();
+        let mint = self.token_mint.to_account_info();
+
+        // Move withheld amounts from the listed token accounts onto the mint
+        if !sources.is_empty() {
+            let cpi_accounts = HarvestWithheldTokensToMint {
+                token_program_id: token_program.clone(),
+                mint: mint.clone(),
+            };
+            transfer_fee::harvest_withheld_tokens_to_mint(
+                CpiContext::new(token_program.clone(), cpi_accounts),
+                sources.to_vec(),
+            )?;
+        }
+
+        // Withdraw everything withheld on the mint, signed as withdraw authority
+        let balance_before = self.treasury_token.amount;
+        let seeds = &[b"treasury".as_ref(), &[self.treasury.bump]];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = WithdrawWithheldTokensFromMint {
+            token_program_id: token_program.clone(),
+            mint,
+            destination: self.treasury_token.to_account_info(),
+            authority: self.treasury.to_account_info(),
+        };
+        transfer_fee::withdraw_withheld_tokens_from_mint(CpiContext::new_with_signer(
+            token_program,
+            cpi_accounts,
+            signer,
+        ))?;
+
+        self.treasury_token.reload()?;
+        let harvested = self
+            .treasury_token
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(ErrorCode::Overflow)?;
+
+        self.treasury.record_transfer_fees(harvested)?;
+        Ok(harvested)
+    }
+}
+
+#[derive(Accounts)]
+pub struct CreateDividendDistribution<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + DividendDistribution::INIT_SPACE,
+        seeds = [b"dividend", treasury.dividend_epoch.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub distribution: Account<'info, DividendDistribution>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = treasury_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = treasury_usdc.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"dividend_vault", distribution.key().as_ref()],
+        bump,
+        token::mint = usdc_mint,
+        token::authority = distribution,
+    )]
+    pub dividend_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        mint::token_program = token_program,
+        constraint = usdc_mint.key() == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+    )]
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimDividend<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"dividend", distribution.epoch.to_le_bytes().as_ref()],
+        bump = distribution.bump,
+    )]
+    pub distribution: Account<'info, DividendDistribution>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        seeds = [b"dividend_vault", distribution.key().as_ref()],
+        bump,
+    )]
+    pub dividend_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        init,
+        payer = claimant,
+        space = 8 + DividendClaim::INIT_SPACE,
+        seeds = [b"dividend_claim", distribution.key().as_ref(), claimant.key().as_ref()],
+        bump
+    )]
+    pub claim: Account<'info, DividendClaim>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        token::mint = dividend_vault.mint,
+        token::authority = claimant,
+    )]
+    pub claimant_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeBountyEscrow<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"bounty_vault"],
+        bump,
+        token::mint = usdc_mint,
+        token::authority = treasury,
+    )]
+    pub bounty_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        mint::token_program = token_program,
+        constraint = usdc_mint.key() == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+    )]
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundBountyEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = treasury_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = treasury_usdc.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        seeds = [b"bounty_vault"],
+        bump,
+    )]
+    pub bounty_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeBountyPayout<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + BountyPayout::INIT_SPACE,
+        seeds = [b"bounty_payout", treasury.bounty_payout_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub payout: Account<'info, BountyPayout>,
+
+    #[account(
+        seeds = [b"bounty_vault"],
+        bump,
+    )]
+    pub bounty_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        token::mint = bounty_vault.mint,
+    )]
+    pub recipient_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteBountyPayout<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"bounty_payout", payout.index.to_le_bytes().as_ref()],
+        bump = payout.bump,
+    )]
+    pub payout: Account<'info, BountyPayout>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        seeds = [b"bounty_vault"],
+        bump,
+    )]
+    pub bounty_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        address = payout.recipient @ ErrorCode::BountyRecipientMismatch,
+    )]
+    pub recipient_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CancelBountyPayout<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"bounty_payout", payout.index.to_le_bytes().as_ref()],
+        bump = payout.bump,
+    )]
+    pub payout: Account<'info, BountyPayout>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PingWatchdog<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct OpenOperation<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init,
+        payer = operator,
+        space = 8 + PendingOperation::INIT_SPACE,
+        seeds = [b"operation", treasury.operation_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub operation: Account<'info, PendingOperation>,
+
+    #[account(
+        init,
+        payer = operator,
+        seeds = [b"operation_escrow", operation.key().as_ref()],
+        bump,
+        token::mint = usdc_mint,
+        token::authority = treasury,
+    )]
+    pub escrow: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = treasury_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = treasury_usdc.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        mint::token_program = token_program,
+        constraint = usdc_mint.key() == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+    )]
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"operator", operator.key().as_ref()],
+        bump = operator_stats.bump,
+        constraint = operator_stats.active @ ErrorCode::UnauthorizedOperator,
+    )]
+    pub operator_stats: Account<'info, Operator>,
+
+    #[account(mut)]
+    pub operator: Signer<'info>,
+
+    /// Required when `usdc_amount` exceeds `treasury.cosign_threshold_usdc`
+    pub co_signer: Option<Signer<'info>>,
+
+    /// `Operator` account of `co_signer` when a second registered operator co-signs
+    pub co_signer_operator: Option<Account<'info, Operator>>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SettleOperation<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"operation", operation.index.to_le_bytes().as_ref()],
+        bump = operation.bump,
+        constraint = operation.initiator == operator.key() @ ErrorCode::NotOperationInitiator,
+    )]
+    pub operation: Account<'info, PendingOperation>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        seeds = [b"operation_escrow", operation.key().as_ref()],
+        bump,
+    )]
+    pub escrow: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        seeds = [b"pool_registry"],
+        bump = pool_registry.bump,
+    )]
+    pub pool_registry: Account<'info, PoolRegistry>,
+
+    /// USDC vault of a registered pool on `operation.venue`
+    #[account(
+        token::token_program = token_program,
+        mut,
+        token::mint = escrow.mint,
+        constraint = pool_registry.find_usdc_vault(operation.venue, &destination_usdc.key()).is_some()
+            @ ErrorCode::UnregisteredPool,
+    )]
+    pub destination_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"operator", operator.key().as_ref()],
+        bump = operator_stats.bump,
+        constraint = operator_stats.active @ ErrorCode::UnauthorizedOperator,
+    )]
+    pub operator_stats: Account<'info, Operator>,
+
+    #[account(mut)]
+    pub operator: Signer<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct RefundExpired<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        close = initiator,
+        seeds = [b"operation", operation.index.to_le_bytes().as_ref()],
+        bump = operation.bump,
+        has_one = initiator,
+    )]
+    pub operation: Account<'info, PendingOperation>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        seeds = [b"operation_escrow", operation.key().as_ref()],
+        bump,
+    )]
+    pub escrow: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        address = operation.funding_vault @ ErrorCode::WrongFundingVault,
+    )]
+    pub treasury_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"operator", operation.initiator.as_ref()],
+        bump = operator_stats.bump,
+    )]
+    pub operator_stats: Account<'info, Operator>,
+
+    /// CHECK: receives the rent of the closed accounts; must match `operation.initiator`
+    #[account(mut)]
+    pub initiator: UncheckedAccount<'info>,
+
+    pub caller: Signer<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+
+    /// Required when the operation committed a receipt
+    #[account(
+        mut,
+        seeds = [b"operation_receipt", operation.key().as_ref()],
+        bump,
+    )]
+    pub receipt: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    /// Mint of the receipt, required with it
+    #[account(mut)]
+    pub receipt_mint: Option<InterfaceAccount<'info, token_interface::Mint>>,
+
+    /// Treasury account of the receipt's mint
+    #[account(
+        mut,
+        constraint = receipt_destination.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub receipt_destination: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    pub receipt_token_program: Option<Interface<'info, token_interface::TokenInterface>>,
+}
+
+#[derive(Accounts)]
+pub struct CommitOperationReceipt<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"operation", operation.index.to_le_bytes().as_ref()],
+        bump = operation.bump,
+        constraint = operation.initiator == operator.key() @ ErrorCode::NotOperationInitiator,
+    )]
+    pub operation: Account<'info, PendingOperation>,
+
+    #[account(
+        init,
+        payer = operator,
+        seeds = [b"operation_receipt", operation.key().as_ref()],
+        bump,
+        token::mint = output_mint,
+        token::authority = treasury,
+        token::token_program = token_program,
+    )]
+    pub receipt: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(mint::token_program = token_program)]
+    pub output_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(mut)]
+    pub operator: Signer<'info>,
+
+    /// Token program of `output_mint`
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SettleReceipt<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        close = initiator,
+        seeds = [b"operation", operation.index.to_le_bytes().as_ref()],
+        bump = operation.bump,
+        has_one = initiator,
+    )]
+    pub operation: Account<'info, PendingOperation>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        seeds = [b"operation_receipt", operation.key().as_ref()],
+        bump,
+        constraint = receipt.key() == operation.receipt @ ErrorCode::ReceiptMismatch,
+    )]
+    pub receipt: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        mut,
+        address = receipt.mint @ ErrorCode::ReceiptMismatch,
+        mint::token_program = token_program,
+    )]
+    pub receipt_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        token::mint = receipt.mint,
+        constraint = destination.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub destination: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// CHECK: receives the rent of the closed accounts; must match `operation.initiator`
+    #[account(mut)]
+    pub initiator: UncheckedAccount<'info>,
+
+    pub caller: Signer<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(operator: Pubkey)]
+pub struct RegisterOperator<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Operator::INIT_SPACE,
+        seeds = [b"operator", operator.as_ref()],
+        bump
+    )]
+    pub operator_stats: Account<'info, Operator>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetOperatorActive<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"operator", operator_stats.operator.as_ref()],
+        bump = operator_stats.bump,
+    )]
+    pub operator_stats: Account<'info, Operator>,
+
+    #[account(
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReportSkip<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"operator", operator.key().as_ref()],
+        bump = operator_stats.bump,
+        constraint = operator_stats.active @ ErrorCode::UnauthorizedOperator,
+    )]
+    pub operator_stats: Account<'info, Operator>,
+
+    pub operator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ViewOperator<'info> {
+    #[account(
+        seeds = [b"operator", operator_stats.operator.as_ref()],
+        bump = operator_stats.bump,
+    )]
+    pub operator_stats: Account<'info, Operator>,
+}
+
+#[derive(Accounts)]
+#[instruction(venue: Venue)]
+pub struct InitializeRouteEscrow<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    /// Intermediate token of the route, e.g. wrapped SOL
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"route_escrow".as_ref(), &[venue as u8], mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = treasury,
+    )]
+    pub route_escrow: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(venue: Venue)]
+pub struct SweepRouteEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        seeds = [b"route_escrow".as_ref(), &[venue as u8], route_escrow.mint.as_ref()],
+        bump,
+    )]
+    pub route_escrow: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        token::mint = route_escrow.mint,
+        token::authority = treasury,
+    )]
+    pub treasury_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    pub caller: Signer<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct AdvanceEpoch<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init,
+        payer = caller,
+        space = 8 + EpochStats::INIT_SPACE,
+        seeds = [b"epoch_stats", treasury.current_epoch.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub epoch_stats: Account<'info, EpochStats>,
+
+    #[account(mut)]
+    pub caller: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RollEpoch<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init,
+        payer = caller,
+        space = 8 + Epoch::INIT_SPACE,
+        seeds = [b"epoch", treasury.budget_epoch.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub epoch: Account<'info, Epoch>,
+
+    #[account(mut)]
+    pub caller: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DrawReserve<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = treasury_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = treasury_usdc.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = destination_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+    )]
+    pub destination_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// Address book entry of the destination
+    #[account(
+        seeds = [b"address_book", destination_usdc.key().as_ref()],
+        bump = entry.bump,
+    )]
+    pub entry: Account<'info, AddressBookEntry>,
+
+    #[account(
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    /// Required for `ReserveTier::MarketDefense`
+    pub guardian: Option<Signer<'info>>,
+
+    /// Required for `ReserveTier::FullDrawdown`
+    pub governance: Option<Signer<'info>>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct AddAddressBookEntry<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        constraint = destination_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+    )]
+    pub destination_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + AddressBookEntry::INIT_SPACE,
+        seeds = [b"address_book", destination_usdc.key().as_ref()],
+        bump
+    )]
+    pub entry: Account<'info, AddressBookEntry>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveAddressBookEntry<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"address_book", entry.destination.as_ref()],
+        bump = entry.bump,
+    )]
+    pub entry: Account<'info, AddressBookEntry>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveReserveDestination<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"reserve_destination", entry.destination.as_ref()],
+        bump = entry.bump,
+    )]
+    pub entry: Account<'info, ReserveDestination>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct QueueReserveWithdrawal<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    /// Address book entry of the destination
+    #[account(
+        seeds = [b"address_book", entry.destination.as_ref()],
+        bump = entry.bump,
+    )]
+    pub entry: Account<'info, AddressBookEntry>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ReserveWithdrawal::INIT_SPACE,
+        seeds = [
+            b"reserve_withdrawal",
+            treasury.reserve_withdrawal_count.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub withdrawal: Account<'info, ReserveWithdrawal>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawReserve<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"reserve_withdrawal", withdrawal.index.to_le_bytes().as_ref()],
+        bump = withdrawal.bump,
+    )]
+    pub withdrawal: Account<'info, ReserveWithdrawal>,
+
+    /// The destination must still be in the address book when the withdrawal executes
+    #[account(
+        seeds = [b"address_book", withdrawal.destination.as_ref()],
+        bump = entry.bump,
+    )]
+    pub entry: Account<'info, AddressBookEntry>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = treasury_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = treasury_usdc.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        address = withdrawal.destination,
+    )]
+    pub destination_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CancelReserveWithdrawal<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"reserve_withdrawal", withdrawal.index.to_le_bytes().as_ref()],
+        bump = withdrawal.bump,
+    )]
+    pub withdrawal: Account<'info, ReserveWithdrawal>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AttestReserves<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Attestation::INIT_SPACE,
+        seeds = [b"attestation", treasury.current_epoch.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AttestLpLocks<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+}
+
+#[derive(Accounts)]
+pub struct PlaceConditionalBuyback<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ConditionalBuyback::INIT_SPACE,
+        seeds = [
+            b"conditional_buyback",
+            treasury.conditional_buyback_count.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub order: Account<'info, ConditionalBuyback>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.operator
+    )]
+    pub authority: Signer<'info>,
+
+    /// Required when `usdc_amount` exceeds `treasury.cosign_threshold_usdc`
+    pub co_signer: Option<Signer<'info>>,
+
+    /// `Operator` account of `co_signer` when a second registered operator co-signs
+    pub co_signer_operator: Option<Account<'info, Operator>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelConditionalBuyback<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"conditional_buyback", order.index.to_le_bytes().as_ref()],
+        bump = order.bump,
+    )]
+    pub order: Account<'info, ConditionalBuyback>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.operator
+    )]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteConditionalBuyback<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        close = creator,
+        seeds = [b"conditional_buyback", order.index.to_le_bytes().as_ref()],
+        bump = order.bump,
+        has_one = creator,
+    )]
+    pub order: Account<'info, ConditionalBuyback>,
+
+    #[account(
+        mut,
+        constraint = treasury_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = treasury_usdc.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        address = treasury.token_mint @ ErrorCode::InvalidTokenMint,
+    )]
+    pub token_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    /// Receives the bought tokens
+    #[account(
+        mut,
+        constraint = treasury_token.mint == treasury.token_mint @ ErrorCode::InvalidTokenMint,
+        constraint = treasury_token.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_token: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// CHECK: validated against `treasury.token_usd_oracle` and parsed by `oracle::read_price`
+    #[account(address = treasury.token_usd_oracle @ ErrorCode::InvalidOracle)]
+    pub token_usd_oracle: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"operator", operator.key().as_ref()],
+        bump = operator_stats.bump,
+        constraint = operator_stats.active @ ErrorCode::UnauthorizedOperator,
+    )]
+    pub operator_stats: Account<'info, Operator>,
+
+    pub operator: Signer<'info>,
+
+    /// CHECK: receives the order's rent; must match `order.creator`
+    #[account(mut)]
+    pub creator: UncheckedAccount<'info>,
+
+    /// Quote board to publish the fill on, when it has been opened
+    #[account(
+        mut,
+        seeds = [b"quote_board"],
+        bump = quote_board.bump,
+    )]
+    pub quote_board: Option<Account<'info, QuoteBoard>>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeVaultMigration<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        constraint = treasury.guardian != Pubkey::default()
+            && guardian.key() == treasury.guardian @ ErrorCode::GuardianRequired
+    )]
+    pub guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"key_activity", guardian.key().as_ref()],
+        bump = guardian_activity.bump,
+    )]
+    pub guardian_activity: Option<Account<'info, KeyActivity>>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteVaultMigration<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct Heartbeat<'info> {
+    #[account(
+        mut,
+        seeds = [b"operator", operator.key().as_ref()],
+        bump = operator_stats.bump,
+        constraint = operator_stats.active @ ErrorCode::UnauthorizedOperator,
+    )]
+    pub operator_stats: Account<'info, Operator>,
+
+    pub operator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializePoolRegistry<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PoolRegistry::INIT_SPACE,
+        seeds = [b"pool_registry"],
+        bump
+    )]
+    pub pool_registry: Account<'info, PoolRegistry>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePoolRegistry<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"pool_registry"],
+        bump = pool_registry.bump,
+    )]
+    pub pool_registry: Account<'info, PoolRegistry>,
+
+    #[account(
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateCampaign<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+        has_one = token_mint,
+    )]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Campaign::INIT_SPACE,
+        seeds = [b"campaign", treasury.campaign_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub campaign: Box<Account<'info, Campaign>>,
+
+    #[account(
+        seeds = [b"pool_registry"],
+        bump = pool_registry.bump,
+        constraint = pool_registry
+            .find(&lp_position.pool_usdc, &lp_position.pool_token)
+            .is_some() @ ErrorCode::UnregisteredPool,
+    )]
+    pub pool_registry: Box<Account<'info, PoolRegistry>>,
+
+    /// Treasury position in the incentivized pool, which pins its LP mint
+    #[account(
+        seeds = [b"lp_position", lp_position.pool_usdc.as_ref(), lp_position.pool_token.as_ref()],
+        bump = lp_position.bump,
+        constraint = lp_position.lp_mint == lp_mint.key() @ ErrorCode::LpPositionMismatch,
+    )]
+    pub lp_position: Box<Account<'info, LpPosition>>,
+
+    #[account(mint::token_program = token_program)]
+    pub lp_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    #[account(
+        mint::token_program = token_2022_program,
+    )]
+    pub token_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::token_program = token_2022_program,
+        constraint = treasury_token.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_token: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"campaign_rewards", campaign.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = treasury,
+        token::token_program = token_2022_program,
+    )]
+    pub reward_vault: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"campaign_stake", campaign.key().as_ref()],
+        bump,
+        token::mint = lp_mint,
+        token::authority = treasury,
+    )]
+    pub stake_vault: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+    pub token_2022_program: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelCampaign<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+        has_one = token_mint,
+    )]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.index.to_le_bytes().as_ref()],
+        bump = campaign.bump,
+    )]
+    pub campaign: Box<Account<'info, Campaign>>,
+
+    #[account(
+        mint::token_program = token_2022_program,
+    )]
+    pub token_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::token_program = token_2022_program,
+        constraint = treasury_token.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_token: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"campaign_rewards", campaign.key().as_ref()],
+        bump,
+    )]
+    pub reward_vault: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        constraint = authority.key() == treasury.authority
+            || (treasury.guardian != Pubkey::default() && authority.key() == treasury.guardian)
+    )]
+    pub authority: Signer<'info>,
+
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct OpenStakePosition<'info> {
+    #[account(
+        seeds = [b"campaign", campaign.index.to_le_bytes().as_ref()],
+        bump = campaign.bump,
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + StakePosition::INIT_SPACE,
+        seeds = [b"stake", campaign.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub position: Account<'info, StakePosition>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct StakeLp<'info> {
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.index.to_le_bytes().as_ref()],
+        bump = campaign.bump,
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        mut,
+        seeds = [b"stake", campaign.key().as_ref(), owner.key().as_ref()],
+        bump = position.bump,
+    )]
+    pub position: Account<'info, StakePosition>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        seeds = [b"campaign_stake", campaign.key().as_ref()],
+        bump,
+    )]
+    pub stake_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        token::mint = campaign.lp_mint,
+    )]
+    pub owner_lp: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(address = campaign.lp_mint)]
+    pub lp_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    pub owner: Signer<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct UnstakeLp<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.index.to_le_bytes().as_ref()],
+        bump = campaign.bump,
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        mut,
+        seeds = [b"stake", campaign.key().as_ref(), owner.key().as_ref()],
+        bump = position.bump,
+    )]
+    pub position: Account<'info, StakePosition>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        seeds = [b"campaign_stake", campaign.key().as_ref()],
+        bump,
+    )]
+    pub stake_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        token::mint = campaign.lp_mint,
+    )]
+    pub owner_lp: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(address = campaign.lp_mint)]
+    pub lp_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    pub owner: Signer<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimCampaignRewards<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+        has_one = token_mint,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.index.to_le_bytes().as_ref()],
+        bump = campaign.bump,
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        mut,
+        seeds = [b"stake", campaign.key().as_ref(), owner.key().as_ref()],
+        bump = position.bump,
+    )]
+    pub position: Account<'info, StakePosition>,
+
+    #[account(
+        mut,
+        seeds = [b"campaign_rewards", campaign.key().as_ref()],
+        bump,
+    )]
+    pub reward_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        mint::token_program = token_2022_program,
+    )]
+    pub token_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::token_program = token_2022_program,
+    )]
+    pub owner_token: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    pub owner: Signer<'info>,
+
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct RouteBest<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        constraint = treasury_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = treasury_usdc.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = treasury_token.mint == treasury.token_mint @ ErrorCode::InvalidTokenMint,
+        constraint = treasury_token.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_token: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        address = treasury.token_mint @ ErrorCode::InvalidTokenMint,
+    )]
+    pub token_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    /// CHECK: must be `treasury.token_usd_oracle`; required once a token price feed is set
+    pub token_usd_oracle: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: must be `treasury.token_switchboard_feed`; used when the Pyth price is unusable
+    pub token_switchboard_feed: Option<UncheckedAccount<'info>>,
+
+    /// TWAP of the chosen pool, whose vaults are its candidate's first two accounts.
+    /// Required once `treasury.twap_max_deviation_bps` is set.
+    #[account(
+        mut,
+        seeds = [b"twap", twap.usdc_vault.as_ref(), twap.token_vault.as_ref()],
+        bump = twap.bump,
+    )]
+    pub twap: Option<Box<Account<'info, Twap>>>,
+
+    #[account(
+        seeds = [b"pool_registry"],
+        bump = pool_registry.bump,
+    )]
+    pub pool_registry: Account<'info, PoolRegistry>,
+
+    #[account(
+        constraint = authority.key() == treasury.operator
+    )]
+    pub authority: Signer<'info>,
+
+    /// `Operator` account of `authority`; its scope must cover the operation
+    #[account(
+        seeds = [b"operator", authority.key().as_ref()],
+        bump = operator_stats.bump,
+        constraint = operator_stats.active @ ErrorCode::UnauthorizedOperator,
+    )]
+    pub operator_stats: Account<'info, Operator>,
+
+    #[account(
+        mut,
+        seeds = [b"key_activity", authority.key().as_ref()],
+        bump = authority_activity.bump,
+    )]
+    pub authority_activity: Option<Account<'info, KeyActivity>>,
+
+    /// Required when `usdc_amount` exceeds `treasury.cosign_threshold_usdc`
+    pub co_signer: Option<Signer<'info>>,
+
+    /// `Operator` account of `co_signer` when a second registered operator co-signs
+    pub co_signer_operator: Option<Account<'info, Operator>>,
+
+    /// Quote board to publish the fill on, when it has been opened
+    #[account(
+        mut,
+        seeds = [b"quote_board"],
+        bump = quote_board.bump,
+    )]
+    pub quote_board: Option<Account<'info, QuoteBoard>>,
+}
+
+#[derive(Accounts)]
+pub struct CheckUsdcPeg<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    /// CHECK: verified against `treasury.usdc_usd_oracle` and parsed by `oracle::read_price`
+    pub usdc_usd_oracle: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteBurn<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+        has_one = token_mint,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        mint::token_program = token_program,
+    )]
+    pub token_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = treasury,
+        token::token_program = token_program,
+    )]
+    pub treasury_token: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        constraint = authority.key() == treasury.operator
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"key_activity", authority.key().as_ref()],
+        bump = authority_activity.bump,
+    )]
+    pub authority_activity: Option<Account<'info, KeyActivity>>,
+
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct ExternalBurn<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+        has_one = token_mint,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    pub token_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"key_activity", authority.key().as_ref()],
+        bump = authority_activity.bump,
+    )]
+    pub authority_activity: Option<Account<'info, KeyActivity>>,
+}
+
+#[derive(Accounts)]
+pub struct RecordHolderSnapshot<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + HolderSnapshot::INIT_SPACE,
+        seeds = [b"holder_snapshot", treasury.current_epoch.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub snapshot: Account<'info, HolderSnapshot>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ViewHolderBoost<'info> {
+    #[account(
+        seeds = [b"holder_snapshot", snapshot.epoch.to_le_bytes().as_ref()],
+        bump = snapshot.bump,
+    )]
+    pub snapshot: Account<'info, HolderSnapshot>,
+
+    /// CHECK: only its key is used, as the leaf's holder
+    pub holder: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct BuybackAndBurn<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+        has_one = token_mint,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        constraint = treasury_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = treasury_usdc.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        mut,
+        mint::token_program = token_2022_program,
+    )]
+    pub token_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = treasury,
+        token::token_program = token_2022_program,
+    )]
+    pub treasury_token: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// CHECK: must be `treasury.token_usd_oracle`; required once a token price feed is set
+    pub token_usd_oracle: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: must be `treasury.token_switchboard_feed`; used when the Pyth price is unusable
+    pub token_switchboard_feed: Option<UncheckedAccount<'info>>,
+
+    /// TWAP of the pool the buyback trades against, with that pool's vaults.
+    /// Required once `treasury.twap_max_deviation_bps` is set.
+    #[account(
+        mut,
+        seeds = [b"twap", twap.usdc_vault.as_ref(), twap.token_vault.as_ref()],
+        bump = twap.bump,
+    )]
+    pub twap: Option<Box<Account<'info, Twap>>>,
+    pub twap_pool_usdc: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    pub twap_pool_token: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+
+    #[account(
+        constraint = authority.key() == treasury.operator
+    )]
+    pub authority: Signer<'info>,
+
+    /// `Operator` account of `authority`; its scope must cover the operation
+    #[account(
+        seeds = [b"operator", authority.key().as_ref()],
+        bump = operator_stats.bump,
+        constraint = operator_stats.active @ ErrorCode::UnauthorizedOperator,
+    )]
+    pub operator_stats: Account<'info, Operator>,
+
+    #[account(
+        mut,
+        seeds = [b"key_activity", authority.key().as_ref()],
+        bump = authority_activity.bump,
+    )]
+    pub authority_activity: Option<Account<'info, KeyActivity>>,
+
+    /// Required when `usdc_amount` exceeds `treasury.cosign_threshold_usdc`
+    pub co_signer: Option<Signer<'info>>,
+
+    /// `Operator` account of `co_signer` when a second registered operator co-signs
+    pub co_signer_operator: Option<Account<'info, Operator>>,
+
+    pub token_2022_program: Program<'info, Token2022>,
+
+    /// Quote board to publish the fill on, when it has been opened
+    #[account(
+        mut,
+        seeds = [b"quote_board"],
+        bump = quote_board.bump,
+    )]
+    pub quote_board: Option<Account<'info, QuoteBoard>>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_maker: Pubkey)]
+pub struct RegisterMarketMaker<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+        has_one = usdc_mint,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + MarketMakerLoan::INIT_SPACE,
+        seeds = [b"mm_loan", market_maker.as_ref()],
+        bump
+    )]
+    pub loan: Account<'info, MarketMakerLoan>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"mm_collateral", market_maker.as_ref()],
+        bump,
+        token::mint = usdc_mint,
+        token::authority = treasury,
+    )]
+    pub collateral_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(mint::token_program = token_program)]
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetMarketMakerActive<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"mm_loan", loan.market_maker.as_ref()],
+        bump = loan.bump,
+    )]
+    pub loan: Account<'info, MarketMakerLoan>,
+
+    #[account(
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct BorrowInventory<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+        has_one = token_mint,
+    )]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    #[account(
+        mut,
+        seeds = [b"mm_loan", market_maker.key().as_ref()],
+        bump = loan.bump,
+        has_one = market_maker,
+    )]
+    pub loan: Account<'info, MarketMakerLoan>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        seeds = [b"mm_collateral", market_maker.key().as_ref()],
+        bump,
+    )]
+    pub collateral_vault: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = market_maker_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+    )]
+    pub market_maker_usdc: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        mint::token_program = token_2022_program,
+    )]
+    pub token_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = treasury,
+        token::token_program = token_2022_program,
+    )]
+    pub treasury_token: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::token_program = token_2022_program,
+    )]
+    pub market_maker_token: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    /// CHECK: must be `treasury.token_usd_oracle`; parsed by `oracle::read_price`
+    #[account(
+        constraint = token_usd_oracle.key() == treasury.token_usd_oracle @ ErrorCode::InvalidOracle
+    )]
+    pub token_usd_oracle: UncheckedAccount<'info>,
+
+    pub market_maker: Signer<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct RepayInventory<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+        has_one = token_mint,
+    )]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    #[account(
+        mut,
+        seeds = [b"mm_loan", market_maker.key().as_ref()],
+        bump = loan.bump,
+        has_one = market_maker,
+    )]
+    pub loan: Account<'info, MarketMakerLoan>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        seeds = [b"mm_collateral", market_maker.key().as_ref()],
+        bump,
+    )]
+    pub collateral_vault: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = market_maker_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+    )]
+    pub market_maker_usdc: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        mint::token_program = token_2022_program,
+    )]
+    pub token_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = treasury,
+        token::token_program = token_2022_program,
+    )]
+    pub treasury_token: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::token_program = token_2022_program,
+    )]
+    pub market_maker_token: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    /// CHECK: must be `treasury.token_usd_oracle`; parsed by `oracle::read_price`
+    #[account(
+        constraint = token_usd_oracle.key() == treasury.token_usd_oracle @ ErrorCode::InvalidOracle
+    )]
+    pub token_usd_oracle: UncheckedAccount<'info>,
+
+    pub market_maker: Signer<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct LiquidateMarketMaker<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+        has_one = token_mint,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"mm_loan", loan.market_maker.as_ref()],
+        bump = loan.bump,
+    )]
+    pub loan: Account<'info, MarketMakerLoan>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        seeds = [b"mm_collateral", loan.market_maker.as_ref()],
+        bump,
+    )]
+    pub collateral_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = treasury_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = treasury_usdc.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    pub token_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    /// CHECK: must be `treasury.token_usd_oracle`; parsed by `oracle::read_price`
+    #[account(
+        constraint = token_usd_oracle.key() == treasury.token_usd_oracle @ ErrorCode::InvalidOracle
+    )]
+    pub token_usd_oracle: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeTwap<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        seeds = [b"pool_registry"],
+        bump = pool_registry.bump,
+        constraint = pool_registry.find(&pool_usdc.key(), &pool_token.key()).is_some()
+            @ ErrorCode::UnregisteredPool,
+    )]
+    pub pool_registry: Account<'info, PoolRegistry>,
+
+    /// CHECK: only its key is recorded; must be a registered pool vault
+    pub pool_usdc: UncheckedAccount<'info>,
+
+    /// CHECK: only its key is recorded; must be a registered pool vault
+    pub pool_token: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Twap::INIT_SPACE,
+        seeds = [b"twap", pool_usdc.key().as_ref(), pool_token.key().as_ref()],
+        bump
+    )]
+    pub twap: Account<'info, Twap>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyMintConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+        has_one = token_mint,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    pub token_mint: InterfaceAccount<'info, token_interface::Mint>,
+}
+
+#[derive(Accounts)]
+pub struct CrankBuyback<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+        has_one = token_mint,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = treasury_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = treasury_usdc.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_usdc: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    pub token_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    #[account(
+        mut,
+        constraint = treasury_token.mint == treasury.token_mint @ ErrorCode::InvalidTokenMint,
+        constraint = treasury_token.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_token: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    /// CHECK: must be `treasury.token_usd_oracle`
+    pub token_usd_oracle: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: must be `treasury.token_switchboard_feed`; used when the Pyth price is unusable
+    pub token_switchboard_feed: Option<UncheckedAccount<'info>>,
+
+    /// Receives the keeper reward
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = keeper_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+    )]
+    pub keeper_usdc: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    pub keeper: Signer<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+
+    /// Quote board to publish the fill on, when it has been opened
+    #[account(
+        mut,
+        seeds = [b"quote_board"],
+        bump = quote_board.bump,
+    )]
+    pub quote_board: Option<Box<Account<'info, QuoteBoard>>>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeCalendar<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Calendar::INIT_SPACE,
+        seeds = [b"calendar"],
+        bump
+    )]
+    pub calendar: Account<'info, Calendar>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SyncCalendar<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"calendar"],
+        bump = calendar.bump,
+    )]
+    pub calendar: Account<'info, Calendar>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeTokenMigration<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + TokenMigration::INIT_SPACE,
+        seeds = [b"token_migration"],
+        bump
+    )]
+    pub migration: Account<'info, TokenMigration>,
+
+    /// Burns and LP deposits go through Token-2022, so the new mint must too
+    #[account(
+        mint::token_program = token_2022_program,
+    )]
+    pub new_token_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        constraint = treasury.guardian != Pubkey::default()
+            && guardian.key() == treasury.guardian @ ErrorCode::GuardianRequired
+    )]
+    pub guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"key_activity", guardian.key().as_ref()],
+        bump = guardian_activity.bump,
+    )]
+    pub guardian_activity: Option<Account<'info, KeyActivity>>,
+
+    pub token_2022_program: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelTokenMigration<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"token_migration"],
+        bump = migration.bump,
+        close = authority,
+    )]
+    pub migration: Account<'info, TokenMigration>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateTreasuryTokens<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    #[account(
+        mut,
+        seeds = [b"token_migration"],
+        bump = migration.bump,
+    )]
+    pub migration: Box<Account<'info, TokenMigration>>,
+
+    #[account(
+        address = migration.old_mint,
+        mint::token_program = token_program,
+    )]
+    pub old_token_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    #[account(
+        address = migration.new_mint,
+        mint::token_program = token_program,
+    )]
+    pub new_token_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    #[account(
+        mut,
+        token::mint = old_token_mint,
+        token::authority = treasury,
+        token::token_program = token_program,
+    )]
+    pub treasury_old_token: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = new_token_mint,
+        token::authority = treasury,
+        token::token_program = token_program,
+    )]
+    pub treasury_new_token: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = old_token_mint,
+        token::authority = migration,
+        token::token_program = token_program,
+    )]
+    pub old_token_escrow: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = new_token_mint,
+        token::authority = migration,
+        token::token_program = token_program,
+    )]
+    pub new_token_escrow: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeTokenMigration<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"token_migration"],
+        bump = migration.bump,
+    )]
+    pub migration: Account<'info, TokenMigration>,
+
+    #[account(
+        address = migration.new_mint,
+    )]
+    pub new_token_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        constraint = treasury.guardian != Pubkey::default()
+            && guardian.key() == treasury.guardian @ ErrorCode::GuardianRequired
+    )]
+    pub guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"key_activity", guardian.key().as_ref()],
+        bump = guardian_activity.bump,
+    )]
+    pub guardian_activity: Option<Account<'info, KeyActivity>>,
+}
+
+#[derive(Accounts)]
+pub struct BindSquadsMultisig<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    /// CHECK: owner and discriminator checked by `squads::check_multisig`
+    pub multisig: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(key: Pubkey)]
+pub struct OpenKeyActivity<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + KeyActivity::INIT_SPACE,
+        seeds = [b"key_activity", key.as_ref()],
+        bump
+    )]
+    pub activity: Account<'info, KeyActivity>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeConfig<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ConfigProposal::INIT_SPACE,
+        seeds = [b"config_proposal"],
+        bump
+    )]
+    pub proposal: Account<'info, ConfigProposal>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"key_activity", authority.key().as_ref()],
+        bump = authority_activity.bump,
+    )]
+    pub authority_activity: Option<Account<'info, KeyActivity>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"config_proposal"],
+        bump = proposal.bump,
+        close = authority,
+    )]
+    pub proposal: Account<'info, ConfigProposal>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"key_activity", authority.key().as_ref()],
+        bump = authority_activity.bump,
+    )]
+    pub authority_activity: Option<Account<'info, KeyActivity>>,
+}
+
+#[derive(Accounts)]
+pub struct CancelConfig<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"config_proposal"],
+        bump = proposal.bump,
+        close = authority,
+    )]
+    pub proposal: Account<'info, ConfigProposal>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SimulateCycle<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+        has_one = token_mint,
+    )]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    #[account(
+        seeds = [b"fee_vault"],
+        bump = fee_vault.bump,
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    pub token_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        constraint = treasury_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = treasury_usdc.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// CHECK: must be `treasury.sol_usd_oracle` to value the conversion leg
+    pub sol_usd_oracle: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: must be `treasury.token_usd_oracle` to quote the buyback leg
+    pub token_usd_oracle: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: must be `treasury.token_switchboard_feed`; used when Pyth is unusable
+    pub token_switchboard_feed: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct BindRealmsGovernance<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    /// CHECK: owner, account type and address checked by `realms::check_governance`
+    pub governance: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAllocations<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+        constraint = treasury.realms_governance != Pubkey::default()
+            @ ErrorCode::AdminNotRealmsGovernance,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    /// The bound governance, signing as proposal executor
+    #[account(
+        constraint = governance.key() == treasury.authority
+            && governance.key() == treasury.realms_governance
+            @ ErrorCode::AdminNotRealmsGovernance
+    )]
+    pub governance: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateLbp<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+        has_one = token_mint,
+    )]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Lbp::INIT_SPACE,
+        seeds = [b"lbp", treasury.lbp_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub lbp: Box<Account<'info, Lbp>>,
+
+    #[account(
+        mint::token_program = token_2022_program,
+    )]
+    pub token_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = treasury,
+        token::token_program = token_2022_program,
+    )]
+    pub treasury_token: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = treasury_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = treasury_usdc.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_usdc: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    /// Token reserve of the pool, owned by the LBP PDA
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = lbp,
+        token::token_program = token_2022_program,
+    )]
+    pub lbp_token: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    /// USDC reserve of the pool, owned by the LBP PDA
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = lbp_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = lbp_usdc.owner == lbp.key(),
+    )]
+    pub lbp_usdc: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        seeds = [b"pool_registry"],
+        bump = pool_registry.bump,
+    )]
+    pub pool_registry: Box<Account<'info, PoolRegistry>>,
+
+    /// CHECK: must be `treasury.usdc_usd_oracle`; required once the depeg guard is set
+    pub usdc_usd_oracle: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.operator
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"key_activity", authority.key().as_ref()],
+        bump = authority_activity.bump,
+    )]
+    pub authority_activity: Option<Account<'info, KeyActivity>>,
+
+    /// Required when `usdc_amount` exceeds `treasury.cosign_threshold_usdc`
+    pub co_signer: Option<Signer<'info>>,
+
+    /// `Operator` account of `co_signer` when a second registered operator co-signs
+    pub co_signer_operator: Option<Account<'info, Operator>>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+    pub token_2022_program: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ContributeLbp<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    #[account(
+        mut,
+        seeds = [b"lbp", lbp.index.to_le_bytes().as_ref()],
+        bump = lbp.bump,
+        has_one = token_mint,
+    )]
+    pub lbp: Box<Account<'info, Lbp>>,
+
+    pub token_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    #[account(
+        token::token_program = token_2022_program,
+        mut,
+        address = lbp.token_vault,
+    )]
+    pub lbp_token: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        address = lbp.usdc_vault,
+    )]
+    pub lbp_usdc: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = contributor_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+    )]
+    pub contributor_usdc: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::token_program = token_2022_program,
+    )]
+    pub contributor_token: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    pub contributor: Signer<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct SettleLbp<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    #[account(
+        mut,
+        seeds = [b"lbp", lbp.index.to_le_bytes().as_ref()],
+        bump = lbp.bump,
+        has_one = token_mint,
+    )]
+    pub lbp: Box<Account<'info, Lbp>>,
+
+    pub token_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    #[account(
+        token::token_program = token_2022_program,
+        mut,
+        address = lbp.token_vault,
+    )]
+    pub lbp_token: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        address = lbp.usdc_vault,
+    )]
+    pub lbp_usdc: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = treasury,
+        token::token_program = token_2022_program,
+    )]
+    pub treasury_token: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = treasury_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = treasury_usdc.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_usdc: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    pub caller: Signer<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct KaminoLending<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    /// CHECK: must be the configured reserve; its contents are validated by Kamino
+    #[account(
+        mut,
+        constraint = treasury.kamino_reserve != Pubkey::default()
+            && kamino_reserve.key() == treasury.kamino_reserve @ ErrorCode::KaminoNotConfigured,
+    )]
+    pub kamino_reserve: UncheckedAccount<'info>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = treasury_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = treasury_usdc.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// kTokens of `kamino_reserve` held by the treasury
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = treasury_collateral.mint == reserve_collateral_mint.key()
+            @ ErrorCode::InvalidCollateralAccount,
+        constraint = treasury_collateral.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_collateral: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        address = treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        mint::token_program = token_program,
+    )]
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    /// CHECK: validated by Kamino against `kamino_reserve`
+    pub lending_market: UncheckedAccount<'info>,
+
+    /// CHECK: validated by Kamino against `lending_market`
+    pub lending_market_authority: UncheckedAccount<'info>,
+
+    /// CHECK: validated by Kamino against `kamino_reserve`
+    #[account(mut)]
+    pub reserve_liquidity_supply: UncheckedAccount<'info>,
+
+    /// CHECK: validated by Kamino against `kamino_reserve`
+    #[account(mut)]
+    pub reserve_collateral_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Kamino reads it to check the reserve was refreshed in this transaction
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    /// CHECK: address checked
+    #[account(address = kamino::ID)]
+    pub kamino_program: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+}
+
+impl<'info> KaminoLending<'info> {
+    fn reserve_accounts(&self) -> kamino::ReserveAccounts<'info> {
+        kamino::ReserveAccounts {
+            owner: self.treasury.to_account_info(),
+            reserve: self.kamino_reserve.to_account_info(),
+            lending_market: self.lending_market.to_account_info(),
+            lending_market_authority: self.lending_market_authority.to_account_info(),
+            liquidity_mint: self.usdc_mint.to_account_info(),
+            liquidity_supply: self.reserve_liquidity_supply.to_account_info(),
+            collateral_mint: self.reserve_collateral_mint.to_account_info(),
+            user_liquidity: self.treasury_usdc.to_account_info(),
+            user_collateral: self.treasury_collateral.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            instructions: self.instructions.to_account_info(),
+            program: self.kamino_program.to_account_info(),
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct SolendLending<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    /// CHECK: must be the configured reserve; its contents are validated by Solend
+    #[account(
+        mut,
+        constraint = treasury.solend_reserve != Pubkey::default()
+            && solend_reserve.key() == treasury.solend_reserve @ ErrorCode::SolendNotConfigured,
+    )]
+    pub solend_reserve: UncheckedAccount<'info>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = treasury_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = treasury_usdc.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// cTokens of `solend_reserve` held by the treasury
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = treasury_collateral.mint == reserve_collateral_mint.key()
+            @ ErrorCode::InvalidCollateralAccount,
+        constraint = treasury_collateral.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_collateral: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// CHECK: validated by Solend against `solend_reserve`
+    pub lending_market: UncheckedAccount<'info>,
+
+    /// CHECK: validated by Solend against `lending_market`
+    pub lending_market_authority: UncheckedAccount<'info>,
+
+    /// CHECK: validated by Solend against `solend_reserve`
+    #[account(mut)]
+    pub reserve_liquidity_supply: UncheckedAccount<'info>,
+
+    /// CHECK: validated by Solend against `solend_reserve`
+    #[account(mut)]
+    pub reserve_collateral_mint: UncheckedAccount<'info>,
+
+    /// CHECK: address checked
+    #[account(address = solend::ID)]
+    pub solend_program: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+}
+
+impl<'info> SolendLending<'info> {
+    fn reserve_accounts(&self) -> solend::ReserveAccounts<'info> {
+        solend::ReserveAccounts {
+            user_liquidity: self.treasury_usdc.to_account_info(),
+            user_collateral: self.treasury_collateral.to_account_info(),
+            reserve: self.solend_reserve.to_account_info(),
+            liquidity_supply: self.reserve_liquidity_supply.to_account_info(),
+            collateral_mint: self.reserve_collateral_mint.to_account_info(),
+            lending_market: self.lending_market.to_account_info(),
+            lending_market_authority: self.lending_market_authority.to_account_info(),
+            transfer_authority: self.treasury.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            program: self.solend_program.to_account_info(),
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct OpenQuoteBoard<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + QuoteBoard::INIT_SPACE,
+        seeds = [b"quote_board"],
+        bump
+    )]
+    pub quote_board: Account<'info, QuoteBoard>,
+
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(address = treasury.token_mint @ ErrorCode::InvalidTokenMint)]
+    pub token_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitMarginFiAccount<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    /// Fresh keypair; MarginFi creates the account
+    #[account(mut)]
+    pub marginfi_account: Signer<'info>,
+
+    /// CHECK: validated by MarginFi
+    pub marginfi_group: UncheckedAccount<'info>,
+
+    /// CHECK: address checked
+    #[account(address = marginfi::ID)]
+    pub marginfi_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MarginFiLending<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    /// CHECK: must be the configured bank; its contents are validated by MarginFi
+    #[account(
+        mut,
+        constraint = treasury.marginfi_bank != Pubkey::default()
+            && marginfi_bank.key() == treasury.marginfi_bank @ ErrorCode::MarginFiNotConfigured,
+    )]
+    pub marginfi_bank: UncheckedAccount<'info>,
+
+    /// CHECK: must be the treasury's MarginFi account
+    #[account(
+        mut,
+        constraint = treasury.marginfi_account != Pubkey::default()
+            && marginfi_account.key() == treasury.marginfi_account @ ErrorCode::MarginFiNotConfigured,
+    )]
+    pub marginfi_account: UncheckedAccount<'info>,
+
+    /// CHECK: validated by MarginFi against `marginfi_bank` and `marginfi_account`
+    pub marginfi_group: UncheckedAccount<'info>,
+
+    #[account(
+        token::token_program = token_program,
+        mut,
+        constraint = treasury_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = treasury_usdc.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// CHECK: validated by MarginFi against `marginfi_bank`
+    #[account(mut)]
+    pub bank_liquidity_vault: UncheckedAccount<'info>,
+
+    /// CHECK: validated by MarginFi against `marginfi_bank`; only used by withdrawals
+    pub bank_liquidity_vault_authority: UncheckedAccount<'info>,
+
+    /// CHECK: address checked
+    #[account(address = marginfi::ID)]
+    pub marginfi_program: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+}
+
+impl<'info> MarginFiLending<'info> {
+    fn bank_accounts(&self) -> marginfi::BankAccounts<'info> {
+        marginfi::BankAccounts {
+            group: self.marginfi_group.to_account_info(),
+            marginfi_account: self.marginfi_account.to_account_info(),
+            authority: self.treasury.to_account_info(),
+            bank: self.marginfi_bank.to_account_info(),
+            user_liquidity: self.treasury_usdc.to_account_info(),
+            liquidity_vault: self.bank_liquidity_vault.to_account_info(),
+            liquidity_vault_authority: self.bank_liquidity_vault_authority.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            program: self.marginfi_program.to_account_info(),
+        }
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(guardian: Pubkey)]
+pub struct OpenGuardianRecord<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + GuardianRecord::INIT_SPACE,
+        seeds = [b"guardian_record", guardian.as_ref()],
+        bump
+    )]
+    pub record: Account<'info, GuardianRecord>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(kind: MilestoneKind, threshold: u64)]
+pub struct RecordMilestone<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Milestone::INIT_SPACE,
+        seeds = [b"milestone".as_ref(), &[kind as u8], threshold.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub milestone: Account<'info, Milestone>,
+
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GuardianRespond<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        constraint = treasury.guardian != Pubkey::default()
+            && guardian.key() == treasury.guardian @ ErrorCode::GuardianRequired
+    )]
+    pub guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"guardian_record", guardian.key().as_ref()],
+        bump = record.bump,
+    )]
+    pub record: Account<'info, GuardianRecord>,
+}
+
+#[derive(Accounts)]
+pub struct ReviewConfigProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"config_proposal"],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, ConfigProposal>,
+
+    /// CHECK: receives the rent of a vetoed proposal
+    #[account(mut, address = treasury.authority)]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = treasury.guardian != Pubkey::default()
+            && guardian.key() == treasury.guardian @ ErrorCode::GuardianRequired
+    )]
+    pub guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"guardian_record", guardian.key().as_ref()],
+        bump = record.bump,
+    )]
+    pub record: Account<'info, GuardianRecord>,
+}
+
+#[derive(Accounts)]
+pub struct OpenWhirlpoolPosition<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + LiquidityPosition::INIT_SPACE,
+        seeds = [b"liquidity_position", position_mint.key().as_ref()],
+        bump
+    )]
+    pub liquidity_position: Account<'info, LiquidityPosition>,
+
+    /// CHECK: must hold a registered pool's vaults; Orca validates the rest
+    pub whirlpool: UncheckedAccount<'info>,
+
+    /// Pool's USDC vault, identifying the whirlpool in the registry
+    #[account(
+        token::token_program = token_program,
+        constraint = pool_usdc_vault.owner == whirlpool.key() @ ErrorCode::UnregisteredPool,
+    )]
+    pub pool_usdc_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// Pool's token vault, identifying the whirlpool in the registry
+    #[account(
+        token::token_program = token_2022_program,
+        constraint = pool_token_vault.owner == whirlpool.key() @ ErrorCode::UnregisteredPool,
+    )]
+    pub pool_token_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        seeds = [b"pool_registry"],
+        bump = pool_registry.bump,
+        constraint = pool_registry.find(&pool_usdc_vault.key(), &pool_token_vault.key()).is_some()
+            @ ErrorCode::UnregisteredPool,
+    )]
+    pub pool_registry: Account<'info, PoolRegistry>,
+
+    /// CHECK: Whirlpool's position PDA for `position_mint`, created by Orca
+    #[account(
+        mut,
+        seeds = [b"position", position_mint.key().as_ref()],
+        bump,
+        seeds::program = orca_whirlpool::ID,
+    )]
+    pub position: UncheckedAccount<'info>,
+
+    /// Fresh keypair; Orca creates the position NFT mint
+    #[account(mut)]
+    pub position_mint: Signer<'info>,
+
+    /// CHECK: the treasury's associated token account for the position NFT,
+    /// created by Orca
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &treasury.key(),
+            &position_mint.key(),
+            &token_2022_program.key(),
+        ),
+    )]
+    pub position_token_account: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.operator
+    )]
+    pub authority: Signer<'info>,
+
+    /// `Operator` account of `authority`; its scope must cover the operation
+    #[account(
+        seeds = [b"operator", authority.key().as_ref()],
+        bump = operator_stats.bump,
+        constraint = operator_stats.active @ ErrorCode::UnauthorizedOperator,
+    )]
+    pub operator_stats: Account<'info, Operator>,
+
+    #[account(
+        mut,
+        seeds = [b"key_activity", authority.key().as_ref()],
+        bump = authority_activity.bump,
+    )]
+    pub authority_activity: Option<Account<'info, KeyActivity>>,
+
+    /// CHECK: address checked
+    #[account(address = orca_whirlpool::metadata_update_auth::ID)]
+    pub metadata_update_auth: UncheckedAccount<'info>,
+
+    /// CHECK: address checked
+    #[account(address = orca_whirlpool::ID)]
+    pub whirlpool_program: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+    pub token_2022_program: Program<'info, Token2022>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WhirlpoolLiquidity<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+        has_one = token_mint,
+    )]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    /// CHECK: must be the pool the position is in
+    #[account(
+        mut,
+        address = liquidity_position.whirlpool @ ErrorCode::WhirlpoolPositionMismatch,
+    )]
+    pub whirlpool: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"liquidity_position", liquidity_position.position_mint.as_ref()],
+        bump = liquidity_position.bump,
+    )]
+    pub liquidity_position: Account<'info, LiquidityPosition>,
+
+    /// CHECK: Whirlpool's position account, validated by Orca against the NFT
+    #[account(
+        mut,
+        address = liquidity_position.position @ ErrorCode::WhirlpoolPositionMismatch,
+    )]
+    pub position: UncheckedAccount<'info>,
+
+    /// Treasury-held position NFT
+    #[account(
+        token::token_program = token_2022_program,
+        constraint = position_token_account.mint == liquidity_position.position_mint
+            && position_token_account.amount == 1 @ ErrorCode::WhirlpoolPositionMismatch,
+        constraint = position_token_account.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub position_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// CHECK: validated by Orca against `whirlpool`
+    #[account(mut)]
+    pub tick_array_lower: UncheckedAccount<'info>,
+
+    /// CHECK: validated by Orca against `whirlpool`
+    #[account(mut)]
+    pub tick_array_upper: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::token_program = token_program,
+        constraint = treasury_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = treasury_usdc.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::token_program = token_2022_program,
+        constraint = treasury_token.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_token: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// Pool's USDC vault
+    #[account(
+        mut,
+        token::token_program = token_program,
+        constraint = pool_usdc_vault.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+    )]
+    pub pool_usdc_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// Pool's token vault
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::token_program = token_2022_program,
+    )]
+    pub pool_token_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        seeds = [b"pool_registry"],
+        bump = pool_registry.bump,
+        constraint = pool_registry.find(&pool_usdc_vault.key(), &pool_token_vault.key()).is_some()
+            @ ErrorCode::UnregisteredPool,
+    )]
+    pub pool_registry: Account<'info, PoolRegistry>,
+
+    #[account(
+        address = treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        mint::token_program = token_program,
+    )]
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(mint::token_program = token_2022_program)]
+    pub token_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    /// CHECK: must be `treasury.usdc_usd_oracle`; required to increase liquidity once
+    /// the depeg guard is set
+    pub usdc_usd_oracle: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        constraint = authority.key() == treasury.operator
+    )]
+    pub authority: Signer<'info>,
+
+    /// `Operator` account of `authority`; its scope must cover the operation
+    #[account(
+        seeds = [b"operator", authority.key().as_ref()],
+        bump = operator_stats.bump,
+        constraint = operator_stats.active @ ErrorCode::UnauthorizedOperator,
+    )]
+    pub operator_stats: Account<'info, Operator>,
+
+    #[account(
+        mut,
+        seeds = [b"key_activity", authority.key().as_ref()],
+        bump = authority_activity.bump,
+    )]
+    pub authority_activity: Option<Account<'info, KeyActivity>>,
+
+    /// Required when `usdc_amount_max` exceeds `treasury.cosign_threshold_usdc`
+    pub co_signer: Option<Signer<'info>>,
+
+    /// `Operator` account of `co_signer` when a second registered operator co-signs
+    pub co_signer_operator: Option<Account<'info, Operator>>,
+
+    /// CHECK: address checked
+    #[account(address = orca_whirlpool::memo::ID)]
+    pub memo_program: UncheckedAccount<'info>,
+
+    /// CHECK: address checked
+    #[account(address = orca_whirlpool::ID)]
+    pub whirlpool_program: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+impl<'info> WhirlpoolLiquidity<'info> {
+    fn position_accounts(
+        &self,
+    ) -> (
+        orca_whirlpool::PositionAccounts<'info>,
+        orca_whirlpool::TickArrays<'info>,
+    ) {
+        let (
+            (token_owner_account_a, token_vault_a, token_mint_a, token_program_a),
+            (token_owner_account_b, token_vault_b, token_mint_b, token_program_b),
+        ) = orca_whirlpool::order_pair(
+            &self.treasury.usdc_mint,
+            &self.treasury.token_mint,
+            (
+                self.treasury_usdc.to_account_info(),
+                self.pool_usdc_vault.to_account_info(),
+                self.usdc_mint.to_account_info(),
+                self.token_program.to_account_info(),
+            ),
+            (
+                self.treasury_token.to_account_info(),
+                self.pool_token_vault.to_account_info(),
+                self.token_mint.to_account_info(),
+                self.token_2022_program.to_account_info(),
+            ),
+        );
+        let position = orca_whirlpool::PositionAccounts {
+            whirlpool: self.whirlpool.to_account_info(),
+            position_authority: self.treasury.to_account_info(),
+            position: self.position.to_account_info(),
+            position_token_account: self.position_token_account.to_account_info(),
+            token_mint_a,
+            token_mint_b,
+            token_owner_account_a,
+            token_owner_account_b,
+            token_vault_a,
+            token_vault_b,
+            token_program_a,
+            token_program_b,
+            memo_program: self.memo_program.to_account_info(),
+            program: self.whirlpool_program.to_account_info(),
+        };
+        let ticks = orca_whirlpool::TickArrays {
+            lower: self.tick_array_lower.to_account_info(),
+            upper: self.tick_array_upper.to_account_info(),
+        };
+        (position, ticks)
+    }
+}
+
+#[derive(Accounts)]
+pub struct CollectWhirlpoolFees<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+        has_one = token_mint,
+    )]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    /// CHECK: must be the pool the position is in
+    #[account(
+        mut,
+        address = liquidity_position.whirlpool @ ErrorCode::WhirlpoolPositionMismatch,
+    )]
+    pub whirlpool: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"liquidity_position", liquidity_position.position_mint.as_ref()],
+        bump = liquidity_position.bump,
+    )]
+    pub liquidity_position: Account<'info, LiquidityPosition>,
+
+    /// CHECK: Whirlpool's position account, validated by Orca against the NFT
+    #[account(
+        mut,
+        address = liquidity_position.position @ ErrorCode::WhirlpoolPositionMismatch,
+    )]
+    pub position: UncheckedAccount<'info>,
+
+    /// Treasury-held position NFT
+    #[account(
+        token::token_program = token_2022_program,
+        constraint = position_token_account.mint == liquidity_position.position_mint
+            && position_token_account.amount == 1 @ ErrorCode::WhirlpoolPositionMismatch,
+        constraint = position_token_account.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub position_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        mut,
+        token::token_program = token_program,
+        constraint = treasury_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = treasury_usdc.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::token_program = token_2022_program,
+        constraint = treasury_token.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_token: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// Pool's USDC vault
+    #[account(
+        mut,
+        token::token_program = token_program,
+        constraint = pool_usdc_vault.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+    )]
+    pub pool_usdc_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// Pool's token vault
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::token_program = token_2022_program,
+    )]
+    pub pool_token_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        address = treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        mint::token_program = token_program,
+    )]
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(mint::token_program = token_2022_program)]
+    pub token_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    pub caller: Signer<'info>,
+
+    /// CHECK: address checked
+    #[account(address = orca_whirlpool::memo::ID)]
+    pub memo_program: UncheckedAccount<'info>,
+
+    /// CHECK: address checked
+    #[account(address = orca_whirlpool::ID)]
+    pub whirlpool_program: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+impl<'info> CollectWhirlpoolFees<'info> {
+    fn position_accounts(&self) -> orca_whirlpool::PositionAccounts<'info> {
+        let (
+            (token_owner_account_a, token_vault_a, token_mint_a, token_program_a),
+            (token_owner_account_b, token_vault_b, token_mint_b, token_program_b),
+        ) = orca_whirlpool::order_pair(
+            &self.treasury.usdc_mint,
+            &self.treasury.token_mint,
+            (
+                self.treasury_usdc.to_account_info(),
+                self.pool_usdc_vault.to_account_info(),
+                self.usdc_mint.to_account_info(),
+                self.token_program.to_account_info(),
+            ),
+            (
+                self.treasury_token.to_account_info(),
+                self.pool_token_vault.to_account_info(),
+                self.token_mint.to_account_info(),
+                self.token_2022_program.to_account_info(),
+            ),
+        );
+        orca_whirlpool::PositionAccounts {
+            whirlpool: self.whirlpool.to_account_info(),
+            position_authority: self.treasury.to_account_info(),
+            position: self.position.to_account_info(),
+            position_token_account: self.position_token_account.to_account_info(),
+            token_mint_a,
+            token_mint_b,
+            token_owner_account_a,
+            token_owner_account_b,
+            token_vault_a,
+            token_vault_b,
+            token_program_a,
+            token_program_b,
+            memo_program: self.memo_program.to_account_info(),
+            program: self.whirlpool_program.to_account_info(),
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct OpenMeteoraPosition<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    /// CHECK: must own a registered DLMM pair's reserves; Meteora validates the rest
+    pub lb_pair: UncheckedAccount<'info>,
+
+    /// Pair's USDC reserve
+    #[account(
+        token::token_program = token_program,
+        constraint = reserve_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = reserve_usdc.owner == lb_pair.key() @ ErrorCode::UnregisteredPool,
+    )]
+    pub reserve_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// Pair's token reserve, identifying the pair in the registry
+    #[account(
+        token::mint = treasury.token_mint,
+        constraint = reserve_token.owner == lb_pair.key() @ ErrorCode::UnregisteredPool,
+    )]
+    pub reserve_token: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        seeds = [b"pool_registry"],
+        bump = pool_registry.bump,
+    )]
+    pub pool_registry: Account<'info, PoolRegistry>,
+
+    /// Fresh keypair; Meteora creates the position account
+    #[account(mut)]
+    pub position: Signer<'info>,
+
+    /// CHECK: must be `treasury.usdc_usd_oracle`; required once the depeg guard is set
+    pub usdc_usd_oracle: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.operator
+    )]
+    pub authority: Signer<'info>,
+
+    /// `Operator` account of `authority`; its scope must cover the operation
+    #[account(
+        seeds = [b"operator", authority.key().as_ref()],
+        bump = operator_stats.bump,
+        constraint = operator_stats.active @ ErrorCode::UnauthorizedOperator,
+    )]
+    pub operator_stats: Account<'info, Operator>,
+
+    #[account(
+        mut,
+        seeds = [b"key_activity", authority.key().as_ref()],
+        bump = authority_activity.bump,
+    )]
+    pub authority_activity: Option<Account<'info, KeyActivity>>,
+
+    /// CHECK: Meteora's event authority PDA
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+        seeds::program = meteora_dlmm::ID,
+    )]
+    pub event_authority: UncheckedAccount<'info>,
+
+    /// CHECK: address checked
+    #[account(address = meteora_dlmm::ID)]
+    pub meteora_program: UncheckedAccount<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct AddLiquidityMeteora<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    /// CHECK: must be the pair the treasury's position is in
+    #[account(
+        mut,
+        constraint = lb_pair.key() == treasury.dlmm_lb_pair
+            && treasury.dlmm_position != Pubkey::default() @ ErrorCode::DlmmPositionNotOpen,
+    )]
+    pub lb_pair: UncheckedAccount<'info>,
+
+    /// CHECK: must be the treasury's position
+    #[account(
+        mut,
+        address = treasury.dlmm_position @ ErrorCode::DlmmPositionNotOpen,
+    )]
+    pub position: UncheckedAccount<'info>,
+
+    /// Pair's USDC reserve
+    #[account(
+        mut,
+        token::token_program = token_program,
+        constraint = reserve_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = reserve_usdc.owner == lb_pair.key() @ ErrorCode::UnregisteredPool,
+    )]
+    pub reserve_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// Pair's token reserve, identifying the pair in the registry
+    #[account(
+        token::mint = treasury.token_mint,
+        constraint = reserve_token.owner == lb_pair.key() @ ErrorCode::UnregisteredPool,
+    )]
+    pub reserve_token: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        seeds = [b"pool_registry"],
+        bump = pool_registry.bump,
+    )]
+    pub pool_registry: Account<'info, PoolRegistry>,
+
+    /// CHECK: validated by Meteora against `lb_pair`
+    #[account(mut)]
+    pub bin_array_lower: UncheckedAccount<'info>,
+
+    /// CHECK: validated by Meteora against `lb_pair`
+    #[account(mut)]
+    pub bin_array_upper: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::token_program = token_program,
+        constraint = treasury_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = treasury_usdc.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        address = treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        mint::token_program = token_program,
+    )]
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    /// CHECK: must be `treasury.usdc_usd_oracle`; required once the depeg guard is set
+    pub usdc_usd_oracle: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        constraint = authority.key() == treasury.operator
+    )]
+    pub authority: Signer<'info>,
+
+    /// `Operator` account of `authority`; its scope must cover the operation
+    #[account(
+        seeds = [b"operator", authority.key().as_ref()],
+        bump = operator_stats.bump,
+        constraint = operator_stats.active @ ErrorCode::UnauthorizedOperator,
+    )]
+    pub operator_stats: Account<'info, Operator>,
+
+    #[account(
+        mut,
+        seeds = [b"key_activity", authority.key().as_ref()],
+        bump = authority_activity.bump,
+    )]
+    pub authority_activity: Option<Account<'info, KeyActivity>>,
+
+    /// Required when `usdc_amount` exceeds `treasury.cosign_threshold_usdc`
+    pub co_signer: Option<Signer<'info>>,
+
+    /// `Operator` account of `co_signer` when a second registered operator co-signs
+    pub co_signer_operator: Option<Account<'info, Operator>>,
+
+    /// CHECK: Meteora's event authority PDA
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+        seeds::program = meteora_dlmm::ID,
+    )]
+    pub event_authority: UncheckedAccount<'info>,
+
+    /// CHECK: address checked
+    #[account(address = meteora_dlmm::ID)]
+    pub meteora_program: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+}
+
+impl<'info> AddLiquidityMeteora<'info> {
+    fn deposit_accounts(&self) -> meteora_dlmm::DepositAccounts<'info> {
+        meteora_dlmm::DepositAccounts {
+            position: self.position.to_account_info(),
+            lb_pair: self.lb_pair.to_account_info(),
+            user_token: self.treasury_usdc.to_account_info(),
+            reserve: self.reserve_usdc.to_account_info(),
+            token_mint: self.usdc_mint.to_account_info(),
+            bin_array_lower: self.bin_array_lower.to_account_info(),
+            bin_array_upper: self.bin_array_upper.to_account_info(),
+            sender: self.treasury.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            event_authority: self.event_authority.to_account_info(),
+            program: self.meteora_program.to_account_info(),
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct OpenLpPosition<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + LpPosition::INIT_SPACE,
+        seeds = [b"lp_position", pool_usdc.key().as_ref(), pool_token.key().as_ref()],
+        bump
+    )]
+    pub lp_position: Account<'info, LpPosition>,
+
+    #[account(
+        token::token_program = token_program,
+        constraint = pool_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+    )]
+    pub pool_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        constraint = pool_token.mint == treasury.token_mint @ ErrorCode::InvalidTokenMint,
+    )]
+    pub pool_token: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        seeds = [b"pool_registry"],
+        bump = pool_registry.bump,
+    )]
+    pub pool_registry: Account<'info, PoolRegistry>,
+
+    pub lp_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.operator
+    )]
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveLiquidity<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"lp_position",
+            lp_position.pool_usdc.as_ref(),
+            lp_position.pool_token.as_ref(),
+        ],
+        bump = lp_position.bump,
+    )]
+    pub lp_position: Account<'info, LpPosition>,
+
+    #[account(
+        mut,
+        constraint = treasury_lp.mint == lp_position.lp_mint @ ErrorCode::LpPositionMismatch,
+        constraint = treasury_lp.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_lp: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = treasury_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = treasury_usdc.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = treasury_token.mint == treasury.token_mint @ ErrorCode::InvalidTokenMint,
+        constraint = treasury_token.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_token: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"key_activity", authority.key().as_ref()],
+        bump = authority_activity.bump,
+    )]
+    pub authority_activity: Option<Account<'info, KeyActivity>>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateTreasury<'info> {
+    /// CHECK: a Treasury under an older layout, which `Account` cannot deserialize;
+    /// `migrate_treasury` checks its discriminator and admin
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump,
+        owner = crate::ID @ ErrorCode::InvalidTreasuryAccount,
+    )]
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CollectLpFees<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"lp_position",
+            lp_position.pool_usdc.as_ref(),
+            lp_position.pool_token.as_ref(),
+        ],
+        bump = lp_position.bump,
+    )]
+    pub lp_position: Account<'info, LpPosition>,
+
+    #[account(
+        mut,
+        constraint = treasury_lp.mint == lp_position.lp_mint @ ErrorCode::LpPositionMismatch,
+        constraint = treasury_lp.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_lp: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = treasury_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = treasury_usdc.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = treasury_token.mint == treasury.token_mint @ ErrorCode::InvalidTokenMint,
+        constraint = treasury_token.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_token: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// Pool reserves and LP supply, read after the withdrawal to value what is left
+    #[account(address = lp_position.pool_usdc @ ErrorCode::LpPositionMismatch)]
+    pub pool_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(address = lp_position.pool_token @ ErrorCode::LpPositionMismatch)]
+    pub pool_token: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(address = lp_position.lp_mint @ ErrorCode::LpPositionMismatch)]
+    pub lp_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        constraint = authority.key() == treasury.operator
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"key_activity", authority.key().as_ref()],
+        bump = authority_activity.bump,
+    )]
+    pub authority_activity: Option<Account<'info, KeyActivity>>,
+}
+
+#[derive(Accounts)]
+pub struct LockLp<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + LpLock::INIT_SPACE,
+        seeds = [b"lp_lock", treasury.lp_lock_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub lp_lock: Account<'info, LpLock>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"lp_lock_vault", lp_lock.key().as_ref()],
+        bump,
+        token::mint = lp_mint,
+        token::authority = lp_lock,
+        token::token_program = token_program,
+    )]
+    pub lp_lock_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(mint::token_program = token_program)]
+    pub lp_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        mut,
+        token::mint = lp_mint,
+        token::authority = treasury,
+        token::token_program = token_program,
+    )]
+    pub treasury_lp: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BurnLp<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        mint::token_program = token_program,
+    )]
+    pub lp_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        mut,
+        token::mint = lp_mint,
+        token::authority = treasury,
+        token::token_program = token_program,
+    )]
+    pub treasury_lp: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseLp<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"lp_lock", lp_lock.index.to_le_bytes().as_ref()],
+        bump = lp_lock.bump,
+    )]
+    pub lp_lock: Account<'info, LpLock>,
+
+    #[account(
+        mut,
+        address = lp_lock.vault,
+    )]
+    pub lp_lock_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        address = lp_lock.lp_mint,
+        mint::token_program = token_program,
+    )]
+    pub lp_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        mut,
+        token::mint = lp_mint,
+        token::authority = treasury,
+        token::token_program = token_program,
+    )]
+    pub treasury_lp: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeStrategyAdapter<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + StrategyAdapter::INIT_SPACE,
+        seeds = [b"strategy_adapter", adapter_program.key().as_ref()],
+        bump
+    )]
+    pub adapter: Account<'info, StrategyAdapter>,
+
+    /// CHECK: any executable program; it is trusted only up to the cap
+    #[account(executable)]
+    pub adapter_program: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateStrategyAdapter<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"strategy_adapter", adapter.program.as_ref()],
+        bump = adapter.bump,
+    )]
+    pub adapter: Account<'info, StrategyAdapter>,
+
+    #[account(
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveStrategyAdapter<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"strategy_adapter", adapter.program.as_ref()],
+        bump = adapter.bump,
+    )]
+    pub adapter: Account<'info, StrategyAdapter>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct StrategyAdapterFunds<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"strategy_adapter", adapter.program.as_ref()],
+        bump = adapter.bump,
+    )]
+    pub adapter: Account<'info, StrategyAdapter>,
+
+    /// CHECK: the whitelisted adapter program
+    #[account(address = adapter.program)]
+    pub adapter_program: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::token_program = token_program,
+        constraint = treasury_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = treasury_usdc.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(address = treasury.usdc_mint @ ErrorCode::WrongUsdcMint)]
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        constraint = authority.key() == treasury.authority
+    )]
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+}
+
+impl<'info> StrategyAdapterFunds<'info> {
+    fn adapter_accounts<'a>(
+        &self,
+        extra: &'a [AccountInfo<'info>],
+    ) -> strategy::AdapterAccounts<'a, 'info> {
+        strategy::AdapterAccounts {
+            program: self.adapter_program.to_account_info(),
+            treasury: self.treasury.to_account_info(),
+            treasury_usdc: self.treasury_usdc.to_account_info(),
+            usdc_mint: self.usdc_mint.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            extra,
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct ReportStrategyValue<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"strategy_adapter", adapter.program.as_ref()],
+        bump = adapter.bump,
+    )]
+    pub adapter: Account<'info, StrategyAdapter>,
+
+    /// CHECK: the whitelisted adapter program
+    #[account(address = adapter.program)]
+    pub adapter_program: UncheckedAccount<'info>,
+
+    #[account(
+        token::token_program = token_program,
+        constraint = treasury_usdc.mint == treasury.usdc_mint @ ErrorCode::WrongUsdcMint,
+        constraint = treasury_usdc.owner == treasury.key() @ ErrorCode::VaultNotOwnedByTreasury,
+    )]
+    pub treasury_usdc: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(address = treasury.usdc_mint @ ErrorCode::WrongUsdcMint)]
+    pub usdc_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+}
+
+// ============================================================================
+// State
+// ============================================================================
+
+#[account]
+#[derive(InitSpace)]
+pub struct Treasury {
+    /// Admin role: configuration and role assignment
+    pub authority: Pubkey,
+    /// USDCBALL mint managed by this treasury
+    pub token_mint: Pubkey,
+    /// USDC mint checked at initialize
+    pub usdc_mint: Pubkey,
+    pub buyback_allocation_bps: u16,
+    pub liquidity_allocation_bps: u16,
+    pub reserve_allocation_bps: u16,
+    pub max_usdc_per_cycle: u64,
+    pub cooldown_seconds: i64,
+    pub slippage_bps: u16,
+    pub total_sol_collected: u64,
+    pub total_usdc_converted: u64,
+    pub total_buybacks_usdc: u64,
+    pub total_liquidity_usdc: u64,
+    pub total_tokens_burned: u64,
+    /// USDCBALL swept from Token-2022 transfer fees and burned
+    pub total_transfer_fees_harvested: u64,
+    /// Time of the most recent buyback or liquidity operation
+    pub last_operation_timestamp: i64,
+    /// Bitmask of paused operation types, indexed by `PauseFlag`;
+    /// `emergency_pause` sets every bit
+    pub pause_flags: u8,
+    /// Bitmask of enabled instructions, indexed by `GatedInstruction`
+    pub instruction_enabled: u64,
+    /// Share of the reserve allocation distributed to holders, 0 = dividends off
+    pub dividend_share_bps: u16,
+    /// Index of the next dividend distribution
+    pub dividend_epoch: u64,
+    pub total_dividends_usdc: u64,
+    /// Second key that must co-sign operations above `cosign_threshold_usdc`
+    pub co_signer: Pubkey,
+    /// USDC amount above which a co-signature is required, 0 = disabled
+    pub cosign_threshold_usdc: u64,
+    /// Warm-up after `resume`; 0 means a fresh `cooldown_seconds`
+    pub post_resume_delay_seconds: i64,
+    /// No buyback or liquidity operation may run before this timestamp
+    pub warmup_until: i64,
+    /// Largest single bug bounty payout
+    pub bounty_max_payout_usdc: u64,
+    /// Delay between proposing and executing a bounty payout
+    pub bounty_timelock_seconds: i64,
+    /// Index of the next bounty payout
+    pub bounty_payout_count: u64,
+    pub total_bounty_funded_usdc: u64,
+    pub total_bounty_paid_usdc: u64,
+    /// Idle time with available budget before the watchdog flags the treasury, 0 = off
+    pub watchdog_threshold_seconds: i64,
+    /// Raised by `ping_watchdog`, cleared by the next buyback or liquidity operation
+    pub stalled: bool,
+    /// Slots an escrowed operation has to settle before anyone can refund it
+    pub settlement_window_slots: u64,
+    /// Index of the next escrowed operation
+    pub operation_count: u64,
+    /// Lamports that must accumulate before a SOL -> USDC conversion may run
+    pub min_sol_to_convert: u64,
+    /// Lamports recorded since the last conversion
+    pub sol_pending_conversion: u64,
+    /// SOL/USD price feed used to value fees; unset disables USD accounting
+    pub sol_usd_oracle: Pubkey,
+    pub oracle_max_age_seconds: i64,
+    /// Lifetime fees in micro-USD, valued at the price when each fee was recorded
+    pub total_sol_collected_usd: u64,
+    pub epoch_duration_seconds: i64,
+    pub current_epoch: u64,
+    pub epoch_start_timestamp: i64,
+    /// Tokens the treasury commits to burn each epoch
+    pub burn_target_per_epoch: u64,
+    /// Shortfall of earlier epochs added to the current epoch's target
+    pub burn_carryover: u64,
+    pub epoch_tokens_burned: u64,
+    /// Must co-sign tier-2 (market defense) reserve drawdowns
+    pub guardian: Pubkey,
+    /// Governance signer (e.g. a DAO governance PDA) required for tier-3 drawdowns
+    pub governance: Pubkey,
+    /// Tier-1 (operations) allowance, streamed linearly per 30 days
+    pub reserve_ops_monthly_limit_usdc: u64,
+    pub reserve_ops_stream_start: i64,
+    pub total_reserve_ops_usdc: u64,
+    /// Reserve USDC drawn across all tiers
+    pub total_reserve_spent_usdc: u64,
+    /// Paid to whoever rolls the epoch over
+    pub epoch_crank_tip_lamports: u64,
+    /// USDCBALL/USD Pyth feed checked by conditional buybacks and the buyback price guard
+    pub token_usd_oracle: Pubkey,
+    pub conditional_buyback_count: u64,
+    /// Successor proposed to take over the treasury vaults; unset when none is pending
+    pub pending_vault_successor: Pubkey,
+    pub vault_migration_eta: i64,
+    pub campaign_count: u64,
+    /// USDC amount above which operations also emit `LargeOperationAlert` (0 = off)
+    pub large_operation_threshold: u64,
+    /// USDC/USD price feed guarding conversions and liquidity adds (default = off)
+    pub usdc_usd_oracle: Pubkey,
+    pub depeg_threshold_bps: u16,
+    /// Max value of lent USDCBALL as a share of a market maker's USDC collateral
+    pub mm_ltv_bps: u16,
+    /// Loan-to-value at which a market maker loan can be liquidated
+    pub mm_liquidation_bps: u16,
+    /// USDCBALL currently lent out to market makers
+    pub total_tokens_lent: u64,
+    /// Hard cap on USDC leaving the treasury over a rolling 7 days (0 = uncapped)
+    pub weekly_outflow_cap_usdc: u64,
+    /// Raised cap waiting out `OUTFLOW_CAP_TIMELOCK_SECONDS`
+    pub pending_weekly_outflow_cap_usdc: u64,
+    pub weekly_outflow_cap_eta: i64,
+    /// Day number (unix time / 1 day) of the most recent outflow bucket
+    pub outflow_day: i64,
+    /// USDC spent per day, indexed by day number modulo the window
+    pub daily_outflows_usdc: [u64; OUTFLOW_WINDOW_DAYS],
+    /// Switchboard USDCBALL/USD feed used when the Pyth feed is stale or missing
+    pub token_switchboard_feed: Pubkey,
+    pub switchboard_max_age_seconds: i64,
+    /// Max deviation of the pool spot price from its TWAP before buybacks and
+    /// liquidity adds are refused (0 = no check, TWAP accounts optional)
+    pub twap_max_deviation_bps: u16,
+    /// Mint and freeze authority the USDCBALL mint must keep for buybacks to run
+    pub expected_mint_authority: Option<Pubkey>,
+    pub expected_freeze_authority: Option<Pubkey>,
+    pub mint_config_verified_at: i64,
+    /// Buybacks settling worse than the reference price by more than this count as
+    /// degraded; `degraded_execution_limit` of them pause the treasury (0 = off)
+    pub settlement_max_deviation_bps: u16,
+    pub degraded_execution_limit: u16,
+    /// Degraded executions since the last resume
+    pub degraded_executions: u16,
+    /// Share of each `crank_buyback` paid to the caller, from the buyback allocation
+    pub keeper_reward_bps: u16,
+    /// Maximum keeper reward per crank (0 = uncapped)
+    pub keeper_reward_cap_usdc: u64,
+    /// Operator role: buybacks, burns, liquidity and conversions
+    pub operator: Pubkey,
+    /// Pauser role: may only pause (the admin can pause too)
+    pub pauser: Pubkey,
+    /// Squads v4 multisig whose vault `vault_index` is the admin, once bound
+    pub squads_multisig: Pubkey,
+    pub squads_vault_index: u8,
+    /// Delay between `propose_config` and `execute_config`; zero lets
+    /// `update_config` apply changes immediately
+    pub config_delay_seconds: i64,
+    /// Realms governance that is the admin, once bound; `update_allocations`
+    /// is only callable through it
+    pub realms_governance: Pubkey,
+    /// Index of the next liquidity bootstrapping pool
+    pub lbp_count: u64,
+    /// A single buyback realizing a price this far above the reference pauses
+    /// the treasury at once (0 = off)
+    pub circuit_breaker_deviation_bps: u16,
+    /// How the allocation split rounds converted USDC into buckets
+    pub rounding_policy: RoundingPolicy,
+    /// Hard cap on buyback + liquidity USDC over a rolling 24 hours (0 = uncapped)
+    pub daily_volume_cap_usdc: u64,
+    /// Raised cap waiting out `OUTFLOW_CAP_TIMELOCK_SECONDS`
+    pub pending_daily_volume_cap_usdc: u64,
+    pub daily_volume_cap_eta: i64,
+    /// Hour number (unix time / 1 hour) of the most recent volume bucket
+    pub volume_hour: i64,
+    /// Buyback + liquidity USDC per hour, indexed by hour number modulo the window
+    pub hourly_volume_usdc: [u64; VOLUME_WINDOW_HOURS],
+    /// Time of the most recent buyback, which starts the buyback cooldown
+    pub last_buyback_timestamp: i64,
+    /// Time of the most recent liquidity operation, which starts the liquidity cooldown
+    pub last_liquidity_timestamp: i64,
+    /// Cooldown between liquidity operations; 0 falls back to `cooldown_seconds`
+    pub liquidity_cooldown_seconds: i64,
+    /// Length of a budget epoch; 0 runs the allocation budgets against lifetime totals
+    pub budget_epoch_duration_seconds: i64,
+    /// Index of the open budget epoch, the seed of its `Epoch` record once rolled
+    pub budget_epoch: u64,
+    pub budget_epoch_start: i64,
+    /// Lifetime totals when the open budget epoch started; budgets only count what
+    /// was converted and spent since
+    pub epoch_base_usdc_converted: u64,
+    pub epoch_base_buybacks_usdc: u64,
+    pub epoch_base_liquidity_usdc: u64,
+    pub epoch_base_reserve_usdc: u64,
+    /// LP tokens the treasury has burned; that liquidity can never be withdrawn
+    pub total_lp_burned: u64,
+    /// Wait between `queue_reserve_withdrawal` and `withdraw_reserve`
+    pub reserve_withdrawal_delay_seconds: i64,
+    /// Reserve withdrawals queued so far; seeds the next `ReserveWithdrawal`
+    pub reserve_withdrawal_count: u64,
+    /// Kamino reserve idle reserve USDC is lent into; default while unset
+    pub kamino_reserve: Pubkey,
+    /// kTokens the treasury holds in `kamino_reserve`
+    pub kamino_collateral_amount: u64,
+    /// Reserve USDC deposited into Kamino and not yet redeemed, excluding interest
+    pub kamino_deposited_usdc: u64,
+    /// Venue reserve lending deposits go to; only one is active at a time
+    pub lending_venue: LendingVenue,
+    /// MarginFi bank idle reserve USDC is lent into; default while unset
+    pub marginfi_bank: Pubkey,
+    /// MarginFi account the treasury owns; default until `init_marginfi_account`
+    pub marginfi_account: Pubkey,
+    /// Reserve USDC deposited into MarginFi and not yet withdrawn, excluding interest
+    pub marginfi_deposited_usdc: u64,
+    /// When the circuit breaker tripped, while the guardian has not acknowledged it;
+    /// zero otherwise
+    pub circuit_breaker_tripped_at: i64,
+    /// `proposed_at` of the last config proposal the guardian reviewed
+    pub last_reviewed_proposal_at: i64,
+    /// Launch mode lasts until `current_epoch` reaches this; zero when the
+    /// treasury launched without it
+    pub launch_mode_until_epoch: u64,
+    /// Solend reserve idle reserve USDC is lent into; default while unset
+    pub solend_reserve: Pubkey,
+    /// cTokens the treasury holds in `solend_reserve`
+    pub solend_collateral_amount: u64,
+    /// Reserve USDC deposited into Solend and not yet redeemed, excluding interest
+    pub solend_deposited_usdc: u64,
+    /// Token supply at the last `snapshot_token_supply`
+    pub supply_snapshot: u64,
+    /// `total_tokens_burned` at the last `snapshot_token_supply`
+    pub supply_snapshot_burned: u64,
+    /// Slot of the last `snapshot_token_supply`; zero before the first
+    pub supply_snapshot_slot: u64,
+    /// Burns made outside the program and credited by `register_external_burn`, lifetime
+    pub total_external_burned: u64,
+    /// Lending interest credited by the `harvest_*_yield` instructions, lifetime
+    pub total_yield_harvested_usdc: u64,
+    /// Tick range the next Raydium CLMM position is opened over
+    pub clmm_tick_lower: i32,
+    pub clmm_tick_upper: i32,
+    /// Pool of the treasury's Raydium CLMM position; default while none is open
+    pub clmm_pool: Pubkey,
+    /// Mint of the position NFT, held in the treasury PDA's token account
+    pub clmm_position_mint: Pubkey,
+    /// Liquidity the treasury added to its CLMM position
+    pub clmm_liquidity: u128,
+    /// Tokens burned between burn milestones; zero while off
+    pub burn_milestone_interval_tokens: u64,
+    /// USDC spent on buybacks between buyback milestones; zero while off
+    pub buyback_milestone_interval_usdc: u64,
+    /// Bins the next Meteora DLMM position spans, as offsets from the active bin
+    /// when it is opened
+    pub dlmm_bin_offset_lower: i32,
+    pub dlmm_bin_offset_upper: i32,
+    /// Pair of the treasury's DLMM position; default while none is open
+    pub dlmm_lb_pair: Pubkey,
+    pub dlmm_position: Pubkey,
+    /// First and last bin of the open DLMM position
+    pub dlmm_lower_bin_id: i32,
+    pub dlmm_upper_bin_id: i32,
+    /// USDC deposited into the DLMM position
+    pub dlmm_usdc_deposited: u64,
+    /// USDC fees claimed from constant-product LP positions by `collect_lp_fees`
+    pub total_lp_fees_earned: u64,
+    /// `total_lp_fees_earned` when the current budget epoch started
+    pub epoch_base_lp_fees_earned: u64,
+    /// LP locks made so far; seeds the next `LpLock`
+    pub lp_lock_count: u64,
+    /// Reserve USDC deposited across strategy adapters, excluding gains
+    pub strategy_deposited_usdc: u64,
+    /// Tokens delivered by buybacks, before any burn
+    pub total_buyback_tokens_received: u64,
+    /// Lifetime totals when the current burn epoch opened, from which its
+    /// conversion funnel is measured
+    pub funnel_base_sol_collected: u64,
+    pub funnel_base_usdc_converted: u64,
+    pub funnel_base_buybacks_usdc: u64,
+    pub funnel_base_tokens_received: u64,
+    /// `total_dividends_usdc` when the current budget epoch started
+    pub epoch_base_dividends_usdc: u64,
+    pub bump: u8,
+}
+
+impl Treasury {
+    /// Read the `u64` at `offset` (after the discriminator) of a serialized Treasury
+    pub fn read_u64_at(data: &[u8], offset: usize) -> Result<u64> {
+        data.get(8 + offset..16 + offset)
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(u64::from_le_bytes)
+            .ok_or_else(|| ErrorCode::InvalidTreasuryAccount.into())
+    }
+
+    /// Read the `Pubkey` at `offset` (after the discriminator) of a serialized Treasury
+    pub fn read_pubkey_at(data: &[u8], offset: usize) -> Result<Pubkey> {
+        data.get(8 + offset..40 + offset)
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .map(Pubkey::new_from_array)
+            .ok_or_else(|| ErrorCode::InvalidTreasuryAccount.into())
+    }
+
+    /// Upgrade a Treasury account written under an older layout in place. `data`
+    /// is the account grown to the current size with the old account's `old_len`
+    /// bytes at its start. Old fields keep their values, appended fields take their
+    /// `initialize` defaults and `bump` moves to the end.
+    pub fn upgrade_layout(data: &mut [u8], old_len: usize) -> Result<()> {
+        let new_len = 8 + Treasury::INIT_SPACE;
+        require!(
+            data.len() == new_len
+                && (TREASURY_MIN_MIGRATABLE_SIZE..new_len).contains(&old_len)
+                && data[..8] == Treasury::DISCRIMINATOR,
+            ErrorCode::UnknownTreasuryLayout
+        );
+        // `bump` ends the old full-size layout, so fields from its offset on are new
+        let old_bump = old_len - 9;
+        let appended = |offset: usize| offset >= old_bump;
+
+        // Each `None` option serializes 32 bytes short of its full size, leaving
+        // zero padding after `bump`
+        let mut serialized_len = old_bump;
+        let mut tag = TREASURY_EXPECTED_MINT_AUTHORITY_OFFSET;
+        for offset in [
+            TREASURY_EXPECTED_MINT_AUTHORITY_OFFSET,
+            TREASURY_EXPECTED_FREEZE_AUTHORITY_OFFSET,
+        ] {
+            if appended(offset) {
+                break;
+            }
+            if data[8 + tag] == 0 {
+                serialized_len -= 32;
+                tag += 1;
+            } else {
+                tag += 33;
+            }
+        }
+        let bump = data[8 + serialized_len];
+
+        // The old fields followed by zeroes read as the current layout with every
+        // appended field zeroed
+        let mut fields = data[8..8 + serialized_len].to_vec();
+        fields.resize(Treasury::INIT_SPACE, 0);
+        let mut treasury = Treasury::deserialize(&mut &fields[..])?;
+        treasury.bump = bump;
+        if appended(TREASURY_OPERATOR_OFFSET) {
+            treasury.operator = treasury.authority;
+        }
+        if appended(TREASURY_PAUSER_OFFSET) {
+            treasury.pauser = treasury.authority;
+        }
+        if appended(TREASURY_RESERVE_WITHDRAWAL_DELAY_OFFSET) {
+            treasury.reserve_withdrawal_delay_seconds = DEFAULT_RESERVE_WITHDRAWAL_DELAY_SECONDS;
+        }
+
+        data[8..].fill(0);
+        treasury.try_serialize(&mut &mut data[..])
+    }
+
+    /// Current buyback / liquidity / reserve allocation
+    pub fn allocation_split(&self) -> AllocationSplit {
+        AllocationSplit {
+            buyback_bps: self.buyback_allocation_bps,
+            liquidity_bps: self.liquidity_allocation_bps,
+            reserve_bps: self.reserve_allocation_bps,
+        }
+    }
+
+    /// USDC converted in the current budget epoch (lifetime when budget epochs are
+    /// off) split into the buyback, liquidity and reserve buckets under
+    /// `rounding_policy`
+    pub fn allocation_caps(&self) -> Result<[u64; 3]> {
+        let total = self
+            .total_usdc_converted
+            .saturating_sub(self.epoch_base_usdc_converted) as u128;
+        let bps = [
+            self.buyback_allocation_bps,
+            self.liquidity_allocation_bps,
+            self.reserve_allocation_bps,
+        ];
+        let mut caps = [0u64; 3];
+        let mut remainders = [0u128; 3];
+        for (i, bps) in bps.iter().enumerate() {
+            let share = total.checked_mul(*bps as u128).ok_or(ErrorCode::Overflow)?;
+            caps[i] = (share / 10_000) as u64;
+            remainders[i] = share % 10_000;
+        }
+
+        if self.rounding_policy == RoundingPolicy::LargestRemainder {
+            // The floors leave at most two units unassigned; hand them to the
+            // buckets with the largest remainders, earlier buckets first on ties
+            let assigned: u128 = caps.iter().map(|c| *c as u128).sum();
+            let mut order = [0usize, 1, 2];
+            order.sort_by(|a, b| remainders[*b].cmp(&remainders[*a]));
+            for i in order
+                .into_iter()
+                .take(total.saturating_sub(assigned) as usize)
+            {
+                caps[i] += 1;
+            }
+        }
+        Ok(caps)
+    }
+
+    /// Buyback allocation not yet spent, based on USDC converted in the current
+    /// budget epoch (lifetime when budget epochs are off)
+    pub fn remaining_buyback_budget(&self) -> Result<u64> {
+        let lp_fees = self
+            .total_lp_fees_earned
+            .saturating_sub(self.epoch_base_lp_fees_earned);
+        let max_buyback = self.allocation_caps()?[0].saturating_add(lp_fees);
+        let spent = self
+            .total_buybacks_usdc
+            .saturating_sub(self.epoch_base_buybacks_usdc);
+
+        Ok(max_buyback.saturating_sub(spent))
+    }
+
+    /// Liquidity allocation not yet spent, based on USDC converted in the current
+    /// budget epoch (lifetime when budget epochs are off)
+    pub fn remaining_liquidity_budget(&self) -> Result<u64> {
+        let max_liquidity = self.allocation_caps()?[1];
+        let spent = self
+            .total_liquidity_usdc
+            .saturating_sub(self.epoch_base_liquidity_usdc);
+
+        Ok(max_liquidity.saturating_sub(spent))
+    }
+
+    /// Dividend share (`dividend_share_bps`) of the reserve allocation not yet
+    /// distributed in the current budget epoch (lifetime when budget epochs are
+    /// off). Dividends also draw on `remaining_reserve_budget`.
+    pub fn remaining_dividend_budget(&self) -> Result<u64> {
+        let max_dividends = (self.allocation_caps()?[2] as u128)
+            .checked_mul(self.dividend_share_bps as u128)
+            .ok_or(ErrorCode::Overflow)?
+            / 10_000;
+        let spent = self
+            .total_dividends_usdc
+            .saturating_sub(self.epoch_base_dividends_usdc);
+
+        Ok((max_dividends as u64).saturating_sub(spent))
+    }
+
+    /// Reserve USDC committed to dividends, the bounty escrow, drawdowns, lending
+    /// deposits or strategy adapters, lifetime
+    pub fn total_reserve_committed_usdc(&self) -> u64 {
+        self.total_dividends_usdc
+            .saturating_add(self.total_bounty_funded_usdc)
+            .saturating_add(self.total_reserve_spent_usdc)
+            .saturating_add(self.total_lent_usdc())
+            .saturating_add(self.strategy_deposited_usdc)
+    }
+
+    /// Reserve USDC deposited across every lending venue, excluding interest
+    pub fn total_lent_usdc(&self) -> u64 {
+        LendingVenue::ALL
+            .iter()
+            .filter_map(|venue| venue.adapter())
+            .fold(0, |total, adapter| {
+                total.saturating_add(adapter.deposited_usdc(self))
+            })
+    }
+
+    /// Whether any reserve USDC is still lent out
+    pub fn has_open_lending_position(&self) -> bool {
+        LendingVenue::ALL
+            .iter()
+            .filter_map(|venue| venue.adapter())
+            .any(|adapter| adapter.has_position(self))
+    }
+
+    /// Deposited principal backing `collateral_amount` of the treasury's kTokens
+    pub fn kamino_principal_share(&self, collateral_amount: u64) -> u64 {
+        lending::principal_share(
+            self.kamino_deposited_usdc,
+            self.kamino_collateral_amount,
+            collateral_amount,
+        )
+    }
+
+    /// Deposited principal backing `collateral_amount` of the treasury's cTokens
+    pub fn solend_principal_share(&self, collateral_amount: u64) -> u64 {
+        lending::principal_share(
+            self.solend_deposited_usdc,
+            self.solend_collateral_amount,
+            collateral_amount,
+        )
+    }
+
+    /// Reserve allocation not yet committed to dividends, the bounty escrow,
+    /// drawdowns or lending deposits in the current budget epoch (lifetime when
+    /// budget epochs are off)
+    pub fn remaining_reserve_budget(&self) -> Result<u64> {
+        let max_reserve = self.allocation_caps()?[2];
+        let spent = self
+            .total_reserve_committed_usdc()
+            .saturating_sub(self.epoch_base_reserve_usdc);
+
+        Ok(max_reserve.saturating_sub(spent))
+    }
+
+    /// Open a new budget epoch at `now`: budgets restart from what is converted
+    /// and spent from here on
+    pub fn start_budget_epoch(&mut self, now: i64) {
+        self.budget_epoch_start = now;
+        self.epoch_base_usdc_converted = self.total_usdc_converted;
+        self.epoch_base_buybacks_usdc = self.total_buybacks_usdc;
+        self.epoch_base_liquidity_usdc = self.total_liquidity_usdc;
+        self.epoch_base_reserve_usdc = self.total_reserve_committed_usdc();
+        self.epoch_base_lp_fees_earned = self.total_lp_fees_earned;
+        self.epoch_base_dividends_usdc = self.total_dividends_usdc;
+    }
+
+    /// Tier-1 spending streamed in so far and not yet drawn
+    pub fn streamed_reserve_ops_allowance(&self, now: i64) -> Result<u64> {
+        if self.reserve_ops_stream_start == 0 {
+            return Ok(0);
+        }
+        let elapsed = now.saturating_sub(self.reserve_ops_stream_start).max(0) as u128;
+        let streamed = (self.reserve_ops_monthly_limit_usdc as u128)
+            .checked_mul(elapsed)
+            .ok_or(ErrorCode::Overflow)?
+            .checked_div(SECONDS_PER_MONTH as u128)
+            .ok_or(ErrorCode::Overflow)?;
+        let streamed = u64::try_from(streamed).unwrap_or(u64::MAX);
+        Ok(streamed.saturating_sub(self.total_reserve_ops_usdc))
+    }
+
+    /// Why a crank-driven buyback can't run at `now`, or `None` if it can.
+    /// Cranks should emit `SkippedOperation` with this reason instead of failing.
+    pub fn buyback_skip_reason(&self, now: i64) -> Result<Option<SkipReason>> {
+        if self.is_paused(PauseFlag::Buybacks) {
+            return Ok(Some(SkipReason::Paused));
+        }
+        if !self.is_enabled(GatedInstruction::ExecuteBuyback) {
+            return Ok(Some(SkipReason::InstructionDisabled));
+        }
+        if now < self.ready_at(OperationKind::Buyback) {
+            return Ok(Some(SkipReason::CooldownActive));
+        }
+        if now < self.warmup_until {
+            return Ok(Some(SkipReason::WarmupActive));
+        }
+        if self.remaining_buyback_budget()? == 0 {
+            return Ok(Some(SkipReason::BudgetExhausted));
+        }
+        Ok(None)
+    }
+
+    /// Burn target of the current epoch including any carried-over shortfall
+    pub fn epoch_burn_target(&self) -> Result<u64> {
+        self.burn_target_per_epoch
+            .checked_add(self.burn_carryover)
+            .ok_or(ErrorCode::Overflow.into())
+    }
+
+    /// Keeper reward for a crank moving `usdc_amount`
+    pub fn keeper_reward(&self, usdc_amount: u64) -> u64 {
+        let reward = (usdc_amount as u128 * self.keeper_reward_bps as u128 / 10_000) as u64;
+        if self.keeper_reward_cap_usdc == 0 {
+            reward
+        } else {
+            reward.min(self.keeper_reward_cap_usdc)
+        }
+    }
+
+    /// Upcoming scheduled operations, earliest first
+    pub fn calendar_entries(&self) -> Result<Vec<CalendarEntry>> {
+        let epoch_end = self
+            .epoch_start_timestamp
+            .checked_add(self.epoch_duration_seconds)
+            .ok_or(ErrorCode::Overflow)?;
+        let mut entries = vec![
+            CalendarEntry {
+                kind: CalendarEventKind::BuybackEligible,
+                at: self.ready_at(OperationKind::Buyback).max(self.warmup_until),
+                amount: self.remaining_buyback_budget()?,
+            },
+            CalendarEntry {
+                kind: CalendarEventKind::EpochEnd,
+                at: epoch_end,
+                amount: self.current_epoch,
+            },
+        ];
+        let burn_due = self
+            .epoch_burn_target()?
+            .saturating_sub(self.epoch_tokens_burned);
+        if burn_due > 0 {
+            entries.push(CalendarEntry {
+                kind: CalendarEventKind::BurnDue,
+                at: epoch_end,
+                amount: burn_due,
+            });
+        }
+        if self.vault_migration_eta != 0 {
+            entries.push(CalendarEntry {
+                kind: CalendarEventKind::VaultMigration,
+                at: self.vault_migration_eta,
+                amount: 0,
+            });
+        }
+        if self.weekly_outflow_cap_eta != 0 {
+            entries.push(CalendarEntry {
+                kind: CalendarEventKind::OutflowCapChange,
+                at: self.weekly_outflow_cap_eta,
+                amount: self.pending_weekly_outflow_cap_usdc,
+            });
+        }
+        entries.sort_by_key(|entry| entry.at);
+        Ok(entries)
+    }
+
+    /// Add tokens delivered by a buyback to the lifetime total
+    pub fn record_buyback_tokens(&mut self, received: u64) -> Result<()> {
+        self.total_buyback_tokens_received = self
+            .total_buyback_tokens_received
+            .checked_add(received)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+
+    /// Count USDCBALL swept from withheld transfer fees. Buybacks did not deliver
+    /// it, so burning it raises the funnel's burn share like older holdings.
+    pub fn record_transfer_fees(&mut self, harvested: u64) -> Result<()> {
+        self.total_transfer_fees_harvested = self
+            .total_transfer_fees_harvested
+            .checked_add(harvested)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+
+    /// Fee-to-burn funnel of the current burn epoch so far. SOL recorded by
+    /// `record_fee` counts once `sync_fee_counter` folds it into the treasury, so
+    /// lamports still unsynced at the boundary land in the next epoch's funnel.
+    pub fn epoch_funnel(&self) -> ConversionFunnel {
+        ConversionFunnel::new(
+            self.total_sol_collected
+                .saturating_sub(self.funnel_base_sol_collected),
+            self.total_usdc_converted
+                .saturating_sub(self.funnel_base_usdc_converted),
+            self.total_buybacks_usdc
+                .saturating_sub(self.funnel_base_buybacks_usdc),
+            self.total_buyback_tokens_received
+                .saturating_sub(self.funnel_base_tokens_received),
+            self.epoch_tokens_burned,
+        )
+    }
+
+    /// Measure the next burn epoch's funnel from the current totals
+    pub fn start_funnel_epoch(&mut self) {
+        self.funnel_base_sol_collected = self.total_sol_collected;
+        self.funnel_base_usdc_converted = self.total_usdc_converted;
+        self.funnel_base_buybacks_usdc = self.total_buybacks_usdc;
+        self.funnel_base_tokens_received = self.total_buyback_tokens_received;
+    }
+
+    /// Account for burned tokens in the lifetime and current-epoch totals
+    pub fn record_burn(&mut self, amount: u64) -> Result<()> {
+        self.total_tokens_burned = self
+            .total_tokens_burned
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        self.epoch_tokens_burned = self
+            .epoch_tokens_burned
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+
+    /// Credit LP fees claimed into the vault to the buyback budget
+    pub fn record_lp_fees(&mut self, usdc_amount: u64) -> Result<()> {
+        self.total_lp_fees_earned = self
+            .total_lp_fees_earned
+            .checked_add(usdc_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+
+    /// Credit harvested lending interest as newly available USDC, growing the
+    /// allocation budgets like converted fees
+    pub fn record_yield(&mut self, usdc_amount: u64) -> Result<()> {
+        self.total_usdc_converted = self
+            .total_usdc_converted
+            .checked_add(usdc_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        self.total_yield_harvested_usdc = self
+            .total_yield_harvested_usdc
+            .checked_add(usdc_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+
+    /// Check `threshold` is a milestone of `kind` at the configured interval and
+    /// the lifetime total has reached it
+    pub fn require_milestone_reached(&self, kind: MilestoneKind, threshold: u64) -> Result<()> {
+        let (interval, total) = match kind {
+            MilestoneKind::Burns => (
+                self.burn_milestone_interval_tokens,
+                self.total_tokens_burned,
+            ),
+            MilestoneKind::Buybacks => (
+                self.buyback_milestone_interval_usdc,
+                self.total_buybacks_usdc,
+            ),
+        };
+        require!(
+            threshold > 0 && threshold.checked_rem(interval) == Some(0),
+            ErrorCode::InvalidMilestone
+        );
+        require!(total >= threshold, ErrorCode::MilestoneNotReached);
+        Ok(())
+    }
+
+    /// Take `supply` as the baseline for verifying external burns
+    pub fn snapshot_supply(&mut self, supply: u64, slot: u64) {
+        self.supply_snapshot = supply;
+        self.supply_snapshot_burned = self.total_tokens_burned;
+        self.supply_snapshot_slot = slot;
+    }
+
+    /// Supply drop since the snapshot not yet accounted for by recorded burns,
+    /// which includes external burns already registered. Mints since the snapshot
+    /// offset the drop, so the result never overstates what was burned.
+    pub fn unregistered_external_burn(&self, supply: u64) -> Result<u64> {
+        require!(self.supply_snapshot_slot != 0, ErrorCode::NoSupplySnapshot);
+        let recorded = self
+            .total_tokens_burned
+            .saturating_sub(self.supply_snapshot_burned);
+        Ok(self
+            .supply_snapshot
+            .saturating_sub(supply)
+            .saturating_sub(recorded))
+    }
+
+    /// Check `usdc_amount` against the budget `spend` draws on and count it against
+    /// the outflow limits, before any USDC leaves the vault
+    pub fn authorize_usdc_spend(
+        &mut self,
+        spend: UsdcSpend,
+        usdc_amount: u64,
+        now: i64,
+    ) -> Result<()> {
+        let remaining = match spend {
+            UsdcSpend::Buyback => self.remaining_buyback_budget()?,
+            UsdcSpend::Liquidity => self.remaining_liquidity_budget()?,
+            UsdcSpend::Dividends => self
+                .remaining_dividend_budget()?
+                .min(self.remaining_reserve_budget()?),
+            UsdcSpend::Bounty
+            | UsdcSpend::Reserve
+            | UsdcSpend::KaminoDeposit
+            | UsdcSpend::MarginFiDeposit
+            | UsdcSpend::SolendDeposit
+            | UsdcSpend::StrategyDeposit => self.remaining_reserve_budget()?,
+        };
+        require!(usdc_amount <= remaining, ErrorCode::ExceedsAllocation);
+        self.record_outflow(now, usdc_amount)?;
+        if matches!(spend, UsdcSpend::Buyback | UsdcSpend::Liquidity) {
+            self.record_volume(now, usdc_amount)?;
+        }
+        Ok(())
+    }
+
+    /// Add the USDC that actually left the vault to the lifetime counter for
+    /// `spend`, and give the part of the `authorized` amount it did not use back
+    /// to the outflow and volume windows. Venues and adapters pull the USDC
+    /// themselves, so `spent` is the vault delta rather than the amount requested.
+    pub fn settle_usdc_spend(
+        &mut self,
+        spend: UsdcSpend,
+        authorized: u64,
+        spent: u64,
+        now: i64,
+    ) -> Result<()> {
+        let total = match spend {
+            UsdcSpend::Buyback => &mut self.total_buybacks_usdc,
+            UsdcSpend::Liquidity => &mut self.total_liquidity_usdc,
+            UsdcSpend::Dividends => &mut self.total_dividends_usdc,
+            UsdcSpend::Bounty => &mut self.total_bounty_funded_usdc,
+            UsdcSpend::Reserve => &mut self.total_reserve_spent_usdc,
+            UsdcSpend::KaminoDeposit => &mut self.kamino_deposited_usdc,
+            UsdcSpend::MarginFiDeposit => &mut self.marginfi_deposited_usdc,
+            UsdcSpend::SolendDeposit => &mut self.solend_deposited_usdc,
+            UsdcSpend::StrategyDeposit => &mut self.strategy_deposited_usdc,
+        };
+        *total = total.checked_add(spent).ok_or(ErrorCode::Overflow)?;
+        self.uncharge_usdc_spend(spend, authorized.saturating_sub(spent), now);
+        Ok(())
+    }
+
+    /// Give back the allocation, outflow and volume charged at `opened_at` by an
+    /// operation that was refunded
+    pub fn release_operation(
+        &mut self,
+        kind: OperationKind,
+        usdc_amount: u64,
+        opened_at: i64,
+    ) -> Result<()> {
+        let total = match kind {
+            OperationKind::Buyback => &mut self.total_buybacks_usdc,
+            OperationKind::Liquidity => &mut self.total_liquidity_usdc,
+        };
+        *total = total.checked_sub(usdc_amount).ok_or(ErrorCode::Overflow)?;
+        self.uncharge_usdc_spend(kind.into(), usdc_amount, opened_at);
+        Ok(())
+    }
+
+    /// Take `usdc_amount` charged at `charged_at` back off the rolling outflow and
+    /// volume windows. A bucket that has rolled out of its window since has
+    /// nothing left to give back.
+    fn uncharge_usdc_spend(&mut self, spend: UsdcSpend, usdc_amount: u64, charged_at: i64) {
+        if usdc_amount == 0 {
+            return;
+        }
+        let day = charged_at.div_euclid(24 * 3600);
+        let days = OUTFLOW_WINDOW_DAYS as i64;
+        if day <= self.outflow_day && day > self.outflow_day - days {
+            let bucket = &mut self.daily_outflows_usdc[day.rem_euclid(days) as usize];
+            *bucket = bucket.saturating_sub(usdc_amount);
+        }
+        if matches!(spend, UsdcSpend::Buyback | UsdcSpend::Liquidity) {
+            let hour = charged_at.div_euclid(3600);
+            let hours = VOLUME_WINDOW_HOURS as i64;
+            if hour <= self.volume_hour && hour > self.volume_hour - hours {
+                let bucket = &mut self.hourly_volume_usdc[hour.rem_euclid(hours) as usize];
+                *bucket = bucket.saturating_sub(usdc_amount);
+            }
+        }
+    }
+
+    /// Alert for an operation moving more than `large_operation_threshold`, if any
+    pub fn large_operation_alert(
+        &self,
+        operation: AlertedOperation,
+        usdc_amount: u64,
+        initiator: Pubkey,
+        destination: Pubkey,
+        vault_balance_before: u64,
+        clock: &Clock,
+    ) -> Option<LargeOperationAlert> {
+        if self.large_operation_threshold == 0 || usdc_amount <= self.large_operation_threshold {
+            return None;
+        }
+        Some(LargeOperationAlert {
+            operation,
+            usdc_amount,
+            threshold: self.large_operation_threshold,
+            initiator,
+            destination,
+            vault_balance_before,
+            slot: clock.slot,
+            timestamp: clock.unix_timestamp,
+        })
+    }
+
+    /// Read the USDC/USD oracle and, if USDC is off its peg by more than
+    /// `depeg_threshold_bps`, disable conversions and liquidity adds and emit
+    /// `DepegDetected`. Returns whether the guard tripped.
+    pub fn check_usdc_peg(
+        &mut self,
+        oracle_account: Option<&AccountInfo>,
+        clock: &Clock,
+    ) -> Result<bool> {
+        if self.usdc_usd_oracle == Pubkey::default() {
+            return Ok(false);
+        }
+        let oracle_account = oracle_account
+            .filter(|a| a.key() == self.usdc_usd_oracle)
+            .ok_or(ErrorCode::InvalidOracle)?;
+        let one_usdc = 10u64.pow(USDC_DECIMALS as u32);
+        let price_micro_usd = oracle::read_price(
+            oracle_account,
+            clock.unix_timestamp,
+            self.oracle_max_age_seconds,
+        )?
+        .value_usd(one_usdc, USDC_DECIMALS)?;
+        let deviation_bps =
+            (price_micro_usd.abs_diff(one_usdc) as u128 * 10_000 / one_usdc as u128) as u64;
+        if deviation_bps <= self.depeg_threshold_bps as u64 {
+            return Ok(false);
+        }
+
+        self.instruction_enabled &= !(GatedInstruction::RecordUsdcConversion.mask()
+            | GatedInstruction::AddLiquidity.mask());
+        emit!(DepegDetected {
+            price_micro_usd,
+            deviation_bps,
+            threshold_bps: self.depeg_threshold_bps,
+            timestamp: clock.unix_timestamp,
+        });
+        Ok(true)
+    }
+
+    /// Add a buyback or liquidity operation to the rolling 24-hour volume and
+    /// enforce `daily_volume_cap_usdc`, independent of the per-cycle limit
+    pub fn record_volume(&mut self, now: i64, usdc_amount: u64) -> Result<()> {
+        let hour = now.div_euclid(3600);
+        let window = VOLUME_WINDOW_HOURS as i64;
+        // Clear the buckets of hours that fell out of the window since the last operation
+        let stale_hours = (hour - self.volume_hour).clamp(0, window);
+        for h in (hour - stale_hours + 1)..=hour {
+            self.hourly_volume_usdc[h.rem_euclid(window) as usize] = 0;
+        }
+        self.volume_hour = self.volume_hour.max(hour);
+
+        let bucket = &mut self.hourly_volume_usdc[hour.rem_euclid(window) as usize];
+        *bucket = bucket.checked_add(usdc_amount).ok_or(ErrorCode::Overflow)?;
+
+        if self.daily_volume_cap_usdc > 0 {
+            let total = self
+                .hourly_volume_usdc
+                .iter()
+                .try_fold(0u64, |acc, v| acc.checked_add(*v))
+                .ok_or(ErrorCode::Overflow)?;
+            require!(
+                total <= self.daily_volume_cap_usdc,
+                ErrorCode::DailyVolumeCapExceeded
+            );
+        }
+        Ok(())
+    }
+
+    /// Count `usdc_amount` leaving the treasury against the rolling weekly cap
+    pub fn record_outflow(&mut self, now: i64, usdc_amount: u64) -> Result<()> {
+        let today = now.div_euclid(24 * 3600);
+        let window = OUTFLOW_WINDOW_DAYS as i64;
+        // Clear the buckets of days that fell out of the window since the last outflow
+        let stale_days = (today - self.outflow_day).clamp(0, window);
+        for day in (today - stale_days + 1)..=today {
+            self.daily_outflows_usdc[day.rem_euclid(window) as usize] = 0;
+        }
+        self.outflow_day = self.outflow_day.max(today);
+
+        let bucket = &mut self.daily_outflows_usdc[today.rem_euclid(window) as usize];
+        *bucket = bucket.checked_add(usdc_amount).ok_or(ErrorCode::Overflow)?;
+
+        if self.weekly_outflow_cap_usdc > 0 {
+            let total = self
+                .daily_outflows_usdc
+                .iter()
+                .try_fold(0u64, |acc, v| acc.checked_add(*v))
+                .ok_or(ErrorCode::Overflow)?;
+            require!(
+                total <= self.weekly_outflow_cap_usdc,
+                ErrorCode::WeeklyOutflowCapExceeded
+            );
+        }
+        Ok(())
+    }
+
+    /// When the cooldown of `kind` ends. Buybacks and liquidity operations each
+    /// run on their own timer, so one pipeline never holds back the other.
+    pub fn ready_at(&self, kind: OperationKind) -> i64 {
+        let (last, cooldown) = match kind {
+            OperationKind::Buyback => (self.last_buyback_timestamp, self.cooldown_seconds),
+            OperationKind::Liquidity => {
+                let cooldown = if self.liquidity_cooldown_seconds > 0 {
+                    self.liquidity_cooldown_seconds
+                } else {
+                    self.cooldown_seconds
+                };
+                (self.last_liquidity_timestamp, cooldown)
+            }
+        };
+        last.saturating_add(cooldown)
+    }
+
+    /// Start the cooldown of `kind` at `now`
+    pub fn record_operation(&mut self, kind: OperationKind, now: i64) {
+        self.last_operation_timestamp = now;
+        match kind {
+            OperationKind::Buyback => self.last_buyback_timestamp = now,
+            OperationKind::Liquidity => self.last_liquidity_timestamp = now,
+        }
+    }
+
+    pub fn require_ready(&self, kind: OperationKind, now: i64) -> Result<()> {
+        require!(now >= self.ready_at(kind), ErrorCode::CooldownNotMet);
+        require!(now >= self.warmup_until, ErrorCode::WarmupActive);
+        Ok(())
+    }
+
+    /// Whether the treasury is still within its first `launch_epochs` epochs
+    pub fn in_launch_mode(&self) -> bool {
+        self.current_epoch < self.launch_mode_until_epoch
+    }
+
+    /// `limit` capped at `LAUNCH_MAX_USDC_PER_CYCLE` during launch mode
+    pub fn launch_capped(&self, limit: u64) -> u64 {
+        if self.in_launch_mode() {
+            limit.min(LAUNCH_MAX_USDC_PER_CYCLE)
+        } else {
+            limit
+        }
+    }
+
+    /// Per-cycle USDC limit in force
+    pub fn cycle_limit_usdc(&self) -> u64 {
+        self.launch_capped(self.max_usdc_per_cycle)
+    }
+
+    /// Above the co-sign threshold a second key must sign alongside `signer`: the
+    /// admin, another active registered operator (whose `Operator` account is
+    /// passed as `co_signer_operator`) or the dedicated co-signer
+    pub fn require_co_signer(
+        &self,
+        usdc_amount: u64,
+        signer: Pubkey,
+        co_signer: Option<Pubkey>,
+        co_signer_operator: Option<&Operator>,
+    ) -> Result<()> {
+        // During launch mode every operation needs the guardian, whatever its size
+        if self.in_launch_mode() {
+            require!(
+                self.guardian != Pubkey::default() && co_signer == Some(self.guardian),
+                ErrorCode::GuardianRequired
+            );
+            return Ok(());
+        }
+        if self.cosign_threshold_usdc == 0 || usdc_amount <= self.cosign_threshold_usdc {
+            return Ok(());
+        }
+        let Some(co_signer) = co_signer.filter(|key| *key != signer) else {
+            return err!(ErrorCode::CoSignerRequired);
+        };
+        let second_operator = co_signer_operator
+            .is_some_and(|operator| operator.operator == co_signer && operator.active);
+        require!(
+            co_signer == self.authority
+                || second_operator
+                || (self.co_signer != Pubkey::default() && co_signer == self.co_signer),
+            ErrorCode::CoSignerRequired
+        );
+        Ok(())
+    }
+
+    pub fn is_enabled(&self, instruction: GatedInstruction) -> bool {
+        self.instruction_enabled & instruction.mask() != 0
+    }
+
+    /// Apply the set fields of a validated `change`
+    pub fn apply_config_change(&mut self, change: &ConfigChange) -> Result<()> {
+        change.validate()?;
+        if let Some(split) = &change.allocations {
+            self.buyback_allocation_bps = split.buyback_bps;
+            self.liquidity_allocation_bps = split.liquidity_bps;
+            self.reserve_allocation_bps = split.reserve_bps;
+        }
+        if let Some(max_cycle) = change.max_usdc_per_cycle {
+            self.max_usdc_per_cycle = max_cycle;
+        }
+        if let Some(cooldown) = change.cooldown_seconds {
+            self.cooldown_seconds = cooldown;
+        }
+        if let Some(slippage) = change.slippage_bps {
+            self.slippage_bps = slippage;
+        }
+        if let Some(reward_bps) = change.keeper_reward_bps {
+            self.keeper_reward_bps = reward_bps;
+        }
+        if let Some(reward_cap) = change.keeper_reward_cap_usdc {
+            self.keeper_reward_cap_usdc = reward_cap;
+        }
+        if let Some(delay) = change.config_delay_seconds {
+            self.config_delay_seconds = delay;
+        }
+        if let Some(max_payout) = change.bounty_max_payout_usdc {
+            self.bounty_max_payout_usdc = max_payout;
+        }
+        if let Some(timelock) = change.bounty_timelock_seconds {
+            self.bounty_timelock_seconds = timelock;
+        }
+        Ok(())
+    }
+
+    /// Move the fees `record_fee` left in `counter` into the SOL totals
+    pub fn absorb_fee_counter(&mut self, counter: &mut FeeCounter) -> Result<()> {
+        self.total_sol_collected = self
+            .total_sol_collected
+            .checked_add(counter.unsynced_lamports)
+            .ok_or(ErrorCode::Overflow)?;
+        self.sol_pending_conversion = self
+            .sol_pending_conversion
+            .checked_add(counter.unsynced_lamports)
+            .ok_or(ErrorCode::Overflow)?;
+        self.total_sol_collected_usd = self
+            .total_sol_collected_usd
+            .checked_add(counter.unsynced_usd)
+            .ok_or(ErrorCode::Overflow)?;
+        counter.unsynced_lamports = 0;
+        counter.unsynced_usd = 0;
+        Ok(())
+    }
+
+    /// Add `amount` lamports of received fees to the SOL totals, valuing them at
+    /// the current SOL/USD price once an oracle is configured. Returns the USD value.
+    pub fn credit_sol_fee(
+        &mut self,
+        amount: u64,
+        sol_usd_oracle: Option<&AccountInfo>,
+        clock: &Clock,
+    ) -> Result<u64> {
+        self.total_sol_collected = self
+            .total_sol_collected
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        self.sol_pending_conversion = self
+            .sol_pending_conversion
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let usd_value = if self.sol_usd_oracle == Pubkey::default() {
+            0
+        } else {
+            let oracle_account = sol_usd_oracle
+                .filter(|a| a.key() == self.sol_usd_oracle)
+                .ok_or(ErrorCode::InvalidOracle)?;
+            oracle::read_price(
+                oracle_account,
+                clock.unix_timestamp,
+                self.oracle_max_age_seconds,
+            )?
+            .value_usd(amount, SOL_DECIMALS)?
+        };
+        self.total_sol_collected_usd = self
+            .total_sol_collected_usd
+            .checked_add(usd_value)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(usd_value)
+    }
+
+    /// Reject a buyback whose `min_tokens_out` implies worse execution than
+    /// `fair_tokens_out` at the oracle price less `slippage_bps`
+    pub fn require_oracle_floor(&self, fair_tokens_out: u64, min_tokens_out: u64) -> Result<()> {
+        let floor =
+            (fair_tokens_out as u128 * (10_000 - self.slippage_bps as u128) / 10_000) as u64;
+        require!(min_tokens_out >= floor, ErrorCode::MinOutBelowOracleFloor);
+        Ok(())
+    }
+
+    /// Check a buyback that spent `spent` of its `usdc_amount` for `received` tokens
+    /// against `min_tokens_out`, then book it. The realized price is compared with
+    /// the pre-trade `reference_price` to catch sandwiched fills that still cleared
+    /// `min_tokens_out`. Returns the execution price and whether it was degraded.
+    #[allow(clippy::too_many_arguments)]
+    pub fn settle_buyback(
+        &mut self,
+        usdc_amount: u64,
+        spent: u64,
+        received: u64,
+        min_tokens_out: u64,
+        reference_price: Option<u64>,
+        token_decimals: u8,
+        now: i64,
+    ) -> Result<(u64, bool)> {
+        require!(spent <= usdc_amount, ErrorCode::InvalidVenueInstruction);
+        require!(
+            received > 0 && received >= min_tokens_out,
+            ErrorCode::SlippageExceeded
+        );
+        let execution_price =
+            Twap::spot_price(spent, received, token_decimals).ok_or(ErrorCode::Overflow)?;
+        let degraded = self.record_settlement(reference_price, execution_price, now);
+
+        self.record_buyback_tokens(received)?;
+        self.settle_usdc_spend(UsdcSpend::Buyback, usdc_amount, spent, now)?;
+        self.record_operation(OperationKind::Buyback, now);
+        self.stalled = false;
+        Ok((execution_price, degraded))
+    }
+
+    /// Flag a settlement whose `execution_price` exceeds `reference_price` by more
+    /// than `settlement_max_deviation_bps`, pausing once `degraded_execution_limit`
+    /// degraded executions accumulate, or at once when the deviation exceeds
+    /// `circuit_breaker_deviation_bps`. Returns whether the execution was degraded.
+    pub fn record_settlement(
+        &mut self,
+        reference_price: Option<u64>,
+        execution_price: u64,
+        now: i64,
+    ) -> bool {
+        let Some(reference_price) = reference_price.filter(|p| *p > 0) else {
+            return false;
+        };
+        let deviation_bps = execution_price.saturating_sub(reference_price) as u128 * 10_000
+            / reference_price as u128;
+
+        let degraded = self.settlement_max_deviation_bps > 0
+            && deviation_bps > self.settlement_max_deviation_bps as u128;
+        if degraded {
+            self.degraded_executions = self.degraded_executions.saturating_add(1);
+        }
+        let limit_reached = degraded
+            && self.degraded_execution_limit > 0
+            && self.degraded_executions >= self.degraded_execution_limit;
+        let deviation_tripped = self.circuit_breaker_deviation_bps > 0
+            && deviation_bps > self.circuit_breaker_deviation_bps as u128;
+
+        if (limit_reached || deviation_tripped) && !self.fully_paused() {
+            self.pause_flags = PAUSE_ALL;
+            if self.circuit_breaker_tripped_at == 0 {
+                self.circuit_breaker_tripped_at = now;
+            }
+            emit!(CircuitBreakerTripped {
+                degraded_executions: self.degraded_executions,
+                reference_price,
+                execution_price,
+                deviation_bps: u64::try_from(deviation_bps).unwrap_or(u64::MAX),
+                timestamp: now,
+            });
+        }
+        degraded
+    }
+
+    pub fn is_paused(&self, flag: PauseFlag) -> bool {
+        self.pause_flags & flag.mask() != 0
+    }
+
+    pub fn require_not_paused(&self, flag: PauseFlag) -> Result<()> {
+        require!(!self.is_paused(flag), ErrorCode::Paused);
+        Ok(())
+    }
+
+    /// Every operation type paused, as after `emergency_pause`
+    pub fn fully_paused(&self) -> bool {
+        self.pause_flags & PAUSE_ALL == PAUSE_ALL
+    }
+
+    pub fn require_enabled(&self, instruction: GatedInstruction) -> Result<()> {
+        require!(self.is_enabled(instruction), ErrorCode::InstructionDisabled);
+        Ok(())
+    }
+}
+
+/// Instructions that can be switched off individually via `set_instruction_enabled`.
+/// Variant order defines the bit position, so new variants must be appended.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GatedInstruction {
+    RecordFee,
+    ExecuteBuyback,
+    AddLiquidity,
+    RecordUsdcConversion,
+    HarvestTransferFees,
+    CreateDividendDistribution,
+    ClaimDividend,
+    FundBountyEscrow,
+    ExecuteBountyPayout,
+    ExecuteBurn,
+    RecordTransferFee,
+}
+
+impl GatedInstruction {
+    pub fn mask(self) -> u64 {
+        1u64 << (self as u8)
+    }
+}
+
+/// Rounding of the allocation split. `Floor` rounds every bucket down and leaves
+/// up to two micro-USDC of lifetime revenue unassigned; `LargestRemainder` hands
+/// that dust to the buckets with the largest fractional shares, so the buckets
+/// always add up to the converted total.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RoundingPolicy {
+    Floor,
+    LargestRemainder,
+}
+
+/// Operation types that can be paused independently. Instructions outside these
+/// types (dividends, bounties, reserve draws) stop only on a full pause.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PauseFlag {
+    /// Buybacks and burns
+    Buybacks,
+    /// Liquidity adds, inventory loans and LBPs
+    Liquidity,
+    /// SOL -> USDC conversions
+    Conversions,
+    /// Fee deposits, fee recording and transfer-fee harvests
+    Fees,
+}
+
+impl PauseFlag {
+    pub fn mask(self) -> u8 {
+        1u8 << (self as u8)
+    }
+}
+
+/// Reason reported in `SkippedOperation` when a crank finds nothing executable.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SkipReason {
+    Paused,
+    InstructionDisabled,
+    CooldownActive,
+    BudgetExhausted,
+    WarmupActive,
+    /// Launch mode needs the guardian's co-signature, which a crank cannot bring
+    LaunchMode,
+}
+
+/// Upcoming treasury operations, so UIs can render a calendar from one account
+#[account]
+#[derive(InitSpace)]
+pub struct Calendar {
+    #[max_len(MAX_CALENDAR_ENTRIES)]
+    pub entries: Vec<CalendarEntry>,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Debug)]
+pub struct CalendarEntry {
+    pub kind: CalendarEventKind,
+    pub at: i64,
+    /// Remaining buyback budget, epoch number, tokens left to burn or pending
+    /// outflow cap, depending on `kind`
+    pub amount: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CalendarEventKind {
+    BuybackEligible,
+    EpochEnd,
+    BurnDue,
+    VaultMigration,
+    OutflowCapChange,
+}
+
+/// One dividend epoch: USDC escrowed in its vault, claimable against `merkle_root`
+#[account]
+#[derive(InitSpace)]
+pub struct DividendDistribution {
+    pub epoch: u64,
+    pub merkle_root: [u8; 32],
+    pub total_usdc: u64,
+    pub claimed_usdc: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+/// Marks a holder's dividend for one epoch as claimed
+#[account]
+#[derive(InitSpace)]
+pub struct DividendClaim {
+    pub distribution: Pubkey,
+    pub claimant: Pubkey,
+    pub amount: u64,
+    pub claimed_at: i64,
+}
+
+/// A queued bug bounty payout from the escrow
+#[account]
+#[derive(InitSpace)]
+pub struct BountyPayout {
+    pub index: u64,
+    /// USDC token account receiving the payout
+    pub recipient: Pubkey,
+    pub usdc_amount: u64,
+    pub proposed_at: i64,
+    pub executable_at: i64,
+    pub executed: bool,
+    pub bump: u8,
+}
+
+/// An operation whose USDC sits in escrow until it is settled or refunded
+#[account]
+#[derive(InitSpace)]
+pub struct PendingOperation {
+    pub index: u64,
+    pub kind: OperationKind,
+    pub usdc_amount: u64,
+    /// Receives the rent back when the operation closes
+    pub initiator: Pubkey,
+    pub opened_slot: u64,
+    /// Last slot at which the operation may settle
+    pub deadline_slot: u64,
+    /// Venue the operator declared when opening the operation
+    pub venue: Venue,
+    /// One-time account the venue delivers the output to, for venues that settle
+    /// across transactions; default when the operation settles in one
+    pub receipt: Pubkey,
+    /// Delivery the receipt settles for before its claim window closes
+    pub min_output: u64,
+    /// Escrow released to the venue; the operation stays open until the
+    /// receipt is swept
+    pub released: bool,
+    /// Treasury USDC vault the escrow was funded from and is refunded to
+    pub funding_vault: Pubkey,
+    /// When the escrow was charged to the outflow and volume windows
+    pub opened_at: i64,
+    pub bump: u8,
+}
+
+impl PendingOperation {
+    pub fn has_receipt(&self) -> bool {
+        self.receipt != Pubkey::default()
+    }
+
+    /// Last slot before a receipt delivered short of `min_output` can be swept
+    /// anyway
+    pub fn claim_deadline_slot(&self, settlement_window_slots: u64) -> Result<u64> {
+        self.deadline_slot
+            .checked_add(settlement_window_slots)
+            .ok_or(ErrorCode::Overflow.into())
+    }
+
+    /// Whether `received` settles the receipt at `slot`: in full once it reaches
+    /// `min_output`, or whatever arrived once the claim window has closed.
+    /// Returns whether the delivery fell short.
+    pub fn require_settleable(
+        &self,
+        received: u64,
+        slot: u64,
+        settlement_window_slots: u64,
+    ) -> Result<bool> {
+        require!(self.released, ErrorCode::OperationNotReleased);
+        let short = received < self.min_output;
+        require!(
+            !short || slot > self.claim_deadline_slot(settlement_window_slots)?,
+            ErrorCode::ReceiptBelowMinimum
+        );
+        Ok(short)
+    }
+}
+
+/// Budget an outbound USDC transfer is checked against and counted in
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UsdcSpend {
+    Buyback,
+    Liquidity,
+    Dividends,
+    /// Bounty escrow funding, drawn from the reserve
+    Bounty,
+    Reserve,
+    /// Reserve USDC lent to Kamino; committed until redeemed
+    KaminoDeposit,
+    /// Reserve USDC lent to MarginFi; committed until withdrawn
+    MarginFiDeposit,
+    /// Reserve USDC lent to Solend; committed until redeemed
+    SolendDeposit,
+    /// Reserve USDC allocated to a strategy adapter; committed until withdrawn
+    StrategyDeposit,
+}
+
+impl From<OperationKind> for UsdcSpend {
+    fn from(kind: OperationKind) -> Self {
+        match kind {
+            OperationKind::Buyback => UsdcSpend::Buyback,
+            OperationKind::Liquidity => UsdcSpend::Liquidity,
+        }
+    }
+}
+
+/// Allocation bucket a two-phase operation draws from
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OperationKind {
+    Buyback,
+    Liquidity,
+}
+
+impl OperationKind {
+    pub fn mask(self) -> u8 {
+        1u8 << (self as u8)
+    }
+
+    pub fn gate(self) -> GatedInstruction {
+        match self {
+            OperationKind::Buyback => GatedInstruction::ExecuteBuyback,
+            OperationKind::Liquidity => GatedInstruction::AddLiquidity,
+        }
+    }
+
+    pub fn pause_flag(self) -> PauseFlag {
+        match self {
+            OperationKind::Buyback => PauseFlag::Buybacks,
+            OperationKind::Liquidity => PauseFlag::Liquidity,
+        }
+    }
+}
+
+/// A key allowed to run two-phase operations, with its on-chain track record. The
+/// operator role key needs one as well: its scope bounds every operator-gated
+/// instruction.
+#[account]
+#[derive(InitSpace)]
+pub struct Operator {
+    pub operator: Pubkey,
+    /// Cleared by the authority to revoke the key
+    pub active: bool,
+    pub successful_operations: u64,
+    pub skipped_operations: u64,
+    pub expired_operations: u64,
+    /// USDC moved by settled operations
+    pub total_volume_usdc: u64,
+    pub last_active_timestamp: i64,
+    /// Last liveness ping from the operator's automation
+    pub last_heartbeat_timestamp: i64,
+    pub heartbeat_count: u64,
+    /// Bitmasks of the `OperationKind`s and `Venue`s the operator may use
+    pub allowed_kinds: u8,
+    pub allowed_venues: u8,
+    /// Last `report_skip` that counted
+    pub last_skip_timestamp: i64,
+    pub bump: u8,
+}
+
+impl Operator {
+    /// Reject an operation outside the operator's scope; `venue` is `None` when
+    /// the operation does not name one
+    pub fn require_scope(&self, kind: OperationKind, venue: Option<Venue>) -> Result<()> {
+        require!(
+            self.allowed_kinds & kind.mask() != 0,
+            ErrorCode::OperatorScopeViolation
+        );
+        if let Some(venue) = venue {
+            self.require_venue(venue)?;
+        }
+        Ok(())
+    }
+
+    pub fn allows_venue(&self, venue: Venue) -> bool {
+        self.allowed_venues & venue.mask() != 0
+    }
+
+    pub fn require_venue(&self, venue: Venue) -> Result<()> {
+        require!(self.allows_venue(venue), ErrorCode::OperatorScopeViolation);
+        Ok(())
+    }
+
+    /// Count a reported skip, unless one was already counted within the cooldown
+    /// window (at least `MIN_SKIP_REPORT_INTERVAL_SECONDS`)
+    pub fn record_skip(&mut self, now: i64, cooldown_seconds: i64) -> Result<()> {
+        let window = cooldown_seconds.max(MIN_SKIP_REPORT_INTERVAL_SECONDS);
+        require!(
+            now >= self.last_skip_timestamp.saturating_add(window),
+            ErrorCode::SkipAlreadyReported
+        );
+        self.skipped_operations = self
+            .skipped_operations
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+        self.last_skip_timestamp = now;
+        self.last_active_timestamp = now;
+        Ok(())
+    }
+
+    pub fn stats(&self) -> OperatorStats {
+        let attempts = self
+            .successful_operations
+            .saturating_add(self.skipped_operations)
+            .saturating_add(self.expired_operations);
+        let success_rate_bps = if attempts == 0 {
+            10000
+        } else {
+            (self.successful_operations as u128 * 10000 / attempts as u128) as u16
+        };
+        OperatorStats {
+            operator: self.operator,
+            active: self.active,
+            successful_operations: self.successful_operations,
+            skipped_operations: self.skipped_operations,
+            expired_operations: self.expired_operations,
+            total_volume_usdc: self.total_volume_usdc,
+            success_rate_bps,
+            last_active_timestamp: self.last_active_timestamp,
+            last_heartbeat_timestamp: self.last_heartbeat_timestamp,
+            heartbeat_count: self.heartbeat_count,
+        }
+    }
+}
+
+/// Return value of `get_operator_stats`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct OperatorStats {
+    pub operator: Pubkey,
+    pub active: bool,
+    pub successful_operations: u64,
+    pub skipped_operations: u64,
+    pub expired_operations: u64,
+    pub total_volume_usdc: u64,
+    /// Settled operations over all recorded attempts
+    pub success_rate_bps: u16,
+    pub last_active_timestamp: i64,
+    pub last_heartbeat_timestamp: i64,
+    pub heartbeat_count: u64,
+}
+
+/// DEX a route leg executes on; see `venues` for the per-venue handlers
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Venue {
+    Jupiter,
+    Raydium,
+    Orca,
+    Meteora,
+    Phoenix,
+}
+
+/// Burn commitment of a closed epoch: target vs achieved
+#[account]
+#[derive(InitSpace)]
+pub struct EpochStats {
+    pub epoch: u64,
+    pub start_timestamp: i64,
+    pub end_timestamp: i64,
+    /// Target including `carried_over`
+    pub burn_target: u64,
+    /// Shortfall inherited from the previous epoch
+    pub carried_over: u64,
+    pub tokens_burned: u64,
+    /// Carried over into the next epoch
+    pub shortfall: u64,
+    pub funnel: ConversionFunnel,
+    pub bump: u8,
+}
+
+/// Where an epoch's fee revenue went on its way to being burned: the amount
+/// reaching each step and the conversion ratio between consecutive steps. A
+/// ratio is 0 when the step before it saw nothing.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq, Debug, Default,
+)]
+pub struct ConversionFunnel {
+    /// Lamports of SOL fees recorded, excluding any still unsynced in the `FeeCounter`
+    pub sol_collected: u64,
+    /// USDC converted from fees, including USDC revenue forwarded directly
+    pub usdc_converted: u64,
+    pub buybacks_usdc: u64,
+    /// Tokens the buybacks delivered
+    pub tokens_received: u64,
+    /// Tokens burned, whether bought this epoch or earlier
+    pub tokens_burned: u64,
+    /// Micro-USDC converted per SOL collected
+    pub usdc_per_sol: u64,
+    /// Converted USDC spent on buybacks
+    pub buyback_share_bps: u64,
+    /// Token base units received per USDC spent on buybacks
+    pub tokens_per_usdc: u64,
+    /// Received tokens burned; above 10000 when older holdings were burned too
+    pub burn_share_bps: u64,
+}
+
+impl ConversionFunnel {
+    pub fn new(
+        sol_collected: u64,
+        usdc_converted: u64,
+        buybacks_usdc: u64,
+        tokens_received: u64,
+        tokens_burned: u64,
+    ) -> Self {
+        let ratio = |numerator: u64, denominator: u64, scale: u64| {
+            if denominator == 0 {
+                0
+            } else {
+                (numerator as u128 * scale as u128 / denominator as u128).min(u64::MAX as u128)
+                    as u64
+            }
+        };
+        Self {
+            sol_collected,
+            usdc_converted,
+            buybacks_usdc,
+            tokens_received,
+            tokens_burned,
+            usdc_per_sol: ratio(
+                usdc_converted,
+                sol_collected,
+                10u64.pow(SOL_DECIMALS as u32),
+            ),
+            buyback_share_bps: ratio(buybacks_usdc, usdc_converted, 10000),
+            tokens_per_usdc: ratio(
+                tokens_received,
+                buybacks_usdc,
+                10u64.pow(USDC_DECIMALS as u32),
+            ),
+            burn_share_bps: ratio(tokens_burned, tokens_received, 10000),
+        }
+    }
+}
+
+/// Allocation accounting of a closed budget epoch: USDC converted and spent per bucket
+#[account]
+#[derive(InitSpace)]
+pub struct Epoch {
+    pub index: u64,
+    pub start_timestamp: i64,
+    pub end_timestamp: i64,
+    pub usdc_converted: u64,
+    pub buybacks_usdc: u64,
+    pub liquidity_usdc: u64,
+    /// Committed to dividends, the bounty escrow or drawdowns
+    pub reserve_usdc: u64,
+    pub bump: u8,
+}
+
+/// Reserve drawdown tier, from routine to exceptional
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ReserveTier {
+    /// Operations spending within the streamed monthly allowance
+    Operations,
+    /// Market defense, co-signed by the guardian
+    MarketDefense,
+    /// Full drawdown, approved by governance
+    FullDrawdown,
+}
+
+/// Proof-of-reserves snapshot for one epoch, signed by the treasury authority
+#[account]
+#[derive(InitSpace)]
+pub struct Attestation {
+    pub epoch: u64,
+    pub attester: Pubkey,
+    pub slot: u64,
+    pub timestamp: i64,
+    #[max_len(MAX_ATTESTED_HOLDINGS)]
+    pub holdings: Vec<AttestedHolding>,
+    /// `keccak(epoch || slot || (kind || account || mint || amount)*)`
+    pub hash: [u8; 32],
+    pub bump: u8,
+}
+
+impl Attestation {
+    pub fn hash_holdings(epoch: u64, slot: u64, holdings: &[AttestedHolding]) -> [u8; 32] {
+        let mut data = Vec::with_capacity(16 + holdings.len() * 73);
+        data.extend_from_slice(&epoch.to_le_bytes());
+        data.extend_from_slice(&slot.to_le_bytes());
+        for h in holdings {
+            data.push(h.kind as u8);
+            data.extend_from_slice(h.account.as_ref());
+            data.extend_from_slice(h.mint.as_ref());
+            data.extend_from_slice(&h.amount.to_le_bytes());
+        }
+        keccak::hash(&data).to_bytes()
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Debug)]
+pub struct AttestedHolding {
+    pub kind: HoldingKind,
+    pub account: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+/// Venue reserve lending deposits go to
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LendingVenue {
+    /// Reserve USDC stays in the vault
+    None,
+    Kamino,
+    MarginFi,
+    Solend,
+}
+
+/// What an attested token account represents
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HoldingKind {
+    Vault,
+    LpPosition,
+    LendingPosition,
+}
+
+/// Buyback that may only execute while the token price is at or below a limit
+#[account]
+#[derive(InitSpace)]
+pub struct ConditionalBuyback {
+    pub index: u64,
+    pub creator: Pubkey,
+    pub usdc_amount: u64,
+    /// Highest acceptable oracle price of one token, in micro-USD
+    pub max_price_micro_usd: u64,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+/// Fees recorded by `record_fee` and not yet added to the treasury totals. Zero-copy
+/// so the hot path writes a few bytes instead of re-serializing the treasury.
+#[account(zero_copy)]
+pub struct FeeCounter {
+    pub unsynced_lamports: u64,
+    pub unsynced_usd: u64,
+    /// Lifetime number of `record_fee` calls
+    pub fee_count: u64,
+    pub last_fee_timestamp: i64,
+    pub bump: u8,
+    pub _padding: [u8; 7],
+}
+
+/// Token-2022 transfer fees withheld on USDCBALL transfers, as recorded by the
+/// transfer hook through `record_transfer_fee`. Zero-copy for the same reason as
+/// `FeeCounter`: it is written on every transfer.
+#[account(zero_copy)]
+pub struct TransferFeeCounter {
+    /// Lifetime fees withheld, harvested or not
+    pub accrued_fees: u64,
+    /// Lifetime amount transferred, before fees
+    pub transferred_amount: u64,
+    pub transfer_count: u64,
+    pub last_transfer_timestamp: i64,
+    pub bump: u8,
+    pub _padding: [u8; 7],
+}
+
+/// Program-owned vault holding collected SOL fees
+#[account]
+#[derive(InitSpace)]
+pub struct FeeVault {
+    /// Lamports above rent already credited to `total_sol_collected`
+    pub recorded_lamports: u64,
+    pub bump: u8,
+}
+
+impl FeeVault {
+    /// Lamports that reached the vault without being recorded as fees
+    pub fn unrecorded_lamports(&self, info: &AccountInfo) -> Result<u64> {
+        let rent = Rent::get()?.minimum_balance(info.data_len());
+        Ok(info
+            .lamports()
+            .saturating_sub(rent)
+            .saturating_sub(self.recorded_lamports))
+    }
+}
+
+/// Pools the treasury may deploy liquidity to
+#[account]
+#[derive(InitSpace)]
+pub struct PoolRegistry {
+    #[max_len(MAX_REGISTERED_POOLS)]
+    pub pools: Vec<RegisteredPool>,
+    pub bump: u8,
+}
+
+impl PoolRegistry {
+    pub fn find(&self, usdc_vault: &Pubkey, token_vault: &Pubkey) -> Option<usize> {
+        self.pools
+            .iter()
+            .position(|p| p.usdc_vault == *usdc_vault && p.token_vault == *token_vault)
+    }
+
+    /// Pool on `venue` whose USDC vault is `usdc_vault`
+    pub fn find_usdc_vault(&self, venue: Venue, usdc_vault: &Pubkey) -> Option<usize> {
+        self.pools
+            .iter()
+            .position(|p| p.venue == venue && p.usdc_vault == *usdc_vault)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Debug)]
+pub struct RegisteredPool {
+    pub venue: Venue,
+    pub usdc_vault: Pubkey,
+    pub token_vault: Pubkey,
+    /// Overrides `Treasury::slippage_bps` for this pool when non-zero
+    pub slippage_bps: u16,
+    /// Tightens `Treasury::max_usdc_per_cycle` for this pool when non-zero
+    pub max_usdc_amount: u64,
+    pub pool_type: PoolType,
+}
+
+/// How the treasury deposits into a registered pool
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PoolType {
+    /// Both sides transferred into the pool vaults at the pool's price (`add_liquidity`)
+    ConstantProduct,
+    /// Single-sided USDC into the treasury's DLMM position (`add_liquidity_meteora`)
+    MeteoraDlmm,
+}
+
+impl RegisteredPool {
+    pub fn slippage_bps(&self, treasury: &Treasury) -> u16 {
+        if self.slippage_bps == 0 {
+            treasury.slippage_bps
+        } else {
+            self.slippage_bps
+        }
+    }
+
+    /// Size limit for one operation on this pool; a pool limit never lifts the
+    /// per-cycle limit
+    pub fn max_usdc_amount(&self, treasury: &Treasury) -> u64 {
+        if self.max_usdc_amount == 0 {
+            treasury.cycle_limit_usdc()
+        } else {
+            self.max_usdc_amount.min(treasury.cycle_limit_usdc())
+        }
+    }
+}
+
+/// Liquidity mining campaign streaming USDCBALL rewards to LP stakers
+#[account]
+#[derive(InitSpace)]
+pub struct Campaign {
+    pub index: u64,
+    /// USDC vault of the registered pool the campaign incentivizes
+    pub pool: Pubkey,
+    pub lp_mint: Pubkey,
+    pub reward_budget: u64,
+    pub reward_rate_per_second: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    /// Rewards per staked LP token, scaled by `REWARD_PRECISION`
+    pub acc_reward_per_share: u128,
+    pub last_update_time: i64,
+    pub total_staked: u64,
+    pub total_claimed: u64,
+    pub bump: u8,
+}
+
+impl Campaign {
+    /// Stream rewards accrued since the last update into `acc_reward_per_share`.
+    /// Nothing streams while no LP is staked; that stretch is paid to the next
+    /// stakers instead of staying in the reward vault.
+    pub fn accrue(&mut self, now: i64) -> Result<()> {
+        let until = now.min(self.end_time);
+        if until <= self.last_update_time || self.total_staked == 0 {
+            return Ok(());
+        }
+        let elapsed = (until - self.last_update_time) as u128;
+        let increment = elapsed
+            .checked_mul(self.reward_rate_per_second as u128)
+            .and_then(|r| r.checked_mul(REWARD_PRECISION))
+            .ok_or(ErrorCode::Overflow)?
+            / self.total_staked as u128;
+        self.acc_reward_per_share = self
+            .acc_reward_per_share
+            .checked_add(increment)
+            .ok_or(ErrorCode::Overflow)?;
+        self.last_update_time = until;
+        Ok(())
+    }
+
+    /// End the campaign before it streams anything
+    pub fn cancel(&mut self, now: i64) {
+        self.reward_budget = 0;
+        self.reward_rate_per_second = 0;
+        self.end_time = now;
+    }
+
+    pub fn reward_debt_for(&self, amount: u64) -> Result<u128> {
+        (amount as u128)
+            .checked_mul(self.acc_reward_per_share)
+            .ok_or(ErrorCode::Overflow.into())
+    }
+
+    /// Move what `position` earned since its last update into its pending rewards
+    pub fn settle(&self, position: &mut StakePosition) -> Result<()> {
+        let earned = self
+            .reward_debt_for(position.amount)?
+            .saturating_sub(position.reward_debt)
+            / REWARD_PRECISION;
+        position.pending_rewards = position
+            .pending_rewards
+            .checked_add(earned as u64)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+}
+
+/// A staker's position in a campaign
+#[account]
+#[derive(InitSpace)]
+pub struct StakePosition {
+    pub campaign: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub reward_debt: u128,
+    pub pending_rewards: u64,
+    pub bump: u8,
+}
+
+/// Operations that can raise a `LargeOperationAlert`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AlertedOperation {
+    Buyback,
+    ConditionalBuyback,
+    Liquidity,
+    EscrowOpened,
+    ReserveDraw,
+}
+
+/// Swap for one `route_best` candidate pool, prepared off-chain
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct RouteCandidate {
+    /// Venue program plus swap accounts following the pool's two vaults
+    pub account_count: u8,
+    pub swap_data: Vec<u8>,
+}
+
+/// One fee in a `record_fees_batch` call, attributed to the router's source
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct FeeEntry {
+    pub source_id: u32,
+    pub amount: u64,
+}
+
+/// Merkle root of holder balances taken once per epoch
+#[account]
+#[derive(InitSpace)]
+pub struct HolderSnapshot {
+    pub epoch: u64,
+    pub root: [u8; 32],
+    pub holder_count: u32,
+    pub total_balance: u64,
+    pub slot: u64,
+    pub timestamp: i64,
+    pub bump: u8,
+}
+
+impl HolderSnapshot {
+    /// Boost for a holding of `holding_seconds`, linear up to `MAX_HOLDER_BOOST_BPS`
+    pub fn boost_bps(holding_seconds: i64) -> u16 {
+        let capped = holding_seconds.clamp(0, HOLDER_BOOST_FULL_SECONDS);
+        (capped as u128 * MAX_HOLDER_BOOST_BPS as u128 / HOLDER_BOOST_FULL_SECONDS as u128) as u16
+    }
+}
+
+/// Returned by `attest_lp_locks`; LP amounts in the LP mints' base units
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct LpLockAttestation {
+    pub holdings: Vec<LpHolding>,
+    pub total_lp_held: u64,
+    /// Part of `total_lp_held` still under a time lock
+    pub total_lp_locked: u64,
+    pub total_lp_burned: u64,
+    pub slot: u64,
+    pub timestamp: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct LpHolding {
+    pub account: Pubkey,
+    pub lp_mint: Pubkey,
+    pub amount: u64,
+    /// 0 when the tokens can be withdrawn now
+    pub locked_until: i64,
+}
+
+/// Returned by `simulate_cycle`; USDC amounts in base units
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct CycleSimulation {
+    /// Lamports the conversion leg would swap
+    pub sol_to_convert: u64,
+    /// Oracle value of `sol_to_convert`
+    pub usdc_from_conversion: u64,
+    pub buyback_usdc: u64,
+    /// Oracle-priced tokens for `buyback_usdc`, before slippage
+    pub buyback_tokens_out: u64,
+    /// Why the buyback leg would not run
+    pub buyback_skip_reason: Option<SkipReason>,
+    pub liquidity_usdc: u64,
+    /// Reserve allocation left uncommitted after the conversion
+    pub reserve_usdc: u64,
+    pub timestamp: i64,
+}
+
+/// Returned by `get_holder_boost`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct HolderBoost {
+    pub epoch: u64,
+    pub balance: u64,
+    pub holding_seconds: i64,
+    pub boost_bps: u16,
+}
+
+/// USDCBALL inventory lent to a market maker against USDC collateral
+#[account]
+#[derive(InitSpace)]
+pub struct MarketMakerLoan {
+    pub market_maker: Pubkey,
+    /// Whether the market maker may borrow more
+    pub active: bool,
+    pub collateral_usdc: u64,
+    pub borrowed_tokens: u64,
+    pub last_update_timestamp: i64,
+    pub bump: u8,
+}
+
+impl MarketMakerLoan {
+    /// Whether borrowed tokens worth `borrowed_value_usd` stay within `ltv_bps`
+    /// of the posted collateral
+    pub fn within_ltv(&self, borrowed_value_usd: u64, ltv_bps: u16) -> bool {
+        borrowed_value_usd as u128 * 10_000 <= self.collateral_usdc as u128 * ltv_bps as u128
+    }
+}
+
+/// Ring buffer of spot price observations for one pool
+#[account]
+#[derive(InitSpace)]
+pub struct Twap {
+    pub usdc_vault: Pubkey,
+    pub token_vault: Pubkey,
+    pub observations: [PriceObservation; TWAP_SAMPLES],
+    /// Slot the next observation is written to
+    pub head: u8,
+    /// Number of filled slots
+    pub len: u8,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Default, Debug)]
+pub struct PriceObservation {
+    pub timestamp: i64,
+    /// Micro-USD per whole token
+    pub price: u64,
+}
+
+impl Twap {
+    /// Pool price in micro-USD per whole token, `None` for an empty pool
+    pub fn spot_price(pool_usdc: u64, pool_token: u64, token_decimals: u8) -> Option<u64> {
+        if pool_token == 0 {
+            return None;
+        }
+        let price = pool_usdc as u128 * 10u128.pow(token_decimals as u32) / pool_token as u128;
+        u64::try_from(price).ok()
+    }
+
+    pub fn observe(&mut self, timestamp: i64, price: u64) {
+        // Several operations in one second update the same sample
+        if self.len > 0 {
+            let last = (self.head as usize + TWAP_SAMPLES - 1) % TWAP_SAMPLES;
+            if self.observations[last].timestamp == timestamp {
+                self.observations[last].price = price;
+                return;
+            }
+        }
+        self.observations[self.head as usize] = PriceObservation { timestamp, price };
+        self.head = ((self.head as usize + 1) % TWAP_SAMPLES) as u8;
+        self.len = (self.len + 1).min(TWAP_SAMPLES as u8);
+    }
+
+    /// Time-weighted average over the buffer, each price weighted until the next
+    /// observation (the newest until `now`). `None` until time has passed.
+    pub fn average(&self, now: i64) -> Option<u64> {
+        let len = self.len as usize;
+        let oldest = (self.head as usize + TWAP_SAMPLES - len) % TWAP_SAMPLES;
+        let mut weighted = 0u128;
+        let mut elapsed = 0u128;
+        for i in 0..len {
+            let obs = self.observations[(oldest + i) % TWAP_SAMPLES];
+            let until = if i + 1 < len {
+                self.observations[(oldest + i + 1) % TWAP_SAMPLES].timestamp
+            } else {
+                now
+            };
+            let duration = until.saturating_sub(obs.timestamp).max(0) as u128;
+            weighted += obs.price as u128 * duration;
+            elapsed += duration;
+        }
+        if elapsed == 0 {
+            return None;
+        }
+        u64::try_from(weighted / elapsed).ok()
+    }
+}
+
+/// Governed end-of-life migration from `old_mint` to `new_mint`. The PDA owns the
+/// swap escrows; it stays after finalization as the migration record.
+#[account]
+#[derive(InitSpace)]
+pub struct TokenMigration {
+    pub old_mint: Pubkey,
+    pub new_mint: Pubkey,
+    /// New tokens paid per `ratio_denominator` old tokens
+    pub ratio_numerator: u64,
+    pub ratio_denominator: u64,
+    /// Earliest time for swaps and finalization
+    pub eta: i64,
+    pub old_tokens_migrated: u64,
+    pub new_tokens_received: u64,
+    /// Zero until the treasury was redirected to `new_mint`
+    pub finalized_at: i64,
+    pub bump: u8,
+}
+
+impl TokenMigration {
+    /// New tokens owed for `old_amount` old tokens, rounded down
+    pub fn new_amount_for(&self, old_amount: u64) -> Result<u64> {
+        let amount = (old_amount as u128)
+            .checked_mul(self.ratio_numerator as u128)
+            .ok_or(ErrorCode::Overflow)?
+            / self.ratio_denominator as u128;
+        u64::try_from(amount).map_err(|_| ErrorCode::Overflow.into())
+    }
+}
+
+/// Usage log of one admin, operator, pauser or guardian key, for auditing and for
+/// spotting dormant keys to revoke. Instructions signed by these keys take the
+/// signer's log as an optional `*_activity` account, and record to it when it has
+/// been opened.
+#[account]
+#[derive(InitSpace)]
+pub struct KeyActivity {
+    pub key: Pubkey,
+    pub action_count: u64,
+    pub last_action: KeyAction,
+    /// Zero until the first recorded action
+    pub last_action_at: i64,
+    /// USDC moved by the key's buybacks, liquidity adds and conversions
+    pub total_volume_usdc: u64,
+    pub opened_at: i64,
+    pub bump: u8,
+}
+
+impl KeyActivity {
+    pub fn record(&mut self, action: KeyAction, volume_usdc: u64, now: i64) -> Result<()> {
+        self.action_count = self.action_count.saturating_add(1);
+        self.last_action = action;
+        self.last_action_at = now;
+        self.total_volume_usdc = self
+            .total_volume_usdc
+            .checked_add(volume_usdc)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+}
+
+/// Kind of action logged in a `KeyActivity`
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum KeyAction {
+    Config,
+    Emergency,
+    Buyback,
+    Liquidity,
+    Conversion,
+    Burn,
+    GuardianCoSign,
+}
+
+/// Pending timelocked configuration change
+#[account]
+#[derive(InitSpace)]
+pub struct ConfigProposal {
+    pub change: ConfigChange,
+    pub proposed_at: i64,
+    /// Earliest time `execute_config` applies the change
+    pub eta: i64,
+    pub bump: u8,
+}
+
+/// Configuration fields a proposal can change; `None` leaves a field as is
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Debug, PartialEq, Eq)]
+pub struct ConfigChange {
+    pub allocations: Option<AllocationSplit>,
+    pub max_usdc_per_cycle: Option<u64>,
+    pub cooldown_seconds: Option<i64>,
+    pub slippage_bps: Option<u16>,
+    pub keeper_reward_bps: Option<u16>,
+    pub keeper_reward_cap_usdc: Option<u64>,
+    pub config_delay_seconds: Option<i64>,
+    pub bounty_max_payout_usdc: Option<u64>,
+    pub bounty_timelock_seconds: Option<i64>,
+}
+
+impl ConfigChange {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(split) = &self.allocations {
+            split.validate()?;
+        }
+        if let Some(slippage) = self.slippage_bps {
+            require!(slippage <= 1000, ErrorCode::SlippageTooHigh);
+        }
+        if let Some(reward_bps) = self.keeper_reward_bps {
+            require!(
+                reward_bps <= MAX_KEEPER_REWARD_BPS,
+                ErrorCode::InvalidKeeperReward
+            );
+        }
+        if let Some(delay) = self.config_delay_seconds {
+            require!(delay >= 0, ErrorCode::InvalidAmount);
+        }
+        if let Some(timelock) = self.bounty_timelock_seconds {
+            require!(timelock >= 0, ErrorCode::InvalidDelay);
+        }
+        Ok(())
+    }
+}
+
+/// Split of converted USDC between buybacks, liquidity and the reserve
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AllocationSplit {
+    pub buyback_bps: u16,
+    pub liquidity_bps: u16,
+    pub reserve_bps: u16,
+}
+
+impl AllocationSplit {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.buyback_bps as u32 + self.liquidity_bps as u32 + self.reserve_bps as u32 == 10_000,
+            ErrorCode::InvalidAllocation
+        );
+        Ok(())
+    }
+}
+
+/// Treasury-funded liquidity bootstrapping pool. Prices follow a weighted pool
+/// whose token weight shifts over the sale window; the PDA owns both reserves
+/// and stays after settlement as the sale record.
+#[account]
+#[derive(InitSpace)]
+pub struct Lbp {
+    pub index: u64,
+    pub token_mint: Pubkey,
+    pub token_vault: Pubkey,
+    pub usdc_vault: Pubkey,
+    pub start_time: i64,
+    pub end_time: i64,
+    /// Token weight at `start_time` and `end_time`; USDC has the rest
+    pub start_weight_bps: u16,
+    pub end_weight_bps: u16,
+    pub token_reserve: u64,
+    pub usdc_reserve: u64,
+    /// USDC the treasury put in at creation
+    pub usdc_seeded: u64,
+    pub tokens_sold: u64,
+    pub usdc_raised: u64,
+    pub contribution_count: u64,
+    /// Zero until `settle_lbp`
+    pub settled_at: i64,
+    pub bump: u8,
+}
+
+impl Lbp {
+    /// Token weight at `now`, interpolated linearly over the sale window
+    pub fn token_weight_bps(&self, now: i64) -> u16 {
+        let duration = (self.end_time - self.start_time).max(1) as i128;
+        let elapsed = (now - self.start_time).clamp(0, duration as i64) as i128;
+        let start = self.start_weight_bps as i128;
+        let end = self.end_weight_bps as i128;
+        (start + (end - start) * elapsed / duration) as u16
+    }
+
+    /// Tokens paid for `usdc_in` at token weight `weight_bps`: constant product
+    /// against the USDC reserve scaled by `weight / (10000 - weight)`, which has the
+    /// weighted pool's spot price, rounded down
+    pub fn tokens_out(&self, usdc_in: u64, weight_bps: u16) -> Result<u64> {
+        let virtual_usdc = (self.usdc_reserve as u128)
+            .checked_mul(weight_bps as u128)
+            .ok_or(ErrorCode::Overflow)?
+            / (10_000 - weight_bps as u128);
+        let out = (self.token_reserve as u128)
+            .checked_mul(usdc_in as u128)
+            .ok_or(ErrorCode::Overflow)?
+            / virtual_usdc
+                .checked_add(usdc_in as u128)
+                .ok_or(ErrorCode::Overflow)?;
+        u64::try_from(out).map_err(|_| ErrorCode::Overflow.into())
+    }
+}
+
+/// Allowlisted destination for reserve withdrawals, one per USDC token account.
+/// Replaced by `AddressBookEntry`; existing entries can only be closed.
+#[account]
+#[derive(InitSpace)]
+pub struct ReserveDestination {
+    pub destination: Pubkey,
+    pub registered_at: i64,
+    pub bump: u8,
+}
+
+/// Reserve withdrawal waiting out `Treasury::reserve_withdrawal_delay_seconds`
+#[account]
+#[derive(InitSpace)]
+pub struct ReserveWithdrawal {
+    pub index: u64,
+    pub destination: Pubkey,
+    pub usdc_amount: u64,
+    pub queued_at: i64,
+    /// Earliest time `withdraw_reserve` can execute it
+    pub eta: i64,
+    pub bump: u8,
+}
+
+/// Execution price of the last settled buyback, for wallets and partner UIs to
+/// display without an indexer
+#[account]
+#[derive(InitSpace)]
+pub struct QuoteBoard {
+    /// Micro-USD per whole USDCBALL paid by the last settled buyback
+    pub last_price: u64,
+    pub last_usdc_amount: u64,
+    pub last_token_amount: u64,
+    pub last_slot: u64,
+    pub last_timestamp: i64,
+    /// Buybacks published so far
+    pub updates: u64,
+    pub token_decimals: u8,
+    pub bump: u8,
+}
+
+impl QuoteBoard {
+    pub fn record(&mut self, usdc_amount: u64, token_amount: u64, clock: &Clock) {
+        let Some(price) = Twap::spot_price(usdc_amount, token_amount, self.token_decimals) else {
+            return;
+        };
+        self.last_price = price;
+        self.last_usdc_amount = usdc_amount;
+        self.last_token_amount = token_amount;
+        self.last_slot = clock.slot;
+        self.last_timestamp = clock.unix_timestamp;
+        self.updates = self.updates.saturating_add(1);
+    }
+}
+
+/// Commemorative record of a lifetime total crossing a milestone, with the
+/// treasury's totals at the time it was recorded
+#[account]
+#[derive(InitSpace)]
+pub struct Milestone {
+    pub kind: MilestoneKind,
+    /// Total the milestone marks: tokens for burns, USDC for buybacks
+    pub threshold: u64,
+    pub total_tokens_burned: u64,
+    pub total_buybacks_usdc: u64,
+    pub total_usdc_converted: u64,
+    pub epoch: u64,
+    pub slot: u64,
+    pub timestamp: i64,
+    pub recorded_by: Pubkey,
+    pub bump: u8,
+}
+
+/// Lifetime total a milestone tracks
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MilestoneKind {
+    Burns,
+    Buybacks,
+}
+
+/// How quickly one guardian key responds to circuit-breaker trips and config
+/// proposals, for the DAO to weigh when electing or rotating guardians
+#[account]
+#[derive(InitSpace)]
+pub struct GuardianRecord {
+    pub guardian: Pubkey,
+    pub responses: u64,
+    /// Sum of all response times; divided by `responses` for the average
+    pub total_response_seconds: u64,
+    pub last_response_seconds: u64,
+    pub slowest_response_seconds: u64,
+    /// Config proposals the guardian vetoed
+    pub vetoes: u64,
+    pub opened_at: i64,
+    pub bump: u8,
+}
+
+impl GuardianRecord {
+    pub fn record_response(&mut self, response_seconds: i64, vetoed: bool) {
+        let response_seconds = response_seconds.max(0) as u64;
+        self.responses = self.responses.saturating_add(1);
+        self.total_response_seconds = self.total_response_seconds.saturating_add(response_seconds);
+        self.last_response_seconds = response_seconds;
+        self.slowest_response_seconds = self.slowest_response_seconds.max(response_seconds);
+        if vetoed {
+            self.vetoes = self.vetoes.saturating_add(1);
+        }
+    }
+
+    /// Mean response time, zero before the first response
+    pub fn average_response_seconds(&self) -> u64 {
+        self.total_response_seconds
+            .checked_div(self.responses)
+            .unwrap_or(0)
+    }
+}
+
+/// What a guardian responded to
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GuardianReview {
+    CircuitBreaker,
+    ConfigProposal,
+}
+
+/// A treasury Orca Whirlpool position, keyed by its position NFT mint, with the
+/// liquidity and amounts that went in and out of it
+#[account]
+#[derive(InitSpace)]
+pub struct LiquidityPosition {
+    pub whirlpool: Pubkey,
+    pub position_mint: Pubkey,
+    /// Whirlpool's position account
+    pub position: Pubkey,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity: u128,
+    pub usdc_deposited: u64,
+    pub token_deposited: u64,
+    pub usdc_withdrawn: u64,
+    pub token_withdrawn: u64,
+    pub usdc_fees_collected: u64,
+    pub token_fees_collected: u64,
+    pub opened_at: i64,
+    pub bump: u8,
+}
+
+impl LiquidityPosition {
+    pub fn add_liquidity(
+        &mut self,
+        liquidity: u128,
+        usdc_amount: u64,
+        token_amount: u64,
+    ) -> Result<()> {
+        self.liquidity = self
+            .liquidity
+            .checked_add(liquidity)
+            .ok_or(ErrorCode::Overflow)?;
+        self.usdc_deposited = self
+            .usdc_deposited
+            .checked_add(usdc_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        self.token_deposited = self
+            .token_deposited
+            .checked_add(token_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+
+    pub fn remove_liquidity(
+        &mut self,
+        liquidity: u128,
+        usdc_amount: u64,
+        token_amount: u64,
+    ) -> Result<()> {
+        self.liquidity = self
+            .liquidity
+            .checked_sub(liquidity)
+            .ok_or(ErrorCode::InsufficientPositionLiquidity)?;
+        self.usdc_withdrawn = self
+            .usdc_withdrawn
+            .checked_add(usdc_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        self.token_withdrawn = self
+            .token_withdrawn
+            .checked_add(token_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+
+    pub fn record_fees(&mut self, usdc_amount: u64, token_amount: u64) -> Result<()> {
+        self.usdc_fees_collected = self
+            .usdc_fees_collected
+            .checked_add(usdc_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        self.token_fees_collected = self
+            .token_fees_collected
+            .checked_add(token_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+}
+
+/// The treasury's liquidity in a constant-product pool, keyed by the pool's vaults,
+/// with the LP tokens it holds and the amounts that went in and out of the pool
+#[account]
+#[derive(InitSpace)]
+pub struct LpPosition {
+    pub venue: Venue,
+    pub pool_usdc: Pubkey,
+    pub pool_token: Pubkey,
+    pub lp_mint: Pubkey,
+    /// LP tokens in the treasury's LP account as of the last deposit or withdrawal
+    pub lp_amount: u64,
+    pub lp_withdrawn: u64,
+    pub usdc_contributed: u64,
+    pub token_contributed: u64,
+    pub usdc_withdrawn: u64,
+    pub token_withdrawn: u64,
+    pub usdc_fees_collected: u64,
+    pub token_fees_collected: u64,
+    pub opened_at: i64,
+    pub bump: u8,
+}
+
+impl LpPosition {
+    pub fn record_deposit(
+        &mut self,
+        usdc_amount: u64,
+        token_amount: u64,
+        lp_balance: u64,
+    ) -> Result<()> {
+        self.usdc_contributed = self
+            .usdc_contributed
+            .checked_add(usdc_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        self.token_contributed = self
+            .token_contributed
+            .checked_add(token_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        self.lp_amount = lp_balance;
+        Ok(())
+    }
+
+    pub fn record_withdrawal(
+        &mut self,
+        lp_amount: u64,
+        usdc_amount: u64,
+        token_amount: u64,
+        lp_balance: u64,
+    ) -> Result<()> {
+        self.lp_withdrawn = self
+            .lp_withdrawn
+            .checked_add(lp_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        self.usdc_withdrawn = self
+            .usdc_withdrawn
+            .checked_add(usdc_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        self.token_withdrawn = self
+            .token_withdrawn
+            .checked_add(token_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        self.lp_amount = lp_balance;
+        Ok(())
+    }
+
+    /// Record a fee claim. The LP tokens burned count as withdrawn; the amounts
+    /// received do not reduce the net contribution.
+    pub fn record_fees(
+        &mut self,
+        lp_amount: u64,
+        usdc_amount: u64,
+        token_amount: u64,
+        lp_balance: u64,
+    ) -> Result<()> {
+        self.lp_withdrawn = self
+            .lp_withdrawn
+            .checked_add(lp_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        self.usdc_fees_collected = self
+            .usdc_fees_collected
+            .checked_add(usdc_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        self.token_fees_collected = self
+            .token_fees_collected
+            .checked_add(token_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        self.lp_amount = lp_balance;
+        Ok(())
+    }
+
+    /// Whether `lp_amount` of `lp_supply` LP tokens are worth at least the net USDC
+    /// and tokens contributed, valued in USDC at the pool price. A constant-product
+    /// pool holds equal value on both sides, so the share is worth twice its USDC.
+    pub fn covers_principal(
+        &self,
+        lp_amount: u64,
+        lp_supply: u64,
+        pool_usdc: u64,
+        pool_token: u64,
+    ) -> bool {
+        if lp_supply == 0 || pool_token == 0 {
+            return false;
+        }
+        let value = 2 * lp_amount as u128 * pool_usdc as u128 / lp_supply as u128;
+        let net_usdc = self.usdc_contributed.saturating_sub(self.usdc_withdrawn) as u128;
+        let net_token = self.token_contributed.saturating_sub(self.token_withdrawn) as u128;
+        value >= net_usdc + net_token * pool_usdc as u128 / pool_token as u128
+    }
+}
+
+/// LP tokens locked out of the treasury's reach until `locked_until`, held in
+/// `vault` under this PDA's authority. Closed by `release_lp` once it expires.
+#[account]
+#[derive(InitSpace)]
+pub struct LpLock {
+    /// Seeds the PDA; the treasury's `lp_lock_count` when the lock was made
+    pub index: u64,
+    pub lp_mint: Pubkey,
+    pub vault: Pubkey,
+    pub amount: u64,
+    pub locked_at: i64,
+    pub locked_until: i64,
+    pub bump: u8,
+}
+
+/// Whitelisted third-party strategy adapter (see `strategy`), one per program.
+/// Reserve USDC can be deposited into it from `eta` on, up to `cap_usdc`.
+#[account]
+#[derive(InitSpace)]
+pub struct StrategyAdapter {
+    pub program: Pubkey,
+    pub cap_usdc: u64,
+    /// Reserve USDC deposited and not yet withdrawn, excluding gains
+    pub deposited_usdc: u64,
+    /// Last value the adapter reported for the treasury's position
+    pub reported_value_usdc: u64,
+    pub reported_at: i64,
+    pub proposed_at: i64,
+    /// Earliest time for deposits
+    pub eta: i64,
+    pub bump: u8,
+}
+
+/// Address book entry: a USDC token account reserve draws and withdrawals may pay
+/// out to, with the label reported in their events
+#[account]
+#[derive(InitSpace)]
+pub struct AddressBookEntry {
+    pub destination: Pubkey,
+    #[max_len(MAX_ADDRESS_LABEL_LEN)]
+    pub label: String,
+    pub added_at: i64,
+    /// Earliest time payments can target the entry
+    pub active_at: i64,
+    pub bump: u8,
+}
+
+impl AddressBookEntry {
+    pub fn require_active(&self, now: i64) -> Result<()> {
+        require!(now >= self.active_at, ErrorCode::AddressBookEntryPending);
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct TreasuryInitialized {
+    pub authority: Pubkey,
+    pub buyback_allocation_bps: u16,
+    pub liquidity_allocation_bps: u16,
+    pub reserve_allocation_bps: u16,
+    pub usdc_mint: Pubkey,
+    pub token_mint: Pubkey,
+    pub token_decimals: u8,
+    pub token_mint_authority: Option<Pubkey>,
+}
+
+#[event]
+pub struct TransferFeeRecorded {
+    pub source: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    /// Token-2022 transfer fee withheld on `amount`
+    pub fee: u64,
+    pub total_accrued: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeeRecorded {
+    pub amount: u64,
+    pub total_collected: u64,
+    /// Micro-USD value at the oracle price; 0 while no oracle is configured
+    pub usd_value: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeeForwarded {
+    /// Program that forwarded the fee by CPI
+    pub source_program: Pubkey,
+    pub lamports: u64,
+    pub usdc_amount: u64,
+    /// Micro-USD value of `lamports` at the oracle price; 0 without an oracle
+    pub usd_value: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct UsdcConverted {
+    pub amount: u64,
+    pub total_converted: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BuybackExecuted {
+    /// Amount actually executed; lower than requested for partial fills
+    pub usdc_amount: u64,
+    pub requested_usdc_amount: u64,
+    pub min_tokens_out: u64,
+    pub tokens_received: u64,
+    /// Realized and pre-trade reference price in micro-USD per whole token; only
+    /// `execute_buyback` measures them (reference 0 without an oracle or TWAP)
+    pub execution_price: u64,
+    pub reference_price: u64,
+    /// Execution was worse than the reference by more than `settlement_max_deviation_bps`
+    pub degraded_execution: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LiquidityAdded {
+    pub usdc_amount: u64,
+    pub token_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EmergencyPaused {
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OperationsResumed {
+    pub timestamp: i64,
+    pub warmup_until: i64,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SkippedOperation {
+    pub instruction: GatedInstruction,
+    pub reason: SkipReason,
+    pub caller: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InstructionToggled {
+    pub instruction: GatedInstruction,
+    pub enabled: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DividendShareUpdated {
+    pub old_share_bps: u16,
+    pub new_share_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DividendDistributionCreated {
+    pub epoch: u64,
+    pub merkle_root: [u8; 32],
+    pub usdc_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DividendClaimed {
+    pub epoch: u64,
+    pub claimant: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CosignPolicyUpdated {
+    pub co_signer: Pubkey,
+    pub threshold_usdc: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BountyEscrowInitialized {
+    pub vault: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BountyPolicyUpdated {
+    pub max_payout_usdc: u64,
+    pub timelock_seconds: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BountyEscrowFunded {
+    pub usdc_amount: u64,
+    pub total_funded: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BountyPayoutProposed {
+    pub index: u64,
+    pub recipient: Pubkey,
+    pub usdc_amount: u64,
+    pub executable_at: i64,
+}
+
+#[event]
+pub struct BountyPaid {
+    pub index: u64,
+    pub recipient: Pubkey,
+    pub usdc_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BountyPayoutCancelled {
+    pub index: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryStalled {
+    pub idle_seconds: i64,
+    pub buyback_budget: u64,
+    pub liquidity_budget: u64,
+    pub caller: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OperationOpened {
+    pub index: u64,
+    pub kind: OperationKind,
+    pub venue: Venue,
+    pub usdc_amount: u64,
+    pub deadline_slot: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OperationSettled {
+    pub index: u64,
+    pub kind: OperationKind,
+    pub usdc_amount: u64,
+    pub destination: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OperationRefunded {
+    pub index: u64,
+    pub kind: OperationKind,
+    pub usdc_amount: u64,
+    /// Escrow balance above `usdc_amount`, swept without crediting an allocation
+    pub surplus_usdc: u64,
+    pub caller: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OperationReceiptCommitted {
+    pub index: u64,
+    pub kind: OperationKind,
+    pub receipt: Pubkey,
+    pub mint: Pubkey,
+    pub min_output: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OperationReceiptSettled {
+    pub index: u64,
+    pub kind: OperationKind,
+    pub received: u64,
+    pub min_output: u64,
+    /// Swept after the claim window with less than `min_output`
+    pub short: bool,
+    pub destination: Pubkey,
+    pub caller: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OperatorUpdated {
+    pub operator: Pubkey,
+    pub active: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RouteEscrowInitialized {
+    pub venue: Venue,
+    pub mint: Pubkey,
+    pub escrow: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RouteResidualSwept {
+    pub venue: Venue,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub caller: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EpochFunnelRecorded {
+    pub epoch: u64,
+    pub funnel: ConversionFunnel,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EpochAdvanced {
+    pub epoch: u64,
+    pub burn_target: u64,
+    pub tokens_burned: u64,
+    pub shortfall: u64,
+    pub caller: Pubkey,
+    pub tip_lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EpochRolled {
+    pub epoch: u64,
+    pub usdc_converted: u64,
+    pub buybacks_usdc: u64,
+    pub liquidity_usdc: u64,
+    pub reserve_usdc: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReservePolicyUpdated {
+    pub guardian: Pubkey,
+    pub governance: Pubkey,
+    pub ops_monthly_limit_usdc: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReserveDrawn {
+    pub tier: ReserveTier,
+    pub usdc_amount: u64,
+    pub destination: Pubkey,
+    pub label: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReservesAttested {
+    pub epoch: u64,
+    pub holdings: u8,
+    pub hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConditionalBuybackPlaced {
+    pub index: u64,
+    pub usdc_amount: u64,
+    pub max_price_micro_usd: u64,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConditionalBuybackClosed {
+    pub index: u64,
+    pub executed: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultMigrationProposed {
+    pub successor: Pubkey,
+    pub guardian: Pubkey,
+    pub eta: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultMigrationCancelled {
+    pub successor: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultAuthorityMigrated {
+    pub vault: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub successor: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OperatorHeartbeat {
+    pub operator: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolRegistryUpdated {
+    pub venue: Venue,
+    pub usdc_vault: Pubkey,
+    pub token_vault: Pubkey,
+    pub registered: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CampaignCreated {
+    pub index: u64,
+    pub pool: Pubkey,
+    pub lp_mint: Pubkey,
+    pub reward_budget: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CampaignCancelled {
+    pub index: u64,
+    pub refunded: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LpStaked {
+    pub campaign: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub total_staked: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LpUnstaked {
+    pub campaign: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub total_staked: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CampaignRewardsClaimed {
+    pub campaign: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LargeOperationAlert {
+    pub operation: AlertedOperation,
+    pub usdc_amount: u64,
+    pub threshold: u64,
+    pub initiator: Pubkey,
+    pub destination: Pubkey,
+    pub vault_balance_before: u64,
+    pub slot: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RouteSelected {
+    pub venue: Venue,
+    pub pool_usdc: Pubkey,
+    pub candidates: u8,
+    pub expected_tokens_out: u64,
+    pub runner_up_tokens_out: u64,
+    pub tokens_received: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DepegDetected {
+    pub price_micro_usd: u64,
+    pub deviation_bps: u64,
+    pub threshold_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokensBurned {
+    pub amount: u64,
+    pub total_burned: u64,
+    pub epoch: u64,
+    pub epoch_burned: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct HolderSnapshotRecorded {
+    pub epoch: u64,
+    pub root: [u8; 32],
+    pub holder_count: u32,
+    pub total_balance: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MarketMakerUpdated {
+    pub market_maker: Pubkey,
+    pub active: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InventoryBorrowed {
+    pub market_maker: Pubkey,
+    pub collateral_added: u64,
+    pub tokens_borrowed: u64,
+    pub collateral_usdc: u64,
+    pub borrowed_tokens: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InventoryRepaid {
+    pub market_maker: Pubkey,
+    pub tokens_repaid: u64,
+    pub collateral_withdrawn: u64,
+    pub collateral_usdc: u64,
+    pub borrowed_tokens: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MarketMakerLiquidated {
+    pub market_maker: Pubkey,
+    pub collateral_seized: u64,
+    pub tokens_written_off: u64,
+    pub borrowed_value_usd: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VolumeCapUpdated {
+    pub cap_usdc: u64,
+    pub pending_cap_usdc: u64,
+    pub eta: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OutflowCapUpdated {
+    pub cap_usdc: u64,
+    pub pending_cap_usdc: u64,
+    pub eta: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MintConfigChanged {
+    pub mint_authority: Option<Pubkey>,
+    pub freeze_authority: Option<Pubkey>,
+    pub expected_mint_authority: Option<Pubkey>,
+    pub expected_freeze_authority: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CircuitBreakerTripped {
+    pub degraded_executions: u16,
+    /// Prices of the execution that tripped the breaker, in micro-USD per whole token
+    pub reference_price: u64,
+    pub execution_price: u64,
+    /// How far `execution_price` was above `reference_price`
+    pub deviation_bps: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeeBatchRecorded {
+    pub depositor: Pubkey,
+    pub entries: Vec<FeeEntry>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct KeeperRewardPaid {
+    pub keeper: Pubkey,
+    pub usdc_amount: u64,
+    /// USDC the crank swapped and the reward rate applied to it
+    pub cranked_usdc_amount: u64,
+    pub reward_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OperatorScopeUpdated {
+    pub operator: Pubkey,
+    pub allowed_kinds: u8,
+    pub allowed_venues: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeeCounterSynced {
+    pub lamports: u64,
+    pub total_collected: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokenMigrationProposed {
+    pub old_mint: Pubkey,
+    pub new_mint: Pubkey,
+    pub ratio_numerator: u64,
+    pub ratio_denominator: u64,
+    pub guardian: Pubkey,
+    pub eta: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokenMigrationCancelled {
+    pub old_mint: Pubkey,
+    pub new_mint: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryTokensMigrated {
+    pub old_mint: Pubkey,
+    pub new_mint: Pubkey,
+    pub old_amount: u64,
+    pub new_amount: u64,
+    pub ratio_numerator: u64,
+    pub ratio_denominator: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokenMigrationFinalized {
+    pub old_mint: Pubkey,
+    pub new_mint: Pubkey,
+    pub old_tokens_migrated: u64,
+    pub new_tokens_received: u64,
+    pub guardian: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminRoleSet {
+    pub previous: Pubkey,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OperatorRoleSet {
+    pub previous: Pubkey,
+    pub operator: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PauserRoleSet {
+    pub previous: Pubkey,
+    pub pauser: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolLimitsUpdated {
+    pub venue: Venue,
+    pub usdc_vault: Pubkey,
+    pub token_vault: Pubkey,
+    pub slippage_bps: u16,
+    pub max_usdc_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SquadsMultisigBound {
+    pub multisig: Pubkey,
+    pub vault: Pubkey,
+    pub vault_index: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConfigProposed {
+    pub change: ConfigChange,
+    pub eta: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConfigExecuted {
+    pub change: ConfigChange,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConfigProposalCancelled {
+    pub change: ConfigChange,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RealmsGovernanceBound {
+    pub realm: Pubkey,
+    pub governance: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AllocationsUpdated {
+    /// Realms governance, or the admin executing a config proposal
+    pub updated_by: Pubkey,
+    pub previous: AllocationSplit,
+    pub allocations: AllocationSplit,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LbpCreated {
+    pub index: u64,
+    pub token_amount: u64,
+    pub usdc_amount: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub start_weight_bps: u16,
+    pub end_weight_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LbpContribution {
+    pub index: u64,
+    pub contributor: Pubkey,
+    pub usdc_amount: u64,
+    pub tokens_out: u64,
+    pub token_weight_bps: u16,
+    pub timestamp: i64,
 }
 
-// ============================================================================
-// Accounts
-// ============================================================================
+#[event]
+pub struct LbpSettled {
+    pub index: u64,
+    pub usdc_returned: u64,
+    pub tokens_returned: u64,
+    pub usdc_seeded: u64,
+    pub usdc_raised: u64,
+    pub tokens_sold: u64,
+    pub timestamp: i64,
+}
 
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + Treasury::INIT_SPACE,
-        seeds = [b"treasury"],
-        bump
-    )]
-    pub treasury: Account<'info, Treasury>,
+#[event]
+pub struct OperationTypePaused {
+    pub flag: PauseFlag,
+    /// Flags after the change
+    pub pause_flags: u8,
+    pub by: Pubkey,
+    pub timestamp: i64,
+}
 
-    #[account(mut)]
-    pub authority: Signer<'info>,
+#[event]
+pub struct OperationTypeResumed {
+    pub flag: PauseFlag,
+    /// Flags after the change
+    pub pause_flags: u8,
+    pub timestamp: i64,
+}
 
-    pub system_program: Program<'info, System>,
+#[event]
+pub struct ReserveDestinationUpdated {
+    pub destination: Pubkey,
+    pub registered: bool,
+    pub timestamp: i64,
 }
 
-#[derive(Accounts)]
-pub struct RecordFee<'info> {
-    #[account(
-        mut,
-        seeds = [b"treasury"],
-        bump = treasury.bump,
-    )]
-    pub treasury: Account<'info, Treasury>,
+#[event]
+pub struct ReserveWithdrawalQueued {
+    pub index: u64,
+    pub destination: Pubkey,
+    pub label: String,
+    pub usdc_amount: u64,
+    pub eta: i64,
+    pub timestamp: i64,
 }
 
-#[derive(Accounts)]
-pub struct ExecuteBuyback<'info> {
-    #[account(
-        mut,
-        seeds = [b"treasury"],
-        bump = treasury.bump,
-    )]
-    pub treasury: Account<'info, Treasury>,
+#[event]
+pub struct ReserveWithdrawn {
+    pub index: u64,
+    pub destination: Pubkey,
+    pub label: String,
+    pub usdc_amount: u64,
+    pub total_reserve_spent: u64,
+    pub timestamp: i64,
+}
 
-    #[account(mut)]
-    pub treasury_usdc: Account<'info, TokenAccount>,
+#[event]
+pub struct ReserveWithdrawalCancelled {
+    pub index: u64,
+    pub timestamp: i64,
+}
 
-    #[account(mut)]
-    pub destination_usdc: Account<'info, TokenAccount>,
+#[event]
+pub struct KaminoDeposited {
+    pub reserve: Pubkey,
+    pub usdc_amount: u64,
+    pub collateral_minted: u64,
+    pub total_deposited_usdc: u64,
+    pub timestamp: i64,
+}
 
-    #[account(
-        constraint = authority.key() == treasury.authority
-    )]
-    pub authority: Signer<'info>,
+#[event]
+pub struct KaminoWithdrawn {
+    pub reserve: Pubkey,
+    pub collateral_amount: u64,
+    pub usdc_received: u64,
+    pub principal_usdc: u64,
+    pub interest_usdc: u64,
+    pub timestamp: i64,
+}
 
-    pub token_program: Program<'info, Token>,
+#[event]
+pub struct MarginFiDeposited {
+    pub bank: Pubkey,
+    pub usdc_amount: u64,
+    pub total_deposited_usdc: u64,
+    pub timestamp: i64,
 }
 
-#[derive(Accounts)]
-pub struct AddLiquidity<'info> {
-    #[account(
-        mut,
-        seeds = [b"treasury"],
-        bump = treasury.bump,
-    )]
-    pub treasury: Account<'info, Treasury>,
+#[event]
+pub struct MarginFiWithdrawn {
+    pub bank: Pubkey,
+    pub usdc_received: u64,
+    pub principal_usdc: u64,
+    pub interest_usdc: u64,
+    pub timestamp: i64,
+}
 
-    #[account(mut)]
-    pub treasury_usdc: Account<'info, TokenAccount>,
+#[event]
+pub struct GuardianResponded {
+    pub guardian: Pubkey,
+    pub review: GuardianReview,
+    pub response_seconds: u64,
+    pub vetoed: bool,
+    pub average_response_seconds: u64,
+    pub timestamp: i64,
+}
 
-    #[account(mut)]
-    pub pool_usdc: Account<'info, TokenAccount>,
+#[event]
+pub struct LaunchModeEnded {
+    /// First epoch with the regular limits
+    pub epoch: u64,
+    pub timestamp: i64,
+}
 
-    #[account(
-        constraint = authority.key() == treasury.authority
-    )]
-    pub authority: Signer<'info>,
+#[event]
+pub struct SolendDeposited {
+    pub reserve: Pubkey,
+    pub usdc_amount: u64,
+    pub collateral_minted: u64,
+    pub total_deposited_usdc: u64,
+    pub timestamp: i64,
+}
 
-    pub token_program: Program<'info, Token>,
+#[event]
+pub struct SolendWithdrawn {
+    pub reserve: Pubkey,
+    pub collateral_amount: u64,
+    pub usdc_received: u64,
+    pub principal_usdc: u64,
+    pub interest_usdc: u64,
+    pub timestamp: i64,
 }
 
-#[derive(Accounts)]
-pub struct RecordConversion<'info> {
-    #[account(
-        mut,
-        seeds = [b"treasury"],
-        bump = treasury.bump,
-    )]
-    pub treasury: Account<'info, Treasury>,
+#[event]
+pub struct SupplySnapshotRecorded {
+    pub supply: u64,
+    pub total_burned: u64,
+    pub slot: u64,
+}
 
-    #[account(
-        constraint = authority.key() == treasury.authority
-    )]
-    pub authority: Signer<'info>,
+#[event]
+pub struct ExternalBurnRegistered {
+    pub amount: u64,
+    pub burn_signature: [u8; 64],
+    /// Mint supply when the burn was registered
+    pub supply: u64,
+    pub total_burned: u64,
+    pub total_external_burned: u64,
+    pub timestamp: i64,
 }
 
-#[derive(Accounts)]
-pub struct EmergencyAction<'info> {
-    #[account(
-        mut,
-        seeds = [b"treasury"],
-        bump = treasury.bump,
-    )]
-    pub treasury: Account<'info, Treasury>,
+#[event]
+pub struct YieldHarvested {
+    pub venue: LendingVenue,
+    pub usdc_amount: u64,
+    pub total_harvested_usdc: u64,
+    pub total_converted: u64,
+    pub timestamp: i64,
+}
 
-    #[account(
-        constraint = authority.key() == treasury.authority
-    )]
-    pub authority: Signer<'info>,
+#[event]
+pub struct ClmmPositionOpened {
+    pub pool: Pubkey,
+    pub position_mint: Pubkey,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub timestamp: i64,
 }
 
-#[derive(Accounts)]
-pub struct UpdateConfig<'info> {
-    #[account(
-        mut,
-        seeds = [b"treasury"],
-        bump = treasury.bump,
-    )]
-    pub treasury: Account<'info, Treasury>,
+#[event]
+pub struct ClmmLiquidityAdded {
+    pub position_mint: Pubkey,
+    pub liquidity: u128,
+    pub usdc_amount: u64,
+    pub token_amount: u64,
+    pub total_liquidity: u128,
+    pub timestamp: i64,
+}
 
-    #[account(
-        constraint = authority.key() == treasury.authority
-    )]
-    pub authority: Signer<'info>,
+#[event]
+pub struct MilestoneReached {
+    pub milestone: Pubkey,
+    pub kind: MilestoneKind,
+    pub threshold: u64,
+    pub total_tokens_burned: u64,
+    pub total_buybacks_usdc: u64,
+    pub timestamp: i64,
 }
 
-// ============================================================================
-// State
-// ============================================================================
+#[event]
+pub struct WhirlpoolPositionOpened {
+    pub whirlpool: Pubkey,
+    pub position_mint: Pubkey,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub timestamp: i64,
+}
 
-#[account]
-#[derive(InitSpace)]
-pub struct Treasury {
-    pub authority: Pubkey,
-    pub buyback_allocation_bps: u16,
-    pub liquidity_allocation_bps: u16,
-    pub reserve_allocation_bps: u16,
-    pub max_usdc_per_cycle: u64,
-    pub cooldown_seconds: i64,
-    pub slippage_bps: u16,
-    pub total_sol_collected: u64,
-    pub total_usdc_converted: u64,
-    pub total_buybacks_usdc: u64,
-    pub total_liquidity_usdc: u64,
-    pub total_tokens_burned: u64,
-    pub last_operation_timestamp: i64,
-    pub paused: bool,
-    pub bump: u8,
+#[event]
+pub struct WhirlpoolLiquidityChanged {
+    pub position_mint: Pubkey,
+    pub liquidity_delta: u128,
+    pub increased: bool,
+    pub usdc_amount: u64,
+    pub token_amount: u64,
+    pub total_liquidity: u128,
+    pub timestamp: i64,
 }
 
-// ============================================================================
-// Events
-// ============================================================================
+#[event]
+pub struct WhirlpoolFeesCollected {
+    pub position_mint: Pubkey,
+    pub usdc_amount: u64,
+    pub token_amount: u64,
+    pub total_usdc_fees: u64,
+    pub total_token_fees: u64,
+    pub timestamp: i64,
+}
 
 #[event]
-pub struct TreasuryInitialized {
-    pub authority: Pubkey,
-    pub buyback_allocation_bps: u16,
-    pub liquidity_allocation_bps: u16,
-    pub reserve_allocation_bps: u16,
+pub struct PoolTypeUpdated {
+    pub venue: Venue,
+    pub usdc_vault: Pubkey,
+    pub token_vault: Pubkey,
+    pub pool_type: PoolType,
+    pub timestamp: i64,
 }
 
 #[event]
-pub struct FeeRecorded {
+pub struct MeteoraPositionOpened {
+    pub lb_pair: Pubkey,
+    pub position: Pubkey,
+    pub lower_bin_id: i32,
+    pub upper_bin_id: i32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MeteoraLiquidityAdded {
+    pub position: Pubkey,
+    pub usdc_amount: u64,
+    pub total_usdc_deposited: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LpPositionOpened {
+    pub venue: Venue,
+    pub pool_usdc: Pubkey,
+    pub pool_token: Pubkey,
+    pub lp_mint: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LiquidityRemoved {
+    pub pool_usdc: Pubkey,
+    pub pool_token: Pubkey,
+    pub lp_amount: u64,
+    pub usdc_amount: u64,
+    pub token_amount: u64,
+    /// LP tokens the treasury still holds for the pool
+    pub lp_remaining: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryMigrated {
+    pub old_size: u64,
+    pub new_size: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LpFeesCollected {
+    pub pool_usdc: Pubkey,
+    pub pool_token: Pubkey,
+    pub lp_amount: u64,
+    pub usdc_amount: u64,
+    pub token_amount: u64,
+    pub total_lp_fees_earned: u64,
+    pub timestamp: i64,
+}
+
+/// LP tokens made unwithdrawable: time-locked in `lock` until `locked_until`, or
+/// burned (`lock` and `locked_until` zero)
+#[event]
+pub struct LpLocked {
+    pub lp_mint: Pubkey,
+    pub lock: Pubkey,
     pub amount: u64,
-    pub total_collected: u64,
+    pub locked_until: i64,
+    pub burned: bool,
+    pub total_lp_burned: u64,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct UsdcConverted {
+pub struct LpReleased {
+    pub lp_mint: Pubkey,
+    pub lock: Pubkey,
     pub amount: u64,
-    pub total_converted: u64,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct BuybackExecuted {
+pub struct StrategyAdapterProposed {
+    pub program: Pubkey,
+    pub cap_usdc: u64,
+    pub eta: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StrategyAdapterCapSet {
+    pub program: Pubkey,
+    pub cap_usdc: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StrategyAdapterRemoved {
+    pub program: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StrategyDeposited {
+    pub program: Pubkey,
     pub usdc_amount: u64,
-    pub min_tokens_out: u64,
+    pub deposited_usdc: u64,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct LiquidityAdded {
+pub struct StrategyWithdrawn {
+    pub program: Pubkey,
     pub usdc_amount: u64,
-    pub token_amount: u64,
+    pub principal_usdc: u64,
+    pub gain_usdc: u64,
+    pub deposited_usdc: u64,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct EmergencyPaused {
+pub struct StrategyValueReported {
+    pub program: Pubkey,
+    pub value_usdc: u64,
+    pub deposited_usdc: u64,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct OperationsResumed {
+pub struct AddressBookEntryAdded {
+    pub destination: Pubkey,
+    pub label: String,
+    pub active_at: i64,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct ConfigUpdated {
+pub struct AddressBookEntryRemoved {
+    pub destination: Pubkey,
+    pub label: String,
+    pub timestamp: i64,
+}
+
+/// Withheld transfer fees swept by `harvest_withheld_fees` and burned
+#[event]
+pub struct HarvestedFees {
+    pub amount: u64,
+    pub total_harvested: u64,
+    pub total_burned: u64,
+    pub source_accounts: u16,
     pub timestamp: i64,
 }
 
@@ -495,4 +16904,248 @@ pub enum ErrorCode {
     ExceedsAllocation,
     #[msg("Arithmetic overflow")]
     Overflow,
+    #[msg("Instruction is disabled")]
+    InstructionDisabled,
+    #[msg("Dividend share must be at most 10000 bps")]
+    InvalidDividendShare,
+    #[msg("Dividends are disabled")]
+    DividendsDisabled,
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+    #[msg("Invalid Merkle proof")]
+    InvalidMerkleProof,
+    #[msg("Co-signer must differ from the authority")]
+    InvalidCoSigner,
+    #[msg("Operation above the co-sign threshold requires the co-signer")]
+    CoSignerRequired,
+    #[msg("Post-resume warm-up period not over")]
+    WarmupActive,
+    #[msg("Delay must not be negative")]
+    InvalidDelay,
+    #[msg("Bounty payout exceeds the per-payout cap")]
+    ExceedsBountyCap,
+    #[msg("Bounty payout already executed")]
+    BountyAlreadyExecuted,
+    #[msg("Timelock has not elapsed")]
+    TimelockActive,
+    #[msg("Recipient does not match the queued bounty payout")]
+    BountyRecipientMismatch,
+    #[msg("USDC account is not the vault the operation was funded from")]
+    WrongFundingVault,
+    #[msg("Operation settlement deadline has passed")]
+    OperationExpired,
+    #[msg("Operation settlement deadline has not passed yet")]
+    OperationNotExpired,
+    #[msg("Signer is not an active registered operator")]
+    UnauthorizedOperator,
+    #[msg("Operation is executable; nothing to skip")]
+    NothingToSkip,
+    #[msg("Only the operator that opened the operation can settle it")]
+    NotOperationInitiator,
+    #[msg("A skip was already reported in this cooldown window")]
+    SkipAlreadyReported,
+    #[msg("Not enough SOL has accumulated to convert")]
+    BelowConversionThreshold,
+    #[msg("Oracle account is missing, unexpected or malformed")]
+    InvalidOracle,
+    #[msg("Oracle price is stale or not trading")]
+    OracleStale,
+    #[msg("Accounts or instruction data do not match the venue")]
+    InvalidVenueInstruction,
+    #[msg("USDC mint does not have 6 decimals")]
+    InvalidUsdcMint,
+    #[msg("Token mint decimals or mint authority differ from the expected configuration")]
+    InvalidTokenMint,
+    #[msg("Deposit ratio deviates from the pool price by more than the slippage limit")]
+    DepositRatioMismatch,
+    #[msg("The current epoch has not ended yet")]
+    EpochNotEnded,
+    #[msg("Amount exceeds the streamed operations allowance")]
+    ExceedsStreamedAllowance,
+    #[msg("Guardian co-signature required")]
+    GuardianRequired,
+    #[msg("Governance approval required")]
+    GovernanceRequired,
+    #[msg("Attested accounts must be treasury-owned token accounts matching the listed kinds")]
+    InvalidAttestation,
+    #[msg("Oracle price is above the order's limit")]
+    PriceConditionNotMet,
+    #[msg("Order has expired")]
+    OrderExpired,
+    #[msg("Treasury must be paused")]
+    NotPaused,
+    #[msg("Invalid migration successor")]
+    InvalidSuccessor,
+    #[msg("No vault migration is pending")]
+    NoPendingMigration,
+    #[msg("Token account is not owned by the treasury")]
+    VaultNotOwnedByTreasury,
+    #[msg("Account is not for the treasury's USDC mint")]
+    WrongUsdcMint,
+    #[msg("Pool is not in the pool registry")]
+    UnregisteredPool,
+    #[msg("Pool is already registered")]
+    PoolAlreadyRegistered,
+    #[msg("Pool registry is full")]
+    PoolRegistryFull,
+    #[msg("Campaign has ended")]
+    CampaignEnded,
+    #[msg("Campaign has already started")]
+    CampaignStarted,
+    #[msg("Route candidates or accounts are invalid")]
+    InvalidRoute,
+    #[msg("Venue pools cannot be quoted on-chain")]
+    VenueNotQuotable,
+    #[msg("Swap output below minimum")]
+    SlippageExceeded,
+    #[msg("Depeg threshold must be between 1 and 10000 bps")]
+    InvalidDepegThreshold,
+    #[msg("LTV must be below the liquidation threshold, which is at most 10000 bps")]
+    InvalidLendingPolicy,
+    #[msg("Loan exceeds the allowed loan-to-value")]
+    LoanUndercollateralized,
+    #[msg("Loan is not eligible for liquidation")]
+    LoanHealthy,
+    #[msg("Market maker is not active")]
+    MarketMakerInactive,
+    #[msg("min_tokens_out is below the oracle price less slippage")]
+    MinOutBelowOracleFloor,
+    #[msg("Global weekly USDC outflow cap exceeded")]
+    WeeklyOutflowCapExceeded,
+    #[msg("No outflow cap change is pending")]
+    NoPendingOutflowCap,
+    #[msg("A TWAP account is required while the deviation check is on")]
+    TwapRequired,
+    #[msg("TWAP pool vaults do not match")]
+    InvalidTwapPool,
+    #[msg("Pool spot price deviates too far from its TWAP")]
+    TwapDeviationExceeded,
+    #[msg("Fee vault has not received the lamports being recorded")]
+    FeeNotReceived,
+    #[msg("Fee batch is empty, too large or has a zero entry")]
+    InvalidFeeBatch,
+    #[msg("Fee vault holds fewer recorded lamports than requested")]
+    InsufficientFeeVault,
+    #[msg("Account does not hold wrapped SOL")]
+    NotWrappedSol,
+    #[msg("Keeper reward exceeds the maximum")]
+    InvalidKeeperReward,
+    #[msg("Operator is not allowed this operation kind or venue")]
+    OperatorScopeViolation,
+    #[msg("Account is not the treasury")]
+    InvalidTreasuryAccount,
+    #[msg("Invalid token migration ratio or mint")]
+    InvalidMigrationRatio,
+    #[msg("Token migration already finalized")]
+    MigrationAlreadyFinalized,
+    #[msg("Treasury tokens were already migrated")]
+    TokenMigrationInProgress,
+    #[msg("Invalid role account")]
+    InvalidRoleAccount,
+    #[msg("Account is not a Squads multisig")]
+    InvalidSquadsMultisig,
+    #[msg("Admin is not the multisig's vault")]
+    AdminNotSquadsVault,
+    #[msg("Configuration changes must go through propose_config")]
+    ConfigChangeTimelocked,
+    #[msg("Account is not a Realms governance")]
+    InvalidRealmsGovernance,
+    #[msg("Admin is not a bound Realms governance")]
+    AdminNotRealmsGovernance,
+    #[msg("Invalid LBP window or weights")]
+    InvalidLbpSchedule,
+    #[msg("LBP is not accepting contributions")]
+    LbpNotActive,
+    #[msg("LBP has not ended")]
+    LbpNotEnded,
+    #[msg("LBP already settled")]
+    LbpAlreadySettled,
+    #[msg("Operation would exceed the rolling 24-hour volume cap")]
+    DailyVolumeCapExceeded,
+    #[msg("Budget epochs are not enabled")]
+    BudgetEpochsDisabled,
+    #[msg("Fee source is not the calling program's fee source PDA")]
+    InvalidFeeSource,
+    #[msg("Forwarding USDC needs the source and treasury USDC accounts and the token program")]
+    MissingUsdcAccounts,
+    #[msg("No Kamino reserve is configured, or this is not it")]
+    KaminoNotConfigured,
+    #[msg("Redeem the open lending position first")]
+    LendingPositionOpen,
+    #[msg("Collateral account does not hold the reserve's collateral token")]
+    InvalidCollateralAccount,
+    #[msg("Lending venue moved a different amount than requested")]
+    UnexpectedLendingTransfer,
+    #[msg("No MarginFi bank or account is configured, or these are not them")]
+    MarginFiNotConfigured,
+    #[msg("Deposits go to a different lending venue")]
+    LendingVenueInactive,
+    #[msg("No circuit-breaker trip is awaiting acknowledgment")]
+    NoCircuitBreakerTrip,
+    #[msg("The guardian already reviewed this proposal")]
+    ProposalAlreadyReviewed,
+    #[msg("No Solend reserve is configured, or this is not it")]
+    SolendNotConfigured,
+    #[msg("No token supply snapshot has been recorded")]
+    NoSupplySnapshot,
+    #[msg("The supply has not dropped by that much since the snapshot")]
+    UnverifiableBurn,
+    #[msg("The collateral left would no longer cover the deposited principal")]
+    HarvestExceedsInterest,
+    #[msg("Tick range must satisfy min tick <= lower < upper <= max tick")]
+    InvalidTickRange,
+    #[msg("The treasury already has a CLMM position open")]
+    ClmmPositionOpen,
+    #[msg("The treasury has no CLMM position, or these accounts are not it")]
+    ClmmPositionNotOpen,
+    #[msg("Not a milestone at the configured interval")]
+    InvalidMilestone,
+    #[msg("The lifetime total has not reached this milestone yet")]
+    MilestoneNotReached,
+    #[msg("Accounts do not match the liquidity position")]
+    WhirlpoolPositionMismatch,
+    #[msg("Position holds less liquidity than requested")]
+    InsufficientPositionLiquidity,
+    #[msg("The pool's type does not take this kind of deposit")]
+    WrongPoolType,
+    #[msg("Bin offsets must be ordered and span at most one position's bins")]
+    InvalidBinRange,
+    #[msg("The treasury already has an open DLMM position")]
+    DlmmPositionOpen,
+    #[msg("The treasury has no DLMM position, or these accounts are not it")]
+    DlmmPositionNotOpen,
+    #[msg("LP token account or withdrawal does not match the pool's LP position")]
+    LpPositionMismatch,
+    #[msg("Treasury account is not an older layout this program can upgrade")]
+    UnknownTreasuryLayout,
+    #[msg("Signer is not the treasury admin")]
+    NotTreasuryAdmin,
+    #[msg("The LP tokens left would no longer cover the position's net contribution")]
+    LpFeesExceedEarnings,
+    #[msg("The lock must end in the future")]
+    InvalidLockTime,
+    #[msg("The LP tokens are still locked")]
+    LpStillLocked,
+    #[msg("Deposit would exceed the strategy adapter's cap")]
+    ExceedsStrategyCap,
+    #[msg("Strategy adapter still holds deposited USDC")]
+    StrategyAdapterInUse,
+    #[msg("Strategy adapter moved a different amount of USDC than requested")]
+    UnexpectedStrategyTransfer,
+    #[msg("Strategy adapter did not report a value")]
+    InvalidStrategyReport,
+    #[msg("Address labels must be 1 to 32 bytes")]
+    InvalidAddressLabel,
+    #[msg("The address book entry is not active yet")]
+    AddressBookEntryPending,
+    #[msg("Operation already committed a receipt")]
+    ReceiptAlreadyCommitted,
+    #[msg("Receipt account does not match the one the operation committed")]
+    ReceiptMismatch,
+    #[msg("Operation escrow has not been released to the venue")]
+    OperationNotReleased,
+    #[msg("Receipt holds less than the minimum output and its claim window is open")]
+    ReceiptBelowMinimum,
+    #[msg("Token mint has no transfer hook program")]
+    TransferHookNotConfigured,
 }
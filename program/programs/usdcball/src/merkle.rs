@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+
+/// Leaf committed to by a distribution root: `keccak(claimant || amount_le)`.
+pub fn claim_leaf(claimant: &Pubkey, amount: u64) -> [u8; 32] {
+    keccak::hashv(&[claimant.as_ref(), &amount.to_le_bytes()]).to_bytes()
+}
+
+/// Leaf of a holder snapshot root: `keccak(holder || balance_le || held_since_le)`,
+/// where `held_since` is when the holder last went from zero to a positive balance.
+pub fn holder_leaf(holder: &Pubkey, balance: u64, held_since: i64) -> [u8; 32] {
+    keccak::hashv(&[
+        holder.as_ref(),
+        &balance.to_le_bytes(),
+        &held_since.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Verify `leaf` against `root` using a proof of sibling hashes.
+/// Pairs are hashed in sorted order, so proofs carry no left/right flags.
+pub fn verify(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            keccak::hashv(&[&node, sibling]).to_bytes()
+        } else {
+            keccak::hashv(&[sibling, &node]).to_bytes()
+        }
+    });
+    computed == *root
+}
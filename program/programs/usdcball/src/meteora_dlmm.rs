@@ -0,0 +1,169 @@
+//! Meteora DLMM liquidity support. The treasury holds one DLMM position, owned by
+//! the treasury PDA, over a bin range set relative to the pair's active bin, and
+//! deposits USDC into it single-sided. Bins on one side of the active bin only
+//! hold one of the pair's tokens, so the configured range must sit on USDC's side;
+//! Meteora rejects a deposit into bins on the wrong side.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+};
+
+pub use crate::venues::meteora::ID;
+
+/// Most bins one DLMM position spans
+pub const MAX_BINS_PER_POSITION: i32 = 70;
+
+/// Anchor discriminator of `initialize_position`
+const INITIALIZE_POSITION: [u8; 8] = [219, 192, 234, 71, 190, 191, 102, 80];
+/// Anchor discriminator of `add_liquidity_one_side`
+const ADD_LIQUIDITY_ONE_SIDE: [u8; 8] = [94, 155, 103, 151, 70, 95, 220, 165];
+
+/// Accounts opening a position takes. `owner` is the treasury PDA.
+pub struct OpenAccounts<'info> {
+    pub payer: AccountInfo<'info>,
+    pub position: AccountInfo<'info>,
+    pub lb_pair: AccountInfo<'info>,
+    pub owner: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub rent: AccountInfo<'info>,
+    pub event_authority: AccountInfo<'info>,
+    pub program: AccountInfo<'info>,
+}
+
+/// Accounts of a single-sided deposit. `sender` is the treasury PDA and
+/// `user_token` its USDC vault.
+pub struct DepositAccounts<'info> {
+    pub position: AccountInfo<'info>,
+    pub lb_pair: AccountInfo<'info>,
+    pub user_token: AccountInfo<'info>,
+    pub reserve: AccountInfo<'info>,
+    pub token_mint: AccountInfo<'info>,
+    pub bin_array_lower: AccountInfo<'info>,
+    pub bin_array_upper: AccountInfo<'info>,
+    pub sender: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub event_authority: AccountInfo<'info>,
+    pub program: AccountInfo<'info>,
+}
+
+/// Open an empty position over `width` bins starting at `lower_bin_id`
+pub fn initialize_position(
+    accounts: &OpenAccounts,
+    lower_bin_id: i32,
+    width: i32,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let metas = vec![
+        AccountMeta::new(accounts.payer.key(), true),
+        AccountMeta::new(accounts.position.key(), true),
+        AccountMeta::new_readonly(accounts.lb_pair.key(), false),
+        AccountMeta::new_readonly(accounts.owner.key(), true),
+        AccountMeta::new_readonly(accounts.system_program.key(), false),
+        AccountMeta::new_readonly(accounts.rent.key(), false),
+        AccountMeta::new_readonly(accounts.event_authority.key(), false),
+        AccountMeta::new_readonly(accounts.program.key(), false),
+    ];
+    let mut data = INITIALIZE_POSITION.to_vec();
+    data.extend_from_slice(&lower_bin_id.to_le_bytes());
+    data.extend_from_slice(&width.to_le_bytes());
+
+    let ix = Instruction {
+        program_id: ID,
+        accounts: metas,
+        data,
+    };
+    invoke_signed(
+        &ix,
+        &[
+            accounts.payer.clone(),
+            accounts.position.clone(),
+            accounts.lb_pair.clone(),
+            accounts.owner.clone(),
+            accounts.system_program.clone(),
+            accounts.rent.clone(),
+            accounts.event_authority.clone(),
+            accounts.program.clone(),
+        ],
+        signer_seeds,
+    )?;
+    Ok(())
+}
+
+/// Deposit `amount` of one token spread evenly over `bins`. Meteora rejects the
+/// deposit when the active bin moved more than `max_active_bin_slippage` bins
+/// away from `active_id`.
+pub fn add_liquidity_one_side(
+    accounts: &DepositAccounts,
+    amount: u64,
+    active_id: i32,
+    max_active_bin_slippage: i32,
+    bins: std::ops::RangeInclusive<i32>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let metas = vec![
+        AccountMeta::new(accounts.position.key(), false),
+        AccountMeta::new(accounts.lb_pair.key(), false),
+        // No bitmap extension: the program's own id stands in for `None`
+        AccountMeta::new_readonly(ID, false),
+        AccountMeta::new(accounts.user_token.key(), false),
+        AccountMeta::new(accounts.reserve.key(), false),
+        AccountMeta::new_readonly(accounts.token_mint.key(), false),
+        AccountMeta::new(accounts.bin_array_lower.key(), false),
+        AccountMeta::new(accounts.bin_array_upper.key(), false),
+        AccountMeta::new_readonly(accounts.sender.key(), true),
+        AccountMeta::new_readonly(accounts.token_program.key(), false),
+        AccountMeta::new_readonly(accounts.event_authority.key(), false),
+        AccountMeta::new_readonly(accounts.program.key(), false),
+    ];
+    let mut data = ADD_LIQUIDITY_ONE_SIDE.to_vec();
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.extend_from_slice(&active_id.to_le_bytes());
+    data.extend_from_slice(&max_active_bin_slippage.to_le_bytes());
+    let bin_count = (bins.end() - bins.start() + 1) as u32;
+    data.extend_from_slice(&bin_count.to_le_bytes());
+    for bin_id in bins {
+        data.extend_from_slice(&bin_id.to_le_bytes());
+        data.extend_from_slice(&1u16.to_le_bytes());
+    }
+
+    let ix = Instruction {
+        program_id: ID,
+        accounts: metas,
+        data,
+    };
+    invoke_signed(
+        &ix,
+        &[
+            accounts.position.clone(),
+            accounts.lb_pair.clone(),
+            accounts.program.clone(),
+            accounts.user_token.clone(),
+            accounts.reserve.clone(),
+            accounts.token_mint.clone(),
+            accounts.bin_array_lower.clone(),
+            accounts.bin_array_upper.clone(),
+            accounts.sender.clone(),
+            accounts.token_program.clone(),
+            accounts.event_authority.clone(),
+        ],
+        signer_seeds,
+    )?;
+    Ok(())
+}
+
+/// Bins `[active_id + lower_offset, active_id + upper_offset]`, or `None` when
+/// the offsets are out of order or span more bins than one position holds
+pub fn bin_range(
+    active_id: i32,
+    lower_offset: i32,
+    upper_offset: i32,
+) -> Option<std::ops::RangeInclusive<i32>> {
+    if lower_offset > upper_offset
+        || upper_offset.checked_sub(lower_offset)? >= MAX_BINS_PER_POSITION
+    {
+        return None;
+    }
+    Some(active_id.checked_add(lower_offset)?..=active_id.checked_add(upper_offset)?)
+}
@@ -0,0 +1,166 @@
+use anchor_lang::prelude::*;
+
+use crate::ErrorCode;
+
+/// Decimals of USD values derived from oracle prices (micro-USD, same as USDC).
+pub const USD_DECIMALS: u8 = 6;
+
+// Offsets into a Pyth price account (legacy push-oracle layout).
+const MAGIC: u32 = 0xa1b2_c3d4;
+const ACCOUNT_TYPE_PRICE: u32 = 3;
+const STATUS_TRADING: u32 = 1;
+const EXPO_OFFSET: usize = 20;
+const TIMESTAMP_OFFSET: usize = 96;
+const AGG_PRICE_OFFSET: usize = 208;
+const AGG_STATUS_OFFSET: usize = 224;
+const MIN_LEN: usize = 240;
+
+// Offsets into a Switchboard V2 `AggregatorAccountData` (packed, after the
+// 8-byte discriminator), within `latest_confirmed_round`.
+const SWITCHBOARD_DISCRIMINATOR: [u8; 8] = [217, 230, 65, 101, 201, 162, 27, 125];
+const SWITCHBOARD_ROUND_OPEN_TIMESTAMP_OFFSET: usize = 358;
+const SWITCHBOARD_RESULT_MANTISSA_OFFSET: usize = 366;
+const SWITCHBOARD_RESULT_SCALE_OFFSET: usize = 382;
+const SWITCHBOARD_MIN_LEN: usize = 386;
+
+/// Aggregate price read from an oracle account: `price * 10^expo` USD per whole unit.
+#[derive(Clone, Copy, Debug)]
+pub struct OraclePrice {
+    pub price: i64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+impl OraclePrice {
+    /// Value `amount` base units of an asset with `decimals` in micro-USD.
+    pub fn value_usd(&self, amount: u64, decimals: u8) -> Result<u64> {
+        let scale = self.expo + USD_DECIMALS as i32 - decimals as i32;
+        let raw = (amount as u128)
+            .checked_mul(self.price as u128)
+            .ok_or(ErrorCode::Overflow)?;
+        let pow = 10u128
+            .checked_pow(scale.unsigned_abs())
+            .ok_or(ErrorCode::Overflow)?;
+        let value = if scale >= 0 {
+            raw.checked_mul(pow).ok_or(ErrorCode::Overflow)?
+        } else {
+            raw / pow
+        };
+        u64::try_from(value).map_err(|_| ErrorCode::Overflow.into())
+    }
+
+    /// Base units of an asset with `decimals` worth `usd_value` micro-USD.
+    pub fn amount_for_usd(&self, usd_value: u64, decimals: u8) -> Result<u64> {
+        let scale = self.expo + USD_DECIMALS as i32 - decimals as i32;
+        let pow = 10u128
+            .checked_pow(scale.unsigned_abs())
+            .ok_or(ErrorCode::Overflow)?;
+        let amount = if scale >= 0 {
+            usd_value as u128
+                / (self.price as u128)
+                    .checked_mul(pow)
+                    .ok_or(ErrorCode::Overflow)?
+        } else {
+            (usd_value as u128)
+                .checked_mul(pow)
+                .ok_or(ErrorCode::Overflow)?
+                / self.price as u128
+        };
+        u64::try_from(amount).map_err(|_| ErrorCode::Overflow.into())
+    }
+
+    /// Price of one whole unit in micro-USD.
+    pub fn micro_usd_per_unit(&self) -> Result<u64> {
+        self.value_usd(1, 0)
+    }
+}
+
+/// Read the aggregate price from `account`, rejecting anything that is not a
+/// trading price published within `max_age_seconds` of `now`.
+pub fn read_price(account: &AccountInfo, now: i64, max_age_seconds: i64) -> Result<OraclePrice> {
+    let data = account.try_borrow_data()?;
+    require!(data.len() >= MIN_LEN, ErrorCode::InvalidOracle);
+    require!(
+        read_u32(&data, 0) == MAGIC && read_u32(&data, 8) == ACCOUNT_TYPE_PRICE,
+        ErrorCode::InvalidOracle
+    );
+    require!(
+        read_u32(&data, AGG_STATUS_OFFSET) == STATUS_TRADING,
+        ErrorCode::OracleStale
+    );
+
+    let price = OraclePrice {
+        price: read_u64(&data, AGG_PRICE_OFFSET) as i64,
+        expo: read_u32(&data, EXPO_OFFSET) as i32,
+        publish_time: read_u64(&data, TIMESTAMP_OFFSET) as i64,
+    };
+    require!(price.price > 0, ErrorCode::InvalidOracle);
+    require!(
+        now.saturating_sub(price.publish_time) <= max_age_seconds,
+        ErrorCode::OracleStale
+    );
+    Ok(price)
+}
+
+/// Read the latest confirmed result of a Switchboard V2 aggregator, rejecting
+/// results older than `max_age_seconds`.
+pub fn read_switchboard_price(
+    account: &AccountInfo,
+    now: i64,
+    max_age_seconds: i64,
+) -> Result<OraclePrice> {
+    let data = account.try_borrow_data()?;
+    require!(
+        data.len() >= SWITCHBOARD_MIN_LEN && data[..8] == SWITCHBOARD_DISCRIMINATOR,
+        ErrorCode::InvalidOracle
+    );
+
+    let mut mantissa = i128::from_le_bytes(
+        data[SWITCHBOARD_RESULT_MANTISSA_OFFSET..SWITCHBOARD_RESULT_MANTISSA_OFFSET + 16]
+            .try_into()
+            .unwrap(),
+    );
+    let mut scale = read_u32(&data, SWITCHBOARD_RESULT_SCALE_OFFSET) as i32;
+    // Drop precision until the mantissa fits the Pyth-style i64 price
+    while mantissa > i64::MAX as i128 && scale > 0 {
+        mantissa /= 10;
+        scale -= 1;
+    }
+    let price = OraclePrice {
+        price: i64::try_from(mantissa).map_err(|_| ErrorCode::InvalidOracle)?,
+        expo: -scale,
+        publish_time: read_u64(&data, SWITCHBOARD_ROUND_OPEN_TIMESTAMP_OFFSET) as i64,
+    };
+    require!(price.price > 0, ErrorCode::InvalidOracle);
+    require!(
+        now.saturating_sub(price.publish_time) <= max_age_seconds,
+        ErrorCode::OracleStale
+    );
+    Ok(price)
+}
+
+/// Read the Pyth `primary` feed, falling back to the Switchboard `fallback` feed
+/// when the primary is missing, stale or not trading.
+pub fn read_price_with_fallback(
+    primary: Option<&AccountInfo>,
+    fallback: Option<&AccountInfo>,
+    now: i64,
+    primary_max_age_seconds: i64,
+    fallback_max_age_seconds: i64,
+) -> Result<OraclePrice> {
+    let primary_price = primary.map(|a| read_price(a, now, primary_max_age_seconds));
+    match (primary_price, fallback) {
+        (Some(Ok(price)), _) => Ok(price),
+        (_, Some(account)) => read_switchboard_price(account, now, fallback_max_age_seconds),
+        (Some(Err(err)), None) => Err(err),
+        (None, None) => err!(ErrorCode::InvalidOracle),
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
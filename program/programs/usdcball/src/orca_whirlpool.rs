@@ -0,0 +1,285 @@
+//! Orca Whirlpool position management. Each treasury position is a Whirlpool
+//! position whose Token-2022 NFT sits in a token account owned by the treasury
+//! PDA; the program mirrors it in a `LiquidityPosition` account. Whirlpools order
+//! a pool's tokens A and B by mint address; `order_pair` puts the USDC and token
+//! sides in that order.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::{invoke, invoke_signed},
+};
+
+pub use crate::venues::orca::ID;
+
+/// Update authority Whirlpools sets on position NFT metadata
+pub mod metadata_update_auth {
+    use anchor_lang::prelude::*;
+
+    declare_id!("3axbTs2z5GBy6usVbNVoqEgZMng3vZvMnAoX29BWfwBr");
+}
+
+/// SPL Memo, which the v2 instructions take for Token-2022 transfer hooks
+pub mod memo {
+    use anchor_lang::prelude::*;
+
+    declare_id!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+}
+
+/// Anchor discriminator of `open_position_with_token_extensions`
+const OPEN_POSITION_WITH_TOKEN_EXTENSIONS: [u8; 8] = [212, 47, 95, 92, 114, 102, 131, 250];
+/// Anchor discriminator of `increase_liquidity_v2`
+const INCREASE_LIQUIDITY_V2: [u8; 8] = [133, 29, 89, 223, 69, 238, 176, 10];
+/// Anchor discriminator of `decrease_liquidity_v2`
+const DECREASE_LIQUIDITY_V2: [u8; 8] = [58, 127, 188, 62, 79, 82, 196, 96];
+/// Anchor discriminator of `collect_fees_v2`
+const COLLECT_FEES_V2: [u8; 8] = [207, 117, 95, 191, 229, 180, 226, 15];
+
+/// Accounts opening a position takes. `owner` is the treasury PDA.
+pub struct OpenAccounts<'info> {
+    pub funder: AccountInfo<'info>,
+    pub owner: AccountInfo<'info>,
+    pub position: AccountInfo<'info>,
+    pub position_mint: AccountInfo<'info>,
+    pub position_token_account: AccountInfo<'info>,
+    pub whirlpool: AccountInfo<'info>,
+    pub token_2022_program: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub associated_token_program: AccountInfo<'info>,
+    pub metadata_update_auth: AccountInfo<'info>,
+    pub program: AccountInfo<'info>,
+}
+
+/// Pool, position and token accounts shared by liquidity changes and fee
+/// collection. `position_authority` is the treasury PDA.
+pub struct PositionAccounts<'info> {
+    pub whirlpool: AccountInfo<'info>,
+    pub position_authority: AccountInfo<'info>,
+    pub position: AccountInfo<'info>,
+    pub position_token_account: AccountInfo<'info>,
+    pub token_mint_a: AccountInfo<'info>,
+    pub token_mint_b: AccountInfo<'info>,
+    pub token_owner_account_a: AccountInfo<'info>,
+    pub token_owner_account_b: AccountInfo<'info>,
+    pub token_vault_a: AccountInfo<'info>,
+    pub token_vault_b: AccountInfo<'info>,
+    pub token_program_a: AccountInfo<'info>,
+    pub token_program_b: AccountInfo<'info>,
+    pub memo_program: AccountInfo<'info>,
+    pub program: AccountInfo<'info>,
+}
+
+/// Tick arrays holding a position's lower and upper ticks
+pub struct TickArrays<'info> {
+    pub lower: AccountInfo<'info>,
+    pub upper: AccountInfo<'info>,
+}
+
+/// Open an empty position over `[tick_lower, tick_upper)`, minting its Token-2022
+/// NFT into `position_token_account`. `funder` pays rent; no tokens move.
+pub fn open_position(open: &OpenAccounts, tick_lower: i32, tick_upper: i32) -> Result<()> {
+    let metas = vec![
+        AccountMeta::new(open.funder.key(), true),
+        AccountMeta::new_readonly(open.owner.key(), false),
+        AccountMeta::new(open.position.key(), false),
+        AccountMeta::new(open.position_mint.key(), true),
+        AccountMeta::new(open.position_token_account.key(), false),
+        AccountMeta::new_readonly(open.whirlpool.key(), false),
+        AccountMeta::new_readonly(open.token_2022_program.key(), false),
+        AccountMeta::new_readonly(open.system_program.key(), false),
+        AccountMeta::new_readonly(open.associated_token_program.key(), false),
+        AccountMeta::new_readonly(open.metadata_update_auth.key(), false),
+    ];
+    let mut data = OPEN_POSITION_WITH_TOKEN_EXTENSIONS.to_vec();
+    data.extend_from_slice(&tick_lower.to_le_bytes());
+    data.extend_from_slice(&tick_upper.to_le_bytes());
+    // with_token_metadata_extension: false
+    data.push(0);
+
+    let ix = Instruction {
+        program_id: ID,
+        accounts: metas,
+        data,
+    };
+    invoke(
+        &ix,
+        &[
+            open.funder.clone(),
+            open.owner.clone(),
+            open.position.clone(),
+            open.position_mint.clone(),
+            open.position_token_account.clone(),
+            open.whirlpool.clone(),
+            open.token_2022_program.clone(),
+            open.system_program.clone(),
+            open.associated_token_program.clone(),
+            open.metadata_update_auth.clone(),
+            open.program.clone(),
+        ],
+    )?;
+    Ok(())
+}
+
+/// Add `liquidity` to the position, paying at most `token_max_a` / `token_max_b`
+pub fn increase_liquidity<'info>(
+    accounts: &PositionAccounts<'info>,
+    ticks: &TickArrays<'info>,
+    liquidity: u128,
+    token_max_a: u64,
+    token_max_b: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    modify_liquidity(
+        accounts,
+        ticks,
+        INCREASE_LIQUIDITY_V2,
+        liquidity,
+        token_max_a,
+        token_max_b,
+        signer_seeds,
+    )
+}
+
+/// Remove `liquidity` from the position, receiving at least `token_min_a` / `token_min_b`
+pub fn decrease_liquidity<'info>(
+    accounts: &PositionAccounts<'info>,
+    ticks: &TickArrays<'info>,
+    liquidity: u128,
+    token_min_a: u64,
+    token_min_b: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    modify_liquidity(
+        accounts,
+        ticks,
+        DECREASE_LIQUIDITY_V2,
+        liquidity,
+        token_min_a,
+        token_min_b,
+        signer_seeds,
+    )
+}
+
+fn modify_liquidity<'info>(
+    accounts: &PositionAccounts<'info>,
+    ticks: &TickArrays<'info>,
+    discriminator: [u8; 8],
+    liquidity: u128,
+    token_a: u64,
+    token_b: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let metas = vec![
+        AccountMeta::new(accounts.whirlpool.key(), false),
+        AccountMeta::new_readonly(accounts.token_program_a.key(), false),
+        AccountMeta::new_readonly(accounts.token_program_b.key(), false),
+        AccountMeta::new_readonly(accounts.memo_program.key(), false),
+        AccountMeta::new_readonly(accounts.position_authority.key(), true),
+        AccountMeta::new(accounts.position.key(), false),
+        AccountMeta::new_readonly(accounts.position_token_account.key(), false),
+        AccountMeta::new_readonly(accounts.token_mint_a.key(), false),
+        AccountMeta::new_readonly(accounts.token_mint_b.key(), false),
+        AccountMeta::new(accounts.token_owner_account_a.key(), false),
+        AccountMeta::new(accounts.token_owner_account_b.key(), false),
+        AccountMeta::new(accounts.token_vault_a.key(), false),
+        AccountMeta::new(accounts.token_vault_b.key(), false),
+        AccountMeta::new(ticks.lower.key(), false),
+        AccountMeta::new(ticks.upper.key(), false),
+    ];
+    let mut data = discriminator.to_vec();
+    data.extend_from_slice(&liquidity.to_le_bytes());
+    data.extend_from_slice(&token_a.to_le_bytes());
+    data.extend_from_slice(&token_b.to_le_bytes());
+    // remaining_accounts_info: None
+    data.push(0);
+
+    let ix = Instruction {
+        program_id: ID,
+        accounts: metas,
+        data,
+    };
+    invoke_signed(
+        &ix,
+        &[
+            accounts.whirlpool.clone(),
+            accounts.token_program_a.clone(),
+            accounts.token_program_b.clone(),
+            accounts.memo_program.clone(),
+            accounts.position_authority.clone(),
+            accounts.position.clone(),
+            accounts.position_token_account.clone(),
+            accounts.token_mint_a.clone(),
+            accounts.token_mint_b.clone(),
+            accounts.token_owner_account_a.clone(),
+            accounts.token_owner_account_b.clone(),
+            accounts.token_vault_a.clone(),
+            accounts.token_vault_b.clone(),
+            ticks.lower.clone(),
+            ticks.upper.clone(),
+            accounts.program.clone(),
+        ],
+        signer_seeds,
+    )?;
+    Ok(())
+}
+
+/// Collect the fees owed to the position into the treasury's token accounts.
+/// Whirlpools only brings owed fees up to date on a liquidity change or an
+/// explicit `update_fees_and_rewards`.
+pub fn collect_fees(accounts: &PositionAccounts, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+    let metas = vec![
+        AccountMeta::new_readonly(accounts.whirlpool.key(), false),
+        AccountMeta::new_readonly(accounts.position_authority.key(), true),
+        AccountMeta::new(accounts.position.key(), false),
+        AccountMeta::new_readonly(accounts.position_token_account.key(), false),
+        AccountMeta::new_readonly(accounts.token_mint_a.key(), false),
+        AccountMeta::new_readonly(accounts.token_mint_b.key(), false),
+        AccountMeta::new(accounts.token_owner_account_a.key(), false),
+        AccountMeta::new(accounts.token_vault_a.key(), false),
+        AccountMeta::new(accounts.token_owner_account_b.key(), false),
+        AccountMeta::new(accounts.token_vault_b.key(), false),
+        AccountMeta::new_readonly(accounts.token_program_a.key(), false),
+        AccountMeta::new_readonly(accounts.token_program_b.key(), false),
+        AccountMeta::new_readonly(accounts.memo_program.key(), false),
+    ];
+    let mut data = COLLECT_FEES_V2.to_vec();
+    // remaining_accounts_info: None
+    data.push(0);
+
+    let ix = Instruction {
+        program_id: ID,
+        accounts: metas,
+        data,
+    };
+    invoke_signed(
+        &ix,
+        &[
+            accounts.whirlpool.clone(),
+            accounts.position_authority.clone(),
+            accounts.position.clone(),
+            accounts.position_token_account.clone(),
+            accounts.token_mint_a.clone(),
+            accounts.token_mint_b.clone(),
+            accounts.token_owner_account_a.clone(),
+            accounts.token_vault_a.clone(),
+            accounts.token_owner_account_b.clone(),
+            accounts.token_vault_b.clone(),
+            accounts.token_program_a.clone(),
+            accounts.token_program_b.clone(),
+            accounts.memo_program.clone(),
+            accounts.program.clone(),
+        ],
+        signer_seeds,
+    )?;
+    Ok(())
+}
+
+/// `(usdc, token)` reordered into Whirlpool's token A / token B order, which
+/// sorts a pool's mints by address
+pub fn order_pair<T>(usdc_mint: &Pubkey, token_mint: &Pubkey, usdc: T, token: T) -> (T, T) {
+    if usdc_mint < token_mint {
+        (usdc, token)
+    } else {
+        (token, usdc)
+    }
+}
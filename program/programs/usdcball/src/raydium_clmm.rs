@@ -0,0 +1,206 @@
+//! Raydium concentrated-liquidity (CLMM) support. The treasury holds one position
+//! whose NFT sits in a token account owned by the treasury PDA, and adds liquidity
+//! to it within the tick range set in config. Raydium orders a pool's tokens by
+//! mint address; `order_pair` puts the USDC and token sides in that order.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+};
+
+declare_id!("CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK");
+
+/// Anchor discriminator of `open_position_with_token22_nft`
+const OPEN_POSITION_WITH_TOKEN22_NFT: [u8; 8] = [77, 255, 174, 82, 125, 29, 201, 46];
+/// Anchor discriminator of `increase_liquidity_v2`
+const INCREASE_LIQUIDITY_V2: [u8; 8] = [133, 29, 89, 223, 69, 238, 176, 10];
+
+/// Pool and token accounts shared by opening a position and adding to it.
+/// `nft_owner` is the treasury PDA.
+pub struct PositionAccounts<'info> {
+    pub nft_owner: AccountInfo<'info>,
+    pub nft_account: AccountInfo<'info>,
+    pub pool_state: AccountInfo<'info>,
+    pub protocol_position: AccountInfo<'info>,
+    pub personal_position: AccountInfo<'info>,
+    pub tick_array_lower: AccountInfo<'info>,
+    pub tick_array_upper: AccountInfo<'info>,
+    pub token_account_0: AccountInfo<'info>,
+    pub token_account_1: AccountInfo<'info>,
+    pub token_vault_0: AccountInfo<'info>,
+    pub token_vault_1: AccountInfo<'info>,
+    pub vault_0_mint: AccountInfo<'info>,
+    pub vault_1_mint: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub token_program_2022: AccountInfo<'info>,
+    pub program: AccountInfo<'info>,
+}
+
+/// Accounts only opening a position needs
+pub struct OpenAccounts<'info> {
+    pub payer: AccountInfo<'info>,
+    pub nft_mint: AccountInfo<'info>,
+    pub rent: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub associated_token_program: AccountInfo<'info>,
+}
+
+/// Tick bounds of a position and the start indices of the tick arrays holding them
+pub struct TickRange {
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub tick_array_lower_start: i32,
+    pub tick_array_upper_start: i32,
+}
+
+/// Open an empty position over `range`, minting its Token-2022 NFT into
+/// `nft_account`. With no liquidity Raydium moves no tokens, so `payer` only
+/// pays rent and the treasury's vaults stay untouched.
+pub fn open_position<'info>(
+    accounts: &PositionAccounts<'info>,
+    open: &OpenAccounts<'info>,
+    range: &TickRange,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let metas = vec![
+        AccountMeta::new(open.payer.key(), true),
+        AccountMeta::new_readonly(accounts.nft_owner.key(), false),
+        AccountMeta::new(open.nft_mint.key(), true),
+        AccountMeta::new(accounts.nft_account.key(), false),
+        AccountMeta::new(accounts.pool_state.key(), false),
+        AccountMeta::new(accounts.protocol_position.key(), false),
+        AccountMeta::new(accounts.tick_array_lower.key(), false),
+        AccountMeta::new(accounts.tick_array_upper.key(), false),
+        AccountMeta::new(accounts.personal_position.key(), false),
+        AccountMeta::new(accounts.token_account_0.key(), false),
+        AccountMeta::new(accounts.token_account_1.key(), false),
+        AccountMeta::new(accounts.token_vault_0.key(), false),
+        AccountMeta::new(accounts.token_vault_1.key(), false),
+        AccountMeta::new_readonly(open.rent.key(), false),
+        AccountMeta::new_readonly(open.system_program.key(), false),
+        AccountMeta::new_readonly(accounts.token_program.key(), false),
+        AccountMeta::new_readonly(open.associated_token_program.key(), false),
+        AccountMeta::new_readonly(accounts.token_program_2022.key(), false),
+        AccountMeta::new_readonly(accounts.vault_0_mint.key(), false),
+        AccountMeta::new_readonly(accounts.vault_1_mint.key(), false),
+    ];
+    let mut data = OPEN_POSITION_WITH_TOKEN22_NFT.to_vec();
+    data.extend_from_slice(&range.tick_lower.to_le_bytes());
+    data.extend_from_slice(&range.tick_upper.to_le_bytes());
+    data.extend_from_slice(&range.tick_array_lower_start.to_le_bytes());
+    data.extend_from_slice(&range.tick_array_upper_start.to_le_bytes());
+    // liquidity, amount_0_max, amount_1_max
+    data.extend_from_slice(&0u128.to_le_bytes());
+    data.extend_from_slice(&0u64.to_le_bytes());
+    data.extend_from_slice(&0u64.to_le_bytes());
+    // with_metadata: false, base_flag: None
+    data.extend_from_slice(&[0, 0]);
+
+    let ix = Instruction {
+        program_id: ID,
+        accounts: metas,
+        data,
+    };
+    invoke_signed(
+        &ix,
+        &[
+            open.payer.clone(),
+            accounts.nft_owner.clone(),
+            open.nft_mint.clone(),
+            accounts.nft_account.clone(),
+            accounts.pool_state.clone(),
+            accounts.protocol_position.clone(),
+            accounts.tick_array_lower.clone(),
+            accounts.tick_array_upper.clone(),
+            accounts.personal_position.clone(),
+            accounts.token_account_0.clone(),
+            accounts.token_account_1.clone(),
+            accounts.token_vault_0.clone(),
+            accounts.token_vault_1.clone(),
+            open.rent.clone(),
+            open.system_program.clone(),
+            accounts.token_program.clone(),
+            open.associated_token_program.clone(),
+            accounts.token_program_2022.clone(),
+            accounts.vault_0_mint.clone(),
+            accounts.vault_1_mint.clone(),
+            accounts.program.clone(),
+        ],
+        signer_seeds,
+    )?;
+    Ok(())
+}
+
+/// Add `liquidity` to the position, paying at most `amount_0_max` / `amount_1_max`
+/// from the treasury's token accounts
+pub fn increase_liquidity(
+    accounts: &PositionAccounts,
+    liquidity: u128,
+    amount_0_max: u64,
+    amount_1_max: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let metas = vec![
+        AccountMeta::new_readonly(accounts.nft_owner.key(), true),
+        AccountMeta::new_readonly(accounts.nft_account.key(), false),
+        AccountMeta::new(accounts.pool_state.key(), false),
+        AccountMeta::new(accounts.protocol_position.key(), false),
+        AccountMeta::new(accounts.personal_position.key(), false),
+        AccountMeta::new(accounts.tick_array_lower.key(), false),
+        AccountMeta::new(accounts.tick_array_upper.key(), false),
+        AccountMeta::new(accounts.token_account_0.key(), false),
+        AccountMeta::new(accounts.token_account_1.key(), false),
+        AccountMeta::new(accounts.token_vault_0.key(), false),
+        AccountMeta::new(accounts.token_vault_1.key(), false),
+        AccountMeta::new_readonly(accounts.token_program.key(), false),
+        AccountMeta::new_readonly(accounts.token_program_2022.key(), false),
+        AccountMeta::new_readonly(accounts.vault_0_mint.key(), false),
+        AccountMeta::new_readonly(accounts.vault_1_mint.key(), false),
+    ];
+    let mut data = INCREASE_LIQUIDITY_V2.to_vec();
+    data.extend_from_slice(&liquidity.to_le_bytes());
+    data.extend_from_slice(&amount_0_max.to_le_bytes());
+    data.extend_from_slice(&amount_1_max.to_le_bytes());
+    // base_flag: None
+    data.push(0);
+
+    let ix = Instruction {
+        program_id: ID,
+        accounts: metas,
+        data,
+    };
+    invoke_signed(
+        &ix,
+        &[
+            accounts.nft_owner.clone(),
+            accounts.nft_account.clone(),
+            accounts.pool_state.clone(),
+            accounts.protocol_position.clone(),
+            accounts.personal_position.clone(),
+            accounts.tick_array_lower.clone(),
+            accounts.tick_array_upper.clone(),
+            accounts.token_account_0.clone(),
+            accounts.token_account_1.clone(),
+            accounts.token_vault_0.clone(),
+            accounts.token_vault_1.clone(),
+            accounts.token_program.clone(),
+            accounts.token_program_2022.clone(),
+            accounts.vault_0_mint.clone(),
+            accounts.vault_1_mint.clone(),
+            accounts.program.clone(),
+        ],
+        signer_seeds,
+    )?;
+    Ok(())
+}
+
+/// `(usdc, token)` reordered into Raydium's token 0 / token 1 order, which sorts
+/// a pool's mints by address
+pub fn order_pair<T>(usdc_mint: &Pubkey, token_mint: &Pubkey, usdc: T, token: T) -> (T, T) {
+    if usdc_mint < token_mint {
+        (usdc, token)
+    } else {
+        (token, usdc)
+    }
+}
@@ -0,0 +1,31 @@
+//! SPL Governance (Realms) support. The admin can be a governance PDA: passed
+//! proposals execute their instructions with the governance as signer, so the
+//! allocation split can be put to a token-holder vote through `update_allocations`.
+
+use anchor_lang::prelude::*;
+
+use crate::ErrorCode;
+
+declare_id!("GovER5Lthms3bLBqWub97yVrMmEogzX7xNjdXpPPCir");
+
+/// `GovernanceAccountType` tags of governance accounts (v1 and v2 layouts;
+/// account, program, mint and token governances)
+const GOVERNANCE_ACCOUNT_TYPES: [u8; 8] = [3, 4, 9, 10, 18, 19, 20, 21];
+
+/// Realm the governance belongs to, read from its account data
+/// (`account_type: u8, realm: Pubkey, governance_seed: Pubkey, ..`).
+/// Rejects anything that is not a governance owned by the Realms program.
+pub fn check_governance(account: &AccountInfo) -> Result<Pubkey> {
+    require_keys_eq!(*account.owner, ID, ErrorCode::InvalidRealmsGovernance);
+    let data = account.try_borrow_data()?;
+    require!(
+        data.len() >= 65 && GOVERNANCE_ACCOUNT_TYPES.contains(&data[0]),
+        ErrorCode::InvalidRealmsGovernance
+    );
+    let realm = Pubkey::try_from(&data[1..33]).map_err(|_| ErrorCode::InvalidRealmsGovernance)?;
+    let seed = Pubkey::try_from(&data[33..65]).map_err(|_| ErrorCode::InvalidRealmsGovernance)?;
+    let (expected, _) =
+        Pubkey::find_program_address(&[b"account-governance", realm.as_ref(), seed.as_ref()], &ID);
+    require_keys_eq!(expected, account.key(), ErrorCode::InvalidRealmsGovernance);
+    Ok(realm)
+}
@@ -0,0 +1,32 @@
+//! Squads v4 multisig support. The admin can be a Squads vault PDA: the vault
+//! signs admin instructions through the multisig's executor CPI, so every admin
+//! check (a key comparison on a `Signer`) passes unchanged.
+
+use anchor_lang::prelude::*;
+
+use crate::ErrorCode;
+
+declare_id!("SQDS4ep65T869zMMBKyuUq6aD6EgTu8psMjkvj52pCf");
+
+/// Anchor discriminator of the Squads v4 `Multisig` account
+const MULTISIG_DISCRIMINATOR: [u8; 8] = [224, 116, 121, 186, 68, 161, 79, 236];
+
+/// Vault PDA `vault_index` of `multisig`
+pub fn vault_address(multisig: &Pubkey, vault_index: u8) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"multisig", multisig.as_ref(), b"vault", &[vault_index]],
+        &ID,
+    )
+    .0
+}
+
+/// Reject anything that is not a Squads v4 multisig account
+pub fn check_multisig(account: &AccountInfo) -> Result<()> {
+    require_keys_eq!(*account.owner, ID, ErrorCode::InvalidSquadsMultisig);
+    let data = account.try_borrow_data()?;
+    require!(
+        data.len() >= 8 && data[..8] == MULTISIG_DISCRIMINATOR,
+        ErrorCode::InvalidSquadsMultisig
+    );
+    Ok(())
+}
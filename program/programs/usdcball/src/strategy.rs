@@ -0,0 +1,120 @@
+//! Third-party strategy adapters. An adapter is any program implementing the
+//! three instructions below; once whitelisted through the configuration timelock
+//! the treasury can allocate reserve USDC to it, up to its cap, without usdcball
+//! itself being upgraded.
+//!
+//! Instruction data starts with the Anchor discriminator of the instruction name
+//! (`sha256("global:<name>")[..8]`), so an Anchor program implements the
+//! interface by declaring instructions of the same names and arguments. Every
+//! instruction receives these accounts first, followed by the adapter's own
+//! accounts in the order it documents:
+//!
+//! 0. `[signer]` treasury PDA
+//! 1. `[writable]` treasury USDC vault (read-only for `report_value`)
+//! 2. `[]` USDC mint
+//! 3. `[]` token program of the vault
+//!
+//! - `deposit(amount: u64)` moves exactly `amount` USDC out of the vault, with
+//!   the treasury's signature, into the strategy.
+//! - `withdraw(amount: u64)` sends `amount` USDC back into the vault.
+//! - `report_value()` sets return data to the current USDC value of the
+//!   treasury's position as a little-endian `u64`. The treasury does not sign it.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::{get_return_data, invoke_signed},
+};
+
+use crate::ErrorCode;
+
+/// Anchor discriminator of `deposit`
+pub const DEPOSIT: [u8; 8] = [242, 35, 198, 137, 82, 225, 242, 182];
+/// Anchor discriminator of `withdraw`
+pub const WITHDRAW: [u8; 8] = [183, 18, 70, 156, 148, 109, 161, 34];
+/// Anchor discriminator of `report_value`
+pub const REPORT_VALUE: [u8; 8] = [174, 151, 171, 75, 154, 188, 28, 230];
+
+/// Accounts of a strategy adapter call. `treasury` is the treasury PDA; `extra`
+/// are the adapter's own accounts.
+pub struct AdapterAccounts<'a, 'info> {
+    pub program: AccountInfo<'info>,
+    pub treasury: AccountInfo<'info>,
+    pub treasury_usdc: AccountInfo<'info>,
+    pub usdc_mint: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub extra: &'a [AccountInfo<'info>],
+}
+
+impl<'a, 'info> AdapterAccounts<'a, 'info> {
+    fn invoke(&self, data: Vec<u8>, treasury_signs: bool, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        let mut metas = vec![
+            AccountMeta::new_readonly(self.treasury.key(), treasury_signs),
+            AccountMeta {
+                pubkey: self.treasury_usdc.key(),
+                is_signer: false,
+                is_writable: treasury_signs,
+            },
+            AccountMeta::new_readonly(self.usdc_mint.key(), false),
+            AccountMeta::new_readonly(self.token_program.key(), false),
+        ];
+        // The treasury never signs for the adapter's own accounts
+        metas.extend(self.extra.iter().map(|a| AccountMeta {
+            pubkey: a.key(),
+            is_signer: a.is_signer && a.key() != self.treasury.key(),
+            is_writable: a.is_writable,
+        }));
+        let ix = Instruction {
+            program_id: self.program.key(),
+            accounts: metas,
+            data,
+        };
+
+        let mut infos = vec![
+            self.program.clone(),
+            self.treasury.clone(),
+            self.treasury_usdc.clone(),
+            self.usdc_mint.clone(),
+            self.token_program.clone(),
+        ];
+        infos.extend_from_slice(self.extra);
+        invoke_signed(&ix, &infos, signer_seeds)?;
+        Ok(())
+    }
+}
+
+fn amount_data(discriminator: [u8; 8], amount: u64) -> Vec<u8> {
+    let mut data = discriminator.to_vec();
+    data.extend_from_slice(&amount.to_le_bytes());
+    data
+}
+
+/// Ask the adapter to take `amount` USDC from the treasury vault
+pub fn deposit(accounts: &AdapterAccounts, amount: u64, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+    accounts.invoke(amount_data(DEPOSIT, amount), true, signer_seeds)
+}
+
+/// Ask the adapter to return `amount` USDC to the treasury vault
+pub fn withdraw(accounts: &AdapterAccounts, amount: u64, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+    accounts.invoke(amount_data(WITHDRAW, amount), true, signer_seeds)
+}
+
+/// USDC value of the treasury's position as reported by the adapter
+pub fn report_value(accounts: &AdapterAccounts) -> Result<u64> {
+    accounts.invoke(REPORT_VALUE.to_vec(), false, &[])?;
+    let (program, data) = get_return_data().ok_or(ErrorCode::InvalidStrategyReport)?;
+    require_keys_eq!(
+        program,
+        accounts.program.key(),
+        ErrorCode::InvalidStrategyReport
+    );
+    parse_value(&data)
+}
+
+/// Value in `report_value` return data
+pub fn parse_value(data: &[u8]) -> Result<u64> {
+    let bytes: [u8; 8] = data
+        .try_into()
+        .map_err(|_| ErrorCode::InvalidStrategyReport)?;
+    Ok(u64::from_le_bytes(bytes))
+}
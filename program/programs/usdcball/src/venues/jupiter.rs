@@ -0,0 +1,32 @@
+//! Jupiter aggregator v6 swap handler.
+
+use anchor_lang::prelude::*;
+
+use super::{require_discriminator, VenueHandler};
+
+declare_id!("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4");
+
+/// Anchor discriminators of `route`, `shared_accounts_route`, `exact_out_route`
+/// and `shared_accounts_exact_out_route`
+const SWAPS: [[u8; 8]; 4] = [
+    [229, 23, 203, 151, 122, 227, 173, 42],
+    [193, 32, 155, 51, 65, 214, 156, 129],
+    [208, 51, 239, 151, 123, 43, 237, 92],
+    [176, 209, 105, 168, 154, 125, 69, 62],
+];
+
+pub struct Jupiter;
+
+impl VenueHandler for Jupiter {
+    fn program_id(&self) -> Pubkey {
+        ID
+    }
+
+    fn min_accounts(&self) -> usize {
+        9
+    }
+
+    fn check_swap_data(&self, data: &[u8]) -> Result<()> {
+        require_discriminator(data, &SWAPS)
+    }
+}
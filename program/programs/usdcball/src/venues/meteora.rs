@@ -0,0 +1,33 @@
+//! Meteora DLMM swap handler.
+
+use anchor_lang::prelude::*;
+
+use super::{require_discriminator, VenueHandler};
+
+declare_id!("LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo");
+
+/// Anchor discriminators of `swap`, `swap2`, `swap_exact_out` and
+/// `swap_exact_out2`; position instructions such as `remove_liquidity` must not
+/// pass as swaps
+const SWAPS: [[u8; 8]; 4] = [
+    [248, 198, 158, 145, 225, 117, 135, 200],
+    [65, 75, 63, 76, 235, 91, 91, 136],
+    [250, 73, 101, 33, 38, 207, 75, 184],
+    [43, 215, 247, 132, 137, 60, 243, 81],
+];
+
+pub struct Meteora;
+
+impl VenueHandler for Meteora {
+    fn program_id(&self) -> Pubkey {
+        ID
+    }
+
+    fn min_accounts(&self) -> usize {
+        15
+    }
+
+    fn check_swap_data(&self, data: &[u8]) -> Result<()> {
+        require_discriminator(data, &SWAPS)
+    }
+}
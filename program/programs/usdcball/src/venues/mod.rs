@@ -0,0 +1,182 @@
+//! Venue dispatch. Each supported DEX lives in its own module and implements
+//! [`VenueHandler`]; adding a venue means adding a module and a `Venue` variant.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+};
+
+use crate::{ErrorCode, Venue};
+
+pub mod jupiter;
+pub mod meteora;
+pub mod orca;
+pub mod phoenix;
+pub mod raydium;
+
+/// What the program needs to know about a venue to route a swap through it.
+pub trait VenueHandler {
+    fn program_id(&self) -> Pubkey;
+
+    /// Accounts the venue's swap instruction takes, excluding the program itself
+    fn min_accounts(&self) -> usize;
+
+    /// Reject instruction data that is not a swap on this venue. The treasury PDA
+    /// signs whatever passes, so each venue allowlists its swap instructions.
+    fn check_swap_data(&self, data: &[u8]) -> Result<()>;
+
+    /// Fee of a constant-product pool on this venue, or `None` when its price
+    /// cannot be derived from the pool's token vault balances
+    fn constant_product_fee_bps(&self) -> Option<u16> {
+        None
+    }
+
+    /// LP tokens burned by `data` when it is a withdrawal from a pool on this
+    /// venue, or `None` when it is not
+    fn withdraw_lp_amount(&self, _data: &[u8]) -> Option<u64> {
+        None
+    }
+
+    /// Whether `data` is a deposit into a pool on this venue that mints LP tokens
+    fn is_deposit(&self, _data: &[u8]) -> bool {
+        false
+    }
+}
+
+impl Venue {
+    pub fn handler(self) -> &'static dyn VenueHandler {
+        match self {
+            Venue::Jupiter => &jupiter::Jupiter,
+            Venue::Raydium => &raydium::Raydium,
+            Venue::Orca => &orca::Orca,
+            Venue::Meteora => &meteora::Meteora,
+            Venue::Phoenix => &phoenix::Phoenix,
+        }
+    }
+
+    /// Bit of this venue in an operator's `allowed_venues`
+    pub fn mask(self) -> u8 {
+        1u8 << (self as u8)
+    }
+}
+
+/// Expected output of swapping `amount_in` into a constant-product pool holding
+/// `reserve_in`/`reserve_out`, after the pool's `fee_bps` input fee.
+pub fn quote_constant_product(
+    amount_in: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    fee_bps: u16,
+) -> Option<u64> {
+    let amount_in = (amount_in as u128) * (10_000 - fee_bps as u128) / 10_000;
+    let out = amount_in.checked_mul(reserve_out as u128)?
+        / (reserve_in as u128).checked_add(amount_in)?.max(1);
+    u64::try_from(out).ok()
+}
+
+/// Accept `data` only when it starts with one of the Anchor discriminators in
+/// `allowed`
+pub fn require_discriminator(data: &[u8], allowed: &[[u8; 8]]) -> Result<()> {
+    require!(
+        data.get(..8)
+            .is_some_and(|d| allowed.iter().any(|a| a[..] == *d)),
+        ErrorCode::InvalidVenueInstruction
+    );
+    Ok(())
+}
+
+/// Invoke a swap on `venue`. `accounts` starts with the venue program followed by
+/// the swap accounts in the venue's order; `signer` (the treasury PDA) signs with
+/// `signer_seeds`.
+pub fn invoke_swap(
+    venue: Venue,
+    accounts: &[AccountInfo],
+    data: Vec<u8>,
+    signer: Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let handler = venue.handler();
+    let (program, swap_accounts) = accounts
+        .split_first()
+        .ok_or(ErrorCode::InvalidVenueInstruction)?;
+    require_keys_eq!(
+        program.key(),
+        handler.program_id(),
+        ErrorCode::InvalidVenueInstruction
+    );
+    require!(
+        swap_accounts.len() >= handler.min_accounts(),
+        ErrorCode::InvalidVenueInstruction
+    );
+    handler.check_swap_data(&data)?;
+    invoke_venue(accounts, data, signer, signer_seeds)
+}
+
+/// Invoke a liquidity withdrawal on `venue`, laid out like [`invoke_swap`].
+/// Returns the LP tokens the withdrawal burns.
+pub fn invoke_withdraw(
+    venue: Venue,
+    accounts: &[AccountInfo],
+    data: Vec<u8>,
+    signer: Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<u64> {
+    let handler = venue.handler();
+    let program = accounts.first().ok_or(ErrorCode::InvalidVenueInstruction)?;
+    require_keys_eq!(
+        program.key(),
+        handler.program_id(),
+        ErrorCode::InvalidVenueInstruction
+    );
+    let lp_amount = handler
+        .withdraw_lp_amount(&data)
+        .ok_or(ErrorCode::InvalidVenueInstruction)?;
+    invoke_venue(accounts, data, signer, signer_seeds)?;
+    Ok(lp_amount)
+}
+
+/// Invoke a liquidity deposit on `venue`, laid out like [`invoke_swap`]
+pub fn invoke_deposit(
+    venue: Venue,
+    accounts: &[AccountInfo],
+    data: Vec<u8>,
+    signer: Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let handler = venue.handler();
+    let program = accounts.first().ok_or(ErrorCode::InvalidVenueInstruction)?;
+    require_keys_eq!(
+        program.key(),
+        handler.program_id(),
+        ErrorCode::InvalidVenueInstruction
+    );
+    require!(handler.is_deposit(&data), ErrorCode::InvalidVenueInstruction);
+    invoke_venue(accounts, data, signer, signer_seeds)
+}
+
+fn invoke_venue(
+    accounts: &[AccountInfo],
+    data: Vec<u8>,
+    signer: Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let (program, venue_accounts) = accounts
+        .split_first()
+        .ok_or(ErrorCode::InvalidVenueInstruction)?;
+    let metas = venue_accounts
+        .iter()
+        .map(|a| AccountMeta {
+            pubkey: a.key(),
+            is_signer: a.is_signer || a.key() == signer,
+            is_writable: a.is_writable,
+        })
+        .collect();
+    let ix = Instruction {
+        program_id: program.key(),
+        accounts: metas,
+        data,
+    };
+    invoke_signed(&ix, accounts, signer_seeds)?;
+    Ok(())
+}
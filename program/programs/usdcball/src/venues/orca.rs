@@ -0,0 +1,30 @@
+//! Orca Whirlpools swap handler.
+
+use anchor_lang::prelude::*;
+
+use super::{require_discriminator, VenueHandler};
+
+declare_id!("whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc");
+
+/// Anchor discriminators of `swap` and `swap_v2`; position instructions such as
+/// `decrease_liquidity` must not pass as swaps
+const SWAPS: [[u8; 8]; 2] = [
+    [248, 198, 158, 145, 225, 117, 135, 200],
+    [43, 4, 237, 11, 26, 201, 30, 98],
+];
+
+pub struct Orca;
+
+impl VenueHandler for Orca {
+    fn program_id(&self) -> Pubkey {
+        ID
+    }
+
+    fn min_accounts(&self) -> usize {
+        11
+    }
+
+    fn check_swap_data(&self, data: &[u8]) -> Result<()> {
+        require_discriminator(data, &SWAPS)
+    }
+}
@@ -0,0 +1,31 @@
+//! Phoenix order book swap handler.
+
+use anchor_lang::prelude::*;
+
+use super::VenueHandler;
+use crate::ErrorCode;
+
+declare_id!("PhoeNiXZ8ByJGLkxNfZRnkUfjvmuYqLR89jjFHGqdXY");
+
+/// Tag of `PhoenixInstruction::Swap`
+const SWAP: u8 = 0;
+
+pub struct Phoenix;
+
+impl VenueHandler for Phoenix {
+    fn program_id(&self) -> Pubkey {
+        ID
+    }
+
+    fn min_accounts(&self) -> usize {
+        8
+    }
+
+    fn check_swap_data(&self, data: &[u8]) -> Result<()> {
+        require!(
+            data.first() == Some(&SWAP),
+            ErrorCode::InvalidVenueInstruction
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+//! Raydium AMM v4 swap handler.
+
+use anchor_lang::prelude::*;
+
+use super::VenueHandler;
+use crate::ErrorCode;
+
+declare_id!("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8");
+
+const SWAP_BASE_IN: u8 = 9;
+const SWAP_BASE_OUT: u8 = 11;
+const DEPOSIT: u8 = 3;
+const WITHDRAW: u8 = 4;
+const TRADE_FEE_BPS: u16 = 25;
+
+pub struct Raydium;
+
+impl VenueHandler for Raydium {
+    fn program_id(&self) -> Pubkey {
+        ID
+    }
+
+    fn min_accounts(&self) -> usize {
+        17
+    }
+
+    fn check_swap_data(&self, data: &[u8]) -> Result<()> {
+        require!(
+            matches!(data.first(), Some(&SWAP_BASE_IN) | Some(&SWAP_BASE_OUT)),
+            ErrorCode::InvalidVenueInstruction
+        );
+        Ok(())
+    }
+
+    fn constant_product_fee_bps(&self) -> Option<u16> {
+        Some(TRADE_FEE_BPS)
+    }
+
+    fn withdraw_lp_amount(&self, data: &[u8]) -> Option<u64> {
+        match data {
+            [WITHDRAW, amount @ ..] => Some(u64::from_le_bytes(amount.get(..8)?.try_into().ok()?)),
+            _ => None,
+        }
+    }
+
+    fn is_deposit(&self, data: &[u8]) -> bool {
+        data.first() == Some(&DEPOSIT)
+    }
+}
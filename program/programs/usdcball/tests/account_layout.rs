@@ -0,0 +1,264 @@
+//! Pins the serialized layout of every program account.
+//!
+//! Clients, indexers and the replay tool decode these accounts by offset, so a
+//! size change or reordered field breaks them silently. When a layout change is
+//! intended, update the pinned values here in the same change (and migrate the
+//! existing accounts).
+
+use anchor_lang::{prelude::Pubkey, AnchorDeserialize, AnchorSerialize, Discriminator, Space};
+use usdcball::{
+    AddressBookEntry, Attestation, BountyPayout, Calendar, Campaign, ConditionalBuyback,
+    ConfigProposal, DividendClaim, DividendDistribution, Epoch, EpochStats, FeeCounter, FeeVault,
+    GuardianRecord, HolderSnapshot, KeyActivity, Lbp, LiquidityPosition, LpLock, LpPosition,
+    MarketMakerLoan, Milestone, Operator, PendingOperation, PoolRegistry, QuoteBoard,
+    ReserveDestination, ReserveWithdrawal, StakePosition, StrategyAdapter, TokenMigration,
+    TransferFeeCounter, Treasury, Twap, TREASURY_INSTRUCTION_ENABLED_OFFSET,
+    TREASURY_ORACLE_MAX_AGE_OFFSET, TREASURY_PAUSE_FLAGS_OFFSET, TREASURY_SOL_USD_ORACLE_OFFSET,
+    TREASURY_TOKEN_MINT_OFFSET, TREASURY_TOTAL_SOL_COLLECTED_OFFSET,
+};
+
+const MARKER: u8 = 0xa5;
+
+/// An account with every field zeroed (empty vectors, first enum variants).
+fn zeroed<T: AnchorDeserialize + Space>() -> T {
+    T::deserialize(&mut &vec![0u8; T::INIT_SPACE][..]).expect("zeroed account")
+}
+
+/// Offset of the first non-zero byte of `account`'s serialization, i.e. of the
+/// single field set to a marker value.
+fn marked_offset<T: AnchorSerialize>(account: &T) -> usize {
+    let data = account.try_to_vec().unwrap();
+    data.iter()
+        .position(|b| *b != 0)
+        .expect("marker not serialized")
+}
+
+fn marker_key() -> Pubkey {
+    Pubkey::new_from_array([MARKER; 32])
+}
+
+/// Asserts `8 + INIT_SPACE` and the offsets (after the discriminator) of the
+/// listed fields, each set to its marker value in turn.
+macro_rules! assert_layout {
+    ($ty:ty, size = $size:expr, { $($field:ident = $marker:expr => $offset:expr),+ $(,)? }) => {{
+        assert_eq!(
+            8 + <$ty as Space>::INIT_SPACE,
+            $size,
+            concat!(stringify!($ty), " account size changed")
+        );
+        $(
+            let mut account: $ty = zeroed();
+            account.$field = $marker;
+            assert_eq!(
+                marked_offset(&account),
+                $offset,
+                concat!(stringify!($ty), "::", stringify!($field), " moved")
+            );
+        )+
+    }};
+}
+
+#[test]
+fn treasury_layout_is_stable() {
+    assert_layout!(Treasury, size = 1892, {
+        authority = marker_key() => 0,
+        token_mint = marker_key() => 32,
+        usdc_mint = marker_key() => 64,
+        total_sol_collected = MARKER as u64 => 120,
+        pause_flags = MARKER => 176,
+        instruction_enabled = MARKER as u64 => 177,
+        sol_usd_oracle = marker_key() => 340,
+        oracle_max_age_seconds = MARKER as i64 => 372,
+        current_epoch = MARKER as u64 => 396,
+        bump = MARKER => 1819,
+    });
+    // `record_fee` and `record_transfer_fee` read these fields in place
+    assert_eq!(TREASURY_TOKEN_MINT_OFFSET, 32);
+    assert_eq!(TREASURY_TOTAL_SOL_COLLECTED_OFFSET, 120);
+    assert_eq!(TREASURY_PAUSE_FLAGS_OFFSET, 176);
+    assert_eq!(TREASURY_INSTRUCTION_ENABLED_OFFSET, 177);
+    assert_eq!(TREASURY_SOL_USD_ORACLE_OFFSET, 340);
+    assert_eq!(TREASURY_ORACLE_MAX_AGE_OFFSET, 372);
+}
+
+#[test]
+fn in_place_treasury_reads_match_the_layout() {
+    let mut treasury = zeroed::<Treasury>();
+    treasury.authority = marker_key();
+    treasury.total_sol_collected = 0xa5a5;
+    let mut data = Treasury::DISCRIMINATOR.to_vec();
+    treasury.serialize(&mut data).unwrap();
+
+    assert_eq!(Treasury::read_pubkey_at(&data, 0).unwrap(), marker_key());
+    assert_eq!(
+        Treasury::read_u64_at(&data, TREASURY_TOTAL_SOL_COLLECTED_OFFSET).unwrap(),
+        0xa5a5
+    );
+    // A truncated account is rejected rather than read past its end
+    assert!(Treasury::read_u64_at(&data[..128], TREASURY_TOTAL_SOL_COLLECTED_OFFSET).is_err());
+    assert!(Treasury::read_pubkey_at(&data[..39], 0).is_err());
+}
+
+#[test]
+fn auxiliary_account_layouts_are_stable() {
+    assert_layout!(DividendDistribution, size = 73, {
+        epoch = MARKER as u64 => 0,
+        bump = MARKER => 64,
+    });
+    assert_layout!(DividendClaim, size = 88, {
+        distribution = marker_key() => 0,
+        claimed_at = MARKER as i64 => 72,
+    });
+    assert_layout!(BountyPayout, size = 74, {
+        index = MARKER as u64 => 0,
+        bump = MARKER => 65,
+    });
+    assert_layout!(PendingOperation, size = 156, {
+        index = MARKER as u64 => 0,
+        receipt = marker_key() => 66,
+        funding_vault = marker_key() => 107,
+        opened_at = MARKER as i64 => 139,
+        bump = MARKER => 147,
+    });
+    assert_layout!(Operator, size = 108, {
+        operator = marker_key() => 0,
+        last_skip_timestamp = MARKER as i64 => 91,
+        bump = MARKER => 99,
+    });
+    assert_layout!(EpochStats, size = 137, {
+        epoch = MARKER as u64 => 0,
+        bump = MARKER => 128,
+    });
+    assert_layout!(ReserveDestination, size = 49, {
+        destination = marker_key() => 0,
+        bump = MARKER => 40,
+    });
+    assert_layout!(ReserveWithdrawal, size = 73, {
+        index = MARKER as u64 => 0,
+        destination = marker_key() => 8,
+        bump = MARKER => 64,
+    });
+    assert_layout!(Epoch, size = 65, {
+        index = MARKER as u64 => 0,
+        bump = MARKER => 56,
+    });
+    assert_layout!(Attestation, size = 1269, {
+        epoch = MARKER as u64 => 0,
+        bump = MARKER => 92,
+    });
+    assert_layout!(ConditionalBuyback, size = 73, {
+        index = MARKER as u64 => 0,
+        bump = MARKER => 64,
+    });
+    assert_layout!(PoolRegistry, size = 1229, {
+        bump = MARKER => 4,
+    });
+    assert_layout!(Campaign, size = 153, {
+        index = MARKER as u64 => 0,
+        bump = MARKER => 144,
+    });
+    assert_layout!(StakePosition, size = 105, {
+        campaign = marker_key() => 0,
+        bump = MARKER => 96,
+    });
+    assert_layout!(HolderSnapshot, size = 77, {
+        epoch = MARKER as u64 => 0,
+        bump = MARKER => 68,
+    });
+    assert_layout!(MarketMakerLoan, size = 66, {
+        market_maker = marker_key() => 0,
+        bump = MARKER => 57,
+    });
+    assert_layout!(Twap, size = 331, {
+        usdc_vault = marker_key() => 0,
+        head = MARKER => 320,
+        bump = MARKER => 322,
+    });
+    assert_layout!(FeeVault, size = 17, {
+        recorded_lamports = MARKER as u64 => 0,
+        bump = MARKER => 8,
+    });
+    assert_layout!(Calendar, size = 157, {
+        bump = MARKER => 12,
+    });
+    // Offsets with every `ConfigChange` field unset
+    assert_layout!(ConfigProposal, size = 92, {
+        eta = MARKER as i64 => 17,
+        bump = MARKER => 25,
+    });
+    assert_layout!(KeyActivity, size = 74, {
+        key = marker_key() => 0,
+        last_action_at = MARKER as i64 => 41,
+        bump = MARKER => 65,
+    });
+    assert_layout!(TokenMigration, size = 121, {
+        new_mint = marker_key() => 32,
+        finalized_at = MARKER as i64 => 104,
+        bump = MARKER => 112,
+    });
+    assert_layout!(Lbp, size = 189, {
+        usdc_vault = marker_key() => 72,
+        settled_at = MARKER as i64 => 172,
+        bump = MARKER => 180,
+    });
+    assert_layout!(QuoteBoard, size = 58, {
+        last_price = MARKER as u64 => 0,
+        last_timestamp = MARKER as i64 => 32,
+        bump = MARKER => 49,
+    });
+    assert_layout!(GuardianRecord, size = 89, {
+        guardian = marker_key() => 0,
+        slowest_response_seconds = MARKER as u64 => 56,
+        bump = MARKER => 80,
+    });
+    assert_layout!(Milestone, size = 98, {
+        threshold = MARKER as u64 => 1,
+        recorded_by = marker_key() => 57,
+        bump = MARKER => 89,
+    });
+    assert_layout!(LiquidityPosition, size = 185, {
+        position_mint = marker_key() => 32,
+        liquidity = MARKER as u128 => 104,
+        bump = MARKER => 176,
+    });
+    assert_layout!(LpPosition, size = 178, {
+        pool_usdc = marker_key() => 1,
+        lp_amount = MARKER as u64 => 97,
+        usdc_contributed = MARKER as u64 => 113,
+        bump = MARKER => 169,
+    });
+    assert_layout!(LpLock, size = 105, {
+        lp_mint = marker_key() => 8,
+        amount = MARKER as u64 => 72,
+        locked_until = MARKER as i64 => 88,
+        bump = MARKER => 96,
+    });
+    assert_layout!(StrategyAdapter, size = 89, {
+        program = marker_key() => 0,
+        deposited_usdc = MARKER as u64 => 40,
+        eta = MARKER as i64 => 72,
+        bump = MARKER => 80,
+    });
+    // `label` is empty here; later fields move with its length
+    assert_layout!(AddressBookEntry, size = 93, {
+        destination = marker_key() => 0,
+        active_at = MARKER as i64 => 44,
+        bump = MARKER => 52,
+    });
+}
+
+#[test]
+fn fee_counter_layout_is_stable() {
+    // Zero-copy: the account is the raw `repr(C)` struct after the discriminator
+    assert_eq!(8 + std::mem::size_of::<FeeCounter>(), 48);
+    assert_eq!(std::mem::offset_of!(FeeCounter, unsynced_lamports), 0);
+    assert_eq!(std::mem::offset_of!(FeeCounter, fee_count), 16);
+    assert_eq!(std::mem::offset_of!(FeeCounter, bump), 32);
+}
+
+#[test]
+fn transfer_fee_counter_layout_is_stable() {
+    assert_eq!(8 + std::mem::size_of::<TransferFeeCounter>(), 48);
+    assert_eq!(std::mem::offset_of!(TransferFeeCounter, accrued_fees), 0);
+    assert_eq!(std::mem::offset_of!(TransferFeeCounter, bump), 32);
+}
@@ -0,0 +1,59 @@
+//! The allocation split must account for every micro-USDC of converted revenue
+//! under `RoundingPolicy::LargestRemainder`, and keep the historical floor
+//! behaviour under `RoundingPolicy::Floor`.
+
+use anchor_lang::{AnchorDeserialize, Space};
+use usdcball::{RoundingPolicy, Treasury};
+
+fn treasury(total_usdc_converted: u64, policy: RoundingPolicy) -> Treasury {
+    let mut treasury =
+        Treasury::deserialize(&mut &vec![0u8; Treasury::INIT_SPACE][..]).expect("zeroed account");
+    treasury.buyback_allocation_bps = 3_333;
+    treasury.liquidity_allocation_bps = 3_333;
+    treasury.reserve_allocation_bps = 3_334;
+    treasury.total_usdc_converted = total_usdc_converted;
+    treasury.rounding_policy = policy;
+    treasury
+}
+
+#[test]
+fn floor_drops_the_remainders() {
+    let caps = treasury(1_000_001, RoundingPolicy::Floor)
+        .allocation_caps()
+        .unwrap();
+    assert_eq!(caps, [333_300, 333_300, 333_400]);
+    assert_eq!(caps.iter().sum::<u64>(), 1_000_000);
+}
+
+#[test]
+fn largest_remainder_assigns_every_unit() {
+    for total in [
+        0,
+        1,
+        2,
+        7,
+        999_999,
+        1_000_001,
+        123_456_789,
+        u64::MAX / 10_000,
+    ] {
+        let caps = treasury(total, RoundingPolicy::LargestRemainder)
+            .allocation_caps()
+            .unwrap();
+        assert_eq!(caps.iter().sum::<u64>(), total, "total {total}");
+    }
+}
+
+#[test]
+fn largest_remainder_favours_the_largest_fraction() {
+    // Shares of 1: 0.3333, 0.3333, 0.3334 -> the reserve takes the unit
+    let caps = treasury(1, RoundingPolicy::LargestRemainder)
+        .allocation_caps()
+        .unwrap();
+    assert_eq!(caps, [0, 0, 1]);
+    // Shares of 2: 0.6666, 0.6666, 0.6668 -> reserve, then buyback on the tie
+    let caps = treasury(2, RoundingPolicy::LargestRemainder)
+        .allocation_caps()
+        .unwrap();
+    assert_eq!(caps, [1, 0, 1]);
+}
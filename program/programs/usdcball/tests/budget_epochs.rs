@@ -0,0 +1,85 @@
+//! Allocation budgets only count USDC converted and spent since the open budget
+//! epoch started, and fall back to lifetime totals when epochs are off.
+
+use anchor_lang::{AnchorDeserialize, Space};
+use usdcball::Treasury;
+
+fn treasury() -> Treasury {
+    let mut treasury =
+        Treasury::deserialize(&mut &vec![0u8; Treasury::INIT_SPACE][..]).expect("zeroed account");
+    treasury.buyback_allocation_bps = 5_000;
+    treasury.liquidity_allocation_bps = 3_000;
+    treasury.reserve_allocation_bps = 2_000;
+    treasury
+}
+
+#[test]
+fn lifetime_budgets_without_epochs() {
+    let mut treasury = treasury();
+    treasury.total_usdc_converted = 1_000_000;
+    treasury.total_buybacks_usdc = 400_000;
+    treasury.total_liquidity_usdc = 300_000;
+    treasury.total_dividends_usdc = 50_000;
+
+    assert_eq!(treasury.remaining_buyback_budget().unwrap(), 100_000);
+    assert_eq!(treasury.remaining_liquidity_budget().unwrap(), 0);
+    assert_eq!(treasury.remaining_reserve_budget().unwrap(), 150_000);
+}
+
+#[test]
+fn a_new_epoch_resets_the_budgets() {
+    let mut treasury = treasury();
+    treasury.budget_epoch_duration_seconds = 86_400;
+    treasury.total_usdc_converted = 1_000_000;
+    treasury.total_buybacks_usdc = 400_000;
+    treasury.total_liquidity_usdc = 300_000;
+    treasury.total_reserve_spent_usdc = 200_000;
+    treasury.start_budget_epoch(86_400);
+
+    // Nothing converted yet this epoch: unspent budget does not carry over
+    assert_eq!(treasury.allocation_caps().unwrap(), [0, 0, 0]);
+    assert_eq!(treasury.remaining_buyback_budget().unwrap(), 0);
+
+    treasury.total_usdc_converted += 200_000;
+    treasury.total_buybacks_usdc += 60_000;
+    treasury.total_bounty_funded_usdc += 10_000;
+
+    assert_eq!(
+        treasury.allocation_caps().unwrap(),
+        [100_000, 60_000, 40_000]
+    );
+    assert_eq!(treasury.remaining_buyback_budget().unwrap(), 40_000);
+    assert_eq!(treasury.remaining_liquidity_budget().unwrap(), 60_000);
+    assert_eq!(treasury.remaining_reserve_budget().unwrap(), 30_000);
+}
+
+#[test]
+fn refunds_below_the_epoch_base_do_not_underflow() {
+    let mut treasury = treasury();
+    treasury.total_usdc_converted = 1_000_000;
+    treasury.total_liquidity_usdc = 300_000;
+    treasury.start_budget_epoch(0);
+
+    // An LBP settlement returning its seed drops the lifetime total below the base
+    treasury.total_liquidity_usdc -= 100_000;
+    treasury.total_usdc_converted += 100_000;
+
+    assert_eq!(treasury.remaining_liquidity_budget().unwrap(), 30_000);
+}
+
+#[test]
+fn lp_fees_extend_the_buyback_budget_for_their_epoch() {
+    let mut treasury = treasury();
+    treasury.budget_epoch_duration_seconds = 86_400;
+    treasury.total_usdc_converted = 1_000_000;
+    treasury.total_buybacks_usdc = 500_000;
+    treasury.record_lp_fees(25_000).unwrap();
+    assert_eq!(treasury.remaining_buyback_budget().unwrap(), 25_000);
+
+    // Fees collected in an earlier epoch are not spendable again
+    treasury.start_budget_epoch(86_400);
+    assert_eq!(treasury.remaining_buyback_budget().unwrap(), 0);
+    treasury.record_lp_fees(5_000).unwrap();
+    assert_eq!(treasury.remaining_buyback_budget().unwrap(), 5_000);
+    assert_eq!(treasury.total_lp_fees_earned, 30_000);
+}
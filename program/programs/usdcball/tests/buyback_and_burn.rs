@@ -0,0 +1,54 @@
+//! `buyback_and_burn` books the swap and the burn together, so the buyback
+//! counters and the burn counters always move by the same fill.
+
+use anchor_lang::{AnchorDeserialize, Space};
+use usdcball::{Treasury, UsdcSpend};
+
+const NOW: i64 = 10 * 86_400;
+
+fn treasury() -> Treasury {
+    let mut treasury =
+        Treasury::deserialize(&mut &vec![0u8; Treasury::INIT_SPACE][..]).expect("zeroed account");
+    treasury.buyback_allocation_bps = 5_000;
+    treasury.liquidity_allocation_bps = 3_000;
+    treasury.reserve_allocation_bps = 2_000;
+    treasury.total_usdc_converted = 1_000_000;
+    treasury
+}
+
+/// Book a buyback of `received` tokens for `spent` USDC the way `buyback_and_burn` does
+fn buyback_and_burn(treasury: &mut Treasury, spent: u64, received: u64) {
+    treasury
+        .authorize_usdc_spend(UsdcSpend::Buyback, spent, NOW)
+        .unwrap();
+    treasury
+        .settle_buyback(spent, spent, received, received, None, 6, NOW)
+        .unwrap();
+    treasury.record_burn(received).unwrap();
+}
+
+#[test]
+fn fill_is_counted_as_bought_and_burned() {
+    let mut treasury = treasury();
+
+    buyback_and_burn(&mut treasury, 200_000, 900_000);
+    buyback_and_burn(&mut treasury, 100_000, 400_000);
+
+    assert_eq!(treasury.total_buybacks_usdc, 300_000);
+    assert_eq!(treasury.total_buyback_tokens_received, 1_300_000);
+    assert_eq!(treasury.total_tokens_burned, 1_300_000);
+    assert_eq!(treasury.epoch_tokens_burned, 1_300_000);
+    assert_eq!(treasury.epoch_funnel().burn_share_bps, 10_000);
+}
+
+#[test]
+fn fills_draw_on_the_buyback_budget() {
+    let mut treasury = treasury();
+
+    buyback_and_burn(&mut treasury, 400_000, 1_000_000);
+
+    assert_eq!(treasury.remaining_buyback_budget().unwrap(), 100_000);
+    assert!(treasury
+        .authorize_usdc_spend(UsdcSpend::Buyback, 100_001, NOW)
+        .is_err());
+}
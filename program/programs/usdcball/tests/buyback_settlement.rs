@@ -0,0 +1,101 @@
+//! Every buyback fill is held to what it was allowed to spend and its minimum
+//! output, then judged against the pre-trade reference price before it is booked.
+
+use anchor_lang::{AnchorDeserialize, Space};
+use usdcball::Treasury;
+
+const NOW: i64 = 10 * 86_400;
+const DECIMALS: u8 = 6;
+
+fn treasury() -> Treasury {
+    let mut treasury =
+        Treasury::deserialize(&mut &vec![0u8; Treasury::INIT_SPACE][..]).expect("zeroed account");
+    treasury.buyback_allocation_bps = 10_000;
+    treasury.total_usdc_converted = 10_000_000;
+    treasury.stalled = true;
+    treasury
+}
+
+#[test]
+fn fill_at_the_reference_is_booked() {
+    let mut treasury = treasury();
+
+    // 1 USDC for 2 tokens is $0.50 a token
+    let settled = treasury
+        .settle_buyback(1_000_000, 1_000_000, 2_000_000, 1_900_000, Some(500_000), DECIMALS, NOW)
+        .unwrap();
+
+    assert_eq!(settled, (500_000, false));
+    assert_eq!(treasury.total_buybacks_usdc, 1_000_000);
+    assert_eq!(treasury.total_buyback_tokens_received, 2_000_000);
+    assert!(!treasury.stalled);
+}
+
+#[test]
+fn fill_outside_its_limits_is_rejected() {
+    let mut treasury = treasury();
+
+    // Spent more than allowed, delivered less than the minimum, or nothing at all
+    for (spent, received) in [(1_000_001, 2_000_000), (1_000_000, 1_899_999), (1_000_000, 0)] {
+        assert!(treasury
+            .settle_buyback(1_000_000, spent, received, 1_900_000, None, DECIMALS, NOW)
+            .is_err());
+    }
+    assert_eq!(treasury.total_buybacks_usdc, 0);
+    assert_eq!(treasury.total_buyback_tokens_received, 0);
+}
+
+#[test]
+fn fill_above_the_reference_is_degraded() {
+    let mut treasury = treasury();
+    treasury.settlement_max_deviation_bps = 100;
+
+    // $0.52 against $0.50 is 400 bps worse
+    let (price, degraded) = treasury
+        .settle_buyback(1_040_000, 1_040_000, 2_000_000, 0, Some(500_000), DECIMALS, NOW)
+        .unwrap();
+
+    assert_eq!(price, 520_000);
+    assert!(degraded);
+    assert_eq!(treasury.degraded_executions, 1);
+    assert!(!treasury.fully_paused());
+}
+
+#[test]
+fn degraded_fills_trip_the_circuit_breaker() {
+    let mut treasury = treasury();
+    treasury.settlement_max_deviation_bps = 100;
+    treasury.degraded_execution_limit = 2;
+
+    for _ in 0..2 {
+        treasury
+            .settle_buyback(1_040_000, 1_040_000, 2_000_000, 0, Some(500_000), DECIMALS, NOW)
+            .unwrap();
+    }
+
+    assert!(treasury.fully_paused());
+    assert_eq!(treasury.circuit_breaker_tripped_at, NOW);
+}
+
+#[test]
+fn fill_without_a_reference_is_not_judged() {
+    let mut treasury = treasury();
+    treasury.settlement_max_deviation_bps = 1;
+
+    let (_, degraded) = treasury
+        .settle_buyback(1_000_000, 1_000_000, 1_000_000, 0, None, DECIMALS, NOW)
+        .unwrap();
+
+    assert!(!degraded);
+    assert_eq!(treasury.degraded_executions, 0);
+}
+
+#[test]
+fn min_out_below_the_oracle_floor_is_rejected() {
+    let mut treasury = treasury();
+    treasury.slippage_bps = 100;
+
+    // 2_000_000 tokens at the oracle price leaves a floor of 1_980_000
+    treasury.require_oracle_floor(2_000_000, 1_980_000).unwrap();
+    assert!(treasury.require_oracle_floor(2_000_000, 1_979_999).is_err());
+}
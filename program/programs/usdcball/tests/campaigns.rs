@@ -0,0 +1,99 @@
+//! Campaign rewards stream per second to whoever is staked; stretches with no
+//! stake roll over to the next stakers instead of staying in the reward vault.
+
+use anchor_lang::prelude::Pubkey;
+use usdcball::{Campaign, StakePosition, REWARD_PRECISION};
+
+fn campaign(rate: u64, start_time: i64, end_time: i64) -> Campaign {
+    Campaign {
+        index: 0,
+        pool: Pubkey::new_unique(),
+        lp_mint: Pubkey::new_unique(),
+        reward_budget: rate * (end_time - start_time) as u64,
+        reward_rate_per_second: rate,
+        start_time,
+        end_time,
+        acc_reward_per_share: 0,
+        last_update_time: start_time,
+        total_staked: 0,
+        total_claimed: 0,
+        bump: 255,
+    }
+}
+
+fn position(campaign: &Campaign, amount: u64) -> StakePosition {
+    StakePosition {
+        campaign: Pubkey::new_unique(),
+        owner: Pubkey::new_unique(),
+        amount,
+        reward_debt: campaign.reward_debt_for(amount).unwrap(),
+        pending_rewards: 0,
+        bump: 255,
+    }
+}
+
+fn stake(campaign: &mut Campaign, now: i64, amount: u64) -> StakePosition {
+    campaign.accrue(now).unwrap();
+    campaign.total_staked += amount;
+    position(campaign, amount)
+}
+
+fn earned(campaign: &mut Campaign, now: i64, position: &mut StakePosition) -> u64 {
+    campaign.accrue(now).unwrap();
+    campaign.settle(position).unwrap();
+    position.reward_debt = campaign.reward_debt_for(position.amount).unwrap();
+    position.pending_rewards
+}
+
+#[test]
+fn rewards_before_the_first_stake_go_to_the_first_staker() {
+    let mut campaign = campaign(10, 1_000, 2_000);
+
+    let mut late = stake(&mut campaign, 1_400, 50);
+
+    assert_eq!(earned(&mut campaign, 2_000, &mut late), 10_000);
+}
+
+#[test]
+fn a_gap_with_nothing_staked_rolls_over() {
+    let mut campaign = campaign(10, 1_000, 2_000);
+    let mut first = stake(&mut campaign, 1_000, 100);
+    assert_eq!(earned(&mut campaign, 1_200, &mut first), 2_000);
+    campaign.total_staked -= first.amount;
+
+    campaign.accrue(1_500).unwrap();
+    let mut second = stake(&mut campaign, 1_600, 100);
+
+    assert_eq!(earned(&mut campaign, 2_000, &mut second), 8_000);
+}
+
+#[test]
+fn concurrent_stakers_split_by_share() {
+    let mut campaign = campaign(9, 0, 100);
+    let mut small = stake(&mut campaign, 0, 100);
+    let mut large = stake(&mut campaign, 0, 200);
+
+    assert_eq!(earned(&mut campaign, 100, &mut small), 300);
+    assert_eq!(earned(&mut campaign, 100, &mut large), 600);
+    assert_eq!(campaign.acc_reward_per_share, 3 * REWARD_PRECISION);
+}
+
+#[test]
+fn nothing_streams_past_the_end() {
+    let mut campaign = campaign(10, 0, 100);
+    let mut staker = stake(&mut campaign, 50, 1);
+
+    assert_eq!(earned(&mut campaign, 10_000, &mut staker), 1_000);
+    assert_eq!(earned(&mut campaign, 20_000, &mut staker), 1_000);
+}
+
+#[test]
+fn cancelled_campaign_streams_nothing() {
+    let mut campaign = campaign(10, 1_000, 2_000);
+    let mut early = stake(&mut campaign, 500, 100);
+
+    campaign.cancel(600);
+
+    assert_eq!(campaign.reward_budget, 0);
+    assert_eq!(earned(&mut campaign, 5_000, &mut early), 0);
+}
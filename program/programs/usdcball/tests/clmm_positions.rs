@@ -0,0 +1,14 @@
+//! Raydium CLMM orders a pool's tokens by mint address, so the treasury's USDC
+//! and token sides are passed as token 0 / token 1 accordingly.
+
+use anchor_lang::prelude::Pubkey;
+use usdcball::raydium_clmm::order_pair;
+
+#[test]
+fn lower_mint_is_token_0() {
+    let low = Pubkey::new_from_array([1; 32]);
+    let high = Pubkey::new_from_array([2; 32]);
+
+    assert_eq!(order_pair(&low, &high, "usdc", "token"), ("usdc", "token"));
+    assert_eq!(order_pair(&high, &low, "usdc", "token"), ("token", "usdc"));
+}
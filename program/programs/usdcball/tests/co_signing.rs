@@ -0,0 +1,105 @@
+//! Above the co-sign threshold an operation needs a second, distinct key: the
+//! admin, another active registered operator or the dedicated co-signer.
+
+use anchor_lang::{prelude::Pubkey, AnchorDeserialize, Space};
+use usdcball::{Operator, Treasury};
+
+const THRESHOLD: u64 = 5_000_000_000;
+
+fn treasury() -> Treasury {
+    let mut treasury =
+        Treasury::deserialize(&mut &vec![0u8; Treasury::INIT_SPACE][..]).expect("zeroed account");
+    treasury.authority = Pubkey::new_unique();
+    treasury.cosign_threshold_usdc = THRESHOLD;
+    treasury
+}
+
+fn registered(key: Pubkey, active: bool) -> Operator {
+    let mut operator =
+        Operator::deserialize(&mut &vec![0u8; Operator::INIT_SPACE][..]).expect("zeroed account");
+    operator.operator = key;
+    operator.active = active;
+    operator
+}
+
+#[test]
+fn small_operations_need_no_co_signer() {
+    let treasury = treasury();
+    let operator = Pubkey::new_unique();
+
+    treasury
+        .require_co_signer(THRESHOLD, operator, None, None)
+        .unwrap();
+    assert!(treasury
+        .require_co_signer(THRESHOLD + 1, operator, None, None)
+        .is_err());
+}
+
+#[test]
+fn admin_co_signs_for_an_operator() {
+    let treasury = treasury();
+    let operator = Pubkey::new_unique();
+
+    treasury
+        .require_co_signer(THRESHOLD + 1, operator, Some(treasury.authority), None)
+        .unwrap();
+    // The admin cannot co-sign its own operation
+    assert!(treasury
+        .require_co_signer(
+            THRESHOLD + 1,
+            treasury.authority,
+            Some(treasury.authority),
+            None
+        )
+        .is_err());
+}
+
+#[test]
+fn a_second_active_operator_co_signs() {
+    let treasury = treasury();
+    let operator = Pubkey::new_unique();
+    let second = Pubkey::new_unique();
+
+    treasury
+        .require_co_signer(
+            THRESHOLD + 1,
+            operator,
+            Some(second),
+            Some(&registered(second, true)),
+        )
+        .unwrap();
+    // Revoked, unregistered or the same operator twice
+    assert!(treasury
+        .require_co_signer(
+            THRESHOLD + 1,
+            operator,
+            Some(second),
+            Some(&registered(second, false)),
+        )
+        .is_err());
+    assert!(treasury
+        .require_co_signer(THRESHOLD + 1, operator, Some(second), None)
+        .is_err());
+    assert!(treasury
+        .require_co_signer(
+            THRESHOLD + 1,
+            operator,
+            Some(operator),
+            Some(&registered(operator, true)),
+        )
+        .is_err());
+}
+
+#[test]
+fn an_unset_co_signer_key_authorizes_nobody() {
+    let mut treasury = treasury();
+    let operator = Pubkey::new_unique();
+
+    assert!(treasury
+        .require_co_signer(THRESHOLD + 1, operator, Some(Pubkey::default()), None)
+        .is_err());
+    treasury.co_signer = Pubkey::new_unique();
+    treasury
+        .require_co_signer(THRESHOLD + 1, operator, Some(treasury.co_signer), None)
+        .unwrap();
+}
@@ -0,0 +1,73 @@
+//! An epoch's fee-to-burn funnel is measured from the totals when the epoch
+//! opened, with each ratio taken against the step before it.
+
+use anchor_lang::{AnchorDeserialize, Space};
+use usdcball::{ConversionFunnel, Treasury};
+
+fn treasury() -> Treasury {
+    Treasury::deserialize(&mut &vec![0u8; Treasury::INIT_SPACE][..]).expect("zeroed account")
+}
+
+#[test]
+fn ratios_compare_consecutive_steps() {
+    // 10 SOL -> 1_500 USDC -> 600 USDC of buybacks -> 3_000 tokens -> 2_400 burned
+    let funnel = ConversionFunnel::new(
+        10_000_000_000,
+        1_500_000_000,
+        600_000_000,
+        3_000_000_000,
+        2_400_000_000,
+    );
+
+    assert_eq!(funnel.usdc_per_sol, 150_000_000);
+    assert_eq!(funnel.buyback_share_bps, 4_000);
+    assert_eq!(funnel.tokens_per_usdc, 5_000_000);
+    assert_eq!(funnel.burn_share_bps, 8_000);
+}
+
+#[test]
+fn empty_steps_give_zero_ratios() {
+    let funnel = ConversionFunnel::new(0, 250_000_000, 0, 0, 1_000);
+
+    assert_eq!(funnel.usdc_per_sol, 0);
+    assert_eq!(funnel.buyback_share_bps, 0);
+    assert_eq!(funnel.tokens_per_usdc, 0);
+    assert_eq!(funnel.burn_share_bps, 0);
+}
+
+#[test]
+fn epoch_funnel_counts_only_the_current_epoch() {
+    let mut treasury = treasury();
+    treasury.total_sol_collected = 4_000_000_000;
+    treasury.total_usdc_converted = 600_000_000;
+    treasury.total_buybacks_usdc = 300_000_000;
+    treasury.record_buyback_tokens(1_000_000).unwrap();
+    treasury.start_funnel_epoch();
+
+    treasury.total_sol_collected += 2_000_000_000;
+    treasury.total_usdc_converted += 300_000_000;
+    treasury.total_buybacks_usdc += 150_000_000;
+    treasury.record_buyback_tokens(500_000).unwrap();
+    treasury.record_burn(500_000).unwrap();
+
+    assert_eq!(
+        treasury.epoch_funnel(),
+        ConversionFunnel::new(2_000_000_000, 300_000_000, 150_000_000, 500_000, 500_000)
+    );
+    assert_eq!(treasury.epoch_funnel().burn_share_bps, 10_000);
+}
+
+#[test]
+fn burned_transfer_fees_are_not_tokens_received() {
+    let mut treasury = treasury();
+    treasury.record_buyback_tokens(1_000_000).unwrap();
+    treasury.record_burn(1_000_000).unwrap();
+
+    // Swept fees are burned without a buyback, so only the burn step grows
+    treasury.record_transfer_fees(250_000).unwrap();
+    treasury.record_burn(250_000).unwrap();
+
+    assert_eq!(treasury.total_transfer_fees_harvested, 250_000);
+    assert_eq!(treasury.total_buyback_tokens_received, 1_000_000);
+    assert_eq!(treasury.epoch_funnel().burn_share_bps, 12_500);
+}
@@ -0,0 +1,39 @@
+//! Burns made outside the program are only credited up to the supply drop since
+//! the last snapshot that recorded burns do not already explain.
+
+use anchor_lang::{AnchorDeserialize, Space};
+use usdcball::Treasury;
+
+fn treasury() -> Treasury {
+    Treasury::deserialize(&mut &vec![0u8; Treasury::INIT_SPACE][..]).expect("zeroed account")
+}
+
+#[test]
+fn needs_a_snapshot() {
+    assert!(treasury().unregistered_external_burn(1_000).is_err());
+}
+
+#[test]
+fn program_burns_and_registered_burns_are_not_credited_twice() {
+    let mut treasury = treasury();
+    treasury.record_burn(500).unwrap();
+    treasury.snapshot_supply(1_000_000, 42);
+
+    // 300 burned by the program, 200 burned elsewhere
+    treasury.record_burn(300).unwrap();
+    assert_eq!(treasury.unregistered_external_burn(999_500).unwrap(), 200);
+
+    treasury.record_burn(150).unwrap();
+    assert_eq!(treasury.unregistered_external_burn(999_500).unwrap(), 50);
+    treasury.record_burn(50).unwrap();
+    assert_eq!(treasury.unregistered_external_burn(999_500).unwrap(), 0);
+}
+
+#[test]
+fn mints_offset_the_supply_drop() {
+    let mut treasury = treasury();
+    treasury.snapshot_supply(1_000_000, 42);
+
+    assert_eq!(treasury.unregistered_external_burn(1_000_100).unwrap(), 0);
+    assert_eq!(treasury.unregistered_external_burn(999_900).unwrap(), 100);
+}
@@ -0,0 +1,42 @@
+//! Guardian records keep response-time statistics the DAO can compare across
+//! guardian keys.
+
+use usdcball::GuardianRecord;
+
+fn record() -> GuardianRecord {
+    GuardianRecord {
+        guardian: Default::default(),
+        responses: 0,
+        total_response_seconds: 0,
+        last_response_seconds: 0,
+        slowest_response_seconds: 0,
+        vetoes: 0,
+        opened_at: 0,
+        bump: 0,
+    }
+}
+
+#[test]
+fn averages_response_times() {
+    let mut record = record();
+    assert_eq!(record.average_response_seconds(), 0);
+
+    record.record_response(600, false);
+    record.record_response(3_000, true);
+    record.record_response(300, false);
+
+    assert_eq!(record.responses, 3);
+    assert_eq!(record.vetoes, 1);
+    assert_eq!(record.last_response_seconds, 300);
+    assert_eq!(record.slowest_response_seconds, 3_000);
+    assert_eq!(record.average_response_seconds(), 1_300);
+}
+
+#[test]
+fn clock_skew_counts_as_an_instant_response() {
+    let mut record = record();
+    record.record_response(-5, false);
+
+    assert_eq!(record.last_response_seconds, 0);
+    assert_eq!(record.average_response_seconds(), 0);
+}
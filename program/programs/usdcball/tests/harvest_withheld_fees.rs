@@ -0,0 +1,64 @@
+//! `harvest_withheld_fees` burns the transfer fees it sweeps, so they count as
+//! harvested and burned but never as tokens a buyback delivered.
+
+use anchor_lang::{AnchorDeserialize, Space};
+use usdcball::{GatedInstruction, PauseFlag, Treasury};
+
+fn treasury() -> Treasury {
+    let mut treasury =
+        Treasury::deserialize(&mut &vec![0u8; Treasury::INIT_SPACE][..]).expect("zeroed account");
+    treasury.instruction_enabled = u64::MAX;
+    treasury.total_buyback_tokens_received = 2_000_000;
+    treasury
+}
+
+/// Book a sweep of `harvested` withheld tokens the way `harvest_withheld_fees` does
+fn harvest(treasury: &mut Treasury, harvested: u64) -> anchor_lang::Result<()> {
+    treasury.require_not_paused(PauseFlag::Fees)?;
+    treasury.require_not_paused(PauseFlag::Buybacks)?;
+    treasury.require_enabled(GatedInstruction::HarvestTransferFees)?;
+    treasury.require_enabled(GatedInstruction::ExecuteBurn)?;
+    treasury.record_transfer_fees(harvested)?;
+    treasury.record_burn(harvested)
+}
+
+#[test]
+fn harvest_is_counted_as_harvested_and_burned() {
+    let mut treasury = treasury();
+
+    harvest(&mut treasury, 150_000).unwrap();
+    harvest(&mut treasury, 50_000).unwrap();
+
+    assert_eq!(treasury.total_transfer_fees_harvested, 200_000);
+    assert_eq!(treasury.total_tokens_burned, 200_000);
+    assert_eq!(treasury.epoch_tokens_burned, 200_000);
+    assert_eq!(treasury.total_buyback_tokens_received, 2_000_000);
+}
+
+#[test]
+fn empty_harvest_books_nothing() {
+    let mut treasury = treasury();
+
+    harvest(&mut treasury, 0).unwrap();
+
+    assert_eq!(treasury.total_transfer_fees_harvested, 0);
+    assert_eq!(treasury.total_tokens_burned, 0);
+}
+
+#[test]
+fn paused_fees_or_burns_stop_the_harvest() {
+    for flag in [PauseFlag::Fees, PauseFlag::Buybacks] {
+        let mut treasury = treasury();
+        treasury.pause_flags = flag.mask();
+        assert!(harvest(&mut treasury, 1).is_err());
+    }
+    for gate in [
+        GatedInstruction::HarvestTransferFees,
+        GatedInstruction::ExecuteBurn,
+    ] {
+        let mut treasury = treasury();
+        treasury.instruction_enabled &= !gate.mask();
+        assert!(harvest(&mut treasury, 1).is_err());
+        assert_eq!(treasury.total_tokens_burned, 0);
+    }
+}
@@ -0,0 +1,49 @@
+//! During its first `launch_epochs` epochs the treasury caps every cycle at
+//! 100 USDC and requires the guardian on every operation, then relaxes to the
+//! configured limits on its own.
+
+use anchor_lang::{prelude::Pubkey, AnchorDeserialize, Space};
+use usdcball::{Treasury, LAUNCH_MAX_USDC_PER_CYCLE};
+
+fn treasury() -> Treasury {
+    let mut treasury =
+        Treasury::deserialize(&mut &vec![0u8; Treasury::INIT_SPACE][..]).expect("zeroed account");
+    treasury.max_usdc_per_cycle = 10_000_000_000;
+    treasury.launch_mode_until_epoch = 3;
+    treasury
+}
+
+#[test]
+fn cycles_are_capped_until_launch_ends() {
+    let mut treasury = treasury();
+
+    assert!(treasury.in_launch_mode());
+    assert_eq!(treasury.cycle_limit_usdc(), LAUNCH_MAX_USDC_PER_CYCLE);
+    assert_eq!(treasury.launch_capped(5_000_000), 5_000_000);
+
+    treasury.current_epoch = 3;
+    assert!(!treasury.in_launch_mode());
+    assert_eq!(treasury.cycle_limit_usdc(), 10_000_000_000);
+}
+
+#[test]
+fn launch_mode_requires_the_guardian() {
+    let mut treasury = treasury();
+    let guardian = Pubkey::new_unique();
+    let operator = Pubkey::new_unique();
+
+    // No guardian configured: nothing can be co-signed
+    assert!(treasury.require_co_signer(1, operator, None, None).is_err());
+    treasury.guardian = guardian;
+    assert!(treasury.require_co_signer(1, operator, None, None).is_err());
+    assert!(treasury
+        .require_co_signer(1, operator, Some(Pubkey::new_unique()), None)
+        .is_err());
+    treasury
+        .require_co_signer(1, operator, Some(guardian), None)
+        .unwrap();
+
+    // Below the regular threshold once launch mode is over
+    treasury.current_epoch = 3;
+    treasury.require_co_signer(1, operator, None, None).unwrap();
+}
@@ -0,0 +1,76 @@
+//! A constant-product pool's LP position keeps the treasury's contributions and
+//! withdrawals reconcilable, and only venue withdrawals the program understands
+//! can unwind it.
+
+use anchor_lang::{AnchorDeserialize, Space};
+use usdcball::{LpPosition, Venue};
+
+fn position() -> LpPosition {
+    LpPosition::deserialize(&mut &vec![0u8; LpPosition::INIT_SPACE][..]).expect("zeroed account")
+}
+
+#[test]
+fn deposits_and_withdrawals_are_tracked() {
+    let mut position = position();
+    position
+        .record_deposit(1_000_000, 4_000_000, 2_000)
+        .unwrap();
+    position.record_deposit(500_000, 2_000_000, 3_000).unwrap();
+    assert_eq!(position.usdc_contributed, 1_500_000);
+    assert_eq!(position.token_contributed, 6_000_000);
+    assert_eq!(position.lp_amount, 3_000);
+
+    position
+        .record_withdrawal(1_000, 510_000, 1_950_000, 2_000)
+        .unwrap();
+    assert_eq!(position.lp_withdrawn, 1_000);
+    assert_eq!(position.usdc_withdrawn, 510_000);
+    assert_eq!(position.token_withdrawn, 1_950_000);
+    assert_eq!(position.lp_amount, 2_000);
+}
+
+#[test]
+fn fee_claims_leave_the_net_contribution_untouched() {
+    let mut position = position();
+    position
+        .record_deposit(1_000_000, 4_000_000, 2_000)
+        .unwrap();
+    position.record_fees(20, 10_000, 40_000, 1_980).unwrap();
+    assert_eq!(position.usdc_fees_collected, 10_000);
+    assert_eq!(position.token_fees_collected, 40_000);
+    assert_eq!(position.lp_withdrawn, 20);
+    assert_eq!(position.lp_amount, 1_980);
+    assert_eq!(position.usdc_withdrawn, 0);
+}
+
+#[test]
+fn fees_may_only_come_out_of_growth() {
+    let mut position = position();
+    position
+        .record_deposit(1_000_000, 4_000_000, 2_000)
+        .unwrap();
+    // The pool grew 2% from fees: 2_000 of 10_000 LP is worth 2 * 1_020_000 USDC
+    // against 2_000_000 of net contribution
+    let (supply, pool_usdc, pool_token) = (10_000, 5_100_000, 20_400_000);
+    assert!(position.covers_principal(2_000, supply, pool_usdc, pool_token));
+    assert!(position.covers_principal(1_961, supply, pool_usdc, pool_token));
+    assert!(!position.covers_principal(1_900, supply, pool_usdc, pool_token));
+    // An empty pool values nothing
+    assert!(!position.covers_principal(2_000, 0, pool_usdc, pool_token));
+}
+
+#[test]
+fn only_raydium_withdrawals_name_an_lp_amount() {
+    let mut withdraw = vec![4u8];
+    withdraw.extend_from_slice(&1_234u64.to_le_bytes());
+    let raydium = Venue::Raydium.handler();
+    assert_eq!(raydium.withdraw_lp_amount(&withdraw), Some(1_234));
+    // A swap, and a withdrawal missing its amount
+    assert_eq!(
+        raydium.withdraw_lp_amount(&[9, 0, 0, 0, 0, 0, 0, 0, 0]),
+        None
+    );
+    assert_eq!(raydium.withdraw_lp_amount(&[4, 1, 2]), None);
+
+    assert_eq!(Venue::Jupiter.handler().withdraw_lp_amount(&withdraw), None);
+}
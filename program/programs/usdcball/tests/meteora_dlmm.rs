@@ -0,0 +1,19 @@
+//! A Meteora DLMM position spans the configured bin offsets around the pair's
+//! active bin, and no more bins than one position holds.
+
+use usdcball::meteora_dlmm::{bin_range, MAX_BINS_PER_POSITION};
+
+#[test]
+fn bins_follow_the_active_bin() {
+    assert_eq!(bin_range(100, -10, -1), Some(90..=99));
+    assert_eq!(bin_range(-5, 0, 0), Some(-5..=-5));
+}
+
+#[test]
+fn rejects_unordered_or_oversized_ranges() {
+    assert_eq!(bin_range(0, 1, 0), None);
+    assert_eq!(bin_range(0, 0, MAX_BINS_PER_POSITION), None);
+    assert!(bin_range(0, 0, MAX_BINS_PER_POSITION - 1).is_some());
+    assert_eq!(bin_range(0, i32::MIN, i32::MAX), None);
+    assert_eq!(bin_range(i32::MAX, 0, 1), None);
+}
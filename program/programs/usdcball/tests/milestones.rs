@@ -0,0 +1,41 @@
+//! Milestones sit at multiples of the configured interval and can only be
+//! recorded once the lifetime total has crossed them.
+
+use anchor_lang::{AnchorDeserialize, Space};
+use usdcball::{MilestoneKind, Treasury};
+
+fn treasury() -> Treasury {
+    let mut treasury =
+        Treasury::deserialize(&mut &vec![0u8; Treasury::INIT_SPACE][..]).expect("zeroed account");
+    treasury.burn_milestone_interval_tokens = 1_000_000;
+    treasury.total_tokens_burned = 2_500_000;
+    treasury.total_buybacks_usdc = 10_000_000_000;
+    treasury
+}
+
+#[test]
+fn only_crossed_multiples_of_the_interval_are_milestones() {
+    let treasury = treasury();
+
+    treasury
+        .require_milestone_reached(MilestoneKind::Burns, 2_000_000)
+        .unwrap();
+    assert!(treasury
+        .require_milestone_reached(MilestoneKind::Burns, 3_000_000)
+        .is_err());
+    assert!(treasury
+        .require_milestone_reached(MilestoneKind::Burns, 1_500_000)
+        .is_err());
+    assert!(treasury
+        .require_milestone_reached(MilestoneKind::Burns, 0)
+        .is_err());
+}
+
+#[test]
+fn milestones_without_an_interval_are_off() {
+    let treasury = treasury();
+
+    assert!(treasury
+        .require_milestone_reached(MilestoneKind::Buybacks, 1_000_000)
+        .is_err());
+}
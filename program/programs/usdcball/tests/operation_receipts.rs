@@ -0,0 +1,44 @@
+//! A released operation's receipt settles once the committed minimum arrived,
+//! and with whatever arrived once the claim window past the deadline closes.
+
+use anchor_lang::{prelude::Pubkey, AnchorDeserialize, Space};
+use usdcball::PendingOperation;
+
+const WINDOW: u64 = 150;
+
+fn operation() -> PendingOperation {
+    let mut operation =
+        PendingOperation::deserialize(&mut &vec![0u8; PendingOperation::INIT_SPACE][..])
+            .expect("zeroed account");
+    operation.deadline_slot = 1_000;
+    operation.receipt = Pubkey::new_from_array([7; 32]);
+    operation.min_output = 5_000;
+    operation.released = true;
+    operation
+}
+
+#[test]
+fn full_delivery_settles_at_any_time() {
+    let operation = operation();
+
+    assert!(!operation.require_settleable(5_000, 1_001, WINDOW).unwrap());
+    assert!(!operation.require_settleable(9_000, 5_000, WINDOW).unwrap());
+}
+
+#[test]
+fn short_delivery_waits_for_the_claim_window() {
+    let operation = operation();
+
+    assert_eq!(operation.claim_deadline_slot(WINDOW).unwrap(), 1_150);
+    assert!(operation.require_settleable(4_999, 1_150, WINDOW).is_err());
+    assert!(operation.require_settleable(4_999, 1_151, WINDOW).unwrap());
+    assert!(operation.require_settleable(0, 1_151, WINDOW).unwrap());
+}
+
+#[test]
+fn unreleased_operations_do_not_settle() {
+    let mut operation = operation();
+    operation.released = false;
+
+    assert!(operation.require_settleable(5_000, 1_001, WINDOW).is_err());
+}
@@ -0,0 +1,51 @@
+//! Per-pool overrides tune a registered pool's slippage and size limit, but a
+//! pool's size limit only ever tightens the treasury's per-cycle limit.
+
+use anchor_lang::{prelude::Pubkey, AnchorDeserialize, Space};
+use usdcball::{PoolType, RegisteredPool, Treasury, Venue};
+
+fn treasury() -> Treasury {
+    let mut treasury =
+        Treasury::deserialize(&mut &vec![0u8; Treasury::INIT_SPACE][..]).expect("zeroed account");
+    treasury.slippage_bps = 100;
+    treasury.max_usdc_per_cycle = 1_000_000_000;
+    treasury
+}
+
+fn pool(slippage_bps: u16, max_usdc_amount: u64) -> RegisteredPool {
+    RegisteredPool {
+        venue: Venue::Raydium,
+        usdc_vault: Pubkey::new_unique(),
+        token_vault: Pubkey::new_unique(),
+        slippage_bps,
+        max_usdc_amount,
+        pool_type: PoolType::ConstantProduct,
+    }
+}
+
+#[test]
+fn unset_overrides_fall_back_to_the_treasury() {
+    let treasury = treasury();
+    let pool = pool(0, 0);
+
+    assert_eq!(pool.slippage_bps(&treasury), 100);
+    assert_eq!(pool.max_usdc_amount(&treasury), 1_000_000_000);
+}
+
+#[test]
+fn pool_limit_tightens_the_cycle_limit() {
+    let treasury = treasury();
+
+    assert_eq!(pool(30, 250_000_000).max_usdc_amount(&treasury), 250_000_000);
+    assert_eq!(pool(30, 250_000_000).slippage_bps(&treasury), 30);
+}
+
+#[test]
+fn pool_limit_never_lifts_the_cycle_limit() {
+    let mut treasury = treasury();
+    let pool = pool(0, 5_000_000_000);
+
+    assert_eq!(pool.max_usdc_amount(&treasury), 1_000_000_000);
+    treasury.max_usdc_per_cycle = 0;
+    assert_eq!(pool.max_usdc_amount(&treasury), 0);
+}
@@ -0,0 +1,98 @@
+//! Reserve USDC lent out stays committed against the reserve budget until it is
+//! redeemed, and redemptions release the matching share of the principal.
+//! Harvested interest is credited like newly converted USDC.
+
+use anchor_lang::{prelude::Pubkey, AnchorDeserialize, Space};
+use usdcball::{lending, LendingVenue, Treasury, UsdcSpend};
+
+fn treasury() -> Treasury {
+    let mut treasury =
+        Treasury::deserialize(&mut &vec![0u8; Treasury::INIT_SPACE][..]).expect("zeroed account");
+    treasury.buyback_allocation_bps = 5_000;
+    treasury.liquidity_allocation_bps = 3_000;
+    treasury.reserve_allocation_bps = 2_000;
+    treasury.total_usdc_converted = 1_000_000;
+    treasury
+}
+
+#[test]
+fn kamino_deposits_are_committed_reserve() {
+    let mut treasury = treasury();
+
+    treasury
+        .authorize_usdc_spend(UsdcSpend::KaminoDeposit, 150_000, 0)
+        .unwrap();
+    treasury
+        .settle_usdc_spend(UsdcSpend::KaminoDeposit, 150_000, 150_000, 0)
+        .unwrap();
+    assert_eq!(treasury.kamino_deposited_usdc, 150_000);
+    assert_eq!(treasury.remaining_reserve_budget().unwrap(), 50_000);
+    assert!(treasury
+        .authorize_usdc_spend(UsdcSpend::Reserve, 50_001, 0)
+        .is_err());
+}
+
+#[test]
+fn redemptions_release_their_share_of_principal() {
+    let mut treasury = treasury();
+    treasury.kamino_deposited_usdc = 150_000;
+    treasury.kamino_collateral_amount = 120_000;
+
+    assert_eq!(treasury.kamino_principal_share(40_000), 50_000);
+    assert_eq!(treasury.kamino_principal_share(1), 1);
+    // The last kTokens take whatever principal rounding left behind
+    assert_eq!(treasury.kamino_principal_share(120_000), 150_000);
+}
+
+#[test]
+fn every_venue_counts_towards_the_reserve_commitment() {
+    let mut treasury = treasury();
+    assert!(!treasury.has_open_lending_position());
+
+    treasury
+        .authorize_usdc_spend(UsdcSpend::MarginFiDeposit, 120_000, 0)
+        .unwrap();
+    treasury
+        .settle_usdc_spend(UsdcSpend::MarginFiDeposit, 120_000, 120_000, 0)
+        .unwrap();
+    treasury.kamino_deposited_usdc = 30_000;
+    assert!(treasury.has_open_lending_position());
+    assert_eq!(treasury.total_reserve_committed_usdc(), 150_000);
+    assert_eq!(treasury.remaining_reserve_budget().unwrap(), 50_000);
+}
+
+#[test]
+fn solend_positions_go_through_the_lending_dispatch() {
+    let mut treasury = treasury();
+    treasury.solend_reserve = Pubkey::new_unique();
+
+    // Only the active venue takes deposits
+    assert!(lending::authorize_deposit(&mut treasury, LendingVenue::Solend, 100_000, 0).is_err());
+    treasury.lending_venue = LendingVenue::Solend;
+    let adapter =
+        lending::authorize_deposit(&mut treasury, LendingVenue::Solend, 100_000, 0).unwrap();
+    treasury
+        .settle_usdc_spend(adapter.deposit_spend(), 100_000, 100_000, 0)
+        .unwrap();
+    treasury.solend_collateral_amount = 80_000;
+
+    assert!(treasury.has_open_lending_position());
+    assert_eq!(treasury.total_lent_usdc(), 100_000);
+    assert_eq!(treasury.remaining_reserve_budget().unwrap(), 100_000);
+    assert_eq!(treasury.solend_principal_share(20_000), 25_000);
+}
+
+#[test]
+fn harvests_are_limited_to_interest_and_feed_the_budgets() {
+    // 100 kTokens back 1_000 USDC of principal; 5 redeemed for 55 USDC puts the
+    // rest at 1_045 USDC
+    assert!(lending::covers_principal(95, 55, 5, 1_000));
+    // 10 redeemed for 110 leaves 990, short of the principal
+    assert!(!lending::covers_principal(90, 110, 10, 1_000));
+
+    let mut treasury = treasury();
+    treasury.record_yield(50_000).unwrap();
+    assert_eq!(treasury.total_yield_harvested_usdc, 50_000);
+    assert_eq!(treasury.total_usdc_converted, 1_050_000);
+    assert_eq!(treasury.remaining_buyback_budget().unwrap(), 525_000);
+}
@@ -0,0 +1,49 @@
+//! `route_best` quotes each candidate from its reserves and swaps on the pool
+//! that returns the most USDCBALL; only constant-product venues can be quoted.
+
+use usdcball::venues::quote_constant_product;
+use usdcball::Venue;
+
+#[test]
+fn quote_follows_the_constant_product_curve() {
+    // 100 USDC into a 10_000 USDC / 50_000 token pool without fees
+    assert_eq!(
+        quote_constant_product(100, 10_000, 50_000, 0),
+        Some(50_000 * 100 / 10_100)
+    );
+    // The fee comes off the input before it reaches the curve
+    assert_eq!(
+        quote_constant_product(10_000, 1_000_000, 1_000_000, 25),
+        quote_constant_product(9_975, 1_000_000, 1_000_000, 0)
+    );
+}
+
+#[test]
+fn deeper_pool_quotes_more_at_the_same_price() {
+    let shallow = quote_constant_product(1_000_000, 10_000_000, 50_000_000, 25).unwrap();
+    let deep = quote_constant_product(1_000_000, 100_000_000, 500_000_000, 25).unwrap();
+
+    assert!(deep > shallow);
+}
+
+#[test]
+fn cheaper_pool_quotes_more_at_the_same_depth() {
+    let cheap = quote_constant_product(1_000_000, 10_000_000, 50_000_000, 25).unwrap();
+    let dear = quote_constant_product(1_000_000, 10_000_000, 50_000_000, 100).unwrap();
+
+    assert!(cheap > dear);
+}
+
+#[test]
+fn empty_pool_quotes_nothing() {
+    assert_eq!(quote_constant_product(1_000_000, 0, 0, 25), Some(0));
+    assert_eq!(quote_constant_product(0, 10_000, 50_000, 25), Some(0));
+}
+
+#[test]
+fn only_constant_product_venues_are_quotable() {
+    assert_eq!(Venue::Raydium.handler().constant_product_fee_bps(), Some(25));
+    for venue in [Venue::Jupiter, Venue::Orca, Venue::Meteora, Venue::Phoenix] {
+        assert_eq!(venue.handler().constant_product_fee_bps(), None);
+    }
+}
@@ -0,0 +1,54 @@
+//! Cranks report why a buyback cannot run instead of failing; `execute_buyback`
+//! itself still fails on the same conditions.
+
+use anchor_lang::{AnchorDeserialize, Space};
+use usdcball::{GatedInstruction, OperationKind, PauseFlag, SkipReason, Treasury};
+
+const NOW: i64 = 1_700_000_000;
+
+/// A treasury that can run a buyback at `NOW`
+fn ready_treasury() -> Treasury {
+    let mut treasury =
+        Treasury::deserialize(&mut &vec![0u8; Treasury::INIT_SPACE][..]).expect("zeroed account");
+    treasury.instruction_enabled = u64::MAX;
+    treasury.buyback_allocation_bps = 5_000;
+    treasury.total_usdc_converted = 1_000_000_000;
+    treasury.cooldown_seconds = 3_600;
+    treasury
+}
+
+#[test]
+fn ready_treasury_has_nothing_to_skip() {
+    assert_eq!(ready_treasury().buyback_skip_reason(NOW).unwrap(), None);
+}
+
+#[test]
+fn skip_reason_names_the_blocking_condition() {
+    let mut treasury = ready_treasury();
+    treasury.pause_flags = PauseFlag::Buybacks.mask();
+    assert_eq!(
+        treasury.buyback_skip_reason(NOW).unwrap(),
+        Some(SkipReason::Paused)
+    );
+
+    let mut treasury = ready_treasury();
+    treasury.instruction_enabled &= !GatedInstruction::ExecuteBuyback.mask();
+    assert_eq!(
+        treasury.buyback_skip_reason(NOW).unwrap(),
+        Some(SkipReason::InstructionDisabled)
+    );
+
+    let mut treasury = ready_treasury();
+    treasury.record_operation(OperationKind::Buyback, NOW - 60);
+    assert_eq!(
+        treasury.buyback_skip_reason(NOW).unwrap(),
+        Some(SkipReason::CooldownActive)
+    );
+
+    let mut treasury = ready_treasury();
+    treasury.total_buybacks_usdc = 500_000_000;
+    assert_eq!(
+        treasury.buyback_skip_reason(NOW).unwrap(),
+        Some(SkipReason::BudgetExhausted)
+    );
+}
@@ -0,0 +1,37 @@
+//! An operator's `report_skip` counts at most once per cooldown window, so a
+//! blocked crank cannot inflate its statistics by reporting in a loop.
+
+use anchor_lang::{AnchorDeserialize, Space};
+use usdcball::{Operator, MIN_SKIP_REPORT_INTERVAL_SECONDS};
+
+fn operator() -> Operator {
+    Operator::deserialize(&mut &vec![0u8; Operator::INIT_SPACE][..]).expect("zeroed account")
+}
+
+#[test]
+fn skips_count_once_per_cooldown_window() {
+    let mut operator = operator();
+
+    operator.record_skip(10_000, 3_600).unwrap();
+    assert!(operator.record_skip(10_001, 3_600).is_err());
+    assert!(operator.record_skip(13_599, 3_600).is_err());
+    operator.record_skip(13_600, 3_600).unwrap();
+
+    assert_eq!(operator.skipped_operations, 2);
+    assert_eq!(operator.last_skip_timestamp, 13_600);
+}
+
+#[test]
+fn short_cooldowns_still_space_out_reports() {
+    let mut operator = operator();
+
+    operator.record_skip(10_000, 0).unwrap();
+    assert!(operator
+        .record_skip(10_000 + MIN_SKIP_REPORT_INTERVAL_SECONDS - 1, 0)
+        .is_err());
+    operator
+        .record_skip(10_000 + MIN_SKIP_REPORT_INTERVAL_SECONDS, 0)
+        .unwrap();
+
+    assert_eq!(operator.skipped_operations, 2);
+}
@@ -0,0 +1,51 @@
+//! The strategy adapter interface is what external programs implement, so its
+//! discriminators must stay those of Anchor instructions of the same names, and
+//! USDC allocated to adapters must come out of the reserve budget.
+
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::{AnchorDeserialize, Space};
+use usdcball::{strategy, Treasury, UsdcSpend};
+
+#[test]
+fn discriminators_match_anchor_instruction_names() {
+    for (name, discriminator) in [
+        ("deposit", strategy::DEPOSIT),
+        ("withdraw", strategy::WITHDRAW),
+        ("report_value", strategy::REPORT_VALUE),
+    ] {
+        let preimage = format!("global:{name}");
+        assert_eq!(hash(preimage.as_bytes()).to_bytes()[..8], discriminator);
+    }
+}
+
+#[test]
+fn reported_values_are_a_single_u64() {
+    assert_eq!(
+        strategy::parse_value(&1_500_000u64.to_le_bytes()).unwrap(),
+        1_500_000
+    );
+    assert!(strategy::parse_value(&[1, 2, 3]).is_err());
+    assert!(strategy::parse_value(&[0; 16]).is_err());
+}
+
+#[test]
+fn strategy_deposits_draw_on_the_reserve_budget() {
+    let mut treasury =
+        Treasury::deserialize(&mut &vec![0u8; Treasury::INIT_SPACE][..]).expect("zeroed account");
+    treasury.buyback_allocation_bps = 5_000;
+    treasury.liquidity_allocation_bps = 3_000;
+    treasury.reserve_allocation_bps = 2_000;
+    treasury.total_usdc_converted = 1_000_000;
+
+    treasury
+        .authorize_usdc_spend(UsdcSpend::StrategyDeposit, 150_000, 0)
+        .unwrap();
+    treasury
+        .settle_usdc_spend(UsdcSpend::StrategyDeposit, 150_000, 150_000, 0)
+        .unwrap();
+    assert_eq!(treasury.strategy_deposited_usdc, 150_000);
+    assert_eq!(treasury.remaining_reserve_budget().unwrap(), 50_000);
+    assert!(treasury
+        .authorize_usdc_spend(UsdcSpend::StrategyDeposit, 50_001, 0)
+        .is_err());
+}
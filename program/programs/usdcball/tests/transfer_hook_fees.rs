@@ -0,0 +1,96 @@
+//! `record_transfer_fee` trusts the hook program the mint names and records the
+//! fee the mint's transfer fee config withholds in the current epoch.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token_2022::spl_token_2022::{
+    extension::{
+        transfer_fee::TransferFeeConfig, transfer_hook::TransferHook, BaseStateWithExtensionsMut,
+        ExtensionType, StateWithExtensionsMut,
+    },
+    state::Mint,
+};
+use usdcball::transfer_hook_accrual;
+
+fn hook_program() -> Pubkey {
+    Pubkey::new_from_array([9; 32])
+}
+
+/// A Token-2022 mint with a transfer hook and, with `fee_bps`, a transfer fee
+/// capped at 1_000 that changes to 2x `fee_bps` at epoch 10
+fn mint(fee_bps: Option<u16>) -> Vec<u8> {
+    let mut extensions = vec![ExtensionType::TransferHook];
+    if fee_bps.is_some() {
+        extensions.push(ExtensionType::TransferFeeConfig);
+    }
+    let len = ExtensionType::try_calculate_account_len::<Mint>(&extensions).unwrap();
+    let mut data = vec![0u8; len];
+    let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+
+    let hook = state.init_extension::<TransferHook>(true).unwrap();
+    hook.program_id = Some(hook_program()).try_into().unwrap();
+    if let Some(bps) = fee_bps {
+        let config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+        config.older_transfer_fee.maximum_fee = 1_000.into();
+        config.older_transfer_fee.transfer_fee_basis_points = bps.into();
+        config.newer_transfer_fee.epoch = 10.into();
+        config.newer_transfer_fee.maximum_fee = 1_000.into();
+        config.newer_transfer_fee.transfer_fee_basis_points = (2 * bps).into();
+    }
+    state.base = Mint {
+        mint_authority: COption::None,
+        supply: 1_000_000_000,
+        decimals: 6,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    };
+    state.pack_base();
+    state.init_account_type().unwrap();
+    data
+}
+
+#[test]
+fn fee_follows_the_epoch_schedule_and_cap() {
+    let data = mint(Some(100));
+
+    assert_eq!(
+        transfer_hook_accrual(&data, 0, 10_000).unwrap(),
+        (hook_program(), 100)
+    );
+    assert_eq!(transfer_hook_accrual(&data, 10, 10_000).unwrap().1, 200);
+    assert_eq!(
+        transfer_hook_accrual(&data, 10, 1_000_000).unwrap().1,
+        1_000
+    );
+}
+
+#[test]
+fn mints_without_a_transfer_fee_accrue_nothing() {
+    let data = mint(None);
+
+    assert_eq!(
+        transfer_hook_accrual(&data, 0, 10_000).unwrap(),
+        (hook_program(), 0)
+    );
+}
+
+#[test]
+fn mints_without_a_hook_are_rejected() {
+    let len = ExtensionType::try_calculate_account_len::<Mint>(&[]).unwrap();
+    let mut data = vec![0u8; len];
+    let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+    state.base.is_initialized = true;
+    state.pack_base();
+
+    assert!(transfer_hook_accrual(&data, 0, 10_000).is_err());
+}
+
+#[test]
+fn malformed_transfer_fees_are_rejected_not_waived() {
+    // The fee config is the last extension, so cutting the account short leaves
+    // it running past the end of the data
+    let mut data = mint(Some(100));
+    data.truncate(data.len() - 8);
+
+    assert!(transfer_hook_accrual(&data, 0, 10_000).is_err());
+}
@@ -0,0 +1,187 @@
+//! `migrate_treasury` upgrades a Treasury written under any layout pinned since
+//! `tests/account_layout.rs` was introduced. Each old account is rebuilt from a
+//! populated current one by serializing only the fields its layout had, then
+//! upgraded; every old field must keep its value and every appended field must
+//! come out as `initialize` would set it.
+
+use anchor_lang::{
+    prelude::Pubkey, AccountDeserialize, AccountSerialize, AnchorDeserialize, Space,
+};
+use usdcball::{
+    RoundingPolicy, Treasury, DEFAULT_RESERVE_WITHDRAWAL_DELAY_SECONDS,
+    TREASURY_EXPECTED_FREEZE_AUTHORITY_OFFSET, TREASURY_EXPECTED_MINT_AUTHORITY_OFFSET,
+    TREASURY_MIN_MIGRATABLE_SIZE, TREASURY_OPERATOR_OFFSET, TREASURY_PAUSER_OFFSET,
+    TREASURY_RESERVE_WITHDRAWAL_DELAY_OFFSET,
+};
+
+/// Every Treasury size pinned before the current one, oldest first. Add the
+/// current size here whenever a change grows the Treasury.
+const PINNED_SIZES: [usize; 34] = [
+    691, 779, 819, 821, 895, 901, 903, 911, 975, 1008, 1016, 1048, 1056, 1058, 1059, 1283, 1307,
+    1363, 1371, 1387, 1435, 1508, 1524, 1532, 1580, 1612, 1620, 1708, 1724, 1812, 1828, 1836, 1844,
+    1884,
+];
+
+const NEW_LEN: usize = 8 + Treasury::INIT_SPACE;
+
+fn key(byte: u8) -> Pubkey {
+    Pubkey::new_from_array([byte; 32])
+}
+
+/// A current Treasury with fields set across the whole layout
+fn populated(options: bool) -> Treasury {
+    let mut treasury =
+        Treasury::deserialize(&mut &vec![0u8; Treasury::INIT_SPACE][..]).expect("zeroed account");
+    treasury.authority = key(1);
+    treasury.token_mint = key(2);
+    treasury.usdc_mint = key(3);
+    treasury.buyback_allocation_bps = 5_000;
+    treasury.max_usdc_per_cycle = 1_000_000_000;
+    treasury.total_sol_collected = 42_000_000_000;
+    treasury.total_tokens_burned = 7_777;
+    treasury.pause_flags = 0b0101;
+    treasury.instruction_enabled = u64::MAX - 4;
+    treasury.current_epoch = 12;
+    treasury.guardian = key(4);
+    if options {
+        treasury.expected_mint_authority = Some(key(5));
+        treasury.expected_freeze_authority = Some(key(6));
+    }
+    treasury.operator = key(7);
+    treasury.pauser = key(8);
+    treasury.rounding_policy = RoundingPolicy::LargestRemainder;
+    treasury.reserve_withdrawal_delay_seconds = 3_600;
+    treasury.launch_mode_until_epoch = 3;
+    treasury.clmm_liquidity = 1 << 100;
+    treasury.dlmm_usdc_deposited = 250_000_000;
+    treasury.bump = 254;
+    treasury
+}
+
+fn serialize(treasury: &Treasury) -> Vec<u8> {
+    let mut data = Vec::new();
+    treasury.try_serialize(&mut data).unwrap();
+    data
+}
+
+/// Serialized length (after the discriminator) of the fields a layout of `size`
+/// bytes has, before `bump`
+fn old_fields_len(treasury: &Treasury, size: usize) -> usize {
+    let old_bump = size - 9;
+    let options = [
+        (
+            TREASURY_EXPECTED_MINT_AUTHORITY_OFFSET,
+            treasury.expected_mint_authority,
+        ),
+        (
+            TREASURY_EXPECTED_FREEZE_AUTHORITY_OFFSET,
+            treasury.expected_freeze_authority,
+        ),
+    ];
+    let unset = options
+        .iter()
+        .filter(|(offset, value)| *offset < old_bump && value.is_none())
+        .count();
+    old_bump - 32 * unset
+}
+
+/// `treasury` as an account written under the layout of `size` bytes, grown to
+/// the current size the way `migrate_treasury` reallocs it
+fn old_account(treasury: &Treasury, size: usize) -> Vec<u8> {
+    let fields_len = old_fields_len(treasury, size);
+    let mut data = serialize(treasury)[..8 + fields_len].to_vec();
+    data.push(treasury.bump);
+    data.resize(NEW_LEN, 0);
+    data
+}
+
+#[test]
+fn every_pinned_layout_upgrades() {
+    for options in [false, true] {
+        let current = populated(options);
+        let current_data = serialize(&current);
+        for size in PINNED_SIZES {
+            let mut data = old_account(&current, size);
+            Treasury::upgrade_layout(&mut data, size).unwrap();
+            let upgraded = Treasury::try_deserialize(&mut &data[..]).unwrap();
+
+            // Old fields are preserved byte for byte
+            let fields_len = old_fields_len(&current, size);
+            let upgraded_data = serialize(&upgraded);
+            assert_eq!(
+                upgraded_data[..8 + fields_len],
+                current_data[..8 + fields_len],
+                "fields of the {size} byte layout changed"
+            );
+            assert_eq!(upgraded.bump, current.bump);
+
+            // Appended fields take their defaults
+            let old_bump = size - 9;
+            let appended = |offset: usize| offset >= old_bump;
+            let mut defaults_cleared = upgraded.clone();
+            if appended(TREASURY_OPERATOR_OFFSET) {
+                assert_eq!(upgraded.operator, current.authority);
+                defaults_cleared.operator = Pubkey::default();
+            }
+            if appended(TREASURY_PAUSER_OFFSET) {
+                assert_eq!(upgraded.pauser, current.authority);
+                defaults_cleared.pauser = Pubkey::default();
+            }
+            if appended(TREASURY_RESERVE_WITHDRAWAL_DELAY_OFFSET) {
+                assert_eq!(
+                    upgraded.reserve_withdrawal_delay_seconds,
+                    DEFAULT_RESERVE_WITHDRAWAL_DELAY_SECONDS
+                );
+                defaults_cleared.reserve_withdrawal_delay_seconds = 0;
+            }
+            let cleared_data = serialize(&defaults_cleared);
+            assert!(
+                cleared_data[8 + fields_len..cleared_data.len() - 1]
+                    .iter()
+                    .all(|b| *b == 0),
+                "fields appended to the {size} byte layout are not zeroed"
+            );
+            assert_eq!(data.len(), NEW_LEN);
+        }
+    }
+}
+
+#[test]
+fn current_and_unknown_layouts_are_rejected() {
+    let current = populated(true);
+    let mut data = serialize(&current);
+    data.resize(NEW_LEN, 0);
+    assert!(Treasury::upgrade_layout(&mut data.clone(), NEW_LEN).is_err());
+    assert!(Treasury::upgrade_layout(&mut data.clone(), TREASURY_MIN_MIGRATABLE_SIZE - 1).is_err());
+
+    let mut foreign = old_account(&current, PINNED_SIZES[0]);
+    foreign[..8].copy_from_slice(&[0; 8]);
+    assert!(Treasury::upgrade_layout(&mut foreign, PINNED_SIZES[0]).is_err());
+}
+
+#[test]
+fn full_size_offsets_are_stable() {
+    // Offsets with every option set, where serialized and full-size offsets agree
+    let base = populated(true);
+    let offset_of = |edit: fn(&mut Treasury)| {
+        let mut edited = base.clone();
+        edit(&mut edited);
+        let (a, b) = (serialize(&base), serialize(&edited));
+        a.iter().zip(&b).position(|(x, y)| x != y).unwrap() - 8
+    };
+    assert_eq!(TREASURY_MIN_MIGRATABLE_SIZE, PINNED_SIZES[0]);
+    assert_eq!(
+        offset_of(|t| t.expected_mint_authority = None),
+        TREASURY_EXPECTED_MINT_AUTHORITY_OFFSET
+    );
+    assert_eq!(
+        offset_of(|t| t.expected_freeze_authority = None),
+        TREASURY_EXPECTED_FREEZE_AUTHORITY_OFFSET
+    );
+    assert_eq!(offset_of(|t| t.operator = key(9)), TREASURY_OPERATOR_OFFSET);
+    assert_eq!(offset_of(|t| t.pauser = key(9)), TREASURY_PAUSER_OFFSET);
+    assert_eq!(
+        offset_of(|t| t.reserve_withdrawal_delay_seconds = 0x0101),
+        TREASURY_RESERVE_WITHDRAWAL_DELAY_OFFSET
+    );
+}
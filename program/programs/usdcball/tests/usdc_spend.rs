@@ -0,0 +1,152 @@
+//! Outbound USDC is checked against the budget it draws on, counted against the
+//! outflow and volume limits, and added to the matching lifetime counter.
+
+use anchor_lang::{AnchorDeserialize, Space};
+use usdcball::{OperationKind, Treasury, UsdcSpend};
+
+const NOW: i64 = 10 * 86_400;
+
+fn treasury() -> Treasury {
+    let mut treasury =
+        Treasury::deserialize(&mut &vec![0u8; Treasury::INIT_SPACE][..]).expect("zeroed account");
+    treasury.buyback_allocation_bps = 5_000;
+    treasury.liquidity_allocation_bps = 3_000;
+    treasury.reserve_allocation_bps = 2_000;
+    treasury.total_usdc_converted = 1_000_000;
+    treasury
+}
+
+#[test]
+fn spends_count_against_their_own_budget() {
+    let mut treasury = treasury();
+
+    treasury
+        .authorize_usdc_spend(UsdcSpend::Buyback, 500_000, NOW)
+        .unwrap();
+    treasury
+        .settle_usdc_spend(UsdcSpend::Buyback, 500_000, 500_000, NOW)
+        .unwrap();
+    assert_eq!(treasury.total_buybacks_usdc, 500_000);
+    assert_eq!(treasury.remaining_buyback_budget().unwrap(), 0);
+    assert!(treasury
+        .authorize_usdc_spend(UsdcSpend::Buyback, 1, NOW)
+        .is_err());
+
+    // Bounty funding and reserve payouts share the reserve budget
+    treasury
+        .authorize_usdc_spend(UsdcSpend::Bounty, 150_000, NOW)
+        .unwrap();
+    treasury
+        .settle_usdc_spend(UsdcSpend::Bounty, 150_000, 150_000, NOW)
+        .unwrap();
+    assert_eq!(treasury.total_bounty_funded_usdc, 150_000);
+    assert!(treasury
+        .authorize_usdc_spend(UsdcSpend::Reserve, 50_001, NOW)
+        .is_err());
+    treasury
+        .authorize_usdc_spend(UsdcSpend::Reserve, 50_000, NOW)
+        .unwrap();
+}
+
+#[test]
+fn dividends_stay_within_the_reserve_budget() {
+    let mut treasury = treasury();
+    treasury.dividend_share_bps = 5_000;
+    assert_eq!(treasury.remaining_dividend_budget().unwrap(), 100_000);
+
+    // Other reserve commitments leave less for dividends than their own share
+    treasury
+        .settle_usdc_spend(UsdcSpend::Bounty, 150_000, 150_000, NOW)
+        .unwrap();
+    assert!(treasury
+        .authorize_usdc_spend(UsdcSpend::Dividends, 50_001, NOW)
+        .is_err());
+    treasury
+        .authorize_usdc_spend(UsdcSpend::Dividends, 50_000, NOW)
+        .unwrap();
+
+    // A new budget epoch measures the dividend share from its own conversions
+    treasury
+        .settle_usdc_spend(UsdcSpend::Dividends, 50_000, 50_000, NOW)
+        .unwrap();
+    treasury.start_budget_epoch(NOW);
+    assert_eq!(treasury.remaining_dividend_budget().unwrap(), 0);
+    treasury.total_usdc_converted += 1_000_000;
+    assert_eq!(treasury.remaining_dividend_budget().unwrap(), 100_000);
+}
+
+#[test]
+fn spends_count_against_the_outflow_cap() {
+    let mut treasury = treasury();
+    treasury.weekly_outflow_cap_usdc = 300_000;
+
+    treasury
+        .authorize_usdc_spend(UsdcSpend::Liquidity, 200_000, NOW)
+        .unwrap();
+    assert!(treasury
+        .authorize_usdc_spend(UsdcSpend::Reserve, 100_001, NOW)
+        .is_err());
+}
+
+#[test]
+fn only_market_operations_count_as_volume() {
+    let mut treasury = treasury();
+    treasury.daily_volume_cap_usdc = 100_000;
+
+    // A reserve payout is not trading volume
+    treasury
+        .authorize_usdc_spend(UsdcSpend::Reserve, 150_000, NOW)
+        .unwrap();
+    treasury
+        .authorize_usdc_spend(UsdcSpend::Buyback, 100_000, NOW)
+        .unwrap();
+    assert!(treasury
+        .authorize_usdc_spend(UsdcSpend::Liquidity, 1, NOW)
+        .is_err());
+}
+
+#[test]
+fn unspent_authorization_is_given_back_on_settle() {
+    let mut treasury = treasury();
+    treasury.weekly_outflow_cap_usdc = 300_000;
+    treasury.daily_volume_cap_usdc = 300_000;
+
+    // The venue only pulled 100_000 of the 300_000 it was allowed
+    treasury
+        .authorize_usdc_spend(UsdcSpend::Buyback, 300_000, NOW)
+        .unwrap();
+    treasury
+        .settle_usdc_spend(UsdcSpend::Buyback, 300_000, 100_000, NOW)
+        .unwrap();
+
+    assert_eq!(treasury.total_buybacks_usdc, 100_000);
+    treasury
+        .authorize_usdc_spend(UsdcSpend::Liquidity, 200_000, NOW)
+        .unwrap();
+    assert!(treasury
+        .authorize_usdc_spend(UsdcSpend::Liquidity, 1, NOW)
+        .is_err());
+}
+
+#[test]
+fn refunded_operation_is_given_back_to_the_caps() {
+    let mut treasury = treasury();
+    treasury.weekly_outflow_cap_usdc = 300_000;
+    treasury.daily_volume_cap_usdc = 300_000;
+    treasury
+        .authorize_usdc_spend(UsdcSpend::Liquidity, 300_000, NOW)
+        .unwrap();
+    treasury
+        .settle_usdc_spend(UsdcSpend::Liquidity, 300_000, 300_000, NOW)
+        .unwrap();
+
+    // Refunded two hours later, the escrow comes off the hour it was charged to
+    treasury
+        .release_operation(OperationKind::Liquidity, 300_000, NOW)
+        .unwrap();
+
+    assert_eq!(treasury.total_liquidity_usdc, 0);
+    treasury
+        .authorize_usdc_spend(UsdcSpend::Liquidity, 300_000, NOW + 7_200)
+        .unwrap();
+}
@@ -0,0 +1,48 @@
+//! The treasury PDA signs whatever `invoke_swap` passes to a venue, so each venue
+//! only accepts its own swap instructions, never position or withdrawal ones.
+
+use usdcball::Venue;
+
+/// Anchor discriminator of `global:<name>`
+fn discriminator(name: &str) -> Vec<u8> {
+    anchor_lang::solana_program::hash::hash(format!("global:{name}").as_bytes()).to_bytes()[..8]
+        .to_vec()
+}
+
+#[test]
+fn venues_accept_only_their_swaps() {
+    let accepts = |venue: Venue, data: &[u8]| venue.handler().check_swap_data(data).is_ok();
+
+    assert!(accepts(Venue::Jupiter, &discriminator("route")));
+    assert!(accepts(Venue::Orca, &discriminator("swap_v2")));
+    assert!(accepts(Venue::Meteora, &discriminator("swap2")));
+    assert!(accepts(Venue::Raydium, &[9]));
+    assert!(accepts(Venue::Phoenix, &[0]));
+
+    // Instructions on positions the treasury owns
+    assert!(!accepts(
+        Venue::Orca,
+        &discriminator("decrease_liquidity_v2")
+    ));
+    assert!(!accepts(Venue::Orca, &discriminator("close_position")));
+    assert!(!accepts(Venue::Meteora, &discriminator("remove_liquidity")));
+    assert!(!accepts(Venue::Raydium, &[4]));
+    assert!(!accepts(Venue::Phoenix, &[2]));
+
+    for venue in [Venue::Jupiter, Venue::Orca, Venue::Meteora] {
+        assert!(!accepts(venue, &[]));
+        assert!(!accepts(venue, &discriminator("swap")[..7]));
+    }
+}
+
+#[test]
+fn only_venue_deposits_pass_as_deposits() {
+    let deposits = |venue: Venue, data: &[u8]| venue.handler().is_deposit(data);
+
+    assert!(deposits(Venue::Raydium, &[3]));
+    // A swap or withdrawal would leave the treasury without LP tokens for its USDC
+    assert!(!deposits(Venue::Raydium, &[9]));
+    assert!(!deposits(Venue::Raydium, &[4]));
+    assert!(!deposits(Venue::Raydium, &[]));
+    assert!(!deposits(Venue::Orca, &discriminator("increase_liquidity")));
+}
@@ -0,0 +1,46 @@
+//! A treasury Whirlpool position's liquidity account tracks what went into and
+//! came out of the position, and never lets more liquidity out than went in.
+
+use anchor_lang::{prelude::Pubkey, AnchorDeserialize, Space};
+use usdcball::{orca_whirlpool::order_pair, LiquidityPosition};
+
+fn position() -> LiquidityPosition {
+    LiquidityPosition::deserialize(&mut &vec![0u8; LiquidityPosition::INIT_SPACE][..])
+        .expect("zeroed account")
+}
+
+#[test]
+fn lower_mint_is_token_a() {
+    let low = Pubkey::new_from_array([1; 32]);
+    let high = Pubkey::new_from_array([2; 32]);
+
+    assert_eq!(order_pair(&low, &high, "usdc", "token"), ("usdc", "token"));
+    assert_eq!(order_pair(&high, &low, "usdc", "token"), ("token", "usdc"));
+}
+
+#[test]
+fn liquidity_changes_are_tracked() {
+    let mut position = position();
+    position.add_liquidity(1_000, 500_000, 2_000_000).unwrap();
+    position.add_liquidity(500, 250_000, 1_000_000).unwrap();
+    assert_eq!(position.liquidity, 1_500);
+    assert_eq!(position.usdc_deposited, 750_000);
+    assert_eq!(position.token_deposited, 3_000_000);
+
+    position.remove_liquidity(600, 310_000, 1_150_000).unwrap();
+    assert_eq!(position.liquidity, 900);
+    assert_eq!(position.usdc_withdrawn, 310_000);
+    assert_eq!(position.token_withdrawn, 1_150_000);
+
+    // More than the position holds
+    assert!(position.remove_liquidity(901, 0, 0).is_err());
+}
+
+#[test]
+fn fees_accumulate() {
+    let mut position = position();
+    position.record_fees(1_200, 4_000).unwrap();
+    position.record_fees(800, 0).unwrap();
+    assert_eq!(position.usdc_fees_collected, 2_000);
+    assert_eq!(position.token_fees_collected, 4_000);
+}
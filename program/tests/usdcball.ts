@@ -1,6 +1,15 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { Usdcball } from "../target/types/usdcball";
+import {
+  NATIVE_MINT,
+  TOKEN_2022_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  createAccount,
+  createMint,
+  getAccount,
+  mintTo,
+} from "@solana/spl-token";
 import { expect } from "chai";
 
 describe("usdcball", () => {
@@ -11,12 +20,35 @@ describe("usdcball", () => {
 
   let treasuryPda: anchor.web3.PublicKey;
   let treasuryBump: number;
+  let tokenMint: anchor.web3.PublicKey;
+  let usdcMint: anchor.web3.PublicKey;
+  let feeCounterPda: anchor.web3.PublicKey;
 
   before(async () => {
     [treasuryPda, treasuryBump] = anchor.web3.PublicKey.findProgramAddressSync(
       [Buffer.from("treasury")],
       program.programId
     );
+    [feeCounterPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("fee_counter")],
+      program.programId
+    );
+
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    tokenMint = await createMint(
+      provider.connection,
+      payer,
+      payer.publicKey,
+      null,
+      6
+    );
+    usdcMint = await createMint(
+      provider.connection,
+      payer,
+      payer.publicKey,
+      null,
+      6
+    );
   });
 
   it("Initializes the treasury", async () => {
@@ -27,10 +59,15 @@ describe("usdcball", () => {
         2000, // 20% reserve
         new anchor.BN(10_000_000_000), // 10,000 USDC max per cycle (6 decimals)
         new anchor.BN(3600), // 1 hour cooldown
-        200 // 2% slippage
+        200, // 2% slippage
+        6, // token decimals
+        provider.wallet.publicKey, // token mint authority
+        new anchor.BN(0) // no launch mode
       )
       .accounts({
         treasury: treasuryPda,
+        tokenMint,
+        usdcMint,
         authority: provider.wallet.publicKey,
         systemProgram: anchor.web3.SystemProgram.programId,
       })
@@ -41,20 +78,51 @@ describe("usdcball", () => {
     expect(treasury.authority.toString()).to.equal(
       provider.wallet.publicKey.toString()
     );
+    expect(treasury.tokenMint.toString()).to.equal(tokenMint.toString());
+    expect(treasury.usdcMint.toString()).to.equal(usdcMint.toString());
     expect(treasury.buybackAllocationBps).to.equal(5000);
     expect(treasury.liquidityAllocationBps).to.equal(3000);
     expect(treasury.reserveAllocationBps).to.equal(2000);
     expect(treasury.slippageBps).to.equal(200);
-    expect(treasury.paused).to.equal(false);
+    expect(treasury.pauseFlags).to.equal(0);
   });
 
   it("Records SOL fees", async () => {
     const feeAmount = new anchor.BN(1_000_000_000); // 1 SOL
+    const [feeVault] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("fee_vault")],
+      program.programId
+    );
+
+    await program.methods
+      .initializeFeeVault()
+      .accounts({
+        treasury: treasuryPda,
+        feeVault,
+        authority: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .initializeFeeCounter()
+      .accounts({
+        treasury: treasuryPda,
+        feeCounter: feeCounterPda,
+        authority: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
 
+    const vaultBefore = await provider.connection.getBalance(feeVault);
     await program.methods
-      .recordFee(feeAmount)
+      .depositFee(feeAmount)
       .accounts({
         treasury: treasuryPda,
+        feeVault,
+        feeCounter: feeCounterPda,
+        solUsdOracle: null,
+        depositor: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
       })
       .rpc();
 
@@ -62,6 +130,154 @@ describe("usdcball", () => {
     expect(treasury.totalSolCollected.toString()).to.equal(
       feeAmount.toString()
     );
+    expect(
+      (await provider.connection.getBalance(feeVault)) - vaultBefore
+    ).to.equal(feeAmount.toNumber());
+    // No oracle configured yet, so nothing is valued in USD
+    expect(treasury.totalSolCollectedUsd.toNumber()).to.equal(0);
+  });
+
+  it("Only records SOL fees the vault actually received", async () => {
+    const [feeVault] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("fee_vault")],
+      program.programId
+    );
+
+    try {
+      await program.methods
+        .recordFee(new anchor.BN(1))
+        .accounts({
+          treasury: treasuryPda,
+          feeCounter: feeCounterPda,
+          feeVault,
+          solUsdOracle: null,
+        })
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("FeeNotReceived");
+    }
+
+    const amount = 5_000_000;
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: provider.wallet.publicKey,
+          toPubkey: feeVault,
+          lamports: amount,
+        })
+      )
+    );
+    await program.methods
+      .recordFee(new anchor.BN(amount))
+      .accounts({
+        treasury: treasuryPda,
+        feeCounter: feeCounterPda,
+        feeVault,
+        solUsdOracle: null,
+      })
+      .rpc();
+
+    // record_fee only writes the fee counter until it is synced
+    let treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.totalSolCollected.toNumber()).to.equal(1_000_000_000);
+    let counter = await program.account.feeCounter.fetch(feeCounterPda);
+    expect(counter.unsyncedLamports.toNumber()).to.equal(amount);
+    expect(counter.feeCount.toNumber()).to.equal(1);
+
+    await program.methods
+      .syncFeeCounter()
+      .accounts({ treasury: treasuryPda, feeCounter: feeCounterPda })
+      .rpc();
+
+    treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.totalSolCollected.toNumber()).to.equal(
+      1_000_000_000 + amount
+    );
+    counter = await program.account.feeCounter.fetch(feeCounterPda);
+    expect(counter.unsyncedLamports.toNumber()).to.equal(0);
+  });
+
+  it("Records a batch of fees with one transfer", async () => {
+    const [feeVault] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("fee_vault")],
+      program.programId
+    );
+    const before = await program.account.treasury.fetch(treasuryPda);
+    const vaultBefore = await provider.connection.getBalance(feeVault);
+
+    await program.methods
+      .recordFeesBatch([
+        { sourceId: 1, amount: new anchor.BN(10_000) },
+        { sourceId: 2, amount: new anchor.BN(25_000) },
+        { sourceId: 1, amount: new anchor.BN(5_000) },
+      ])
+      .accounts({
+        treasury: treasuryPda,
+        feeVault,
+        feeCounter: feeCounterPda,
+        solUsdOracle: null,
+        depositor: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const after = await program.account.treasury.fetch(treasuryPda);
+    expect(
+      after.totalSolCollected.sub(before.totalSolCollected).toNumber()
+    ).to.equal(40_000);
+    expect(
+      (await provider.connection.getBalance(feeVault)) - vaultBefore
+    ).to.equal(40_000);
+
+    try {
+      await program.methods
+        .recordFeesBatch([{ sourceId: 1, amount: new anchor.BN(0) }])
+        .accounts({
+          treasury: treasuryPda,
+          feeVault,
+          feeCounter: feeCounterPda,
+          solUsdOracle: null,
+          depositor: provider.wallet.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("InvalidFeeBatch");
+    }
+  });
+
+  it("Only forwards fees signed by the source program's PDA", async () => {
+    const [feeVault] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("fee_vault")],
+      program.programId
+    );
+    // A plain keypair cannot stand in for a program's fee source PDA
+    const impostor = anchor.web3.Keypair.generate();
+
+    try {
+      await program.methods
+        .forwardFee(new anchor.BN(10_000), new anchor.BN(0), 255)
+        .accounts({
+          treasury: treasuryPda,
+          feeVault,
+          feeCounter: feeCounterPda,
+          solUsdOracle: null,
+          sourceProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          sourceAuthority: impostor.publicKey,
+          payer: provider.wallet.publicKey,
+          sourceUsdc: null,
+          treasuryUsdc: null,
+          tokenProgram: null,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([impostor])
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("InvalidFeeSource");
+    }
   });
 
   it("Records USDC conversion", async () => {
@@ -71,7 +287,9 @@ describe("usdcball", () => {
       .recordUsdcConversion(usdcAmount)
       .accounts({
         treasury: treasuryPda,
+        usdcUsdOracle: null,
         authority: provider.wallet.publicKey,
+        authorityActivity: null,
       })
       .rpc();
 
@@ -86,12 +304,13 @@ describe("usdcball", () => {
       .emergencyPause()
       .accounts({
         treasury: treasuryPda,
-        authority: provider.wallet.publicKey,
+        pauser: provider.wallet.publicKey,
+        pauserActivity: null,
       })
       .rpc();
 
     const treasury = await program.account.treasury.fetch(treasuryPda);
-    expect(treasury.paused).to.equal(true);
+    expect(treasury.pauseFlags).to.equal(0b1111);
   });
 
   it("Resume works", async () => {
@@ -100,21 +319,27 @@ describe("usdcball", () => {
       .accounts({
         treasury: treasuryPda,
         authority: provider.wallet.publicKey,
+        authorityActivity: null,
       })
       .rpc();
 
     const treasury = await program.account.treasury.fetch(treasuryPda);
-    expect(treasury.paused).to.equal(false);
+    expect(treasury.pauseFlags).to.equal(0);
+    // First operation after resume must wait a fresh cooldown
+    expect(treasury.warmupUntil.toNumber()).to.be.greaterThan(
+      Math.floor(Date.now() / 1000)
+    );
   });
 
   it("Config update works", async () => {
     const newMaxUsdc = new anchor.BN(20_000_000_000); // 20,000 USDC
 
     await program.methods
-      .updateConfig(newMaxUsdc, null, null)
+      .updateConfig(newMaxUsdc, null, null, null, null)
       .accounts({
         treasury: treasuryPda,
         authority: provider.wallet.publicKey,
+        authorityActivity: null,
       })
       .rpc();
 
@@ -124,6 +349,2244 @@ describe("usdcball", () => {
     );
   });
 
+  it("Disabled instruction is rejected", async () => {
+    await program.methods
+      .setInstructionEnabled({ recordFee: {} }, false)
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+
+    try {
+      await program.methods
+        .recordFee(new anchor.BN(1))
+        .accounts({
+          treasury: treasuryPda,
+          feeCounter: feeCounterPda,
+          feeVault: anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("fee_vault")],
+            program.programId
+          )[0],
+          solUsdOracle: null,
+        })
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("InstructionDisabled");
+    }
+
+    await program.methods
+      .setInstructionEnabled({ recordFee: {} }, true)
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+  });
+
+  it("Sets the dividend share", async () => {
+    await program.methods
+      .setDividendShare(2500)
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.dividendShareBps).to.equal(2500);
+  });
+
+  it("Sets the co-sign policy", async () => {
+    const coSigner = anchor.web3.Keypair.generate().publicKey;
+    const threshold = new anchor.BN(5_000_000_000); // 5,000 USDC
+
+    await program.methods
+      .setCosignPolicy(coSigner, threshold)
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.coSigner.toString()).to.equal(coSigner.toString());
+    expect(treasury.cosignThresholdUsdc.toString()).to.equal(
+      threshold.toString()
+    );
+  });
+
+  it("Sets the bounty policy", async () => {
+    const maxPayout = new anchor.BN(25_000_000_000); // 25,000 USDC
+
+    await program.methods
+      .setBountyPolicy(maxPayout, new anchor.BN(7 * 24 * 3600))
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.bountyMaxPayoutUsdc.toString()).to.equal(
+      maxPayout.toString()
+    );
+    expect(treasury.bountyTimelockSeconds.toNumber()).to.equal(7 * 24 * 3600);
+  });
+
+  it("Sets the settlement window", async () => {
+    await program.methods
+      .setSettlementWindow(new anchor.BN(300))
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.settlementWindowSlots.toNumber()).to.equal(300);
+  });
+
+  it("Keeps buyback and liquidity cooldowns apart", async () => {
+    await program.methods
+      .setLiquidityCooldown(new anchor.BN(600))
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.liquidityCooldownSeconds.toNumber()).to.equal(600);
+    expect(treasury.lastBuybackTimestamp.toNumber()).to.equal(0);
+    expect(treasury.lastLiquidityTimestamp.toNumber()).to.equal(0);
+
+    try {
+      await program.methods
+        .setLiquidityCooldown(new anchor.BN(-1))
+        .accounts({
+          treasury: treasuryPda,
+          authority: provider.wallet.publicKey,
+          authorityActivity: null,
+        })
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("InvalidDelay");
+    }
+  });
+
+  it("Registers an operator and exposes its stats", async () => {
+    const operator = anchor.web3.Keypair.generate().publicKey;
+    const [operatorPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("operator"), operator.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .registerOperator(operator)
+      .accounts({
+        treasury: treasuryPda,
+        operatorStats: operatorPda,
+        authority: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    await program.methods
+      .setOperatorActive(false)
+      .accounts({
+        treasury: treasuryPda,
+        operatorStats: operatorPda,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    const stats = await program.methods
+      .getOperatorStats()
+      .accounts({ operatorStats: operatorPda })
+      .view();
+    expect(stats.operator.toString()).to.equal(operator.toString());
+    expect(stats.active).to.be.false;
+    expect(stats.successfulOperations.toNumber()).to.equal(0);
+    expect(stats.successRateBps).to.equal(10000);
+  });
+
+  it("Refuses to convert below the SOL threshold", async () => {
+    await program.methods
+      .setMinSolToConvert(new anchor.BN(1_000_000_000_000))
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+
+    try {
+      await program.methods
+        .recordUsdcConversion(new anchor.BN(1_000_000))
+        .accounts({
+          treasury: treasuryPda,
+          usdcUsdOracle: null,
+          authority: provider.wallet.publicKey,
+          authorityActivity: null,
+        })
+        .rpc();
+      expect.fail("Should have failed below the conversion threshold");
+    } catch (err) {
+      expect(err.toString()).to.include("BelowConversionThreshold");
+    }
+
+    await program.methods
+      .setMinSolToConvert(new anchor.BN(0))
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+  });
+
+  it("Creates a treasury-owned route escrow per venue", async () => {
+    const [routeEscrow] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("route_escrow"), Buffer.from([0]), tokenMint.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .initializeRouteEscrow({ jupiter: {} })
+      .accounts({
+        treasury: treasuryPda,
+        routeEscrow,
+        mint: tokenMint,
+        authority: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const escrow = await getAccount(provider.connection, routeEscrow);
+    expect(escrow.owner.toString()).to.equal(treasuryPda.toString());
+    expect(escrow.mint.toString()).to.equal(tokenMint.toString());
+  });
+
+  it("Rejects a token program that does not own the mint", async () => {
+    const [routeEscrow] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("route_escrow"), Buffer.from([1]), tokenMint.toBuffer()],
+      program.programId
+    );
+
+    try {
+      await program.methods
+        .initializeRouteEscrow({ raydium: {} })
+        .accounts({
+          treasury: treasuryPda,
+          routeEscrow,
+          mint: tokenMint,
+          authority: provider.wallet.publicKey,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("ConstraintMintTokenProgram");
+    }
+  });
+
+  it("Only credits external burns the supply snapshot can verify", async () => {
+    const accounts = {
+      treasury: treasuryPda,
+      tokenMint,
+      authority: provider.wallet.publicKey,
+      authorityActivity: null,
+    };
+    await program.methods.snapshotTokenSupply().accounts(accounts).rpc();
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.supplySnapshotSlot.toNumber()).to.be.greaterThan(0);
+
+    // Nothing was burned since the snapshot
+    try {
+      await program.methods
+        .registerExternalBurn(new anchor.BN(1), Array(64).fill(0))
+        .accounts(accounts)
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("UnverifiableBurn");
+    }
+  });
+
+  it("Sets the per-epoch burn target", async () => {
+    const target = new anchor.BN(1_000_000_000);
+
+    await program.methods
+      .setBurnTarget(target, new anchor.BN(24 * 3600))
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.burnTargetPerEpoch.toString()).to.equal(target.toString());
+    expect(treasury.epochDurationSeconds.toNumber()).to.equal(24 * 3600);
+  });
+
+  it("Sets the reserve drawdown policy", async () => {
+    const guardian = anchor.web3.Keypair.generate().publicKey;
+    const governance = anchor.web3.Keypair.generate().publicKey;
+    const opsLimit = new anchor.BN(5_000_000_000); // 5,000 USDC / month
+
+    await program.methods
+      .setReservePolicy(guardian, governance, opsLimit)
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.guardian.toString()).to.equal(guardian.toString());
+    expect(treasury.governance.toString()).to.equal(governance.toString());
+    expect(treasury.reserveOpsMonthlyLimitUsdc.toString()).to.equal(
+      opsLimit.toString()
+    );
+    expect(treasury.reserveOpsStreamStart.toNumber()).to.be.greaterThan(0);
+  });
+
+  it("Queues reserve withdrawals to active address book entries only", async () => {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    const admin = {
+      treasury: treasuryPda,
+      authority: provider.wallet.publicKey,
+    };
+    const destination = await createAccount(
+      provider.connection,
+      payer,
+      usdcMint,
+      payer.publicKey,
+      anchor.web3.Keypair.generate()
+    );
+    const [entry] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("address_book"), destination.toBuffer()],
+      program.programId
+    );
+    const [withdrawal] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("reserve_withdrawal"), new anchor.BN(0).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const queue = () =>
+      program.methods
+        .queueReserveWithdrawal(new anchor.BN(1_000_000))
+        .accounts({
+          ...admin,
+          entry,
+          withdrawal,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+    try {
+      await queue();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("AccountNotInitialized");
+    }
+
+    const add = (label: string) =>
+      program.methods
+        .addAddressBookEntry(label)
+        .accounts({
+          ...admin,
+          destinationUsdc: destination,
+          entry,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    try {
+      await add("x".repeat(33));
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("InvalidAddressLabel");
+    }
+    await add("Market maker ops");
+    const book = await program.account.addressBookEntry.fetch(entry);
+    expect(book.label).to.equal("Market maker ops");
+    expect(book.activeAt.sub(book.addedAt).toNumber()).to.equal(48 * 3600);
+
+    // Payments wait out the address book timelock
+    try {
+      await queue();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("AddressBookEntryPending");
+    }
+
+    await program.methods
+      .removeAddressBookEntry()
+      .accounts({ ...admin, entry })
+      .rpc();
+    expect(await provider.connection.getAccountInfo(entry)).to.equal(null);
+  });
+
+  it("Only lends reserve USDC to the configured Kamino reserve", async () => {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    const kaminoReserve = anchor.web3.Keypair.generate().publicKey;
+    const vault = () =>
+      createAccount(
+        provider.connection,
+        payer,
+        usdcMint,
+        treasuryPda,
+        anchor.web3.Keypair.generate()
+      );
+    const deposit = async () =>
+      program.methods
+        .depositReserveToKamino(new anchor.BN(1_000_000))
+        .accounts({
+          treasury: treasuryPda,
+          authority: provider.wallet.publicKey,
+          kaminoReserve,
+          treasuryUsdc: await vault(),
+          treasuryCollateral: await vault(),
+          usdcMint,
+          lendingMarket: anchor.web3.Keypair.generate().publicKey,
+          lendingMarketAuthority: anchor.web3.Keypair.generate().publicKey,
+          reserveLiquiditySupply: anchor.web3.Keypair.generate().publicKey,
+          reserveCollateralMint: anchor.web3.Keypair.generate().publicKey,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          kaminoProgram: new anchor.web3.PublicKey(
+            "KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD"
+          ),
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    try {
+      await deposit();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("KaminoNotConfigured");
+    }
+
+    await program.methods
+      .setKaminoReserve(kaminoReserve)
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.kaminoReserve.toString()).to.equal(kaminoReserve.toString());
+
+    // The collateral account must hold the reserve's kTokens
+    try {
+      await deposit();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("InvalidCollateralAccount");
+    }
+
+    await program.methods
+      .setKaminoReserve(anchor.web3.PublicKey.default)
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+  });
+
+  it("Lends to one venue at a time", async () => {
+    const admin = {
+      treasury: treasuryPda,
+      authority: provider.wallet.publicKey,
+      authorityActivity: null,
+    };
+    await program.methods
+      .setLendingVenue({ marginFi: {} })
+      .accounts(admin)
+      .rpc();
+    let treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.lendingVenue).to.deep.equal({ marginFi: {} });
+
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    try {
+      await program.methods
+        .depositReserveToMarginfi(new anchor.BN(1_000_000))
+        .accounts({
+          treasury: treasuryPda,
+          authority: provider.wallet.publicKey,
+          marginfiBank: anchor.web3.Keypair.generate().publicKey,
+          marginfiAccount: anchor.web3.Keypair.generate().publicKey,
+          marginfiGroup: anchor.web3.Keypair.generate().publicKey,
+          treasuryUsdc: await createAccount(
+            provider.connection,
+            payer,
+            usdcMint,
+            treasuryPda,
+            anchor.web3.Keypair.generate()
+          ),
+          bankLiquidityVault: anchor.web3.Keypair.generate().publicKey,
+          bankLiquidityVaultAuthority: anchor.web3.Keypair.generate().publicKey,
+          marginfiProgram: new anchor.web3.PublicKey(
+            "MFv2hWf31Z9kbCa1snEPYctwafyhdvnV7FZnsebVacA"
+          ),
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("MarginFiNotConfigured");
+    }
+
+    await program.methods
+      .setLendingVenue({ none: {} })
+      .accounts(admin)
+      .rpc();
+    treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.lendingVenue).to.deep.equal({ none: {} });
+  });
+
+  it("Lends to Solend only while it is the active venue", async () => {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    const admin = {
+      treasury: treasuryPda,
+      authority: provider.wallet.publicKey,
+      authorityActivity: null,
+    };
+    const solendReserve = anchor.web3.Keypair.generate().publicKey;
+    const collateralMint = await createMint(
+      provider.connection,
+      payer,
+      payer.publicKey,
+      null,
+      6
+    );
+    const vault = (mint: anchor.web3.PublicKey) =>
+      createAccount(
+        provider.connection,
+        payer,
+        mint,
+        treasuryPda,
+        anchor.web3.Keypair.generate()
+      );
+    const deposit = async () =>
+      program.methods
+        .depositReserveToSolend(new anchor.BN(1_000_000))
+        .accounts({
+          treasury: treasuryPda,
+          authority: provider.wallet.publicKey,
+          solendReserve,
+          treasuryUsdc: await vault(usdcMint),
+          treasuryCollateral: await vault(collateralMint),
+          lendingMarket: anchor.web3.Keypair.generate().publicKey,
+          lendingMarketAuthority: anchor.web3.Keypair.generate().publicKey,
+          reserveLiquiditySupply: anchor.web3.Keypair.generate().publicKey,
+          reserveCollateralMint: collateralMint,
+          solendProgram: new anchor.web3.PublicKey(
+            "So1endDq2YkqhipRh3WViPa8hdiSpxWy6z3Z6tMCpAo"
+          ),
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    try {
+      await deposit();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("SolendNotConfigured");
+    }
+
+    await program.methods
+      .setSolendReserve(solendReserve)
+      .accounts(admin)
+      .rpc();
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.solendReserve.toString()).to.equal(solendReserve.toString());
+
+    // Configured, but Solend is not the active venue
+    try {
+      await deposit();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("LendingVenueInactive");
+    }
+
+    await program.methods
+      .setSolendReserve(anchor.web3.PublicKey.default)
+      .accounts(admin)
+      .rpc();
+  });
+
+  it("Harvests yield only from a configured venue", async () => {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    const vault = () =>
+      createAccount(
+        provider.connection,
+        payer,
+        usdcMint,
+        treasuryPda,
+        anchor.web3.Keypair.generate()
+      );
+    try {
+      await program.methods
+        .harvestMarginfiYield()
+        .accounts({
+          treasury: treasuryPda,
+          authority: provider.wallet.publicKey,
+          marginfiBank: anchor.web3.Keypair.generate().publicKey,
+          marginfiAccount: anchor.web3.Keypair.generate().publicKey,
+          marginfiGroup: anchor.web3.Keypair.generate().publicKey,
+          treasuryUsdc: await vault(),
+          bankLiquidityVault: anchor.web3.Keypair.generate().publicKey,
+          bankLiquidityVaultAuthority: anchor.web3.Keypair.generate().publicKey,
+          marginfiProgram: new anchor.web3.PublicKey(
+            "MFv2hWf31Z9kbCa1snEPYctwafyhdvnV7FZnsebVacA"
+          ),
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("MarginFiNotConfigured");
+    }
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.totalYieldHarvestedUsdc.toNumber()).to.equal(0);
+  });
+
+  it("Records milestones only once crossed", async () => {
+    await program.methods
+      .setMilestoneIntervals(new anchor.BN(1_000_000), new anchor.BN(0))
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+
+    const threshold = new anchor.BN(1_000_000_000_000);
+    const [milestone] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("milestone"),
+        Buffer.from([0]),
+        threshold.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    try {
+      await program.methods
+        .recordMilestone({ burns: {} }, threshold)
+        .accounts({
+          milestone,
+          treasury: treasuryPda,
+          payer: provider.wallet.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("MilestoneNotReached");
+    }
+  });
+
+  it("Opens the quote board", async () => {
+    const [quoteBoard] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("quote_board")],
+      program.programId
+    );
+    await program.methods
+      .openQuoteBoard()
+      .accounts({
+        quoteBoard,
+        treasury: treasuryPda,
+        tokenMint,
+        payer: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const board = await program.account.quoteBoard.fetch(quoteBoard);
+    expect(board.updates.toNumber()).to.equal(0);
+    expect(board.tokenDecimals).to.equal(6);
+  });
+
+  it("Attests treasury reserves for the epoch", async () => {
+    const [routeEscrow] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("route_escrow"), Buffer.from([0]), tokenMint.toBuffer()],
+      program.programId
+    );
+    const [attestationPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("attestation"), new anchor.BN(0).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+
+    await program.methods
+      .attestReserves([{ vault: {} }])
+      .accounts({
+        treasury: treasuryPda,
+        attestation: attestationPda,
+        authority: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .remainingAccounts([
+        { pubkey: routeEscrow, isSigner: false, isWritable: false },
+      ])
+      .rpc();
+
+    const attestation = await program.account.attestation.fetch(attestationPda);
+    expect(attestation.holdings.length).to.equal(1);
+    expect(attestation.holdings[0].account.toString()).to.equal(
+      routeEscrow.toString()
+    );
+    expect(attestation.holdings[0].amount.toNumber()).to.equal(0);
+  });
+
+  it("Reports treasury LP holdings for a lock attestation", async () => {
+    // Any treasury-owned token account stands in for an LP position here
+    const [routeEscrow] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("route_escrow"), Buffer.from([0]), tokenMint.toBuffer()],
+      program.programId
+    );
+
+    const report = await program.methods
+      .attestLpLocks()
+      .accounts({ treasury: treasuryPda })
+      .remainingAccounts([
+        { pubkey: routeEscrow, isSigner: false, isWritable: false },
+      ])
+      .view();
+    expect(report.holdings.length).to.equal(1);
+    expect(report.holdings[0].lpMint.toString()).to.equal(tokenMint.toString());
+    expect(report.holdings[0].lockedUntil.toNumber()).to.equal(0);
+    expect(report.totalLpLocked.toNumber()).to.equal(0);
+    expect(report.totalLpBurned.toNumber()).to.equal(0);
+  });
+
+  it("Rejects rolling an epoch that has not ended", async () => {
+    const [epochStatsPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("epoch_stats"), new anchor.BN(0).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+
+    try {
+      await program.methods
+        .advanceEpoch()
+        .accounts({
+          treasury: treasuryPda,
+          epochStats: epochStatsPda,
+          caller: provider.wallet.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("EpochNotEnded");
+    }
+  });
+
+  it("Rolls budget epochs only once enabled and ended", async () => {
+    const [epochPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("epoch"), new anchor.BN(0).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const rollEpoch = () =>
+      program.methods
+        .rollEpoch()
+        .accounts({
+          treasury: treasuryPda,
+          epoch: epochPda,
+          caller: provider.wallet.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    const setDuration = (seconds: number) =>
+      program.methods
+        .setBudgetEpochDuration(new anchor.BN(seconds))
+        .accounts({
+          treasury: treasuryPda,
+          authority: provider.wallet.publicKey,
+          authorityActivity: null,
+        })
+        .rpc();
+
+    try {
+      await rollEpoch();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("BudgetEpochsDisabled");
+    }
+
+    await setDuration(7 * 24 * 3600);
+    let treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.budgetEpochStart.toNumber()).to.be.greaterThan(0);
+    expect(treasury.epochBaseUsdcConverted.toString()).to.equal(
+      treasury.totalUsdcConverted.toString()
+    );
+
+    try {
+      await rollEpoch();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("EpochNotEnded");
+    }
+
+    // Back to lifetime budgets for the rest of the suite
+    await setDuration(0);
+    treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.epochBaseUsdcConverted.toNumber()).to.equal(0);
+  });
+
+  it("Places and cancels a conditional buyback", async () => {
+    await program.methods
+      .setTokenOracle(anchor.web3.Keypair.generate().publicKey)
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+
+    const [orderPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("conditional_buyback"),
+        new anchor.BN(0).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const expiresAt = new anchor.BN(Math.floor(Date.now() / 1000) + 3600);
+
+    await program.methods
+      .placeConditionalBuyback(
+        new anchor.BN(1_000_000), // 1 USDC
+        new anchor.BN(50_000), // $0.05 per token
+        expiresAt
+      )
+      .accounts({
+        treasury: treasuryPda,
+        order: orderPda,
+        authority: provider.wallet.publicKey,
+        coSigner: null,
+        coSignerOperator: null,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const order = await program.account.conditionalBuyback.fetch(orderPda);
+    expect(order.maxPriceMicroUsd.toNumber()).to.equal(50_000);
+
+    await program.methods
+      .cancelConditionalBuyback()
+      .accounts({
+        treasury: treasuryPda,
+        order: orderPda,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    const closed = await provider.connection.getAccountInfo(orderPda);
+    expect(closed).to.be.null;
+  });
+
+  it("Rejects a vault migration without the guardian", async () => {
+    try {
+      await program.methods
+        .proposeVaultMigration(anchor.web3.Keypair.generate().publicKey)
+        .accounts({
+          treasury: treasuryPda,
+          authority: provider.wallet.publicKey,
+          guardian: provider.wallet.publicKey,
+          guardianActivity: null,
+        })
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("GuardianRequired");
+    }
+  });
+
+  it("Records operator heartbeats", async () => {
+    const operator = provider.wallet.publicKey;
+    const [operatorPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("operator"), operator.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .registerOperator(operator)
+      .accounts({
+        treasury: treasuryPda,
+        operatorStats: operatorPda,
+        authority: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    await program.methods
+      .heartbeat()
+      .accounts({ operatorStats: operatorPda, operator })
+      .rpc();
+
+    const stats = await program.account.operator.fetch(operatorPda);
+    expect(stats.heartbeatCount.toNumber()).to.equal(1);
+    expect(stats.lastHeartbeatTimestamp.toNumber()).to.be.greaterThan(0);
+  });
+
+  it("Sets the large operation alert threshold", async () => {
+    await program.methods
+      .setLargeOperationThreshold(new anchor.BN(50_000_000_000)) // 50k USDC
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.largeOperationThreshold.toNumber()).to.equal(50_000_000_000);
+  });
+
+  it("Rejects an out-of-range depeg threshold", async () => {
+    try {
+      await program.methods
+        .setDepegGuard(anchor.web3.Keypair.generate().publicKey, 10_001)
+        .accounts({
+          treasury: treasuryPda,
+          authority: provider.wallet.publicKey,
+          authorityActivity: null,
+        })
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("InvalidDepegThreshold");
+    }
+  });
+
+  it("Records a holder snapshot and rejects unproven boosts", async () => {
+    const [snapshotPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("holder_snapshot"),
+        new anchor.BN(0).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const root = Array.from(Buffer.alloc(32, 7));
+
+    await program.methods
+      .recordHolderSnapshot(root, 1, new anchor.BN(1_000_000))
+      .accounts({
+        treasury: treasuryPda,
+        snapshot: snapshotPda,
+        authority: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const snapshot = await program.account.holderSnapshot.fetch(snapshotPda);
+    expect(snapshot.holderCount).to.equal(1);
+    expect(Buffer.from(snapshot.root).equals(Buffer.alloc(32, 7))).to.be.true;
+
+    try {
+      await program.methods
+        .getHolderBoost(new anchor.BN(1_000_000), new anchor.BN(0), [])
+        .accounts({
+          snapshot: snapshotPda,
+          holder: provider.wallet.publicKey,
+        })
+        .view();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("InvalidMerkleProof");
+    }
+  });
+
+  it("Registers a market maker for inventory loans", async () => {
+    try {
+      await program.methods
+        .setMmLendingPolicy(8000, 7000)
+        .accounts({
+          treasury: treasuryPda,
+          authority: provider.wallet.publicKey,
+          authorityActivity: null,
+        })
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("InvalidLendingPolicy");
+    }
+
+    const marketMaker = anchor.web3.Keypair.generate().publicKey;
+    const [loanPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("mm_loan"), marketMaker.toBuffer()],
+      program.programId
+    );
+    const [collateralVault] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("mm_collateral"), marketMaker.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .registerMarketMaker(marketMaker)
+      .accounts({
+        treasury: treasuryPda,
+        loan: loanPda,
+        collateralVault,
+        usdcMint,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    const loan = await program.account.marketMakerLoan.fetch(loanPda);
+    expect(loan.active).to.be.true;
+    expect(loan.borrowedTokens.toNumber()).to.equal(0);
+    const vault = await getAccount(provider.connection, collateralVault);
+    expect(vault.owner.toString()).to.equal(treasuryPda.toString());
+  });
+
+  it("Timelocks raising the weekly outflow cap", async () => {
+    await program.methods
+      .setWeeklyOutflowCap(new anchor.BN(1_000_000_000_000)) // 1M USDC
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+
+    await program.methods
+      .setWeeklyOutflowCap(new anchor.BN(2_000_000_000_000))
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.weeklyOutflowCapUsdc.toNumber()).to.equal(1_000_000_000_000);
+    expect(treasury.pendingWeeklyOutflowCapUsdc.toNumber()).to.equal(
+      2_000_000_000_000
+    );
+
+    try {
+      await program.methods
+        .applyWeeklyOutflowCap()
+        .accounts({
+          treasury: treasuryPda,
+          authority: provider.wallet.publicKey,
+          authorityActivity: null,
+        })
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("TimelockActive");
+    }
+  });
+
+  it("Timelocks raising the daily volume cap", async () => {
+    await program.methods
+      .setDailyVolumeCap(new anchor.BN(1_000_000_000_000)) // 1M USDC
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+
+    await program.methods
+      .setDailyVolumeCap(new anchor.BN(0))
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.dailyVolumeCapUsdc.toNumber()).to.equal(1_000_000_000_000);
+    expect(treasury.pendingDailyVolumeCapUsdc.toNumber()).to.equal(0);
+    expect(treasury.dailyVolumeCapEta.toNumber()).to.be.greaterThan(0);
+    expect(treasury.hourlyVolumeUsdc).to.have.lengthOf(24);
+  });
+
+  it("Sets the Switchboard fallback feed", async () => {
+    const feed = anchor.web3.Keypair.generate().publicKey;
+    await program.methods
+      .setSwitchboardFallback(feed, new anchor.BN(120))
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.tokenSwitchboardFeed.toString()).to.equal(feed.toString());
+    expect(treasury.switchboardMaxAgeSeconds.toNumber()).to.equal(120);
+  });
+
+  it("Sets the TWAP deviation threshold", async () => {
+    await program.methods
+      .setTwapDeviation(500)
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+
+    let treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.twapMaxDeviationBps).to.equal(500);
+
+    await program.methods
+      .setTwapDeviation(0)
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+    treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.twapMaxDeviationBps).to.equal(0);
+  });
+
+  it("Pauses buybacks when the mint authority changes", async () => {
+    let treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.expectedMintAuthority.toString()).to.equal(
+      provider.wallet.publicKey.toString()
+    );
+    expect(treasury.expectedFreezeAuthority).to.equal(null);
+
+    await program.methods
+      .verifyMintConfig()
+      .accounts({ treasury: treasuryPda, tokenMint })
+      .rpc();
+    treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.mintConfigVerifiedAt.toNumber()).to.be.greaterThan(0);
+
+    // Governance now expects a revoked mint authority, which this mint lacks
+    await program.methods
+      .setExpectedMintConfig(null, null)
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+    await program.methods
+      .verifyMintConfig()
+      .accounts({ treasury: treasuryPda, tokenMint })
+      .rpc();
+
+    const enabledBefore = treasury.instructionEnabled;
+    treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.instructionEnabled.eq(enabledBefore)).to.equal(false);
+
+    await program.methods
+      .setExpectedMintConfig(provider.wallet.publicKey, null)
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+    await program.methods
+      .setInstructionEnabled({ executeBuyback: {} }, true)
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+  });
+
+  it("Configures the settlement price guard", async () => {
+    await program.methods
+      .setSettlementGuard(300, 3)
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+
+    let treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.settlementMaxDeviationBps).to.equal(300);
+    expect(treasury.degradedExecutionLimit).to.equal(3);
+    expect(treasury.degradedExecutions).to.equal(0);
+
+    await program.methods
+      .setSettlementGuard(0, 0)
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+    treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.settlementMaxDeviationBps).to.equal(0);
+  });
+
+  it("Arms the price-deviation circuit breaker", async () => {
+    await program.methods
+      .setCircuitBreaker(1500)
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+    let treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.circuitBreakerDeviationBps).to.equal(1500);
+
+    try {
+      await program.methods
+        .setCircuitBreaker(10_001)
+        .accounts({
+          treasury: treasuryPda,
+          authority: provider.wallet.publicKey,
+          authorityActivity: null,
+        })
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("SlippageTooHigh");
+    }
+
+    await program.methods
+      .setCircuitBreaker(0)
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+    treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.circuitBreakerDeviationBps).to.equal(0);
+  });
+
+  it("Rejects converting more SOL than the fee vault recorded", async () => {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    const [feeVault] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("fee_vault")],
+      program.programId
+    );
+    // The operator key needs its own Operator account to be scoped
+    const [operatorPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("operator"), provider.wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .registerOperator(provider.wallet.publicKey)
+      .accounts({
+        treasury: treasuryPda,
+        operatorStats: operatorPda,
+        authority: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    const treasuryWsol = await createAccount(
+      provider.connection,
+      payer,
+      NATIVE_MINT,
+      treasuryPda,
+      anchor.web3.Keypair.generate()
+    );
+    const treasuryUsdc = await createAccount(
+      provider.connection,
+      payer,
+      usdcMint,
+      treasuryPda,
+      anchor.web3.Keypair.generate()
+    );
+
+    try {
+      await program.methods
+        .convertSolToUsdc(
+          new anchor.BN(1_000_000_000_000),
+          new anchor.BN(1),
+          { jupiter: {} },
+          Buffer.from([0])
+        )
+        .accounts({
+          treasury: treasuryPda,
+          feeVault,
+          treasuryWsol,
+          treasuryUsdc,
+          usdcUsdOracle: null,
+          authority: provider.wallet.publicKey,
+          operatorStats: operatorPda,
+          authorityActivity: null,
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("InsufficientFeeVault");
+    }
+  });
+
+  it("Updates the keeper reward schedule", async () => {
+    await program.methods
+      .updateConfig(null, null, null, 50, new anchor.BN(5_000_000))
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.keeperRewardBps).to.equal(50);
+    expect(treasury.keeperRewardCapUsdc.toNumber()).to.equal(5_000_000);
+
+    try {
+      await program.methods
+        .updateConfig(null, null, null, 501, null)
+        .accounts({
+          treasury: treasuryPda,
+          authority: provider.wallet.publicKey,
+          authorityActivity: null,
+        })
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("InvalidKeeperReward");
+    }
+  });
+
+  it("Publishes the operations calendar", async () => {
+    const [calendarPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("calendar")],
+      program.programId
+    );
+    await program.methods
+      .initializeCalendar()
+      .accounts({
+        treasury: treasuryPda,
+        calendar: calendarPda,
+        authority: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .syncCalendar()
+      .accounts({ treasury: treasuryPda, calendar: calendarPda })
+      .rpc();
+
+    const calendar = await program.account.calendar.fetch(calendarPda);
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    const epochEnd = calendar.entries.find((e) => "epochEnd" in e.kind);
+    expect(epochEnd.at.toNumber()).to.equal(
+      treasury.epochStartTimestamp.add(treasury.epochDurationSeconds).toNumber()
+    );
+    const times = calendar.entries.map((e) => e.at.toNumber());
+    expect(times).to.deep.equal([...times].sort((a, b) => a - b));
+  });
+
+  it("Scopes an operator to buybacks on Jupiter", async () => {
+    const operator = anchor.web3.Keypair.generate().publicKey;
+    const [operatorPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("operator"), operator.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .registerOperator(operator)
+      .accounts({
+        treasury: treasuryPda,
+        operatorStats: operatorPda,
+        authority: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    let stats = await program.account.operator.fetch(operatorPda);
+    expect(stats.allowedKinds).to.equal(0xff);
+    expect(stats.allowedVenues).to.equal(0xff);
+
+    // Bit 0: OperationKind::Buyback and Venue::Jupiter
+    await program.methods
+      .setOperatorScope(0b1, 0b1)
+      .accounts({
+        treasury: treasuryPda,
+        operatorStats: operatorPda,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+    stats = await program.account.operator.fetch(operatorPda);
+    expect(stats.allowedKinds).to.equal(1);
+    expect(stats.allowedVenues).to.equal(1);
+  });
+
+  it("Rejects a token migration without the guardian", async () => {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    const newTokenMint = await createMint(
+      provider.connection,
+      payer,
+      payer.publicKey,
+      null,
+      6,
+      undefined,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    const [migration] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("token_migration")],
+      program.programId
+    );
+
+    try {
+      await program.methods
+        .proposeTokenMigration(new anchor.BN(1), new anchor.BN(10))
+        .accounts({
+          treasury: treasuryPda,
+          migration,
+          newTokenMint,
+          authority: provider.wallet.publicKey,
+          guardian: provider.wallet.publicKey,
+          guardianActivity: null,
+          token2022Program: TOKEN_2022_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("GuardianRequired");
+    }
+    expect(await provider.connection.getAccountInfo(migration)).to.equal(null);
+  });
+
+  it("Lets the pauser pause but not operate", async () => {
+    const pauser = anchor.web3.Keypair.generate();
+    await program.methods
+      .setPauser(pauser.publicKey)
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+
+    try {
+      await program.methods
+        .recordUsdcConversion(new anchor.BN(1))
+        .accounts({
+          treasury: treasuryPda,
+          usdcUsdOracle: null,
+          authority: pauser.publicKey,
+          authorityActivity: null,
+        })
+        .signers([pauser])
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("ConstraintRaw");
+    }
+
+    await program.methods
+      .emergencyPause()
+      .accounts({
+        treasury: treasuryPda,
+        pauser: pauser.publicKey,
+        pauserActivity: null,
+      })
+      .signers([pauser])
+      .rpc();
+    let treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.pauseFlags).to.equal(0b1111);
+    expect(treasury.pauser.toString()).to.equal(pauser.publicKey.toString());
+    expect(treasury.operator.toString()).to.equal(
+      provider.wallet.publicKey.toString()
+    );
+
+    await program.methods
+      .resume()
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+    treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.pauseFlags).to.equal(0);
+  });
+
+  it("Guardian can pause but not resume", async () => {
+    const guardian = anchor.web3.Keypair.generate();
+    let treasury = await program.account.treasury.fetch(treasuryPda);
+    await program.methods
+      .setReservePolicy(
+        guardian.publicKey,
+        treasury.governance,
+        treasury.reserveOpsMonthlyLimitUsdc
+      )
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+
+    await program.methods
+      .emergencyPause()
+      .accounts({
+        treasury: treasuryPda,
+        pauser: guardian.publicKey,
+        pauserActivity: null,
+      })
+      .signers([guardian])
+      .rpc();
+    treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.pauseFlags).to.equal(0b1111);
+
+    try {
+      await program.methods
+        .resume()
+        .accounts({
+          treasury: treasuryPda,
+          authority: guardian.publicKey,
+          authorityActivity: null,
+        })
+        .signers([guardian])
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("ConstraintRaw");
+    }
+
+    await program.methods
+      .resume()
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+  });
+
+  it("Records guardian response times", async () => {
+    const guardian = anchor.web3.Keypair.generate();
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    await program.methods
+      .setReservePolicy(
+        guardian.publicKey,
+        treasury.governance,
+        treasury.reserveOpsMonthlyLimitUsdc
+      )
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+
+    const [record] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("guardian_record"), guardian.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .openGuardianRecord(guardian.publicKey)
+      .accounts({
+        record,
+        payer: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    try {
+      await program.methods
+        .acknowledgeCircuitBreaker()
+        .accounts({ treasury: treasuryPda, guardian: guardian.publicKey, record })
+        .signers([guardian])
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("NoCircuitBreakerTrip");
+    }
+
+    const [proposal] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("config_proposal")],
+      program.programId
+    );
+    await program.methods
+      .proposeConfig({
+        allocations: null,
+        maxUsdcPerCycle: null,
+        cooldownSeconds: null,
+        slippageBps: null,
+        keeperRewardBps: null,
+        keeperRewardCapUsdc: null,
+        configDelaySeconds: null,
+        bountyMaxPayoutUsdc: null,
+        bountyTimelockSeconds: null,
+      })
+      .accounts({
+        treasury: treasuryPda,
+        proposal,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .reviewConfigProposal(true)
+      .accounts({
+        treasury: treasuryPda,
+        proposal,
+        authority: provider.wallet.publicKey,
+        guardian: guardian.publicKey,
+        record,
+      })
+      .signers([guardian])
+      .rpc();
+
+    expect(await provider.connection.getAccountInfo(proposal)).to.equal(null);
+    const log = await program.account.guardianRecord.fetch(record);
+    expect(log.responses.toNumber()).to.equal(1);
+    expect(log.vetoes.toNumber()).to.equal(1);
+  });
+
+  it("Pauses liquidity while buybacks stay open", async () => {
+    const outsider = anchor.web3.Keypair.generate();
+    await program.methods
+      .pauseOperation({ liquidity: {} })
+      .accounts({
+        treasury: treasuryPda,
+        pauser: provider.wallet.publicKey,
+        pauserActivity: null,
+      })
+      .rpc();
+    let treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.pauseFlags).to.equal(0b0010);
+
+    try {
+      await program.methods
+        .resumeOperation({ liquidity: {} })
+        .accounts({
+          treasury: treasuryPda,
+          authority: outsider.publicKey,
+          authorityActivity: null,
+        })
+        .signers([outsider])
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("ConstraintRaw");
+    }
+
+    await program.methods
+      .resumeOperation({ liquidity: {} })
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+    treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.pauseFlags).to.equal(0);
+  });
+
+  it("Sets the CLMM tick range", async () => {
+    const admin = {
+      treasury: treasuryPda,
+      authority: provider.wallet.publicKey,
+      authorityActivity: null,
+    };
+    try {
+      await program.methods.setClmmTickRange(600, -600).accounts(admin).rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("InvalidTickRange");
+    }
+
+    await program.methods.setClmmTickRange(-600, 600).accounts(admin).rpc();
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.clmmTickLower).to.equal(-600);
+    expect(treasury.clmmTickUpper).to.equal(600);
+    expect(treasury.clmmPositionMint.toString()).to.equal(
+      anchor.web3.PublicKey.default.toString()
+    );
+  });
+
+  it("Overrides slippage and size limits per pool", async () => {
+    const [poolRegistry] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pool_registry")],
+      program.programId
+    );
+    const usdcVault = anchor.web3.Keypair.generate().publicKey;
+    const tokenVault = anchor.web3.Keypair.generate().publicKey;
+    const accounts = {
+      treasury: treasuryPda,
+      poolRegistry,
+      authority: provider.wallet.publicKey,
+    };
+
+    await program.methods
+      .initializePoolRegistry()
+      .accounts({
+        ...accounts,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .registerPool({ raydium: {} }, usdcVault, tokenVault)
+      .accounts(accounts)
+      .rpc();
+    await program.methods
+      .setPoolLimits(usdcVault, tokenVault, 50, new anchor.BN(1_000_000_000))
+      .accounts(accounts)
+      .rpc();
+
+    const registry = await program.account.poolRegistry.fetch(poolRegistry);
+    const pool = registry.pools[registry.pools.length - 1];
+    expect(pool.slippageBps).to.equal(50);
+    expect(pool.maxUsdcAmount.toNumber()).to.equal(1_000_000_000);
+
+    try {
+      await program.methods
+        .setPoolLimits(usdcVault, tokenVault, 1001, new anchor.BN(0))
+        .accounts(accounts)
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("SlippageTooHigh");
+    }
+  });
+
+  it("Types a registered pool as a Meteora DLMM pair", async () => {
+    const [poolRegistry] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pool_registry")],
+      program.programId
+    );
+    const reserveUsdc = anchor.web3.Keypair.generate().publicKey;
+    const reserveToken = anchor.web3.Keypair.generate().publicKey;
+    const accounts = {
+      treasury: treasuryPda,
+      poolRegistry,
+      authority: provider.wallet.publicKey,
+    };
+
+    await program.methods
+      .registerPool({ meteora: {} }, reserveUsdc, reserveToken)
+      .accounts(accounts)
+      .rpc();
+    await program.methods
+      .setPoolType(reserveUsdc, reserveToken, { meteoraDlmm: {} })
+      .accounts(accounts)
+      .rpc();
+
+    const registry = await program.account.poolRegistry.fetch(poolRegistry);
+    const pool = registry.pools[registry.pools.length - 1];
+    expect(pool.poolType).to.deep.equal({ meteoraDlmm: {} });
+
+    const admin = {
+      treasury: treasuryPda,
+      authority: provider.wallet.publicKey,
+      authorityActivity: null,
+    };
+    try {
+      await program.methods.setDlmmBinRange(-80, -1).accounts(admin).rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("InvalidBinRange");
+    }
+    await program.methods.setDlmmBinRange(-20, -1).accounts(admin).rpc();
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.dlmmBinOffsetLower).to.equal(-20);
+    expect(treasury.dlmmBinOffsetUpper).to.equal(-1);
+  });
+
+  it("Only tracks LP positions in constant-product pools", async () => {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    const poolAuthority = anchor.web3.Keypair.generate().publicKey;
+    const [poolRegistry] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pool_registry")],
+      program.programId
+    );
+    const poolUsdc = await createAccount(
+      provider.connection,
+      payer,
+      usdcMint,
+      poolAuthority,
+      anchor.web3.Keypair.generate()
+    );
+    const poolToken = await createAccount(
+      provider.connection,
+      payer,
+      tokenMint,
+      poolAuthority,
+      anchor.web3.Keypair.generate()
+    );
+    const lpMint = await createMint(
+      provider.connection,
+      payer,
+      poolAuthority,
+      null,
+      6
+    );
+    const [lpPosition] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("lp_position"), poolUsdc.toBuffer(), poolToken.toBuffer()],
+      program.programId
+    );
+    const admin = {
+      treasury: treasuryPda,
+      poolRegistry,
+      authority: provider.wallet.publicKey,
+    };
+    const open = () =>
+      program.methods
+        .openLpPosition()
+        .accounts({
+          treasury: treasuryPda,
+          lpPosition,
+          poolUsdc,
+          poolToken,
+          poolRegistry,
+          lpMint,
+          authority: provider.wallet.publicKey,
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    await program.methods
+      .registerPool({ raydium: {} }, poolUsdc, poolToken)
+      .accounts(admin)
+      .rpc();
+    await program.methods
+      .setPoolType(poolUsdc, poolToken, { meteoraDlmm: {} })
+      .accounts(admin)
+      .rpc();
+    try {
+      await open();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("WrongPoolType");
+    }
+
+    await program.methods
+      .setPoolType(poolUsdc, poolToken, { constantProduct: {} })
+      .accounts(admin)
+      .rpc();
+    await open();
+    const position = await program.account.lpPosition.fetch(lpPosition);
+    expect(position.venue).to.deep.equal({ raydium: {} });
+    expect(position.lpMint.toString()).to.equal(lpMint.toString());
+    expect(position.usdcContributed.toNumber()).to.equal(0);
+  });
+
+  it("Refuses to migrate a treasury already on the current layout", async () => {
+    try {
+      await program.methods
+        .migrateTreasury()
+        .accounts({
+          treasury: treasuryPda,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("UnknownTreasuryLayout");
+    }
+  });
+
+  it("Burns LP tokens and locks them until a set time", async () => {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    const lpMint = await createMint(
+      provider.connection,
+      payer,
+      payer.publicKey,
+      null,
+      6
+    );
+    const treasuryLp = await createAccount(
+      provider.connection,
+      payer,
+      lpMint,
+      treasuryPda,
+      anchor.web3.Keypair.generate()
+    );
+    await mintTo(provider.connection, payer, lpMint, treasuryLp, payer, 1_000);
+
+    await program.methods
+      .burnLp(new anchor.BN(400))
+      .accounts({
+        treasury: treasuryPda,
+        lpMint,
+        treasuryLp,
+        authority: provider.wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    let treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.totalLpBurned.toNumber()).to.equal(400);
+
+    const [lpLock] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("lp_lock"), treasury.lpLockCount.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const [lpLockVault] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("lp_lock_vault"), lpLock.toBuffer()],
+      program.programId
+    );
+    const lock = (lockedUntil: number) =>
+      program.methods
+        .lockLp(new anchor.BN(300), new anchor.BN(lockedUntil))
+        .accounts({
+          treasury: treasuryPda,
+          lpLock,
+          lpLockVault,
+          lpMint,
+          treasuryLp,
+          authority: provider.wallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    const now = Math.floor(Date.now() / 1000);
+    try {
+      await lock(now - 60);
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("InvalidLockTime");
+    }
+    await lock(now + 86_400);
+    expect(Number((await getAccount(provider.connection, lpLockVault)).amount)).to.equal(300);
+
+    try {
+      await program.methods
+        .releaseLp()
+        .accounts({
+          treasury: treasuryPda,
+          lpLock,
+          lpLockVault,
+          lpMint,
+          treasuryLp,
+          authority: provider.wallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("LpStillLocked");
+    }
+
+    const report = await program.methods
+      .attestLpLocks()
+      .accounts({ treasury: treasuryPda })
+      .remainingAccounts([
+        { pubkey: treasuryLp, isSigner: false, isWritable: false },
+        { pubkey: lpLock, isSigner: false, isWritable: false },
+      ])
+      .view();
+    expect(report.holdings[1].account.toString()).to.equal(lpLockVault.toString());
+    expect(report.totalLpHeld.toNumber()).to.equal(600);
+    expect(report.totalLpLocked.toNumber()).to.equal(300);
+    expect(report.totalLpBurned.toNumber()).to.equal(400);
+  });
+
+  it("Whitelists strategy adapters with caps that only drop immediately", async () => {
+    // Any executable program stands in for an adapter here
+    const adapterProgram = anchor.web3.SystemProgram.programId;
+    const [adapter] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("strategy_adapter"), adapterProgram.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .proposeStrategyAdapter(new anchor.BN(1_000_000))
+      .accounts({
+        treasury: treasuryPda,
+        adapter,
+        adapterProgram,
+        authority: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const setCap = (cap: number) =>
+      program.methods
+        .setStrategyAdapterCap(new anchor.BN(cap))
+        .accounts({
+          treasury: treasuryPda,
+          adapter,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+    try {
+      await setCap(2_000_000);
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("ConfigChangeTimelocked");
+    }
+    await setCap(500_000);
+    const entry = await program.account.strategyAdapter.fetch(adapter);
+    expect(entry.capUsdc.toNumber()).to.equal(500_000);
+    expect(entry.depositedUsdc.toNumber()).to.equal(0);
+
+    await program.methods
+      .removeStrategyAdapter()
+      .accounts({
+        treasury: treasuryPda,
+        adapter,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+    expect(await provider.connection.getAccountInfo(adapter)).to.be.null;
+  });
+
+  it("Only binds a Squads multisig whose vault is the admin", async () => {
+    try {
+      await program.methods
+        .bindSquadsMultisig(0)
+        .accounts({
+          treasury: treasuryPda,
+          multisig: usdcMint,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("InvalidSquadsMultisig");
+    }
+
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.squadsMultisig.toString()).to.equal(
+      anchor.web3.PublicKey.default.toString()
+    );
+  });
+
+  it("Only a bound Realms governance can update allocations", async () => {
+    try {
+      await program.methods
+        .bindRealmsGovernance()
+        .accounts({
+          treasury: treasuryPda,
+          governance: usdcMint,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("InvalidRealmsGovernance");
+    }
+
+    try {
+      await program.methods
+        .updateAllocations({
+          buybackBps: 5000,
+          liquidityBps: 3000,
+          reserveBps: 2000,
+        })
+        .accounts({
+          treasury: treasuryPda,
+          governance: provider.wallet.publicKey,
+        })
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("AdminNotRealmsGovernance");
+    }
+  });
+
+  it("Logs admin key activity", async () => {
+    const admin = provider.wallet.publicKey;
+    const [activity] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("key_activity"), admin.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .openKeyActivity(admin)
+      .accounts({
+        activity,
+        payer: admin,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .setLargeOperationThreshold(new anchor.BN(50_000_000_000))
+      .accounts({
+        treasury: treasuryPda,
+        authority: admin,
+        authorityActivity: activity,
+      })
+      .rpc();
+
+    const log = await program.account.keyActivity.fetch(activity);
+    expect(log.key.toString()).to.equal(admin.toString());
+    expect(log.actionCount.toNumber()).to.equal(1);
+    expect(log.lastAction).to.deep.equal({ config: {} });
+    expect(log.lastActionAt.toNumber()).to.be.greaterThan(0);
+  });
+
+  it("Timelocks configuration changes once a delay is set", async () => {
+    const admin = {
+      treasury: treasuryPda,
+      authority: provider.wallet.publicKey,
+      authorityActivity: null,
+    };
+    const [proposal] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("config_proposal")],
+      program.programId
+    );
+    const change = {
+      allocations: { buybackBps: 5000, liquidityBps: 3000, reserveBps: 2000 },
+      maxUsdcPerCycle: null,
+      cooldownSeconds: null,
+      slippageBps: null,
+      keeperRewardBps: null,
+      keeperRewardCapUsdc: null,
+      configDelaySeconds: new anchor.BN(0),
+      bountyMaxPayoutUsdc: null,
+      bountyTimelockSeconds: new anchor.BN(24 * 3600),
+    };
+
+    await program.methods
+      .setConfigDelay(new anchor.BN(1))
+      .accounts(admin)
+      .rpc();
+
+    try {
+      await program.methods
+        .updateConfig(null, null, 150, null, null)
+        .accounts(admin)
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("ConfigChangeTimelocked");
+    }
+
+    // Loosening the bounty policy waits for the delay; tightening does not
+    const before = await program.account.treasury.fetch(treasuryPda);
+    try {
+      await program.methods
+        .setBountyPolicy(before.bountyMaxPayoutUsdc, new anchor.BN(24 * 3600))
+        .accounts(admin)
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("ConfigChangeTimelocked");
+    }
+    await program.methods
+      .setBountyPolicy(
+        before.bountyMaxPayoutUsdc.divn(2),
+        before.bountyTimelockSeconds
+      )
+      .accounts(admin)
+      .rpc();
+
+    try {
+      await program.methods
+        .proposeConfig({
+          ...change,
+          allocations: { buybackBps: 5000, liquidityBps: 3000, reserveBps: 0 },
+        })
+        .accounts({
+          ...admin,
+          proposal,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      expect.fail("Should have thrown error");
+    } catch (err) {
+      expect(err.toString()).to.include("InvalidAllocation");
+    }
+
+    await program.methods
+      .proposeConfig(change)
+      .accounts({
+        ...admin,
+        proposal,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    await new Promise((resolve) => setTimeout(resolve, 2000));
+    const signature = await program.methods
+      .executeConfig()
+      .accounts({ ...admin, proposal })
+      .rpc({ commitment: "confirmed" });
+
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.configDelaySeconds.toNumber()).to.equal(0);
+    expect(treasury.buybackAllocationBps).to.equal(5000);
+    expect(treasury.bountyTimelockSeconds.toNumber()).to.equal(24 * 3600);
+
+    // Executing an allocation change reports the old and new split
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const parser = new anchor.EventParser(program.programId, program.coder);
+    const events = [...parser.parseLogs(tx.meta.logMessages)];
+    expect(
+      events.some((event) => event.name.toLowerCase() === "allocationsupdated")
+    ).to.equal(true);
+    expect(await provider.connection.getAccountInfo(proposal)).to.equal(null);
+  });
+
+  it("Simulates a cycle without oracles", async () => {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    const [feeVault] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("fee_vault")],
+      program.programId
+    );
+    const treasuryUsdc = await createAccount(
+      provider.connection,
+      payer,
+      usdcMint,
+      treasuryPda,
+      anchor.web3.Keypair.generate()
+    );
+
+    const sim = await program.methods
+      .simulateCycle()
+      .accounts({
+        treasury: treasuryPda,
+        feeVault,
+        tokenMint,
+        treasuryUsdc,
+        solUsdOracle: null,
+        tokenUsdOracle: null,
+        tokenSwitchboardFeed: null,
+      })
+      .view();
+    expect(sim.usdcFromConversion.toNumber()).to.equal(0);
+    expect(sim.buybackTokensOut.toNumber()).to.equal(0);
+    expect(sim.liquidityUsdc.toNumber()).to.equal(0);
+  });
+
+  it("Watchdog flags an idle treasury with budget", async () => {
+    await program.methods
+      .setWatchdogThreshold(new anchor.BN(60))
+      .accounts({
+        treasury: treasuryPda,
+        authority: provider.wallet.publicKey,
+        authorityActivity: null,
+      })
+      .rpc();
+
+    await program.methods
+      .pingWatchdog()
+      .accounts({
+        treasury: treasuryPda,
+        caller: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    const treasury = await program.account.treasury.fetch(treasuryPda);
+    expect(treasury.stalled).to.equal(true);
+  });
+
   it("Rejects invalid allocations", async () => {
     const [invalidTreasuryPda] = anchor.web3.PublicKey.findProgramAddressSync(
       [Buffer.from("treasury2")],
@@ -138,10 +2601,15 @@ describe("usdcball", () => {
           2000, // 20%
           new anchor.BN(10_000_000_000),
           new anchor.BN(3600),
-          200
+          200,
+          6,
+          provider.wallet.publicKey,
+          new anchor.BN(0)
         )
         .accounts({
           treasury: invalidTreasuryPda,
+          tokenMint,
+          usdcMint,
           authority: provider.wallet.publicKey,
           systemProgram: anchor.web3.SystemProgram.programId,
         })
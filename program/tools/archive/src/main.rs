@@ -0,0 +1,387 @@
+//! Disaster-recovery archive of the USDCBALL program state.
+//!
+//! Exports every program-owned account to a versioned archive, verifies a cluster
+//! against an archive, and restores an archive into account files a local
+//! validator can load, for audits and incident rehearsals.
+//!
+//! Usage:
+//!
+//! ```text
+//! usdcball-archive export <accounts.json> <archive.json>
+//! usdcball-archive verify <archive.json> <accounts.json>
+//! usdcball-archive restore <archive.json> <out-dir>
+//! ```
+//!
+//! `accounts.json` is the raw JSON-RPC response of `getProgramAccounts` for the
+//! program with `"encoding": "base64"` (optionally `"withContext": true`, which
+//! records the slot), e.g.
+//!
+//! ```text
+//! curl $RPC -H 'Content-Type: application/json' -d '{"jsonrpc":"2.0","id":1,
+//!   "method":"getProgramAccounts","params":["<program-id>",
+//!   {"encoding":"base64","withContext":true}]}' > accounts.json
+//! ```
+//!
+//! `restore` writes one `<pubkey>.json` per account in the format of
+//! `solana account --output json`, loadable with
+//! `solana-test-validator --account-dir <out-dir>` on a cluster running the
+//! program under the same id.
+//!
+//! With `USDCBALL_LOG_FORMAT=json`, a JSON record per step goes to stderr, tagged
+//! with `USDCBALL_OPERATION_ID` when set (see `usdcball-log`).
+
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::{prelude::Pubkey, Discriminator};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::{env, fs, path::Path, process};
+use usdcball::{
+    AddressBookEntry, Attestation, BountyPayout, Calendar, Campaign, ConditionalBuyback,
+    ConfigProposal, DividendClaim, DividendDistribution, Epoch, EpochStats, FeeCounter, FeeVault,
+    GuardianRecord, HolderSnapshot, KeyActivity, Lbp, LiquidityPosition, LpLock, LpPosition,
+    MarketMakerLoan, Milestone, Operator, PendingOperation, PoolRegistry, QuoteBoard,
+    ReserveDestination, ReserveWithdrawal, StakePosition, StrategyAdapter, TokenMigration,
+    TransferFeeCounter, Treasury, Twap,
+};
+use usdcball_log::Logger;
+
+/// Bumped whenever the archive layout changes
+const ARCHIVE_VERSION: u64 = 1;
+
+/// One program-owned account as stored in the archive
+#[derive(Clone, Debug, PartialEq)]
+struct ArchivedAccount {
+    pubkey: String,
+    lamports: u64,
+    owner: String,
+    executable: bool,
+    rent_epoch: u64,
+    data: Vec<u8>,
+}
+
+impl ArchivedAccount {
+    fn kind(&self) -> &'static str {
+        account_kind(&self.data)
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "pubkey": self.pubkey,
+            "kind": self.kind(),
+            "lamports": self.lamports,
+            "owner": self.owner,
+            "executable": self.executable,
+            "rent_epoch": self.rent_epoch,
+            "data": STANDARD.encode(&self.data),
+            "hash": hash(&self.data).to_string(),
+        })
+    }
+
+    /// `solana account --output json` representation
+    fn to_cli_json(&self) -> Value {
+        json!({
+            "pubkey": self.pubkey,
+            "account": {
+                "lamports": self.lamports,
+                "data": [STANDARD.encode(&self.data), "base64"],
+                "owner": self.owner,
+                "executable": self.executable,
+                "rentEpoch": self.rent_epoch,
+                "space": self.data.len(),
+            },
+        })
+    }
+}
+
+/// Account type named by the Anchor discriminator at the start of `data`
+fn account_kind(data: &[u8]) -> &'static str {
+    let kinds: [(&[u8], &'static str); 34] = [
+        (&Treasury::DISCRIMINATOR, "Treasury"),
+        (&Calendar::DISCRIMINATOR, "Calendar"),
+        (&DividendDistribution::DISCRIMINATOR, "DividendDistribution"),
+        (&DividendClaim::DISCRIMINATOR, "DividendClaim"),
+        (&BountyPayout::DISCRIMINATOR, "BountyPayout"),
+        (&PendingOperation::DISCRIMINATOR, "PendingOperation"),
+        (&Operator::DISCRIMINATOR, "Operator"),
+        (&Epoch::DISCRIMINATOR, "Epoch"),
+        (&EpochStats::DISCRIMINATOR, "EpochStats"),
+        (&ReserveDestination::DISCRIMINATOR, "ReserveDestination"),
+        (&ReserveWithdrawal::DISCRIMINATOR, "ReserveWithdrawal"),
+        (&Attestation::DISCRIMINATOR, "Attestation"),
+        (&ConditionalBuyback::DISCRIMINATOR, "ConditionalBuyback"),
+        (&FeeCounter::DISCRIMINATOR, "FeeCounter"),
+        (&FeeVault::DISCRIMINATOR, "FeeVault"),
+        (&PoolRegistry::DISCRIMINATOR, "PoolRegistry"),
+        (&Campaign::DISCRIMINATOR, "Campaign"),
+        (&StakePosition::DISCRIMINATOR, "StakePosition"),
+        (&HolderSnapshot::DISCRIMINATOR, "HolderSnapshot"),
+        (&MarketMakerLoan::DISCRIMINATOR, "MarketMakerLoan"),
+        (&Twap::DISCRIMINATOR, "Twap"),
+        (&TokenMigration::DISCRIMINATOR, "TokenMigration"),
+        (&KeyActivity::DISCRIMINATOR, "KeyActivity"),
+        (&ConfigProposal::DISCRIMINATOR, "ConfigProposal"),
+        (&Lbp::DISCRIMINATOR, "Lbp"),
+        (&QuoteBoard::DISCRIMINATOR, "QuoteBoard"),
+        (&GuardianRecord::DISCRIMINATOR, "GuardianRecord"),
+        (&Milestone::DISCRIMINATOR, "Milestone"),
+        (&LiquidityPosition::DISCRIMINATOR, "LiquidityPosition"),
+        (&LpPosition::DISCRIMINATOR, "LpPosition"),
+        (&LpLock::DISCRIMINATOR, "LpLock"),
+        (&StrategyAdapter::DISCRIMINATOR, "StrategyAdapter"),
+        (&AddressBookEntry::DISCRIMINATOR, "AddressBookEntry"),
+        (&TransferFeeCounter::DISCRIMINATOR, "TransferFeeCounter"),
+    ];
+    kinds
+        .iter()
+        .find(|(disc, _)| data.starts_with(disc))
+        .map(|(_, kind)| *kind)
+        .unwrap_or("Unknown")
+}
+
+fn read_json(path: &str) -> Result<Value, String> {
+    let raw = fs::read_to_string(path).map_err(|e| format!("reading {path}: {e}"))?;
+    serde_json::from_str(&raw).map_err(|e| format!("parsing {path}: {e}"))
+}
+
+fn field<'a>(value: &'a Value, key: &str, path: &str) -> Result<&'a Value, String> {
+    value
+        .get(key)
+        .ok_or_else(|| format!("{path}: missing `{key}`"))
+}
+
+fn as_u64(value: &Value, key: &str, path: &str) -> Result<u64, String> {
+    field(value, key, path)?
+        .as_u64()
+        .ok_or_else(|| format!("{path}: `{key}` is not an integer"))
+}
+
+fn as_str<'a>(value: &'a Value, key: &str, path: &str) -> Result<&'a str, String> {
+    field(value, key, path)?
+        .as_str()
+        .ok_or_else(|| format!("{path}: `{key}` is not a string"))
+}
+
+fn decode_base64(encoded: &str, path: &str) -> Result<Vec<u8>, String> {
+    STANDARD
+        .decode(encoded)
+        .map_err(|e| format!("{path}: invalid base64 account data: {e}"))
+}
+
+/// Accounts and slot from a `getProgramAccounts` response, keyed by address
+fn load_program_accounts(
+    path: &str,
+) -> Result<(Option<u64>, BTreeMap<String, ArchivedAccount>), String> {
+    let json = read_json(path)?;
+    let result = field(&json, "result", path)?;
+    // `withContext: true` wraps the list as `{ context: { slot }, value: [..] }`
+    let (slot, list) = match result.get("value") {
+        Some(value) => (result["context"]["slot"].as_u64(), value),
+        None => (None, result),
+    };
+    let list = list
+        .as_array()
+        .ok_or_else(|| format!("{path}: result is not a list of accounts"))?;
+
+    let mut accounts = BTreeMap::new();
+    for entry in list {
+        let account = field(entry, "account", path)?;
+        let data = account["data"][0]
+            .as_str()
+            .ok_or_else(|| format!("{path}: account data is not base64-encoded"))?;
+        let archived = ArchivedAccount {
+            pubkey: as_str(entry, "pubkey", path)?.to_string(),
+            lamports: as_u64(account, "lamports", path)?,
+            owner: as_str(account, "owner", path)?.to_string(),
+            executable: account["executable"].as_bool().unwrap_or(false),
+            rent_epoch: account["rentEpoch"].as_u64().unwrap_or(u64::MAX),
+            data: decode_base64(data, path)?,
+        };
+        accounts.insert(archived.pubkey.clone(), archived);
+    }
+    Ok((slot, accounts))
+}
+
+fn load_archive(path: &str) -> Result<BTreeMap<String, ArchivedAccount>, String> {
+    let json = read_json(path)?;
+    let version = as_u64(&json, "version", path)?;
+    if version != ARCHIVE_VERSION {
+        return Err(format!(
+            "{path}: archive version {version}, this tool reads version {ARCHIVE_VERSION}"
+        ));
+    }
+    let program_id = as_str(&json, "program_id", path)?;
+    if program_id != usdcball::ID.to_string() {
+        return Err(format!(
+            "{path}: archive of program {program_id}, this tool is built for {}",
+            usdcball::ID
+        ));
+    }
+
+    let mut accounts = BTreeMap::new();
+    for entry in field(&json, "accounts", path)?
+        .as_array()
+        .ok_or_else(|| format!("{path}: `accounts` is not a list"))?
+    {
+        let archived = ArchivedAccount {
+            pubkey: as_str(entry, "pubkey", path)?.to_string(),
+            lamports: as_u64(entry, "lamports", path)?,
+            owner: as_str(entry, "owner", path)?.to_string(),
+            executable: entry["executable"].as_bool().unwrap_or(false),
+            rent_epoch: as_u64(entry, "rent_epoch", path)?,
+            data: decode_base64(as_str(entry, "data", path)?, path)?,
+        };
+        let expected_hash = as_str(entry, "hash", path)?;
+        if hash(&archived.data).to_string() != expected_hash {
+            return Err(format!(
+                "{path}: data of {} does not match its recorded hash",
+                archived.pubkey
+            ));
+        }
+        accounts.insert(archived.pubkey.clone(), archived);
+    }
+    Ok(accounts)
+}
+
+fn export(log: &Logger, accounts_path: &str, archive_path: &str) -> Result<bool, String> {
+    let (slot, accounts) = load_program_accounts(accounts_path)?;
+    let program_id = usdcball::ID.to_string();
+    if let Some(foreign) = accounts.values().find(|a| a.owner != program_id) {
+        return Err(format!(
+            "{accounts_path}: {} is owned by {}, not the program",
+            foreign.pubkey, foreign.owner
+        ));
+    }
+
+    let mut kinds: BTreeMap<&str, usize> = BTreeMap::new();
+    for account in accounts.values() {
+        *kinds.entry(account.kind()).or_default() += 1;
+    }
+    let archive = json!({
+        "version": ARCHIVE_VERSION,
+        "program_id": program_id,
+        "slot": slot,
+        "accounts": accounts.values().map(ArchivedAccount::to_json).collect::<Vec<_>>(),
+    });
+    let encoded = serde_json::to_string_pretty(&archive).map_err(|e| e.to_string())?;
+    fs::write(archive_path, encoded).map_err(|e| format!("writing {archive_path}: {e}"))?;
+
+    log.info(
+        "archive exported",
+        json!({ "slot": slot, "accounts": accounts.len(), "kinds": kinds }),
+    );
+    println!("archived {} accounts", accounts.len());
+    for (kind, count) in kinds {
+        println!("  {kind:<24} {count}");
+    }
+    Ok(true)
+}
+
+fn verify(log: &Logger, archive_path: &str, accounts_path: &str) -> Result<bool, String> {
+    let archived = load_archive(archive_path)?;
+    let (slot, live) = load_program_accounts(accounts_path)?;
+    if let Some(slot) = slot {
+        println!("live state at slot {slot}");
+    }
+
+    let mut divergences = Vec::new();
+    for (pubkey, expected) in &archived {
+        match live.get(pubkey) {
+            None => divergences.push(format!("{} {pubkey}: missing on cluster", expected.kind())),
+            Some(actual) if actual.data != expected.data => divergences.push(format!(
+                "{} {pubkey}: data differs (archived {}, live {})",
+                expected.kind(),
+                hash(&expected.data),
+                hash(&actual.data)
+            )),
+            Some(actual) if actual.lamports != expected.lamports => divergences.push(format!(
+                "{} {pubkey}: lamports archived {}, live {}",
+                expected.kind(),
+                expected.lamports,
+                actual.lamports
+            )),
+            Some(_) => {}
+        }
+    }
+    for (pubkey, actual) in &live {
+        if !archived.contains_key(pubkey) {
+            divergences.push(format!("{} {pubkey}: not in archive", actual.kind()));
+        }
+    }
+
+    log.info(
+        "archive verified",
+        json!({
+            "slot": slot,
+            "archived": archived.len(),
+            "live": live.len(),
+            "divergences": divergences.len(),
+        }),
+    );
+    for d in &divergences {
+        log.warn("divergence", json!({ "slot": slot, "divergence": d }));
+    }
+    println!(
+        "compared {} archived and {} live accounts",
+        archived.len(),
+        live.len()
+    );
+    if divergences.is_empty() {
+        println!("OK: cluster matches the archive");
+        return Ok(true);
+    }
+    println!("DIVERGENCE ({}):", divergences.len());
+    for d in &divergences {
+        println!("  - {d}");
+    }
+    Ok(false)
+}
+
+fn restore(log: &Logger, archive_path: &str, out_dir: &str) -> Result<bool, String> {
+    let archived = load_archive(archive_path)?;
+    fs::create_dir_all(out_dir).map_err(|e| format!("creating {out_dir}: {e}"))?;
+    for (pubkey, account) in &archived {
+        pubkey
+            .parse::<Pubkey>()
+            .map_err(|e| format!("{archive_path}: invalid address {pubkey}: {e}"))?;
+        let path = Path::new(out_dir).join(format!("{pubkey}.json"));
+        let encoded =
+            serde_json::to_string_pretty(&account.to_cli_json()).map_err(|e| e.to_string())?;
+        fs::write(&path, encoded).map_err(|e| format!("writing {}: {e}", path.display()))?;
+    }
+    log.info(
+        "archive restored",
+        json!({ "accounts": archived.len(), "out_dir": out_dir }),
+    );
+    println!("wrote {} account files to {out_dir}", archived.len());
+    Ok(true)
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let log = Logger::from_env("usdcball-archive");
+    let result = match args.get(1).map(String::as_str) {
+        Some("export") if args.len() == 4 => export(&log, &args[2], &args[3]),
+        Some("verify") if args.len() == 4 => verify(&log, &args[2], &args[3]),
+        Some("restore") if args.len() == 4 => restore(&log, &args[2], &args[3]),
+        _ => {
+            eprintln!(
+                "usage: {0} export <accounts.json> <archive.json>\n       \
+                 {0} verify <archive.json> <accounts.json>\n       \
+                 {0} restore <archive.json> <out-dir>",
+                args[0]
+            );
+            process::exit(2);
+        }
+    };
+
+    match result {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(e) => {
+            log.error("archive failed", json!({ "error": e }));
+            eprintln!("error: {e}");
+            process::exit(2);
+        }
+    }
+}
@@ -0,0 +1,222 @@
+//! An archive round-trips the program accounts it was exported from: the same
+//! state verifies clean, any drift is reported, and restore writes account files
+//! a local validator loads byte-for-byte.
+
+use anchor_lang::{prelude::Pubkey, Discriminator};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use std::{fs, process};
+use usdcball::{PoolRegistry, Treasury};
+
+/// Fresh scratch directory per test
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("usdcball-archive-{}-{name}", process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn account(pubkey: &Pubkey, owner: &Pubkey, lamports: u64, data: &[u8]) -> Value {
+    json!({
+        "pubkey": pubkey.to_string(),
+        "account": {
+            "lamports": lamports,
+            "data": [STANDARD.encode(data), "base64"],
+            "owner": owner.to_string(),
+            "executable": false,
+            "rentEpoch": 18_446_744_073_709_551_615u64,
+        },
+    })
+}
+
+/// A `getProgramAccounts` response with context at `slot`
+fn write_accounts(path: &PathBuf, slot: u64, accounts: &[Value]) {
+    let response = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "result": { "context": { "slot": slot }, "value": accounts },
+    });
+    fs::write(path, response.to_string()).unwrap();
+}
+
+fn run(args: &[&PathBuf], command: &str) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_usdcball-archive"))
+        .arg(command)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn treasury_data(fill: u8) -> Vec<u8> {
+    let mut data = Treasury::DISCRIMINATOR.to_vec();
+    data.extend([fill; 64]);
+    data
+}
+
+fn registry_data() -> Vec<u8> {
+    let mut data = PoolRegistry::DISCRIMINATOR.to_vec();
+    data.extend([0u8; 32]);
+    data
+}
+
+#[test]
+fn exported_state_verifies_clean() {
+    let dir = scratch("clean");
+    let (accounts, archive) = (dir.join("accounts.json"), dir.join("archive.json"));
+    let treasury = Pubkey::new_unique();
+    let registry = Pubkey::new_unique();
+    write_accounts(
+        &accounts,
+        250_000_000,
+        &[
+            account(&treasury, &usdcball::ID, 5_000_000, &treasury_data(7)),
+            account(&registry, &usdcball::ID, 2_000_000, &registry_data()),
+        ],
+    );
+
+    let exported = run(&[&accounts, &archive], "export");
+    assert!(exported.status.success());
+    assert!(stdout(&exported).contains("archived 2 accounts"));
+    assert!(stdout(&exported).contains("Treasury"));
+    assert!(stdout(&exported).contains("PoolRegistry"));
+
+    let written: Value = serde_json::from_str(&fs::read_to_string(&archive).unwrap()).unwrap();
+    assert_eq!(written["version"], 1);
+    assert_eq!(written["slot"], 250_000_000u64);
+
+    let verified = run(&[&archive, &accounts], "verify");
+    assert_eq!(verified.status.code(), Some(0));
+    assert!(stdout(&verified).contains("OK: cluster matches the archive"));
+}
+
+#[test]
+fn drift_from_the_archive_is_reported() {
+    let dir = scratch("drift");
+    let (accounts, archive) = (dir.join("accounts.json"), dir.join("archive.json"));
+    let (changed, drained, closed, opened) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    write_accounts(
+        &accounts,
+        1,
+        &[
+            account(&changed, &usdcball::ID, 5_000_000, &treasury_data(1)),
+            account(&drained, &usdcball::ID, 2_000_000, &registry_data()),
+            account(&closed, &usdcball::ID, 2_000_000, &registry_data()),
+        ],
+    );
+    assert!(run(&[&accounts, &archive], "export").status.success());
+
+    write_accounts(
+        &accounts,
+        2,
+        &[
+            account(&changed, &usdcball::ID, 5_000_000, &treasury_data(2)),
+            account(&drained, &usdcball::ID, 1_000_000, &registry_data()),
+            account(&opened, &usdcball::ID, 2_000_000, &registry_data()),
+        ],
+    );
+    let verified = run(&[&archive, &accounts], "verify");
+
+    assert_eq!(verified.status.code(), Some(1));
+    let report = stdout(&verified);
+    assert!(report.contains("DIVERGENCE (4)"));
+    assert!(report.contains(&format!("Treasury {changed}: data differs")));
+    assert!(report.contains(&format!(
+        "PoolRegistry {drained}: lamports archived 2000000, live 1000000"
+    )));
+    assert!(report.contains(&format!("PoolRegistry {closed}: missing on cluster")));
+    assert!(report.contains(&format!("PoolRegistry {opened}: not in archive")));
+}
+
+#[test]
+fn restore_writes_loadable_account_files() {
+    let dir = scratch("restore");
+    let (accounts, archive, out) = (
+        dir.join("accounts.json"),
+        dir.join("archive.json"),
+        dir.join("accounts"),
+    );
+    let treasury = Pubkey::new_unique();
+    let data = treasury_data(9);
+    write_accounts(
+        &accounts,
+        1,
+        &[account(&treasury, &usdcball::ID, 5_000_000, &data)],
+    );
+    assert!(run(&[&accounts, &archive], "export").status.success());
+
+    assert!(run(&[&archive, &out], "restore").status.success());
+
+    let file = out.join(format!("{treasury}.json"));
+    let restored: Value = serde_json::from_str(&fs::read_to_string(file).unwrap()).unwrap();
+    assert_eq!(restored["pubkey"], treasury.to_string());
+    assert_eq!(restored["account"]["lamports"], 5_000_000);
+    assert_eq!(restored["account"]["owner"], usdcball::ID.to_string());
+    assert_eq!(restored["account"]["space"], data.len());
+    assert_eq!(restored["account"]["data"][0], STANDARD.encode(&data));
+}
+
+#[test]
+fn accounts_outside_the_program_are_not_exported() {
+    let dir = scratch("foreign");
+    let (accounts, archive) = (dir.join("accounts.json"), dir.join("archive.json"));
+    write_accounts(
+        &accounts,
+        1,
+        &[account(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            1_000_000,
+            &treasury_data(0),
+        )],
+    );
+
+    let exported = run(&[&accounts, &archive], "export");
+
+    assert_eq!(exported.status.code(), Some(2));
+    assert!(!archive.exists());
+}
+
+#[test]
+fn tampered_archive_is_refused() {
+    let dir = scratch("tampered");
+    let (accounts, archive, out) = (
+        dir.join("accounts.json"),
+        dir.join("archive.json"),
+        dir.join("accounts"),
+    );
+    write_accounts(
+        &accounts,
+        1,
+        &[account(
+            &Pubkey::new_unique(),
+            &usdcball::ID,
+            5_000_000,
+            &treasury_data(3),
+        )],
+    );
+    assert!(run(&[&accounts, &archive], "export").status.success());
+
+    let mut written: Value = serde_json::from_str(&fs::read_to_string(&archive).unwrap()).unwrap();
+    written["accounts"][0]["data"] = json!(STANDARD.encode(treasury_data(4)));
+    fs::write(&archive, written.to_string()).unwrap();
+
+    for (command, target) in [("verify", &accounts), ("restore", &out)] {
+        let output = run(&[&archive, target], command);
+        assert_eq!(output.status.code(), Some(2));
+        assert!(
+            String::from_utf8_lossy(&output.stderr).contains("does not match its recorded hash")
+        );
+    }
+    assert!(!out.exists());
+}
@@ -0,0 +1,100 @@
+//! Structured log lines for the USDCBALL off-chain tools.
+//!
+//! With `USDCBALL_LOG_FORMAT=json` every event is written to stderr as one JSON
+//! object carrying the tool name, an operation ID and any slot or signature the
+//! event concerns, so a run can be correlated with keeper operations in log
+//! aggregation. The operation ID is taken from `USDCBALL_OPERATION_ID` (set it to
+//! the keeper's `operationId` when running a tool as part of an incident) or
+//! generated per run. Without the variable the tools keep their plain output and
+//! events are dropped.
+
+use serde_json::{json, Map, Value};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{env, process};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    fn as_str(self) -> &'static str {
+        match self {
+            Level::Info => "info",
+            Level::Warn => "warn",
+            Level::Error => "error",
+        }
+    }
+}
+
+pub struct Logger {
+    tool: &'static str,
+    operation_id: String,
+    json: bool,
+}
+
+impl Logger {
+    pub fn from_env(tool: &'static str) -> Self {
+        let operation_id = env::var("USDCBALL_OPERATION_ID")
+            .unwrap_or_else(|_| format!("{tool}-{}-{}", unix_millis(), process::id()));
+        Self::new(
+            tool,
+            operation_id,
+            env::var("USDCBALL_LOG_FORMAT").as_deref() == Ok("json"),
+        )
+    }
+
+    pub fn new(tool: &'static str, operation_id: String, json: bool) -> Self {
+        Self {
+            tool,
+            operation_id,
+            json,
+        }
+    }
+
+    pub fn operation_id(&self) -> &str {
+        &self.operation_id
+    }
+
+    /// The JSON object logged for an event. `fields` (an object) is merged in,
+    /// e.g. `json!({ "slot": 123, "signature": "..." })`.
+    pub fn record(&self, level: Level, message: &str, fields: Value) -> Value {
+        let mut record = Map::new();
+        record.insert("timestamp_ms".into(), json!(unix_millis()));
+        record.insert("level".into(), json!(level.as_str()));
+        record.insert("service".into(), json!(self.tool));
+        record.insert("operationId".into(), json!(self.operation_id));
+        record.insert("message".into(), json!(message));
+        if let Value::Object(fields) = fields {
+            record.extend(fields);
+        }
+        Value::Object(record)
+    }
+
+    pub fn event(&self, level: Level, message: &str, fields: Value) {
+        if self.json {
+            eprintln!("{}", self.record(level, message, fields));
+        }
+    }
+
+    pub fn info(&self, message: &str, fields: Value) {
+        self.event(Level::Info, message, fields);
+    }
+
+    pub fn warn(&self, message: &str, fields: Value) {
+        self.event(Level::Warn, message, fields);
+    }
+
+    pub fn error(&self, message: &str, fields: Value) {
+        self.event(Level::Error, message, fields);
+    }
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
@@ -0,0 +1,23 @@
+//! Log records use the same keys as the keeper's JSON lines, so the three
+//! sources can be joined on `operationId` in log aggregation.
+
+use serde_json::json;
+use usdcball_log::{Level, Logger};
+
+#[test]
+fn records_carry_the_operation_and_event_fields() {
+    let logger = Logger::new("usdcball-replay", "cycle-1234".into(), true);
+    let record = logger.record(
+        Level::Warn,
+        "divergence",
+        json!({ "slot": 250_000_000u64, "field": "total_buybacks_usdc" }),
+    );
+
+    assert_eq!(record["level"], "warn");
+    assert_eq!(record["service"], "usdcball-replay");
+    assert_eq!(record["operationId"], "cycle-1234");
+    assert_eq!(record["message"], "divergence");
+    assert_eq!(record["slot"], 250_000_000u64);
+    assert_eq!(record["field"], "total_buybacks_usdc");
+    assert!(record["timestamp_ms"].as_u64().is_some());
+}
@@ -0,0 +1,385 @@
+//! Deterministic state replay for the USDCBALL treasury.
+//!
+//! Reads the program's event stream, re-derives the Treasury counters from it and
+//! compares them against the live account, flagging any divergence.
+//!
+//! Usage:
+//!
+//! ```text
+//! usdcball-replay <logs-file> <treasury-account.json>
+//! ```
+//!
+//! `logs-file` contains program log lines in slot order (e.g. concatenated
+//! `meta.logMessages` from `getTransaction`, or the output of `solana logs`).
+//! Only `Program data:` lines are decoded; everything else is ignored.
+//! `treasury-account.json` is the output of `solana account <treasury> --output json`.
+//! Fees from `record_fee` reach the treasury totals on `sync_fee_counter`, so sync
+//! the fee counter before taking the snapshot.
+//!
+//! With `USDCBALL_LOG_FORMAT=json`, a JSON record per step goes to stderr, tagged
+//! with `USDCBALL_OPERATION_ID` when set (see `usdcball-log`).
+
+use anchor_lang::{AccountDeserialize, AnchorDeserialize, Discriminator};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::json;
+use std::{env, fs, process};
+use usdcball::{
+    BuybackExecuted, CircuitBreakerTripped, ClmmLiquidityAdded, EmergencyPaused,
+    ExternalBurnRegistered, FeeForwarded, FeeRecorded, LbpCreated, LbpSettled, LiquidityAdded,
+    MeteoraLiquidityAdded, OperationKind, OperationOpened, OperationRefunded, OperationTypePaused,
+    OperationTypeResumed, OperationsResumed, TokensBurned, Treasury, UsdcConverted,
+    WhirlpoolLiquidityChanged, YieldHarvested, PAUSE_ALL,
+};
+use usdcball_log::Logger;
+
+const EVENT_PREFIX: &str = "Program data: ";
+
+/// Treasury counters as implied by the event stream.
+#[derive(Default, Debug)]
+struct Expected {
+    total_sol_collected: u64,
+    total_sol_collected_usd: u64,
+    total_usdc_converted: u64,
+    total_buybacks_usdc: u64,
+    total_liquidity_usdc: u64,
+    total_tokens_burned: u64,
+    last_operation_timestamp: i64,
+    last_buyback_timestamp: i64,
+    last_liquidity_timestamp: i64,
+    pause_flags: u8,
+}
+
+impl Expected {
+    fn total_for(&mut self, kind: OperationKind) -> &mut u64 {
+        match kind {
+            OperationKind::Buyback => &mut self.total_buybacks_usdc,
+            OperationKind::Liquidity => &mut self.total_liquidity_usdc,
+        }
+    }
+
+    fn record_operation(&mut self, kind: OperationKind, timestamp: i64) {
+        self.last_operation_timestamp = self.last_operation_timestamp.max(timestamp);
+        let last = match kind {
+            OperationKind::Buyback => &mut self.last_buyback_timestamp,
+            OperationKind::Liquidity => &mut self.last_liquidity_timestamp,
+        };
+        *last = (*last).max(timestamp);
+    }
+}
+
+#[derive(Default)]
+struct Replay {
+    expected: Expected,
+    divergences: Vec<String>,
+    decoded: usize,
+    skipped: usize,
+}
+
+impl Replay {
+    fn apply_line(&mut self, line: &str) {
+        let Some(idx) = line.find(EVENT_PREFIX) else {
+            return;
+        };
+        match STANDARD.decode(line[idx + EVENT_PREFIX.len()..].trim()) {
+            Ok(data) => self.apply_event(&data),
+            Err(_) => self.skipped += 1,
+        }
+    }
+
+    fn apply_event(&mut self, data: &[u8]) {
+        if data.len() < 8 {
+            self.skipped += 1;
+            return;
+        }
+        let (disc, body) = data.split_at(8);
+        let exp = &mut self.expected;
+
+        if disc == &FeeRecorded::DISCRIMINATOR[..] {
+            let Some(e) = decode::<FeeRecorded>(body) else {
+                return self.skipped += 1;
+            };
+            exp.total_sol_collected = exp.total_sol_collected.saturating_add(e.amount);
+            exp.total_sol_collected_usd = exp.total_sol_collected_usd.saturating_add(e.usd_value);
+            if e.total_collected != exp.total_sol_collected {
+                self.divergences.push(format!(
+                    "FeeRecorded at {}: event total {} != replayed total {}",
+                    e.timestamp, e.total_collected, exp.total_sol_collected
+                ));
+                exp.total_sol_collected = e.total_collected;
+            }
+        } else if disc == &FeeForwarded::DISCRIMINATOR[..] {
+            let Some(e) = decode::<FeeForwarded>(body) else {
+                return self.skipped += 1;
+            };
+            // Forwarded lamports are credited like a recorded fee, USDC as converted
+            exp.total_sol_collected = exp.total_sol_collected.saturating_add(e.lamports);
+            exp.total_sol_collected_usd = exp.total_sol_collected_usd.saturating_add(e.usd_value);
+            exp.total_usdc_converted = exp.total_usdc_converted.saturating_add(e.usdc_amount);
+        } else if disc == &UsdcConverted::DISCRIMINATOR[..] {
+            let Some(e) = decode::<UsdcConverted>(body) else {
+                return self.skipped += 1;
+            };
+            exp.total_usdc_converted = exp.total_usdc_converted.saturating_add(e.amount);
+            if e.total_converted != exp.total_usdc_converted {
+                self.divergences.push(format!(
+                    "UsdcConverted at {}: event total {} != replayed total {}",
+                    e.timestamp, e.total_converted, exp.total_usdc_converted
+                ));
+                exp.total_usdc_converted = e.total_converted;
+            }
+        } else if disc == &YieldHarvested::DISCRIMINATOR[..] {
+            let Some(e) = decode::<YieldHarvested>(body) else {
+                return self.skipped += 1;
+            };
+            // Harvested interest grows the budgets like converted fees
+            exp.total_usdc_converted = exp.total_usdc_converted.saturating_add(e.usdc_amount);
+            if e.total_converted != exp.total_usdc_converted {
+                self.divergences.push(format!(
+                    "YieldHarvested at {}: event total {} != replayed total {}",
+                    e.timestamp, e.total_converted, exp.total_usdc_converted
+                ));
+                exp.total_usdc_converted = e.total_converted;
+            }
+        } else if disc == &BuybackExecuted::DISCRIMINATOR[..] {
+            let Some(e) = decode::<BuybackExecuted>(body) else {
+                return self.skipped += 1;
+            };
+            exp.total_buybacks_usdc = exp.total_buybacks_usdc.saturating_add(e.usdc_amount);
+            exp.record_operation(OperationKind::Buyback, e.timestamp);
+        } else if disc == &LiquidityAdded::DISCRIMINATOR[..] {
+            let Some(e) = decode::<LiquidityAdded>(body) else {
+                return self.skipped += 1;
+            };
+            exp.total_liquidity_usdc = exp.total_liquidity_usdc.saturating_add(e.usdc_amount);
+            exp.record_operation(OperationKind::Liquidity, e.timestamp);
+        } else if disc == &ClmmLiquidityAdded::DISCRIMINATOR[..] {
+            let Some(e) = decode::<ClmmLiquidityAdded>(body) else {
+                return self.skipped += 1;
+            };
+            exp.total_liquidity_usdc = exp.total_liquidity_usdc.saturating_add(e.usdc_amount);
+            exp.record_operation(OperationKind::Liquidity, e.timestamp);
+        } else if disc == &WhirlpoolLiquidityChanged::DISCRIMINATOR[..] {
+            let Some(e) = decode::<WhirlpoolLiquidityChanged>(body) else {
+                return self.skipped += 1;
+            };
+            // Removing liquidity returns it to the vault without crediting the total back
+            if e.increased {
+                exp.total_liquidity_usdc = exp.total_liquidity_usdc.saturating_add(e.usdc_amount);
+                exp.record_operation(OperationKind::Liquidity, e.timestamp);
+            }
+        } else if disc == &MeteoraLiquidityAdded::DISCRIMINATOR[..] {
+            let Some(e) = decode::<MeteoraLiquidityAdded>(body) else {
+                return self.skipped += 1;
+            };
+            exp.total_liquidity_usdc = exp.total_liquidity_usdc.saturating_add(e.usdc_amount);
+            exp.record_operation(OperationKind::Liquidity, e.timestamp);
+        } else if disc == &LbpCreated::DISCRIMINATOR[..] {
+            let Some(e) = decode::<LbpCreated>(body) else {
+                return self.skipped += 1;
+            };
+            exp.total_liquidity_usdc = exp.total_liquidity_usdc.saturating_add(e.usdc_amount);
+            exp.record_operation(OperationKind::Liquidity, e.timestamp);
+        } else if disc == &LbpSettled::DISCRIMINATOR[..] {
+            let Some(e) = decode::<LbpSettled>(body) else {
+                return self.skipped += 1;
+            };
+            // The seed returns to the liquidity allocation, proceeds count as converted
+            exp.total_liquidity_usdc = exp.total_liquidity_usdc.saturating_sub(e.usdc_seeded);
+            exp.total_usdc_converted = exp.total_usdc_converted.saturating_add(e.usdc_raised);
+        } else if disc == &OperationOpened::DISCRIMINATOR[..] {
+            let Some(e) = decode::<OperationOpened>(body) else {
+                return self.skipped += 1;
+            };
+            *exp.total_for(e.kind) = exp.total_for(e.kind).saturating_add(e.usdc_amount);
+            exp.record_operation(e.kind, e.timestamp);
+        } else if disc == &OperationRefunded::DISCRIMINATOR[..] {
+            let Some(e) = decode::<OperationRefunded>(body) else {
+                return self.skipped += 1;
+            };
+            *exp.total_for(e.kind) = exp.total_for(e.kind).saturating_sub(e.usdc_amount);
+        } else if disc == &TokensBurned::DISCRIMINATOR[..] {
+            let Some(e) = decode::<TokensBurned>(body) else {
+                return self.skipped += 1;
+            };
+            exp.total_tokens_burned = exp.total_tokens_burned.saturating_add(e.amount);
+            if e.total_burned != exp.total_tokens_burned {
+                self.divergences.push(format!(
+                    "TokensBurned at {}: event total {} != replayed total {}",
+                    e.timestamp, e.total_burned, exp.total_tokens_burned
+                ));
+                exp.total_tokens_burned = e.total_burned;
+            }
+        } else if disc == &ExternalBurnRegistered::DISCRIMINATOR[..] {
+            let Some(e) = decode::<ExternalBurnRegistered>(body) else {
+                return self.skipped += 1;
+            };
+            exp.total_tokens_burned = exp.total_tokens_burned.saturating_add(e.amount);
+            if e.total_burned != exp.total_tokens_burned {
+                self.divergences.push(format!(
+                    "ExternalBurnRegistered at {}: event total {} != replayed total {}",
+                    e.timestamp, e.total_burned, exp.total_tokens_burned
+                ));
+                exp.total_tokens_burned = e.total_burned;
+            }
+        } else if disc == &EmergencyPaused::DISCRIMINATOR[..]
+            || disc == &CircuitBreakerTripped::DISCRIMINATOR[..]
+        {
+            exp.pause_flags = PAUSE_ALL;
+        } else if disc == &OperationsResumed::DISCRIMINATOR[..] {
+            exp.pause_flags = 0;
+        } else if disc == &OperationTypePaused::DISCRIMINATOR[..] {
+            let Some(e) = decode::<OperationTypePaused>(body) else {
+                return self.skipped += 1;
+            };
+            exp.pause_flags = e.pause_flags;
+        } else if disc == &OperationTypeResumed::DISCRIMINATOR[..] {
+            let Some(e) = decode::<OperationTypeResumed>(body) else {
+                return self.skipped += 1;
+            };
+            exp.pause_flags = e.pause_flags;
+        } else {
+            // Events that don't affect the replayed counters.
+            self.skipped += 1;
+            return;
+        }
+        self.decoded += 1;
+    }
+
+    fn compare(&mut self, live: &Treasury) {
+        let exp = &self.expected;
+        let checks: [(&str, i128, i128); 10] = [
+            (
+                "total_sol_collected",
+                exp.total_sol_collected.into(),
+                live.total_sol_collected.into(),
+            ),
+            (
+                "total_sol_collected_usd",
+                exp.total_sol_collected_usd.into(),
+                live.total_sol_collected_usd.into(),
+            ),
+            (
+                "total_usdc_converted",
+                exp.total_usdc_converted.into(),
+                live.total_usdc_converted.into(),
+            ),
+            (
+                "total_buybacks_usdc",
+                exp.total_buybacks_usdc.into(),
+                live.total_buybacks_usdc.into(),
+            ),
+            (
+                "total_liquidity_usdc",
+                exp.total_liquidity_usdc.into(),
+                live.total_liquidity_usdc.into(),
+            ),
+            (
+                "total_tokens_burned",
+                exp.total_tokens_burned.into(),
+                live.total_tokens_burned.into(),
+            ),
+            (
+                "last_operation_timestamp",
+                exp.last_operation_timestamp.into(),
+                live.last_operation_timestamp.into(),
+            ),
+            (
+                "last_buyback_timestamp",
+                exp.last_buyback_timestamp.into(),
+                live.last_buyback_timestamp.into(),
+            ),
+            (
+                "last_liquidity_timestamp",
+                exp.last_liquidity_timestamp.into(),
+                live.last_liquidity_timestamp.into(),
+            ),
+            (
+                "pause_flags",
+                exp.pause_flags.into(),
+                live.pause_flags.into(),
+            ),
+        ];
+
+        for (field, expected, actual) in checks {
+            println!("{field:<28} replayed={expected:<20} live={actual}");
+            if expected != actual {
+                self.divergences.push(format!(
+                    "{field}: replayed {expected}, live account has {actual}"
+                ));
+            }
+        }
+    }
+}
+
+fn decode<T: AnchorDeserialize>(mut body: &[u8]) -> Option<T> {
+    T::deserialize(&mut body).ok()
+}
+
+fn load_treasury(path: &str) -> Result<Treasury, String> {
+    let raw = fs::read_to_string(path).map_err(|e| format!("reading {path}: {e}"))?;
+    let json: serde_json::Value =
+        serde_json::from_str(&raw).map_err(|e| format!("parsing {path}: {e}"))?;
+    let encoded = json["account"]["data"][0]
+        .as_str()
+        .ok_or_else(|| format!("{path}: missing account.data[0]"))?;
+    let data = STANDARD
+        .decode(encoded)
+        .map_err(|e| format!("{path}: invalid base64 account data: {e}"))?;
+    Treasury::try_deserialize(&mut data.as_slice())
+        .map_err(|e| format!("{path}: not a Treasury account: {e}"))
+}
+
+fn run(log: &Logger, logs_path: &str, account_path: &str) -> Result<bool, String> {
+    let logs = fs::read_to_string(logs_path).map_err(|e| format!("reading {logs_path}: {e}"))?;
+    let live = load_treasury(account_path)?;
+
+    let mut replay = Replay::default();
+    for line in logs.lines() {
+        replay.apply_line(line);
+    }
+    println!(
+        "replayed {} events ({} skipped)",
+        replay.decoded, replay.skipped
+    );
+    replay.compare(&live);
+    log.info(
+        "replay finished",
+        json!({
+            "decoded": replay.decoded,
+            "skipped": replay.skipped,
+            "divergences": replay.divergences.len(),
+        }),
+    );
+    for d in &replay.divergences {
+        log.warn("divergence", json!({ "divergence": d }));
+    }
+
+    if replay.divergences.is_empty() {
+        println!("OK: live treasury matches the event stream");
+        return Ok(true);
+    }
+    println!("DIVERGENCE ({}):", replay.divergences.len());
+    for d in &replay.divergences {
+        println!("  - {d}");
+    }
+    Ok(false)
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 3 {
+        eprintln!("usage: {} <logs-file> <treasury-account.json>", args[0]);
+        process::exit(2);
+    }
+
+    let log = Logger::from_env("usdcball-replay");
+    match run(&log, &args[1], &args[2]) {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(e) => {
+            log.error("replay failed", json!({ "error": e }));
+            eprintln!("error: {e}");
+            process::exit(2);
+        }
+    }
+}